@@ -0,0 +1,243 @@
+//! A single type over all the Cluster API kinds, for streams mixing kinds such as
+//! cluster templates or `clusterctl move` dumps.
+
+use std::borrow::Cow;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::api::{DynamicObject, TypeMeta};
+use kube::core::dynamic::ParseDynamicObjectError;
+use kube::Resource;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::api::capi_clusterresourceset::ClusterResourceSet;
+use crate::api::capi_clusterresourcesetbinding::ClusterResourceSetBinding;
+use crate::api::capi_extensionconfig::ExtensionConfig;
+use crate::api::capi_ipaddress::IPAddress;
+use crate::api::capi_ipaddressclaim::IPAddressClaim;
+use crate::api::capi_machine::Machine;
+use crate::api::capi_machinedeployment::MachineDeployment;
+use crate::api::capi_machinehealthcheck::MachineHealthCheck;
+use crate::api::capi_machinepool::MachinePool;
+use crate::api::capi_machineset::MachineSet;
+
+macro_rules! any_capi_object {
+    ($($variant:ident),* $(,)?) => {
+        /// Any Cluster API object, dispatched on `apiVersion` and `kind`.
+        ///
+        /// Objects of other kinds, or of Cluster API kinds in an apiVersion this crate
+        /// does not model, are kept as [`AnyCapiObject::Unknown`]. The types are generated
+        /// from the storage versions of the CRDs, so objects in the older versions a
+        /// release still serves (e.g. `ipam.cluster.x-k8s.io/v1alpha1`) or in the versions
+        /// of a newer release (e.g. `cluster.x-k8s.io/v1beta2`) are unknown too, rather
+        /// than being read into a type whose schema may differ.
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum AnyCapiObject {
+            $($variant(Box<$variant>),)*
+            Unknown(Box<DynamicObject>),
+        }
+
+        impl AnyCapiObject {
+            /// Converts a dynamic object into the typed variant matching its apiVersion and kind.
+            pub fn from_dynamic(object: DynamicObject) -> Result<Self, ParseDynamicObjectError> {
+                let types = object.types.clone().unwrap_or_default();
+                $(
+                    if is_kind::<$variant>(&types.api_version, &types.kind) {
+                        return object.try_parse().map(|o| Self::$variant(Box::new(o)));
+                    }
+                )*
+                Ok(Self::Unknown(Box::new(object)))
+            }
+
+            fn from_yaml_value(
+                types: &TypeMeta,
+                value: serde_yaml::Value,
+            ) -> Result<Self, serde_yaml::Error> {
+                $(
+                    if is_kind::<$variant>(&types.api_version, &types.kind) {
+                        return serde_yaml::from_value(value).map(|o| Self::$variant(Box::new(o)));
+                    }
+                )*
+                serde_yaml::from_value(value).map(|o| Self::Unknown(Box::new(o)))
+            }
+
+            /// The metadata of the object.
+            pub fn meta(&self) -> &ObjectMeta {
+                match self {
+                    $(Self::$variant(o) => o.meta(),)*
+                    Self::Unknown(o) => &o.metadata,
+                }
+            }
+
+            /// The apiVersion of the object, empty if an unknown object has none.
+            pub fn api_version(&self) -> Cow<'_, str> {
+                match self {
+                    $(Self::$variant(_) => $variant::api_version(&()),)*
+                    Self::Unknown(o) => o.types.as_ref().map_or("", |t| &t.api_version).into(),
+                }
+            }
+
+            /// The kind of the object, empty if an unknown object has none.
+            pub fn kind(&self) -> Cow<'_, str> {
+                match self {
+                    $(Self::$variant(_) => $variant::kind(&()),)*
+                    Self::Unknown(o) => o.types.as_ref().map_or("", |t| &t.kind).into(),
+                }
+            }
+        }
+
+        impl Serialize for AnyCapiObject {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $(Self::$variant(o) => o.serialize(serializer),)*
+                    Self::Unknown(o) => o.serialize(serializer),
+                }
+            }
+        }
+
+        $(
+            impl From<$variant> for AnyCapiObject {
+                fn from(object: $variant) -> Self {
+                    Self::$variant(Box::new(object))
+                }
+            }
+        )*
+    };
+}
+
+any_capi_object!(
+    Cluster,
+    ClusterClass,
+    ClusterResourceSet,
+    ClusterResourceSetBinding,
+    ExtensionConfig,
+    IPAddress,
+    IPAddressClaim,
+    Machine,
+    MachineDeployment,
+    MachineHealthCheck,
+    MachinePool,
+    MachineSet,
+);
+
+fn is_kind<K: Resource<DynamicType = ()>>(api_version: &str, kind: &str) -> bool {
+    api_version == K::api_version(&()) && kind == K::kind(&())
+}
+
+impl<'de> Deserialize<'de> for AnyCapiObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let object = DynamicObject::deserialize(deserializer)?;
+        if object.types.is_none() {
+            return Err(D::Error::missing_field("apiVersion"));
+        }
+        Self::from_dynamic(object).map_err(D::Error::custom)
+    }
+}
+
+impl From<DynamicObject> for AnyCapiObject {
+    fn from(object: DynamicObject) -> Self {
+        Self::Unknown(Box::new(object))
+    }
+}
+
+/// Error loading a multi-document YAML stream.
+#[derive(Debug, thiserror::Error)]
+#[error("document {document}{}: {source}", .line.map(|line| format!(" at line {line}")).unwrap_or_default())]
+pub struct LoadError {
+    /// Index of the failing document among the non-empty documents of the stream, starting at 0.
+    pub document: usize,
+    /// Line of the error in the stream, starting at 1, if the parser reports a location.
+    /// Errors in the fields of a well-formed document have none.
+    pub line: Option<usize>,
+    /// Column of the error, starting at 1, if the parser reports a location.
+    pub column: Option<usize>,
+    #[source]
+    pub source: serde_yaml::Error,
+}
+
+/// Parses a multi-document YAML stream into objects, skipping empty documents.
+///
+/// Stops at the first document that fails to parse, reporting its position in the stream.
+pub fn from_yaml_stream(stream: &str) -> Result<Vec<AnyCapiObject>, LoadError> {
+    let mut objects = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(stream) {
+        let index = objects.len();
+        let error = |source: serde_yaml::Error| LoadError {
+            document: index,
+            line: source.location().map(|l| l.line()),
+            column: source.location().map(|l| l.column()),
+            source,
+        };
+        let value = serde_yaml::Value::deserialize(document).map_err(error)?;
+        let Some(types) =
+            serde_yaml::from_value::<Option<TypeMeta>>(value.clone()).map_err(error)?
+        else {
+            continue;
+        };
+        let object = AnyCapiObject::from_yaml_value(&types, value).map_err(error)?;
+        objects.push(object);
+    }
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_documents_of_a_stream() {
+        let stream = r#"%YAML 1.2
+---
+apiVersion: cluster.x-k8s.io/v1beta1
+kind: Cluster
+metadata:
+  name: c
+  annotations:
+    script: |
+      ---
+      ...
+      not a marker
+spec: {}
+--- # comment
+...
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: cm
+---
+apiVersion: ipam.cluster.x-k8s.io/v1alpha1
+kind: IPAddress
+metadata:
+  name: ip
+"#;
+        let objects = from_yaml_stream(stream).unwrap();
+        assert_eq!(objects.len(), 3);
+        let AnyCapiObject::Cluster(cluster) = &objects[0] else {
+            panic!("expected a Cluster, got {:?}", objects[0]);
+        };
+        assert_eq!(
+            cluster.metadata.annotations.as_ref().unwrap()["script"],
+            "---\n...\nnot a marker\n"
+        );
+        assert!(
+            matches!(&objects[1], AnyCapiObject::Unknown(o) if o.metadata.name.as_deref() == Some("cm"))
+        );
+        assert!(matches!(&objects[2], AnyCapiObject::Unknown(_)));
+        assert_eq!(objects[2].api_version(), "ipam.cluster.x-k8s.io/v1alpha1");
+    }
+
+    #[test]
+    fn reports_the_failing_document() {
+        let stream = "apiVersion: v1\nkind: ConfigMap\n---\nkind: [\n";
+        let err = from_yaml_stream(stream).unwrap_err();
+        assert_eq!((err.document, err.line), (1, Some(5)));
+
+        let stream = "apiVersion: v1\nkind: ConfigMap\n---\napiVersion: cluster.x-k8s.io/v1beta1\nkind: Cluster\nspec: 3\n";
+        let err = from_yaml_stream(stream).unwrap_err();
+        assert_eq!((err.document, err.line), (1, None));
+        assert!(err.to_string().starts_with("document 1: "), "{err}");
+    }
+}
@@ -4,8 +4,11 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
+    pub use crate::api::duration::GoDuration;
     pub use k8s_openapi::api::core::v1::ObjectReference;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+    pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
     pub use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
@@ -78,6 +81,12 @@ pub struct ClusterSpec {
     /// this feature is highly experimental, and parts of it might still be not implemented.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub topology: Option<ClusterTopology>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterAvailabilityGate contains the type of a Cluster condition to be used as availability gate.
@@ -95,6 +104,12 @@ pub struct ClusterAvailabilityGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterAvailabilityGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterAvailabilityGate contains the type of a Cluster condition to be used as availability gate.
@@ -102,6 +117,9 @@ pub struct ClusterAvailabilityGates {
 pub enum ClusterAvailabilityGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// clusterNetwork represents the cluster network configuration.
@@ -128,6 +146,12 @@ pub struct ClusterClusterNetwork {
     /// services is the network ranges from which service VIPs are allocated.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub services: Option<ClusterClusterNetworkServices>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// pods is the network ranges from which Pod networks are allocated.
@@ -136,6 +160,12 @@ pub struct ClusterClusterNetworkPods {
     /// cidrBlocks is a list of CIDR blocks.
     #[serde(rename = "cidrBlocks")]
     pub cidr_blocks: Vec<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// services is the network ranges from which service VIPs are allocated.
@@ -144,6 +174,12 @@ pub struct ClusterClusterNetworkServices {
     /// cidrBlocks is a list of CIDR blocks.
     #[serde(rename = "cidrBlocks")]
     pub cidr_blocks: Vec<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// controlPlaneEndpoint represents the endpoint used to communicate with the control plane.
@@ -153,6 +189,12 @@ pub struct ClusterControlPlaneEndpoint {
     pub host: String,
     /// port is the port on which the API server is serving.
     pub port: i32,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// controlPlaneRef is an optional reference to a provider-specific resource that holds
@@ -199,6 +241,12 @@ pub struct ClusterControlPlaneRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructureRef is a reference to a provider-specific resource that holds the details
@@ -245,6 +293,12 @@ pub struct ClusterInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// topology encapsulates the topology for the cluster.
@@ -282,7 +336,7 @@ pub struct ClusterTopology {
         skip_serializing_if = "Option::is_none",
         rename = "rolloutAfter"
     )]
-    pub rollout_after: Option<String>,
+    pub rollout_after: Option<Time>,
     /// variables can be used to customize the Cluster through
     /// patches. They must comply to the corresponding
     /// VariableClasses defined in the ClusterClass.
@@ -294,6 +348,12 @@ pub struct ClusterTopology {
     /// for the cluster.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workers: Option<ClusterTopologyWorkers>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// controlPlane describes the cluster control plane.
@@ -321,7 +381,7 @@ pub struct ClusterTopologyControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -330,7 +390,7 @@ pub struct ClusterTopologyControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -338,7 +398,7 @@ pub struct ClusterTopologyControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// readinessGates specifies additional conditions to include when evaluating Machine Ready condition.
     ///
     /// This field can be used e.g. to instruct the machine controller to include in the computation for Machine's ready
@@ -364,6 +424,12 @@ pub struct ClusterTopologyControlPlane {
     /// variables can be used to customize the ControlPlane through patches.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variables: Option<ClusterTopologyControlPlaneVariables>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineHealthCheck allows to enable, disable and override
@@ -407,7 +473,7 @@ pub struct ClusterTopologyControlPlaneMachineHealthCheck {
         skip_serializing_if = "Option::is_none",
         rename = "nodeStartupTimeout"
     )]
-    pub node_startup_timeout: Option<String>,
+    pub node_startup_timeout: Option<GoDuration>,
     /// remediationTemplate is a reference to a remediation template
     /// provided by an infrastructure provider.
     ///
@@ -442,6 +508,12 @@ pub struct ClusterTopologyControlPlaneMachineHealthCheck {
         rename = "unhealthyRange"
     )]
     pub unhealthy_range: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediationTemplate is a reference to a remediation template
@@ -492,6 +564,12 @@ pub struct ClusterTopologyControlPlaneMachineHealthCheckRemediationTemplate {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// UnhealthyCondition represents a Node condition type and value with a timeout
@@ -505,10 +583,16 @@ pub struct ClusterTopologyControlPlaneMachineHealthCheckUnhealthyConditions {
     /// after which the node is considered unhealthy.
     /// For example, with a value of "1h", the node must match the status
     /// for at least 1 hour before being considered unhealthy.
-    pub timeout: String,
+    pub timeout: GoDuration,
     /// type of Node condition
     #[serde(rename = "type")]
     pub r#type: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the ControlPlane and the Machines of the ControlPlane
@@ -529,6 +613,12 @@ pub struct ClusterTopologyControlPlaneMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -546,6 +636,12 @@ pub struct ClusterTopologyControlPlaneReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterTopologyControlPlaneReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -553,6 +649,9 @@ pub struct ClusterTopologyControlPlaneReadinessGates {
 pub enum ClusterTopologyControlPlaneReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// variables can be used to customize the ControlPlane through patches.
@@ -561,6 +660,12 @@ pub struct ClusterTopologyControlPlaneVariables {
     /// overrides can be used to override Cluster level variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<ClusterTopologyControlPlaneVariablesOverrides>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterVariable can be used to customize the Cluster through patches. Each ClusterVariable is associated with a
@@ -586,6 +691,12 @@ pub struct ClusterTopologyControlPlaneVariablesOverrides {
     /// i.e. it is not possible to have no type field.
     /// Ref: https://github.com/kubernetes-sigs/controller-tools/blob/d0e03a142d0ecdd5491593e941ee1d6b5d91dba6/pkg/crd/known_types.go#L106-L111
     pub value: serde_json::Value,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterVariable can be used to customize the Cluster through patches. Each ClusterVariable is associated with a
@@ -611,6 +722,12 @@ pub struct ClusterTopologyVariables {
     /// i.e. it is not possible to have no type field.
     /// Ref: https://github.com/kubernetes-sigs/controller-tools/blob/d0e03a142d0ecdd5491593e941ee1d6b5d91dba6/pkg/crd/known_types.go#L106-L111
     pub value: serde_json::Value,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// workers encapsulates the different constructs that form the worker nodes
@@ -631,6 +748,12 @@ pub struct ClusterTopologyWorkers {
         rename = "machinePools"
     )]
     pub machine_pools: Option<Vec<ClusterTopologyWorkersMachinePools>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineDeploymentTopology specifies the different parameters for a set of worker nodes in the topology.
@@ -684,7 +807,7 @@ pub struct ClusterTopologyWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -693,7 +816,7 @@ pub struct ClusterTopologyWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -701,7 +824,7 @@ pub struct ClusterTopologyWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// readinessGates specifies additional conditions to include when evaluating Machine Ready condition.
     ///
     /// This field can be used e.g. to instruct the machine controller to include in the computation for Machine's ready
@@ -729,6 +852,12 @@ pub struct ClusterTopologyWorkersMachineDeployments {
     /// variables can be used to customize the MachineDeployment through patches.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variables: Option<ClusterTopologyWorkersMachineDeploymentsVariables>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineHealthCheck allows to enable, disable and override
@@ -772,7 +901,7 @@ pub struct ClusterTopologyWorkersMachineDeploymentsMachineHealthCheck {
         skip_serializing_if = "Option::is_none",
         rename = "nodeStartupTimeout"
     )]
-    pub node_startup_timeout: Option<String>,
+    pub node_startup_timeout: Option<GoDuration>,
     /// remediationTemplate is a reference to a remediation template
     /// provided by an infrastructure provider.
     ///
@@ -807,6 +936,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsMachineHealthCheck {
         rename = "unhealthyRange"
     )]
     pub unhealthy_range: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediationTemplate is a reference to a remediation template
@@ -857,6 +992,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsMachineHealthCheckRemediation
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// UnhealthyCondition represents a Node condition type and value with a timeout
@@ -870,10 +1011,16 @@ pub struct ClusterTopologyWorkersMachineDeploymentsMachineHealthCheckUnhealthyCo
     /// after which the node is considered unhealthy.
     /// For example, with a value of "1h", the node must match the status
     /// for at least 1 hour before being considered unhealthy.
-    pub timeout: String,
+    pub timeout: GoDuration,
     /// type of Node condition
     #[serde(rename = "type")]
     pub r#type: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the MachineDeployment and the machines of the MachineDeployment.
@@ -892,6 +1039,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -909,6 +1062,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterTopologyWorkersMachineDeploymentsReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -916,6 +1075,9 @@ pub struct ClusterTopologyWorkersMachineDeploymentsReadinessGates {
 pub enum ClusterTopologyWorkersMachineDeploymentsReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -938,6 +1100,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsStrategy {
     /// The default is RollingUpdate.
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
     pub r#type: Option<ClusterTopologyWorkersMachineDeploymentsStrategyType>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediation controls the strategy of remediating unhealthy machines
@@ -964,6 +1132,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsStrategyRemediation {
         rename = "maxInFlight"
     )]
     pub max_in_flight: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -1013,6 +1187,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsStrategyRollingUpdate {
         rename = "maxUnavailable"
     )]
     pub max_unavailable: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -1022,6 +1202,9 @@ pub enum ClusterTopologyWorkersMachineDeploymentsStrategyRollingUpdateDeletePoli
     Random,
     Newest,
     Oldest,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -1030,6 +1213,9 @@ pub enum ClusterTopologyWorkersMachineDeploymentsStrategyRollingUpdateDeletePoli
 pub enum ClusterTopologyWorkersMachineDeploymentsStrategyType {
     RollingUpdate,
     OnDelete,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// variables can be used to customize the MachineDeployment through patches.
@@ -1038,6 +1224,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsVariables {
     /// overrides can be used to override Cluster level variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<ClusterTopologyWorkersMachineDeploymentsVariablesOverrides>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterVariable can be used to customize the Cluster through patches. Each ClusterVariable is associated with a
@@ -1063,6 +1255,12 @@ pub struct ClusterTopologyWorkersMachineDeploymentsVariablesOverrides {
     /// i.e. it is not possible to have no type field.
     /// Ref: https://github.com/kubernetes-sigs/controller-tools/blob/d0e03a142d0ecdd5491593e941ee1d6b5d91dba6/pkg/crd/known_types.go#L106-L111
     pub value: serde_json::Value,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachinePoolTopology specifies the different parameters for a pool of worker nodes in the topology.
@@ -1108,7 +1306,7 @@ pub struct ClusterTopologyWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -1117,7 +1315,7 @@ pub struct ClusterTopologyWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -1125,7 +1323,7 @@ pub struct ClusterTopologyWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// replicas is the number of nodes belonging to this pool.
     /// If the value is nil, the MachinePool is created without the number of Replicas (defaulting to 1)
     /// and it's assumed that an external entity (like cluster autoscaler) is responsible for the management
@@ -1135,6 +1333,12 @@ pub struct ClusterTopologyWorkersMachinePools {
     /// variables can be used to customize the MachinePool through patches.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variables: Option<ClusterTopologyWorkersMachinePoolsVariables>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the MachinePool.
@@ -1153,6 +1357,12 @@ pub struct ClusterTopologyWorkersMachinePoolsMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// variables can be used to customize the MachinePool through patches.
@@ -1161,6 +1371,12 @@ pub struct ClusterTopologyWorkersMachinePoolsVariables {
     /// overrides can be used to override Cluster level variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub overrides: Option<Vec<ClusterTopologyWorkersMachinePoolsVariablesOverrides>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterVariable can be used to customize the Cluster through patches. Each ClusterVariable is associated with a
@@ -1186,6 +1402,12 @@ pub struct ClusterTopologyWorkersMachinePoolsVariablesOverrides {
     /// i.e. it is not possible to have no type field.
     /// Ref: https://github.com/kubernetes-sigs/controller-tools/blob/d0e03a142d0ecdd5491593e941ee1d6b5d91dba6/pkg/crd/known_types.go#L106-L111
     pub value: serde_json::Value,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of Cluster.
@@ -1193,7 +1415,7 @@ pub struct ClusterTopologyWorkersMachinePoolsVariablesOverrides {
 pub struct ClusterStatus {
     /// conditions defines current service state of the cluster.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// controlPlaneReady denotes if the control plane became ready during initial provisioning
     /// to receive requests.
     /// NOTE: this field is part of the Cluster API contract and it is used to orchestrate provisioning.
@@ -1253,6 +1475,12 @@ pub struct ClusterStatus {
     /// v1beta2 groups all the fields that will be added or modified in Cluster's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<ClusterStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// failureDomains is a slice of failure domain objects synced from the infrastructure provider.
@@ -1268,6 +1496,12 @@ pub struct ClusterStatusFailureDomains {
         rename = "controlPlane"
     )]
     pub control_plane: Option<bool>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of Cluster.
@@ -1279,6 +1513,9 @@ pub enum ClusterStatusPhase {
     Deleting,
     Failed,
     Unknown,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// v1beta2 groups all the fields that will be added or modified in Cluster's status with the V1Beta2 version.
@@ -1300,6 +1537,12 @@ pub struct ClusterStatusV1beta2 {
     /// workers groups all the observations about Cluster's Workers current state.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workers: Option<ClusterStatusV1beta2Workers>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// controlPlane groups all the observations about Cluster's ControlPlane current state.
@@ -1337,6 +1580,12 @@ pub struct ClusterStatusV1beta2ControlPlane {
         rename = "upToDateReplicas"
     )]
     pub up_to_date_replicas: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// workers groups all the observations about Cluster's Workers current state.
@@ -1374,4 +1623,10 @@ pub struct ClusterStatusV1beta2Workers {
         rename = "upToDateReplicas"
     )]
     pub up_to_date_replicas: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
@@ -4,6 +4,8 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
+    pub use crate::api::duration::GoDuration;
     pub use k8s_openapi::api::core::v1::ObjectReference;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
     pub use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
@@ -74,6 +76,12 @@ pub struct ClusterClassSpec {
     /// the worker nodes of the cluster.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workers: Option<ClusterClassWorkers>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterAvailabilityGate contains the type of a Cluster condition to be used as availability gate.
@@ -91,6 +99,12 @@ pub struct ClusterClassAvailabilityGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterClassAvailabilityGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterAvailabilityGate contains the type of a Cluster condition to be used as availability gate.
@@ -98,6 +112,9 @@ pub struct ClusterClassAvailabilityGates {
 pub enum ClusterClassAvailabilityGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// controlPlane is a reference to a local struct that holds the details
@@ -149,7 +166,7 @@ pub struct ClusterClassControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -159,7 +176,7 @@ pub struct ClusterClassControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     /// NOTE: This value can be overridden while defining a Cluster.Topology.
@@ -168,7 +185,7 @@ pub struct ClusterClassControlPlane {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// readinessGates specifies additional conditions to include when evaluating Machine Ready condition.
     ///
     /// This field can be used e.g. to instruct the machine controller to include in the computation for Machine's ready
@@ -189,6 +206,12 @@ pub struct ClusterClassControlPlane {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineHealthCheck defines a MachineHealthCheck for this ControlPlaneClass.
@@ -222,7 +245,7 @@ pub struct ClusterClassControlPlaneMachineHealthCheck {
         skip_serializing_if = "Option::is_none",
         rename = "nodeStartupTimeout"
     )]
-    pub node_startup_timeout: Option<String>,
+    pub node_startup_timeout: Option<GoDuration>,
     /// remediationTemplate is a reference to a remediation template
     /// provided by an infrastructure provider.
     ///
@@ -257,6 +280,12 @@ pub struct ClusterClassControlPlaneMachineHealthCheck {
         rename = "unhealthyRange"
     )]
     pub unhealthy_range: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediationTemplate is a reference to a remediation template
@@ -307,6 +336,12 @@ pub struct ClusterClassControlPlaneMachineHealthCheckRemediationTemplate {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// UnhealthyCondition represents a Node condition type and value with a timeout
@@ -320,10 +355,16 @@ pub struct ClusterClassControlPlaneMachineHealthCheckUnhealthyConditions {
     /// after which the node is considered unhealthy.
     /// For example, with a value of "1h", the node must match the status
     /// for at least 1 hour before being considered unhealthy.
-    pub timeout: String,
+    pub timeout: GoDuration,
     /// type of Node condition
     #[serde(rename = "type")]
     pub r#type: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineInfrastructure defines the metadata and infrastructure information
@@ -337,6 +378,12 @@ pub struct ClusterClassControlPlaneMachineInfrastructure {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -383,6 +430,12 @@ pub struct ClusterClassControlPlaneMachineInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the ControlPlane and the Machines of the ControlPlane
@@ -406,6 +459,12 @@ pub struct ClusterClassControlPlaneMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// namingStrategy allows changing the naming pattern used when creating the control plane provider object.
@@ -420,6 +479,12 @@ pub struct ClusterClassControlPlaneNamingStrategy {
     /// * `.random`: A random alphanumeric string, without vowels, of length 5.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -437,6 +502,12 @@ pub struct ClusterClassControlPlaneReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterClassControlPlaneReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -444,6 +515,9 @@ pub struct ClusterClassControlPlaneReadinessGates {
 pub enum ClusterClassControlPlaneReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// ref is a required reference to a custom resource
@@ -490,6 +564,12 @@ pub struct ClusterClassControlPlaneRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructure is a reference to a provider-specific template that holds
@@ -503,6 +583,12 @@ pub struct ClusterClassInfrastructure {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -549,6 +635,12 @@ pub struct ClusterClassInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructureNamingStrategy allows changing the naming pattern used when creating the infrastructure object.
@@ -563,6 +655,12 @@ pub struct ClusterClassInfrastructureNamingStrategy {
     /// * `.random`: A random alphanumeric string, without vowels, of length 5.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterClassPatch defines a patch which is applied to customize the referenced templates.
@@ -589,6 +687,12 @@ pub struct ClusterClassPatches {
     pub external: Option<ClusterClassPatchesExternal>,
     /// name of the patch.
     pub name: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// PatchDefinition defines a patch which is applied to customize the referenced templates.
@@ -601,6 +705,12 @@ pub struct ClusterClassPatchesDefinitions {
     pub json_patches: Vec<ClusterClassPatchesDefinitionsJsonPatches>,
     /// selector defines on which templates the patch should be applied.
     pub selector: ClusterClassPatchesDefinitionsSelector,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// JSONPatch defines a JSON patch.
@@ -629,6 +739,12 @@ pub struct ClusterClassPatchesDefinitionsJsonPatches {
     /// operations. Only one of them is allowed to be set at the same time.
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "valueFrom")]
     pub value_from: Option<ClusterClassPatchesDefinitionsJsonPatchesValueFrom>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// JSONPatch defines a JSON patch.
@@ -640,6 +756,9 @@ pub enum ClusterClassPatchesDefinitionsJsonPatchesOp {
     Replace,
     #[serde(rename = "remove")]
     Remove,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// valueFrom defines the value of the patch.
@@ -656,6 +775,12 @@ pub struct ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
     /// Variable can be one of the variables defined in .spec.variables or a builtin variable.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variable: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// selector defines on which templates the patch should be applied.
@@ -669,6 +794,12 @@ pub struct ClusterClassPatchesDefinitionsSelector {
     /// matchResources selects templates based on where they are referenced.
     #[serde(rename = "matchResources")]
     pub match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// matchResources selects templates based on where they are referenced.
@@ -708,6 +839,12 @@ pub struct ClusterClassPatchesDefinitionsSelectorMatchResources {
     )]
     pub machine_pool_class:
         Option<ClusterClassPatchesDefinitionsSelectorMatchResourcesMachinePoolClass>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineDeploymentClass selects templates referenced in specific MachineDeploymentClasses in
@@ -717,6 +854,12 @@ pub struct ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeployment
     /// names selects templates by class names.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub names: Option<Vec<String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machinePoolClass selects templates referenced in specific MachinePoolClasses in
@@ -726,6 +869,12 @@ pub struct ClusterClassPatchesDefinitionsSelectorMatchResourcesMachinePoolClass
     /// names selects templates by class names.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub names: Option<Vec<String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// external defines an external patch.
@@ -758,6 +907,12 @@ pub struct ClusterClassPatchesExternal {
         rename = "validateExtension"
     )]
     pub validate_extension: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterClassVariable defines a variable which can
@@ -780,6 +935,12 @@ pub struct ClusterClassVariables {
     pub required: bool,
     /// schema defines the schema of the variable.
     pub schema: ClusterClassVariablesSchema,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata of a variable.
@@ -798,6 +959,12 @@ pub struct ClusterClassVariablesMetadata {
     /// (scope and select) variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// schema defines the schema of the variable.
@@ -808,6 +975,12 @@ pub struct ClusterClassVariablesSchema {
     /// Kubernetes CRDs.
     #[serde(rename = "openAPIV3Schema")]
     pub open_apiv3_schema: ClusterClassVariablesSchemaOpenApiv3Schema,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// openAPIV3Schema defines the schema of a variable via OpenAPI v3
@@ -1003,6 +1176,12 @@ pub struct ClusterClassVariablesSchemaOpenApiv3Schema {
         rename = "x-metadata"
     )]
     pub x_metadata: Option<ClusterClassVariablesSchemaOpenApiv3SchemaXMetadata>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// openAPIV3Schema defines the schema of a variable via OpenAPI v3
@@ -1022,6 +1201,9 @@ pub enum ClusterClassVariablesSchemaOpenApiv3SchemaType {
     Number,
     #[serde(rename = "boolean")]
     Boolean,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// ValidationRule describes a validation rule written in the CEL expression language.
@@ -1112,6 +1294,12 @@ pub struct ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidations {
     /// Transition rules by default are applied only on UPDATE requests and are
     /// skipped if an old value could not be found.
     pub rule: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ValidationRule describes a validation rule written in the CEL expression language.
@@ -1121,6 +1309,9 @@ pub enum ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidationsReason
     FieldValueForbidden,
     FieldValueRequired,
     FieldValueDuplicate,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// x-metadata is the metadata of a variable or a nested field within a variable.
@@ -1136,6 +1327,12 @@ pub struct ClusterClassVariablesSchemaOpenApiv3SchemaXMetadata {
     /// (scope and select) variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// workers describes the worker nodes for the cluster.
@@ -1159,6 +1356,12 @@ pub struct ClusterClassWorkers {
         rename = "machinePools"
     )]
     pub machine_pools: Option<Vec<ClusterClassWorkersMachinePools>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineDeploymentClass serves as a template to define a set of worker nodes of the cluster
@@ -1212,7 +1415,7 @@ pub struct ClusterClassWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -1222,7 +1425,7 @@ pub struct ClusterClassWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     /// NOTE: This value can be overridden while defining a Cluster.Topology using this MachineDeploymentClass.
@@ -1231,7 +1434,7 @@ pub struct ClusterClassWorkersMachineDeployments {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// readinessGates specifies additional conditions to include when evaluating Machine Ready condition.
     ///
     /// This field can be used e.g. to instruct the machine controller to include in the computation for Machine's ready
@@ -1254,6 +1457,12 @@ pub struct ClusterClassWorkersMachineDeployments {
     /// template is a local struct containing a collection of templates for creation of
     /// MachineDeployment objects representing a set of worker nodes.
     pub template: ClusterClassWorkersMachineDeploymentsTemplate,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineHealthCheck defines a MachineHealthCheck for this MachineDeploymentClass.
@@ -1285,7 +1494,7 @@ pub struct ClusterClassWorkersMachineDeploymentsMachineHealthCheck {
         skip_serializing_if = "Option::is_none",
         rename = "nodeStartupTimeout"
     )]
-    pub node_startup_timeout: Option<String>,
+    pub node_startup_timeout: Option<GoDuration>,
     /// remediationTemplate is a reference to a remediation template
     /// provided by an infrastructure provider.
     ///
@@ -1320,6 +1529,12 @@ pub struct ClusterClassWorkersMachineDeploymentsMachineHealthCheck {
         rename = "unhealthyRange"
     )]
     pub unhealthy_range: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediationTemplate is a reference to a remediation template
@@ -1370,6 +1585,12 @@ pub struct ClusterClassWorkersMachineDeploymentsMachineHealthCheckRemediationTem
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// UnhealthyCondition represents a Node condition type and value with a timeout
@@ -1383,10 +1604,16 @@ pub struct ClusterClassWorkersMachineDeploymentsMachineHealthCheckUnhealthyCondi
     /// after which the node is considered unhealthy.
     /// For example, with a value of "1h", the node must match the status
     /// for at least 1 hour before being considered unhealthy.
-    pub timeout: String,
+    pub timeout: GoDuration,
     /// type of Node condition
     #[serde(rename = "type")]
     pub r#type: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// namingStrategy allows changing the naming pattern used when creating the MachineDeployment.
@@ -1402,6 +1629,12 @@ pub struct ClusterClassWorkersMachineDeploymentsNamingStrategy {
     /// * `.machineDeployment.topologyName`: The name of the MachineDeployment topology (Cluster.spec.topology.workers.machineDeployments[].name).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -1419,6 +1652,12 @@ pub struct ClusterClassWorkersMachineDeploymentsReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<ClusterClassWorkersMachineDeploymentsReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -1426,6 +1665,9 @@ pub struct ClusterClassWorkersMachineDeploymentsReadinessGates {
 pub enum ClusterClassWorkersMachineDeploymentsReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -1449,6 +1691,12 @@ pub struct ClusterClassWorkersMachineDeploymentsStrategy {
     /// The default is RollingUpdate.
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
     pub r#type: Option<ClusterClassWorkersMachineDeploymentsStrategyType>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediation controls the strategy of remediating unhealthy machines
@@ -1475,6 +1723,12 @@ pub struct ClusterClassWorkersMachineDeploymentsStrategyRemediation {
         rename = "maxInFlight"
     )]
     pub max_in_flight: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -1524,6 +1778,12 @@ pub struct ClusterClassWorkersMachineDeploymentsStrategyRollingUpdate {
         rename = "maxUnavailable"
     )]
     pub max_unavailable: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -1533,6 +1793,9 @@ pub enum ClusterClassWorkersMachineDeploymentsStrategyRollingUpdateDeletePolicy
     Random,
     Newest,
     Oldest,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -1542,6 +1805,9 @@ pub enum ClusterClassWorkersMachineDeploymentsStrategyRollingUpdateDeletePolicy
 pub enum ClusterClassWorkersMachineDeploymentsStrategyType {
     RollingUpdate,
     OnDelete,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// template is a local struct containing a collection of templates for creation of
@@ -1558,6 +1824,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplate {
     /// At runtime this metadata is merged with the corresponding metadata from the topology.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<ClusterClassWorkersMachineDeploymentsTemplateMetadata>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// bootstrap contains the bootstrap template reference to be used
@@ -1568,6 +1840,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplateBootstrap {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -1614,6 +1892,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplateBootstrapRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructure contains the infrastructure template reference to be used
@@ -1624,6 +1908,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplateInfrastructure {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -1670,6 +1960,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplateInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the MachineDeployment and the machines of the MachineDeployment.
@@ -1688,6 +1984,12 @@ pub struct ClusterClassWorkersMachineDeploymentsTemplateMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachinePoolClass serves as a template to define a pool of worker nodes of the cluster
@@ -1734,7 +2036,7 @@ pub struct ClusterClassWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -1744,7 +2046,7 @@ pub struct ClusterClassWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     /// NOTE: This value can be overridden while defining a Cluster.Topology using this MachinePoolClass.
@@ -1753,10 +2055,16 @@ pub struct ClusterClassWorkersMachinePools {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// template is a local struct containing a collection of templates for creation of
     /// MachinePools objects representing a pool of worker nodes.
     pub template: ClusterClassWorkersMachinePoolsTemplate,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// namingStrategy allows changing the naming pattern used when creating the MachinePool.
@@ -1772,6 +2080,12 @@ pub struct ClusterClassWorkersMachinePoolsNamingStrategy {
     /// * `.machinePool.topologyName`: The name of the MachinePool topology (Cluster.spec.topology.workers.machinePools[].name).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// template is a local struct containing a collection of templates for creation of
@@ -1788,6 +2102,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplate {
     /// At runtime this metadata is merged with the corresponding metadata from the topology.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<ClusterClassWorkersMachinePoolsTemplateMetadata>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// bootstrap contains the bootstrap template reference to be used
@@ -1798,6 +2118,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplateBootstrap {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -1844,6 +2170,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplateBootstrapRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructure contains the infrastructure template reference to be used
@@ -1854,6 +2186,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplateInfrastructure {
     /// offered by a provider.
     #[serde(rename = "ref")]
     pub r#ref: ObjectReference,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ref is a required reference to a custom resource
@@ -1900,6 +2238,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplateInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata applied to the MachinePool.
@@ -1918,6 +2262,12 @@ pub struct ClusterClassWorkersMachinePoolsTemplateMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of ClusterClass.
@@ -1925,7 +2275,7 @@ pub struct ClusterClassWorkersMachinePoolsTemplateMetadata {
 pub struct ClusterClassStatus {
     /// conditions defines current observed state of the ClusterClass.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// observedGeneration is the latest generation observed by the controller.
     #[serde(
         default,
@@ -1939,6 +2289,12 @@ pub struct ClusterClassStatus {
     /// variables is a list of ClusterClassStatusVariable that are defined for the ClusterClass.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variables: Option<Vec<ClusterClassStatusVariables>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// v1beta2 groups all the fields that will be added or modified in ClusterClass's status with the V1Beta2 version.
@@ -1948,6 +2304,12 @@ pub struct ClusterClassStatusV1beta2 {
     /// Known condition types are VariablesReady, RefVersionsUpToDate, Paused.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<Condition>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterClassStatusVariable defines a variable which appears in the status of a ClusterClass.
@@ -1964,6 +2326,12 @@ pub struct ClusterClassStatusVariables {
     pub definitions_conflict: Option<bool>,
     /// name is the name of the variable.
     pub name: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ClusterClassStatusVariableDefinition defines a variable which appears in the status of a ClusterClass.
@@ -1987,6 +2355,12 @@ pub struct ClusterClassStatusVariablesDefinitions {
     pub required: bool,
     /// schema defines the schema of the variable.
     pub schema: ClusterClassStatusVariablesDefinitionsSchema,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the metadata of a variable.
@@ -2005,6 +2379,12 @@ pub struct ClusterClassStatusVariablesDefinitionsMetadata {
     /// (scope and select) variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// schema defines the schema of the variable.
@@ -2015,6 +2395,12 @@ pub struct ClusterClassStatusVariablesDefinitionsSchema {
     /// Kubernetes CRDs.
     #[serde(rename = "openAPIV3Schema")]
     pub open_apiv3_schema: ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3Schema,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// openAPIV3Schema defines the schema of a variable via OpenAPI v3
@@ -2211,6 +2597,12 @@ pub struct ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3Schema {
         rename = "x-metadata"
     )]
     pub x_metadata: Option<ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3SchemaXMetadata>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// openAPIV3Schema defines the schema of a variable via OpenAPI v3
@@ -2230,6 +2622,9 @@ pub enum ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3SchemaType {
     Number,
     #[serde(rename = "boolean")]
     Boolean,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// ValidationRule describes a validation rule written in the CEL expression language.
@@ -2322,6 +2717,12 @@ pub struct ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3SchemaXKubernete
     /// Transition rules by default are applied only on UPDATE requests and are
     /// skipped if an old value could not be found.
     pub rule: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ValidationRule describes a validation rule written in the CEL expression language.
@@ -2331,6 +2732,9 @@ pub enum ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3SchemaXKubernetesV
     FieldValueForbidden,
     FieldValueRequired,
     FieldValueDuplicate,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// x-metadata is the metadata of a variable or a nested field within a variable.
@@ -2346,4 +2750,10 @@ pub struct ClusterClassStatusVariablesDefinitionsSchemaOpenApiv3SchemaXMetadata
     /// (scope and select) variables.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
@@ -4,6 +4,7 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
@@ -37,6 +38,12 @@ pub struct ClusterResourceSetSpec {
     /// strategy is the strategy to be used during applying resources. Defaults to ApplyOnce. This field is immutable.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub strategy: Option<ClusterResourceSetStrategy>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// clusterSelector is the label selector for Clusters. The Clusters that are
@@ -61,6 +68,12 @@ pub struct ClusterResourceSetClusterSelector {
         rename = "matchLabels"
     )]
     pub match_labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// A label selector requirement is a selector that contains values, a key, and an operator that
@@ -78,6 +91,12 @@ pub struct ClusterResourceSetClusterSelectorMatchExpressions {
     /// merge patch.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub values: Option<Vec<String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ResourceRef specifies a resource.
@@ -87,6 +106,12 @@ pub struct ClusterResourceSetResources {
     pub kind: ClusterResourceSetResourcesKind,
     /// name of the resource that is in the same namespace with ClusterResourceSet object.
     pub name: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ResourceRef specifies a resource.
@@ -94,6 +119,9 @@ pub struct ClusterResourceSetResources {
 pub enum ClusterResourceSetResourcesKind {
     Secret,
     ConfigMap,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// spec is the desired state of ClusterResourceSet.
@@ -101,6 +129,9 @@ pub enum ClusterResourceSetResourcesKind {
 pub enum ClusterResourceSetStrategy {
     ApplyOnce,
     Reconcile,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// status is the observed state of ClusterResourceSet.
@@ -108,7 +139,7 @@ pub enum ClusterResourceSetStrategy {
 pub struct ClusterResourceSetStatus {
     /// conditions defines current state of the ClusterResourceSet.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// observedGeneration reflects the generation of the most recently observed ClusterResourceSet.
     #[serde(
         default,
@@ -119,6 +150,12 @@ pub struct ClusterResourceSetStatus {
     /// v1beta2 groups all the fields that will be added or modified in ClusterResourceSet's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<ClusterResourceSetStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// v1beta2 groups all the fields that will be added or modified in ClusterResourceSet's status with the V1Beta2 version.
@@ -128,4 +165,10 @@ pub struct ClusterResourceSetStatusV1beta2 {
     /// Known condition types are ResourceSetApplied, Deleting.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<Condition>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
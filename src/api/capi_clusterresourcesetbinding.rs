@@ -4,6 +4,7 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
     pub use serde::{Deserialize, Serialize};
@@ -33,6 +34,12 @@ pub struct ClusterResourceSetBindingSpec {
         rename = "clusterName"
     )]
     pub cluster_name: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ResourceSetBinding keeps info on all of the resources in a ClusterResourceSet.
@@ -44,6 +51,12 @@ pub struct ClusterResourceSetBindingBindings {
     /// resources is a list of resources that the ClusterResourceSet has.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub resources: Option<Vec<ClusterResourceSetBindingBindingsResources>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ResourceBinding shows the status of a resource that belongs to a ClusterResourceSet matched by the owner cluster of the ClusterResourceSetBinding object.
@@ -63,9 +76,15 @@ pub struct ClusterResourceSetBindingBindingsResources {
         skip_serializing_if = "Option::is_none",
         rename = "lastAppliedTime"
     )]
-    pub last_applied_time: Option<String>,
+    pub last_applied_time: Option<Time>,
     /// name of the resource that is in the same namespace with ClusterResourceSet object.
     pub name: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ResourceBinding shows the status of a resource that belongs to a ClusterResourceSet matched by the owner cluster of the ClusterResourceSetBinding object.
@@ -73,4 +92,7 @@ pub struct ClusterResourceSetBindingBindingsResources {
 pub enum ClusterResourceSetBindingBindingsResourcesKind {
     Secret,
     ConfigMap,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
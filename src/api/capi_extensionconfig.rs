@@ -4,6 +4,7 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
@@ -41,6 +42,12 @@ pub struct ExtensionConfigSpec {
     /// Note: Settings can be overridden on the ClusterClass.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub settings: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// clientConfig defines how to communicate with the Extension server.
@@ -72,6 +79,12 @@ pub struct ExtensionConfigClientConfig {
     /// allowed either.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// service is a reference to the Kubernetes service for the Extension server.
@@ -93,6 +106,12 @@ pub struct ExtensionConfigClientConfigService {
     /// Port should be a valid port number (1-65535, inclusive).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub port: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// namespaceSelector decides whether to call the hook for an object based
@@ -116,6 +135,12 @@ pub struct ExtensionConfigNamespaceSelector {
         rename = "matchLabels"
     )]
     pub match_labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// A label selector requirement is a selector that contains values, a key, and an operator that
@@ -133,6 +158,12 @@ pub struct ExtensionConfigNamespaceSelectorMatchExpressions {
     /// merge patch.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub values: Option<Vec<String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the current state of the ExtensionConfig
@@ -140,13 +171,19 @@ pub struct ExtensionConfigNamespaceSelectorMatchExpressions {
 pub struct ExtensionConfigStatus {
     /// conditions define the current service state of the ExtensionConfig.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// handlers defines the current ExtensionHandlers supported by an Extension.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub handlers: Option<Vec<ExtensionConfigStatusHandlers>>,
     /// v1beta2 groups all the fields that will be added or modified in ExtensionConfig's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<ExtensionConfigStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ExtensionHandler specifies the details of a handler for a particular runtime hook registered by an Extension server.
@@ -173,6 +210,12 @@ pub struct ExtensionConfigStatusHandlers {
         rename = "timeoutSeconds"
     )]
     pub timeout_seconds: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// ExtensionHandler specifies the details of a handler for a particular runtime hook registered by an Extension server.
@@ -180,6 +223,9 @@ pub struct ExtensionConfigStatusHandlers {
 pub enum ExtensionConfigStatusHandlersFailurePolicy {
     Ignore,
     Fail,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// requestHook defines the versioned runtime hook which this ExtensionHandler serves.
@@ -190,6 +236,12 @@ pub struct ExtensionConfigStatusHandlersRequestHook {
     pub api_version: String,
     /// hook is the name of the hook.
     pub hook: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// v1beta2 groups all the fields that will be added or modified in ExtensionConfig's status with the V1Beta2 version.
@@ -199,4 +251,10 @@ pub struct ExtensionConfigStatusV1beta2 {
     /// Known condition types are Discovered, Paused.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<Condition>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
@@ -4,6 +4,7 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
@@ -34,6 +35,12 @@ pub struct IPAddressClaimSpec {
     /// poolRef is a reference to the pool from which an IP address should be created.
     #[serde(rename = "poolRef")]
     pub pool_ref: IPAddressClaimPoolRef,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// poolRef is a reference to the pool from which an IP address should be created.
@@ -48,6 +55,12 @@ pub struct IPAddressClaimPoolRef {
     pub kind: String,
     /// Name is the name of resource being referenced
     pub name: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of IPAddressClaim.
@@ -62,10 +75,16 @@ pub struct IPAddressClaimStatus {
     pub address_ref: Option<IPAddressClaimStatusAddressRef>,
     /// conditions summarises the current state of the IPAddressClaim
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// v1beta2 groups all the fields that will be added or modified in IPAddressClaim's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<IPAddressClaimStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// addressRef is a reference to the address that was created for this claim.
@@ -78,6 +97,12 @@ pub struct IPAddressClaimStatusAddressRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#names
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// v1beta2 groups all the fields that will be added or modified in IPAddressClaim's status with the V1Beta2 version.
@@ -86,4 +111,10 @@ pub struct IPAddressClaimStatusV1beta2 {
     /// conditions represents the observations of a IPAddressClaim's current state.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<Condition>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
@@ -4,8 +4,11 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
+    pub use crate::api::duration::GoDuration;
     pub use k8s_openapi::api::core::v1::ObjectReference;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+    pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
     pub use serde::{Deserialize, Serialize};
@@ -51,7 +54,7 @@ pub struct MachineSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -60,7 +63,7 @@ pub struct MachineSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -68,7 +71,7 @@ pub struct MachineSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// providerID is the identification ID of the machine provided by the provider.
     /// This field must match the provider ID as seen on the node object corresponding to this machine.
     /// This field is required by higher level consumers of cluster-api. Example use case is cluster autoscaler
@@ -109,6 +112,12 @@ pub struct MachineSpec {
     /// This field is meant to be optionally used by bootstrap providers.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// bootstrap is a reference to a local struct which encapsulates
@@ -129,6 +138,12 @@ pub struct MachineBootstrap {
         rename = "dataSecretName"
     )]
     pub data_secret_name: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// configRef is a reference to a bootstrap provider-specific resource
@@ -177,6 +192,12 @@ pub struct MachineBootstrapConfigRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructureRef is a required reference to a custom resource
@@ -223,6 +244,12 @@ pub struct MachineInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -240,6 +267,12 @@ pub struct MachineReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<MachineReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -247,6 +280,9 @@ pub struct MachineReadinessGates {
 pub enum MachineReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// status is the observed state of Machine.
@@ -270,10 +306,10 @@ pub struct MachineStatus {
         skip_serializing_if = "Option::is_none",
         rename = "certificatesExpiryDate"
     )]
-    pub certificates_expiry_date: Option<String>,
+    pub certificates_expiry_date: Option<Time>,
     /// conditions defines current service state of the Machine.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// deletion contains information relating to removal of the Machine.
     /// Only present when the Machine has a deletionTimestamp and drain or wait for volume detach started.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -339,7 +375,7 @@ pub struct MachineStatus {
         skip_serializing_if = "Option::is_none",
         rename = "lastUpdated"
     )]
-    pub last_updated: Option<String>,
+    pub last_updated: Option<Time>,
     /// nodeInfo is a set of ids/uuids to uniquely identify the node.
     /// More info: https://kubernetes.io/docs/concepts/nodes/node/#info
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "nodeInfo")]
@@ -360,6 +396,12 @@ pub struct MachineStatus {
     /// v1beta2 groups all the fields that will be added or modified in Machine's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<MachineStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineAddress contains information for the node's address.
@@ -370,6 +412,12 @@ pub struct MachineStatusAddresses {
     /// type is the machine address type, one of Hostname, ExternalIP, InternalIP, ExternalDNS or InternalDNS.
     #[serde(rename = "type")]
     pub r#type: MachineStatusAddressesType,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineAddress contains information for the node's address.
@@ -384,6 +432,9 @@ pub enum MachineStatusAddressesType {
     ExternalDns,
     #[serde(rename = "InternalDNS")]
     InternalDns,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// deletion contains information relating to removal of the Machine.
@@ -398,7 +449,7 @@ pub struct MachineStatusDeletion {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainStartTime"
     )]
-    pub node_drain_start_time: Option<String>,
+    pub node_drain_start_time: Option<Time>,
     /// waitForNodeVolumeDetachStartTime is the time when waiting for volume detachment started
     /// and is used to determine if the NodeVolumeDetachTimeout is exceeded.
     /// Detaching volumes from nodes is usually done by CSI implementations and the current state
@@ -409,7 +460,13 @@ pub struct MachineStatusDeletion {
         skip_serializing_if = "Option::is_none",
         rename = "waitForNodeVolumeDetachStartTime"
     )]
-    pub wait_for_node_volume_detach_start_time: Option<String>,
+    pub wait_for_node_volume_detach_start_time: Option<Time>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// nodeInfo is a set of ids/uuids to uniquely identify the node.
@@ -449,6 +506,12 @@ pub struct MachineStatusNodeInfo {
     /// https://access.redhat.com/documentation/en-us/red_hat_subscription_management/1/html/rhsm/uuid
     #[serde(rename = "systemUUID")]
     pub system_uuid: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// nodeRef will point to the corresponding Node if it exists.
@@ -494,6 +557,12 @@ pub struct MachineStatusNodeRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of Machine.
@@ -507,6 +576,9 @@ pub enum MachineStatusPhase {
     Deleted,
     Failed,
     Unknown,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// v1beta2 groups all the fields that will be added or modified in Machine's status with the V1Beta2 version.
@@ -520,4 +592,10 @@ pub struct MachineStatusV1beta2 {
     /// APIServerPodHealthy, ControllerManagerPodHealthy, SchedulerPodHealthy, EtcdPodHealthy, EtcdMemberHealthy.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<Condition>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
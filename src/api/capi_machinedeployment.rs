@@ -4,8 +4,11 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
+    pub use crate::api::duration::GoDuration;
     pub use k8s_openapi::api::core::v1::ObjectReference;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+    pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
     pub use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
     pub use kube::CustomResource;
     pub use schemars::JsonSchema;
@@ -103,7 +106,7 @@ pub struct MachineDeploymentSpec {
         skip_serializing_if = "Option::is_none",
         rename = "rolloutAfter"
     )]
-    pub rollout_after: Option<String>,
+    pub rollout_after: Option<Time>,
     /// selector is the label selector for machines. Existing MachineSets whose machines are
     /// selected by this will be the ones affected by this deployment.
     /// It must match the machine template's labels.
@@ -114,6 +117,12 @@ pub struct MachineDeploymentSpec {
     pub strategy: Option<MachineDeploymentStrategy>,
     /// template describes the machines that will be created.
     pub template: MachineDeploymentTemplate,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// machineNamingStrategy allows changing the naming pattern used when creating Machines.
@@ -138,6 +147,12 @@ pub struct MachineDeploymentMachineNamingStrategy {
     /// template. If not provided, validation will fail.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// selector is the label selector for machines. Existing MachineSets whose machines are
@@ -161,6 +176,12 @@ pub struct MachineDeploymentSelector {
         rename = "matchLabels"
     )]
     pub match_labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// A label selector requirement is a selector that contains values, a key, and an operator that
@@ -178,6 +199,12 @@ pub struct MachineDeploymentSelectorMatchExpressions {
     /// merge patch.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub values: Option<Vec<String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -200,6 +227,12 @@ pub struct MachineDeploymentStrategy {
     /// The default is RollingUpdate.
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
     pub r#type: Option<MachineDeploymentStrategyType>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// remediation controls the strategy of remediating unhealthy machines
@@ -226,6 +259,12 @@ pub struct MachineDeploymentStrategyRemediation {
         rename = "maxInFlight"
     )]
     pub max_in_flight: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -274,6 +313,12 @@ pub struct MachineDeploymentStrategyRollingUpdate {
         rename = "maxUnavailable"
     )]
     pub max_unavailable: Option<IntOrString>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// rollingUpdate is the rolling update config params. Present only if
@@ -283,6 +328,9 @@ pub enum MachineDeploymentStrategyRollingUpdateDeletePolicy {
     Random,
     Newest,
     Oldest,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// strategy is the deployment strategy to use to replace existing machines with
@@ -291,6 +339,9 @@ pub enum MachineDeploymentStrategyRollingUpdateDeletePolicy {
 pub enum MachineDeploymentStrategyType {
     RollingUpdate,
     OnDelete,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// template describes the machines that will be created.
@@ -304,6 +355,12 @@ pub struct MachineDeploymentTemplate {
     /// More info: https://git.k8s.io/community/contributors/devel/sig-architecture/api-conventions.md#spec-and-status
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub spec: Option<MachineDeploymentTemplateSpec>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the standard object's metadata.
@@ -322,6 +379,12 @@ pub struct MachineDeploymentTemplateMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// spec is the specification of the desired behavior of the machine.
@@ -354,7 +417,7 @@ pub struct MachineDeploymentTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -363,7 +426,7 @@ pub struct MachineDeploymentTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -371,7 +434,7 @@ pub struct MachineDeploymentTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// providerID is the identification ID of the machine provided by the provider.
     /// This field must match the provider ID as seen on the node object corresponding to this machine.
     /// This field is required by higher level consumers of cluster-api. Example use case is cluster autoscaler
@@ -412,6 +475,12 @@ pub struct MachineDeploymentTemplateSpec {
     /// This field is meant to be optionally used by bootstrap providers.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// bootstrap is a reference to a local struct which encapsulates
@@ -432,6 +501,12 @@ pub struct MachineDeploymentTemplateSpecBootstrap {
         rename = "dataSecretName"
     )]
     pub data_secret_name: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// configRef is a reference to a bootstrap provider-specific resource
@@ -480,6 +555,12 @@ pub struct MachineDeploymentTemplateSpecBootstrapConfigRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructureRef is a required reference to a custom resource
@@ -526,6 +607,12 @@ pub struct MachineDeploymentTemplateSpecInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -543,6 +630,12 @@ pub struct MachineDeploymentTemplateSpecReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<MachineDeploymentTemplateSpecReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -550,6 +643,9 @@ pub struct MachineDeploymentTemplateSpecReadinessGates {
 pub enum MachineDeploymentTemplateSpecReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// status is the observed state of MachineDeployment.
@@ -565,7 +661,7 @@ pub struct MachineDeploymentStatus {
     pub available_replicas: Option<i32>,
     /// conditions defines current service state of the MachineDeployment.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// observedGeneration is the generation observed by the deployment controller.
     #[serde(
         default,
@@ -616,6 +712,12 @@ pub struct MachineDeploymentStatus {
     /// v1beta2 groups all the fields that will be added or modified in MachineDeployment's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<MachineDeploymentStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of MachineDeployment.
@@ -626,6 +728,9 @@ pub enum MachineDeploymentStatusPhase {
     Running,
     Failed,
     Unknown,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// v1beta2 groups all the fields that will be added or modified in MachineDeployment's status with the V1Beta2 version.
@@ -656,4 +761,10 @@ pub struct MachineDeploymentStatusV1beta2 {
         rename = "upToDateReplicas"
     )]
     pub up_to_date_replicas: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
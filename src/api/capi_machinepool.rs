@@ -4,6 +4,8 @@
 
 #[allow(unused_imports)]
 mod prelude {
+    pub use crate::api::condition::Condition as CapiCondition;
+    pub use crate::api::duration::GoDuration;
     pub use k8s_openapi::api::core::v1::ObjectReference;
     pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
     pub use kube::CustomResource;
@@ -60,6 +62,12 @@ pub struct MachinePoolSpec {
     pub replicas: Option<i32>,
     /// template describes the machines that will be created.
     pub template: MachinePoolTemplate,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// template describes the machines that will be created.
@@ -73,6 +81,12 @@ pub struct MachinePoolTemplate {
     /// More info: https://git.k8s.io/community/contributors/devel/sig-architecture/api-conventions.md#spec-and-status
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub spec: Option<MachinePoolTemplateSpec>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// metadata is the standard object's metadata.
@@ -91,6 +105,12 @@ pub struct MachinePoolTemplateMetadata {
     /// More info: http://kubernetes.io/docs/user-guide/labels
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// spec is the specification of the desired behavior of the machine.
@@ -123,7 +143,7 @@ pub struct MachinePoolTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDeletionTimeout"
     )]
-    pub node_deletion_timeout: Option<String>,
+    pub node_deletion_timeout: Option<GoDuration>,
     /// nodeDrainTimeout is the total amount of time that the controller will spend on draining a node.
     /// The default value is 0, meaning that the node can be drained without any time limitations.
     /// NOTE: NodeDrainTimeout is different from `kubectl drain --timeout`
@@ -132,7 +152,7 @@ pub struct MachinePoolTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeDrainTimeout"
     )]
-    pub node_drain_timeout: Option<String>,
+    pub node_drain_timeout: Option<GoDuration>,
     /// nodeVolumeDetachTimeout is the total amount of time that the controller will spend on waiting for all volumes
     /// to be detached. The default value is 0, meaning that the volumes can be detached without any time limitations.
     #[serde(
@@ -140,7 +160,7 @@ pub struct MachinePoolTemplateSpec {
         skip_serializing_if = "Option::is_none",
         rename = "nodeVolumeDetachTimeout"
     )]
-    pub node_volume_detach_timeout: Option<String>,
+    pub node_volume_detach_timeout: Option<GoDuration>,
     /// providerID is the identification ID of the machine provided by the provider.
     /// This field must match the provider ID as seen on the node object corresponding to this machine.
     /// This field is required by higher level consumers of cluster-api. Example use case is cluster autoscaler
@@ -181,6 +201,12 @@ pub struct MachinePoolTemplateSpec {
     /// This field is meant to be optionally used by bootstrap providers.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// bootstrap is a reference to a local struct which encapsulates
@@ -201,6 +227,12 @@ pub struct MachinePoolTemplateSpecBootstrap {
         rename = "dataSecretName"
     )]
     pub data_secret_name: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// configRef is a reference to a bootstrap provider-specific resource
@@ -249,6 +281,12 @@ pub struct MachinePoolTemplateSpecBootstrapConfigRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// infrastructureRef is a required reference to a custom resource
@@ -295,6 +333,12 @@ pub struct MachinePoolTemplateSpecInfrastructureRef {
     /// More info: https://kubernetes.io/docs/concepts/overview/working-with-objects/names/#uids
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uid: Option<String>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -312,6 +356,12 @@ pub struct MachinePoolTemplateSpecReadinessGates {
     /// A negative polarity means that the condition should report a false status under normal conditions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub polarity: Option<MachinePoolTemplateSpecReadinessGatesPolarity>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// MachineReadinessGate contains the type of a Machine condition to be used as a readiness gate.
@@ -319,6 +369,9 @@ pub struct MachinePoolTemplateSpecReadinessGates {
 pub enum MachinePoolTemplateSpecReadinessGatesPolarity {
     Positive,
     Negative,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// status is the observed state of MachinePool.
@@ -340,7 +393,7 @@ pub struct MachinePoolStatus {
     pub bootstrap_ready: Option<bool>,
     /// conditions define the current service state of the MachinePool.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub conditions: Option<Vec<Condition>>,
+    pub conditions: Option<Vec<CapiCondition>>,
     /// failureMessage indicates that there is a problem reconciling the state,
     /// and will be set to a descriptive error message.
     ///
@@ -407,6 +460,12 @@ pub struct MachinePoolStatus {
     /// v1beta2 groups all the fields that will be added or modified in MachinePool's status with the V1Beta2 version.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub v1beta2: Option<MachinePoolStatusV1beta2>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
 /// status is the observed state of MachinePool.
@@ -422,6 +481,9 @@ pub enum MachinePoolStatusPhase {
     Deleting,
     Failed,
     Unknown,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
 }
 
 /// v1beta2 groups all the fields that will be added or modified in MachinePool's status with the V1Beta2 version.
@@ -453,4 +515,10 @@ pub struct MachinePoolStatusV1beta2 {
         rename = "upToDateReplicas"
     )]
     pub up_to_date_replicas: Option<i32>,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
@@ -0,0 +1,105 @@
+// Hand-maintained: kopium maps every `conditions` list onto metav1.Condition, which
+// drops the v1beta1 `severity`. The v1beta1 `status.conditions` fields are rewritten
+// to this type by `just _postprocess`, `status.v1beta2.conditions` stay on metav1.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Condition defines an observation of a Cluster API resource operational state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Condition {
+    /// lastTransitionTime is the last time the condition transitioned from one status to another.
+    /// This should be when the underlying condition changed. If that is not known, then using the time when
+    /// the API field changed is acceptable.
+    #[serde(rename = "lastTransitionTime")]
+    pub last_transition_time: Time,
+    /// message is a human readable message indicating details about the transition.
+    /// This field may be empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// reason is the reason for the condition's last transition in CamelCase.
+    /// The specific API may choose whether or not this field is considered a guaranteed API.
+    /// This field may be empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// severity provides an explicit classification of Reason code, so the users or machines can immediately
+    /// understand the current situation and act accordingly.
+    /// The Severity field MUST be set only when Status=False.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<ConditionSeverity>,
+    /// status of the condition, one of True, False, Unknown.
+    pub status: String,
+    /// type of condition in CamelCase or in foo.example.com/CamelCase.
+    /// Many .condition.type values are consistent across resources like Available, but because arbitrary conditions
+    /// can be useful (see .node.status.conditions), the ability to deconflict is important.
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// Fields unknown to this version of the crate, kept so they survive a round-trip with the
+    /// `preserve-unknown-fields` feature. Always empty without it.
+    #[cfg_attr(feature = "preserve-unknown-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "preserve-unknown-fields"), serde(skip))]
+    #[schemars(skip)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+/// ConditionSeverity expresses the severity of a Condition Type failing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum ConditionSeverity {
+    Error,
+    Warning,
+    Info,
+    /// No severity, which upstream serializes as an empty string.
+    #[serde(rename = "")]
+    None,
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn severity(value: &str) -> Option<ConditionSeverity> {
+        let condition: Condition = serde_json::from_value(serde_json::json!({
+            "type": "Ready",
+            "status": "False",
+            "severity": value,
+            "lastTransitionTime": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        condition.severity
+    }
+
+    #[test]
+    fn severity_round_trips() {
+        for (value, expected) in [
+            ("Error", ConditionSeverity::Error),
+            ("Warning", ConditionSeverity::Warning),
+            ("Info", ConditionSeverity::Info),
+            ("", ConditionSeverity::None),
+            ("Fatal", ConditionSeverity::Other("Fatal".into())),
+        ] {
+            assert_eq!(severity(value), Some(expected.clone()), "{value:?}");
+            assert_eq!(serde_json::to_value(&expected).unwrap(), value);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "preserve-unknown-fields"))]
+    fn drops_unknown_fields_without_the_feature() {
+        let value = serde_json::json!({
+            "type": "Ready",
+            "status": "True",
+            "lastTransitionTime": "2024-01-01T00:00:00Z",
+            "futureField": 1,
+        });
+        let condition: Condition = serde_json::from_value(value).unwrap();
+        assert!(condition.extra.is_empty());
+        assert!(serde_json::to_value(&condition)
+            .unwrap()
+            .get("futureField")
+            .is_none());
+    }
+}
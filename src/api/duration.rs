@@ -0,0 +1,391 @@
+// Hand-maintained: the CRDs declare Go durations such as `nodeDrainTimeout` as plain
+// strings, which kopium maps onto `String`. Those fields are rewritten to this type
+// by `just _postprocess`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const NANOSECOND: u64 = 1;
+const MICROSECOND: u64 = 1000 * NANOSECOND;
+const MILLISECOND: u64 = 1000 * MICROSECOND;
+const SECOND: u64 = 1000 * MILLISECOND;
+const MINUTE: u64 = 60 * SECOND;
+const HOUR: u64 = 60 * MINUTE;
+
+/// A duration in the format of Go's `time.Duration`, e.g. `"1h30m"` or `"-1.5s"`.
+///
+/// Parses like `time.ParseDuration` and formats like `Duration.String`, so a value
+/// read from the API is written back in its canonical form. Go durations are signed
+/// nanosecond counts, hence the conversions to and from [`Duration`] may fail.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    JsonSchema,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct GoDuration(#[schemars(with = "String")] i64);
+
+impl GoDuration {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_nanos(nanos: i64) -> Self {
+        Self(nanos)
+    }
+
+    pub const fn as_nanos(self) -> i64 {
+        self.0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Converts to a [`Duration`], `None` if negative.
+    pub fn to_std(self) -> Option<Duration> {
+        u64::try_from(self.0).ok().map(Duration::from_nanos)
+    }
+}
+
+/// Error for a [`Duration`] longer than a Go duration can hold (about 292 years).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("duration {0:?} overflows a Go duration")]
+pub struct DurationOverflowError(pub Duration);
+
+impl TryFrom<Duration> for GoDuration {
+    type Error = DurationOverflowError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        i64::try_from(duration.as_nanos())
+            .map(Self)
+            .map_err(|_| DurationOverflowError(duration))
+    }
+}
+
+/// Error returned when parsing a [`GoDuration`], with the messages of `time.ParseDuration`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseDurationError {
+    #[error("time: invalid duration {0:?}")]
+    Invalid(String),
+    #[error("time: missing unit in duration {0:?}")]
+    MissingUnit(String),
+    #[error("time: unknown unit {unit:?} in duration {duration:?}")]
+    UnknownUnit { unit: String, duration: String },
+}
+
+impl FromStr for GoDuration {
+    type Err = ParseDurationError;
+
+    // [-+]?([0-9]*(\.[0-9]*)?[a-z]+)+
+    fn from_str(orig: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseDurationError::Invalid(orig.to_string());
+        let mut s = orig;
+        let mut neg = false;
+        if let Some(rest) = s.strip_prefix('-') {
+            neg = true;
+            s = rest;
+        } else if let Some(rest) = s.strip_prefix('+') {
+            s = rest;
+        }
+        // special case: if all that is left is "0", this is zero
+        if s == "0" {
+            return Ok(Self::ZERO);
+        }
+        if s.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut d: u64 = 0;
+        while !s.is_empty() {
+            // the next character must be [0-9.]
+            if !s.starts_with(|c: char| c == '.' || c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            // consume [0-9]*
+            let (mut v, rest) = leading_int(s).ok_or_else(invalid)?;
+            let pre = rest.len() != s.len();
+            s = rest;
+            // consume (\.[0-9]*)?
+            let (mut f, mut scale, mut post) = (0, 1.0, false);
+            if let Some(rest) = s.strip_prefix('.') {
+                let (fraction, fraction_scale, rest_after) = leading_fraction(rest);
+                post = rest_after.len() != rest.len();
+                (f, scale, s) = (fraction, fraction_scale, rest_after);
+            }
+            if !pre && !post {
+                // no digits (e.g. ".s" or "-.s")
+                return Err(invalid());
+            }
+            // consume unit
+            let end = s
+                .find(|c: char| c == '.' || c.is_ascii_digit())
+                .unwrap_or(s.len());
+            if end == 0 {
+                return Err(ParseDurationError::MissingUnit(orig.to_string()));
+            }
+            let (u, rest) = s.split_at(end);
+            s = rest;
+            let unit = unit(u).ok_or_else(|| ParseDurationError::UnknownUnit {
+                unit: u.to_string(),
+                duration: orig.to_string(),
+            })?;
+            if v > (1 << 63) / unit {
+                return Err(invalid());
+            }
+            v *= unit;
+            if f > 0 {
+                // float64 is needed to be nanosecond accurate for fractions of hours.
+                // v >= 0 && (f*unit/scale) <= 3.6e+12 (ns/h, h is the largest unit)
+                v += (f as f64 * (unit as f64 / scale)) as u64;
+                if v > 1 << 63 {
+                    return Err(invalid());
+                }
+            }
+            d += v;
+            if d > 1 << 63 {
+                return Err(invalid());
+            }
+        }
+        if neg {
+            return Ok(Self(0i64.wrapping_sub_unsigned(d)));
+        }
+        i64::try_from(d).map(Self).map_err(|_| invalid())
+    }
+}
+
+fn unit(u: &str) -> Option<u64> {
+    match u {
+        "ns" => Some(NANOSECOND),
+        // U+00B5 micro sign and U+03BC Greek letter mu
+        "us" | "\u{00b5}s" | "\u{03bc}s" => Some(MICROSECOND),
+        "ms" => Some(MILLISECOND),
+        "s" => Some(SECOND),
+        "m" => Some(MINUTE),
+        "h" => Some(HOUR),
+        _ => None,
+    }
+}
+
+/// Consumes the leading [0-9]* from s, `None` on overflow.
+fn leading_int(s: &str) -> Option<(u64, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let mut x: u64 = 0;
+    for c in s[..end].bytes() {
+        if x > (1 << 63) / 10 {
+            return None;
+        }
+        x = x * 10 + u64::from(c - b'0');
+        if x > 1 << 63 {
+            return None;
+        }
+    }
+    Some((x, &s[end..]))
+}
+
+/// Consumes the leading [0-9]* from s. Used only for fractions, so it does not
+/// report overflow, it just stops accumulating precision.
+fn leading_fraction(s: &str) -> (u64, f64, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (mut x, mut scale, mut overflow) = (0u64, 1.0, false);
+    for c in s[..end].bytes() {
+        if overflow {
+            continue;
+        }
+        if x > ((1 << 63) - 1) / 10 {
+            overflow = true;
+            continue;
+        }
+        let y = x * 10 + u64::from(c - b'0');
+        if y > 1 << 63 {
+            overflow = true;
+            continue;
+        }
+        x = y;
+        scale *= 10.0;
+    }
+    (x, scale, &s[end..])
+}
+
+impl fmt::Display for GoDuration {
+    /// Formats as `72h3m0.5s`: leading zero units are omitted, durations under one
+    /// second use a smaller unit so the leading digit is non-zero, and zero is `0s`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let u = self.0.unsigned_abs();
+        if u == 0 {
+            return f.write_str("0s");
+        }
+        if u < SECOND {
+            // use smaller units, e.g. 1.2ms
+            let (unit, prec) = match u {
+                u if u < MICROSECOND => ("ns", 0),
+                u if u < MILLISECOND => ("\u{00b5}s", 3),
+                _ => ("ms", 6),
+            };
+            let (int, frac) = split_frac(u, prec);
+            return write!(f, "{sign}{int}{frac}{unit}");
+        }
+        let (secs, frac) = split_frac(u, 9);
+        let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours > 0 {
+            write!(f, "{sign}{hours}h{mins}m{secs}{frac}s")
+        } else if mins > 0 {
+            write!(f, "{sign}{mins}m{secs}{frac}s")
+        } else {
+            write!(f, "{sign}{secs}{frac}s")
+        }
+    }
+}
+
+/// Splits v into its integer part and `.fraction` with prec digits, omitting
+/// trailing zeros and the dot if the fraction is zero.
+fn split_frac(v: u64, prec: u32) -> (u64, String) {
+    let scale = 10u64.pow(prec);
+    let frac = v % scale;
+    if frac == 0 {
+        return (v / scale, String::new());
+    }
+    let digits = format!("{frac:0width$}", width = prec as usize);
+    (v / scale, format!(".{}", digits.trim_end_matches('0')))
+}
+
+impl From<GoDuration> for String {
+    fn from(duration: GoDuration) -> Self {
+        duration.to_string()
+    }
+}
+
+impl TryFrom<String> for GoDuration {
+    type Error = ParseDurationError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_like_go() {
+        for (input, nanos, formatted) in [
+            ("0", 0, "0s"),
+            ("-0", 0, "0s"),
+            ("0s", 0, "0s"),
+            ("90s", 90 * SECOND as i64, "1m30s"),
+            ("1.5h", 90 * MINUTE as i64, "1h30m0s"),
+            ("1us", 1000, "1µs"),
+            ("1µs", 1000, "1µs"),
+            ("1μs", 1000, "1µs"),
+            ("100ns", 100, "100ns"),
+            ("1100ns", 1100, "1.1µs"),
+            ("2.2ms", 2_200_000, "2.2ms"),
+            ("1500ms", 1_500_000_000, "1.5s"),
+            ("-1.5s", -1_500_000_000, "-1.5s"),
+            ("+10m", 10 * MINUTE as i64, "10m0s"),
+            ("1h2m3s4ms5us6ns", 3_723_004_005_006, "1h2m3.004005006s"),
+            (".5s", 500_000_000, "500ms"),
+            ("5.s", 5 * SECOND as i64, "5s"),
+            (
+                "2562047h47m16.854775807s",
+                i64::MAX,
+                "2562047h47m16.854775807s",
+            ),
+            (
+                "-2562047h47m16.854775808s",
+                i64::MIN,
+                "-2562047h47m16.854775808s",
+            ),
+            (
+                "9223372036854775807ns",
+                i64::MAX,
+                "2562047h47m16.854775807s",
+            ),
+            (
+                "-9223372036854775808ns",
+                i64::MIN,
+                "-2562047h47m16.854775808s",
+            ),
+        ] {
+            let duration: GoDuration = input.parse().unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(duration.as_nanos(), nanos, "{input}");
+            assert_eq!(duration.to_string(), formatted, "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_what_go_rejects() {
+        let invalid = |s: &str| ParseDurationError::Invalid(s.to_string());
+        for (input, err) in [
+            ("", invalid("")),
+            ("-", invalid("-")),
+            (".", invalid(".")),
+            (".s", invalid(".s")),
+            ("s", invalid("s")),
+            ("9223372036854775808ns", invalid("9223372036854775808ns")),
+            ("-9223372036854775809ns", invalid("-9223372036854775809ns")),
+            ("2562048h", invalid("2562048h")),
+            ("3", ParseDurationError::MissingUnit("3".into())),
+            ("1h3", ParseDurationError::MissingUnit("1h3".into())),
+            (
+                "1h-2m",
+                ParseDurationError::UnknownUnit {
+                    unit: "h-".into(),
+                    duration: "1h-2m".into(),
+                },
+            ),
+            (
+                "3x",
+                ParseDurationError::UnknownUnit {
+                    unit: "x".into(),
+                    duration: "3x".into(),
+                },
+            ),
+        ] {
+            assert_eq!(input.parse::<GoDuration>(), Err(err), "{input:?}");
+        }
+        assert_eq!(
+            "3x".parse::<GoDuration>().unwrap_err().to_string(),
+            r#"time: unknown unit "x" in duration "3x""#
+        );
+        assert_eq!(
+            "3".parse::<GoDuration>().unwrap_err().to_string(),
+            r#"time: missing unit in duration "3""#
+        );
+    }
+
+    #[test]
+    fn converts_to_and_from_std() {
+        assert_eq!(
+            GoDuration::from_nanos(-1).to_std(),
+            None,
+            "negative durations have no std equivalent"
+        );
+        assert_eq!(
+            GoDuration::try_from(Duration::from_secs(90)).map(|d| d.to_string()),
+            Ok("1m30s".to_string())
+        );
+        let long = Duration::from_nanos(i64::MAX as u64 + 1);
+        assert_eq!(GoDuration::try_from(long), Err(DurationOverflowError(long)));
+    }
+
+    #[test]
+    fn serializes_canonical_strings() {
+        let duration: GoDuration = serde_json::from_str(r#""90s""#).unwrap();
+        assert_eq!(serde_json::to_string(&duration).unwrap(), r#""1m30s""#);
+        assert!(serde_json::from_str::<GoDuration>(r#""ten minutes""#).is_err());
+    }
+}
@@ -10,3 +10,110 @@ pub mod capi_machinedeployment;
 pub mod capi_machinehealthcheck;
 pub mod capi_machinepool;
 pub mod capi_machineset;
+pub mod condition;
+pub mod duration;
+pub mod well_known;
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use k8s_openapi::chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    use super::*;
+
+    fn time(s: &str) -> Option<Time> {
+        Some(Time(s.parse::<DateTime<Utc>>().unwrap()))
+    }
+
+    #[test]
+    fn date_time_fields_round_trip_as_time() {
+        let value = json!({
+            "metadata": {"name": "m1"},
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+            "status": {
+                "certificatesExpiryDate": "2025-01-01T00:00:00Z",
+                "lastUpdated": "2024-01-01T12:00:00Z",
+                "deletion": {
+                    "nodeDrainStartTime": "2024-01-01T12:01:00Z",
+                    "waitForNodeVolumeDetachStartTime": "2024-01-01T12:02:00Z",
+                },
+            },
+        });
+        let machine: capi_machine::Machine = serde_json::from_value(value.clone()).unwrap();
+        let status = machine.status.as_ref().unwrap();
+        assert_eq!(
+            status.certificates_expiry_date,
+            time("2025-01-01T00:00:00Z")
+        );
+        assert_eq!(status.last_updated, time("2024-01-01T12:00:00Z"));
+        let deletion = status.deletion.as_ref().unwrap();
+        assert_eq!(deletion.node_drain_start_time, time("2024-01-01T12:01:00Z"));
+        assert_eq!(
+            deletion.wait_for_node_volume_detach_start_time,
+            time("2024-01-01T12:02:00Z")
+        );
+        assert_eq!(
+            serde_json::to_value(&machine).unwrap()["status"],
+            value["status"]
+        );
+
+        let value = json!({
+            "metadata": {"name": "md-0"},
+            "spec": {
+                "clusterName": "c1",
+                "rolloutAfter": "2024-01-01T12:00:00Z",
+                "selector": {},
+                "template": {"spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}}},
+            },
+        });
+        let deployment: capi_machinedeployment::MachineDeployment =
+            serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(deployment.spec.rollout_after, time("2024-01-01T12:00:00Z"));
+        assert_eq!(
+            serde_json::to_value(&deployment).unwrap()["spec"]["rolloutAfter"],
+            value["spec"]["rolloutAfter"]
+        );
+
+        // strings that are not RFC 3339 date-times are rejected
+        let parsed = serde_json::from_value::<capi_machinedeployment::MachineDeployment>(json!({
+            "metadata": {"name": "md-0"},
+            "spec": {
+                "clusterName": "c1",
+                "rolloutAfter": "tomorrow",
+                "selector": {},
+                "template": {"spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}}},
+            },
+        }));
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn unknown_enum_values_round_trip() {
+        use capi_machineset::MachineSetDeletePolicy;
+
+        for (value, expected) in [
+            ("Random", MachineSetDeletePolicy::Random),
+            ("Oldest", MachineSetDeletePolicy::Oldest),
+            ("Youngest", MachineSetDeletePolicy::Other("Youngest".into())),
+            ("", MachineSetDeletePolicy::Other(String::new())),
+        ] {
+            let policy: MachineSetDeletePolicy = serde_json::from_value(json!(value)).unwrap();
+            assert_eq!(policy, expected, "{value}");
+            assert_eq!(serde_json::to_value(&policy).unwrap(), json!(value));
+        }
+
+        // renamed variants keep their name, other values are kept as they are
+        let value = json!([
+            {"type": "InternalIP", "address": "10.0.0.1"},
+            {"type": "ExternalIPv6", "address": "fd00::1"},
+        ]);
+        let addresses: Vec<capi_machine::MachineStatusAddresses> =
+            serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(
+            addresses[1].r#type,
+            capi_machine::MachineStatusAddressesType::Other("ExternalIPv6".into())
+        );
+        assert_eq!(serde_json::to_value(&addresses).unwrap(), value);
+    }
+}
@@ -0,0 +1,533 @@
+// Hand-maintained: well-known Cluster API labels, annotations, finalizers and condition
+// types/reasons, which are not part of the CRDs kopium generates from.
+
+/// Cluster API release the constants in this module were reviewed against.
+///
+/// The build fails when `version.yaml` points to another release, so keys removed or
+/// renamed upstream are caught before a bump lands. Review the constants against the
+/// new release and update this value along with `version.yaml`.
+pub const CLUSTER_API_VERSION: &str = "v1.10.3";
+
+const _: () = assert!(
+    has_tag(include_str!("../../version.yaml"), CLUSTER_API_VERSION),
+    "version.yaml changed: review src/api/well_known.rs against the new Cluster API release and bump CLUSTER_API_VERSION"
+);
+
+/// Whether `yaml` contains a `tag: <tag>` line.
+const fn has_tag(yaml: &str, tag: &str) -> bool {
+    let (yaml, tag, key) = (yaml.as_bytes(), tag.as_bytes(), b"tag: ");
+    let mut start = 0;
+    while start + key.len() + tag.len() <= yaml.len() {
+        let mut i = 0;
+        while i < key.len() && yaml[start + i] == key[i] {
+            i += 1;
+        }
+        let mut j = 0;
+        while i == key.len() && j < tag.len() && yaml[start + i + j] == tag[j] {
+            j += 1;
+        }
+        let end = start + key.len() + tag.len();
+        if i == key.len() && j == tag.len() && (end == yaml.len() || yaml[end] == b'\n') {
+            return true;
+        }
+        start += 1;
+    }
+    false
+}
+
+/// Labels set or consumed by Cluster API controllers.
+pub mod labels {
+    /// Name of the Cluster an object belongs to.
+    pub const CLUSTER_NAME: &str = "cluster.x-k8s.io/cluster-name";
+    /// Provider name, set on provider components.
+    pub const PROVIDER_NAME: &str = "cluster.x-k8s.io/provider";
+    /// Set on objects whose reconciliation a controller should filter on, see `--watch-filter`.
+    pub const WATCH_FILTER: &str = "cluster.x-k8s.io/watch-filter";
+    /// Marks objects managed by the topology controller.
+    pub const CLUSTER_TOPOLOGY_OWNED: &str = "topology.cluster.x-k8s.io/owned";
+    /// Topology name of the MachineDeployment an object belongs to.
+    pub const CLUSTER_TOPOLOGY_MACHINE_DEPLOYMENT_NAME: &str =
+        "topology.cluster.x-k8s.io/deployment-name";
+    /// Topology name of the MachinePool an object belongs to.
+    pub const CLUSTER_TOPOLOGY_MACHINE_POOL_NAME: &str = "topology.cluster.x-k8s.io/pool-name";
+    /// Set on Machines that are part of the control plane.
+    pub const MACHINE_CONTROL_PLANE: &str = "cluster.x-k8s.io/control-plane";
+    /// Name of the control plane object a Machine belongs to.
+    pub const MACHINE_CONTROL_PLANE_NAME: &str = "cluster.x-k8s.io/control-plane-name";
+    /// Name of the MachineSet a Machine belongs to.
+    pub const MACHINE_SET_NAME: &str = "cluster.x-k8s.io/set-name";
+    /// Name of the MachineDeployment a MachineSet or Machine belongs to.
+    pub const MACHINE_DEPLOYMENT_NAME: &str = "cluster.x-k8s.io/deployment-name";
+    /// Name of the MachinePool a Machine belongs to.
+    pub const MACHINE_POOL_NAME: &str = "cluster.x-k8s.io/pool-name";
+    /// Hash of the MachineDeployment template, distinguishing its MachineSets.
+    pub const MACHINE_DEPLOYMENT_UNIQUE: &str = "machine-template-hash";
+    /// Set on Machines whose infrastructure can be interrupted, e.g. spot instances.
+    pub const INTERRUPTIBLE: &str = "cluster.x-k8s.io/interruptible";
+    /// Marks CRDs and objects clusterctl moves between management clusters.
+    pub const CLUSTERCTL_MOVE: &str = "clusterctl.cluster.x-k8s.io/move";
+    /// Marks CRDs whose objects clusterctl moves together with their whole owner hierarchy.
+    pub const CLUSTERCTL_MOVE_HIERARCHY: &str = "clusterctl.cluster.x-k8s.io/move-hierarchy";
+    /// Set on all the components installed by clusterctl.
+    pub const CLUSTERCTL: &str = "clusterctl.cluster.x-k8s.io";
+    /// Prefix of the node role labels propagated from Machines to Nodes.
+    pub const NODE_ROLE_PREFIX: &str = "node-role.kubernetes.io";
+}
+
+/// Annotations set or consumed by Cluster API controllers.
+pub mod annotations {
+    /// Pauses reconciliation of the annotated object.
+    pub const PAUSED: &str = "cluster.x-k8s.io/paused";
+    /// Marks a Machine to be deleted first on scale down.
+    pub const DELETE_MACHINE: &str = "cluster.x-k8s.io/delete-machine";
+    /// Name of the template an object was cloned from.
+    pub const TEMPLATE_CLONED_FROM_NAME: &str = "cluster.x-k8s.io/cloned-from-name";
+    /// Group and kind of the template an object was cloned from.
+    pub const TEMPLATE_CLONED_FROM_GROUP_KIND: &str = "cluster.x-k8s.io/cloned-from-groupkind";
+    /// Marks an object as managed by an external controller.
+    pub const MANAGED_BY: &str = "cluster.x-k8s.io/managed-by";
+    /// Marks the replicas of a MachinePool as managed by an external entity, e.g. an autoscaler.
+    pub const REPLICAS_MANAGED_BY: &str = "cluster.x-k8s.io/replicas-managed-by";
+    /// Cluster name, set on Nodes.
+    pub const CLUSTER_NAME: &str = "cluster.x-k8s.io/cluster-name";
+    /// Cluster namespace, set on Nodes.
+    pub const CLUSTER_NAMESPACE: &str = "cluster.x-k8s.io/cluster-namespace";
+    /// Machine name, set on Nodes.
+    pub const MACHINE: &str = "cluster.x-k8s.io/machine";
+    /// Kind of the owner of the Machine, set on Nodes.
+    pub const OWNER_KIND: &str = "cluster.x-k8s.io/owner-kind";
+    /// Name of the owner of the Machine, set on Nodes.
+    pub const OWNER_NAME: &str = "cluster.x-k8s.io/owner-name";
+    /// Labels of a Node that are managed by the Machine, set on Nodes.
+    pub const LABELS_FROM_MACHINE: &str = "cluster.x-k8s.io/labels-from-machine";
+    /// Skips draining the Node of a Machine on deletion.
+    pub const EXCLUDE_NODE_DRAINING: &str = "machine.cluster.x-k8s.io/exclude-node-draining";
+    /// Skips waiting for volumes to detach from the Node of a Machine on deletion.
+    pub const EXCLUDE_WAIT_FOR_NODE_VOLUME_DETACH: &str =
+        "machine.cluster.x-k8s.io/exclude-wait-for-node-volume-detach";
+    /// Expiry date of the certificates of a Machine, overriding the value reported by the provider.
+    pub const MACHINE_CERTIFICATES_EXPIRY_DATE: &str =
+        "machine.cluster.x-k8s.io/certificates-expiry";
+    /// Prefix of the annotations blocking Machine deletion before the Node is drained.
+    pub const PRE_DRAIN_DELETE_HOOK_PREFIX: &str = "pre-drain.delete.hook.machine.cluster.x-k8s.io";
+    /// Prefix of the annotations blocking Machine deletion before the infrastructure is deleted.
+    pub const PRE_TERMINATE_DELETE_HOOK_PREFIX: &str =
+        "pre-terminate.delete.hook.machine.cluster.x-k8s.io";
+    /// Excludes a Machine from remediation by MachineHealthChecks.
+    pub const MACHINE_SKIP_REMEDIATION: &str = "cluster.x-k8s.io/skip-remediation";
+    /// Requests remediation of a Machine.
+    pub const REMEDIATE_MACHINE: &str = "cluster.x-k8s.io/remediate-machine";
+    /// Preflight checks a MachineSet should skip, comma separated.
+    pub const MACHINE_SET_SKIP_PREFLIGHT_CHECKS: &str =
+        "machineset.cluster.x-k8s.io/skip-preflight-checks";
+    /// Prevents a MachineSet from creating new Machines.
+    pub const DISABLE_MACHINE_CREATE: &str = "cluster.x-k8s.io/disable-machine-create";
+    /// Revision of a MachineDeployment, set on the MachineDeployment and its MachineSets.
+    pub const REVISION: &str = "machinedeployment.clusters.x-k8s.io/revision";
+    /// Previous revisions of a MachineSet, comma separated.
+    pub const REVISION_HISTORY: &str = "machinedeployment.clusters.x-k8s.io/revision-history";
+    /// Desired replicas of the MachineDeployment, set on its MachineSets.
+    pub const DESIRED_REPLICAS: &str = "machinedeployment.clusters.x-k8s.io/desired-replicas";
+    /// Maximum replicas (desired + maxSurge) of the MachineDeployment, set on its MachineSets.
+    pub const MAX_REPLICAS: &str = "machinedeployment.clusters.x-k8s.io/max-replicas";
+    /// Minimum size of the node group for the cluster autoscaler.
+    pub const AUTOSCALER_MIN_SIZE: &str =
+        "cluster.x-k8s.io/cluster-api-autoscaler-node-group-min-size";
+    /// Maximum size of the node group for the cluster autoscaler.
+    pub const AUTOSCALER_MAX_SIZE: &str =
+        "cluster.x-k8s.io/cluster-api-autoscaler-node-group-max-size";
+    /// Defers the upgrade of a topology managed MachineDeployment or MachinePool.
+    pub const CLUSTER_TOPOLOGY_DEFER_UPGRADE: &str = "topology.cluster.x-k8s.io/defer-upgrade";
+    /// Holds the upgrade of a topology managed MachineDeployment or MachinePool and the following ones.
+    pub const CLUSTER_TOPOLOGY_HOLD_UPGRADE_SEQUENCE: &str =
+        "topology.cluster.x-k8s.io/hold-upgrade-sequence";
+    /// Number of topology managed MachineDeployments or MachinePools upgraded at the same time.
+    pub const CLUSTER_TOPOLOGY_UPGRADE_CONCURRENCY: &str =
+        "topology.cluster.x-k8s.io/upgrade-concurrency";
+    /// Set on objects computed by a topology dry run.
+    pub const TOPOLOGY_DRY_RUN: &str = "topology.cluster.x-k8s.io/dry-run";
+}
+
+/// Finalizers added by Cluster API controllers.
+pub mod finalizers {
+    /// Added to Clusters.
+    pub const CLUSTER: &str = "cluster.cluster.x-k8s.io";
+    /// Added to Machines.
+    pub const MACHINE: &str = "machine.cluster.x-k8s.io";
+    /// Added to MachinePools.
+    pub const MACHINE_POOL: &str = "machinepool.cluster.x-k8s.io";
+    /// Added to topology managed MachineDeployments.
+    pub const MACHINE_DEPLOYMENT_TOPOLOGY: &str = "machinedeployment.topology.cluster.x-k8s.io";
+    /// Added to topology managed MachineSets.
+    pub const MACHINE_SET_TOPOLOGY: &str = "machineset.topology.cluster.x-k8s.io";
+    /// Added to ClusterResourceSets.
+    pub const CLUSTER_RESOURCE_SET: &str = "addons.cluster.x-k8s.io";
+}
+
+/// Condition types and reasons of the v1beta1 `status.conditions`.
+pub mod conditions {
+    /// Summarizes the operational state of an object.
+    pub const READY: &str = "Ready";
+    /// The object is being deleted.
+    pub const DELETING_REASON: &str = "Deleting";
+    /// Deleting the object failed.
+    pub const DELETION_FAILED_REASON: &str = "DeletionFailed";
+    /// The object has been deleted.
+    pub const DELETED_REASON: &str = "Deleted";
+    /// An external reference is invalid.
+    pub const INCORRECT_EXTERNAL_REF_REASON: &str = "IncorrectExternalRef";
+
+    /// Conditions of Clusters.
+    pub mod cluster {
+        /// The infrastructure cluster is ready.
+        pub const INFRASTRUCTURE_READY: &str = "InfrastructureReady";
+        /// Waiting for the infrastructure cluster, when it does not report conditions.
+        pub const WAITING_FOR_INFRASTRUCTURE_FALLBACK_REASON: &str = "WaitingForInfrastructure";
+        /// The control plane has been initialized.
+        pub const CONTROL_PLANE_INITIALIZED: &str = "ControlPlaneInitialized";
+        /// No control plane Machine has a NodeRef yet.
+        pub const MISSING_NODE_REF_REASON: &str = "MissingNodeRef";
+        /// Waiting for the control plane provider to report initialization.
+        pub const WAITING_FOR_CONTROL_PLANE_PROVIDER_INITIALIZED_REASON: &str =
+            "WaitingForControlPlaneProviderInitialized";
+        /// The control plane is ready.
+        pub const CONTROL_PLANE_READY: &str = "ControlPlaneReady";
+        /// Waiting for the control plane, when it does not report conditions.
+        pub const WAITING_FOR_CONTROL_PLANE_FALLBACK_REASON: &str = "WaitingForControlPlane";
+        /// Waiting for the control plane to become available.
+        pub const WAITING_FOR_CONTROL_PLANE_AVAILABLE_REASON: &str =
+            "WaitingForControlPlaneAvailable";
+        /// The topology has been reconciled.
+        pub const TOPOLOGY_RECONCILED: &str = "TopologyReconciled";
+        /// Reconciling the topology failed.
+        pub const TOPOLOGY_RECONCILE_FAILED_REASON: &str = "TopologyReconcileFailed";
+        /// The control plane upgrade has not been applied yet.
+        pub const TOPOLOGY_RECONCILED_CONTROL_PLANE_UPGRADE_PENDING_REASON: &str =
+            "ControlPlaneUpgradePending";
+        /// Some MachineDeployments have not been created yet.
+        pub const TOPOLOGY_RECONCILED_MACHINE_DEPLOYMENTS_CREATE_PENDING_REASON: &str =
+            "MachineDeploymentsCreatePending";
+        /// Some MachineDeployments have not been upgraded yet.
+        pub const TOPOLOGY_RECONCILED_MACHINE_DEPLOYMENTS_UPGRADE_PENDING_REASON: &str =
+            "MachineDeploymentsUpgradePending";
+        /// The upgrade of some MachineDeployments has been deferred.
+        pub const TOPOLOGY_RECONCILED_MACHINE_DEPLOYMENTS_UPGRADE_DEFERRED_REASON: &str =
+            "MachineDeploymentsUpgradeDeferred";
+        /// Some MachinePools have not been created yet.
+        pub const TOPOLOGY_RECONCILED_MACHINE_POOLS_CREATE_PENDING_REASON: &str =
+            "MachinePoolsCreatePending";
+        /// Some MachinePools have not been upgraded yet.
+        pub const TOPOLOGY_RECONCILED_MACHINE_POOLS_UPGRADE_PENDING_REASON: &str =
+            "MachinePoolsUpgradePending";
+        /// The upgrade of some MachinePools has been deferred.
+        pub const TOPOLOGY_RECONCILED_MACHINE_POOLS_UPGRADE_DEFERRED_REASON: &str =
+            "MachinePoolsUpgradeDeferred";
+        /// A lifecycle hook is blocking the topology reconcile.
+        pub const TOPOLOGY_RECONCILED_HOOK_BLOCKING_REASON: &str = "LifecycleHookBlocking";
+        /// The ClusterClass has not been reconciled yet.
+        pub const TOPOLOGY_RECONCILED_CLUSTER_CLASS_NOT_RECONCILED_REASON: &str =
+            "ClusterClassNotReconciled";
+        /// The topology reconcile is paused.
+        pub const TOPOLOGY_RECONCILED_PAUSED_REASON: &str = "Paused";
+    }
+
+    /// Conditions of ClusterClasses.
+    pub mod cluster_class {
+        /// The variables of the ClusterClass have been reconciled.
+        pub const VARIABLES_RECONCILED: &str = "VariablesReconciled";
+        /// Variable discovery from runtime extensions failed.
+        pub const VARIABLE_DISCOVERY_FAILED_REASON: &str = "VariableDiscoveryFailed";
+    }
+
+    /// Conditions of Machines.
+    pub mod machine {
+        /// The bootstrap data is ready.
+        pub const BOOTSTRAP_READY: &str = "BootstrapReady";
+        /// Waiting for the bootstrap data secret, when the provider does not report conditions.
+        pub const WAITING_FOR_DATA_SECRET_FALLBACK_REASON: &str = "WaitingForDataSecret";
+        /// The infrastructure machine is ready.
+        pub const INFRASTRUCTURE_READY: &str = "InfrastructureReady";
+        /// Waiting for the infrastructure machine, when it does not report conditions.
+        pub const WAITING_FOR_INFRASTRUCTURE_FALLBACK_REASON: &str = "WaitingForInfrastructure";
+        /// The Node has been drained.
+        pub const DRAINING_SUCCEEDED: &str = "DrainingSucceeded";
+        /// The Node is being drained.
+        pub const DRAINING_REASON: &str = "Draining";
+        /// Draining the Node failed.
+        pub const DRAINING_FAILED_REASON: &str = "DrainingFailed";
+        /// The pre-drain delete hooks have completed.
+        pub const PRE_DRAIN_DELETE_HOOK_SUCCEEDED: &str = "PreDrainDeleteHookSucceeded";
+        /// The pre-terminate delete hooks have completed.
+        pub const PRE_TERMINATE_DELETE_HOOK_SUCCEEDED: &str = "PreTerminateDeleteHookSucceeded";
+        /// Waiting for an external delete hook.
+        pub const WAITING_EXTERNAL_HOOK_REASON: &str = "WaitingExternalHook";
+        /// All the volumes have been detached from the Node.
+        pub const VOLUME_DETACH_SUCCEEDED: &str = "VolumeDetachSucceeded";
+        /// Waiting for volumes to detach from the Node.
+        pub const WAITING_FOR_VOLUME_DETACH_REASON: &str = "WaitingForVolumeDetach";
+        /// The Node is healthy.
+        pub const NODE_HEALTHY: &str = "NodeHealthy";
+        /// The Node is being provisioned.
+        pub const NODE_PROVISIONING_REASON: &str = "NodeProvisioning";
+        /// The Node cannot be found.
+        pub const NODE_NOT_FOUND_REASON: &str = "NodeNotFound";
+        /// Some Node conditions report a failure.
+        pub const NODE_CONDITIONS_FAILED_REASON: &str = "NodeConditionsFailed";
+        /// Inspecting the Node failed.
+        pub const NODE_INSPECTION_FAILED_REASON: &str = "NodeInspectionFailed";
+        /// The Machine passed its MachineHealthCheck.
+        pub const HEALTH_CHECK_SUCCEEDED: &str = "HealthCheckSucceeded";
+        /// The Machine reports a failure.
+        pub const MACHINE_HAS_FAILURE_REASON: &str = "MachineHasFailure";
+        /// The Machine has the remediate-machine annotation.
+        pub const HAS_REMEDIATE_MACHINE_ANNOTATION_REASON: &str = "HasRemediateMachineAnnotation";
+        /// The Node did not show up within the node startup timeout.
+        pub const NODE_STARTUP_TIMEOUT_REASON: &str = "NodeStartupTimeout";
+        /// A Node condition matched an unhealthy condition for longer than its timeout.
+        pub const UNHEALTHY_NODE_CONDITION_REASON: &str = "UnhealthyNode";
+        /// The owner of the Machine has remediated it.
+        pub const OWNER_REMEDIATED: &str = "OwnerRemediated";
+        /// Waiting for the owner to remediate the Machine.
+        pub const WAITING_FOR_REMEDIATION_REASON: &str = "WaitingForRemediation";
+        /// Remediating the Machine failed.
+        pub const REMEDIATION_FAILED_REASON: &str = "RemediationFailed";
+        /// Remediation of the Machine is in progress.
+        pub const REMEDIATION_IN_PROGRESS_REASON: &str = "RemediationInProgress";
+        /// The external remediation request has been created.
+        pub const EXTERNAL_REMEDIATION_REQUEST_AVAILABLE: &str =
+            "ExternalRemediationRequestAvailable";
+        /// Creating the external remediation request failed.
+        pub const EXTERNAL_REMEDIATION_REQUEST_CREATION_FAILED_REASON: &str =
+            "ExternalRemediationRequestCreationFailed";
+    }
+
+    /// Conditions of MachineSets.
+    pub mod machine_set {
+        /// All the Machines have been created.
+        pub const MACHINES_CREATED: &str = "MachinesCreated";
+        /// All the Machines are ready.
+        pub const MACHINES_READY: &str = "MachinesReady";
+        /// The MachineSet has the desired number of replicas.
+        pub const RESIZED: &str = "Resized";
+        /// The MachineSet is scaling up.
+        pub const SCALING_UP_REASON: &str = "ScalingUp";
+        /// The MachineSet is scaling down.
+        pub const SCALING_DOWN_REASON: &str = "ScalingDown";
+        /// Creating a Machine failed.
+        pub const MACHINE_CREATION_FAILED_REASON: &str = "MachineCreationFailed";
+        /// Cloning the infrastructure template failed.
+        pub const INFRASTRUCTURE_TEMPLATE_CLONING_FAILED_REASON: &str =
+            "InfrastructureTemplateCloningFailed";
+        /// Cloning the bootstrap template failed.
+        pub const BOOTSTRAP_TEMPLATE_CLONING_FAILED_REASON: &str = "BootstrapTemplateCloningFailed";
+        /// A preflight check prevents creating Machines.
+        pub const PREFLIGHT_CHECK_FAILED_REASON: &str = "PreflightCheckFailed";
+    }
+
+    /// Conditions of MachineDeployments.
+    pub mod machine_deployment {
+        /// The MachineDeployment has at least the minimum number of available Machines.
+        pub const AVAILABLE: &str = "Available";
+        /// Waiting for enough Machines to become available.
+        pub const WAITING_FOR_AVAILABLE_MACHINES_REASON: &str = "WaitingForAvailableMachines";
+    }
+
+    /// Conditions of MachinePools.
+    pub mod machine_pool {
+        /// The bootstrap data is ready.
+        pub const BOOTSTRAP_READY: &str = "BootstrapReady";
+        /// The infrastructure machine pool is ready.
+        pub const INFRASTRUCTURE_READY: &str = "InfrastructureReady";
+        /// All the replicas are ready.
+        pub const REPLICAS_READY: &str = "ReplicasReady";
+        /// Waiting for the replicas to become ready.
+        pub const WAITING_FOR_REPLICAS_READY_REASON: &str = "WaitingForReplicasReady";
+    }
+
+    /// Conditions of MachineHealthChecks.
+    pub mod machine_health_check {
+        /// Remediation is allowed by maxUnhealthy / unhealthyRange.
+        pub const REMEDIATION_ALLOWED: &str = "RemediationAllowed";
+        /// Too many targets are unhealthy to allow remediation.
+        pub const TOO_MANY_UNHEALTHY_REASON: &str = "TooManyUnhealthy";
+        /// The external remediation template exists.
+        pub const EXTERNAL_REMEDIATION_TEMPLATE_AVAILABLE: &str =
+            "ExternalRemediationTemplateAvailable";
+        /// The external remediation template cannot be found.
+        pub const EXTERNAL_REMEDIATION_TEMPLATE_NOT_FOUND_REASON: &str =
+            "ExternalRemediationTemplateNotFound";
+    }
+
+    /// Conditions of ClusterResourceSets.
+    pub mod cluster_resource_set {
+        /// The resources have been applied to all the matching Clusters.
+        pub const RESOURCES_APPLIED: &str = "ResourcesApplied";
+        /// Creating a client for the workload cluster failed.
+        pub const REMOTE_CLUSTER_CLIENT_FAILED_REASON: &str = "RemoteClusterClientFailed";
+        /// Listing the Clusters matching the selector failed.
+        pub const CLUSTER_MATCH_FAILED_REASON: &str = "ClusterMatchFailed";
+        /// Applying the resources failed.
+        pub const APPLY_FAILED_REASON: &str = "ApplyFailed";
+        /// A referenced Secret has a type other than `addons.cluster.x-k8s.io/resource-set`.
+        pub const WRONG_SECRET_TYPE_REASON: &str = "WrongSecretType";
+    }
+
+    /// Conditions of ExtensionConfigs.
+    pub mod extension_config {
+        /// The runtime extension handlers have been discovered.
+        pub const DISCOVERED: &str = "Discovered";
+        /// Discovering the runtime extension handlers failed.
+        pub const DISCOVERY_FAILED_REASON: &str = "DiscoveryFailed";
+    }
+
+    /// Condition types and reasons of the `status.v1beta2.conditions` lists.
+    pub mod v1beta2 {
+        /// Whether an object is available.
+        pub const AVAILABLE: &str = "Available";
+        /// Whether an object is ready.
+        pub const READY: &str = "Ready";
+        /// Whether the Machines of an object are up to date.
+        pub const UP_TO_DATE: &str = "UpToDate";
+        /// Whether the reconciliation of an object is paused.
+        pub const PAUSED: &str = "Paused";
+        /// Whether an object is being deleted.
+        pub const DELETING: &str = "Deleting";
+        /// Whether the bootstrap config is ready.
+        pub const BOOTSTRAP_CONFIG_READY: &str = "BootstrapConfigReady";
+        /// Whether the infrastructure object is ready.
+        pub const INFRASTRUCTURE_READY: &str = "InfrastructureReady";
+        /// The readiness of the Machines of an object.
+        pub const MACHINES_READY: &str = "MachinesReady";
+        /// Whether the Machines of an object are up to date.
+        pub const MACHINES_UP_TO_DATE: &str = "MachinesUpToDate";
+        /// Whether an object is rolling out Machines.
+        pub const ROLLING_OUT: &str = "RollingOut";
+        /// Whether an object is scaling up.
+        pub const SCALING_UP: &str = "ScalingUp";
+        /// Whether an object is scaling down.
+        pub const SCALING_DOWN: &str = "ScalingDown";
+        /// Whether an object is remediating Machines.
+        pub const REMEDIATING: &str = "Remediating";
+
+        /// The condition is True because the object is available.
+        pub const AVAILABLE_REASON: &str = "Available";
+        /// The condition is False because the object is not available.
+        pub const NOT_AVAILABLE_REASON: &str = "NotAvailable";
+        /// Whether the object is available is unknown.
+        pub const AVAILABLE_UNKNOWN_REASON: &str = "AvailableUnknown";
+        /// The condition is True because the object is ready.
+        pub const READY_REASON: &str = "Ready";
+        /// The condition is False because the object is not ready.
+        pub const NOT_READY_REASON: &str = "NotReady";
+        /// Whether the object is ready is unknown.
+        pub const READY_UNKNOWN_REASON: &str = "ReadyUnknown";
+        /// The object is up to date.
+        pub const UP_TO_DATE_REASON: &str = "UpToDate";
+        /// The object is not up to date.
+        pub const NOT_UP_TO_DATE_REASON: &str = "NotUpToDate";
+        /// The reconciliation of the object is paused.
+        pub const PAUSED_REASON: &str = "Paused";
+        /// The reconciliation of the object is not paused.
+        pub const NOT_PAUSED_REASON: &str = "NotPaused";
+        /// The object is being deleted.
+        pub const DELETING_REASON: &str = "Deleting";
+        /// The object is not being deleted.
+        pub const NOT_DELETING_REASON: &str = "NotDeleting";
+        /// The object is rolling out Machines.
+        pub const ROLLING_OUT_REASON: &str = "RollingOut";
+        /// The object is not rolling out Machines.
+        pub const NOT_ROLLING_OUT_REASON: &str = "NotRollingOut";
+        /// The object is scaling up.
+        pub const SCALING_UP_REASON: &str = "ScalingUp";
+        /// The object is not scaling up.
+        pub const NOT_SCALING_UP_REASON: &str = "NotScalingUp";
+        /// The object is scaling down.
+        pub const SCALING_DOWN_REASON: &str = "ScalingDown";
+        /// The object is not scaling down.
+        pub const NOT_SCALING_DOWN_REASON: &str = "NotScalingDown";
+        /// The object is remediating Machines.
+        pub const REMEDIATING_REASON: &str = "Remediating";
+        /// The object is not remediating Machines.
+        pub const NOT_REMEDIATING_REASON: &str = "NotRemediating";
+        /// The condition could not be computed because of an unexpected error.
+        pub const INTERNAL_ERROR_REASON: &str = "InternalError";
+        /// A condition is expected but has not been reported yet.
+        pub const NOT_YET_REPORTED_REASON: &str = "NotYetReported";
+        /// More than one of the merged conditions reports an issue.
+        pub const MULTIPLE_ISSUES_REPORTED_REASON: &str = "MultipleIssuesReported";
+        /// More than one of the merged conditions reports Unknown.
+        pub const MULTIPLE_UNKNOWN_REPORTED_REASON: &str = "MultipleUnknownReported";
+        /// More than one of the merged conditions reports info.
+        pub const MULTIPLE_INFO_REPORTED_REASON: &str = "MultipleInfoReported";
+
+        /// v1beta2 conditions of Clusters.
+        pub mod cluster {
+            /// Whether the control plane has been initialized.
+            pub const CONTROL_PLANE_INITIALIZED: &str = "ControlPlaneInitialized";
+            /// Whether the control plane is available.
+            pub const CONTROL_PLANE_AVAILABLE: &str = "ControlPlaneAvailable";
+            /// Whether the workers are available.
+            pub const WORKERS_AVAILABLE: &str = "WorkersAvailable";
+            /// The readiness of the control plane Machines.
+            pub const CONTROL_PLANE_MACHINES_READY: &str = "ControlPlaneMachinesReady";
+            /// The readiness of the worker Machines.
+            pub const WORKER_MACHINES_READY: &str = "WorkerMachinesReady";
+            /// Whether the control plane Machines are up to date.
+            pub const CONTROL_PLANE_MACHINES_UP_TO_DATE: &str = "ControlPlaneMachinesUpToDate";
+            /// Whether the worker Machines are up to date.
+            pub const WORKER_MACHINES_UP_TO_DATE: &str = "WorkerMachinesUpToDate";
+            /// Whether the workload cluster can be reached.
+            pub const REMOTE_CONNECTION_PROBE: &str = "RemoteConnectionProbe";
+            /// Whether the topology has been reconciled.
+            pub const TOPOLOGY_RECONCILED: &str = "TopologyReconciled";
+        }
+
+        /// v1beta2 conditions of Machines.
+        pub mod machine {
+            /// Whether the Node is ready.
+            pub const NODE_READY: &str = "NodeReady";
+            /// Whether the Node is healthy.
+            pub const NODE_HEALTHY: &str = "NodeHealthy";
+            /// Whether the MachineHealthChecks of the Machine succeeded.
+            pub const HEALTH_CHECK_SUCCEEDED: &str = "HealthCheckSucceeded";
+            /// Whether the owner of the Machine remediated it.
+            pub const OWNER_REMEDIATED: &str = "OwnerRemediated";
+        }
+
+        /// v1beta2 conditions of MachineHealthChecks.
+        pub mod machine_health_check {
+            /// Whether remediation is allowed.
+            pub const REMEDIATION_ALLOWED: &str = "RemediationAllowed";
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_tag, CLUSTER_API_VERSION};
+
+    #[test]
+    fn reviewed_against_version_yaml() {
+        let tag = format!("tag: {CLUSTER_API_VERSION}");
+        assert!(
+            include_str!("../../version.yaml")
+                .lines()
+                .any(|line| line.trim() == tag),
+            "version.yaml changed: review src/api/well_known.rs against the new Cluster API release and bump CLUSTER_API_VERSION"
+        );
+    }
+
+    #[test]
+    fn finds_tags() {
+        for (yaml, expected) in [
+            ("tag: v1.10.3", true),
+            ("version:\n  tag: v1.10.3\n", true),
+            ("tag: v1.10.30\n", false),
+            ("tag: v1.10\n", false),
+            ("tags: v1.10.3\n", false),
+            ("", false),
+        ] {
+            assert_eq!(has_tag(yaml, "v1.10.3"), expected, "{yaml:?}");
+        }
+    }
+}
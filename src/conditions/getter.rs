@@ -0,0 +1,120 @@
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+
+use super::{zero_time, Getter, STATUS_FALSE, STATUS_TRUE, STATUS_UNKNOWN};
+use crate::api::condition::{Condition, ConditionSeverity};
+
+/// Returns the condition with the given type, if it exists.
+pub fn get<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a Condition> {
+    from.get_conditions().iter().find(|c| c.r#type == t)
+}
+
+/// Returns true if a condition with the given type exists.
+pub fn has<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some()
+}
+
+/// Returns true if the condition with the given type is True, false otherwise.
+pub fn is_true<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some_and(|c| c.status == STATUS_TRUE)
+}
+
+/// Returns true if the condition with the given type is False, false otherwise.
+pub fn is_false<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some_and(|c| c.status == STATUS_FALSE)
+}
+
+/// Returns true if the condition with the given type is Unknown or if the condition does not exist.
+pub fn is_unknown<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_none_or(|c| c.status == STATUS_UNKNOWN)
+}
+
+/// Returns the reason of the condition with the given type, if any.
+pub fn get_reason<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a str> {
+    get(from, t).and_then(|c| c.reason.as_deref())
+}
+
+/// Returns the message of the condition with the given type, if any.
+pub fn get_message<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a str> {
+    get(from, t).and_then(|c| c.message.as_deref())
+}
+
+/// Returns the severity of the condition with the given type, if any.
+pub fn get_severity<T: Getter>(from: &T, t: &str) -> Option<ConditionSeverity> {
+    get(from, t).and_then(|c| c.severity.clone())
+}
+
+/// Returns the lastTransitionTime of the condition with the given type, if it exists.
+pub fn get_last_transition_time<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a Time> {
+    get(from, t).map(|c| &c.last_transition_time)
+}
+
+/// Returns a condition with Status=True and the given type.
+pub fn true_condition(t: &str) -> Condition {
+    new_condition(t, STATUS_TRUE, String::new(), None, String::new())
+}
+
+/// Returns a condition with negative polarity, Status=True and the given type
+/// (Status=True has a negative meaning).
+pub fn true_condition_with_negative_polarity(
+    t: &str,
+    reason: impl Into<String>,
+    severity: ConditionSeverity,
+    message: impl Into<String>,
+) -> Condition {
+    new_condition(
+        t,
+        STATUS_TRUE,
+        reason.into(),
+        Some(severity),
+        message.into(),
+    )
+}
+
+/// Returns a condition with Status=False and the given type.
+pub fn false_condition(
+    t: &str,
+    reason: impl Into<String>,
+    severity: ConditionSeverity,
+    message: impl Into<String>,
+) -> Condition {
+    new_condition(
+        t,
+        STATUS_FALSE,
+        reason.into(),
+        Some(severity),
+        message.into(),
+    )
+}
+
+/// Returns a condition with negative polarity, Status=False and the given type
+/// (Status=False has a positive meaning).
+pub fn false_condition_with_negative_polarity(t: &str) -> Condition {
+    new_condition(t, STATUS_FALSE, String::new(), None, String::new())
+}
+
+/// Returns a condition with Status=Unknown and the given type.
+pub fn unknown_condition(
+    t: &str,
+    reason: impl Into<String>,
+    message: impl Into<String>,
+) -> Condition {
+    new_condition(t, STATUS_UNKNOWN, reason.into(), None, message.into())
+}
+
+pub(super) fn new_condition(
+    t: &str,
+    status: &str,
+    reason: String,
+    severity: Option<ConditionSeverity>,
+    message: String,
+) -> Condition {
+    Condition {
+        last_transition_time: zero_time(),
+        message: Some(message).filter(|m| !m.is_empty()),
+        reason: Some(reason).filter(|r| !r.is_empty()),
+        severity,
+        status: status.to_string(),
+        r#type: t.to_string(),
+        extra: Default::default(),
+    }
+}
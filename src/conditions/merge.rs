@@ -0,0 +1,756 @@
+use super::getter::{false_condition, get, new_condition, true_condition, unknown_condition};
+use super::setter::{lexicographic_cmp, set};
+use super::{Getter, Setter, READY_CONDITION, STATUS_FALSE, STATUS_TRUE, STATUS_UNKNOWN};
+use crate::api::condition::{Condition, ConditionSeverity};
+
+/// Options for [`summary`] and [`aggregate`].
+#[derive(Clone, Debug, Default)]
+pub struct MergeOptions {
+    condition_types: Option<Vec<String>>,
+    negative_polarity_condition_types: Vec<String>,
+    add_source_ref: bool,
+    add_step_counter: bool,
+    add_step_counter_if_only_condition_types: Option<Vec<String>>,
+}
+
+impl MergeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the merge to the given condition types, which also define the priority order
+    /// used to pick the reason and message.
+    pub fn with_conditions<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.condition_types = Some(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Considers Status=True as negative and Status=False as positive for the given condition types.
+    pub fn with_negative_polarity_conditions<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.negative_polarity_condition_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Reports a "n of m completed" message instead of the message of the top condition.
+    pub fn with_step_counter(mut self) -> Self {
+        self.add_step_counter = true;
+        self
+    }
+
+    /// Same as [`with_step_counter`](Self::with_step_counter), applied only when `value` is true.
+    pub fn with_step_counter_if(mut self, value: bool) -> Self {
+        self.add_step_counter = value;
+        self
+    }
+
+    /// Adds the step counter only if all the conditions in scope are of the given types.
+    pub fn with_step_counter_if_only<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.add_step_counter_if_only_condition_types =
+            Some(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Localizes the reason with the object the condition originates from, e.g. `Reason @ Machine/m1`.
+    pub fn add_source_ref(mut self) -> Self {
+        self.add_source_ref = true;
+        self
+    }
+}
+
+/// Options for [`mirror`].
+#[derive(Clone, Debug, Default)]
+pub struct MirrorOptions {
+    fallback: Option<Fallback>,
+}
+
+#[derive(Clone, Debug)]
+struct Fallback {
+    value: bool,
+    reason: String,
+    severity: ConditionSeverity,
+    message: String,
+}
+
+impl MirrorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses a condition with the given value when the source has no Ready condition.
+    pub fn with_fallback_value(
+        mut self,
+        value: bool,
+        reason: impl Into<String>,
+        severity: ConditionSeverity,
+        message: impl Into<String>,
+    ) -> Self {
+        self.fallback = Some(Fallback {
+            value,
+            reason: reason.into(),
+            severity,
+            message: message.into(),
+        });
+        self
+    }
+}
+
+/// Returns a Ready condition summarizing all the other conditions of `from`.
+///
+/// Conditions are grouped by status and severity, and the group with the highest merge
+/// priority (False/Error, False/Warning, False/Info, True, Unknown) determines the result.
+/// Returns `None` when there is nothing to summarize.
+pub fn summary<T: Getter>(from: &T, options: &MergeOptions) -> Option<Condition> {
+    let source = Source::of(from);
+    let conditions_in_scope: Vec<_> = from
+        .get_conditions()
+        .iter()
+        .filter(|c| c.r#type != READY_CONDITION)
+        .filter(|c| {
+            options
+                .condition_types
+                .as_ref()
+                .is_none_or(|types| types.contains(&c.r#type))
+        })
+        .map(|c| LocalizedCondition::new(c, &source, options))
+        .collect();
+
+    let mut add_step_counter = options.add_step_counter;
+    if let Some(only) = &options.add_step_counter_if_only_condition_types {
+        if conditions_in_scope
+            .iter()
+            .any(|c| !only.contains(&c.condition.r#type))
+        {
+            add_step_counter = false;
+        }
+    }
+
+    let step_counter = match (
+        &options.add_step_counter_if_only_condition_types,
+        &options.condition_types,
+    ) {
+        (Some(only), _) => only.len(),
+        (None, Some(types)) => types.len(),
+        (None, None) => conditions_in_scope.len(),
+    };
+
+    merge(
+        conditions_in_scope,
+        READY_CONDITION,
+        options,
+        add_step_counter.then_some(step_counter),
+    )
+}
+
+/// Returns a copy of the Ready condition of `from` with the target condition type,
+/// or the configured fallback when `from` has no Ready condition.
+pub fn mirror<T: Getter>(
+    from: &T,
+    target_condition: &str,
+    options: &MirrorOptions,
+) -> Option<Condition> {
+    let condition = match (get(from, READY_CONDITION), &options.fallback) {
+        (Some(ready), _) => ready.clone(),
+        (None, Some(fallback)) if fallback.value => true_condition(target_condition),
+        (None, Some(fallback)) => false_condition(
+            target_condition,
+            fallback.reason.clone(),
+            fallback.severity.clone(),
+            fallback.message.clone(),
+        ),
+        (None, None) => return None,
+    };
+    Some(Condition {
+        r#type: target_condition.to_string(),
+        ..condition
+    })
+}
+
+/// Returns a condition with the target type merging the Ready conditions of all the given objects.
+pub fn aggregate<'a, T, I>(
+    from: I,
+    target_condition: &str,
+    options: &MergeOptions,
+) -> Option<Condition>
+where
+    T: Getter + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let sources: Vec<_> = from
+        .into_iter()
+        .map(|getter| (Source::of(getter), get(getter, READY_CONDITION)))
+        .collect();
+    let conditions_in_scope: Vec<_> = sources
+        .iter()
+        .filter_map(|(source, ready)| ready.map(|c| LocalizedCondition::new(c, source, options)))
+        .collect();
+    merge(
+        conditions_in_scope,
+        target_condition,
+        options,
+        options.add_step_counter.then_some(sources.len()),
+    )
+}
+
+/// Sets a Ready condition summarizing all the other conditions of `to`.
+pub fn set_summary<T: Setter>(to: &mut T, options: &MergeOptions) {
+    if let Some(condition) = summary(to, options) {
+        set(to, condition);
+    }
+}
+
+/// Sets a condition with the target type mirroring the Ready condition of `from`.
+pub fn set_mirror<T: Setter, F: Getter>(
+    to: &mut T,
+    target_condition: &str,
+    from: &F,
+    options: &MirrorOptions,
+) {
+    if let Some(condition) = mirror(from, target_condition, options) {
+        set(to, condition);
+    }
+}
+
+/// Sets a condition with the target type aggregating the Ready conditions of `from`.
+pub fn set_aggregate<'a, T, F, I>(
+    to: &mut T,
+    target_condition: &str,
+    from: I,
+    options: &MergeOptions,
+) where
+    T: Setter,
+    F: Getter + 'a,
+    I: IntoIterator<Item = &'a F>,
+{
+    if let Some(condition) = aggregate(from, target_condition, options) {
+        set(to, condition);
+    }
+}
+
+struct Source {
+    kind: String,
+    name: String,
+}
+
+impl Source {
+    fn of<T: Getter>(from: &T) -> Self {
+        Source {
+            kind: T::kind(&()).into_owned(),
+            name: from.meta().name.clone().unwrap_or_default(),
+        }
+    }
+}
+
+struct LocalizedCondition<'a> {
+    condition: &'a Condition,
+    /// Status with negative polarity conditions flipped, so they can be merged as positive ones.
+    merge_status: &'a str,
+    source: &'a Source,
+}
+
+impl<'a> LocalizedCondition<'a> {
+    fn new(condition: &'a Condition, source: &'a Source, options: &MergeOptions) -> Self {
+        let negative = options
+            .negative_polarity_condition_types
+            .contains(&condition.r#type);
+        let merge_status = match condition.status.as_str() {
+            STATUS_TRUE if negative => STATUS_FALSE,
+            STATUS_FALSE if negative => STATUS_TRUE,
+            status => status,
+        };
+        LocalizedCondition {
+            condition,
+            merge_status,
+            source,
+        }
+    }
+}
+
+struct ConditionGroup<'a> {
+    status: &'a str,
+    severity: Option<ConditionSeverity>,
+    conditions: Vec<LocalizedCondition<'a>>,
+}
+
+impl ConditionGroup<'_> {
+    fn merge_priority(&self) -> u8 {
+        match (self.status, &self.severity) {
+            (STATUS_FALSE, Some(ConditionSeverity::Error)) => 0,
+            (STATUS_FALSE, Some(ConditionSeverity::Warning)) => 1,
+            (STATUS_FALSE, Some(ConditionSeverity::Info)) => 2,
+            (STATUS_TRUE, _) => 3,
+            (STATUS_UNKNOWN, _) => 4,
+            // this should never happen
+            _ => 99,
+        }
+    }
+}
+
+fn condition_groups(conditions: Vec<LocalizedCondition<'_>>) -> Vec<ConditionGroup<'_>> {
+    let mut groups: Vec<ConditionGroup> = Vec::new();
+    for condition in conditions {
+        let severity = condition.condition.severity.clone();
+        match groups
+            .iter_mut()
+            .find(|g| g.status == condition.merge_status && g.severity == severity)
+        {
+            Some(group) => group.conditions.push(condition),
+            None => groups.push(ConditionGroup {
+                status: condition.merge_status,
+                severity,
+                conditions: vec![condition],
+            }),
+        }
+    }
+    groups.sort_by_key(ConditionGroup::merge_priority);
+    // Sort the top group so merge strategies give predictable results; conditions with the
+    // same type are sorted by the name of the object they originate from.
+    if let Some(top) = groups.first_mut() {
+        top.conditions.sort_by(|a, b| {
+            lexicographic_cmp(a.condition, b.condition)
+                .then_with(|| a.source.name.cmp(&b.source.name))
+        });
+    }
+    groups
+}
+
+fn merge(
+    conditions: Vec<LocalizedCondition<'_>>,
+    target_condition: &str,
+    options: &MergeOptions,
+    step_counter: Option<usize>,
+) -> Option<Condition> {
+    let groups = condition_groups(conditions);
+    let top = groups.first()?;
+    if top.status == STATUS_TRUE {
+        return Some(true_condition(target_condition));
+    }
+
+    let first = first_condition(top, options.condition_types.as_deref());
+    let reason = match first.condition.reason.as_deref().unwrap_or_default() {
+        reason if options.add_source_ref && !reason.contains('@') => {
+            format!("{} @ {}/{}", reason, first.source.kind, first.source.name)
+        }
+        reason => reason.to_string(),
+    };
+    let message = match step_counter {
+        Some(total) => {
+            let completed = groups
+                .iter()
+                .find(|g| g.status == STATUS_TRUE)
+                .map_or(0, |g| g.conditions.len());
+            format!("{completed} of {total} completed")
+        }
+        None => first.condition.message.clone().unwrap_or_default(),
+    };
+
+    if top.status == STATUS_FALSE {
+        return Some(new_condition(
+            target_condition,
+            STATUS_FALSE,
+            reason,
+            top.severity.clone(),
+            message,
+        ));
+    }
+    Some(unknown_condition(target_condition, reason, message))
+}
+
+/// Returns the first condition of the group according to the given priority,
+/// falling back to the first condition in the group.
+fn first_condition<'g, 'a>(
+    group: &'g ConditionGroup<'a>,
+    priority: Option<&[String]>,
+) -> &'g LocalizedCondition<'a> {
+    priority
+        .unwrap_or_default()
+        .iter()
+        .find_map(|t| group.conditions.iter().find(|c| &c.condition.r#type == t))
+        .unwrap_or(&group.conditions[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::capi_machine::Machine;
+    use crate::conditions::{mark_false, mark_true};
+
+    fn machine(name: &str, conditions: Vec<Condition>) -> Machine {
+        let mut machine: Machine = serde_json::from_value(json!({
+            "metadata": {"name": name},
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+        }))
+        .unwrap();
+        machine.set_conditions(conditions);
+        machine
+    }
+
+    fn info(t: &str, n: u8) -> Condition {
+        false_condition(
+            t,
+            format!("reason falseInfo{n}"),
+            ConditionSeverity::Info,
+            format!("message falseInfo{n}"),
+        )
+    }
+
+    fn ready(condition: Condition) -> Condition {
+        Condition {
+            r#type: READY_CONDITION.to_string(),
+            ..condition
+        }
+    }
+
+    #[test]
+    fn summarizes_conditions() {
+        let foo = true_condition("foo");
+        let bar = info("bar", 1);
+        let baz = info("baz", 2);
+        // an existing Ready condition would have the highest priority
+        let existing_ready = false_condition(
+            READY_CONDITION,
+            "reason falseError1",
+            ConditionSeverity::Error,
+            "message falseError1",
+        );
+        let options = MergeOptions::new;
+        for (conditions, options, expected) in [
+            (vec![], options(), None),
+            (
+                vec![foo.clone()],
+                options(),
+                Some(true_condition(READY_CONDITION)),
+            ),
+            (
+                vec![foo.clone(), bar.clone()],
+                options(),
+                Some(ready(info("", 1))),
+            ),
+            (
+                vec![existing_ready, foo.clone(), bar.clone()],
+                options(),
+                Some(ready(info("", 1))),
+            ),
+            (
+                vec![foo.clone(), bar.clone()],
+                options().with_step_counter(),
+                Some(ready(Condition {
+                    message: Some("1 of 2 completed".to_string()),
+                    ..info("", 1)
+                })),
+            ),
+            (
+                vec![foo.clone(), bar.clone()],
+                options().with_step_counter_if(false),
+                Some(ready(info("", 1))),
+            ),
+            (
+                vec![bar.clone()],
+                options()
+                    .with_step_counter()
+                    .with_step_counter_if_only(["bar"]),
+                Some(ready(Condition {
+                    message: Some("0 of 1 completed".to_string()),
+                    ..info("", 1)
+                })),
+            ),
+            (
+                vec![foo.clone(), bar.clone()],
+                options()
+                    .with_step_counter()
+                    .with_step_counter_if_only(["foo"]),
+                Some(ready(info("", 1))),
+            ),
+            (
+                vec![foo.clone(), bar.clone()],
+                options().with_conditions(["foo"]),
+                Some(true_condition(READY_CONDITION)),
+            ),
+            (
+                vec![foo.clone(), bar.clone(), baz.clone()],
+                options()
+                    .with_conditions(["foo", "bar"])
+                    .with_step_counter(),
+                Some(ready(Condition {
+                    message: Some("1 of 2 completed".to_string()),
+                    ..info("", 1)
+                })),
+            ),
+            // the condition types give the priority within the top group
+            (
+                vec![bar.clone(), baz.clone()],
+                options()
+                    .with_conditions(["baz", "bar"])
+                    .with_step_counter()
+                    .with_step_counter_if_only(["bar", "baz"]),
+                Some(ready(Condition {
+                    message: Some("0 of 2 completed".to_string()),
+                    ..info("", 2)
+                })),
+            ),
+            (
+                vec![bar.clone(), baz.clone()],
+                options()
+                    .with_conditions(["bar", "baz"])
+                    .with_step_counter()
+                    .with_step_counter_if_only(["bar"]),
+                Some(ready(info("", 1))),
+            ),
+        ] {
+            let types: Vec<_> = conditions.iter().map(|c| c.r#type.clone()).collect();
+            assert_eq!(
+                summary(&machine("m", conditions), &options),
+                expected,
+                "{types:?} {options:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn merges_by_status_and_severity() {
+        let warning = |t: &str| false_condition(t, "warning", ConditionSeverity::Warning, "w");
+        let error = |t: &str| false_condition(t, "error", ConditionSeverity::Error, "e");
+        let unknown = |t: &str| unknown_condition(t, "unknown", "u");
+        for (conditions, expected) in [
+            (
+                vec![info("a", 1), warning("b"), error("c"), unknown("d")],
+                ready(error("")),
+            ),
+            (
+                vec![info("a", 1), warning("b"), true_condition("c")],
+                ready(warning("")),
+            ),
+            (
+                vec![info("a", 1), true_condition("b"), unknown("c")],
+                ready(info("", 1)),
+            ),
+            // True takes precedence over Unknown
+            (
+                vec![true_condition("a"), unknown("b")],
+                true_condition(READY_CONDITION),
+            ),
+            (vec![unknown("a"), unknown("b")], ready(unknown(""))),
+            // conditions without a severity are not issues
+            (
+                vec![
+                    false_condition("a", "none", ConditionSeverity::None, "n"),
+                    unknown("b"),
+                ],
+                ready(unknown("")),
+            ),
+        ] {
+            let types: Vec<_> = conditions.iter().map(|c| c.r#type.clone()).collect();
+            assert_eq!(
+                summary(&machine("m", conditions), &MergeOptions::new()),
+                Some(expected),
+                "{types:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn negative_polarity() {
+        let options = MergeOptions::new().with_negative_polarity_conditions(["Deleting"]);
+        let deleting = crate::conditions::true_condition_with_negative_polarity(
+            "Deleting",
+            "Drain",
+            ConditionSeverity::Warning,
+            "draining",
+        );
+        let not_deleting = crate::conditions::false_condition_with_negative_polarity("Deleting");
+        assert_eq!(
+            summary(
+                &machine("m", vec![true_condition("a"), deleting.clone()]),
+                &options
+            ),
+            Some(false_condition(
+                READY_CONDITION,
+                "Drain",
+                ConditionSeverity::Warning,
+                "draining"
+            ))
+        );
+        assert_eq!(
+            summary(
+                &machine("m", vec![true_condition("a"), not_deleting.clone()]),
+                &options.clone().with_step_counter()
+            ),
+            Some(true_condition(READY_CONDITION))
+        );
+        assert_eq!(
+            summary(
+                &machine("m", vec![info("a", 1), not_deleting]),
+                &options.with_step_counter()
+            ),
+            Some(ready(Condition {
+                message: Some("1 of 2 completed".to_string()),
+                ..info("", 1)
+            }))
+        );
+        // without the option, True is positive
+        assert_eq!(
+            summary(&machine("m", vec![deleting]), &MergeOptions::new()),
+            Some(true_condition(READY_CONDITION))
+        );
+    }
+
+    #[test]
+    fn mirrors_ready() {
+        let fallback = MirrorOptions::new().with_fallback_value(
+            false,
+            "NotReported",
+            ConditionSeverity::Warning,
+            "no Ready",
+        );
+        for (conditions, options, expected) in [
+            (
+                vec![true_condition(READY_CONDITION)],
+                MirrorOptions::new(),
+                Some(true_condition("foo")),
+            ),
+            (
+                vec![ready(info("", 1)), info("bar", 2)],
+                fallback.clone(),
+                Some(info("foo", 1)),
+            ),
+            (
+                vec![info("bar", 2)],
+                fallback,
+                Some(false_condition(
+                    "foo",
+                    "NotReported",
+                    ConditionSeverity::Warning,
+                    "no Ready",
+                )),
+            ),
+            (
+                vec![],
+                MirrorOptions::new().with_fallback_value(
+                    true,
+                    "Ignored",
+                    ConditionSeverity::Info,
+                    "",
+                ),
+                Some(true_condition("foo")),
+            ),
+            (vec![info("bar", 2)], MirrorOptions::new(), None),
+        ] {
+            assert_eq!(
+                mirror(&machine("m", conditions), "foo", &options),
+                expected,
+                "{options:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn aggregates_ready_conditions() {
+        let failed = |n: u8| {
+            ready(false_condition(
+                "",
+                format!("Failed{n}"),
+                ConditionSeverity::Error,
+                format!("m{n} failed"),
+            ))
+        };
+        let machines = [
+            machine("m3", vec![failed(3)]),
+            machine("m2", vec![true_condition(READY_CONDITION)]),
+            machine("m1", vec![failed(1)]),
+            machine("m4", vec![]),
+        ];
+        for (options, expected) in [
+            (
+                MergeOptions::new(),
+                false_condition("Agg", "Failed1", ConditionSeverity::Error, "m1 failed"),
+            ),
+            (
+                MergeOptions::new().add_source_ref().with_step_counter(),
+                false_condition(
+                    "Agg",
+                    "Failed1 @ Machine/m1",
+                    ConditionSeverity::Error,
+                    "1 of 4 completed",
+                ),
+            ),
+        ] {
+            assert_eq!(
+                aggregate(&machines, "Agg", &options),
+                Some(expected),
+                "{options:?}"
+            );
+        }
+        // reasons localized already are kept
+        let localized = machine(
+            "m1",
+            vec![ready(false_condition(
+                "",
+                "Failed @ Machine/other",
+                ConditionSeverity::Error,
+                "",
+            ))],
+        );
+        assert_eq!(
+            aggregate([&localized], "Agg", &MergeOptions::new().add_source_ref())
+                .and_then(|c| c.reason),
+            Some("Failed @ Machine/other".to_string())
+        );
+        assert_eq!(
+            aggregate(&machines[1..2], "Agg", &MergeOptions::new()),
+            Some(true_condition("Agg"))
+        );
+        assert_eq!(aggregate(&machines[3..], "Agg", &MergeOptions::new()), None);
+    }
+
+    #[test]
+    fn sets_merged_conditions() {
+        let mut cluster = machine("c", vec![]);
+        mark_true(&mut cluster, "foo");
+        mark_false(
+            &mut cluster,
+            "bar",
+            "Failed",
+            ConditionSeverity::Warning,
+            "failed",
+        );
+        set_summary(&mut cluster, &MergeOptions::new());
+        let source = machine("m", vec![true_condition(READY_CONDITION)]);
+        set_mirror(&mut cluster, "Mirrored", &source, &MirrorOptions::new());
+        set_aggregate(&mut cluster, "Aggregated", [&source], &MergeOptions::new());
+        // nothing to aggregate
+        set_aggregate(
+            &mut cluster,
+            "Empty",
+            std::iter::empty::<&Machine>(),
+            &MergeOptions::new(),
+        );
+        let conditions: Vec<_> = cluster
+            .get_conditions()
+            .iter()
+            .map(|c| (c.r#type.as_str(), c.status.as_str(), c.reason.as_deref()))
+            .collect();
+        assert_eq!(
+            conditions,
+            [
+                ("Ready", "False", Some("Failed")),
+                ("Aggregated", "True", None),
+                ("Mirrored", "True", None),
+                ("bar", "False", Some("Failed")),
+                ("foo", "True", None),
+            ]
+        );
+    }
+}
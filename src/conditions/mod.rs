@@ -0,0 +1,117 @@
+//! Helpers for reading and writing Cluster API conditions, modelled after upstream `util/conditions`.
+//!
+//! The functions exported at this level operate on the v1beta1 `status.conditions` lists, the
+//! [`v1beta2`] module provides the same for the `status.v1beta2.conditions` lists.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{Condition as MetaCondition, Time};
+use k8s_openapi::chrono::{DateTime, SubsecRound, Utc};
+use kube::Resource;
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::api::capi_clusterresourceset::ClusterResourceSet;
+use crate::api::capi_extensionconfig::ExtensionConfig;
+use crate::api::capi_ipaddressclaim::IPAddressClaim;
+use crate::api::capi_machine::Machine;
+use crate::api::capi_machinedeployment::MachineDeployment;
+use crate::api::capi_machinehealthcheck::MachineHealthCheck;
+use crate::api::capi_machinepool::MachinePool;
+use crate::api::capi_machineset::MachineSet;
+use crate::api::condition::Condition;
+use crate::api::well_known;
+
+mod getter;
+mod merge;
+mod setter;
+pub mod v1beta2;
+
+pub use getter::*;
+pub use merge::*;
+pub use setter::*;
+
+/// Ready is the condition type summarizing the operational state of an object.
+pub const READY_CONDITION: &str = well_known::conditions::READY;
+
+pub(crate) const STATUS_TRUE: &str = "True";
+pub(crate) const STATUS_FALSE: &str = "False";
+pub(crate) const STATUS_UNKNOWN: &str = "Unknown";
+
+/// Getter gives access to the conditions of a Cluster API object.
+pub trait Getter: Resource<DynamicType = ()> {
+    /// Returns the v1beta1 `status.conditions`.
+    fn get_conditions(&self) -> &[Condition];
+
+    /// Returns the `status.v1beta2.conditions`.
+    fn get_v1beta2_conditions(&self) -> &[MetaCondition];
+}
+
+/// Setter replaces the conditions of a Cluster API object.
+pub trait Setter: Getter {
+    /// Replaces the v1beta1 `status.conditions`, creating the status if required.
+    fn set_conditions(&mut self, conditions: Vec<Condition>);
+
+    /// Replaces the `status.v1beta2.conditions`, creating the status if required.
+    fn set_v1beta2_conditions(&mut self, conditions: Vec<MetaCondition>);
+}
+
+macro_rules! impl_conditions {
+    ($($kind:ty),* $(,)?) => {$(
+        impl Getter for $kind {
+            fn get_conditions(&self) -> &[Condition] {
+                self.status
+                    .as_ref()
+                    .and_then(|status| status.conditions.as_deref())
+                    .unwrap_or_default()
+            }
+
+            fn get_v1beta2_conditions(&self) -> &[MetaCondition] {
+                self.status
+                    .as_ref()
+                    .and_then(|status| status.v1beta2.as_ref())
+                    .and_then(|v1beta2| v1beta2.conditions.as_deref())
+                    .unwrap_or_default()
+            }
+        }
+
+        impl Setter for $kind {
+            fn set_conditions(&mut self, conditions: Vec<Condition>) {
+                self.status.get_or_insert_with(Default::default).conditions = Some(conditions);
+            }
+
+            fn set_v1beta2_conditions(&mut self, conditions: Vec<MetaCondition>) {
+                self.status
+                    .get_or_insert_with(Default::default)
+                    .v1beta2
+                    .get_or_insert_with(Default::default)
+                    .conditions = Some(conditions);
+            }
+        }
+    )*};
+}
+
+impl_conditions!(
+    Cluster,
+    ClusterClass,
+    ClusterResourceSet,
+    ExtensionConfig,
+    IPAddressClaim,
+    Machine,
+    MachineDeployment,
+    MachineHealthCheck,
+    MachinePool,
+    MachineSet,
+);
+
+/// The current time truncated to seconds, which is the precision the apiserver stores.
+pub(crate) fn now() -> Time {
+    Time(Utc::now().trunc_subsecs(0))
+}
+
+/// The zero value of a lastTransitionTime, replaced with [`now`] when a condition is set.
+pub(crate) fn is_zero(time: &Time) -> bool {
+    time.0 == DateTime::<Utc>::default()
+}
+
+pub(crate) fn zero_time() -> Time {
+    Time(Default::default())
+}
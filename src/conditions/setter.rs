@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+
+use super::getter::{
+    false_condition, false_condition_with_negative_polarity, true_condition,
+    true_condition_with_negative_polarity, unknown_condition,
+};
+use super::{is_zero, now, Setter, READY_CONDITION};
+use crate::api::condition::{Condition, ConditionSeverity};
+
+/// Sets the given condition.
+///
+/// If a condition with the same type already exists, it is replaced only on a state change
+/// (status, severity, reason or message), so the lastTransitionTime is preserved otherwise.
+/// Conditions are kept sorted with Ready first and the others by type.
+pub fn set<T: Setter>(to: &mut T, mut condition: Condition) {
+    let mut conditions = to.get_conditions().to_vec();
+    match conditions.iter_mut().find(|c| c.r#type == condition.r#type) {
+        Some(existing) => {
+            if !has_same_state(existing, &condition) {
+                condition.last_transition_time = now();
+                *existing = condition;
+            }
+        }
+        None => {
+            if is_zero(&condition.last_transition_time) {
+                condition.last_transition_time = now();
+            }
+            conditions.push(condition);
+        }
+    }
+    conditions.sort_by(lexicographic_cmp);
+    to.set_conditions(conditions);
+}
+
+/// Sets Status=True for the condition with the given type.
+pub fn mark_true<T: Setter>(to: &mut T, t: &str) {
+    set(to, true_condition(t));
+}
+
+/// Sets Status=True for a condition with negative polarity and the given type.
+pub fn mark_true_with_negative_polarity<T: Setter>(
+    to: &mut T,
+    t: &str,
+    reason: impl Into<String>,
+    severity: ConditionSeverity,
+    message: impl Into<String>,
+) {
+    set(
+        to,
+        true_condition_with_negative_polarity(t, reason, severity, message),
+    );
+}
+
+/// Sets Status=Unknown for the condition with the given type.
+pub fn mark_unknown<T: Setter>(
+    to: &mut T,
+    t: &str,
+    reason: impl Into<String>,
+    message: impl Into<String>,
+) {
+    set(to, unknown_condition(t, reason, message));
+}
+
+/// Sets Status=False for the condition with the given type.
+pub fn mark_false<T: Setter>(
+    to: &mut T,
+    t: &str,
+    reason: impl Into<String>,
+    severity: ConditionSeverity,
+    message: impl Into<String>,
+) {
+    set(to, false_condition(t, reason, severity, message));
+}
+
+/// Sets Status=False for a condition with negative polarity and the given type.
+pub fn mark_false_with_negative_polarity<T: Setter>(to: &mut T, t: &str) {
+    set(to, false_condition_with_negative_polarity(t));
+}
+
+/// Deletes the condition with the given type.
+pub fn delete<T: Setter>(to: &mut T, t: &str) {
+    let conditions = to
+        .get_conditions()
+        .iter()
+        .filter(|c| c.r#type != t)
+        .cloned()
+        .collect();
+    to.set_conditions(conditions);
+}
+
+/// Orders conditions with Ready first and all the others by type.
+pub(crate) fn lexicographic_cmp(a: &Condition, b: &Condition) -> Ordering {
+    (b.r#type == READY_CONDITION)
+        .cmp(&(a.r#type == READY_CONDITION))
+        .then_with(|| a.r#type.cmp(&b.r#type))
+}
+
+fn has_same_state(a: &Condition, b: &Condition) -> bool {
+    a.r#type == b.r#type
+        && a.status == b.status
+        && a.reason == b.reason
+        && a.severity == b.severity
+        && a.message == b.message
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::capi_machine::Machine;
+    use crate::conditions::{get, zero_time, Getter};
+
+    fn machine() -> Machine {
+        serde_json::from_value(json!({
+            "metadata": {"name": "m"},
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+        }))
+        .unwrap()
+    }
+
+    fn time(s: &str) -> Time {
+        Time(s.parse().unwrap())
+    }
+
+    #[test]
+    fn keeps_the_last_transition_time_without_a_state_change() {
+        let then = time("2024-01-01T00:00:00Z");
+        let mut machine = machine();
+        set(
+            &mut machine,
+            Condition {
+                last_transition_time: then.clone(),
+                ..false_condition("foo", "Reason", ConditionSeverity::Info, "message")
+            },
+        );
+        assert_eq!(get(&machine, "foo").unwrap().last_transition_time, then);
+
+        mark_false(
+            &mut machine,
+            "foo",
+            "Reason",
+            ConditionSeverity::Info,
+            "message",
+        );
+        assert_eq!(get(&machine, "foo").unwrap().last_transition_time, then);
+
+        let existing = machine.get_conditions().to_vec();
+        for changed in [
+            false_condition("foo", "Other", ConditionSeverity::Info, "message"),
+            false_condition("foo", "Reason", ConditionSeverity::Warning, "message"),
+            false_condition("foo", "Reason", ConditionSeverity::Info, "other"),
+            true_condition("foo"),
+        ] {
+            machine.set_conditions(existing.clone());
+            // the given lastTransitionTime is replaced on a state change
+            set(
+                &mut machine,
+                Condition {
+                    last_transition_time: then.clone(),
+                    ..changed.clone()
+                },
+            );
+            let current = get(&machine, "foo").unwrap();
+            assert_ne!(current.last_transition_time, then, "{changed:?}");
+            assert_eq!(
+                Condition {
+                    last_transition_time: zero_time(),
+                    ..current.clone()
+                },
+                changed
+            );
+        }
+    }
+
+    #[test]
+    fn sorts_and_deletes_conditions() {
+        let mut machine = machine();
+        mark_true(&mut machine, "b");
+        mark_unknown(&mut machine, "A", "Reason", "message");
+        mark_true(&mut machine, READY_CONDITION);
+        mark_false_with_negative_polarity(&mut machine, "a");
+        mark_true_with_negative_polarity(
+            &mut machine,
+            "c",
+            "Reason",
+            ConditionSeverity::Error,
+            "message",
+        );
+        let types = |machine: &Machine| -> Vec<String> {
+            machine
+                .get_conditions()
+                .iter()
+                .map(|c| c.r#type.clone())
+                .collect()
+        };
+        assert_eq!(types(&machine), ["Ready", "A", "a", "b", "c"]);
+        delete(&mut machine, "a");
+        delete(&mut machine, "missing");
+        assert_eq!(types(&machine), ["Ready", "A", "b", "c"]);
+    }
+}
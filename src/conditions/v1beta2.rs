@@ -0,0 +1,914 @@
+//! Helpers for the `status.v1beta2.conditions` lists, modelled after upstream `util/conditions/v1beta2`.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+
+use crate::api::well_known::conditions::v1beta2;
+
+use super::{is_zero, now, Getter, Setter, STATUS_FALSE, STATUS_TRUE, STATUS_UNKNOWN};
+
+pub use crate::api::well_known::conditions::v1beta2::{
+    MULTIPLE_INFO_REPORTED_REASON, MULTIPLE_ISSUES_REPORTED_REASON,
+    MULTIPLE_UNKNOWN_REPORTED_REASON, NOT_YET_REPORTED_REASON,
+};
+
+/// Available is the condition type reporting whether an object is available.
+pub const AVAILABLE_CONDITION: &str = v1beta2::AVAILABLE;
+/// Ready is the condition type reporting whether an object is ready.
+pub const READY_CONDITION: &str = v1beta2::READY;
+/// Paused is the condition type reporting whether reconciliation of an object is paused.
+pub const PAUSED_CONDITION: &str = v1beta2::PAUSED;
+/// Deleting is the condition type reporting whether an object is being deleted.
+pub const DELETING_CONDITION: &str = v1beta2::DELETING;
+
+const FIRST_CONDITIONS: [&str; 2] = [AVAILABLE_CONDITION, READY_CONDITION];
+const LAST_CONDITIONS: [&str; 2] = [PAUSED_CONDITION, DELETING_CONDITION];
+const MAX_OBJECTS_PER_MESSAGE: usize = 3;
+const MAX_MESSAGES: usize = 3;
+
+/// Returns the condition with the given type, if it exists.
+pub fn get<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a Condition> {
+    from.get_v1beta2_conditions().iter().find(|c| c.type_ == t)
+}
+
+/// Returns true if a condition with the given type exists.
+pub fn has<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some()
+}
+
+/// Returns true if the condition with the given type is True, false otherwise.
+pub fn is_true<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some_and(|c| c.status == STATUS_TRUE)
+}
+
+/// Returns true if the condition with the given type is False, false otherwise.
+pub fn is_false<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_some_and(|c| c.status == STATUS_FALSE)
+}
+
+/// Returns true if the condition with the given type is Unknown or if the condition does not exist.
+pub fn is_unknown<T: Getter>(from: &T, t: &str) -> bool {
+    get(from, t).is_none_or(|c| c.status == STATUS_UNKNOWN)
+}
+
+/// Returns the reason of the condition with the given type, if it exists.
+pub fn get_reason<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a str> {
+    get(from, t).map(|c| c.reason.as_str())
+}
+
+/// Returns the message of the condition with the given type, if it exists.
+pub fn get_message<'a, T: Getter>(from: &'a T, t: &str) -> Option<&'a str> {
+    get(from, t).map(|c| c.message.as_str())
+}
+
+/// Sets the given condition, stamping it with the generation of the target object.
+///
+/// Follows `meta.SetStatusCondition`: the lastTransitionTime changes only when the status
+/// changes, a default (zero) lastTransitionTime is replaced with the current time.
+/// Conditions are sorted with Available and Ready first, Paused and Deleting last and the
+/// others by type.
+pub fn set<T: Setter>(to: &mut T, mut condition: Condition) {
+    condition.observed_generation = to.meta().generation;
+    let mut conditions = to.get_v1beta2_conditions().to_vec();
+    match conditions.iter_mut().find(|c| c.type_ == condition.type_) {
+        Some(existing) => {
+            if existing.status != condition.status {
+                existing.status = condition.status;
+                existing.last_transition_time = if is_zero(&condition.last_transition_time) {
+                    now()
+                } else {
+                    condition.last_transition_time
+                };
+            }
+            existing.reason = condition.reason;
+            existing.message = condition.message;
+            existing.observed_generation = condition.observed_generation;
+        }
+        None => {
+            if is_zero(&condition.last_transition_time) {
+                condition.last_transition_time = now();
+            }
+            conditions.push(condition);
+        }
+    }
+    conditions.sort_by(default_sort_cmp);
+    to.set_v1beta2_conditions(conditions);
+}
+
+/// Deletes the condition with the given type.
+pub fn delete<T: Setter>(to: &mut T, t: &str) {
+    let conditions = to
+        .get_v1beta2_conditions()
+        .iter()
+        .filter(|c| c.type_ != t)
+        .cloned()
+        .collect();
+    to.set_v1beta2_conditions(conditions);
+}
+
+fn default_sort_cmp(a: &Condition, b: &Condition) -> Ordering {
+    let position = |list: &[&str], c: &Condition| list.iter().position(|t| *t == c.type_);
+    let first = |c| position(&FIRST_CONDITIONS, c).unwrap_or(usize::MAX);
+    let last = |c| position(&LAST_CONDITIONS, c).map_or(0, |p| p + 1);
+    first(a)
+        .cmp(&first(b))
+        .then_with(|| last(a).cmp(&last(b)))
+        .then_with(|| a.type_.cmp(&b.type_))
+}
+
+/// How a condition contributes to a merged condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MergePriority {
+    /// The condition reports an issue: False for positive polarity, True for negative polarity.
+    Issue,
+    /// The condition status is Unknown.
+    Unknown,
+    /// The condition reports the normal state: True for positive polarity, False for negative polarity.
+    Info,
+}
+
+/// Returns the merge priority of a condition, considering the polarity of its type.
+pub fn merge_priority(
+    condition: &Condition,
+    negative_polarity_condition_types: &[String],
+) -> MergePriority {
+    let negative = negative_polarity_condition_types.contains(&condition.type_);
+    match condition.status.as_str() {
+        STATUS_TRUE if negative => MergePriority::Issue,
+        STATUS_TRUE => MergePriority::Info,
+        STATUS_FALSE if negative => MergePriority::Info,
+        STATUS_FALSE => MergePriority::Issue,
+        // Unknown, or an invalid status which is considered like Unknown.
+        _ => MergePriority::Unknown,
+    }
+}
+
+/// Options for [`new_summary_condition`].
+#[derive(Clone, Debug, Default)]
+pub struct SummaryOptions {
+    negative_polarity_condition_types: Vec<String>,
+    ignore_types_if_missing: Vec<String>,
+    target_negative_polarity: bool,
+}
+
+impl SummaryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Considers Status=True as an issue and Status=False as info for the given condition types.
+    pub fn negative_polarity_condition_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.negative_polarity_condition_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Skips the given condition types when missing, instead of reporting them as not yet reported.
+    pub fn ignore_types_if_missing<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_types_if_missing = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the polarity of the resulting condition; with negative polarity, issues yield Status=True.
+    pub fn target_condition_has_positive_polarity(mut self, positive: bool) -> Self {
+        self.target_negative_polarity = !positive;
+        self
+    }
+}
+
+/// Options for [`new_aggregate_condition`].
+#[derive(Clone, Debug, Default)]
+pub struct AggregateOptions {
+    target_condition_type: Option<String>,
+    negative_polarity_condition_types: Vec<String>,
+    target_negative_polarity: bool,
+}
+
+impl AggregateOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the type of the resulting condition, which defaults to the source condition type.
+    pub fn target_condition_type(mut self, t: impl Into<String>) -> Self {
+        self.target_condition_type = Some(t.into());
+        self
+    }
+
+    /// Considers Status=True as an issue and Status=False as info for the given condition types.
+    pub fn negative_polarity_condition_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.negative_polarity_condition_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the polarity of the resulting condition; with negative polarity, issues yield Status=True.
+    pub fn target_condition_has_positive_polarity(mut self, positive: bool) -> Self {
+        self.target_negative_polarity = !positive;
+        self
+    }
+}
+
+/// Options for [`new_mirror_condition`].
+#[derive(Clone, Debug, Default)]
+pub struct MirrorOptions {
+    target_condition_type: Option<String>,
+    fallback_condition: Option<Condition>,
+}
+
+impl MirrorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the type of the resulting condition, which defaults to the source condition type.
+    pub fn target_condition_type(mut self, t: impl Into<String>) -> Self {
+        self.target_condition_type = Some(t.into());
+        self
+    }
+
+    /// Uses the given condition when the source condition does not exist.
+    pub fn fallback_condition(mut self, condition: Condition) -> Self {
+        self.fallback_condition = Some(condition);
+        self
+    }
+}
+
+/// Returns a condition of the target type summarizing the given condition types of `source`.
+///
+/// Missing conditions are considered Unknown with reason NotYetReported, unless listed in
+/// [`SummaryOptions::ignore_types_if_missing`]. Returns `None` if there is nothing to summarize.
+pub fn new_summary_condition<T, I, S>(
+    source: &T,
+    target_condition_type: &str,
+    for_condition_types: I,
+    options: &SummaryOptions,
+) -> Option<Condition>
+where
+    T: Getter,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let conditions: Vec<Condition> = for_condition_types
+        .into_iter()
+        .filter_map(|t| {
+            let t = t.as_ref();
+            match get(source, t) {
+                Some(condition) => Some(condition.clone()),
+                None if options.ignore_types_if_missing.iter().any(|i| i == t) => None,
+                None => Some(not_yet_reported(t)),
+            }
+        })
+        .collect();
+
+    let mut entries: Vec<_> = conditions
+        .iter()
+        .map(|c| {
+            (
+                merge_priority(c, &options.negative_polarity_condition_types),
+                c,
+            )
+        })
+        .collect();
+    entries.sort_by_key(|(priority, _)| *priority);
+
+    let (status, reason) = merged_status_and_reason(
+        entries.iter().map(|(p, c)| (*p, c.reason.as_str())),
+        options.target_negative_polarity,
+    )?;
+    let message = entries
+        .iter()
+        .filter(|(_, c)| !c.message.is_empty())
+        .map(|(_, c)| bullet(&c.type_, &c.message))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(new_condition(
+        target_condition_type,
+        status,
+        reason,
+        message,
+    ))
+}
+
+/// Returns a condition merging the condition of the given type across all the source objects.
+///
+/// Objects missing the condition are considered Unknown with reason NotYetReported.
+/// Returns `None` if there are no source objects.
+pub fn new_aggregate_condition<'a, T, I>(
+    sources: I,
+    source_condition_type: &str,
+    options: &AggregateOptions,
+) -> Option<Condition>
+where
+    T: Getter + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let kind = T::kind(&()).into_owned();
+    let mut entries: Vec<(MergePriority, Condition, String)> = sources
+        .into_iter()
+        .map(|source| {
+            let condition = get(source, source_condition_type)
+                .cloned()
+                .unwrap_or_else(|| not_yet_reported(source_condition_type));
+            let priority = merge_priority(&condition, &options.negative_polarity_condition_types);
+            (
+                priority,
+                condition,
+                source.meta().name.clone().unwrap_or_default(),
+            )
+        })
+        .collect();
+    entries.sort_by(|(pa, _, na), (pb, _, nb)| pa.cmp(pb).then_with(|| na.cmp(nb)));
+
+    let (status, reason) = merged_status_and_reason(
+        entries.iter().map(|(p, c, _)| (*p, c.reason.as_str())),
+        options.target_negative_polarity,
+    )?;
+
+    // Only the objects in the top priority group contribute to the message, grouped by message.
+    let top = entries[0].0;
+    let mut by_message: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (_, condition, name) in entries
+        .iter()
+        .filter(|(p, c, _)| *p == top && !c.message.is_empty())
+    {
+        by_message.entry(&condition.message).or_default().push(name);
+    }
+    let mut groups: Vec<_> = by_message.into_iter().collect();
+    groups.sort_by(|(ma, na), (mb, nb)| nb.len().cmp(&na.len()).then_with(|| ma.cmp(mb)));
+
+    let mut lines: Vec<String> = groups
+        .iter()
+        .take(MAX_MESSAGES)
+        .map(|(message, names)| bullet(&objects_label(&kind, names), message))
+        .collect();
+    let others: usize = groups
+        .iter()
+        .skip(MAX_MESSAGES)
+        .map(|(_, names)| names.len())
+        .sum();
+    if others > 0 {
+        let what = match top {
+            MergePriority::Issue => "issues",
+            MergePriority::Unknown => "unknown status",
+            MergePriority::Info => "info messages",
+        };
+        lines.push(format!(
+            "And {} {} with other {}",
+            others,
+            pluralize(&kind, others),
+            what
+        ));
+    }
+
+    let target = options
+        .target_condition_type
+        .as_deref()
+        .unwrap_or(source_condition_type);
+    Some(new_condition(target, status, reason, lines.join("\n")))
+}
+
+/// Returns a copy of the condition of the given type from `source`, with the target type.
+///
+/// If the source condition does not exist the fallback condition is used, or an Unknown
+/// condition with reason NotYetReported.
+pub fn new_mirror_condition<T: Getter>(
+    source: &T,
+    source_condition_type: &str,
+    options: &MirrorOptions,
+) -> Condition {
+    let target = options
+        .target_condition_type
+        .as_deref()
+        .unwrap_or(source_condition_type);
+    match (
+        get(source, source_condition_type),
+        &options.fallback_condition,
+    ) {
+        (Some(condition), _) => new_condition(
+            target,
+            &condition.status,
+            condition.reason.clone(),
+            condition.message.clone(),
+        ),
+        (None, Some(fallback)) => Condition {
+            type_: target.to_string(),
+            ..fallback.clone()
+        },
+        (None, None) => new_condition(
+            target,
+            STATUS_UNKNOWN,
+            NOT_YET_REPORTED_REASON.to_string(),
+            format!(
+                "Condition {} not yet reported from {}",
+                source_condition_type,
+                T::kind(&())
+            ),
+        ),
+    }
+}
+
+/// Sets a condition of the target type summarizing the given condition types of `to`.
+pub fn set_summary_condition<T, I, S>(
+    to: &mut T,
+    target_condition_type: &str,
+    for_condition_types: I,
+    options: &SummaryOptions,
+) where
+    T: Setter,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    if let Some(condition) =
+        new_summary_condition(to, target_condition_type, for_condition_types, options)
+    {
+        set(to, condition);
+    }
+}
+
+/// Sets a condition on `to` merging the condition of the given type across all the source objects.
+pub fn set_aggregate_condition<'a, T, F, I>(
+    to: &mut T,
+    sources: I,
+    source_condition_type: &str,
+    options: &AggregateOptions,
+) where
+    T: Setter,
+    F: Getter + 'a,
+    I: IntoIterator<Item = &'a F>,
+{
+    if let Some(condition) = new_aggregate_condition(sources, source_condition_type, options) {
+        set(to, condition);
+    }
+}
+
+/// Sets a condition on `to` mirroring the condition of the given type from `source`.
+pub fn set_mirror_condition<T: Setter, F: Getter>(
+    to: &mut T,
+    source: &F,
+    source_condition_type: &str,
+    options: &MirrorOptions,
+) {
+    set(
+        to,
+        new_mirror_condition(source, source_condition_type, options),
+    );
+}
+
+/// Computes status and reason of a merged condition from (priority, reason) pairs sorted by priority.
+///
+/// A single condition in the top priority group lends its reason, more than one
+/// yields the generic reason for that group.
+fn merged_status_and_reason<'a>(
+    entries: impl Iterator<Item = (MergePriority, &'a str)>,
+    target_negative_polarity: bool,
+) -> Option<(&'static str, String)> {
+    let mut entries = entries.peekable();
+    let (top, first_reason) = *entries.peek()?;
+    let count = entries.take_while(|(p, _)| *p == top).count();
+    let (status, multiple_reason) = match top {
+        MergePriority::Issue if target_negative_polarity => {
+            (STATUS_TRUE, MULTIPLE_ISSUES_REPORTED_REASON)
+        }
+        MergePriority::Issue => (STATUS_FALSE, MULTIPLE_ISSUES_REPORTED_REASON),
+        MergePriority::Unknown => (STATUS_UNKNOWN, MULTIPLE_UNKNOWN_REPORTED_REASON),
+        MergePriority::Info if target_negative_polarity => {
+            (STATUS_FALSE, MULTIPLE_INFO_REPORTED_REASON)
+        }
+        MergePriority::Info => (STATUS_TRUE, MULTIPLE_INFO_REPORTED_REASON),
+    };
+    let reason = if count == 1 {
+        first_reason
+    } else {
+        multiple_reason
+    };
+    Some((status, reason.to_string()))
+}
+
+fn not_yet_reported(t: &str) -> Condition {
+    new_condition(
+        t,
+        STATUS_UNKNOWN,
+        NOT_YET_REPORTED_REASON.to_string(),
+        format!("Condition {t} not yet reported"),
+    )
+}
+
+fn new_condition(t: &str, status: &str, reason: String, message: String) -> Condition {
+    Condition {
+        last_transition_time: super::zero_time(),
+        message,
+        observed_generation: None,
+        reason,
+        status: status.to_string(),
+        type_: t.to_string(),
+    }
+}
+
+/// Formats a message as a bullet, indenting continuation lines.
+fn bullet(label: &str, message: &str) -> String {
+    format!("* {}: {}", label, message.replace('\n', "\n  "))
+}
+
+fn objects_label(kind: &str, names: &[&str]) -> String {
+    let mut label = format!(
+        "{} {}",
+        pluralize(kind, names.len()),
+        names
+            .iter()
+            .take(MAX_OBJECTS_PER_MESSAGE)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if names.len() > MAX_OBJECTS_PER_MESSAGE {
+        label.push_str(&format!(
+            ", ... ({} more)",
+            names.len() - MAX_OBJECTS_PER_MESSAGE
+        ));
+    }
+    label
+}
+
+fn pluralize(kind: &str, count: usize) -> String {
+    if count == 1 {
+        kind.to_string()
+    } else {
+        format!("{kind}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use serde_json::json;
+
+    use super::*;
+    use crate::api::capi_machine::Machine;
+    use crate::api::capi_machinedeployment::MachineDeployment;
+
+    fn machine(name: &str, conditions: Vec<Condition>) -> Machine {
+        let mut machine: Machine = serde_json::from_value(json!({
+            "metadata": {"name": name, "generation": 3},
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+        }))
+        .unwrap();
+        machine.set_v1beta2_conditions(conditions);
+        machine
+    }
+
+    fn condition(t: &str, status: &str, reason: &str, message: &str) -> Condition {
+        new_condition(t, status, reason.to_string(), message.to_string())
+    }
+
+    fn time(s: &str) -> Time {
+        Time(s.parse().unwrap())
+    }
+
+    #[test]
+    fn sets_conditions() {
+        let then = time("2024-01-01T00:00:00Z");
+        let later = time("2024-01-02T00:00:00Z");
+        let mut machine = machine("m", vec![]);
+        set(
+            &mut machine,
+            Condition {
+                last_transition_time: then.clone(),
+                ..condition("Foo", STATUS_FALSE, "Reason", "message")
+            },
+        );
+        // the lastTransitionTime only changes with the status
+        set(
+            &mut machine,
+            condition("Foo", STATUS_FALSE, "Other", "other message"),
+        );
+        assert_eq!(
+            get(&machine, "Foo"),
+            Some(&Condition {
+                last_transition_time: then.clone(),
+                observed_generation: Some(3),
+                ..condition("Foo", STATUS_FALSE, "Other", "other message")
+            })
+        );
+        set(
+            &mut machine,
+            Condition {
+                last_transition_time: later.clone(),
+                ..condition("Foo", STATUS_TRUE, "Done", "")
+            },
+        );
+        assert_eq!(get(&machine, "Foo").unwrap().last_transition_time, later);
+        set(&mut machine, condition("Foo", STATUS_UNKNOWN, "Done", ""));
+        let foo = get(&machine, "Foo").unwrap();
+        assert_ne!(foo.last_transition_time, later);
+        assert!(!is_zero(&foo.last_transition_time));
+        assert!(is_unknown(&machine, "Foo"));
+        assert!(is_unknown(&machine, "Missing"));
+        assert_eq!(get_reason(&machine, "Foo"), Some("Done"));
+        assert_eq!(get_message(&machine, "Missing"), None);
+
+        for t in [
+            DELETING_CONDITION,
+            "Bar",
+            READY_CONDITION,
+            PAUSED_CONDITION,
+            AVAILABLE_CONDITION,
+        ] {
+            set(&mut machine, condition(t, STATUS_TRUE, "Reason", ""));
+        }
+        delete(&mut machine, "Bar");
+        let types: Vec<_> = machine
+            .get_v1beta2_conditions()
+            .iter()
+            .map(|c| c.type_.as_str())
+            .collect();
+        assert_eq!(types, ["Available", "Ready", "Foo", "Paused", "Deleting"]);
+    }
+
+    #[test]
+    fn merge_priorities() {
+        let negative = ["Deleting".to_string()];
+        for (t, status, expected) in [
+            ("Ready", STATUS_TRUE, MergePriority::Info),
+            ("Ready", STATUS_FALSE, MergePriority::Issue),
+            ("Ready", STATUS_UNKNOWN, MergePriority::Unknown),
+            ("Ready", "Invalid", MergePriority::Unknown),
+            ("Deleting", STATUS_TRUE, MergePriority::Issue),
+            ("Deleting", STATUS_FALSE, MergePriority::Info),
+            ("Deleting", STATUS_UNKNOWN, MergePriority::Unknown),
+        ] {
+            assert_eq!(
+                merge_priority(&condition(t, status, "", ""), &negative),
+                expected,
+                "{t} {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn summarizes_conditions() {
+        let machine = machine(
+            "m",
+            vec![
+                condition("A", STATUS_TRUE, "AReason", "a is fine"),
+                condition("B", STATUS_FALSE, "BReason", "b failed\nbecause of x"),
+                condition("C", STATUS_UNKNOWN, "CReason", "c is unknown"),
+                condition("D", STATUS_FALSE, "DReason", ""),
+                condition("Deleting", STATUS_TRUE, "Deleting", "being deleted"),
+            ],
+        );
+        let options = SummaryOptions::new;
+        for (types, options, expected) in [
+            (
+                vec!["A"],
+                options(),
+                Some(condition("Ready", STATUS_TRUE, "AReason", "* A: a is fine")),
+            ),
+            (
+                vec!["A", "C", "B"],
+                options(),
+                Some(condition(
+                    "Ready",
+                    STATUS_FALSE,
+                    "BReason",
+                    "* B: b failed\n  because of x\n* C: c is unknown\n* A: a is fine",
+                )),
+            ),
+            (
+                vec!["A", "B", "D"],
+                options(),
+                Some(condition(
+                    "Ready",
+                    STATUS_FALSE,
+                    MULTIPLE_ISSUES_REPORTED_REASON,
+                    "* B: b failed\n  because of x\n* A: a is fine",
+                )),
+            ),
+            (
+                vec!["A", "C", "Missing"],
+                options(),
+                Some(condition(
+                    "Ready",
+                    STATUS_UNKNOWN,
+                    MULTIPLE_UNKNOWN_REPORTED_REASON,
+                    "* C: c is unknown\n* Missing: Condition Missing not yet reported\n* A: a is fine",
+                )),
+            ),
+            (
+                vec!["A", "Missing"],
+                options().ignore_types_if_missing(["Missing"]),
+                Some(condition("Ready", STATUS_TRUE, "AReason", "* A: a is fine")),
+            ),
+            (vec!["Missing"], options().ignore_types_if_missing(["Missing"]), None),
+            (
+                vec!["A", "Deleting"],
+                options().negative_polarity_condition_types(["Deleting"]),
+                Some(condition(
+                    "Ready",
+                    STATUS_FALSE,
+                    "Deleting",
+                    "* Deleting: being deleted\n* A: a is fine",
+                )),
+            ),
+            (
+                vec!["A", "Deleting"],
+                options(),
+                Some(condition(
+                    "Ready",
+                    STATUS_TRUE,
+                    MULTIPLE_INFO_REPORTED_REASON,
+                    "* A: a is fine\n* Deleting: being deleted",
+                )),
+            ),
+            // with negative polarity, issues are True
+            (
+                vec!["B"],
+                options().target_condition_has_positive_polarity(false),
+                Some(condition(
+                    "Ready",
+                    STATUS_TRUE,
+                    "BReason",
+                    "* B: b failed\n  because of x",
+                )),
+            ),
+            (
+                vec!["A"],
+                options().target_condition_has_positive_polarity(false),
+                Some(condition("Ready", STATUS_FALSE, "AReason", "* A: a is fine")),
+            ),
+        ] {
+            assert_eq!(
+                new_summary_condition(&machine, "Ready", &types, &options),
+                expected,
+                "{types:?} {options:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn aggregates_conditions() {
+        let ready = |name: &str, status: &str, reason: &str, message: &str| {
+            machine(name, vec![condition("Ready", status, reason, message)])
+        };
+        let machines = [
+            ready("m5", STATUS_FALSE, "Drain", "draining"),
+            ready("m1", STATUS_FALSE, "Drain", "draining"),
+            ready("m2", STATUS_FALSE, "NodeDown", "node down"),
+            ready("m3", STATUS_TRUE, "Ready", ""),
+            ready("m4", STATUS_FALSE, "Drain", "draining"),
+            ready("m6", STATUS_FALSE, "Drain", "draining"),
+            ready("m7", STATUS_FALSE, "Stuck", "stuck"),
+            ready("m8", STATUS_FALSE, "Other", "other"),
+            ready("m9", STATUS_FALSE, "Other", "something else"),
+        ];
+        assert_eq!(
+            new_aggregate_condition(&machines, "Ready", &AggregateOptions::new()),
+            Some(condition(
+                "Ready",
+                STATUS_FALSE,
+                MULTIPLE_ISSUES_REPORTED_REASON,
+                "* Machines m1, m4, m5, ... (1 more): draining\n\
+                 * Machine m2: node down\n\
+                 * Machine m8: other\n\
+                 And 2 Machines with other issues",
+            ))
+        );
+        assert_eq!(
+            new_aggregate_condition(
+                &machines[1..4],
+                "Ready",
+                &AggregateOptions::new().target_condition_type("MachinesReady")
+            ),
+            Some(condition(
+                "MachinesReady",
+                STATUS_FALSE,
+                MULTIPLE_ISSUES_REPORTED_REASON,
+                "* Machine m1: draining\n* Machine m2: node down",
+            ))
+        );
+        assert_eq!(
+            new_aggregate_condition(
+                [&machines[0], &machines[3], &machine("m0", vec![])],
+                "Ready",
+                &AggregateOptions::new()
+            ),
+            Some(condition(
+                "Ready",
+                STATUS_FALSE,
+                "Drain",
+                "* Machine m5: draining"
+            ))
+        );
+        assert_eq!(
+            new_aggregate_condition(
+                [&machines[3], &machine("m0", vec![])],
+                "Ready",
+                &AggregateOptions::new()
+            ),
+            Some(condition(
+                "Ready",
+                STATUS_UNKNOWN,
+                NOT_YET_REPORTED_REASON,
+                "* Machine m0: Condition Ready not yet reported"
+            ))
+        );
+        let deleting = [
+            machine(
+                "a",
+                vec![condition("Deleting", STATUS_FALSE, "NotDeleting", "")],
+            ),
+            machine(
+                "b",
+                vec![condition("Deleting", STATUS_FALSE, "NotDeleting", "")],
+            ),
+        ];
+        assert_eq!(
+            new_aggregate_condition(
+                &deleting,
+                "Deleting",
+                &AggregateOptions::new()
+                    .negative_polarity_condition_types(["Deleting"])
+                    .target_condition_has_positive_polarity(false)
+            ),
+            Some(condition(
+                "Deleting",
+                STATUS_FALSE,
+                MULTIPLE_INFO_REPORTED_REASON,
+                ""
+            ))
+        );
+        assert_eq!(
+            new_aggregate_condition(
+                std::iter::empty::<&MachineDeployment>(),
+                "Ready",
+                &AggregateOptions::new()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn mirrors_conditions() {
+        let source = machine(
+            "m",
+            vec![Condition {
+                last_transition_time: time("2024-01-01T00:00:00Z"),
+                observed_generation: Some(3),
+                ..condition("Ready", STATUS_FALSE, "Reason", "message")
+            }],
+        );
+        assert_eq!(
+            new_mirror_condition(&source, "Ready", &MirrorOptions::new()),
+            condition("Ready", STATUS_FALSE, "Reason", "message")
+        );
+        assert_eq!(
+            new_mirror_condition(
+                &source,
+                "Ready",
+                &MirrorOptions::new().target_condition_type("MachineReady")
+            ),
+            condition("MachineReady", STATUS_FALSE, "Reason", "message")
+        );
+        assert_eq!(
+            new_mirror_condition(&source, "Available", &MirrorOptions::new()),
+            condition(
+                "Available",
+                STATUS_UNKNOWN,
+                NOT_YET_REPORTED_REASON,
+                "Condition Available not yet reported from Machine"
+            )
+        );
+        assert_eq!(
+            new_mirror_condition(
+                &source,
+                "Available",
+                &MirrorOptions::new()
+                    .target_condition_type("MachineAvailable")
+                    .fallback_condition(condition("Fallback", STATUS_TRUE, "Fallback", ""))
+            ),
+            condition("MachineAvailable", STATUS_TRUE, "Fallback", "")
+        );
+
+        let mut target = machine("t", vec![]);
+        set_mirror_condition(&mut target, &source, "Ready", &MirrorOptions::new());
+        set_summary_condition(&mut target, "Available", ["Ready"], &SummaryOptions::new());
+        set_aggregate_condition(&mut target, [&source], "Ready", &AggregateOptions::new());
+        assert_eq!(get_reason(&target, "Available"), Some("Reason"));
+        assert_eq!(get_message(&target, "Ready"), Some("* Machine m: message"));
+        assert!(is_false(&target, "Ready"));
+        assert!(!has(&target, "Paused"));
+    }
+}
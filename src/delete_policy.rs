@@ -0,0 +1,490 @@
+//! Selection of the Machines a MachineSet deletes when it is scaled down, following the delete
+//! policies of the upstream MachineSet controller (`internal/controllers/machineset`).
+//!
+//! Every Machine gets a delete priority between 0 and 100, and the Machines with the highest
+//! priority are deleted first. Machines being deleted come first, then the ones with the
+//! `cluster.x-k8s.io/delete-machine` annotation, then unhealthy ones. The policy only orders
+//! the healthy Machines: `Oldest` and `Newest` by age, `Random` not at all. Machines with the
+//! same priority are ordered by name.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use k8s_openapi::chrono::{DateTime, Utc};
+
+use crate::api::capi_machine::Machine;
+use crate::api::capi_machinedeployment::MachineDeploymentStrategyRollingUpdateDeletePolicy;
+use crate::api::capi_machineset::{MachineSet, MachineSetDeletePolicy};
+use crate::api::well_known::{annotations, conditions as condition_types};
+use crate::conditions;
+
+/// Priority of Machines that are being deleted.
+pub const MUST_DELETE: f64 = 100.0;
+/// Priority of Machines with the delete-machine annotation.
+pub const SHOULD_DELETE: f64 = 75.0;
+/// Priority of unhealthy Machines, and the upper bound of healthy ones.
+pub const BETTER_DELETE: f64 = 50.0;
+/// Priority of healthy Machines with the Random policy.
+pub const COULD_DELETE: f64 = 20.0;
+/// Priority of healthy Machines without a creation timestamp with the Oldest policy.
+pub const MUST_NOT_DELETE: f64 = 0.0;
+
+/// The age at which the priority of a healthy Machine is about 63% of [`BETTER_DELETE`] with
+/// the Oldest policy.
+const SECONDS_PER_TEN_DAYS: f64 = 864000.0;
+
+/// A delete policy of a MachineSet, or of the rolling updates of a MachineDeployment, which
+/// its MachineSets use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DeletePolicy {
+    #[default]
+    Random,
+    Newest,
+    Oldest,
+}
+
+/// Error for a delete policy that is not supported.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Unsupported delete policy {0}. Must be one of 'Random', 'Newest', or 'Oldest'")]
+pub struct DeletePolicyError(pub String);
+
+impl DeletePolicy {
+    /// The delete policy of a MachineSet, `Random` if it has none.
+    pub fn of_machine_set(machine_set: &MachineSet) -> Result<Self, DeletePolicyError> {
+        machine_set
+            .spec
+            .delete_policy
+            .as_ref()
+            .map_or(Ok(Self::Random), Self::try_from)
+    }
+
+    /// The delete priority of a Machine at `now`, and why it has it.
+    pub fn priority(self, machine: &Machine, now: DateTime<Utc>) -> (f64, DeleteReason) {
+        if machine.metadata.deletion_timestamp.is_some() {
+            return (MUST_DELETE, DeleteReason::BeingDeleted);
+        }
+        let has_delete_annotation = machine
+            .metadata
+            .annotations
+            .as_ref()
+            .is_some_and(|a| a.contains_key(annotations::DELETE_MACHINE));
+        if has_delete_annotation {
+            return (SHOULD_DELETE, DeleteReason::DeleteMachineAnnotation);
+        }
+        if let Some(reason) = unhealthy(machine) {
+            return (BETTER_DELETE, reason);
+        }
+        let priority = match self {
+            Self::Random => COULD_DELETE,
+            Self::Oldest => oldest_priority(machine, now),
+            Self::Newest => BETTER_DELETE - oldest_priority(machine, now),
+        };
+        (priority, DeleteReason::Healthy(self))
+    }
+}
+
+impl TryFrom<&MachineSetDeletePolicy> for DeletePolicy {
+    type Error = DeletePolicyError;
+
+    fn try_from(policy: &MachineSetDeletePolicy) -> Result<Self, Self::Error> {
+        match policy {
+            MachineSetDeletePolicy::Random => Ok(Self::Random),
+            MachineSetDeletePolicy::Newest => Ok(Self::Newest),
+            MachineSetDeletePolicy::Oldest => Ok(Self::Oldest),
+            MachineSetDeletePolicy::Other(other) if other.is_empty() => Ok(Self::Random),
+            MachineSetDeletePolicy::Other(other) => Err(DeletePolicyError(other.clone())),
+        }
+    }
+}
+
+impl TryFrom<&MachineDeploymentStrategyRollingUpdateDeletePolicy> for DeletePolicy {
+    type Error = DeletePolicyError;
+
+    fn try_from(
+        policy: &MachineDeploymentStrategyRollingUpdateDeletePolicy,
+    ) -> Result<Self, Self::Error> {
+        use MachineDeploymentStrategyRollingUpdateDeletePolicy as Policy;
+        match policy {
+            Policy::Random => Ok(Self::Random),
+            Policy::Newest => Ok(Self::Newest),
+            Policy::Oldest => Ok(Self::Oldest),
+            Policy::Other(other) if other.is_empty() => Ok(Self::Random),
+            Policy::Other(other) => Err(DeletePolicyError(other.clone())),
+        }
+    }
+}
+
+impl fmt::Display for DeletePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Random => "Random",
+            Self::Newest => "Newest",
+            Self::Oldest => "Oldest",
+        })
+    }
+}
+
+/// Why a Machine has its delete priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeleteReason {
+    /// The Machine has a deletion timestamp.
+    BeingDeleted,
+    /// The Machine has the `cluster.x-k8s.io/delete-machine` annotation.
+    DeleteMachineAnnotation,
+    /// The Machine has no nodeRef yet.
+    NoNode,
+    /// The Machine has a failureReason or failureMessage.
+    Failed,
+    /// The `NodeHealthy` condition of the Machine is False.
+    NodeUnhealthy,
+    /// The `HealthCheckSucceeded` condition of the Machine is False.
+    HealthCheckFailed,
+    /// The Machine is healthy, and ordered by the policy.
+    Healthy(DeletePolicy),
+}
+
+impl fmt::Display for DeleteReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BeingDeleted => f.write_str("Machine is being deleted"),
+            Self::DeleteMachineAnnotation => write!(
+                f,
+                "Machine has the {} annotation",
+                annotations::DELETE_MACHINE
+            ),
+            Self::NoNode => f.write_str("Machine has no Node"),
+            Self::Failed => f.write_str("Machine has a failure reason or message"),
+            Self::NodeUnhealthy => write!(
+                f,
+                "Machine has the {} condition False",
+                condition_types::machine::NODE_HEALTHY
+            ),
+            Self::HealthCheckFailed => write!(
+                f,
+                "Machine has the {} condition False",
+                condition_types::machine::HEALTH_CHECK_SUCCEEDED
+            ),
+            Self::Healthy(DeletePolicy::Random) => f.write_str("Machine is healthy"),
+            Self::Healthy(DeletePolicy::Oldest) => {
+                f.write_str("Machine is healthy, older Machines are deleted first")
+            }
+            Self::Healthy(DeletePolicy::Newest) => {
+                f.write_str("Machine is healthy, newer Machines are deleted first")
+            }
+        }
+    }
+}
+
+/// A Machine with its delete priority.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedMachine<'a> {
+    pub machine: &'a Machine,
+    pub priority: f64,
+    pub reason: DeleteReason,
+}
+
+/// Ranks Machines for deletion at `now`, the first one is deleted first.
+pub fn rank_machines_for_deletion(
+    machines: &[Machine],
+    policy: DeletePolicy,
+    now: DateTime<Utc>,
+) -> Vec<RankedMachine<'_>> {
+    let mut ranked: Vec<_> = machines
+        .iter()
+        .map(|machine| {
+            let (priority, reason) = policy.priority(machine, now);
+            RankedMachine {
+                machine,
+                priority,
+                reason,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.priority
+            .partial_cmp(&a.priority)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.machine.metadata.name.cmp(&b.machine.metadata.name))
+    });
+    ranked
+}
+
+/// The Machines a MachineSet deletes to remove `diff` of them, like
+/// `getMachinesToDeletePrioritized`: all of them in their order if `diff` is not smaller than
+/// their number, else the first ones of the ranking.
+pub fn machines_to_delete(
+    machines: &[Machine],
+    diff: usize,
+    policy: DeletePolicy,
+    now: DateTime<Utc>,
+) -> Vec<&Machine> {
+    if diff >= machines.len() {
+        return machines.iter().collect();
+    }
+    rank_machines_for_deletion(machines, policy, now)
+        .into_iter()
+        .take(diff)
+        .map(|ranked| ranked.machine)
+        .collect()
+}
+
+/// Why a Machine is not healthy, like `isMachineHealthy`. Conditions that are Unknown do not
+/// make a Machine unhealthy.
+fn unhealthy(machine: &Machine) -> Option<DeleteReason> {
+    let status = machine.status.as_ref();
+    if status.and_then(|s| s.node_ref.as_ref()).is_none() {
+        return Some(DeleteReason::NoNode);
+    }
+    if status.is_some_and(|s| s.failure_reason.is_some() || s.failure_message.is_some()) {
+        return Some(DeleteReason::Failed);
+    }
+    if conditions::is_false(machine, condition_types::machine::NODE_HEALTHY) {
+        return Some(DeleteReason::NodeUnhealthy);
+    }
+    if conditions::is_false(machine, condition_types::machine::HEALTH_CHECK_SUCCEEDED) {
+        return Some(DeleteReason::HealthCheckFailed);
+    }
+    None
+}
+
+/// The priority of a healthy Machine with the Oldest policy, growing with its age towards
+/// [`BETTER_DELETE`].
+fn oldest_priority(machine: &Machine, now: DateTime<Utc>) -> f64 {
+    let Some(created) = machine.metadata.creation_timestamp.as_ref() else {
+        return MUST_NOT_DELETE;
+    };
+    let age = (now - created.0).as_seconds_f64();
+    if age < 0.0 {
+        return MUST_NOT_DELETE;
+    }
+    BETTER_DELETE * (1.0 - (-age / SECONDS_PER_TEN_DAYS).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::chrono::TimeDelta;
+    use serde_json::json;
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2024-01-01T12:00:00Z".parse().unwrap()
+    }
+
+    /// A Machine created `age` seconds ago, with the given metadata and status merged in.
+    fn machine(
+        name: &str,
+        age: i64,
+        metadata: serde_json::Value,
+        status: serde_json::Value,
+    ) -> Machine {
+        let mut machine = json!({
+            "metadata": {
+                "name": name,
+                "creationTimestamp": (now() - TimeDelta::seconds(age)).to_rfc3339(),
+            },
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+            "status": status,
+        });
+        for (key, value) in metadata.as_object().into_iter().flatten() {
+            machine["metadata"][key] = value.clone();
+        }
+        serde_json::from_value(machine).unwrap()
+    }
+
+    fn healthy(name: &str, age: i64) -> Machine {
+        machine(name, age, json!({}), json!({"nodeRef": {"name": name}}))
+    }
+
+    fn with_condition(condition: &str) -> serde_json::Value {
+        json!({
+            "nodeRef": {"name": "node"},
+            "conditions": [{
+                "type": condition,
+                "status": "False",
+                "lastTransitionTime": "2024-01-01T11:00:00Z",
+            }],
+        })
+    }
+
+    fn machines() -> Vec<Machine> {
+        let day = 24 * 3600;
+        vec![
+            healthy("a-new", 3600),
+            healthy("b-old", 10 * day),
+            healthy("c-new", 3600),
+            machine("d-no-node", day, json!({}), json!({})),
+            machine(
+                "e-failed",
+                day,
+                json!({}),
+                json!({"nodeRef": {"name": "e"}, "failureReason": "UpdateError"}),
+            ),
+            machine(
+                "f-node-unhealthy",
+                day,
+                json!({}),
+                with_condition(condition_types::machine::NODE_HEALTHY),
+            ),
+            machine(
+                "g-health-check-failed",
+                day,
+                json!({}),
+                with_condition(condition_types::machine::HEALTH_CHECK_SUCCEEDED),
+            ),
+            machine(
+                "h-annotated",
+                day,
+                json!({"annotations": {annotations::DELETE_MACHINE: ""}}),
+                json!({"nodeRef": {"name": "h"}}),
+            ),
+            // being deleted takes precedence over the annotation
+            machine(
+                "i-deleting",
+                day,
+                json!({
+                    "deletionTimestamp": "2024-01-01T11:59:00Z",
+                    "finalizers": ["machine.cluster.x-k8s.io"],
+                    "annotations": {annotations::DELETE_MACHINE: ""},
+                }),
+                json!({}),
+            ),
+            // unhealthy Machines are ordered by name, whatever the reason
+            machine("a-no-node", 0, json!({}), json!({})),
+        ]
+    }
+
+    fn ranking(policy: DeletePolicy) -> Vec<(String, DeleteReason)> {
+        let machines = machines();
+        rank_machines_for_deletion(&machines, policy, now())
+            .into_iter()
+            .map(|ranked| (ranked.machine.metadata.name.clone().unwrap(), ranked.reason))
+            .collect()
+    }
+
+    fn expected(healthy: [&str; 3], policy: DeletePolicy) -> Vec<(String, DeleteReason)> {
+        [
+            ("i-deleting", DeleteReason::BeingDeleted),
+            ("h-annotated", DeleteReason::DeleteMachineAnnotation),
+            ("a-no-node", DeleteReason::NoNode),
+            ("d-no-node", DeleteReason::NoNode),
+            ("e-failed", DeleteReason::Failed),
+            ("f-node-unhealthy", DeleteReason::NodeUnhealthy),
+            ("g-health-check-failed", DeleteReason::HealthCheckFailed),
+        ]
+        .into_iter()
+        .chain(healthy.map(|name| (name, DeleteReason::Healthy(policy))))
+        .map(|(name, reason)| (name.to_string(), reason))
+        .collect()
+    }
+
+    #[test]
+    fn ranks_machines_for_deletion() {
+        assert_eq!(
+            ranking(DeletePolicy::Random),
+            expected(["a-new", "b-old", "c-new"], DeletePolicy::Random)
+        );
+        assert_eq!(
+            ranking(DeletePolicy::Oldest),
+            expected(["b-old", "a-new", "c-new"], DeletePolicy::Oldest)
+        );
+        assert_eq!(
+            ranking(DeletePolicy::Newest),
+            expected(["a-new", "c-new", "b-old"], DeletePolicy::Newest)
+        );
+    }
+
+    #[test]
+    fn priorities() {
+        let day = 24 * 3600;
+        for (policy, age, expected) in [
+            (DeletePolicy::Random, day, COULD_DELETE),
+            (DeletePolicy::Oldest, 0, MUST_NOT_DELETE),
+            (DeletePolicy::Oldest, -60, MUST_NOT_DELETE),
+            (DeletePolicy::Oldest, 10 * day, 31.606),
+            (DeletePolicy::Oldest, 1000 * day, BETTER_DELETE),
+            (DeletePolicy::Newest, 0, BETTER_DELETE),
+            (DeletePolicy::Newest, 10 * day, 18.394),
+        ] {
+            let (priority, reason) = policy.priority(&healthy("m", age), now());
+            assert!(
+                (priority - expected).abs() < 0.001,
+                "{policy} {age}: {priority}"
+            );
+            assert_eq!(reason, DeleteReason::Healthy(policy));
+        }
+        let mut machine = healthy("m", day);
+        machine.metadata.creation_timestamp = None;
+        assert_eq!(
+            DeletePolicy::Oldest.priority(&machine, now()).0,
+            MUST_NOT_DELETE
+        );
+    }
+
+    #[test]
+    fn machines_to_delete_in_order() {
+        let machines = machines();
+        let names = |machines: Vec<&Machine>| -> Vec<String> {
+            machines
+                .into_iter()
+                .map(|m| m.metadata.name.clone().unwrap())
+                .collect()
+        };
+        assert_eq!(
+            names(machines_to_delete(
+                &machines,
+                3,
+                DeletePolicy::Oldest,
+                now()
+            )),
+            ["i-deleting", "h-annotated", "a-no-node"]
+        );
+        assert_eq!(
+            names(machines_to_delete(
+                &machines[..3],
+                1,
+                DeletePolicy::Oldest,
+                now()
+            )),
+            ["b-old"]
+        );
+        // all Machines are deleted in their order, not ranked
+        assert_eq!(
+            names(machines_to_delete(
+                &machines[..3],
+                3,
+                DeletePolicy::Oldest,
+                now()
+            )),
+            ["a-new", "b-old", "c-new"]
+        );
+        assert!(machines_to_delete(&machines, 0, DeletePolicy::Random, now()).is_empty());
+    }
+
+    #[test]
+    fn delete_policies() {
+        for (policy, expected) in [
+            (MachineSetDeletePolicy::Random, Ok(DeletePolicy::Random)),
+            (MachineSetDeletePolicy::Newest, Ok(DeletePolicy::Newest)),
+            (MachineSetDeletePolicy::Oldest, Ok(DeletePolicy::Oldest)),
+            (
+                MachineSetDeletePolicy::Other(String::new()),
+                Ok(DeletePolicy::Random),
+            ),
+            (
+                MachineSetDeletePolicy::Other("Largest".to_string()),
+                Err(DeletePolicyError("Largest".to_string())),
+            ),
+        ] {
+            assert_eq!(DeletePolicy::try_from(&policy), expected, "{policy:?}");
+        }
+        assert_eq!(
+            DeletePolicyError("Largest".to_string()).to_string(),
+            "Unsupported delete policy Largest. Must be one of 'Random', 'Newest', or 'Oldest'"
+        );
+        assert_eq!(
+            DeletePolicy::try_from(&MachineDeploymentStrategyRollingUpdateDeletePolicy::Newest),
+            Ok(DeletePolicy::Newest)
+        );
+    }
+}
@@ -0,0 +1,1015 @@
+//! Offline evaluation of MachineHealthChecks, following the upstream MachineHealthCheck
+//! controller (`internal/controllers/machinehealthcheck`), to see which Machines a
+//! MachineHealthCheck would remediate at a given time without running it.
+//!
+//! Remediation itself is not simulated: the result tells which Machines need remediation and
+//! whether `maxUnhealthy` or `unhealthyRange` allow it.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use k8s_openapi::api::core::v1::Node;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use k8s_openapi::chrono::{DateTime, TimeDelta, Utc};
+use kube::core::SelectorExt;
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_machine::Machine;
+use crate::api::capi_machinehealthcheck::{MachineHealthCheck, MachineHealthCheckStatus};
+use crate::api::condition::ConditionSeverity;
+use crate::api::duration::GoDuration;
+use crate::api::well_known::{annotations, conditions as condition_types, labels};
+use crate::conditions;
+use crate::intstr::{scaled_value, IntOrPercentError, Rounding};
+use crate::selector::{LabelSelectorExt, SelectorError};
+
+/// The node startup timeout the MachineHealthCheck webhook defaults to.
+pub const DEFAULT_NODE_STARTUP_TIMEOUT: GoDuration =
+    GoDuration::from_nanos(10 * 60 * 1_000_000_000);
+
+/// Why a Machine needs remediation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UnhealthyReason {
+    /// The Machine has the `cluster.x-k8s.io/remediate-machine` annotation.
+    RemediateMachineAnnotation,
+    /// The Machine has a failureReason or failureMessage.
+    MachineHasFailure,
+    /// The Machine has a nodeRef, but the Node does not exist.
+    NodeNotFound,
+    /// The Machine did not get a Node within the node startup timeout.
+    NodeStartupTimeout,
+    /// A Node condition matched an unhealthy condition for longer than its timeout.
+    UnhealthyNodeCondition,
+}
+
+impl UnhealthyReason {
+    /// The reason of the `HealthCheckSucceeded` condition upstream sets on the Machine.
+    pub fn as_str(self) -> &'static str {
+        use condition_types::machine;
+        match self {
+            Self::RemediateMachineAnnotation => machine::HAS_REMEDIATE_MACHINE_ANNOTATION_REASON,
+            Self::MachineHasFailure => machine::MACHINE_HAS_FAILURE_REASON,
+            Self::NodeNotFound => machine::NODE_NOT_FOUND_REASON,
+            Self::NodeStartupTimeout => machine::NODE_STARTUP_TIMEOUT_REASON,
+            Self::UnhealthyNodeCondition => machine::UNHEALTHY_NODE_CONDITION_REASON,
+        }
+    }
+}
+
+/// The health of a Machine targeted by a MachineHealthCheck.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Health {
+    /// The Machine has a Node, is not being deleted and does not need remediation. The Node
+    /// conditions are only checked once the control plane is initialized and the cluster
+    /// infrastructure is ready.
+    Healthy,
+    /// The Machine needs remediation.
+    Unhealthy {
+        reason: UnhealthyReason,
+        /// The message of the `HealthCheckSucceeded` condition, e.g.
+        /// `Node failed to report startup in 10m0s`.
+        message: String,
+    },
+    /// The Machine is waiting for its Node, or a Node condition matches an unhealthy
+    /// condition but not for longer than its timeout yet. The Machine is checked again after
+    /// `next_check`.
+    Pending { next_check: Duration },
+    /// The Machine has no Node or is being deleted, and does not need remediation, e.g. because
+    /// the control plane is not initialized yet. It does not count as healthy.
+    NotChecked,
+}
+
+impl Health {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Healthy)
+    }
+
+    pub fn needs_remediation(&self) -> bool {
+        matches!(self, Self::Unhealthy { .. })
+    }
+}
+
+/// The health of a Machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachineHealth {
+    pub machine: String,
+    pub health: Health,
+}
+
+/// A Machine matching the selector that is not a target of the MachineHealthCheck.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedMachine {
+    pub machine: String,
+    /// Why the Machine is skipped, e.g. `machine has skip-remediation annotation`.
+    pub reason: &'static str,
+}
+
+/// The result of evaluating a MachineHealthCheck.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCheckEvaluation {
+    /// The targets of the MachineHealthCheck, in the order of the given Machines.
+    pub machines: Vec<MachineHealth>,
+    pub skipped: Vec<SkippedMachine>,
+    /// Whether `maxUnhealthy` or `unhealthyRange` allow remediation. If not, remediation is
+    /// short-circuited and no Machine is remediated.
+    pub remediation_allowed: bool,
+    /// Why remediation is not allowed.
+    pub message: Option<String>,
+    /// When the MachineHealthCheck has to be evaluated again, for the first pending Machine.
+    pub requeue_after: Option<Duration>,
+    /// The status the controller would set, with the `RemediationAllowed` condition and the
+    /// names of the targets sorted.
+    pub status: MachineHealthCheckStatus,
+}
+
+impl HealthCheckEvaluation {
+    /// The Machines that would be remediated: the unhealthy ones, if remediation is allowed.
+    pub fn to_remediate(&self) -> impl Iterator<Item = &MachineHealth> {
+        self.machines
+            .iter()
+            .filter(move |m| self.remediation_allowed && m.health.needs_remediation())
+    }
+}
+
+/// Error evaluating a MachineHealthCheck.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum HealthCheckError {
+    #[error("spec.selector: {0}")]
+    Selector(#[from] SelectorError),
+    #[error("error checking if remediation is allowed: {0}")]
+    UnhealthyRange(#[from] ParseUnhealthyRangeError),
+    #[error("error checking if remediation is allowed: {0}")]
+    MaxUnhealthy(#[from] IntOrPercentError),
+}
+
+/// Evaluates a MachineHealthCheck at `now`, like one reconcile of the upstream controller.
+///
+/// The targets are the Machines in the namespace of the MachineHealthCheck that match its
+/// selector and belong to its Cluster, except Machines that are paused or excluded from
+/// remediation. Nodes are matched to Machines by the name in their `status.nodeRef`; a Machine
+/// whose Node is not in `nodes` is unhealthy with `NodeNotFound`. A missing `maxUnhealthy` or
+/// `nodeStartupTimeout` gets the default of the webhook, `100%` and `10m`.
+pub fn evaluate_health_check(
+    health_check: &MachineHealthCheck,
+    cluster: &Cluster,
+    machines: &[Machine],
+    nodes: &[Node],
+    now: DateTime<Utc>,
+) -> Result<HealthCheckEvaluation, HealthCheckError> {
+    let spec = &health_check.spec;
+    let selector = spec.selector.to_selector()?;
+    let namespace = health_check.metadata.namespace.as_deref();
+    let mut evaluation = HealthCheckEvaluation {
+        machines: Vec::new(),
+        skipped: Vec::new(),
+        remediation_allowed: true,
+        message: None,
+        requeue_after: None,
+        status: MachineHealthCheckStatus {
+            observed_generation: health_check.metadata.generation,
+            ..Default::default()
+        },
+    };
+    let node_startup_timeout = spec
+        .node_startup_timeout
+        .unwrap_or(DEFAULT_NODE_STARTUP_TIMEOUT);
+    let mut next_checks = Vec::new();
+    for machine in machines {
+        let machine_labels = machine.metadata.labels.clone().unwrap_or_default();
+        let selected = machine.metadata.namespace.as_deref() == namespace
+            && machine_labels.get(labels::CLUSTER_NAME) == Some(&spec.cluster_name)
+            && selector.matches(&machine_labels);
+        if !selected {
+            continue;
+        }
+        let name = machine.metadata.name.clone().unwrap_or_default();
+        let machine_annotations = machine.metadata.annotations.as_ref();
+        let has_annotation =
+            |annotation| machine_annotations.is_some_and(|a| a.contains_key(annotation));
+        if has_annotation(annotations::PAUSED) {
+            evaluation.skipped.push(SkippedMachine {
+                machine: name,
+                reason: "machine has paused annotation",
+            });
+            continue;
+        }
+        if has_annotation(annotations::MACHINE_SKIP_REMEDIATION) {
+            evaluation.skipped.push(SkippedMachine {
+                machine: name,
+                reason: "machine has skip-remediation annotation",
+            });
+            continue;
+        }
+        let node_ref = machine
+            .status
+            .as_ref()
+            .and_then(|status| status.node_ref.as_ref());
+        let node = node_ref.map(|node_ref| {
+            nodes
+                .iter()
+                .find(|node| node.metadata.name.is_some() && node.metadata.name == node_ref.name)
+        });
+        let health = check_machine(
+            health_check,
+            cluster,
+            machine,
+            node,
+            node_startup_timeout,
+            now,
+        );
+        if let Health::Pending { next_check } = health {
+            next_checks.push(next_check);
+        }
+        evaluation.machines.push(MachineHealth {
+            machine: name,
+            health,
+        });
+    }
+
+    let total = evaluation.machines.len() as i32;
+    let healthy = evaluation
+        .machines
+        .iter()
+        .filter(|m| m.health.is_healthy())
+        .count() as i32;
+    let unhealthy = evaluation
+        .machines
+        .iter()
+        .filter(|m| m.health.needs_remediation())
+        .count();
+    let status = &mut evaluation.status;
+    status.expected_machines = Some(total);
+    status.current_healthy = Some(healthy);
+    let mut targets: Vec<String> = evaluation
+        .machines
+        .iter()
+        .map(|m| m.machine.clone())
+        .collect();
+    targets.sort();
+    status.targets = Some(targets);
+    evaluation.requeue_after = next_checks.into_iter().min();
+
+    // machines that are not healthy yet count as unhealthy here
+    let not_healthy = total - healthy;
+    let max_unhealthy = spec
+        .max_unhealthy
+        .clone()
+        .unwrap_or_else(|| IntOrString::String("100%".to_string()));
+    let (allowed, count) = match &spec.unhealthy_range {
+        Some(range) => {
+            let range: UnhealthyRange = range.parse()?;
+            let count = i64::from(range.max) - i64::from(not_healthy);
+            (range.contains(not_healthy), count as i32)
+        }
+        None => {
+            let max = scaled_value(&max_unhealthy, total, Rounding::Down)?;
+            (not_healthy <= max, max - not_healthy)
+        }
+    };
+    let mut condition = if allowed {
+        status.remediations_allowed = Some(count);
+        conditions::true_condition(condition_types::machine_health_check::REMEDIATION_ALLOWED)
+    } else {
+        let message = match &spec.unhealthy_range {
+            Some(range) => format!(
+                "Remediation is not allowed, the number of not started or unhealthy machines does not fall within the range (total: {total}, unhealthy: {unhealthy}, unhealthyRange: {range})"
+            ),
+            None => format!(
+                "Remediation is not allowed, the number of not started or unhealthy machines exceeds maxUnhealthy (total: {total}, unhealthy: {unhealthy}, maxUnhealthy: {})",
+                match &max_unhealthy {
+                    IntOrString::Int(value) => value.to_string(),
+                    IntOrString::String(value) => value.clone(),
+                }
+            ),
+        };
+        status.remediations_allowed = Some(0);
+        evaluation.remediation_allowed = false;
+        evaluation.message = Some(message.clone());
+        conditions::false_condition(
+            condition_types::machine_health_check::REMEDIATION_ALLOWED,
+            condition_types::machine_health_check::TOO_MANY_UNHEALTHY_REASON,
+            ConditionSeverity::Warning,
+            message,
+        )
+    };
+    condition.last_transition_time = Time(now);
+    status.conditions = Some(vec![condition]);
+    Ok(evaluation)
+}
+
+/// The health of a target, like `needsRemediation` upstream. `node` is `None` without a
+/// nodeRef and `Some(None)` if the Node of the nodeRef does not exist.
+fn check_machine(
+    health_check: &MachineHealthCheck,
+    cluster: &Cluster,
+    machine: &Machine,
+    node: Option<Option<&Node>>,
+    node_startup_timeout: GoDuration,
+    now: DateTime<Utc>,
+) -> Health {
+    let unhealthy = |reason, message: String| Health::Unhealthy { reason, message };
+    let has_annotation = |annotation| {
+        machine
+            .metadata
+            .annotations
+            .as_ref()
+            .is_some_and(|a| a.contains_key(annotation))
+    };
+    if has_annotation(annotations::REMEDIATE_MACHINE) {
+        return unhealthy(
+            UnhealthyReason::RemediateMachineAnnotation,
+            "Marked for remediation via remediate-machine annotation".to_string(),
+        );
+    }
+    let status = machine.status.as_ref();
+    if let Some(reason) = status.and_then(|status| status.failure_reason.as_ref()) {
+        return unhealthy(
+            UnhealthyReason::MachineHasFailure,
+            format!("FailureReason: {reason}"),
+        );
+    }
+    if let Some(message) = status.and_then(|status| status.failure_message.as_ref()) {
+        return unhealthy(
+            UnhealthyReason::MachineHasFailure,
+            format!("FailureMessage: {message}"),
+        );
+    }
+    let node = match node {
+        Some(None) => return unhealthy(UnhealthyReason::NodeNotFound, String::new()),
+        Some(Some(node)) => Some(node),
+        None => None,
+    };
+    // a target that does not need remediation counts as healthy if it has a Node
+    let not_unhealthy = || {
+        if node.is_some() && machine.metadata.deletion_timestamp.is_none() {
+            Health::Healthy
+        } else {
+            Health::NotChecked
+        }
+    };
+
+    use condition_types::cluster::{CONTROL_PLANE_INITIALIZED, INFRASTRUCTURE_READY};
+    let control_plane_machine = machine
+        .metadata
+        .labels
+        .as_ref()
+        .is_some_and(|labels| labels.contains_key(labels::MACHINE_CONTROL_PLANE));
+    // the first control plane Machine can be remediated before the control plane is initialized
+    if !conditions::is_true(cluster, CONTROL_PLANE_INITIALIZED) && !control_plane_machine {
+        return not_unhealthy();
+    }
+    if !conditions::is_true(cluster, INFRASTRUCTURE_READY) {
+        return not_unhealthy();
+    }
+
+    let Some(node) = node else {
+        if node_startup_timeout == GoDuration::ZERO {
+            return Health::NotChecked;
+        }
+        // the node startup timeout starts with the latest of these events
+        let mut start = machine
+            .metadata
+            .creation_timestamp
+            .as_ref()
+            .map_or(DateTime::<Utc>::MIN_UTC, |time| time.0);
+        let transitions = [
+            ready_since(cluster, CONTROL_PLANE_INITIALIZED),
+            ready_since(cluster, INFRASTRUCTURE_READY),
+            ready_since(machine, condition_types::machine::INFRASTRUCTURE_READY),
+        ];
+        for time in transitions.into_iter().flatten() {
+            start = start.max(time);
+        }
+        let timeout = delta(node_startup_timeout);
+        if start + timeout < now {
+            return unhealthy(
+                UnhealthyReason::NodeStartupTimeout,
+                format!("Node failed to report startup in {node_startup_timeout}"),
+            );
+        }
+        return pending(timeout - (now - start));
+    };
+
+    let node_conditions = node
+        .status
+        .as_ref()
+        .and_then(|status| status.conditions.as_deref())
+        .unwrap_or_default();
+    let mut next_checks = Vec::new();
+    for condition in health_check.spec.unhealthy_conditions.iter().flatten() {
+        let Some(node_condition) = node_conditions
+            .iter()
+            .find(|c| c.type_ == condition.r#type && c.status == condition.status)
+        else {
+            continue;
+        };
+        let since = node_condition
+            .last_transition_time
+            .as_ref()
+            .map_or(DateTime::<Utc>::MIN_UTC, |time| time.0);
+        let timeout = delta(condition.timeout);
+        if since + timeout < now {
+            return unhealthy(
+                UnhealthyReason::UnhealthyNodeCondition,
+                format!(
+                    "Condition {} on node is reporting status {} for more than {}",
+                    condition.r#type, condition.status, condition.timeout
+                ),
+            );
+        }
+        if let Health::Pending { next_check } = pending(timeout - (now - since)) {
+            next_checks.push(next_check);
+        }
+    }
+    match next_checks.into_iter().min() {
+        Some(next_check) => Health::Pending { next_check },
+        None => not_unhealthy(),
+    }
+}
+
+/// When a condition became true, if it is.
+fn ready_since<T: conditions::Getter>(from: &T, t: &str) -> Option<DateTime<Utc>> {
+    conditions::is_true(from, t)
+        .then(|| conditions::get_last_transition_time(from, t))
+        .flatten()
+        .map(|time| time.0)
+}
+
+fn delta(duration: GoDuration) -> TimeDelta {
+    TimeDelta::nanoseconds(duration.as_nanos())
+}
+
+/// A Machine to check again a second after the remaining time, as upstream does.
+fn pending(remaining: TimeDelta) -> Health {
+    match (remaining + TimeDelta::seconds(1)).to_std() {
+        Ok(next_check) if !next_check.is_zero() => Health::Pending { next_check },
+        _ => Health::NotChecked,
+    }
+}
+
+/// The number of not healthy Machines a MachineHealthCheck allows remediation for, parsed
+/// from `unhealthyRange`, e.g. `[3-5]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UnhealthyRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl UnhealthyRange {
+    /// Whether remediation is allowed with this number of not healthy Machines.
+    pub fn contains(&self, not_healthy: i32) -> bool {
+        i64::from(not_healthy) >= i64::from(self.min)
+            && i64::from(not_healthy) <= i64::from(self.max)
+    }
+}
+
+impl fmt::Display for UnhealthyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}-{}]", self.min, self.max)
+    }
+}
+
+/// Error for an `unhealthyRange` that is not valid, with the messages of the CRD validation and
+/// of the upstream controller.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseUnhealthyRangeError {
+    #[error("{0:?} should match '^\\[[0-9]+-[0-9]+\\]$'")]
+    Pattern(String),
+    #[error("strconv.ParseUint: parsing {0:?}: value out of range")]
+    OutOfRange(String),
+    #[error("max value {max} cannot be less than min value {min} for unhealthyRange")]
+    MaxLessThanMin { min: u32, max: u32 },
+}
+
+impl FromStr for UnhealthyRange {
+    type Err = ParseUnhealthyRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern = || ParseUnhealthyRangeError::Pattern(s.to_string());
+        let (min, max) = s
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .and_then(|s| s.split_once('-'))
+            .ok_or_else(pattern)?;
+        let parse = |value: &str| {
+            if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(pattern());
+            }
+            value
+                .parse::<u32>()
+                .map_err(|_| ParseUnhealthyRangeError::OutOfRange(value.to_string()))
+        };
+        let (min, max) = (parse(min)?, parse(max)?);
+        if max < min {
+            return Err(ParseUnhealthyRangeError::MaxLessThanMin { min, max });
+        }
+        Ok(Self { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2024-01-01T12:00:00Z".parse().unwrap()
+    }
+
+    /// The time `seconds` before now.
+    fn ago(seconds: i64) -> String {
+        (now() - TimeDelta::seconds(seconds)).to_rfc3339()
+    }
+
+    fn health_check(spec: serde_json::Value) -> MachineHealthCheck {
+        let mut health_check = json!({
+            "metadata": {"name": "mhc", "namespace": "ns"},
+            "spec": {
+                "clusterName": "c1",
+                "selector": {},
+                "unhealthyConditions": [
+                    {"type": "Ready", "status": "False", "timeout": "5m"},
+                    {"type": "Ready", "status": "Unknown", "timeout": "10m"},
+                ],
+            },
+        });
+        for (key, value) in spec.as_object().into_iter().flatten() {
+            health_check["spec"][key] = value.clone();
+        }
+        serde_json::from_value(health_check).unwrap()
+    }
+
+    /// A Cluster whose control plane was initialized and infrastructure became ready an hour
+    /// ago, unless listed in `not_ready`.
+    fn cluster(not_ready: &[&str]) -> Cluster {
+        let conditions: Vec<_> = [
+            condition_types::cluster::CONTROL_PLANE_INITIALIZED,
+            condition_types::cluster::INFRASTRUCTURE_READY,
+        ]
+        .into_iter()
+        .map(|t| {
+            let status = if not_ready.contains(&t) {
+                "False"
+            } else {
+                "True"
+            };
+            json!({"type": t, "status": status, "lastTransitionTime": ago(3600)})
+        })
+        .collect();
+        serde_json::from_value(json!({
+            "metadata": {"name": "c1", "namespace": "ns"},
+            "spec": {},
+            "status": {"conditions": conditions},
+        }))
+        .unwrap()
+    }
+
+    /// A Machine created an hour ago, with the given metadata and status merged in.
+    fn machine(name: &str, metadata: serde_json::Value, status: serde_json::Value) -> Machine {
+        let mut machine = json!({
+            "metadata": {
+                "name": name,
+                "namespace": "ns",
+                "creationTimestamp": ago(3600),
+                "labels": {labels::CLUSTER_NAME: "c1"},
+            },
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+            "status": status,
+        });
+        for (key, value) in metadata.as_object().into_iter().flatten() {
+            match (key.as_str(), value) {
+                ("labels", serde_json::Value::Object(labels)) => {
+                    for (label, value) in labels {
+                        machine["metadata"]["labels"][label] = value.clone();
+                    }
+                }
+                _ => machine["metadata"][key] = value.clone(),
+            }
+        }
+        serde_json::from_value(machine).unwrap()
+    }
+
+    fn with_node(name: &str) -> serde_json::Value {
+        json!({"nodeRef": {"name": name}})
+    }
+
+    fn node(name: &str, conditions: serde_json::Value) -> Node {
+        serde_json::from_value(json!({
+            "metadata": {"name": name},
+            "status": {"conditions": conditions},
+        }))
+        .unwrap()
+    }
+
+    fn ready(status: &str, seconds_ago: i64) -> serde_json::Value {
+        json!([{"type": "Ready", "status": status, "lastTransitionTime": ago(seconds_ago)}])
+    }
+
+    fn unhealthy(reason: UnhealthyReason, message: &str) -> Health {
+        Health::Unhealthy {
+            reason,
+            message: message.to_string(),
+        }
+    }
+
+    fn pending(seconds: u64) -> Health {
+        Health::Pending {
+            next_check: Duration::from_secs(seconds),
+        }
+    }
+
+    fn health(
+        health_check: &MachineHealthCheck,
+        cluster: &Cluster,
+        machine: Machine,
+        nodes: &[Node],
+    ) -> Health {
+        let evaluation =
+            evaluate_health_check(health_check, cluster, &[machine], nodes, now()).unwrap();
+        evaluation.machines[0].health.clone()
+    }
+
+    #[test]
+    fn checks_machines() {
+        use UnhealthyReason::*;
+        let mhc = health_check(json!({}));
+        let ready_cluster = cluster(&[]);
+        let nodes = [
+            node("healthy", ready("True", 60)),
+            node("not-ready-soon", ready("False", 240)),
+            node("not-ready", ready("False", 360)),
+            node("unknown", ready("Unknown", 360)),
+        ];
+        let deleting = json!({"deletionTimestamp": ago(10)});
+        for (name, metadata, status, expected) in [
+            ("a", json!({}), with_node("healthy"), Health::Healthy),
+            (
+                "b",
+                deleting.clone(),
+                with_node("healthy"),
+                Health::NotChecked,
+            ),
+            ("c", json!({}), with_node("not-ready-soon"), pending(61)),
+            (
+                "d",
+                json!({}),
+                with_node("not-ready"),
+                unhealthy(
+                    UnhealthyNodeCondition,
+                    "Condition Ready on node is reporting status False for more than 5m0s",
+                ),
+            ),
+            ("e", json!({}), with_node("unknown"), pending(241)),
+            (
+                "f",
+                json!({}),
+                with_node("missing"),
+                unhealthy(NodeNotFound, ""),
+            ),
+            (
+                "g",
+                json!({"annotations": {annotations::REMEDIATE_MACHINE: ""}}),
+                with_node("healthy"),
+                unhealthy(
+                    RemediateMachineAnnotation,
+                    "Marked for remediation via remediate-machine annotation",
+                ),
+            ),
+            (
+                "h",
+                json!({}),
+                json!({"failureReason": "UpdateError", "failureMessage": "boom"}),
+                unhealthy(MachineHasFailure, "FailureReason: UpdateError"),
+            ),
+            (
+                "i",
+                json!({}),
+                json!({"failureMessage": "boom"}),
+                unhealthy(MachineHasFailure, "FailureMessage: boom"),
+            ),
+        ] {
+            assert_eq!(
+                health(
+                    &mhc,
+                    &ready_cluster,
+                    machine(name, metadata, status),
+                    &nodes
+                ),
+                expected,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn checks_nodes_only_once_the_cluster_is_ready() {
+        use condition_types::cluster::{CONTROL_PLANE_INITIALIZED, INFRASTRUCTURE_READY};
+        let mhc = health_check(json!({}));
+        let nodes = [node("not-ready", ready("False", 3600))];
+        let control_plane = json!({"labels": {labels::MACHINE_CONTROL_PLANE: ""}});
+        let deleting = json!({"deletionTimestamp": ago(10)});
+        let timed_out = unhealthy(
+            UnhealthyReason::NodeStartupTimeout,
+            "Node failed to report startup in 10m0s",
+        );
+        let node_failed = unhealthy(
+            UnhealthyReason::UnhealthyNodeCondition,
+            "Condition Ready on node is reporting status False for more than 5m0s",
+        );
+        for (not_ready, metadata, status, expected) in [
+            // workers are not penalized before the control plane is initialized
+            (
+                &[CONTROL_PLANE_INITIALIZED][..],
+                json!({}),
+                with_node("not-ready"),
+                Health::Healthy,
+            ),
+            (
+                &[CONTROL_PLANE_INITIALIZED],
+                json!({}),
+                json!({}),
+                Health::NotChecked,
+            ),
+            (
+                &[CONTROL_PLANE_INITIALIZED],
+                deleting.clone(),
+                with_node("not-ready"),
+                Health::NotChecked,
+            ),
+            // but the first control plane Machine is
+            (
+                &[CONTROL_PLANE_INITIALIZED],
+                control_plane.clone(),
+                with_node("not-ready"),
+                node_failed.clone(),
+            ),
+            (
+                &[CONTROL_PLANE_INITIALIZED],
+                control_plane.clone(),
+                json!({}),
+                timed_out.clone(),
+            ),
+            // nothing is penalized before the infrastructure is ready
+            (
+                &[INFRASTRUCTURE_READY],
+                json!({}),
+                with_node("not-ready"),
+                Health::Healthy,
+            ),
+            (
+                &[INFRASTRUCTURE_READY],
+                control_plane.clone(),
+                with_node("not-ready"),
+                Health::Healthy,
+            ),
+            (
+                &[INFRASTRUCTURE_READY],
+                control_plane,
+                json!({}),
+                Health::NotChecked,
+            ),
+            (
+                &[INFRASTRUCTURE_READY],
+                deleting,
+                with_node("not-ready"),
+                Health::NotChecked,
+            ),
+            // failures are remediated regardless
+            (
+                &[CONTROL_PLANE_INITIALIZED, INFRASTRUCTURE_READY],
+                json!({}),
+                json!({"nodeRef": {"name": "missing"}}),
+                unhealthy(UnhealthyReason::NodeNotFound, ""),
+            ),
+            (&[], json!({}), json!({}), timed_out),
+        ] {
+            assert_eq!(
+                health(
+                    &mhc,
+                    &cluster(not_ready),
+                    machine("m", metadata.clone(), status.clone()),
+                    &nodes
+                ),
+                expected,
+                "{not_ready:?} {metadata} {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn node_startup_timeout() {
+        let machine_ready = |seconds_ago| {
+            json!({"conditions": [{
+                "type": condition_types::machine::INFRASTRUCTURE_READY,
+                "status": "True",
+                "lastTransitionTime": ago(seconds_ago),
+            }]})
+        };
+        let cluster = cluster(&[]);
+        for (timeout, status, expected) in [
+            // the Machine was created an hour ago
+            (
+                json!("10m"),
+                json!({}),
+                unhealthy(
+                    UnhealthyReason::NodeStartupTimeout,
+                    "Node failed to report startup in 10m0s",
+                ),
+            ),
+            (json!("2h"), json!({}), pending(3601)),
+            // the timeout starts when the Machine infrastructure got ready
+            (json!("10m"), machine_ready(60), pending(541)),
+            (json!("1m"), machine_ready(60), pending(1)),
+            (json!("0s"), json!({}), Health::NotChecked),
+            (serde_json::Value::Null, machine_ready(599), pending(2)),
+        ] {
+            let mhc = health_check(json!({"nodeStartupTimeout": timeout}));
+            assert_eq!(
+                health(&mhc, &cluster, machine("m", json!({}), status), &[]),
+                expected,
+                "{timeout}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_unhealthy_ranges() {
+        for (range, expected) in [
+            ("[3-5]", UnhealthyRange { min: 3, max: 5 }),
+            ("[0-0]", UnhealthyRange { min: 0, max: 0 }),
+            ("[007-10]", UnhealthyRange { min: 7, max: 10 }),
+            (
+                "[0-4294967295]",
+                UnhealthyRange {
+                    min: 0,
+                    max: u32::MAX,
+                },
+            ),
+        ] {
+            assert_eq!(range.parse(), Ok(expected), "{range}");
+        }
+        assert_eq!(UnhealthyRange { min: 3, max: 5 }.to_string(), "[3-5]");
+        for (range, expected) in [
+            ("3-5", r#""3-5" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[3-5", r#""[3-5" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[3-]", r#""[3-]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[-5]", r#""[-5]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[3--5]", r#""[3--5]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[+3-5]", r#""[+3-5]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[ 3-5]", r#""[ 3-5]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("[a-5]", r#""[a-5]" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            ("", r#""" should match '^\[[0-9]+-[0-9]+\]$'"#),
+            (
+                "[1-4294967296]",
+                r#"strconv.ParseUint: parsing "4294967296": value out of range"#,
+            ),
+            (
+                "[5-3]",
+                "max value 3 cannot be less than min value 5 for unhealthyRange",
+            ),
+        ] {
+            assert_eq!(
+                range.parse::<UnhealthyRange>().unwrap_err().to_string(),
+                expected,
+                "{range}"
+            );
+        }
+    }
+
+    #[test]
+    fn allows_remediation_within_the_unhealthy_range() {
+        let cluster = cluster(&[]);
+        let nodes = [
+            node("healthy", ready("True", 60)),
+            node("not-ready", ready("False", 360)),
+        ];
+        let machines = [
+            machine("a", json!({}), with_node("healthy")),
+            machine("b", json!({}), with_node("not-ready")),
+            machine("c", json!({}), with_node("not-ready")),
+        ];
+        for (range, allowed, remediations_allowed) in [
+            ("[1-3]", true, 1),
+            ("[2-2]", true, 0),
+            ("[0-1]", false, 0),
+            ("[3-5]", false, 0),
+        ] {
+            let mhc = health_check(json!({"unhealthyRange": range, "maxUnhealthy": 0}));
+            let evaluation =
+                evaluate_health_check(&mhc, &cluster, &machines, &nodes, now()).unwrap();
+            assert_eq!(evaluation.remediation_allowed, allowed, "{range}");
+            assert_eq!(
+                evaluation.status.remediations_allowed,
+                Some(remediations_allowed),
+                "{range}"
+            );
+            if !allowed {
+                assert_eq!(
+                    evaluation.message,
+                    Some(format!("Remediation is not allowed, the number of not started or unhealthy machines does not fall within the range (total: 3, unhealthy: 2, unhealthyRange: {range})")),
+                );
+            }
+        }
+        let mhc = health_check(json!({"unhealthyRange": "[5-3]"}));
+        assert_eq!(
+            evaluate_health_check(&mhc, &cluster, &machines, &nodes, now())
+                .unwrap_err()
+                .to_string(),
+            "error checking if remediation is allowed: max value 3 cannot be less than min value 5 for unhealthyRange"
+        );
+        let mhc = health_check(json!({"maxUnhealthy": "x"}));
+        assert_eq!(
+            evaluate_health_check(&mhc, &cluster, &machines, &nodes, now())
+                .unwrap_err()
+                .to_string(),
+            "error checking if remediation is allowed: invalid value for IntOrString: invalid type: string is not a percentage"
+        );
+    }
+
+    #[test]
+    fn evaluates_and_requeues() {
+        let mhc = health_check(json!({"maxUnhealthy": "40%"}));
+        let cluster = cluster(&[]);
+        let machines = [
+            machine("healthy", json!({}), with_node("healthy")),
+            machine("soon", json!({}), with_node("not-ready-soon")),
+            machine("later", json!({}), with_node("unknown")),
+            machine("failed", json!({}), with_node("not-ready")),
+            machine(
+                "paused",
+                json!({"annotations": {annotations::PAUSED: ""}}),
+                with_node("healthy"),
+            ),
+            machine(
+                "skipped",
+                json!({"annotations": {annotations::MACHINE_SKIP_REMEDIATION: ""}}),
+                with_node("healthy"),
+            ),
+            machine(
+                "other-cluster",
+                json!({"labels": {labels::CLUSTER_NAME: "c2"}}),
+                with_node("healthy"),
+            ),
+        ];
+        let nodes = [
+            node("healthy", ready("True", 60)),
+            node("not-ready-soon", ready("False", 240)),
+            node("not-ready", ready("False", 360)),
+            node("unknown", ready("Unknown", 360)),
+        ];
+        let evaluation = evaluate_health_check(&mhc, &cluster, &machines, &nodes, now()).unwrap();
+        assert_eq!(
+            evaluation
+                .machines
+                .iter()
+                .map(|m| m.machine.as_str())
+                .collect::<Vec<_>>(),
+            ["healthy", "soon", "later", "failed"]
+        );
+        assert_eq!(
+            evaluation.skipped,
+            [
+                SkippedMachine {
+                    machine: "paused".to_string(),
+                    reason: "machine has paused annotation",
+                },
+                SkippedMachine {
+                    machine: "skipped".to_string(),
+                    reason: "machine has skip-remediation annotation",
+                },
+            ]
+        );
+        // the first pending Machine decides when to check again
+        assert_eq!(evaluation.requeue_after, Some(Duration::from_secs(61)));
+        // three of four Machines are not healthy, more than 40% of them
+        assert!(!evaluation.remediation_allowed);
+        assert_eq!(evaluation.to_remediate().count(), 0);
+        assert_eq!(
+            evaluation.message.as_deref(),
+            Some("Remediation is not allowed, the number of not started or unhealthy machines exceeds maxUnhealthy (total: 4, unhealthy: 1, maxUnhealthy: 40%)")
+        );
+        assert_eq!(evaluation.status.expected_machines, Some(4));
+        assert_eq!(evaluation.status.current_healthy, Some(1));
+        assert_eq!(evaluation.status.remediations_allowed, Some(0));
+        // targets are sorted by name, unlike the evaluated Machines
+        assert_eq!(
+            evaluation.status.targets.as_deref(),
+            Some(&["failed", "healthy", "later", "soon"].map(String::from)[..])
+        );
+
+        let mhc = health_check(json!({"maxUnhealthy": 3}));
+        let evaluation = evaluate_health_check(&mhc, &cluster, &machines, &nodes, now()).unwrap();
+        assert!(evaluation.remediation_allowed);
+        assert_eq!(evaluation.status.remediations_allowed, Some(0));
+        assert_eq!(
+            evaluation
+                .to_remediate()
+                .map(|m| m.machine.as_str())
+                .collect::<Vec<_>>(),
+            ["failed"]
+        );
+    }
+}
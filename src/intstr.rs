@@ -0,0 +1,235 @@
+//! Resolution of IntOrString values that may be percentages, following
+//! `GetScaledValueFromIntOrPercent` of `k8s.io/apimachinery/pkg/util/intstr`, so `maxUnhealthy`,
+//! `maxSurge`, `maxUnavailable` and `maxInFlight` are computed the same way as upstream.
+
+use std::num::IntErrorKind;
+
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+
+use crate::api::capi_machinedeployment::{
+    MachineDeploymentStrategyRemediation, MachineDeploymentStrategyRollingUpdate,
+};
+
+/// How a percentage of a total is rounded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    Up,
+    Down,
+}
+
+/// Error for an IntOrString that is neither an int nor a percentage.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum IntOrPercentError {
+    #[error("invalid value for IntOrString: invalid type: string is not a percentage")]
+    NotAPercentage(String),
+    #[error("invalid value for IntOrString: invalid value {value:?}: strconv.Atoi: parsing {number:?}: {reason}")]
+    InvalidPercentage {
+        value: String,
+        number: String,
+        /// `invalid syntax` or `value out of range`, as reported by Go.
+        reason: &'static str,
+    },
+}
+
+/// The value of an int, or the given percentage of `total`.
+pub fn scaled_value(
+    value: &IntOrString,
+    total: i32,
+    rounding: Rounding,
+) -> Result<i32, IntOrPercentError> {
+    let percent = match value {
+        IntOrString::Int(value) => return Ok(*value),
+        IntOrString::String(value) => {
+            let number = value
+                .strip_suffix('%')
+                .ok_or_else(|| IntOrPercentError::NotAPercentage(value.clone()))?;
+            number
+                .parse::<i64>()
+                .map_err(|err| IntOrPercentError::InvalidPercentage {
+                    value: value.clone(),
+                    number: number.to_string(),
+                    reason: match err.kind() {
+                        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                            "value out of range"
+                        }
+                        _ => "invalid syntax",
+                    },
+                })?
+        }
+    };
+    let scaled = percent as f64 * f64::from(total) / 100.0;
+    Ok(match rounding {
+        Rounding::Up => scaled.ceil(),
+        Rounding::Down => scaled.floor(),
+    } as i32)
+}
+
+/// Checks that an IntOrString is an int or a percentage, like the webhooks do.
+pub fn validate_int_or_percent(value: &IntOrString) -> Result<(), IntOrPercentError> {
+    scaled_value(value, 0, Rounding::Down).map(|_| ())
+}
+
+/// The number of Machines a rolling update may create above and take down below the desired
+/// replicas, like `ResolveFenceposts` upstream: `maxSurge` is rounded up and `maxUnavailable`
+/// down. They default to 1 and 0. If both resolve to 0, one Machine may be unavailable, as
+/// surging might not be possible because of quota.
+pub fn resolve_fenceposts(
+    max_surge: Option<&IntOrString>,
+    max_unavailable: Option<&IntOrString>,
+    desired: i32,
+) -> Result<(i32, i32), IntOrPercentError> {
+    let surge = match max_surge {
+        Some(max_surge) => scaled_value(max_surge, desired, Rounding::Up)?,
+        None => 1,
+    };
+    let unavailable = match max_unavailable {
+        Some(max_unavailable) => scaled_value(max_unavailable, desired, Rounding::Down)?,
+        None => 0,
+    };
+    if surge == 0 && unavailable == 0 {
+        return Ok((0, 1));
+    }
+    Ok((surge, unavailable))
+}
+
+impl MachineDeploymentStrategyRollingUpdate {
+    /// `maxSurge` and `maxUnavailable` resolved for the desired replicas, see
+    /// [`resolve_fenceposts`].
+    pub fn resolve_fenceposts(&self, desired: i32) -> Result<(i32, i32), IntOrPercentError> {
+        resolve_fenceposts(
+            self.max_surge.as_ref(),
+            self.max_unavailable.as_ref(),
+            desired,
+        )
+    }
+}
+
+impl MachineDeploymentStrategyRemediation {
+    /// How many Machines of a MachineDeployment with the given replicas may be remediated at
+    /// the same time, rounding percentages up. `None` when `maxInFlight` is not set, in which
+    /// case all unhealthy Machines are remediated at once.
+    pub fn resolve_max_in_flight(&self, replicas: i32) -> Result<Option<i32>, IntOrPercentError> {
+        self.max_in_flight
+            .as_ref()
+            .map(|max_in_flight| scaled_value(max_in_flight, replicas, Rounding::Up))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(value: i32) -> IntOrString {
+        IntOrString::Int(value)
+    }
+
+    fn string(value: &str) -> IntOrString {
+        IntOrString::String(value.to_string())
+    }
+
+    #[test]
+    fn scales_values() {
+        for (value, total, up, down) in [
+            (int(3), 10, 3, 3),
+            (int(-1), 10, -1, -1),
+            (string("50%"), 5, 3, 2),
+            (string("50%"), 4, 2, 2),
+            (string("33%"), 3, 1, 0),
+            (string("0%"), 7, 0, 0),
+            (string("100%"), 7, 7, 7),
+            (string("150%"), 3, 5, 4),
+            (string("+25%"), 10, 3, 2),
+            (string("-10%"), 5, 0, -1),
+            (string("10%"), 0, 0, 0),
+        ] {
+            assert_eq!(
+                scaled_value(&value, total, Rounding::Up),
+                Ok(up),
+                "{value:?} of {total}"
+            );
+            assert_eq!(
+                scaled_value(&value, total, Rounding::Down),
+                Ok(down),
+                "{value:?} of {total}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for (value, expected) in [
+            (
+                "5",
+                "invalid value for IntOrString: invalid type: string is not a percentage",
+            ),
+            (
+                "%",
+                "invalid value for IntOrString: invalid value \"%\": strconv.Atoi: parsing \"\": invalid syntax",
+            ),
+            (
+                "a%",
+                "invalid value for IntOrString: invalid value \"a%\": strconv.Atoi: parsing \"a\": invalid syntax",
+            ),
+            (
+                " 5%",
+                "invalid value for IntOrString: invalid value \" 5%\": strconv.Atoi: parsing \" 5\": invalid syntax",
+            ),
+            (
+                "99999999999999999999%",
+                "invalid value for IntOrString: invalid value \"99999999999999999999%\": strconv.Atoi: parsing \"99999999999999999999\": value out of range",
+            ),
+        ] {
+            let err = scaled_value(&string(value), 10, Rounding::Up).unwrap_err();
+            assert_eq!(err.to_string(), expected, "{value}");
+            assert_eq!(validate_int_or_percent(&string(value)), Err(err), "{value}");
+        }
+        assert_eq!(validate_int_or_percent(&string("10%")), Ok(()));
+        assert_eq!(validate_int_or_percent(&int(-5)), Ok(()));
+    }
+
+    #[test]
+    fn resolves_fenceposts() {
+        for (max_surge, max_unavailable, desired, expected) in [
+            (None, None, 3, (1, 0)),
+            (Some(string("25%")), Some(string("25%")), 3, (1, 0)),
+            (Some(string("25%")), Some(string("25%")), 4, (1, 1)),
+            (Some(int(2)), Some(int(1)), 3, (2, 1)),
+            (Some(string("100%")), None, 5, (5, 0)),
+            // neither may be 0, so one Machine may be unavailable
+            (Some(int(0)), Some(int(0)), 3, (0, 1)),
+            (Some(int(0)), None, 3, (0, 1)),
+            (Some(string("0%")), Some(string("10%")), 5, (0, 1)),
+            (Some(string("50%")), Some(string("50%")), 0, (0, 1)),
+        ] {
+            assert_eq!(
+                resolve_fenceposts(max_surge.as_ref(), max_unavailable.as_ref(), desired),
+                Ok(expected),
+                "{max_surge:?} {max_unavailable:?} {desired}"
+            );
+        }
+        assert!(resolve_fenceposts(Some(&string("1")), None, 3).is_err());
+        assert!(resolve_fenceposts(None, Some(&string("x%")), 3).is_err());
+    }
+
+    #[test]
+    fn resolves_max_in_flight() {
+        for (max_in_flight, replicas, expected) in [
+            (None, 3, None),
+            (Some(int(1)), 3, Some(1)),
+            (Some(string("50%")), 3, Some(2)),
+            (Some(string("10%")), 3, Some(1)),
+            (Some(string("10%")), 0, Some(0)),
+        ] {
+            let remediation = MachineDeploymentStrategyRemediation {
+                max_in_flight: max_in_flight.clone(),
+                ..Default::default()
+            };
+            assert_eq!(
+                remediation.resolve_max_in_flight(replicas),
+                Ok(expected),
+                "{max_in_flight:?} {replicas}"
+            );
+        }
+    }
+}
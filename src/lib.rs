@@ -1,3 +1,15 @@
+pub mod any_object;
 pub mod api;
+pub mod conditions;
+pub mod delete_policy;
+pub mod health_check;
+pub mod intstr;
+pub mod object;
+pub mod rollout;
+pub mod selector;
+pub mod topology;
 
+pub use any_object::AnyCapiObject;
 pub use api::*;
+pub use object::CapiObject;
+pub use selector::LabelSelectorExt;
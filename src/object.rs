@@ -0,0 +1,364 @@
+//! Accessors shared by all the Cluster API kinds, so generic code does not need a match arm per kind.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{Condition as MetaCondition, ObjectMeta};
+use kube::Resource;
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::api::capi_clusterresourceset::ClusterResourceSet;
+use crate::api::capi_clusterresourcesetbinding::ClusterResourceSetBinding;
+use crate::api::capi_extensionconfig::ExtensionConfig;
+use crate::api::capi_ipaddress::IPAddress;
+use crate::api::capi_ipaddressclaim::IPAddressClaim;
+use crate::api::capi_machine::Machine;
+use crate::api::capi_machinedeployment::MachineDeployment;
+use crate::api::capi_machinehealthcheck::MachineHealthCheck;
+use crate::api::capi_machinepool::MachinePool;
+use crate::api::capi_machineset::MachineSet;
+use crate::api::condition::Condition;
+use crate::api::well_known::{annotations, labels};
+use crate::conditions::Getter;
+
+/// Common view over the Cluster API custom resources.
+pub trait CapiObject: Resource<DynamicType = ()> {
+    /// Name of the Cluster this object belongs to.
+    ///
+    /// Taken from `spec.clusterName` for the kinds that have it, from the
+    /// `cluster.x-k8s.io/cluster-name` label otherwise. A Cluster belongs to itself.
+    fn cluster_name(&self) -> Option<&str> {
+        cluster_name_label(self.meta())
+    }
+
+    /// `status.observedGeneration`, for the kinds that report it.
+    fn observed_generation(&self) -> Option<i64> {
+        None
+    }
+
+    /// Whether the status reflects the latest spec, i.e. `status.observedGeneration`
+    /// has caught up with `metadata.generation`.
+    fn is_status_up_to_date(&self) -> bool {
+        match self.meta().generation {
+            Some(generation) => self
+                .observed_generation()
+                .is_some_and(|observed| observed >= generation),
+            None => true,
+        }
+    }
+
+    /// Whether reconciliation is paused, either for the whole Cluster or
+    /// for this object through the `cluster.x-k8s.io/paused` annotation.
+    fn is_paused(&self, cluster: &Cluster) -> bool {
+        cluster.spec.paused.unwrap_or_default() || has_paused_annotation(self.meta())
+    }
+
+    /// The v1beta1 `status.conditions`, empty for the kinds without conditions.
+    fn conditions(&self) -> &[Condition] {
+        &[]
+    }
+
+    /// The `status.v1beta2.conditions`, empty for the kinds without conditions.
+    fn v1beta2_conditions(&self) -> &[MetaCondition] {
+        &[]
+    }
+}
+
+fn cluster_name_label(meta: &ObjectMeta) -> Option<&str> {
+    meta.labels
+        .as_ref()?
+        .get(labels::CLUSTER_NAME)
+        .map(String::as_str)
+}
+
+fn has_paused_annotation(meta: &ObjectMeta) -> bool {
+    meta.annotations
+        .as_ref()
+        .is_some_and(|a| a.contains_key(annotations::PAUSED))
+}
+
+/// Delegates the condition accessors to the [`Getter`] implementation.
+macro_rules! getter_conditions {
+    () => {
+        fn conditions(&self) -> &[Condition] {
+            Getter::get_conditions(self)
+        }
+
+        fn v1beta2_conditions(&self) -> &[MetaCondition] {
+            Getter::get_v1beta2_conditions(self)
+        }
+    };
+}
+
+macro_rules! status_observed_generation {
+    () => {
+        fn observed_generation(&self) -> Option<i64> {
+            self.status.as_ref().and_then(|s| s.observed_generation)
+        }
+    };
+}
+
+impl CapiObject for Cluster {
+    fn cluster_name(&self) -> Option<&str> {
+        self.metadata.name.as_deref()
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for ClusterClass {
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for ClusterResourceSet {
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for ClusterResourceSetBinding {
+    fn cluster_name(&self) -> Option<&str> {
+        self.spec
+            .cluster_name
+            .as_deref()
+            .or_else(|| cluster_name_label(&self.metadata))
+    }
+
+    fn is_status_up_to_date(&self) -> bool {
+        true
+    }
+}
+
+impl CapiObject for ExtensionConfig {
+    fn is_status_up_to_date(&self) -> bool {
+        true
+    }
+
+    getter_conditions!();
+}
+
+impl CapiObject for IPAddress {
+    fn is_status_up_to_date(&self) -> bool {
+        true
+    }
+}
+
+impl CapiObject for IPAddressClaim {
+    fn cluster_name(&self) -> Option<&str> {
+        self.spec
+            .cluster_name
+            .as_deref()
+            .or_else(|| cluster_name_label(&self.metadata))
+    }
+
+    fn is_status_up_to_date(&self) -> bool {
+        true
+    }
+
+    getter_conditions!();
+}
+
+impl CapiObject for Machine {
+    fn cluster_name(&self) -> Option<&str> {
+        Some(&self.spec.cluster_name)
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for MachineDeployment {
+    fn cluster_name(&self) -> Option<&str> {
+        Some(&self.spec.cluster_name)
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for MachineHealthCheck {
+    fn cluster_name(&self) -> Option<&str> {
+        Some(&self.spec.cluster_name)
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for MachinePool {
+    fn cluster_name(&self) -> Option<&str> {
+        Some(&self.spec.cluster_name)
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+impl CapiObject for MachineSet {
+    fn cluster_name(&self) -> Option<&str> {
+        Some(&self.spec.cluster_name)
+    }
+
+    status_observed_generation!();
+    getter_conditions!();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn cluster(paused: bool) -> Cluster {
+        serde_json::from_value(json!({
+            "metadata": {"name": "c1", "generation": 3},
+            "spec": {"paused": paused},
+            "status": {
+                "observedGeneration": 2,
+                "conditions": [
+                    {"type": "Ready", "status": "True", "lastTransitionTime": "2024-01-01T00:00:00Z"},
+                ],
+                "v1beta2": {"conditions": [{
+                    "type": "Available",
+                    "status": "False",
+                    "reason": "NotAvailable",
+                    "message": "",
+                    "lastTransitionTime": "2024-01-01T00:00:00Z",
+                }]},
+            },
+        }))
+        .unwrap()
+    }
+
+    fn machine(metadata: serde_json::Value, status: serde_json::Value) -> Machine {
+        serde_json::from_value(json!({
+            "metadata": metadata,
+            "spec": {"clusterName": "c1", "bootstrap": {}, "infrastructureRef": {}},
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn cluster_name() {
+        assert_eq!(cluster(false).cluster_name(), Some("c1"));
+        assert_eq!(
+            machine(json!({"name": "m1"}), json!({})).cluster_name(),
+            Some("c1")
+        );
+
+        let binding = |spec: serde_json::Value, labels: serde_json::Value| {
+            serde_json::from_value::<ClusterResourceSetBinding>(json!({
+                "metadata": {"name": "c1", "labels": labels},
+                "spec": spec,
+            }))
+            .unwrap()
+        };
+        assert_eq!(
+            binding(
+                json!({"clusterName": "c2"}),
+                json!({labels::CLUSTER_NAME: "c3"})
+            )
+            .cluster_name(),
+            Some("c2")
+        );
+        assert_eq!(
+            binding(json!({}), json!({labels::CLUSTER_NAME: "c3"})).cluster_name(),
+            Some("c3")
+        );
+        assert_eq!(binding(json!({}), json!({})).cluster_name(), None);
+
+        let class: ClusterClass = serde_json::from_value(json!({
+            "metadata": {"name": "class", "labels": {labels::CLUSTER_NAME: "c1"}},
+            "spec": {},
+        }))
+        .unwrap();
+        assert_eq!(class.cluster_name(), Some("c1"));
+    }
+
+    #[test]
+    fn observed_generation() {
+        assert_eq!(cluster(false).observed_generation(), Some(2));
+        assert_eq!(
+            machine(json!({"name": "m1"}), json!({})).observed_generation(),
+            None
+        );
+        let binding: ClusterResourceSetBinding =
+            serde_json::from_value(json!({"metadata": {"name": "c1"}, "spec": {}})).unwrap();
+        assert_eq!(binding.observed_generation(), None);
+    }
+
+    #[test]
+    fn is_status_up_to_date() {
+        assert!(!cluster(false).is_status_up_to_date());
+        for (metadata, status, expected) in [
+            (json!({"name": "m1"}), json!({}), true),
+            (json!({"name": "m1", "generation": 2}), json!({}), false),
+            (
+                json!({"name": "m1", "generation": 2}),
+                json!({"observedGeneration": 1}),
+                false,
+            ),
+            (
+                json!({"name": "m1", "generation": 2}),
+                json!({"observedGeneration": 2}),
+                true,
+            ),
+            (
+                json!({"name": "m1", "generation": 2}),
+                json!({"observedGeneration": 3}),
+                true,
+            ),
+        ] {
+            assert_eq!(
+                machine(metadata.clone(), status.clone()).is_status_up_to_date(),
+                expected,
+                "{metadata} {status}"
+            );
+        }
+        // kinds without observedGeneration are always up to date
+        let binding: ClusterResourceSetBinding = serde_json::from_value(json!({
+            "metadata": {"name": "c1", "generation": 2},
+            "spec": {},
+        }))
+        .unwrap();
+        assert!(binding.is_status_up_to_date());
+    }
+
+    #[test]
+    fn is_paused() {
+        let running = machine(json!({"name": "m1"}), json!({}));
+        let paused = machine(
+            json!({"name": "m1", "annotations": {annotations::PAUSED: ""}}),
+            json!({}),
+        );
+        assert!(!running.is_paused(&cluster(false)));
+        assert!(running.is_paused(&cluster(true)));
+        assert!(paused.is_paused(&cluster(false)));
+        assert!(cluster(true).is_paused(&cluster(true)));
+    }
+
+    #[test]
+    fn conditions() {
+        let cluster = cluster(false);
+        let types = |conditions: &[Condition]| -> Vec<String> {
+            conditions.iter().map(|c| c.r#type.clone()).collect()
+        };
+        assert_eq!(types(cluster.conditions()), ["Ready"]);
+        assert_eq!(
+            cluster
+                .v1beta2_conditions()
+                .iter()
+                .map(|c| c.type_.as_str())
+                .collect::<Vec<_>>(),
+            ["Available"]
+        );
+        let machine = machine(json!({"name": "m1"}), json!({}));
+        assert!(machine.conditions().is_empty());
+        assert!(machine.v1beta2_conditions().is_empty());
+        // kinds without conditions
+        let binding: ClusterResourceSetBinding =
+            serde_json::from_value(json!({"metadata": {"name": "c1"}, "spec": {}})).unwrap();
+        assert!(binding.conditions().is_empty());
+        assert!(binding.v1beta2_conditions().is_empty());
+    }
+}
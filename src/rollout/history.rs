@@ -0,0 +1,566 @@
+//! The rollout history of MachineDeployments, with rollbacks and restarts like
+//! `clusterctl alpha rollout history|undo|restart`.
+//!
+//! Every MachineSet of a MachineDeployment is a revision, numbered by its
+//! `machinedeployment.clusters.x-k8s.io/revision` annotation. The MachineDeployment controller
+//! keeps `revisionHistoryLimit` old MachineSets, so older revisions are gone. A rollback copies
+//! the Machine template of a MachineSet back into the MachineDeployment, and a restart sets
+//! `rolloutAfter` so all Machines are replaced.
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use k8s_openapi::chrono::{DateTime, Utc};
+use kube::api::{ListParams, Patch, PatchParams, PostParams};
+use kube::{Api, Client};
+use serde_json::{json, Value};
+
+use crate::api::capi_machinedeployment::{MachineDeployment, MachineDeploymentTemplate};
+use crate::api::capi_machineset::{MachineSet, MachineSetTemplate};
+use crate::api::well_known::{annotations, labels};
+use crate::topology::field;
+
+use super::template::machine_template_up_to_date;
+
+/// The revision history limit the MachineDeployment webhook defaults to.
+pub const DEFAULT_REVISION_HISTORY_LIMIT: i32 = 1;
+
+/// The rollout history of a MachineDeployment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RolloutHistory {
+    /// The revisions, oldest first.
+    pub revisions: Vec<Revision>,
+    /// How many old MachineSets are kept.
+    pub revision_history_limit: i32,
+    /// When all Machines are replaced, if set.
+    pub rollout_after: Option<Time>,
+}
+
+/// A revision of a MachineDeployment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revision {
+    pub revision: i64,
+    pub machine_set: String,
+    /// Revisions the MachineSet had before it was rolled back to, from the
+    /// `machinedeployment.clusters.x-k8s.io/revision-history` annotation.
+    pub previous_revisions: Vec<i64>,
+    pub replicas: i32,
+    /// Whether the Machine template of the MachineSet is the one of the MachineDeployment.
+    pub current: bool,
+    pub template: Option<MachineSetTemplate>,
+    /// The changes of the template from the previous revision, none for the oldest one.
+    pub changes: Vec<TemplateChange>,
+}
+
+/// A field of a Machine template that differs between two revisions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemplateChange {
+    /// The path of the field in the template, e.g. `spec.version`.
+    pub path: String,
+    /// The value in the previous revision, if set.
+    pub from: Option<Value>,
+    /// The value in this revision, if set.
+    pub to: Option<Value>,
+}
+
+/// Error reading or changing the rollout of a MachineDeployment.
+#[derive(Debug, thiserror::Error)]
+pub enum RolloutHistoryError {
+    #[error(transparent)]
+    Kube(Box<kube::Error>),
+    #[error("revision number cannot be negative: {0}")]
+    NegativeRevision(i64),
+    #[error("unable to find specified MachineSet revision: {0}")]
+    RevisionNotFound(i64),
+    #[error("no rollout history found for MachineDeployment")]
+    NoHistory,
+    #[error("can't rollback a paused MachineDeployment: please resume {0} first")]
+    RollbackPaused(String),
+    #[error(
+        "can't restart paused MachineDeployment (run rollout resume first): MachineDeployment/{0}"
+    )]
+    RestartPaused(String),
+    #[error(
+        "can't update MachineDeployment (remove 'spec.rolloutAfter' first): MachineDeployment/{0}"
+    )]
+    RolloutAfterInFuture(String),
+    #[error("MachineSet {0} has no template")]
+    NoTemplate(String),
+    #[error("failed to copy the template of MachineSet {machine_set}: {source}")]
+    Template {
+        machine_set: String,
+        source: serde_json::Error,
+    },
+}
+
+impl From<kube::Error> for RolloutHistoryError {
+    fn from(err: kube::Error) -> Self {
+        Self::Kube(Box::new(err))
+    }
+}
+
+/// The rollout history of a MachineDeployment from its MachineSets. MachineSets without a
+/// revision annotation have revision 0; the ones with an invalid revision or that are not
+/// controlled by the MachineDeployment are ignored.
+pub fn rollout_history(
+    deployment: &MachineDeployment,
+    machine_sets: &[MachineSet],
+) -> RolloutHistory {
+    let mut revisions: Vec<(i64, &MachineSet)> = machine_sets
+        .iter()
+        .filter(|ms| is_controlled_by(ms, deployment))
+        .filter_map(|ms| Some((revision(ms)?, ms)))
+        .collect();
+    revisions.sort_by_key(|(revision, _)| *revision);
+    let mut previous: Option<Value> = None;
+    let revisions = revisions
+        .into_iter()
+        .map(|(revision, ms)| {
+            let template = template_value(ms.spec.template.as_ref());
+            let mut changes = Vec::new();
+            if let Some(previous) = &previous {
+                diff(previous, &template, "", &mut changes);
+            }
+            previous = Some(template);
+            Revision {
+                revision,
+                machine_set: ms.metadata.name.clone().unwrap_or_default(),
+                previous_revisions: ms
+                    .metadata
+                    .annotations
+                    .as_ref()
+                    .and_then(|a| a.get(annotations::REVISION_HISTORY))
+                    .map(|history| {
+                        history
+                            .split(',')
+                            .filter_map(|r| r.trim().parse().ok())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                replicas: ms.spec.replicas.unwrap_or(0),
+                current: machine_template_up_to_date(
+                    &deployment.spec.template,
+                    ms.spec.template.as_ref(),
+                ),
+                template: ms.spec.template.clone(),
+                changes,
+            }
+        })
+        .collect();
+    RolloutHistory {
+        revisions,
+        revision_history_limit: deployment
+            .spec
+            .revision_history_limit
+            .unwrap_or(DEFAULT_REVISION_HISTORY_LIMIT),
+        rollout_after: deployment.spec.rollout_after.clone(),
+    }
+}
+
+/// The MachineSet of a revision, like `findMachineDeploymentRevision` of clusterctl. Revision
+/// 0 is the one before the latest.
+pub fn find_revision(
+    to_revision: i64,
+    machine_sets: &[MachineSet],
+) -> Result<&MachineSet, RolloutHistoryError> {
+    if to_revision < 0 {
+        return Err(RolloutHistoryError::NegativeRevision(to_revision));
+    }
+    let mut latest: Option<(i64, &MachineSet)> = None;
+    let mut previous: Option<(i64, &MachineSet)> = None;
+    for ms in machine_sets {
+        let Some(revision) = revision(ms) else {
+            continue;
+        };
+        if to_revision == 0 {
+            if latest.is_none_or(|(latest, _)| latest < revision) {
+                previous = latest;
+                latest = Some((revision, ms));
+            } else if previous.is_none_or(|(previous, _)| previous < revision) {
+                previous = Some((revision, ms));
+            }
+        } else if to_revision == revision {
+            return Ok(ms);
+        }
+    }
+    if to_revision > 0 {
+        return Err(RolloutHistoryError::RevisionNotFound(to_revision));
+    }
+    previous
+        .map(|(_, ms)| ms)
+        .ok_or(RolloutHistoryError::NoHistory)
+}
+
+/// Copies the Machine template of a MachineSet into a MachineDeployment, without the
+/// `machine-template-hash` label.
+pub fn rollback(
+    deployment: &mut MachineDeployment,
+    machine_set: &MachineSet,
+) -> Result<(), RolloutHistoryError> {
+    let name = machine_set.metadata.name.clone().unwrap_or_default();
+    let template = template_value(machine_set.spec.template.as_ref());
+    if template.is_null() {
+        return Err(RolloutHistoryError::NoTemplate(name));
+    }
+    deployment.spec.template = serde_json::from_value::<MachineDeploymentTemplate>(template)
+        .map_err(|source| RolloutHistoryError::Template {
+            machine_set: name,
+            source,
+        })?;
+    Ok(())
+}
+
+/// MachineDeployment rollouts on a management cluster.
+#[derive(Clone)]
+pub struct RolloutApi {
+    deployments: Api<MachineDeployment>,
+    machine_sets: Api<MachineSet>,
+}
+
+impl RolloutApi {
+    /// Rollouts of the MachineDeployments in a namespace.
+    pub fn namespaced(client: Client, namespace: &str) -> Self {
+        Self {
+            deployments: Api::namespaced(client.clone(), namespace),
+            machine_sets: Api::namespaced(client, namespace),
+        }
+    }
+
+    /// The rollout history of a MachineDeployment.
+    pub async fn history(&self, name: &str) -> Result<RolloutHistory, RolloutHistoryError> {
+        let deployment = self.deployments.get(name).await?;
+        let machine_sets = self.machine_sets(&deployment).await?;
+        Ok(rollout_history(&deployment, &machine_sets))
+    }
+
+    /// Rolls a MachineDeployment back to a revision, or to the one before the latest with
+    /// revision 0. Paused MachineDeployments cannot be rolled back.
+    pub async fn undo(
+        &self,
+        name: &str,
+        to_revision: i64,
+    ) -> Result<MachineDeployment, RolloutHistoryError> {
+        if to_revision < 0 {
+            return Err(RolloutHistoryError::NegativeRevision(to_revision));
+        }
+        let mut deployment = self.deployments.get(name).await?;
+        if deployment.spec.paused == Some(true) {
+            return Err(RolloutHistoryError::RollbackPaused(name.to_string()));
+        }
+        let machine_sets = self.machine_sets(&deployment).await?;
+        let machine_set = find_revision(to_revision, &machine_sets)?;
+        rollback(&mut deployment, machine_set)?;
+        Ok(self
+            .deployments
+            .replace(name, &PostParams::default(), &deployment)
+            .await?)
+    }
+
+    /// Replaces all Machines of a MachineDeployment by setting `rolloutAfter` to now. Paused
+    /// MachineDeployments, and ones with `rolloutAfter` in the future, cannot be restarted.
+    pub async fn restart(&self, name: &str) -> Result<MachineDeployment, RolloutHistoryError> {
+        let now = Utc::now();
+        let deployment = self.deployments.get(name).await?;
+        check_restart(&deployment, now)?;
+        let patch = json!({ "spec": { "rolloutAfter": Time(now) } });
+        Ok(self
+            .deployments
+            .patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await?)
+    }
+
+    /// The MachineSets controlled by a MachineDeployment.
+    async fn machine_sets(
+        &self,
+        deployment: &MachineDeployment,
+    ) -> Result<Vec<MachineSet>, RolloutHistoryError> {
+        let name = deployment.metadata.name.as_deref().unwrap_or_default();
+        let params =
+            ListParams::default().labels(&format!("{}={name}", labels::MACHINE_DEPLOYMENT_NAME));
+        let machine_sets = self.machine_sets.list(&params).await?;
+        Ok(machine_sets
+            .items
+            .into_iter()
+            .filter(|ms| is_controlled_by(ms, deployment))
+            .collect())
+    }
+}
+
+/// Checks that a MachineDeployment can be restarted at `now`.
+pub fn check_restart(
+    deployment: &MachineDeployment,
+    now: DateTime<Utc>,
+) -> Result<(), RolloutHistoryError> {
+    let name = deployment.metadata.name.clone().unwrap_or_default();
+    if deployment.spec.paused == Some(true) {
+        return Err(RolloutHistoryError::RestartPaused(name));
+    }
+    if deployment
+        .spec
+        .rollout_after
+        .as_ref()
+        .is_some_and(|rollout_after| rollout_after.0 > now)
+    {
+        return Err(RolloutHistoryError::RolloutAfterInFuture(name));
+    }
+    Ok(())
+}
+
+/// The revision of a MachineSet, 0 without the annotation, `None` if it is invalid.
+fn revision(machine_set: &MachineSet) -> Option<i64> {
+    match machine_set
+        .metadata
+        .annotations
+        .as_ref()
+        .and_then(|a| a.get(annotations::REVISION))
+    {
+        Some(revision) => revision.parse().ok(),
+        None => Some(0),
+    }
+}
+
+/// Whether the controller owner reference of a MachineSet is the MachineDeployment, by UID if
+/// both have one, else by kind and name.
+fn is_controlled_by(machine_set: &MachineSet, deployment: &MachineDeployment) -> bool {
+    machine_set
+        .metadata
+        .owner_references
+        .iter()
+        .flatten()
+        .find(|owner| owner.controller == Some(true))
+        .is_some_and(|owner| match deployment.metadata.uid.as_deref() {
+            Some(uid) if !owner.uid.is_empty() => owner.uid == uid,
+            _ => {
+                owner.kind == "MachineDeployment"
+                    && Some(&owner.name) == deployment.metadata.name.as_ref()
+            }
+        })
+}
+
+/// A Machine template as JSON, without the `machine-template-hash` label.
+fn template_value(template: Option<&MachineSetTemplate>) -> Value {
+    let mut template = serde_json::to_value(template).unwrap_or_default();
+    if let Some(labels) = template
+        .pointer_mut("/metadata/labels")
+        .and_then(Value::as_object_mut)
+    {
+        labels.remove(labels::MACHINE_DEPLOYMENT_UNIQUE);
+    }
+    template
+}
+
+/// Collects the fields that differ between two templates.
+fn diff(from: &Value, to: &Value, path: &str, changes: &mut Vec<TemplateChange>) {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            for (key, value) in from {
+                let path = field::child(path, key);
+                match to.get(key) {
+                    Some(to) => diff(value, to, &path, changes),
+                    None if value.is_null() => {}
+                    None => changes.push(TemplateChange {
+                        path,
+                        from: Some(value.clone()),
+                        to: None,
+                    }),
+                }
+            }
+            for (key, value) in to {
+                if !from.contains_key(key) && !value.is_null() {
+                    changes.push(TemplateChange {
+                        path: field::child(path, key),
+                        from: None,
+                        to: Some(value.clone()),
+                    });
+                }
+            }
+        }
+        (from, to) if from != to => changes.push(TemplateChange {
+            path: path.to_string(),
+            from: (!from.is_null()).then(|| from.clone()),
+            to: (!to.is_null()).then(|| to.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2024-01-01T12:00:00Z".parse().unwrap()
+    }
+
+    fn deployment(spec: Value) -> MachineDeployment {
+        let mut deployment = json!({
+            "metadata": {"name": "md-0", "namespace": "ns", "uid": "md-uid"},
+            "spec": {
+                "clusterName": "c1",
+                "selector": {},
+                "template": template("v1.30.0"),
+            },
+        });
+        for (key, value) in spec.as_object().into_iter().flatten() {
+            deployment["spec"][key] = value.clone();
+        }
+        serde_json::from_value(deployment).unwrap()
+    }
+
+    fn template(version: &str) -> Value {
+        json!({
+            "metadata": {"labels": {labels::MACHINE_DEPLOYMENT_UNIQUE: version}},
+            "spec": {
+                "clusterName": "c1",
+                "version": version,
+                "bootstrap": {"dataSecretName": "bootstrap"},
+                "infrastructureRef": {"kind": "DockerMachineTemplate", "name": "md-0"},
+            },
+        })
+    }
+
+    fn machine_set(name: &str, revision: Option<&str>, version: &str) -> MachineSet {
+        let annotations = match revision {
+            Some(revision) => json!({annotations::REVISION: revision}),
+            None => json!({}),
+        };
+        serde_json::from_value(json!({
+            "metadata": {
+                "name": name,
+                "annotations": annotations,
+                "ownerReferences": [{
+                    "apiVersion": "cluster.x-k8s.io/v1beta1",
+                    "kind": "MachineDeployment",
+                    "name": "md-0",
+                    "uid": "md-uid",
+                    "controller": true,
+                }],
+            },
+            "spec": {
+                "clusterName": "c1",
+                "replicas": 1,
+                "selector": {},
+                "template": template(version),
+            },
+        }))
+        .unwrap()
+    }
+
+    fn name(machine_set: Result<&MachineSet, RolloutHistoryError>) -> String {
+        match machine_set {
+            Ok(ms) => ms.metadata.name.clone().unwrap(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_revisions() {
+        let machine_sets = [
+            machine_set("ms-1", Some("1"), "v1.28.0"),
+            machine_set("ms-3", Some("3"), "v1.30.0"),
+            machine_set("ms-2", Some("2"), "v1.29.0"),
+            machine_set("ms-invalid", Some("x"), "v1.29.0"),
+        ];
+        for (to_revision, expected) in [
+            // revision 0 is the one before the latest
+            (0, "ms-2"),
+            (1, "ms-1"),
+            (2, "ms-2"),
+            (3, "ms-3"),
+            (4, "unable to find specified MachineSet revision: 4"),
+            (-1, "revision number cannot be negative: -1"),
+        ] {
+            assert_eq!(
+                name(find_revision(to_revision, &machine_sets)),
+                expected,
+                "{to_revision}"
+            );
+        }
+        for (machine_sets, to_revision, expected) in [
+            (vec![], 0, "no rollout history found for MachineDeployment"),
+            (vec![], 1, "unable to find specified MachineSet revision: 1"),
+            (
+                vec![machine_set("ms-1", Some("1"), "v1.28.0")],
+                0,
+                "no rollout history found for MachineDeployment",
+            ),
+            // MachineSets without the annotation have revision 0
+            (
+                vec![
+                    machine_set("ms-1", Some("1"), "v1.28.0"),
+                    machine_set("ms-none", None, "v1.29.0"),
+                ],
+                0,
+                "ms-none",
+            ),
+        ] {
+            assert_eq!(
+                name(find_revision(to_revision, &machine_sets)),
+                expected,
+                "{machine_sets:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn checks_restarts() {
+        for (spec, expected) in [
+            (json!({}), Ok(())),
+            (json!({"paused": false}), Ok(())),
+            (json!({"rolloutAfter": "2024-01-01T11:00:00Z"}), Ok(())),
+            (json!({"rolloutAfter": "2024-01-01T12:00:00Z"}), Ok(())),
+            (
+                json!({"paused": true}),
+                Err("can't restart paused MachineDeployment (run rollout resume first): MachineDeployment/md-0"),
+            ),
+            (
+                json!({"rolloutAfter": "2024-01-01T13:00:00Z"}),
+                Err("can't update MachineDeployment (remove 'spec.rolloutAfter' first): MachineDeployment/md-0"),
+            ),
+        ] {
+            assert_eq!(
+                check_restart(&deployment(spec.clone()), now()).map_err(|err| err.to_string()),
+                expected.map_err(str::to_string),
+                "{spec}"
+            );
+        }
+    }
+
+    #[test]
+    fn rollout_history_and_rollback() {
+        let mut deployment = deployment(json!({"revisionHistoryLimit": 2}));
+        let mut other = machine_set("other", Some("4"), "v1.30.0");
+        other.metadata.owner_references.as_mut().unwrap()[0].uid = "other-uid".to_string();
+        let machine_sets = [
+            machine_set("ms-2", Some("2"), "v1.30.0"),
+            machine_set("ms-1", Some("1"), "v1.29.0"),
+            other,
+        ];
+        let history = rollout_history(&deployment, &machine_sets);
+        assert_eq!(history.revision_history_limit, 2);
+        let revisions: Vec<_> = history
+            .revisions
+            .iter()
+            .map(|r| (r.revision, r.machine_set.as_str(), r.current))
+            .collect();
+        assert_eq!(revisions, [(1, "ms-1", false), (2, "ms-2", true)]);
+        assert!(history.revisions[0].changes.is_empty());
+        assert_eq!(
+            history.revisions[1].changes,
+            [TemplateChange {
+                path: "spec.version".to_string(),
+                from: Some(json!("v1.29.0")),
+                to: Some(json!("v1.30.0")),
+            }]
+        );
+
+        rollback(&mut deployment, find_revision(1, &machine_sets).unwrap()).unwrap();
+        let template = deployment.spec.template;
+        assert_eq!(template.spec.unwrap().version.as_deref(), Some("v1.29.0"));
+        // without the machine-template-hash label of the MachineSet
+        assert!(template
+            .metadata
+            .unwrap()
+            .labels
+            .unwrap_or_default()
+            .is_empty());
+    }
+}
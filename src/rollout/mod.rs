@@ -0,0 +1,12 @@
+//! MachineDeployment rollouts, following the upstream MachineDeployment controller
+//! (`internal/controllers/machinedeployment`), and their history.
+
+pub mod history;
+pub mod simulate;
+pub mod template;
+
+pub use history::{rollout_history, RolloutApi, RolloutHistory, RolloutHistoryError};
+pub use simulate::{
+    simulate_rollout, RolloutAction, RolloutError, RolloutOptions, RolloutSimulation, RolloutStep,
+};
+pub use template::{find_new_machine_set, machine_template_up_to_date};
@@ -0,0 +1,955 @@
+//! Offline simulation of MachineDeployment rollouts, following `rolloutRolling` and
+//! `rolloutOnDelete` of the upstream MachineDeployment controller, to see how a rollout
+//! proceeds, how many Machines it needs on top of the desired replicas and how long it takes.
+//!
+//! The simulation reconciles the MachineDeployment whenever something changes, and assumes the
+//! MachineSet controller creates and deletes Machines right away. A new Machine gets its Node
+//! after the provisioning latency and is available `minReadySeconds` later; a deleted Machine
+//! is gone after the deletion latency. Machines do not fail.
+
+use std::fmt;
+use std::time::Duration;
+
+use kube::core::SelectorExt;
+
+use crate::api::capi_machinedeployment::{
+    MachineDeployment, MachineDeploymentStrategyRollingUpdateDeletePolicy,
+    MachineDeploymentStrategyType,
+};
+use crate::api::capi_machineset::{MachineSet, MachineSetDeletePolicy};
+use crate::api::well_known::annotations;
+use crate::intstr::{resolve_fenceposts, IntOrPercentError};
+use crate::selector::{LabelSelectorExt, SelectorError};
+use crate::topology::naming::{seeded_random, simple_name};
+
+use super::template::{by_creation_timestamp, machine_template_up_to_date};
+
+/// Parameters of a rollout simulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RolloutOptions {
+    /// Time from the creation of a Machine until it has a ready Node.
+    pub provisioning_latency: Duration,
+    /// Time from the deletion of a Machine until it is gone, e.g. to drain its Node. Machines
+    /// being deleted still count against the quota.
+    pub deletion_latency: Duration,
+    /// With the OnDelete strategy, how many old Machines are deleted at a time. The next ones
+    /// are deleted when all Machines are available again.
+    pub on_delete_batch: i32,
+    /// The maximum number of steps, after which the simulation stops.
+    pub max_steps: usize,
+}
+
+impl Default for RolloutOptions {
+    fn default() -> Self {
+        Self {
+            provisioning_latency: Duration::ZERO,
+            deletion_latency: Duration::ZERO,
+            on_delete_batch: 1,
+            max_steps: 1000,
+        }
+    }
+}
+
+/// What happens in a step of a rollout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RolloutAction {
+    /// The MachineDeployment controller creates the MachineSet for the new template.
+    CreateMachineSet { machine_set: String },
+    /// The MachineDeployment controller scales a MachineSet.
+    Scale {
+        machine_set: String,
+        from: i32,
+        to: i32,
+    },
+    /// With the OnDelete strategy, the MachineDeployment controller stops an old MachineSet
+    /// from creating Machines.
+    DisableMachineCreate { machine_set: String },
+    /// With the OnDelete strategy, Machines of an old MachineSet are deleted by someone else.
+    DeleteMachines { machine_set: String, count: i32 },
+}
+
+impl fmt::Display for RolloutAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateMachineSet { machine_set } => {
+                write!(f, "create MachineSet {machine_set}")
+            }
+            Self::Scale {
+                machine_set,
+                from,
+                to,
+            } => {
+                let direction = if to > from { "up" } else { "down" };
+                write!(
+                    f,
+                    "scale {direction} MachineSet {machine_set} from {from} to {to} replicas"
+                )
+            }
+            Self::DisableMachineCreate { machine_set } => {
+                write!(f, "disable machine creation of MachineSet {machine_set}")
+            }
+            Self::DeleteMachines { machine_set, count } => {
+                let plural = if *count == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "delete {count} Machine{plural} of MachineSet {machine_set}"
+                )
+            }
+        }
+    }
+}
+
+/// A step of a rollout, and the state after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RolloutStep {
+    /// Time since the start of the rollout.
+    pub at: Duration,
+    pub actions: Vec<RolloutAction>,
+    /// The replicas of the MachineSets, the old ones by creation timestamp, the new one last.
+    pub replicas: Vec<(String, i32)>,
+    /// The Machines that exist, including the ones being provisioned or deleted.
+    pub machines: i32,
+    /// The available Machines.
+    pub available: i32,
+}
+
+/// The result of a rollout simulation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RolloutSimulation {
+    /// The MachineSet with the template of the MachineDeployment, which may be created by the
+    /// rollout.
+    pub new_machine_set: String,
+    pub desired_replicas: i32,
+    pub steps: Vec<RolloutStep>,
+    /// Whether the rollout completes: all Machines are available and belong to the new
+    /// MachineSet. It does not complete if it stalls or takes more than `max_steps`.
+    pub complete: bool,
+    /// Time until the rollout completes, or until the last step if it does not.
+    pub duration: Duration,
+    /// The Machines created by the rollout.
+    pub machines_created: i32,
+    /// The most Machines existing at the same time, which is what the rollout needs in quota.
+    pub max_machines: i32,
+    /// The most Machines existing on top of the desired replicas at the same time.
+    pub extra_machines: i32,
+    /// The time Machines exist on top of the desired replicas, summed over these Machines.
+    pub extra_machine_time: Duration,
+    /// The fewest available Machines at any time.
+    pub min_available: i32,
+}
+
+/// Error simulating a rollout.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RolloutError {
+    #[error("MachineDeployment is paused")]
+    Paused,
+    #[error("unexpected deployment strategy type: {0}")]
+    UnexpectedStrategy(String),
+    #[error("spec.selector: {0}")]
+    Selector(#[from] SelectorError),
+    #[error("failed to resolve maxSurge and maxUnavailable: {0}")]
+    Fenceposts(#[from] IntOrPercentError),
+}
+
+/// Simulates the rollout of a MachineDeployment from its current MachineSets.
+///
+/// The MachineSets of the MachineDeployment are the ones in its namespace matching its
+/// selector. The new MachineSet is the oldest one whose Machine template is up to date; if there
+/// is none, the rollout creates it. Replicas default to 1, and the strategy to a RollingUpdate
+/// with `maxSurge` 1 and `maxUnavailable` 0. The Machines of a MachineSet in its status that
+/// are not ready or not available yet become so as if they had just been created.
+///
+/// Machines are deleted by the delete policy of the MachineDeployment, or else of their
+/// MachineSet: Machines without a ready Node first, then the oldest or newest ones. Random
+/// picks the newest ones, so simulations are reproducible.
+pub fn simulate_rollout(
+    deployment: &MachineDeployment,
+    machine_sets: &[MachineSet],
+    options: &RolloutOptions,
+) -> Result<RolloutSimulation, RolloutError> {
+    let spec = &deployment.spec;
+    if spec.paused == Some(true) {
+        return Err(RolloutError::Paused);
+    }
+    let desired = spec.replicas.unwrap_or(1);
+    let strategy = spec.strategy.as_ref();
+    let rolling_update = strategy.and_then(|s| s.rolling_update.as_ref());
+    let on_delete = match strategy.and_then(|s| s.r#type.as_ref()) {
+        None | Some(MachineDeploymentStrategyType::RollingUpdate) => false,
+        Some(MachineDeploymentStrategyType::OnDelete) => true,
+        Some(MachineDeploymentStrategyType::Other(other)) => {
+            return Err(RolloutError::UnexpectedStrategy(other.clone()))
+        }
+    };
+    let (max_surge, max_unavailable) = resolve_fenceposts(
+        rolling_update.and_then(|r| r.max_surge.as_ref()),
+        rolling_update.and_then(|r| r.max_unavailable.as_ref()),
+        desired,
+    )?;
+    let deployment_policy = rolling_update.and_then(|r| r.delete_policy.as_ref());
+
+    let selector = spec.selector.to_selector()?;
+    let namespace = deployment.metadata.namespace.as_deref();
+    let mut owned: Vec<&MachineSet> = machine_sets
+        .iter()
+        .filter(|ms| {
+            ms.metadata.namespace.as_deref() == namespace
+                && selector.matches(&ms.metadata.labels.clone().unwrap_or_default())
+        })
+        .collect();
+    owned.sort_by(|a, b| by_creation_timestamp(a, b));
+    let new = owned
+        .iter()
+        .position(|ms| machine_template_up_to_date(&spec.template, ms.spec.template.as_ref()));
+
+    let min_ready = Duration::from_secs(spec.min_ready_seconds.unwrap_or(0).max(0) as u64);
+    let mut simulator = Simulator {
+        desired,
+        on_delete,
+        max_surge,
+        max_unavailable,
+        min_ready,
+        options,
+        now: Duration::ZERO,
+        sets: Vec::new(),
+        created: 0,
+        pending_creation: None,
+        machines_created: 0,
+    };
+    for (i, ms) in owned.iter().enumerate() {
+        if Some(i) == new {
+            continue;
+        }
+        let set = simulator.machine_set(ms, deployment_policy);
+        simulator.sets.push(set);
+    }
+    match new {
+        Some(i) => {
+            let set = simulator.machine_set(owned[i], deployment_policy);
+            simulator.sets.push(set);
+        }
+        None => {
+            let name = deployment.metadata.name.clone().unwrap_or_default();
+            let mut random = seeded_random(format!("{}/{name}", namespace.unwrap_or_default()));
+            let name = simple_name(&format!("{name}-"), &random());
+            simulator.pending_creation = Some(name.clone());
+            simulator.sets.push(SimMachineSet {
+                name,
+                replicas: 0,
+                machines: Vec::new(),
+                disable_create: false,
+                order: delete_order(deployment_policy, None),
+            });
+        }
+    }
+    simulator.sync();
+    Ok(simulator.run())
+}
+
+/// The order in which a MachineSet deletes healthy Machines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DeleteOrder {
+    Newest,
+    Oldest,
+}
+
+fn delete_order(
+    deployment: Option<&MachineDeploymentStrategyRollingUpdateDeletePolicy>,
+    machine_set: Option<&MachineSetDeletePolicy>,
+) -> DeleteOrder {
+    let oldest = match deployment {
+        Some(policy) => *policy == MachineDeploymentStrategyRollingUpdateDeletePolicy::Oldest,
+        None => machine_set == Some(&MachineSetDeletePolicy::Oldest),
+    };
+    if oldest {
+        DeleteOrder::Oldest
+    } else {
+        DeleteOrder::Newest
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SimMachine {
+    /// Creation order, lower is older.
+    created: u64,
+    ready_at: Duration,
+    available_at: Duration,
+    deleted_at: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+struct SimMachineSet {
+    name: String,
+    replicas: i32,
+    machines: Vec<SimMachine>,
+    disable_create: bool,
+    order: DeleteOrder,
+}
+
+impl SimMachineSet {
+    fn active(&self) -> impl Iterator<Item = &SimMachine> {
+        self.machines.iter().filter(|m| m.deleted_at.is_none())
+    }
+
+    fn available(&self, now: Duration) -> i32 {
+        self.active().filter(|m| m.available_at <= now).count() as i32
+    }
+
+    /// Marks Machines as deleted, in the order the MachineSet deletes them.
+    fn delete(&mut self, count: usize, now: Duration, deleted_at: Duration) {
+        let order = self.order;
+        let mut victims: Vec<&mut SimMachine> = self
+            .machines
+            .iter_mut()
+            .filter(|m| m.deleted_at.is_none())
+            .collect();
+        victims.sort_by_key(|m| {
+            let age = match order {
+                DeleteOrder::Oldest => m.created as i128,
+                DeleteOrder::Newest => -(m.created as i128),
+            };
+            (m.ready_at <= now, age)
+        });
+        for machine in victims.into_iter().take(count) {
+            machine.deleted_at = Some(deleted_at);
+        }
+    }
+}
+
+struct Simulator<'a> {
+    desired: i32,
+    on_delete: bool,
+    max_surge: i32,
+    max_unavailable: i32,
+    min_ready: Duration,
+    options: &'a RolloutOptions,
+    now: Duration,
+    /// The old MachineSets by creation timestamp, then the new one.
+    sets: Vec<SimMachineSet>,
+    created: u64,
+    /// The name of the new MachineSet, if it does not exist yet.
+    pending_creation: Option<String>,
+    machines_created: i32,
+}
+
+impl Simulator<'_> {
+    fn machine_set(
+        &mut self,
+        ms: &MachineSet,
+        deployment_policy: Option<&MachineDeploymentStrategyRollingUpdateDeletePolicy>,
+    ) -> SimMachineSet {
+        let status = ms.status.as_ref();
+        let replicas = ms.spec.replicas.unwrap_or(0);
+        let machines = status.and_then(|s| s.replicas).unwrap_or(replicas).max(0);
+        let available = status.and_then(|s| s.available_replicas).unwrap_or(0);
+        let ready = status
+            .and_then(|s| s.ready_replicas)
+            .unwrap_or(0)
+            .max(available);
+        let machines = (0..machines)
+            .map(|i| {
+                self.created += 1;
+                let (ready_at, available_at) = if i < available {
+                    (Duration::ZERO, Duration::ZERO)
+                } else if i < ready {
+                    (Duration::ZERO, self.min_ready)
+                } else {
+                    let ready_at = self.options.provisioning_latency;
+                    (ready_at, ready_at + self.min_ready)
+                };
+                SimMachine {
+                    created: self.created,
+                    ready_at,
+                    available_at,
+                    deleted_at: None,
+                }
+            })
+            .collect();
+        SimMachineSet {
+            name: ms.metadata.name.clone().unwrap_or_default(),
+            replicas,
+            machines,
+            disable_create: ms
+                .metadata
+                .annotations
+                .as_ref()
+                .is_some_and(|a| a.contains_key(annotations::DISABLE_MACHINE_CREATE)),
+            order: delete_order(deployment_policy, ms.spec.delete_policy.as_ref()),
+        }
+    }
+
+    fn run(mut self) -> RolloutSimulation {
+        let mut steps = Vec::new();
+        let mut max_machines = self.machines();
+        let mut min_available = self.available();
+        let mut extra_machine_time = Duration::ZERO;
+        let complete = loop {
+            loop {
+                let actions = self.reconcile();
+                self.sync();
+                if actions.is_empty() {
+                    break;
+                }
+                steps.push(self.step(actions));
+                max_machines = max_machines.max(self.machines());
+                min_available = min_available.min(self.available());
+                if steps.len() >= self.options.max_steps {
+                    break;
+                }
+            }
+            if self.complete() {
+                break true;
+            }
+            if steps.len() >= self.options.max_steps {
+                break false;
+            }
+            match self.next_event() {
+                Some(next) => {
+                    let extra = (self.machines() - self.desired).max(0) as u32;
+                    extra_machine_time += (next - self.now) * extra;
+                    self.now = next;
+                    self.sync();
+                }
+                None if self.on_delete => {
+                    let actions = self.delete_old_machines();
+                    if actions.is_empty() {
+                        break false;
+                    }
+                    self.sync();
+                    steps.push(self.step(actions));
+                }
+                None => break false,
+            }
+            max_machines = max_machines.max(self.machines());
+            min_available = min_available.min(self.available());
+        };
+        RolloutSimulation {
+            new_machine_set: self.new_set().name.clone(),
+            desired_replicas: self.desired,
+            steps,
+            complete,
+            duration: self.now,
+            machines_created: self.machines_created,
+            max_machines,
+            extra_machines: (max_machines - self.desired).max(0),
+            extra_machine_time,
+            min_available,
+        }
+    }
+
+    fn new_set(&self) -> &SimMachineSet {
+        self.sets.last().expect("new MachineSet")
+    }
+
+    fn old_sets(&self) -> &[SimMachineSet] {
+        &self.sets[..self.sets.len() - 1]
+    }
+
+    fn total_replicas(&self) -> i32 {
+        self.sets.iter().map(|set| set.replicas).sum()
+    }
+
+    /// Like `TotalMachineSetsReplicaSum`: the replicas of the MachineSets, or their Machines
+    /// if there are more, e.g. because some are still being deleted.
+    fn total_replica_sum(&self) -> i32 {
+        self.sets
+            .iter()
+            .map(|set| set.replicas.max(set.machines.len() as i32))
+            .sum()
+    }
+
+    fn machines(&self) -> i32 {
+        self.sets.iter().map(|set| set.machines.len() as i32).sum()
+    }
+
+    fn available(&self) -> i32 {
+        self.sets.iter().map(|set| set.available(self.now)).sum()
+    }
+
+    fn step(&self, actions: Vec<RolloutAction>) -> RolloutStep {
+        RolloutStep {
+            at: self.now,
+            actions,
+            replicas: self
+                .sets
+                .iter()
+                .map(|set| (set.name.clone(), set.replicas))
+                .collect(),
+            machines: self.machines(),
+            available: self.available(),
+        }
+    }
+
+    fn scale(&mut self, i: usize, to: i32, actions: &mut Vec<RolloutAction>) {
+        let set = &mut self.sets[i];
+        if set.replicas != to {
+            actions.push(RolloutAction::Scale {
+                machine_set: set.name.clone(),
+                from: set.replicas,
+                to,
+            });
+            set.replicas = to;
+        }
+    }
+
+    /// One reconcile of the MachineDeployment: scale up the new MachineSet, then scale down the
+    /// old ones.
+    fn reconcile(&mut self) -> Vec<RolloutAction> {
+        let mut actions = Vec::new();
+        if let Some(name) = self.pending_creation.take() {
+            actions.push(RolloutAction::CreateMachineSet { machine_set: name });
+        }
+        self.reconcile_new_machine_set(&mut actions);
+        if self.on_delete {
+            self.reconcile_old_machine_sets_on_delete(&mut actions);
+        } else {
+            self.reconcile_old_machine_sets(&mut actions);
+        }
+        actions
+    }
+
+    /// Like `reconcileNewMachineSet` and `NewMSNewReplicas`.
+    fn reconcile_new_machine_set(&mut self, actions: &mut Vec<RolloutAction>) {
+        let new = self.sets.len() - 1;
+        let replicas = self.sets[new].replicas;
+        if replicas == self.desired {
+            return;
+        }
+        if replicas > self.desired {
+            self.scale(new, self.desired, actions);
+            return;
+        }
+        let total = self.total_replica_sum();
+        let target = if self.on_delete {
+            if total >= self.desired {
+                replicas
+            } else {
+                replicas + self.desired - total
+            }
+        } else {
+            let max_total = self.desired + self.max_surge;
+            if total >= max_total {
+                replicas
+            } else {
+                replicas + (max_total - total).min(self.desired - replicas)
+            }
+        };
+        self.scale(new, target, actions);
+    }
+
+    /// Like `reconcileOldMachineSets`: old MachineSets are first scaled down by their
+    /// unavailable Machines, then by what `maxUnavailable` allows.
+    fn reconcile_old_machine_sets(&mut self, actions: &mut Vec<RolloutAction>) {
+        let old_replicas: i32 = self.old_sets().iter().map(|set| set.replicas).sum();
+        if old_replicas == 0 {
+            return;
+        }
+        let max_unavailable = if self.desired == 0 {
+            0
+        } else {
+            self.max_unavailable.min(self.desired)
+        };
+        let min_available = self.desired - max_unavailable;
+        let new = self.new_set();
+        let new_unavailable = new.replicas - new.available(self.now);
+        let max_scaled_down = self.total_replicas() - min_available - new_unavailable;
+        if max_scaled_down <= 0 {
+            return;
+        }
+
+        // cleanupUnhealthyReplicas
+        let old = self.sets.len() - 1;
+        let mut scaled_down = 0;
+        for i in 0..old {
+            if scaled_down >= max_scaled_down {
+                break;
+            }
+            let replicas = self.sets[i].replicas;
+            let available = self.sets[i].available(self.now);
+            if replicas == 0 || replicas <= available {
+                continue;
+            }
+            let count = (max_scaled_down - scaled_down).min(replicas - available);
+            self.scale(i, replicas - count, actions);
+            scaled_down += count;
+        }
+
+        // scaleDownOldMachineSetsForRollingUpdate
+        let available = self.available();
+        if available <= min_available {
+            return;
+        }
+        let max_scaled_down = available - min_available;
+        let mut scaled_down = 0;
+        for i in 0..old {
+            if scaled_down >= max_scaled_down {
+                break;
+            }
+            let replicas = self.sets[i].replicas;
+            if replicas == 0 {
+                continue;
+            }
+            let count = replicas.min(max_scaled_down - scaled_down);
+            self.scale(i, replicas - count, actions);
+            scaled_down += count;
+        }
+    }
+
+    /// Like `reconcileOldMachineSetsOnDelete`: old MachineSets stop creating Machines and are
+    /// scaled down to the Machines that are not being deleted.
+    fn reconcile_old_machine_sets_on_delete(&mut self, actions: &mut Vec<RolloutAction>) {
+        let old_replicas: i32 = self.old_sets().iter().map(|set| set.replicas).sum();
+        if old_replicas == 0 {
+            return;
+        }
+        let mut scale_down = self.total_replicas() - self.desired;
+        for i in 0..self.sets.len() - 1 {
+            let set = &mut self.sets[i];
+            if set.replicas <= 0 {
+                continue;
+            }
+            if !set.disable_create {
+                set.disable_create = true;
+                actions.push(RolloutAction::DisableMachineCreate {
+                    machine_set: set.name.clone(),
+                });
+            }
+            let mut replicas = set.active().count() as i32;
+            scale_down = (scale_down - (set.replicas - replicas)).max(0);
+            if scale_down > 0 && replicas > 0 {
+                let reduced = (replicas - scale_down).max(0);
+                scale_down -= replicas - reduced;
+                replicas = reduced;
+            }
+            self.scale(i, replicas, actions);
+        }
+    }
+
+    /// With the OnDelete strategy, deletes the next Machines of the old MachineSets.
+    fn delete_old_machines(&mut self) -> Vec<RolloutAction> {
+        let mut actions = Vec::new();
+        let mut remaining = self.options.on_delete_batch.max(1);
+        let now = self.now;
+        let deleted_at = now + self.options.deletion_latency;
+        let old = self.sets.len() - 1;
+        for set in &mut self.sets[..old] {
+            if remaining == 0 {
+                break;
+            }
+            let count = (set.active().count() as i32).min(remaining);
+            if count == 0 {
+                continue;
+            }
+            set.delete(count as usize, now, deleted_at);
+            remaining -= count;
+            actions.push(RolloutAction::DeleteMachines {
+                machine_set: set.name.clone(),
+                count,
+            });
+        }
+        actions
+    }
+
+    /// The MachineSet controller creating and deleting Machines to match the replicas, and
+    /// deleted Machines going away.
+    fn sync(&mut self) {
+        let now = self.now;
+        let deleted_at = now + self.options.deletion_latency;
+        for set in &mut self.sets {
+            let active = set.active().count() as i32;
+            if active < set.replicas && !set.disable_create {
+                for _ in active..set.replicas {
+                    self.created += 1;
+                    self.machines_created += 1;
+                    let ready_at = now + self.options.provisioning_latency;
+                    set.machines.push(SimMachine {
+                        created: self.created,
+                        ready_at,
+                        available_at: ready_at + self.min_ready,
+                        deleted_at: None,
+                    });
+                }
+            } else if active > set.replicas {
+                set.delete((active - set.replicas) as usize, now, deleted_at);
+            }
+            set.machines
+                .retain(|m| m.deleted_at.is_none_or(|deleted_at| deleted_at > now));
+        }
+    }
+
+    /// The next time a Machine gets ready, available or gone.
+    fn next_event(&self) -> Option<Duration> {
+        self.sets
+            .iter()
+            .flat_map(|set| &set.machines)
+            .flat_map(|m| match m.deleted_at {
+                Some(deleted_at) => vec![deleted_at],
+                None => vec![m.ready_at, m.available_at],
+            })
+            .filter(|t| *t > self.now)
+            .min()
+    }
+
+    fn complete(&self) -> bool {
+        let new = self.new_set();
+        self.pending_creation.is_none()
+            && new.replicas == self.desired
+            && new.machines.len() as i32 == self.desired
+            && new.available(self.now) == self.desired
+            && self
+                .old_sets()
+                .iter()
+                .all(|set| set.replicas == 0 && set.machines.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn template(version: &str) -> serde_json::Value {
+        json!({
+            "metadata": {"labels": {"app": "md-0"}},
+            "spec": {
+                "clusterName": "c1",
+                "version": version,
+                "bootstrap": {"dataSecretName": "bootstrap"},
+                "infrastructureRef": {
+                    "apiVersion": "infrastructure.cluster.x-k8s.io/v1beta1",
+                    "kind": "DockerMachineTemplate",
+                    "name": format!("md-0-{version}"),
+                },
+            },
+        })
+    }
+
+    fn deployment(strategy: serde_json::Value) -> MachineDeployment {
+        serde_json::from_value(json!({
+            "apiVersion": "cluster.x-k8s.io/v1beta1",
+            "kind": "MachineDeployment",
+            "metadata": {"name": "md-0", "namespace": "ns"},
+            "spec": {
+                "clusterName": "c1",
+                "replicas": 3,
+                "selector": {"matchLabels": {"app": "md-0"}},
+                "strategy": strategy,
+                "template": template("v1.30.0"),
+            },
+        }))
+        .unwrap()
+    }
+
+    fn machine_set(name: &str, version: &str, replicas: i32) -> MachineSet {
+        serde_json::from_value(json!({
+            "apiVersion": "cluster.x-k8s.io/v1beta1",
+            "kind": "MachineSet",
+            "metadata": {
+                "name": name,
+                "namespace": "ns",
+                "labels": {"app": "md-0"},
+                "creationTimestamp": "2024-01-01T00:00:00Z",
+            },
+            "spec": {
+                "clusterName": "c1",
+                "replicas": replicas,
+                "selector": {"matchLabels": {"app": "md-0"}},
+                "template": template(version),
+            },
+            "status": {
+                "replicas": replicas,
+                "readyReplicas": replicas,
+                "availableReplicas": replicas,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn steps(simulation: &RolloutSimulation) -> Vec<String> {
+        simulation
+            .steps
+            .iter()
+            .map(|step| {
+                let actions: Vec<String> = step.actions.iter().map(|a| a.to_string()).collect();
+                let replicas: Vec<String> = step
+                    .replicas
+                    .iter()
+                    .map(|(name, replicas)| format!("{name}={replicas}"))
+                    .collect();
+                format!(
+                    "{}s: {} [{}] {}/{}",
+                    step.at.as_secs(),
+                    actions.join(", "),
+                    replicas.join(" "),
+                    step.available,
+                    step.machines,
+                )
+            })
+            .collect()
+    }
+
+    fn options() -> RolloutOptions {
+        RolloutOptions {
+            provisioning_latency: Duration::from_secs(60),
+            deletion_latency: Duration::from_secs(30),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rolling_update() {
+        let deployment = deployment(json!({
+            "type": "RollingUpdate",
+            "rollingUpdate": {"maxSurge": 1, "maxUnavailable": 0},
+        }));
+        let old = machine_set("md-0-old", "v1.29.0", 3);
+        let simulation = simulate_rollout(&deployment, &[old], &options()).unwrap();
+        assert_eq!(
+            steps(&simulation),
+            [
+                "0s: create MachineSet md-0-v7h94, scale up MachineSet md-0-v7h94 from 0 to 1 replicas [md-0-old=3 md-0-v7h94=1] 3/4",
+                "60s: scale down MachineSet md-0-old from 3 to 2 replicas [md-0-old=2 md-0-v7h94=1] 3/4",
+                // the new MachineSet is scaled up once the old Machine is gone
+                "90s: scale up MachineSet md-0-v7h94 from 1 to 2 replicas [md-0-old=2 md-0-v7h94=2] 3/4",
+                "150s: scale down MachineSet md-0-old from 2 to 1 replicas [md-0-old=1 md-0-v7h94=2] 3/4",
+                "180s: scale up MachineSet md-0-v7h94 from 2 to 3 replicas [md-0-old=1 md-0-v7h94=3] 3/4",
+                "240s: scale down MachineSet md-0-old from 1 to 0 replicas [md-0-old=0 md-0-v7h94=3] 3/4",
+            ]
+        );
+        assert_eq!(
+            RolloutSimulation {
+                steps: Vec::new(),
+                ..simulation
+            },
+            RolloutSimulation {
+                new_machine_set: "md-0-v7h94".to_string(),
+                desired_replicas: 3,
+                steps: Vec::new(),
+                complete: true,
+                // the last old Machine is gone after the deletion latency
+                duration: Duration::from_secs(270),
+                machines_created: 3,
+                max_machines: 4,
+                extra_machines: 1,
+                extra_machine_time: Duration::from_secs(270),
+                min_available: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn rolling_update_from_an_up_to_date_machine_set() {
+        let deployment = deployment(json!({}));
+        let machine_sets = [
+            machine_set("md-0-new", "v1.30.0", 1),
+            machine_set("md-0-old", "v1.29.0", 2),
+        ];
+        let simulation = simulate_rollout(&deployment, &machine_sets, &options()).unwrap();
+        assert_eq!(simulation.new_machine_set, "md-0-new");
+        assert_eq!(
+            steps(&simulation),
+            [
+                "0s: scale up MachineSet md-0-new from 1 to 2 replicas [md-0-old=2 md-0-new=2] 3/4",
+                "60s: scale down MachineSet md-0-old from 2 to 1 replicas [md-0-old=1 md-0-new=2] 3/4",
+                "90s: scale up MachineSet md-0-new from 2 to 3 replicas [md-0-old=1 md-0-new=3] 3/4",
+                "150s: scale down MachineSet md-0-old from 1 to 0 replicas [md-0-old=0 md-0-new=3] 3/4",
+            ]
+        );
+        assert!(simulation.complete);
+    }
+
+    #[test]
+    fn machines_being_deleted_count_against_max_surge() {
+        let deployment = deployment(json!({
+            "rollingUpdate": {"maxSurge": 1, "maxUnavailable": 0},
+        }));
+        let old = machine_set("md-0-old", "v1.29.0", 3);
+        let options = RolloutOptions {
+            deletion_latency: Duration::from_secs(600),
+            ..options()
+        };
+        let simulation = simulate_rollout(&deployment, &[old], &options).unwrap();
+        assert_eq!(
+            steps(&simulation),
+            [
+                "0s: create MachineSet md-0-v7h94, scale up MachineSet md-0-v7h94 from 0 to 1 replicas [md-0-old=3 md-0-v7h94=1] 3/4",
+                "60s: scale down MachineSet md-0-old from 3 to 2 replicas [md-0-old=2 md-0-v7h94=1] 3/4",
+                "660s: scale up MachineSet md-0-v7h94 from 1 to 2 replicas [md-0-old=2 md-0-v7h94=2] 3/4",
+                "720s: scale down MachineSet md-0-old from 2 to 1 replicas [md-0-old=1 md-0-v7h94=2] 3/4",
+                "1320s: scale up MachineSet md-0-v7h94 from 2 to 3 replicas [md-0-old=1 md-0-v7h94=3] 3/4",
+                "1380s: scale down MachineSet md-0-old from 1 to 0 replicas [md-0-old=0 md-0-v7h94=3] 3/4",
+            ]
+        );
+        assert!(simulation.complete);
+        assert_eq!(simulation.duration, Duration::from_secs(1980));
+        assert_eq!(simulation.max_machines, 4);
+        assert_eq!(simulation.extra_machines, 1);
+        assert_eq!(simulation.min_available, 3);
+    }
+
+    #[test]
+    fn on_delete() {
+        let deployment = deployment(json!({"type": "OnDelete"}));
+        let old = machine_set("md-0-old", "v1.29.0", 3);
+        let simulation = simulate_rollout(&deployment, &[old], &options()).unwrap();
+        assert_eq!(
+            steps(&simulation),
+            [
+                "0s: create MachineSet md-0-v7h94, disable machine creation of MachineSet md-0-old [md-0-old=3 md-0-v7h94=0] 3/3",
+                "0s: delete 1 Machine of MachineSet md-0-old [md-0-old=3 md-0-v7h94=0] 2/3",
+                "0s: scale down MachineSet md-0-old from 3 to 2 replicas [md-0-old=2 md-0-v7h94=0] 2/3",
+                "30s: scale up MachineSet md-0-v7h94 from 0 to 1 replicas [md-0-old=2 md-0-v7h94=1] 2/3",
+                "90s: delete 1 Machine of MachineSet md-0-old [md-0-old=2 md-0-v7h94=1] 2/3",
+                "90s: scale down MachineSet md-0-old from 2 to 1 replicas [md-0-old=1 md-0-v7h94=1] 2/3",
+                "120s: scale up MachineSet md-0-v7h94 from 1 to 2 replicas [md-0-old=1 md-0-v7h94=2] 2/3",
+                "180s: delete 1 Machine of MachineSet md-0-old [md-0-old=1 md-0-v7h94=2] 2/3",
+                "180s: scale down MachineSet md-0-old from 1 to 0 replicas [md-0-old=0 md-0-v7h94=2] 2/3",
+                "210s: scale up MachineSet md-0-v7h94 from 2 to 3 replicas [md-0-old=0 md-0-v7h94=3] 2/3",
+            ]
+        );
+        assert_eq!(
+            RolloutSimulation {
+                steps: Vec::new(),
+                ..simulation
+            },
+            RolloutSimulation {
+                new_machine_set: "md-0-v7h94".to_string(),
+                desired_replicas: 3,
+                steps: Vec::new(),
+                complete: true,
+                duration: Duration::from_secs(270),
+                machines_created: 3,
+                max_machines: 3,
+                extra_machines: 0,
+                extra_machine_time: Duration::ZERO,
+                min_available: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let old = machine_set("md-0-old", "v1.29.0", 3);
+        let mut deployment = deployment(json!({}));
+        deployment.spec.paused = Some(true);
+        assert_eq!(
+            simulate_rollout(&deployment, std::slice::from_ref(&old), &options()),
+            Err(RolloutError::Paused)
+        );
+        let deployment = self::deployment(json!({"type": "Recreate"}));
+        assert_eq!(
+            simulate_rollout(&deployment, std::slice::from_ref(&old), &options())
+                .unwrap_err()
+                .to_string(),
+            "unexpected deployment strategy type: Recreate"
+        );
+        let deployment = self::deployment(json!({"rollingUpdate": {"maxSurge": "1"}}));
+        assert_eq!(
+            simulate_rollout(&deployment, &[old], &options())
+                .unwrap_err()
+                .to_string(),
+            "failed to resolve maxSurge and maxUnavailable: invalid value for IntOrString: invalid type: string is not a percentage"
+        );
+    }
+}
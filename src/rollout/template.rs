@@ -0,0 +1,76 @@
+//! Comparison of the Machine templates of a MachineDeployment and its MachineSets, following
+//! `MachineTemplateUpToDate` and `FindNewMachineSet` of the upstream `mdutil` package.
+
+use serde_json::Value;
+
+use crate::api::capi_machinedeployment::{MachineDeployment, MachineDeploymentTemplate};
+use crate::api::capi_machineset::{MachineSet, MachineSetTemplate};
+
+/// Fields of the Machine template spec that can change without a rollout.
+const IN_PLACE_FIELDS: &[&str] = &[
+    "nodeDrainTimeout",
+    "nodeDeletionTimeout",
+    "nodeVolumeDetachTimeout",
+];
+
+/// Whether the Machine template of a MachineSet matches the one of a MachineDeployment, so its
+/// Machines do not have to be replaced. Labels, annotations and the node timeouts are ignored,
+/// and the templates and bootstrap configs are compared by group, kind and name, ignoring the
+/// API version.
+pub fn machine_template_up_to_date(
+    deployment: &MachineDeploymentTemplate,
+    machine_set: Option<&MachineSetTemplate>,
+) -> bool {
+    let deployment = rollout_fields(serde_json::to_value(&deployment.spec).unwrap_or_default());
+    let machine_set = rollout_fields(
+        machine_set
+            .and_then(|template| serde_json::to_value(&template.spec).ok())
+            .unwrap_or_default(),
+    );
+    deployment == machine_set
+}
+
+/// The MachineSet with the Machine template of the MachineDeployment, the oldest one if
+/// several match. `None` if the MachineDeployment needs a new MachineSet.
+pub fn find_new_machine_set<'a>(
+    deployment: &MachineDeployment,
+    machine_sets: &'a [MachineSet],
+) -> Option<&'a MachineSet> {
+    let mut machine_sets: Vec<_> = machine_sets.iter().collect();
+    machine_sets.sort_by(|a, b| by_creation_timestamp(a, b));
+    machine_sets.into_iter().find(|ms| {
+        machine_template_up_to_date(&deployment.spec.template, ms.spec.template.as_ref())
+    })
+}
+
+/// Orders MachineSets by creation timestamp, then name, like `MachineSetsByCreationTimestamp`.
+pub(crate) fn by_creation_timestamp(a: &MachineSet, b: &MachineSet) -> std::cmp::Ordering {
+    let key = |ms: &MachineSet| {
+        (
+            ms.metadata.creation_timestamp.as_ref().map(|t| t.0),
+            ms.metadata.name.clone(),
+        )
+    };
+    key(a).cmp(&key(b))
+}
+
+fn rollout_fields(mut spec: Value) -> Value {
+    if let Some(fields) = spec.as_object_mut() {
+        for field in IN_PLACE_FIELDS {
+            fields.remove(*field);
+        }
+    }
+    for path in [
+        "/infrastructureRef/apiVersion",
+        "/bootstrap/configRef/apiVersion",
+    ] {
+        if let Some(api_version) = spec.pointer_mut(path) {
+            let group = match api_version.as_str().and_then(|v| v.rsplit_once('/')) {
+                Some((group, _)) => group.to_string(),
+                None => String::new(),
+            };
+            *api_version = group.into();
+        }
+    }
+    spec
+}
@@ -0,0 +1,379 @@
+//! Label selector evaluation for the generated copies of metav1.LabelSelector, e.g. to find
+//! the Machines belonging to a MachineSet or watched by a MachineHealthCheck.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, LabelSelectorRequirement};
+use kube::api::ListParams;
+use kube::core::{Expression, Selector, SelectorExt};
+
+use crate::api::capi_clusterresourceset::ClusterResourceSetClusterSelector;
+use crate::api::capi_extensionconfig::ExtensionConfigNamespaceSelector;
+use crate::api::capi_machinedeployment::MachineDeploymentSelector;
+use crate::api::capi_machinehealthcheck::MachineHealthCheckSelector;
+use crate::api::capi_machineset::MachineSetSelector;
+
+/// Operator of a label selector requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LabelSelectorOperator {
+    In,
+    NotIn,
+    Exists,
+    DoesNotExist,
+}
+
+impl LabelSelectorOperator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::In => "In",
+            Self::NotIn => "NotIn",
+            Self::Exists => "Exists",
+            Self::DoesNotExist => "DoesNotExist",
+        }
+    }
+}
+
+impl fmt::Display for LabelSelectorOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error for an operator that is not one of `In`, `NotIn`, `Exists` and `DoesNotExist`.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{0:?} is not a valid label selector operator")]
+pub struct ParseOperatorError(pub String);
+
+impl FromStr for LabelSelectorOperator {
+    type Err = ParseOperatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "In" => Ok(Self::In),
+            "NotIn" => Ok(Self::NotIn),
+            "Exists" => Ok(Self::Exists),
+            "DoesNotExist" => Ok(Self::DoesNotExist),
+            _ => Err(ParseOperatorError(s.to_string())),
+        }
+    }
+}
+
+/// Error for a label selector requirement that cannot be evaluated.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SelectorError {
+    #[error("matchExpressions[{index}].operator: {source}")]
+    InvalidOperator {
+        index: usize,
+        #[source]
+        source: ParseOperatorError,
+    },
+    #[error("matchExpressions[{index}].values: for '{operator}' operator on key {key:?}, values set can't be empty")]
+    MissingValues {
+        index: usize,
+        key: String,
+        operator: LabelSelectorOperator,
+    },
+    #[error("matchExpressions[{index}].values: for '{operator}' operator on key {key:?}, values set must be empty")]
+    UnexpectedValues {
+        index: usize,
+        key: String,
+        operator: LabelSelectorOperator,
+    },
+    #[error("an empty selector selects nothing")]
+    SelectsNothing,
+}
+
+/// Evaluation of a label selector, shared by all the generated selector types.
+///
+/// Follows `metav1.LabelSelectorAsSelector`: an empty selector selects everything,
+/// `matchLabels` and `matchExpressions` are ANDed. The exception is the cluster selector of a
+/// ClusterResourceSet, which selects nothing when empty like the upstream controller. A `kube`
+/// Selector cannot express that, so [`Self::to_selector`], [`Self::to_selector_string`] and
+/// [`Self::to_list_params`] fail with [`SelectorError::SelectsNothing`] for it, while
+/// [`Self::matches`] matches no labels.
+pub trait LabelSelectorExt {
+    /// Whether an empty selector selects nothing rather than everything.
+    const EMPTY_SELECTS_NOTHING: bool = false;
+
+    /// The selector as a `k8s_openapi` LabelSelector.
+    fn to_label_selector(&self) -> LabelSelector;
+
+    /// Whether the selector has neither `matchLabels` nor `matchExpressions`.
+    fn is_empty(&self) -> bool {
+        let selector = self.to_label_selector();
+        selector.match_labels.as_ref().is_none_or(|l| l.is_empty())
+            && selector
+                .match_expressions
+                .as_ref()
+                .is_none_or(|e| e.is_empty())
+    }
+
+    /// Whether the selector selects nothing, see [`Self::EMPTY_SELECTS_NOTHING`].
+    fn selects_nothing(&self) -> bool {
+        Self::EMPTY_SELECTS_NOTHING && self.is_empty()
+    }
+
+    /// The selector as a `kube` Selector, validating operators and values. Fails with
+    /// [`SelectorError::SelectsNothing`] if the selector selects nothing.
+    fn to_selector(&self) -> Result<Selector, SelectorError> {
+        if self.selects_nothing() {
+            return Err(SelectorError::SelectsNothing);
+        }
+        let LabelSelector {
+            match_expressions,
+            match_labels,
+        } = self.to_label_selector();
+        let mut selector: Selector = match_labels.unwrap_or_default().into_iter().collect();
+        for (index, requirement) in match_expressions.into_iter().flatten().enumerate() {
+            selector.extend(expression(index, requirement)?);
+        }
+        Ok(selector)
+    }
+
+    /// Whether the labels are selected.
+    fn matches(&self, labels: &BTreeMap<String, String>) -> Result<bool, SelectorError> {
+        if self.selects_nothing() {
+            return Ok(false);
+        }
+        Ok(self.to_selector()?.matches(labels))
+    }
+
+    /// The selector in the string form of the `labelSelector` query parameter.
+    fn to_selector_string(&self) -> Result<String, SelectorError> {
+        Ok(self.to_selector()?.to_string())
+    }
+
+    /// List parameters selecting the objects matched by the selector.
+    fn to_list_params(&self) -> Result<ListParams, SelectorError> {
+        Ok(ListParams::default().labels_from(&self.to_selector()?))
+    }
+}
+
+fn expression(
+    index: usize,
+    requirement: LabelSelectorRequirement,
+) -> Result<Expression, SelectorError> {
+    let operator = requirement
+        .operator
+        .parse()
+        .map_err(|source| SelectorError::InvalidOperator { index, source })?;
+    let key = requirement.key;
+    let values = requirement.values.unwrap_or_default();
+    match operator {
+        LabelSelectorOperator::In | LabelSelectorOperator::NotIn if values.is_empty() => {
+            Err(SelectorError::MissingValues {
+                index,
+                key,
+                operator,
+            })
+        }
+        LabelSelectorOperator::Exists | LabelSelectorOperator::DoesNotExist
+            if !values.is_empty() =>
+        {
+            Err(SelectorError::UnexpectedValues {
+                index,
+                key,
+                operator,
+            })
+        }
+        LabelSelectorOperator::In => Ok(Expression::In(key, values.into_iter().collect())),
+        LabelSelectorOperator::NotIn => Ok(Expression::NotIn(key, values.into_iter().collect())),
+        LabelSelectorOperator::Exists => Ok(Expression::Exists(key)),
+        LabelSelectorOperator::DoesNotExist => Ok(Expression::DoesNotExist(key)),
+    }
+}
+
+impl LabelSelectorExt for LabelSelector {
+    fn to_label_selector(&self) -> LabelSelector {
+        self.clone()
+    }
+}
+
+macro_rules! impl_label_selector {
+    ($($selector:ty $({ empty_selects_nothing: $nothing:expr })?),* $(,)?) => {
+        $(
+            impl LabelSelectorExt for $selector {
+                $(const EMPTY_SELECTS_NOTHING: bool = $nothing;)?
+
+                fn to_label_selector(&self) -> LabelSelector {
+                    LabelSelector {
+                        match_expressions: self.match_expressions.as_ref().map(|expressions| {
+                            expressions
+                                .iter()
+                                .map(|e| LabelSelectorRequirement {
+                                    key: e.key.clone(),
+                                    operator: e.operator.clone(),
+                                    values: e.values.clone(),
+                                })
+                                .collect()
+                        }),
+                        match_labels: self.match_labels.clone(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_label_selector!(
+    ClusterResourceSetClusterSelector {
+        empty_selects_nothing: true
+    },
+    ExtensionConfigNamespaceSelector,
+    MachineDeploymentSelector,
+    MachineHealthCheckSelector,
+    MachineSetSelector,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::capi_clusterresourceset::ClusterResourceSetClusterSelectorMatchExpressions;
+
+    fn labels(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn requirement(key: &str, operator: &str, values: &[&str]) -> LabelSelectorRequirement {
+        LabelSelectorRequirement {
+            key: key.into(),
+            operator: operator.into(),
+            values: (!values.is_empty()).then(|| values.iter().map(|v| v.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn empty_selectors() {
+        let any = labels(&[("env", "prod")]);
+        assert!(LabelSelector::default().matches(&any).unwrap());
+        assert!(MachineSetSelector::default().matches(&any).unwrap());
+
+        let crs = ClusterResourceSetClusterSelector::default();
+        assert!(crs.selects_nothing());
+        assert!(!crs.matches(&any).unwrap());
+        assert!(!crs.matches(&BTreeMap::new()).unwrap());
+        assert_eq!(crs.to_selector(), Err(SelectorError::SelectsNothing));
+        assert_eq!(crs.to_selector_string(), Err(SelectorError::SelectsNothing));
+        assert!(matches!(
+            crs.to_list_params(),
+            Err(SelectorError::SelectsNothing)
+        ));
+        assert_eq!(
+            MachineSetSelector::default().to_selector_string(),
+            Ok(String::new())
+        );
+        let crs = ClusterResourceSetClusterSelector {
+            match_labels: Some(BTreeMap::new()),
+            match_expressions: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(!crs.matches(&any).unwrap());
+
+        let crs = ClusterResourceSetClusterSelector {
+            match_expressions: Some(vec![ClusterResourceSetClusterSelectorMatchExpressions {
+                key: "env".into(),
+                operator: "Exists".into(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert!(!crs.selects_nothing());
+        assert!(crs.matches(&any).unwrap());
+        assert_eq!(crs.to_selector_string(), Ok("env".to_string()));
+        assert_eq!(
+            crs.to_list_params().unwrap().label_selector.as_deref(),
+            Some("env")
+        );
+    }
+
+    #[test]
+    fn matches_labels_and_expressions() {
+        let selector = LabelSelector {
+            match_labels: Some(labels(&[("app", "web")])),
+            match_expressions: Some(vec![
+                requirement("env", "In", &["prod", "staging"]),
+                requirement("tier", "NotIn", &["db"]),
+                requirement("zone", "Exists", &[]),
+                requirement("legacy", "DoesNotExist", &[]),
+            ]),
+        };
+        for (pairs, expected) in [
+            (&[("app", "web"), ("env", "prod"), ("zone", "a")][..], true),
+            (
+                &[
+                    ("app", "web"),
+                    ("env", "staging"),
+                    ("zone", "a"),
+                    ("tier", "fe"),
+                ],
+                true,
+            ),
+            (&[("app", "api"), ("env", "prod"), ("zone", "a")], false),
+            (&[("app", "web"), ("env", "dev"), ("zone", "a")], false),
+            (
+                &[
+                    ("app", "web"),
+                    ("env", "prod"),
+                    ("zone", "a"),
+                    ("tier", "db"),
+                ],
+                false,
+            ),
+            (&[("app", "web"), ("env", "prod")], false),
+            (
+                &[
+                    ("app", "web"),
+                    ("env", "prod"),
+                    ("zone", "a"),
+                    ("legacy", ""),
+                ],
+                false,
+            ),
+        ] {
+            assert_eq!(
+                selector.matches(&labels(pairs)).unwrap(),
+                expected,
+                "{pairs:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_requirements() {
+        let matches = |requirement| {
+            LabelSelector {
+                match_expressions: Some(vec![requirement]),
+                ..Default::default()
+            }
+            .matches(&BTreeMap::new())
+        };
+        assert_eq!(
+            matches(requirement("env", "Equals", &["a"])),
+            Err(SelectorError::InvalidOperator {
+                index: 0,
+                source: ParseOperatorError("Equals".into()),
+            })
+        );
+        assert_eq!(
+            matches(requirement("env", "In", &[])),
+            Err(SelectorError::MissingValues {
+                index: 0,
+                key: "env".into(),
+                operator: LabelSelectorOperator::In,
+            })
+        );
+        assert_eq!(
+            matches(requirement("env", "Exists", &["a"])),
+            Err(SelectorError::UnexpectedValues {
+                index: 0,
+                key: "env".into(),
+                operator: LabelSelectorOperator::Exists,
+            })
+        );
+    }
+}
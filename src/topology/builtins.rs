@@ -0,0 +1,596 @@
+//! The `builtin` variable passed to ClusterClass patches, following the `Builtins` types of
+//! `internal/controllers/topology/cluster/patches/variables` and how the topology controller
+//! fills them in for the Cluster, its control plane, MachineDeployments and MachinePools.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::capi_cluster::{
+    Cluster, ClusterTopologyControlPlane, ClusterTopologyWorkersMachineDeployments,
+    ClusterTopologyWorkersMachinePools,
+};
+use crate::api::capi_machinedeployment::MachineDeployment;
+use crate::api::capi_machinepool::MachinePool;
+
+use super::variables::{Variables, BUILTINS_NAME};
+
+/// The builtin variables, as seen by templates under `.builtin`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Builtins {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<ClusterBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_plane: Option<ControlPlaneBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_deployment: Option<MachineDeploymentBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_pool: Option<MachinePoolBuiltins>,
+}
+
+/// `builtin.cluster`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub namespace: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub uid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BuiltinMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology: Option<ClusterTopologyBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<ClusterNetworkBuiltins>,
+}
+
+/// `builtin.cluster.topology`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterTopologyBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class_namespace: String,
+}
+
+/// `builtin.cluster.network`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterNetworkBuiltins {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pods: Vec<String>,
+}
+
+/// Labels and annotations from the topology, e.g. `builtin.controlPlane.metadata.labels`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BuiltinMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<BTreeMap<String, String>>,
+}
+
+/// `builtin.controlPlane`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPlaneBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BuiltinMetadata>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_template: Option<ControlPlaneMachineTemplateBuiltins>,
+}
+
+/// `builtin.controlPlane.machineTemplate`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPlaneMachineTemplateBuiltins {
+    pub infrastructure_ref: NameBuiltins,
+}
+
+/// A reference to a generated object, e.g. `builtin.machineDeployment.infrastructureRef`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct NameBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+/// `builtin.machineDeployment.bootstrap` and `builtin.machinePool.bootstrap`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineBootstrapBuiltins {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_ref: Option<NameBuiltins>,
+}
+
+/// `builtin.machineDeployment`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineDeploymentBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BuiltinMetadata>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub topology_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<MachineBootstrapBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infrastructure_ref: Option<NameBuiltins>,
+}
+
+/// `builtin.machinePool`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MachinePoolBuiltins {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BuiltinMetadata>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub class: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub topology_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<MachineBootstrapBuiltins>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infrastructure_ref: Option<NameBuiltins>,
+}
+
+/// Name of an unstructured object, e.g. a generated template.
+fn object_name(object: &serde_json::Value) -> String {
+    object["metadata"]["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Labels and annotations, unset when neither is, as the topology controller only sets the
+/// metadata builtins then.
+fn builtin_metadata(
+    labels: &Option<BTreeMap<String, String>>,
+    annotations: &Option<BTreeMap<String, String>>,
+) -> Option<BuiltinMetadata> {
+    (labels.is_some() || annotations.is_some()).then(|| BuiltinMetadata {
+        labels: labels.clone(),
+        annotations: annotations.clone(),
+    })
+}
+
+fn name_ref(object: Option<&serde_json::Value>) -> Option<NameBuiltins> {
+    object.map(|object| NameBuiltins {
+        name: object_name(object),
+    })
+}
+
+impl Builtins {
+    /// The global builtins of a Cluster with a managed topology.
+    pub fn for_cluster(cluster: &Cluster) -> Self {
+        let topology = cluster.spec.topology.as_ref();
+        let network = cluster
+            .spec
+            .cluster_network
+            .as_ref()
+            .map(|network| ClusterNetworkBuiltins {
+                service_domain: network
+                    .service_domain
+                    .clone()
+                    .filter(|domain| !domain.is_empty()),
+                services: network
+                    .services
+                    .as_ref()
+                    .map(|services| services.cidr_blocks.clone())
+                    .unwrap_or_default(),
+                pods: network
+                    .pods
+                    .as_ref()
+                    .map(|pods| pods.cidr_blocks.clone())
+                    .unwrap_or_default(),
+            });
+        let metadata = &cluster.metadata;
+        Self {
+            cluster: Some(ClusterBuiltins {
+                name: metadata.name.clone().unwrap_or_default(),
+                namespace: metadata.namespace.clone().unwrap_or_default(),
+                uid: metadata.uid.clone().unwrap_or_default(),
+                metadata: builtin_metadata(&metadata.labels, &metadata.annotations),
+                topology: topology.map(|topology| ClusterTopologyBuiltins {
+                    version: topology.version.clone(),
+                    class: topology.class.clone(),
+                    // the class is looked up in the Cluster namespace unless set
+                    class_namespace: topology
+                        .class_namespace
+                        .clone()
+                        .filter(|namespace| !namespace.is_empty())
+                        .or_else(|| metadata.namespace.clone())
+                        .unwrap_or_default(),
+                }),
+                network,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The builtins of the control plane and its InfrastructureMachineTemplate. The replicas
+    /// are only set when the topology manages them, as the topology controller does.
+    pub fn for_control_plane(
+        topology: Option<&ClusterTopologyControlPlane>,
+        control_plane: &serde_json::Value,
+        infrastructure_machine_template: Option<&serde_json::Value>,
+    ) -> Self {
+        let spec = &control_plane["spec"];
+        let replicas = topology
+            .and_then(|topology| topology.replicas)
+            .and_then(|_| spec["replicas"].as_i64());
+        Self {
+            control_plane: Some(ControlPlaneBuiltins {
+                version: spec["version"].as_str().unwrap_or_default().to_string(),
+                metadata: topology
+                    .and_then(|topology| topology.metadata.as_ref())
+                    .and_then(|metadata| builtin_metadata(&metadata.labels, &metadata.annotations)),
+                name: object_name(control_plane),
+                replicas,
+                machine_template: name_ref(infrastructure_machine_template).map(
+                    |infrastructure_ref| ControlPlaneMachineTemplateBuiltins { infrastructure_ref },
+                ),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The builtins of a MachineDeployment generated for a topology, with its bootstrap
+    /// config template and InfrastructureMachineTemplate.
+    pub fn for_machine_deployment(
+        topology: &ClusterTopologyWorkersMachineDeployments,
+        machine_deployment: &MachineDeployment,
+        bootstrap_template: Option<&serde_json::Value>,
+        infrastructure_machine_template: Option<&serde_json::Value>,
+    ) -> Self {
+        let spec = &machine_deployment.spec;
+        let version = spec
+            .template
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.version.clone())
+            .unwrap_or_default();
+        Self {
+            machine_deployment: Some(MachineDeploymentBuiltins {
+                version,
+                metadata: topology
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| builtin_metadata(&metadata.labels, &metadata.annotations)),
+                class: topology.class.clone(),
+                name: machine_deployment.metadata.name.clone().unwrap_or_default(),
+                topology_name: topology.name.clone(),
+                replicas: spec.replicas.map(i64::from),
+                bootstrap: name_ref(bootstrap_template).map(|config_ref| {
+                    MachineBootstrapBuiltins {
+                        config_ref: Some(config_ref),
+                    }
+                }),
+                infrastructure_ref: name_ref(infrastructure_machine_template),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The builtins of a MachinePool generated for a topology, with its bootstrap config and
+    /// InfrastructureMachinePool.
+    pub fn for_machine_pool(
+        topology: &ClusterTopologyWorkersMachinePools,
+        machine_pool: &MachinePool,
+        bootstrap_config: Option<&serde_json::Value>,
+        infrastructure_machine_pool: Option<&serde_json::Value>,
+    ) -> Self {
+        let spec = &machine_pool.spec;
+        let version = spec
+            .template
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.version.clone())
+            .unwrap_or_default();
+        Self {
+            machine_pool: Some(MachinePoolBuiltins {
+                version,
+                metadata: topology
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| builtin_metadata(&metadata.labels, &metadata.annotations)),
+                class: topology.class.clone(),
+                name: machine_pool.metadata.name.clone().unwrap_or_default(),
+                topology_name: topology.name.clone(),
+                replicas: spec.replicas.map(i64::from),
+                bootstrap: name_ref(bootstrap_config).map(|config_ref| MachineBootstrapBuiltins {
+                    config_ref: Some(config_ref),
+                }),
+                infrastructure_ref: name_ref(infrastructure_machine_pool),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// The builtins as variables, i.e. a map with the single `builtin` variable.
+    pub fn to_variables(&self) -> Variables {
+        let value = serde_json::to_value(self).expect("builtins serialize to JSON");
+        Variables::from([(BUILTINS_NAME.to_string(), value)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn from_json<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn to_json(builtins: &Builtins) -> serde_json::Value {
+        builtins.to_variables().remove(BUILTINS_NAME).unwrap()
+    }
+
+    #[test]
+    fn cluster() {
+        let cluster: Cluster = from_json(json!({
+            "metadata": {
+                "name": "c1",
+                "namespace": "ns",
+                "uid": "1234",
+                "labels": {"l": "v"},
+            },
+            "spec": {
+                "clusterNetwork": {
+                    "serviceDomain": "",
+                    "services": {"cidrBlocks": ["10.96.0.0/12"]},
+                    "pods": {"cidrBlocks": ["192.168.0.0/16"]},
+                },
+                "topology": {"class": "quick-start", "version": "v1.31.0"},
+            },
+        }));
+        assert_eq!(
+            to_json(&Builtins::for_cluster(&cluster)),
+            json!({
+                "cluster": {
+                    "name": "c1",
+                    "namespace": "ns",
+                    "uid": "1234",
+                    "metadata": {"labels": {"l": "v"}},
+                    "topology": {
+                        "version": "v1.31.0",
+                        "class": "quick-start",
+                        "classNamespace": "ns",
+                    },
+                    "network": {
+                        "services": ["10.96.0.0/12"],
+                        "pods": ["192.168.0.0/16"],
+                    },
+                },
+            })
+        );
+
+        let cluster: Cluster = from_json(json!({
+            "metadata": {"name": "c1", "namespace": "ns"},
+            "spec": {
+                "topology": {
+                    "class": "quick-start",
+                    "classNamespace": "classes",
+                    "version": "v1.31.0",
+                },
+            },
+        }));
+        assert_eq!(
+            to_json(&Builtins::for_cluster(&cluster)),
+            json!({
+                "cluster": {
+                    "name": "c1",
+                    "namespace": "ns",
+                    "topology": {
+                        "version": "v1.31.0",
+                        "class": "quick-start",
+                        "classNamespace": "classes",
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn control_plane() {
+        let control_plane = json!({
+            "metadata": {"name": "c1-abcde"},
+            "spec": {"version": "v1.31.0", "replicas": 3},
+        });
+        let template = json!({"metadata": {"name": "c1-control-plane-xyz"}});
+        for (topology, expected) in [
+            (
+                None,
+                json!({
+                    "controlPlane": {
+                        "version": "v1.31.0",
+                        "name": "c1-abcde",
+                        "machineTemplate": {"infrastructureRef": {"name": "c1-control-plane-xyz"}},
+                    },
+                }),
+            ),
+            (
+                Some(json!({"metadata": {}, "replicas": 3})),
+                json!({
+                    "controlPlane": {
+                        "version": "v1.31.0",
+                        "name": "c1-abcde",
+                        "replicas": 3,
+                        "machineTemplate": {"infrastructureRef": {"name": "c1-control-plane-xyz"}},
+                    },
+                }),
+            ),
+            (
+                Some(json!({"metadata": {"annotations": {"a": "b"}}})),
+                json!({
+                    "controlPlane": {
+                        "version": "v1.31.0",
+                        "metadata": {"annotations": {"a": "b"}},
+                        "name": "c1-abcde",
+                        "machineTemplate": {"infrastructureRef": {"name": "c1-control-plane-xyz"}},
+                    },
+                }),
+            ),
+        ] {
+            let topology: Option<ClusterTopologyControlPlane> = topology.map(from_json);
+            assert_eq!(
+                to_json(&Builtins::for_control_plane(
+                    topology.as_ref(),
+                    &control_plane,
+                    Some(&template)
+                )),
+                expected,
+                "{topology:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn machine_deployment() {
+        let machine_deployment: MachineDeployment = from_json(json!({
+            "metadata": {"name": "c1-md-0-abcde"},
+            "spec": {
+                "clusterName": "c1",
+                "replicas": 2,
+                "selector": {},
+                "template": {
+                    "spec": {
+                        "clusterName": "c1",
+                        "bootstrap": {},
+                        "infrastructureRef": {},
+                        "version": "v1.31.0",
+                    },
+                },
+            },
+        }));
+        let bootstrap = json!({"metadata": {"name": "c1-md-0-bootstrap-xyz"}});
+        let infrastructure = json!({"metadata": {"name": "c1-md-0-infra-xyz"}});
+        for (topology, expected_metadata) in [
+            (json!({"class": "default-worker", "name": "md-0"}), None),
+            (
+                json!({"class": "default-worker", "name": "md-0", "metadata": {}}),
+                None,
+            ),
+            (
+                json!({
+                    "class": "default-worker",
+                    "name": "md-0",
+                    "metadata": {"labels": {}},
+                }),
+                Some(json!({"labels": {}})),
+            ),
+        ] {
+            let topology: ClusterTopologyWorkersMachineDeployments = from_json(topology);
+            let mut expected = json!({
+                "machineDeployment": {
+                    "version": "v1.31.0",
+                    "class": "default-worker",
+                    "name": "c1-md-0-abcde",
+                    "topologyName": "md-0",
+                    "replicas": 2,
+                    "bootstrap": {"configRef": {"name": "c1-md-0-bootstrap-xyz"}},
+                    "infrastructureRef": {"name": "c1-md-0-infra-xyz"},
+                },
+            });
+            if let Some(metadata) = expected_metadata {
+                expected["machineDeployment"]["metadata"] = metadata;
+            }
+            assert_eq!(
+                to_json(&Builtins::for_machine_deployment(
+                    &topology,
+                    &machine_deployment,
+                    Some(&bootstrap),
+                    Some(&infrastructure)
+                )),
+                expected,
+                "{topology:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn machine_pool() {
+        let machine_pool: MachinePool = from_json(json!({
+            "metadata": {"name": "c1-mp-0-abcde"},
+            "spec": {
+                "clusterName": "c1",
+                "template": {
+                    "spec": {
+                        "clusterName": "c1",
+                        "bootstrap": {},
+                        "infrastructureRef": {},
+                    },
+                },
+            },
+        }));
+        for (topology, expected) in [
+            (
+                json!({"class": "default-worker", "name": "mp-0", "metadata": {}}),
+                json!({
+                    "machinePool": {
+                        "class": "default-worker",
+                        "name": "c1-mp-0-abcde",
+                        "topologyName": "mp-0",
+                    },
+                }),
+            ),
+            (
+                json!({
+                    "class": "default-worker",
+                    "name": "mp-0",
+                    "metadata": {"labels": {"l": "v"}},
+                }),
+                json!({
+                    "machinePool": {
+                        "metadata": {"labels": {"l": "v"}},
+                        "class": "default-worker",
+                        "name": "c1-mp-0-abcde",
+                        "topologyName": "mp-0",
+                    },
+                }),
+            ),
+        ] {
+            let topology: ClusterTopologyWorkersMachinePools = from_json(topology);
+            assert_eq!(
+                to_json(&Builtins::for_machine_pool(
+                    &topology,
+                    &machine_pool,
+                    None,
+                    None
+                )),
+                expected,
+                "{topology:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,478 @@
+//! Evaluation of parsed CEL expressions.
+//!
+//! Errors follow CEL's commutative logic: `false && error` and `error && false` are both
+//! `false`, and the comprehension macros absorb errors the same way.
+
+use std::collections::BTreeMap;
+
+use super::funcs;
+use super::parse::{BinaryOp, Expr, Macro, UnaryOp};
+use super::value::{Key, Value, TYPE_NAMES};
+use super::Activation;
+
+pub(super) struct Evaluator<'a> {
+    activation: &'a Activation,
+    /// Comprehension variables, innermost last.
+    scopes: Vec<(&'a str, Value)>,
+}
+
+fn no_overload(function: &str, values: &[&Value]) -> String {
+    let types: Vec<_> = values.iter().map(|v| v.type_name()).collect();
+    format!(
+        "found no matching overload for '{function}' applied to '({})'",
+        types.join(", ")
+    )
+}
+
+fn as_bool(value: Value, function: &str) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        value => Err(no_overload(function, &[&value])),
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    pub(super) fn new(activation: &'a Activation) -> Self {
+        Self {
+            activation,
+            scopes: Vec::new(),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Result<Value, String> {
+        if let Some((_, value)) = self.scopes.iter().rev().find(|(n, _)| *n == name) {
+            return Ok(value.clone());
+        }
+        if let Some(value) = self.activation.get(name) {
+            return Ok(value.clone());
+        }
+        if TYPE_NAMES.contains(&name) {
+            return Ok(Value::Type(name.to_string()));
+        }
+        Err(format!(
+            "undeclared reference to '{name}' (in container '')"
+        ))
+    }
+
+    pub(super) fn eval(&mut self, expr: &'a Expr) -> Result<Value, String> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Ident(name) => self.lookup(name),
+            Expr::Select {
+                operand,
+                field,
+                test,
+            } => {
+                let operand = self.eval(operand)?;
+                let Value::Map(map) = &operand else {
+                    return Err(format!(
+                        "no such key: {field} (type {} has no fields)",
+                        operand.type_name()
+                    ));
+                };
+                let value = map.get(&Key::String(field.clone()));
+                match (test, value) {
+                    (true, value) => Ok(Value::Bool(value.is_some())),
+                    (false, Some(value)) => Ok(value.clone()),
+                    (false, None) => Err(format!("no such key: {field}")),
+                }
+            }
+            Expr::Index(operand, index) => {
+                let operand = self.eval(operand)?;
+                let index = self.eval(index)?;
+                self.index(operand, index)
+            }
+            Expr::Call {
+                target,
+                function,
+                args,
+            } => {
+                let target = target.as_ref().map(|t| self.eval(t)).transpose()?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                funcs::call(function, target, args)
+            }
+            Expr::Unary(op, operand) => {
+                let operand = self.eval(operand)?;
+                match (op, operand) {
+                    (UnaryOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+                    (UnaryOp::Negate, Value::Int(i)) => i
+                        .checked_neg()
+                        .map(Value::Int)
+                        .ok_or_else(|| "integer overflow".to_string()),
+                    (UnaryOp::Negate, Value::Double(d)) => Ok(Value::Double(-d)),
+                    (UnaryOp::Negate, Value::Duration(d)) => d
+                        .checked_neg()
+                        .map(Value::Duration)
+                        .ok_or_else(|| "integer overflow".to_string()),
+                    (UnaryOp::Not, operand) => Err(no_overload("!_", &[&operand])),
+                    (UnaryOp::Negate, operand) => Err(no_overload("-_", &[&operand])),
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(*op, left, right)
+            }
+            Expr::And(left, right) => {
+                let left = self.eval(left).and_then(|v| as_bool(v, "_&&_"));
+                if left == Ok(false) {
+                    return Ok(Value::Bool(false));
+                }
+                let right = self.eval(right).and_then(|v| as_bool(v, "_&&_"));
+                match (left, right) {
+                    (_, Ok(false)) => Ok(Value::Bool(false)),
+                    (Err(err), _) | (_, Err(err)) => Err(err),
+                    (Ok(_), Ok(_)) => Ok(Value::Bool(true)),
+                }
+            }
+            Expr::Or(left, right) => {
+                let left = self.eval(left).and_then(|v| as_bool(v, "_||_"));
+                if left == Ok(true) {
+                    return Ok(Value::Bool(true));
+                }
+                let right = self.eval(right).and_then(|v| as_bool(v, "_||_"));
+                match (left, right) {
+                    (_, Ok(true)) => Ok(Value::Bool(true)),
+                    (Err(err), _) | (_, Err(err)) => Err(err),
+                    (Ok(_), Ok(_)) => Ok(Value::Bool(false)),
+                }
+            }
+            Expr::Conditional(condition, then, otherwise) => {
+                if as_bool(self.eval(condition)?, "_?_:_")? {
+                    self.eval(then)
+                } else {
+                    self.eval(otherwise)
+                }
+            }
+            Expr::List(items) => Ok(Value::List(
+                items
+                    .iter()
+                    .map(|item| self.eval(item))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expr::Map(entries) => {
+                let mut map = BTreeMap::new();
+                for (key, value) in entries {
+                    let key = self.eval(key)?;
+                    let key = key
+                        .to_key()
+                        .ok_or_else(|| format!("unsupported key type: {}", key.type_name()))?;
+                    let value = self.eval(value)?;
+                    if map.insert(key.clone(), value).is_some() {
+                        return Err(format!("Failed with repeated key: {}", key.to_value()));
+                    }
+                }
+                Ok(Value::Map(map))
+            }
+            Expr::Comprehension {
+                kind,
+                range,
+                var,
+                body,
+                transform,
+            } => {
+                let range = self.eval(range)?;
+                self.comprehension(*kind, range, var, body, transform.as_deref())
+            }
+        }
+    }
+
+    fn index(&self, operand: Value, index: Value) -> Result<Value, String> {
+        match (&operand, &index) {
+            (Value::List(items), _) => {
+                let i = match index {
+                    Value::Int(i) => i,
+                    Value::Uint(u) => i64::try_from(u).unwrap_or(i64::MAX),
+                    Value::Double(d) if d.fract() == 0.0 => d as i64,
+                    _ => return Err(no_overload("_[_]", &[&operand, &index])),
+                };
+                usize::try_from(i)
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .cloned()
+                    .ok_or_else(|| format!("index out of bounds: {i}"))
+            }
+            (Value::Map(map), _) => {
+                let key = index
+                    .to_key()
+                    .ok_or_else(|| no_overload("_[_]", &[&operand, &index]))?;
+                map.get(&key)
+                    .cloned()
+                    .ok_or_else(|| format!("no such key: {index}"))
+            }
+            _ => Err(no_overload("_[_]", &[&operand, &index])),
+        }
+    }
+
+    fn with_var(&mut self, var: &'a str, value: Value, expr: &'a Expr) -> Result<Value, String> {
+        self.scopes.push((var, value));
+        let result = self.eval(expr);
+        self.scopes.pop();
+        result
+    }
+
+    fn comprehension(
+        &mut self,
+        kind: Macro,
+        range: Value,
+        var: &'a str,
+        body: &'a Expr,
+        transform: Option<&'a Expr>,
+    ) -> Result<Value, String> {
+        let elements: Vec<Value> = match range {
+            Value::List(items) => items,
+            Value::Map(map) => map.keys().map(Key::to_value).collect(),
+            range => {
+                return Err(format!(
+                    "expression of type '{}' cannot be range of a comprehension (must be list, map, or dynamic)",
+                    range.type_name()
+                ))
+            }
+        };
+        match kind {
+            Macro::All | Macro::Exists => {
+                let short_circuit = kind == Macro::Exists;
+                let mut error = None;
+                for element in elements {
+                    match self
+                        .with_var(var, element, body)
+                        .and_then(|v| as_bool(v, "_&&_"))
+                    {
+                        Ok(b) if b == short_circuit => return Ok(Value::Bool(short_circuit)),
+                        Ok(_) => {}
+                        Err(err) => {
+                            error.get_or_insert(err);
+                        }
+                    }
+                }
+                match error {
+                    Some(err) => Err(err),
+                    None => Ok(Value::Bool(!short_circuit)),
+                }
+            }
+            Macro::ExistsOne => {
+                let mut count = 0;
+                for element in elements {
+                    if as_bool(self.with_var(var, element, body)?, "exists_one")? {
+                        count += 1;
+                    }
+                }
+                Ok(Value::Bool(count == 1))
+            }
+            Macro::Map => {
+                let mut out = Vec::new();
+                for element in elements {
+                    match transform {
+                        Some(transform) => {
+                            if as_bool(self.with_var(var, element.clone(), body)?, "map")? {
+                                out.push(self.with_var(var, element, transform)?);
+                            }
+                        }
+                        None => out.push(self.with_var(var, element, body)?),
+                    }
+                }
+                Ok(Value::List(out))
+            }
+            Macro::Filter => {
+                let mut out = Vec::new();
+                for element in elements {
+                    if as_bool(self.with_var(var, element.clone(), body)?, "filter")? {
+                        out.push(element);
+                    }
+                }
+                Ok(Value::List(out))
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    use std::cmp::Ordering::*;
+    let compare = |wanted: &[std::cmp::Ordering]| {
+        left.compare(&right)
+            .map(|ordering| Value::Bool(wanted.contains(&ordering)))
+            .ok_or_else(|| no_overload(op.symbol(), &[&left, &right]))
+    };
+    match op {
+        BinaryOp::Equal => Ok(Value::Bool(left.equals(&right))),
+        BinaryOp::NotEqual => Ok(Value::Bool(!left.equals(&right))),
+        BinaryOp::Less => compare(&[Less]),
+        BinaryOp::LessEqual => compare(&[Less, Equal]),
+        BinaryOp::Greater => compare(&[Greater]),
+        BinaryOp::GreaterEqual => compare(&[Greater, Equal]),
+        BinaryOp::In => match &right {
+            Value::List(items) => Ok(Value::Bool(items.iter().any(|item| item.equals(&left)))),
+            Value::Map(map) => Ok(Value::Bool(
+                left.to_key().is_some_and(|key| map.contains_key(&key)),
+            )),
+            _ => Err(no_overload(op.symbol(), &[&left, &right])),
+        },
+        _ => funcs::arithmetic(op, left, right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::topology::cel::parse::parse;
+
+    fn evaluate(source: &str) -> Result<Value, String> {
+        let activation = Activation::from([
+            (
+                "self".to_string(),
+                Value::from_json(
+                    &json!({"l": [1, 2, 3], "m": {"a": 1, "b": 2}, "s": "str"}),
+                    None,
+                ),
+            ),
+            ("n".to_string(), Value::Null),
+        ]);
+        let expr = parse(source).unwrap();
+        Evaluator::new(&activation).eval(&expr)
+    }
+
+    #[test]
+    fn evaluates() {
+        for (source, expected) in [
+            ("self.s", Value::String("str".into())),
+            ("self.l[1] + self.m['b']", Value::Int(4)),
+            ("self.l[1u]", Value::Int(2)),
+            ("self.l[2.0]", Value::Int(3)),
+            ("has(self.s) && !has(self.x)", Value::Bool(true)),
+            (
+                "'a' in self.m && 2 in self.l && !('c' in self.m)",
+                Value::Bool(true),
+            ),
+            (
+                "1 == 1.0 && 1u == 1 && [1] == [1.0] && 'a' != 1",
+                Value::Bool(true),
+            ),
+            (
+                "{'a': 1} == {'a': 1u} && {'a': 1} != {'b': 1}",
+                Value::Bool(true),
+            ),
+            (
+                "1 < 2u && 2.5 > 2 && 'a' <= 'b' && b'b' >= b'a'",
+                Value::Bool(true),
+            ),
+            ("true ? 1 : error", Value::Int(1)),
+            (
+                "type(self.l) == list && type(1u) == uint",
+                Value::Bool(true),
+            ),
+            ("n == null", Value::Bool(true)),
+            ("-(1)", Value::Int(-1)),
+            (
+                "{'k': [1, 2]}.k",
+                Value::List(vec![Value::Int(1), Value::Int(2)]),
+            ),
+        ] {
+            assert_eq!(evaluate(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn evaluates_expressions_nested_up_to_the_limit() {
+        for source in [
+            format!("{}1{}", "(".repeat(99), ")".repeat(99)),
+            format!("{}true", "!".repeat(98)),
+            format!("1{}", " + 1".repeat(98)),
+            format!("{}1{}{}", "[".repeat(98), "]".repeat(98), "[0]".repeat(98)),
+        ] {
+            assert!(evaluate(&source).is_ok(), "{source}");
+        }
+    }
+
+    #[test]
+    fn macros() {
+        let ints = |items: &[i64]| Value::List(items.iter().copied().map(Value::Int).collect());
+        for (source, expected) in [
+            ("self.l.all(x, x > 0)", Value::Bool(true)),
+            ("self.l.all(x, x > 1)", Value::Bool(false)),
+            ("[].all(x, x > 1)", Value::Bool(true)),
+            ("self.l.exists(x, x == 2)", Value::Bool(true)),
+            ("self.l.exists(x, x == 4)", Value::Bool(false)),
+            ("self.l.exists_one(x, x > 2)", Value::Bool(true)),
+            ("self.l.exists_one(x, x > 1)", Value::Bool(false)),
+            ("self.l.map(x, x * 2)", ints(&[2, 4, 6])),
+            ("self.l.map(x, x % 2 == 1, x * 10)", ints(&[10, 30])),
+            ("self.l.filter(x, x != 2)", ints(&[1, 3])),
+            (
+                "self.m.map(k, k)",
+                Value::List(vec![Value::String("a".into()), Value::String("b".into())]),
+            ),
+            ("self.m.all(k, self.m[k] > 0)", Value::Bool(true)),
+            ("self.l.all(x, self.l.exists(y, y == x))", Value::Bool(true)),
+            // the variable shadows the outer one only inside the macro
+            (
+                "[[1], [2]].all(x, x.all(x, x > 0)) && self.s == 'str'",
+                Value::Bool(true),
+            ),
+            // errors are absorbed when another element decides the result
+            ("[0, 1].exists(x, 1 / x == 1)", Value::Bool(true)),
+            ("[0, 1].all(x, 1 / x == 2)", Value::Bool(false)),
+        ] {
+            assert_eq!(evaluate(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn commutative_logic() {
+        for (source, expected) in [
+            ("false && 1 / 0 == 1", Ok(Value::Bool(false))),
+            ("1 / 0 == 1 && false", Ok(Value::Bool(false))),
+            ("true || self.x", Ok(Value::Bool(true))),
+            ("self.x || true", Ok(Value::Bool(true))),
+            ("self.x || false", Err("no such key: x".to_string())),
+            ("true && 1", Err(no_overload("_&&_", &[&Value::Int(1)]))),
+        ] {
+            assert_eq!(evaluate(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for (source, expected) in [
+            ("9223372036854775807 + 1", "integer overflow"),
+            ("-9223372036854775808 - 1", "integer overflow"),
+            ("-(-9223372036854775808)", "integer overflow"),
+            ("-9223372036854775808 / -1", "integer overflow"),
+            ("-9223372036854775808 % -1", "integer overflow"),
+            ("4611686018427387904 * 2", "integer overflow"),
+            ("0u - 1u", "unsigned integer overflow"),
+            ("18446744073709551615u + 1u", "unsigned integer overflow"),
+            ("1 / 0", "division by zero"),
+            ("1u % 0u", "modulus by zero"),
+            ("1 + 1u", "found no matching overload for '_+_' applied to '(int, uint)'"),
+            ("'a' < 1", "found no matching overload for '_<_' applied to '(string, int)'"),
+            ("-'a'", "found no matching overload for '-_' applied to '(string)'"),
+            ("!1", "found no matching overload for '!_' applied to '(int)'"),
+            ("1 ? 2 : 3", "found no matching overload for '_?_:_' applied to '(int)'"),
+            ("1 in 1", "found no matching overload for '@in' applied to '(int, int)'"),
+            ("x", "undeclared reference to 'x' (in container '')"),
+            ("self.x", "no such key: x"),
+            ("self.s.x", "no such key: x (type string has no fields)"),
+            ("self.l[3]", "index out of bounds: 3"),
+            ("self.l[-1]", "index out of bounds: -1"),
+            ("self.m['c']", "no such key: c"),
+            ("self.s[0]", "found no matching overload for '_[_]' applied to '(string, int)'"),
+            ("{'a': 1, 'a': 2}", "Failed with repeated key: a"),
+            ("{[1]: 2}", "unsupported key type: list"),
+            (
+                "1.all(x, true)",
+                "expression of type 'int' cannot be range of a comprehension (must be list, map, or dynamic)",
+            ),
+            ("self.l.all(x, x)", "found no matching overload for '_&&_' applied to '(int)'"),
+            ("self.l.filter(x, 1)", "found no matching overload for 'filter' applied to '(int)'"),
+            ("self.l.exists_one(x, 1 / 0 == 1)", "division by zero"),
+            ("[0, 1].all(x, 1 / x == 1)", "division by zero"),
+        ] {
+            assert_eq!(evaluate(source), Err(expected.to_string()), "{source}");
+        }
+    }
+}
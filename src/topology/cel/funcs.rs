@@ -0,0 +1,751 @@
+//! CEL operators and functions: the standard definitions plus the extended string, list and
+//! regex libraries the apiserver enables for validation rules.
+
+use std::cmp::Ordering;
+
+use k8s_openapi::chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use regex::Regex;
+
+use super::parse::BinaryOp;
+use super::value::Value;
+use crate::api::duration::GoDuration;
+
+/// Error of a call with arguments no overload accepts.
+fn no_overload(function: &str, target: Option<&Value>, args: &[Value]) -> String {
+    let types: Vec<_> = target
+        .into_iter()
+        .chain(args)
+        .map(Value::type_name)
+        .collect();
+    format!(
+        "found no matching overload for '{function}' applied to '({})'",
+        types.join(", ")
+    )
+}
+
+fn overflow() -> String {
+    "integer overflow".to_string()
+}
+
+/// Evaluates an arithmetic operator.
+pub(super) fn arithmetic(op: BinaryOp, a: Value, b: Value) -> Result<Value, String> {
+    use BinaryOp::*;
+    Ok(match (op, a, b) {
+        (Add, Value::Int(a), Value::Int(b)) => Value::Int(a.checked_add(b).ok_or_else(overflow)?),
+        (Subtract, Value::Int(a), Value::Int(b)) => {
+            Value::Int(a.checked_sub(b).ok_or_else(overflow)?)
+        }
+        (Multiply, Value::Int(a), Value::Int(b)) => {
+            Value::Int(a.checked_mul(b).ok_or_else(overflow)?)
+        }
+        (Divide, Value::Int(_), Value::Int(0)) | (Divide, Value::Uint(_), Value::Uint(0)) => {
+            return Err("division by zero".to_string())
+        }
+        (Modulo, Value::Int(_), Value::Int(0)) | (Modulo, Value::Uint(_), Value::Uint(0)) => {
+            return Err("modulus by zero".to_string())
+        }
+        (Divide, Value::Int(a), Value::Int(b)) => {
+            Value::Int(a.checked_div(b).ok_or_else(overflow)?)
+        }
+        (Modulo, Value::Int(a), Value::Int(b)) => {
+            Value::Int(a.checked_rem(b).ok_or_else(overflow)?)
+        }
+        (Add, Value::Uint(a), Value::Uint(b)) => Value::Uint(
+            a.checked_add(b)
+                .ok_or_else(|| "unsigned integer overflow".to_string())?,
+        ),
+        (Subtract, Value::Uint(a), Value::Uint(b)) => Value::Uint(
+            a.checked_sub(b)
+                .ok_or_else(|| "unsigned integer overflow".to_string())?,
+        ),
+        (Multiply, Value::Uint(a), Value::Uint(b)) => Value::Uint(
+            a.checked_mul(b)
+                .ok_or_else(|| "unsigned integer overflow".to_string())?,
+        ),
+        (Divide, Value::Uint(a), Value::Uint(b)) => Value::Uint(a / b),
+        (Modulo, Value::Uint(a), Value::Uint(b)) => Value::Uint(a % b),
+        (Add, Value::Double(a), Value::Double(b)) => Value::Double(a + b),
+        (Subtract, Value::Double(a), Value::Double(b)) => Value::Double(a - b),
+        (Multiply, Value::Double(a), Value::Double(b)) => Value::Double(a * b),
+        (Divide, Value::Double(a), Value::Double(b)) => Value::Double(a / b),
+        (Add, Value::String(a), Value::String(b)) => Value::String(a + &b),
+        (Add, Value::Bytes(mut a), Value::Bytes(b)) => {
+            a.extend(b);
+            Value::Bytes(a)
+        }
+        (Add, Value::List(mut a), Value::List(b)) => {
+            a.extend(b);
+            Value::List(a)
+        }
+        (Add, Value::Duration(a), Value::Duration(b)) => {
+            Value::Duration(a.checked_add(b).ok_or_else(overflow)?)
+        }
+        (Subtract, Value::Duration(a), Value::Duration(b)) => {
+            Value::Duration(a.checked_sub(b).ok_or_else(overflow)?)
+        }
+        (Add, Value::Timestamp(t), Value::Duration(d))
+        | (Add, Value::Duration(d), Value::Timestamp(t)) => Value::Timestamp(
+            t.checked_add_signed(Duration::nanoseconds(d))
+                .ok_or_else(overflow)?,
+        ),
+        (Subtract, Value::Timestamp(t), Value::Duration(d)) => Value::Timestamp(
+            t.checked_sub_signed(Duration::nanoseconds(d))
+                .ok_or_else(overflow)?,
+        ),
+        (Subtract, Value::Timestamp(a), Value::Timestamp(b)) => {
+            Value::Duration((a - b).num_nanoseconds().ok_or_else(overflow)?)
+        }
+        (op, a, b) => return Err(no_overload(op.symbol(), None, &[a, b])),
+    })
+}
+
+/// Evaluates a function call, `target` is the receiver of a method call.
+pub(super) fn call(
+    function: &str,
+    target: Option<Value>,
+    args: Vec<Value>,
+) -> Result<Value, String> {
+    match target {
+        None => global(function, args),
+        Some(target) => method(function, target, args),
+    }
+}
+
+fn global(function: &str, args: Vec<Value>) -> Result<Value, String> {
+    if !is_global(function) {
+        return Err(undeclared(function));
+    }
+    if let [Value::String(s), Value::String(pattern)] = args.as_slice() {
+        if function == "matches" {
+            return Ok(Value::Bool(regex(pattern)?.is_match(s)));
+        }
+    }
+    let arg = match <[Value; 1]>::try_from(args) {
+        Ok([arg]) => arg,
+        Err(args) => return Err(no_overload(function, None, &args)),
+    };
+    match function {
+        "size" => size(&arg).ok_or_else(|| no_overload(function, None, &[arg])),
+        "int" => to_int(arg),
+        "uint" => to_uint(arg),
+        "double" => to_double(arg),
+        "string" => to_string(arg),
+        "bool" => to_bool(arg),
+        "bytes" => match arg {
+            Value::String(s) => Ok(Value::Bytes(s.into_bytes())),
+            Value::Bytes(b) => Ok(Value::Bytes(b)),
+            arg => Err(no_overload(function, None, &[arg])),
+        },
+        "type" => Ok(Value::Type(arg.type_name().to_string())),
+        "dyn" => Ok(arg),
+        "duration" => match arg {
+            Value::String(s) => s
+                .parse::<GoDuration>()
+                .map(|d| Value::Duration(d.as_nanos()))
+                .map_err(|err| format!("invalid duration {s:?}: {err}")),
+            Value::Duration(d) => Ok(Value::Duration(d)),
+            arg => Err(no_overload(function, None, &[arg])),
+        },
+        "timestamp" => match arg {
+            Value::String(s) => DateTime::parse_from_rfc3339(&s)
+                .map(|t| Value::Timestamp(t.with_timezone(&Utc)))
+                .map_err(|err| format!("invalid timestamp {s:?}: {err}")),
+            Value::Int(secs) => DateTime::from_timestamp(secs, 0)
+                .map(Value::Timestamp)
+                .ok_or_else(|| "timestamp out of range".to_string()),
+            Value::Timestamp(t) => Ok(Value::Timestamp(t)),
+            arg => Err(no_overload(function, None, &[arg])),
+        },
+        _ => Err(no_overload(function, None, &[arg])),
+    }
+}
+
+fn is_global(function: &str) -> bool {
+    matches!(
+        function,
+        "size"
+            | "int"
+            | "uint"
+            | "double"
+            | "string"
+            | "bool"
+            | "bytes"
+            | "type"
+            | "dyn"
+            | "duration"
+            | "timestamp"
+            | "matches"
+    )
+}
+
+fn undeclared(function: &str) -> String {
+    format!("undeclared reference to '{function}' (in container '')")
+}
+
+fn size(value: &Value) -> Option<Value> {
+    let len = match value {
+        Value::String(s) => s.chars().count(),
+        Value::Bytes(b) => b.len(),
+        Value::List(l) => l.len(),
+        Value::Map(m) => m.len(),
+        _ => return None,
+    };
+    Some(Value::Int(len as i64))
+}
+
+fn to_int(value: Value) -> Result<Value, String> {
+    let range = || "range error converting to int".to_string();
+    Ok(Value::Int(match value {
+        Value::Int(i) => i,
+        Value::Uint(u) => i64::try_from(u).map_err(|_| range())?,
+        Value::Double(d) => {
+            if !d.is_finite() || d <= i64::MIN as f64 || d >= i64::MAX as f64 {
+                return Err(range());
+            }
+            d.trunc() as i64
+        }
+        Value::String(s) => s
+            .parse()
+            .map_err(|_| format!("cannot convert {s:?} to int"))?,
+        Value::Timestamp(t) => t.timestamp(),
+        Value::Duration(d) => d,
+        value => return Err(no_overload("int", None, &[value])),
+    }))
+}
+
+fn to_uint(value: Value) -> Result<Value, String> {
+    let range = || "range error converting to uint".to_string();
+    Ok(Value::Uint(match value {
+        Value::Uint(u) => u,
+        Value::Int(i) => u64::try_from(i).map_err(|_| range())?,
+        Value::Double(d) => {
+            if !d.is_finite() || d < 0.0 || d >= u64::MAX as f64 {
+                return Err(range());
+            }
+            d.trunc() as u64
+        }
+        Value::String(s) => s
+            .parse()
+            .map_err(|_| format!("cannot convert {s:?} to uint"))?,
+        value => return Err(no_overload("uint", None, &[value])),
+    }))
+}
+
+fn to_double(value: Value) -> Result<Value, String> {
+    Ok(Value::Double(match value {
+        Value::Double(d) => d,
+        Value::Int(i) => i as f64,
+        Value::Uint(u) => u as f64,
+        Value::String(s) => s
+            .parse()
+            .map_err(|_| format!("cannot convert {s:?} to double"))?,
+        value => return Err(no_overload("double", None, &[value])),
+    }))
+}
+
+fn to_string(value: Value) -> Result<Value, String> {
+    match value {
+        Value::Bytes(b) => String::from_utf8(b)
+            .map(Value::String)
+            .map_err(|_| "invalid UTF-8 in bytes, cannot convert to string".to_string()),
+        Value::List(_) | Value::Map(_) | Value::Null => Err(no_overload("string", None, &[value])),
+        value => Ok(Value::String(value.to_string())),
+    }
+}
+
+fn to_bool(value: Value) -> Result<Value, String> {
+    match value {
+        Value::Bool(b) => Ok(Value::Bool(b)),
+        Value::String(s) => match s.as_str() {
+            "1" | "t" | "T" | "true" | "TRUE" | "True" => Ok(Value::Bool(true)),
+            "0" | "f" | "F" | "false" | "FALSE" | "False" => Ok(Value::Bool(false)),
+            _ => Err(format!(
+                "type conversion error from 'string' to 'bool': {s:?}"
+            )),
+        },
+        value => Err(no_overload("bool", None, &[value])),
+    }
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|err| format!("invalid regex {pattern:?}: {err}"))
+}
+
+fn int_arg(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(i) => Some(*i),
+        _ => None,
+    }
+}
+
+fn method(function: &str, target: Value, args: Vec<Value>) -> Result<Value, String> {
+    let mismatch = |target: &Value, args: &[Value]| no_overload(function, Some(target), args);
+    match (&target, function, args.as_slice()) {
+        (_, "size", []) => size(&target).ok_or_else(|| mismatch(&target, &args)),
+        (Value::String(s), "contains", [Value::String(sub)]) => {
+            Ok(Value::Bool(s.contains(sub.as_str())))
+        }
+        (Value::String(s), "startsWith", [Value::String(prefix)]) => {
+            Ok(Value::Bool(s.starts_with(prefix.as_str())))
+        }
+        (Value::String(s), "endsWith", [Value::String(suffix)]) => {
+            Ok(Value::Bool(s.ends_with(suffix.as_str())))
+        }
+        (Value::String(s), "matches", [Value::String(pattern)]) => {
+            Ok(Value::Bool(regex(pattern)?.is_match(s)))
+        }
+        (Value::String(s), "lowerAscii", []) => Ok(Value::String(s.to_ascii_lowercase())),
+        (Value::String(s), "upperAscii", []) => Ok(Value::String(s.to_ascii_uppercase())),
+        (Value::String(s), "trim", []) => Ok(Value::String(s.trim().to_string())),
+        (Value::String(s), "replace", [Value::String(from), Value::String(to)]) => {
+            Ok(Value::String(s.replace(from.as_str(), to)))
+        }
+        (Value::String(s), "replace", [Value::String(from), Value::String(to), Value::Int(n)]) => {
+            Ok(Value::String(if *n < 0 {
+                s.replace(from.as_str(), to)
+            } else {
+                s.replacen(from.as_str(), to, *n as usize)
+            }))
+        }
+        (Value::String(s), "split", [Value::String(sep)]) => Ok(split(s, sep, -1)),
+        (Value::String(s), "split", [Value::String(sep), Value::Int(n)]) => Ok(split(s, sep, *n)),
+        (Value::String(s), "substring", [start]) | (Value::String(s), "substring", [start, _])
+            if args.iter().all(|a| int_arg(a).is_some()) =>
+        {
+            let chars: Vec<char> = s.chars().collect();
+            let start = int_arg(start).unwrap_or_default();
+            let end = args.get(1).and_then(int_arg).unwrap_or(chars.len() as i64);
+            if start < 0 || start as usize > chars.len() {
+                return Err(format!("index out of range: {start}"));
+            }
+            if end < 0 || end as usize > chars.len() {
+                return Err(format!("index out of range: {end}"));
+            }
+            if start > end {
+                return Err(format!(
+                    "invalid substring range. start: {start}, end: {end}"
+                ));
+            }
+            Ok(Value::String(
+                chars[start as usize..end as usize].iter().collect(),
+            ))
+        }
+        (Value::String(s), "charAt", [Value::Int(i)]) => {
+            let chars: Vec<char> = s.chars().collect();
+            match usize::try_from(*i).ok().filter(|i| *i <= chars.len()) {
+                Some(i) => Ok(Value::String(
+                    chars.get(i).map(char::to_string).unwrap_or_default(),
+                )),
+                None => Err(format!("index out of range: {i}")),
+            }
+        }
+        (Value::String(s), "indexOf" | "lastIndexOf", [Value::String(sub)])
+        | (Value::String(s), "indexOf" | "lastIndexOf", [Value::String(sub), Value::Int(_)]) => {
+            let chars: Vec<char> = s.chars().collect();
+            let sub: Vec<char> = sub.chars().collect();
+            let last = function == "lastIndexOf";
+            let offset = args.get(1).and_then(int_arg);
+            let (from, to) = match (offset, last) {
+                (None, _) => (0, chars.len() as i64),
+                (Some(o), false) => (o, chars.len() as i64),
+                (Some(o), true) => (0, o + sub.len() as i64),
+            };
+            if offset.is_some_and(|o| o < 0 || o as usize > chars.len()) {
+                return Err(format!(
+                    "index out of range: {}",
+                    offset.unwrap_or_default()
+                ));
+            }
+            let to = to.min(chars.len() as i64) as usize;
+            let from = from as usize;
+            let found = if sub.len() > to.saturating_sub(from) {
+                None
+            } else {
+                let mut positions = from..=to - sub.len();
+                let matches = |i: &usize| chars[*i..*i + sub.len()] == sub[..];
+                if last {
+                    positions.rev().find(matches)
+                } else {
+                    positions.find(matches)
+                }
+            };
+            Ok(Value::Int(found.map_or(-1, |i| i as i64)))
+        }
+        (Value::String(s), "find", [Value::String(pattern)]) => Ok(Value::String(
+            regex(pattern)?
+                .find(s)
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default(),
+        )),
+        (Value::String(s), "findAll", [Value::String(pattern)])
+        | (Value::String(s), "findAll", [Value::String(pattern), Value::Int(_)]) => {
+            let limit = args.get(1).and_then(int_arg).unwrap_or(-1);
+            let regex = regex(pattern)?;
+            let matches = regex
+                .find_iter(s)
+                .map(|m| Value::String(m.as_str().to_string()));
+            Ok(Value::List(if limit < 0 {
+                matches.collect()
+            } else {
+                matches.take(limit as usize).collect()
+            }))
+        }
+        (Value::List(items), "join", []) | (Value::List(items), "join", [Value::String(_)]) => {
+            let sep = match args.first() {
+                Some(Value::String(sep)) => sep.as_str(),
+                _ => "",
+            };
+            let strings = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => Ok(s.as_str()),
+                    _ => Err(mismatch(&target, &args)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::String(strings.join(sep)))
+        }
+        (Value::List(items), "isSorted", []) => {
+            let mut sorted = true;
+            for pair in items.windows(2) {
+                match pair[0].compare(&pair[1]) {
+                    Some(Ordering::Greater) => sorted = false,
+                    Some(_) => {}
+                    None => return Err(mismatch(&target, &args)),
+                }
+            }
+            Ok(Value::Bool(sorted))
+        }
+        (Value::List(items), "sort", []) => {
+            if let Some(first) = items.first() {
+                if items.iter().any(|i| i.type_name() != first.type_name()) {
+                    return Err("list elements must have the same type".to_string());
+                }
+                if first.compare(first).is_none() {
+                    return Err("list elements must be comparable".to_string());
+                }
+            }
+            let mut items = items.clone();
+            items.sort_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal));
+            Ok(Value::List(items))
+        }
+        (Value::List(items), "sum", []) => match items.split_first() {
+            None => Ok(Value::Int(0)),
+            Some((first, rest)) => rest.iter().cloned().try_fold(first.clone(), |sum, item| {
+                arithmetic(BinaryOp::Add, sum, item)
+            }),
+        },
+        (Value::List(items), "min" | "max", []) => {
+            let wanted = if function == "min" {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+            let mut best: Option<&Value> = None;
+            for item in items {
+                best = match best {
+                    None => Some(item),
+                    Some(current) => match item.compare(current) {
+                        Some(ordering) if ordering == wanted => Some(item),
+                        Some(_) => Some(current),
+                        None => return Err(mismatch(&target, &args)),
+                    },
+                };
+            }
+            best.cloned()
+                .ok_or_else(|| format!("{function} called on empty list"))
+        }
+        (Value::List(items), "indexOf" | "lastIndexOf", [value]) => {
+            let mut positions = items.iter().enumerate();
+            let found = if function == "indexOf" {
+                positions.find(|(_, item)| item.equals(value))
+            } else {
+                positions.rev().find(|(_, item)| item.equals(value))
+            };
+            Ok(Value::Int(found.map_or(-1, |(i, _)| i as i64)))
+        }
+        (Value::Duration(d), _, []) => {
+            let d = *d;
+            Ok(Value::Int(match function {
+                "getHours" => d / 3_600_000_000_000,
+                "getMinutes" => d / 60_000_000_000,
+                "getSeconds" => d / 1_000_000_000,
+                "getMilliseconds" => d % 1_000_000_000 / 1_000_000,
+                _ => return Err(mismatch(&target, &args)),
+            }))
+        }
+        (Value::Timestamp(t), _, []) => Ok(Value::Int(
+            timestamp_field(function, t).ok_or_else(|| mismatch(&target, &args))?,
+        )),
+        _ => Err(mismatch(&target, &args)),
+    }
+}
+
+/// Splits like Go's `strings.SplitN`.
+fn split(s: &str, sep: &str, n: i64) -> Value {
+    let parts: Vec<Value> = match n {
+        0 => Vec::new(),
+        n if n < 0 => {
+            if sep.is_empty() {
+                s.chars().map(|c| Value::String(c.to_string())).collect()
+            } else {
+                s.split(sep).map(|p| Value::String(p.to_string())).collect()
+            }
+        }
+        n => {
+            if sep.is_empty() {
+                let chars: Vec<char> = s.chars().collect();
+                let n = (n as usize).min(chars.len());
+                let mut parts: Vec<Value> = chars[..n.saturating_sub(1)]
+                    .iter()
+                    .map(|c| Value::String(c.to_string()))
+                    .collect();
+                if n > 0 {
+                    parts.push(Value::String(chars[n - 1..].iter().collect()));
+                }
+                parts
+            } else {
+                s.splitn(n as usize, sep)
+                    .map(|p| Value::String(p.to_string()))
+                    .collect()
+            }
+        }
+    };
+    Value::List(parts)
+}
+
+fn timestamp_field(function: &str, t: &DateTime<Utc>) -> Option<i64> {
+    Some(i64::from(match function {
+        "getFullYear" => t.year(),
+        "getMonth" => t.month0() as i32,
+        "getDate" => t.day() as i32,
+        "getDayOfMonth" => t.day0() as i32,
+        "getDayOfWeek" => t.weekday().num_days_from_sunday() as i32,
+        "getDayOfYear" => t.ordinal0() as i32,
+        "getHours" => t.hour() as i32,
+        "getMinutes" => t.minute() as i32,
+        "getSeconds" => t.second() as i32,
+        "getMilliseconds" => (t.nanosecond() / 1_000_000) as i32,
+        _ => return None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::topology::cel::{Activation, CelError, Program, Value};
+
+    fn evaluate(source: &str) -> Result<Value, CelError> {
+        Program::compile(source)?.evaluate(&Activation::new())
+    }
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn strings(items: &[&str]) -> Value {
+        Value::List(items.iter().map(|s| string(s)).collect())
+    }
+
+    #[test]
+    fn strings_and_regexes() {
+        for (source, expected) in [
+            ("size('héllo')", Value::Int(5)),
+            ("'héllo'.size()", Value::Int(5)),
+            ("size(b'h\\xc3\\xa9')", Value::Int(3)),
+            (
+                "'abc'.contains('b') && 'abc'.startsWith('a') && 'abc'.endsWith('c')",
+                Value::Bool(true),
+            ),
+            (
+                "'abc'.matches('^a.c$') && matches('abc', 'b')",
+                Value::Bool(true),
+            ),
+            ("'AbC'.lowerAscii() + 'AbC'.upperAscii()", string("abcABC")),
+            ("' a b '.trim()", string("a b")),
+            ("'aaa'.replace('a', 'b')", string("bbb")),
+            ("'aaa'.replace('a', 'b', 2)", string("bba")),
+            ("'aaa'.replace('a', 'b', -1)", string("bbb")),
+            ("'a,b,c'.split(',')", strings(&["a", "b", "c"])),
+            ("'a,b,c'.split(',', 2)", strings(&["a", "b,c"])),
+            ("'a,b,c'.split(',', 0)", strings(&[])),
+            ("'abc'.split('')", strings(&["a", "b", "c"])),
+            ("'abc'.split('', 2)", strings(&["a", "bc"])),
+            ("'héllo'.substring(1)", string("éllo")),
+            ("'héllo'.substring(1, 3)", string("él")),
+            ("'héllo'.charAt(1)", string("é")),
+            ("'abc'.charAt(3)", string("")),
+            ("'abcabc'.indexOf('c')", Value::Int(2)),
+            ("'abcabc'.indexOf('c', 3)", Value::Int(5)),
+            ("'abcabc'.indexOf('')", Value::Int(0)),
+            ("'abcabc'.indexOf('x')", Value::Int(-1)),
+            ("'abcabc'.lastIndexOf('a')", Value::Int(3)),
+            ("'abcabc'.lastIndexOf('a', 2)", Value::Int(0)),
+            ("'abc 123 def 456'.find('[0-9]+')", string("123")),
+            ("'abc'.find('[0-9]+')", string("")),
+            ("'a1b2c3'.findAll('[0-9]')", strings(&["1", "2", "3"])),
+            ("'a1b2c3'.findAll('[0-9]', 2)", strings(&["1", "2"])),
+            ("['a', 'b'].join()", string("ab")),
+            ("['a', 'b'].join('-')", string("a-b")),
+        ] {
+            assert_eq!(evaluate(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn lists() {
+        let ints = |items: &[i64]| Value::List(items.iter().copied().map(Value::Int).collect());
+        for (source, expected) in [
+            ("[3, 1, 2].sort()", ints(&[1, 2, 3])),
+            ("[].sort()", ints(&[])),
+            ("['b', 'a', 'B'].sort()", strings(&["B", "a", "b"])),
+            (
+                "[2.5, -1.0].sort()",
+                Value::List(vec![Value::Double(-1.0), Value::Double(2.5)]),
+            ),
+            (
+                "[duration('1m'), duration('1s')].sort()",
+                Value::List(vec![
+                    Value::Duration(1_000_000_000),
+                    Value::Duration(60_000_000_000),
+                ]),
+            ),
+            (
+                "[1, 2, 2].isSorted() && !['b', 'a'].isSorted()",
+                Value::Bool(true),
+            ),
+            ("[1, 2, 3].sum()", Value::Int(6)),
+            ("[].sum()", Value::Int(0)),
+            ("[1.5, 2.5].sum()", Value::Double(4.0)),
+            ("[3, 1, 2].min()", Value::Int(1)),
+            ("['a', 'c', 'b'].max()", string("c")),
+            ("[1, 2, 1].indexOf(1)", Value::Int(0)),
+            ("[1, 2, 1].lastIndexOf(1)", Value::Int(2)),
+            ("[1, 2].indexOf(3)", Value::Int(-1)),
+            ("[1, 2] + [3]", ints(&[1, 2, 3])),
+            ("size({'a': 1}) + [1].size()", Value::Int(2)),
+        ] {
+            assert_eq!(evaluate(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn conversions_and_time() {
+        for (source, expected) in [
+            (
+                "int('-42') + int(2u) + int(2.9) + int(-2.9)",
+                Value::Int(-40),
+            ),
+            ("int(timestamp('1970-01-01T00:01:00Z'))", Value::Int(60)),
+            ("uint(42) + uint('1') + uint(1.5)", Value::Uint(44)),
+            ("double(1) + double(1u) + double('0.5')", Value::Double(2.5)),
+            (
+                "string(1) + string(2u) + string(1.5) + string(true)",
+                string("121.5true"),
+            ),
+            ("string(b'abc') + string(duration('90s'))", string("abc90s")),
+            (
+                "string(1e21) + ' ' + string(0.0001)",
+                string("1e+21 0.0001"),
+            ),
+            ("bool('t') && !bool('False')", Value::Bool(true)),
+            ("bytes('a')", Value::Bytes(b"a".to_vec())),
+            ("type(dyn(1)) == int", Value::Bool(true)),
+            ("duration('1h30m').getMinutes()", Value::Int(90)),
+            ("duration('1.5s').getMilliseconds()", Value::Int(500)),
+            (
+                "timestamp('2024-03-01T12:34:56Z') - timestamp('2024-03-01T12:00:00Z')",
+                Value::Duration(2_096_000_000_000),
+            ),
+            (
+                "string(timestamp('2024-03-01T12:00:00Z') + duration('-1h'))",
+                string("2024-03-01T11:00:00Z"),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getFullYear()",
+                Value::Int(2024),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getMonth()",
+                Value::Int(2),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getDate()",
+                Value::Int(1),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getDayOfMonth()",
+                Value::Int(0),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getDayOfWeek()",
+                Value::Int(5),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getDayOfYear()",
+                Value::Int(60),
+            ),
+            (
+                "timestamp('2024-03-01T12:34:56.789Z').getMilliseconds()",
+                Value::Int(789),
+            ),
+            (
+                "timestamp(0) < timestamp('2024-03-01T00:00:00Z')",
+                Value::Bool(true),
+            ),
+        ] {
+            assert_eq!(evaluate(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for (source, expected) in [
+            ("[1, 'a'].sort()", "list elements must have the same type"),
+            ("[[1], [2]].sort()", "list elements must be comparable"),
+            ("[{}].sort()", "list elements must be comparable"),
+            (
+                "[1, 'a'].isSorted()",
+                "found no matching overload for 'isSorted' applied to '(list)'",
+            ),
+            ("[9223372036854775807, 1].sum()", "integer overflow"),
+            ("[].min()", "min called on empty list"),
+            ("['a', 1].join()", "found no matching overload for 'join' applied to '(list)'"),
+            (
+                "'abc'.contains(1)",
+                "found no matching overload for 'contains' applied to '(string, int)'",
+            ),
+            ("'abc'.substring(4)", "index out of range: 4"),
+            ("'abc'.substring(2, 1)", "invalid substring range. start: 2, end: 1"),
+            ("'abc'.charAt(-1)", "index out of range: -1"),
+            ("'abc'.indexOf('a', 4)", "index out of range: 4"),
+            (
+                "'abc'.matches('(')",
+                "invalid regex \"(\": regex parse error:\n    (\n    ^\nerror: unclosed group",
+            ),
+            ("int(18446744073709551615u)", "range error converting to int"),
+            ("int(1e19)", "range error converting to int"),
+            ("uint(-1)", "range error converting to uint"),
+            ("int('x')", "cannot convert \"x\" to int"),
+            ("bool('yes')", "type conversion error from 'string' to 'bool': \"yes\""),
+            ("string(b'\\xff')", "invalid UTF-8 in bytes, cannot convert to string"),
+            ("string([1])", "found no matching overload for 'string' applied to '(list)'"),
+            ("duration('1x')", "invalid duration \"1x\": time: unknown unit \"x\" in duration \"1x\""),
+            ("size(1)", "found no matching overload for 'size' applied to '(int)'"),
+            ("size(1, 2)", "found no matching overload for 'size' applied to '(int, int)'"),
+            ("nope(1)", "undeclared reference to 'nope' (in container '')"),
+            (
+                "duration('1s').getFullYear()",
+                "found no matching overload for 'getFullYear' applied to '(google.protobuf.Duration)'",
+            ),
+            (
+                "timestamp('2024-03-01T00:00:00Z') + timestamp('2024-03-01T00:00:00Z')",
+                "found no matching overload for '_+_' applied to '(google.protobuf.Timestamp, google.protobuf.Timestamp)'",
+            ),
+        ] {
+            assert_eq!(
+                evaluate(source),
+                Err(CelError::Evaluation(expected.to_string())),
+                "{source}"
+            );
+        }
+    }
+}
@@ -0,0 +1,525 @@
+//! Lexer for CEL expressions, following the grammar of the CEL language definition.
+
+use super::CelError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+    Int(i64),
+    /// The magnitude of an int literal only valid when negated, i.e. `9223372036854775808`.
+    IntMin,
+    Uint(u64),
+    Double(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Ident(String),
+    True,
+    False,
+    Null,
+    In,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    Question,
+    Colon,
+    Dot,
+    Comma,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    Eof,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Item {
+    pub token: Token,
+    /// Byte offset of the token in the expression.
+    pub pos: usize,
+}
+
+/// Identifiers that are reserved by CEL and may not be used as names.
+const RESERVED: &[&str] = &[
+    "as",
+    "break",
+    "const",
+    "continue",
+    "else",
+    "for",
+    "function",
+    "if",
+    "import",
+    "let",
+    "loop",
+    "package",
+    "namespace",
+    "return",
+    "var",
+    "void",
+    "while",
+];
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    items: Vec<Item>,
+}
+
+/// Splits an expression into tokens.
+pub(super) fn lex(source: &str) -> Result<Vec<Item>, CelError> {
+    let mut lexer = Lexer {
+        source,
+        pos: 0,
+        items: Vec::new(),
+    };
+    lexer.run()?;
+    Ok(lexer.items)
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, pos: usize, message: impl Into<String>) -> CelError {
+        CelError::syntax(self.source, pos, message)
+    }
+
+    fn emit(&mut self, token: Token, pos: usize) {
+        self.items.push(Item { token, pos });
+    }
+
+    fn run(&mut self) -> Result<(), CelError> {
+        loop {
+            self.skip_space_and_comments();
+            let start = self.pos;
+            let Some(c) = self.peek() else {
+                self.emit(Token::Eof, start);
+                return Ok(());
+            };
+            let two = self.rest().get(..2).unwrap_or_default();
+            let token = match two {
+                "==" => Some(Token::Eq),
+                "!=" => Some(Token::Ne),
+                "<=" => Some(Token::Le),
+                ">=" => Some(Token::Ge),
+                "&&" => Some(Token::And),
+                "||" => Some(Token::Or),
+                _ => None,
+            };
+            if let Some(token) = token {
+                self.pos += 2;
+                self.emit(token, start);
+                continue;
+            }
+            let token = match c {
+                '+' => Some(Token::Plus),
+                '-' => Some(Token::Minus),
+                '*' => Some(Token::Star),
+                '/' => Some(Token::Slash),
+                '%' => Some(Token::Percent),
+                '<' => Some(Token::Lt),
+                '>' => Some(Token::Gt),
+                '!' => Some(Token::Not),
+                '?' => Some(Token::Question),
+                ':' => Some(Token::Colon),
+                ',' => Some(Token::Comma),
+                '[' => Some(Token::LeftBracket),
+                ']' => Some(Token::RightBracket),
+                '(' => Some(Token::LeftParen),
+                ')' => Some(Token::RightParen),
+                '{' => Some(Token::LeftBrace),
+                '}' => Some(Token::RightBrace),
+                '.' if !self.rest()[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                    Some(Token::Dot)
+                }
+                _ => None,
+            };
+            if let Some(token) = token {
+                self.pos += 1;
+                self.emit(token, start);
+                continue;
+            }
+            if c.is_ascii_digit() || c == '.' {
+                let token = self.number()?;
+                self.emit(token, start);
+            } else if c == '"' || c == '\'' {
+                let bytes = self.string(false)?;
+                let s = String::from_utf8(bytes).map_err(|_| self.error(start, "invalid UTF-8"))?;
+                self.emit(Token::String(s), start);
+            } else if c == '_' || c.is_ascii_alphabetic() {
+                let len = self
+                    .rest()
+                    .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                    .unwrap_or(self.rest().len());
+                let word = &self.rest()[..len];
+                let quote = self.rest()[len..].chars().next();
+                if matches!(quote, Some('"' | '\''))
+                    && matches!(
+                        word,
+                        "r" | "R"
+                            | "b"
+                            | "B"
+                            | "rb"
+                            | "rB"
+                            | "Rb"
+                            | "RB"
+                            | "br"
+                            | "bR"
+                            | "Br"
+                            | "BR"
+                    )
+                {
+                    self.pos += len;
+                    let raw = word.contains(['r', 'R']);
+                    let bytes = self.string(raw)?;
+                    let token = if word.contains(['b', 'B']) {
+                        Token::Bytes(bytes)
+                    } else {
+                        Token::String(
+                            String::from_utf8(bytes)
+                                .map_err(|_| self.error(start, "invalid UTF-8"))?,
+                        )
+                    };
+                    self.emit(token, start);
+                    continue;
+                }
+                let token = match word {
+                    "true" => Token::True,
+                    "false" => Token::False,
+                    "null" => Token::Null,
+                    "in" => Token::In,
+                    word if RESERVED.contains(&word) => {
+                        return Err(self.error(start, format!("reserved identifier: {word}")))
+                    }
+                    word => Token::Ident(word.to_string()),
+                };
+                self.pos += len;
+                self.emit(token, start);
+            } else {
+                return Err(self.error(start, format!("token recognition error at: '{c}'")));
+            }
+        }
+    }
+
+    fn skip_space_and_comments(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches([' ', '\t', '\r', '\n', '\x0c']);
+            self.pos += rest.len() - trimmed.len();
+            if self.rest().starts_with("//") {
+                self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Token, CelError> {
+        let start = self.pos;
+        let rest = self.rest();
+        if rest.starts_with("0x") || rest.starts_with("0X") {
+            let digits = rest[2..]
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len() - 2);
+            let text = &rest[2..2 + digits];
+            self.pos += 2 + digits;
+            let value = u64::from_str_radix(text, 16)
+                .map_err(|_| self.error(start, format!("invalid int literal: 0x{text}")))?;
+            return self.integer(start, value);
+        }
+        let bytes = rest.as_bytes();
+        let mut len = 0;
+        let mut float = false;
+        while len < bytes.len() && bytes[len].is_ascii_digit() {
+            len += 1;
+        }
+        if len < bytes.len()
+            && bytes[len] == b'.'
+            && bytes.get(len + 1).is_some_and(u8::is_ascii_digit)
+        {
+            float = true;
+            len += 1;
+            while len < bytes.len() && bytes[len].is_ascii_digit() {
+                len += 1;
+            }
+        }
+        if len < bytes.len() && matches!(bytes[len], b'e' | b'E') {
+            let mut exp = len + 1;
+            if exp < bytes.len() && matches!(bytes[exp], b'+' | b'-') {
+                exp += 1;
+            }
+            if bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+                float = true;
+                len = exp;
+                while len < bytes.len() && bytes[len].is_ascii_digit() {
+                    len += 1;
+                }
+            }
+        }
+        let text = &rest[..len];
+        self.pos += len;
+        if float {
+            let value = text
+                .parse()
+                .map_err(|_| self.error(start, format!("invalid double literal: {text}")))?;
+            return Ok(Token::Double(value));
+        }
+        let value = text
+            .parse::<u64>()
+            .map_err(|_| self.error(start, format!("invalid int literal: {text}")))?;
+        self.integer(start, value)
+    }
+
+    /// Finishes an int literal, which is a uint when followed by `u`.
+    fn integer(&mut self, start: usize, value: u64) -> Result<Token, CelError> {
+        if self.peek().is_some_and(|c| c == 'u' || c == 'U') {
+            self.pos += 1;
+            return Ok(Token::Uint(value));
+        }
+        match i64::try_from(value) {
+            Ok(value) => Ok(Token::Int(value)),
+            Err(_) if value == i64::MIN.unsigned_abs() => Ok(Token::IntMin),
+            Err(_) => Err(self.error(start, format!("invalid int literal: {value}"))),
+        }
+    }
+
+    /// Lexes a quoted string, returning its bytes.
+    fn string(&mut self, raw: bool) -> Result<Vec<u8>, CelError> {
+        let start = self.pos;
+        let quote = self.peek().unwrap_or('"');
+        let triple = quote.to_string().repeat(3);
+        let delimiter = if self.rest().starts_with(&triple) {
+            triple
+        } else {
+            quote.to_string()
+        };
+        self.pos += delimiter.len();
+        let mut out = Vec::new();
+        loop {
+            if self.rest().starts_with(&delimiter) {
+                self.pos += delimiter.len();
+                return Ok(out);
+            }
+            let Some(c) = self.peek() else {
+                return Err(self.error(start, "unterminated string literal"));
+            };
+            if c == '\n' && delimiter.len() == 1 {
+                return Err(self.error(start, "unterminated string literal"));
+            }
+            self.pos += c.len_utf8();
+            if c != '\\' || raw {
+                let mut buf = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                continue;
+            }
+            let escape_pos = self.pos - 1;
+            let Some(e) = self.peek() else {
+                return Err(self.error(start, "unterminated string literal"));
+            };
+            self.pos += e.len_utf8();
+            let simple = match e {
+                'a' => Some(b'\x07'),
+                'b' => Some(b'\x08'),
+                'f' => Some(b'\x0c'),
+                'n' => Some(b'\n'),
+                'r' => Some(b'\r'),
+                't' => Some(b'\t'),
+                'v' => Some(b'\x0b'),
+                '\\' | '\'' | '"' | '`' | '?' => Some(e as u8),
+                _ => None,
+            };
+            if let Some(b) = simple {
+                out.push(b);
+                continue;
+            }
+            let source = self.source;
+            let invalid = move || {
+                CelError::syntax(
+                    source,
+                    escape_pos,
+                    format!("invalid escape sequence: \\{e}"),
+                )
+            };
+            let (digits, radix) = match e {
+                'x' | 'X' => (2, 16),
+                'u' => (4, 16),
+                'U' => (8, 16),
+                '0'..='3' => {
+                    self.pos -= 1;
+                    (3, 8)
+                }
+                _ => return Err(invalid()),
+            };
+            let text = self.rest().get(..digits).ok_or_else(invalid)?;
+            let code = u32::from_str_radix(text, radix).map_err(|_| invalid())?;
+            self.pos += digits;
+            if matches!(e, 'u' | 'U') {
+                let c = char::from_u32(code).ok_or_else(invalid)?;
+                let mut buf = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            } else {
+                out.push(code as u8);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        lex(source)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.token)
+            .collect()
+    }
+
+    fn error(source: &str) -> String {
+        lex(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn lexes_operators_and_identifiers() {
+        use Token::*;
+        assert_eq!(
+            tokens("self.a[0] >= 1 && !(x in y) || z ? -b : c // comment"),
+            vec![
+                Ident("self".into()),
+                Dot,
+                Ident("a".into()),
+                LeftBracket,
+                Int(0),
+                RightBracket,
+                Ge,
+                Int(1),
+                And,
+                Not,
+                LeftParen,
+                Ident("x".into()),
+                In,
+                Ident("y".into()),
+                RightParen,
+                Or,
+                Ident("z".into()),
+                Question,
+                Minus,
+                Ident("b".into()),
+                Colon,
+                Ident("c".into()),
+                Eof,
+            ]
+        );
+        assert_eq!(
+            tokens("{'k': [1, 2u]} == != < <= > + * / % true false null"),
+            vec![
+                LeftBrace,
+                String("k".into()),
+                Colon,
+                LeftBracket,
+                Int(1),
+                Comma,
+                Uint(2),
+                RightBracket,
+                RightBrace,
+                Eq,
+                Ne,
+                Lt,
+                Le,
+                Gt,
+                Plus,
+                Star,
+                Slash,
+                Percent,
+                True,
+                False,
+                Null,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_literals() {
+        for (source, expected) in [
+            ("42", Token::Int(42)),
+            ("0x1F", Token::Int(31)),
+            ("0x1fu", Token::Uint(31)),
+            ("18446744073709551615u", Token::Uint(u64::MAX)),
+            ("9223372036854775807", Token::Int(i64::MAX)),
+            ("9223372036854775808", Token::IntMin),
+            ("1.5", Token::Double(1.5)),
+            (".5", Token::Double(0.5)),
+            ("1e3", Token::Double(1000.0)),
+            ("2.5E-1", Token::Double(0.25)),
+            (r#""a\tb""#, Token::String("a\tb".into())),
+            (r"'\x41é\U0001F600\101'", Token::String("Aé😀A".into())),
+            (r#"'say "hi"'"#, Token::String("say \"hi\"".into())),
+            (r"r'\n'", Token::String("\\n".into())),
+            ("'''a\nb'''", Token::String("a\nb".into())),
+            (r#"b"\xff""#, Token::Bytes(vec![0xff])),
+            (r"rb'\xff'", Token::Bytes(b"\\xff".to_vec())),
+        ] {
+            assert_eq!(tokens(source), vec![expected, Token::Eof], "{source}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for (source, expected) in [
+            (
+                "1 + 18446744073709551616",
+                "ERROR: <input>:1:5: invalid int literal: 18446744073709551616",
+            ),
+            (
+                "9223372036854775809",
+                "ERROR: <input>:1:1: invalid int literal: 9223372036854775809",
+            ),
+            (
+                "0x10000000000000000",
+                "ERROR: <input>:1:1: invalid int literal: 0x10000000000000000",
+            ),
+            ("'abc", "ERROR: <input>:1:1: unterminated string literal"),
+            (
+                "x == 'a\nb'",
+                "ERROR: <input>:1:6: unterminated string literal",
+            ),
+            (r"'\q'", "ERROR: <input>:1:2: invalid escape sequence: \\q"),
+            (
+                r"'\xZZ'",
+                "ERROR: <input>:1:2: invalid escape sequence: \\x",
+            ),
+            (
+                r"'\uD800'",
+                "ERROR: <input>:1:2: invalid escape sequence: \\u",
+            ),
+            (r#"b"\xff" + "\xff""#, "ERROR: <input>:1:11: invalid UTF-8"),
+            ("a #", "ERROR: <input>:1:3: token recognition error at: '#'"),
+            (
+                "x\n  && y @",
+                "ERROR: <input>:2:8: token recognition error at: '@'",
+            ),
+            ("var == 1", "ERROR: <input>:1:1: reserved identifier: var"),
+        ] {
+            assert_eq!(error(source), expected, "{source}");
+        }
+    }
+}
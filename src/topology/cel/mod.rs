@@ -0,0 +1,81 @@
+//! Evaluation of CEL expressions, as used by the `x-kubernetes-validations` rules of ClusterClass
+//! variable schemas.
+//!
+//! Expressions are evaluated dynamically, without the type checking the apiserver does when a
+//! ClusterClass is written, so a type mismatch shows up as an evaluation error. Besides the
+//! standard definitions and macros, the string, list and regex extensions the apiserver enables
+//! are available, e.g. `lowerAscii`, `split`, `sort` and `findAll`. Optional values and the
+//! Kubernetes URL, IP and quantity libraries are not supported.
+
+use std::collections::BTreeMap;
+
+mod eval;
+mod funcs;
+mod lex;
+mod parse;
+mod value;
+
+pub use value::{Key, Value};
+
+/// Variables an expression is evaluated with, e.g. `self` and `oldSelf`.
+pub type Activation = BTreeMap<String, Value>;
+
+/// A parsed CEL expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    source: String,
+    expr: parse::Expr,
+}
+
+impl Program {
+    pub fn compile(source: impl Into<String>) -> Result<Self, CelError> {
+        let source = source.into();
+        let expr = parse::parse(&source)?;
+        Ok(Self { source, expr })
+    }
+
+    /// Evaluates the expression with the given variables.
+    pub fn evaluate(&self, activation: &Activation) -> Result<Value, CelError> {
+        eval::Evaluator::new(activation)
+            .eval(&self.expr)
+            .map_err(CelError::Evaluation)
+    }
+
+    /// Whether the expression refers to a variable, e.g. whether a rule is a transition rule
+    /// referring to `oldSelf`.
+    pub fn references(&self, variable: &str) -> bool {
+        self.expr.references(variable)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Error compiling or evaluating an expression.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CelError {
+    /// The expression does not parse, formatted like cel-go's issues.
+    #[error("ERROR: <input>:{line}:{column}: {message}")]
+    Syntax {
+        /// Line of the error, starting at 1.
+        line: usize,
+        /// Column of the error in characters, starting at 1.
+        column: usize,
+        message: String,
+    },
+    #[error("{0}")]
+    Evaluation(String),
+}
+
+impl CelError {
+    pub(crate) fn syntax(source: &str, pos: usize, message: impl Into<String>) -> Self {
+        let before = &source[..pos.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self::Syntax {
+            line: 1 + before.matches('\n').count(),
+            column: 1 + before[line_start..].chars().count(),
+            message: message.into(),
+        }
+    }
+}
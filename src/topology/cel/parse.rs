@@ -0,0 +1,693 @@
+//! Parser for CEL expressions. Macros (`has`, `all`, `exists`, `exists_one`, `map` and
+//! `filter`) are expanded while parsing, as cel-go does.
+
+use super::lex::{lex, Item, Token};
+use super::value::Value;
+use super::CelError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Not,
+    Negate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    In,
+}
+
+impl BinaryOp {
+    pub(super) fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "_+_",
+            BinaryOp::Subtract => "_-_",
+            BinaryOp::Multiply => "_*_",
+            BinaryOp::Divide => "_/_",
+            BinaryOp::Modulo => "_%_",
+            BinaryOp::Equal => "_==_",
+            BinaryOp::NotEqual => "_!=_",
+            BinaryOp::Less => "_<_",
+            BinaryOp::LessEqual => "_<=_",
+            BinaryOp::Greater => "_>_",
+            BinaryOp::GreaterEqual => "_>=_",
+            BinaryOp::In => "@in",
+        }
+    }
+}
+
+/// The comprehension macros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Macro {
+    All,
+    Exists,
+    ExistsOne,
+    /// `map(x, f)`, or `map(x, p, f)` with a filter.
+    Map,
+    Filter,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Expr {
+    Literal(Value),
+    Ident(String),
+    /// Field selection, `test` is set for the `has()` macro.
+    Select {
+        operand: Box<Expr>,
+        field: String,
+        test: bool,
+    },
+    Index(Box<Expr>, Box<Expr>),
+    Call {
+        target: Option<Box<Expr>>,
+        function: String,
+        args: Vec<Expr>,
+    },
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    List(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>),
+    Comprehension {
+        kind: Macro,
+        range: Box<Expr>,
+        var: String,
+        /// The predicate, or the transform for `map` without a filter.
+        body: Box<Expr>,
+        /// The transform of `map` with a filter.
+        transform: Option<Box<Expr>>,
+    },
+}
+
+impl Expr {
+    /// Whether the expression refers to a variable, ignoring comprehension variables.
+    pub(super) fn references(&self, name: &str) -> bool {
+        match self {
+            Expr::Literal(_) => false,
+            Expr::Ident(ident) => ident == name,
+            Expr::Select { operand, .. } => operand.references(name),
+            Expr::Index(a, b) | Expr::Binary(_, a, b) | Expr::And(a, b) | Expr::Or(a, b) => {
+                a.references(name) || b.references(name)
+            }
+            Expr::Call { target, args, .. } => {
+                target.as_ref().is_some_and(|t| t.references(name))
+                    || args.iter().any(|a| a.references(name))
+            }
+            Expr::Unary(_, a) => a.references(name),
+            Expr::Conditional(a, b, c) => {
+                a.references(name) || b.references(name) || c.references(name)
+            }
+            Expr::List(items) => items.iter().any(|i| i.references(name)),
+            Expr::Map(entries) => entries
+                .iter()
+                .any(|(k, v)| k.references(name) || v.references(name)),
+            Expr::Comprehension {
+                range,
+                var,
+                body,
+                transform,
+                ..
+            } => {
+                range.references(name)
+                    || (var != name
+                        && (body.references(name)
+                            || transform.as_ref().is_some_and(|t| t.references(name))))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    items: Vec<Item>,
+    pos: usize,
+    depth: usize,
+}
+
+/// Maximum nesting of expressions, counting parentheses and each operator, selection and index
+/// of a chain. Lower than cel-go's default of 250, so parsing and evaluating an expression stays
+/// well within the 2 MiB stack of a spawned thread, even in debug builds.
+const MAX_DEPTH: usize = 100;
+
+pub(super) fn parse(source: &str) -> Result<Expr, CelError> {
+    let mut parser = Parser {
+        source,
+        items: lex(source)?,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expr()?;
+    if parser.peek() != &Token::Eof {
+        return Err(parser.unexpected());
+    }
+    Ok(expr)
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.items[self.pos].token
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.items[self.pos].token.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> CelError {
+        CelError::syntax(self.source, pos, message)
+    }
+
+    fn unexpected(&self) -> CelError {
+        let item = &self.items[self.pos];
+        let text = match &item.token {
+            Token::Eof => "<EOF>".to_string(),
+            _ => {
+                let end = self
+                    .items
+                    .get(self.pos + 1)
+                    .map_or(self.source.len(), |next| next.pos);
+                self.source[item.pos..end].trim_end().to_string()
+            }
+        };
+        self.error_at(item.pos, format!("Syntax error: mismatched input '{text}'"))
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), CelError> {
+        if self.accept(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Enters a nested expression, the caller restores the depth once it is parsed.
+    fn nest(&mut self) -> Result<(), CelError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error_at(
+                self.items[self.pos].pos,
+                "expression recursion limit exceeded",
+            ));
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        self.nest()?;
+        let condition = self.or()?;
+        let expr = if self.accept(&Token::Question) {
+            let then = self.or()?;
+            self.expect(&Token::Colon)?;
+            let otherwise = self.expr()?;
+            Expr::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise))
+        } else {
+            condition
+        };
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut left = self.and()?;
+        while self.accept(&Token::Or) {
+            self.nest()?;
+            let right = self.and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut left = self.relation()?;
+        while self.accept(&Token::And) {
+            self.nest()?;
+            let right = self.relation()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn relation(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut left = self.addition()?;
+        loop {
+            let op = match self.peek() {
+                Token::Eq => BinaryOp::Equal,
+                Token::Ne => BinaryOp::NotEqual,
+                Token::Lt => BinaryOp::Less,
+                Token::Le => BinaryOp::LessEqual,
+                Token::Gt => BinaryOp::Greater,
+                Token::Ge => BinaryOp::GreaterEqual,
+                Token::In => BinaryOp::In,
+                _ => break,
+            };
+            self.pos += 1;
+            self.nest()?;
+            let right = self.addition()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn addition(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut left = self.multiplication()?;
+        loop {
+            let op = match self.peek() {
+                Token::Plus => BinaryOp::Add,
+                Token::Minus => BinaryOp::Subtract,
+                _ => break,
+            };
+            self.pos += 1;
+            self.nest()?;
+            let right = self.multiplication()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn multiplication(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Star => BinaryOp::Multiply,
+                Token::Slash => BinaryOp::Divide,
+                Token::Percent => BinaryOp::Modulo,
+                _ => break,
+            };
+            self.pos += 1;
+            self.nest()?;
+            let right = self.unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CelError> {
+        match self.peek() {
+            Token::Not => {
+                self.pos += 1;
+                self.nest()?;
+                let operand = self.unary()?;
+                self.depth -= 1;
+                Ok(Expr::Unary(UnaryOp::Not, Box::new(operand)))
+            }
+            Token::Minus => {
+                self.pos += 1;
+                // negative literals are folded, so the smallest int can be written
+                match self.peek().clone() {
+                    Token::Int(i) if !self.is_member_next() => {
+                        self.pos += 1;
+                        Ok(Expr::Literal(Value::Int(-i)))
+                    }
+                    Token::IntMin if !self.is_member_next() => {
+                        self.pos += 1;
+                        Ok(Expr::Literal(Value::Int(i64::MIN)))
+                    }
+                    Token::Double(d) if !self.is_member_next() => {
+                        self.pos += 1;
+                        Ok(Expr::Literal(Value::Double(-d)))
+                    }
+                    _ => {
+                        self.nest()?;
+                        let operand = self.unary()?;
+                        self.depth -= 1;
+                        Ok(Expr::Unary(UnaryOp::Negate, Box::new(operand)))
+                    }
+                }
+            }
+            _ => self.member(),
+        }
+    }
+
+    /// Whether the token after the current one continues a member expression.
+    fn is_member_next(&self) -> bool {
+        matches!(
+            self.items.get(self.pos + 1).map(|item| &item.token),
+            Some(Token::Dot | Token::LeftBracket)
+        )
+    }
+
+    fn member(&mut self) -> Result<Expr, CelError> {
+        let depth = self.depth;
+        let mut expr = self.primary()?;
+        loop {
+            match self.peek() {
+                Token::Dot => {
+                    self.pos += 1;
+                    self.nest()?;
+                    let Token::Ident(name) = self.peek().clone() else {
+                        return Err(self.unexpected());
+                    };
+                    self.pos += 1;
+                    if self.accept(&Token::LeftParen) {
+                        let args = self.args(&Token::RightParen)?;
+                        expr = self.method(expr, name, args)?;
+                    } else {
+                        expr = Expr::Select {
+                            operand: Box::new(expr),
+                            field: name,
+                            test: false,
+                        };
+                    }
+                }
+                Token::LeftBracket => {
+                    self.pos += 1;
+                    self.nest()?;
+                    let index = self.expr()?;
+                    self.expect(&Token::RightBracket)?;
+                    expr = Expr::Index(Box::new(expr), Box::new(index));
+                }
+                _ => {
+                    self.depth = depth;
+                    return Ok(expr);
+                }
+            }
+        }
+    }
+
+    /// Parses expressions up to the closing token, allowing a trailing comma.
+    fn args(&mut self, close: &Token) -> Result<Vec<Expr>, CelError> {
+        let mut args = Vec::new();
+        while !self.accept(close) {
+            args.push(self.expr()?);
+            if !self.accept(&Token::Comma) {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn method(&mut self, target: Expr, name: String, args: Vec<Expr>) -> Result<Expr, CelError> {
+        let kind = match (name.as_str(), args.len()) {
+            ("all", 2) => Some(Macro::All),
+            ("exists", 2) => Some(Macro::Exists),
+            ("exists_one", 2) => Some(Macro::ExistsOne),
+            ("map", 2 | 3) => Some(Macro::Map),
+            ("filter", 2) => Some(Macro::Filter),
+            _ => None,
+        };
+        let Some(kind) = kind else {
+            return Ok(Expr::Call {
+                target: Some(Box::new(target)),
+                function: name,
+                args,
+            });
+        };
+        let mut args = args.into_iter();
+        let Some(Expr::Ident(var)) = args.next() else {
+            return Err(self.error_at(
+                self.items[self.pos - 1].pos,
+                format!("{name}() variable name must be a simple identifier"),
+            ));
+        };
+        let body = args.next().unwrap_or(Expr::Literal(Value::Null));
+        let transform = args.next().map(Box::new);
+        Ok(Expr::Comprehension {
+            kind,
+            range: Box::new(target),
+            var,
+            body: Box::new(body),
+            transform,
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr, CelError> {
+        let pos = self.items[self.pos].pos;
+        let token = self.next();
+        Ok(match token {
+            Token::Int(i) => Expr::Literal(Value::Int(i)),
+            Token::IntMin => {
+                return Err(self.error_at(pos, "invalid int literal: 9223372036854775808"))
+            }
+            Token::Uint(u) => Expr::Literal(Value::Uint(u)),
+            Token::Double(d) => Expr::Literal(Value::Double(d)),
+            Token::String(s) => Expr::Literal(Value::String(s)),
+            Token::Bytes(b) => Expr::Literal(Value::Bytes(b)),
+            Token::True => Expr::Literal(Value::Bool(true)),
+            Token::False => Expr::Literal(Value::Bool(false)),
+            Token::Null => Expr::Literal(Value::Null),
+            Token::LeftParen => {
+                let expr = self.expr()?;
+                self.expect(&Token::RightParen)?;
+                expr
+            }
+            Token::LeftBracket => Expr::List(self.args(&Token::RightBracket)?),
+            Token::LeftBrace => {
+                let mut entries = Vec::new();
+                while !self.accept(&Token::RightBrace) {
+                    let key = self.expr()?;
+                    self.expect(&Token::Colon)?;
+                    let value = self.expr()?;
+                    entries.push((key, value));
+                    if !self.accept(&Token::Comma) {
+                        self.expect(&Token::RightBrace)?;
+                        break;
+                    }
+                }
+                Expr::Map(entries)
+            }
+            // a leading dot refers to the root scope, which is the only scope here
+            Token::Dot => return self.primary(),
+            Token::Ident(name) => {
+                if !self.accept(&Token::LeftParen) {
+                    return Ok(Expr::Ident(name));
+                }
+                let args = self.args(&Token::RightParen)?;
+                if name == "has" {
+                    return match <[Expr; 1]>::try_from(args) {
+                        Ok([Expr::Select { operand, field, .. }]) => Ok(Expr::Select {
+                            operand,
+                            field,
+                            test: true,
+                        }),
+                        _ => Err(self.error_at(pos, "invalid argument to has() macro")),
+                    };
+                }
+                Expr::Call {
+                    target: None,
+                    function: name,
+                    args,
+                }
+            }
+            other => {
+                if other != Token::Eof {
+                    self.pos -= 1;
+                }
+                return Err(self.unexpected());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_precedence_and_macros() {
+        let int = |i| Box::new(Expr::Literal(Value::Int(i)));
+        let ident = |name: &str| Box::new(Expr::Ident(name.to_string()));
+        for (source, expected) in [
+            (
+                "1 + 2 * 3",
+                Expr::Binary(
+                    BinaryOp::Add,
+                    int(1),
+                    Box::new(Expr::Binary(BinaryOp::Multiply, int(2), int(3))),
+                ),
+            ),
+            (
+                "a || b && c",
+                Expr::Or(ident("a"), Box::new(Expr::And(ident("b"), ident("c")))),
+            ),
+            (
+                "a ? b : c ? 1 : 2",
+                Expr::Conditional(
+                    ident("a"),
+                    ident("b"),
+                    Box::new(Expr::Conditional(ident("c"), int(1), int(2))),
+                ),
+            ),
+            ("-9223372036854775808", Expr::Literal(Value::Int(i64::MIN))),
+            ("-a", Expr::Unary(UnaryOp::Negate, ident("a"))),
+            (
+                "!!a",
+                Expr::Unary(
+                    UnaryOp::Not,
+                    Box::new(Expr::Unary(UnaryOp::Not, ident("a"))),
+                ),
+            ),
+            (
+                "has(self.a)",
+                Expr::Select {
+                    operand: ident("self"),
+                    field: "a".into(),
+                    test: true,
+                },
+            ),
+            (
+                "l.map(x, x > 0, x * 2)",
+                Expr::Comprehension {
+                    kind: Macro::Map,
+                    range: ident("l"),
+                    var: "x".into(),
+                    body: Box::new(Expr::Binary(BinaryOp::Greater, ident("x"), int(0))),
+                    transform: Some(Box::new(Expr::Binary(
+                        BinaryOp::Multiply,
+                        ident("x"),
+                        int(2),
+                    ))),
+                },
+            ),
+            (
+                "[1, 2,].exists_one(x, x == 1)",
+                Expr::Comprehension {
+                    kind: Macro::ExistsOne,
+                    range: Box::new(Expr::List(vec![*int(1), *int(2)])),
+                    var: "x".into(),
+                    body: Box::new(Expr::Binary(BinaryOp::Equal, ident("x"), int(1))),
+                    transform: None,
+                },
+            ),
+            (
+                "s.all(x)",
+                Expr::Call {
+                    target: Some(ident("s")),
+                    function: "all".into(),
+                    args: vec![*ident("x")],
+                },
+            ),
+            (
+                ".size(x)",
+                Expr::Call {
+                    target: None,
+                    function: "size".into(),
+                    args: vec![*ident("x")],
+                },
+            ),
+        ] {
+            assert_eq!(parse(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn references() {
+        for (source, variable, expected) in [
+            ("self == oldSelf", "oldSelf", true),
+            ("self.a > 0", "oldSelf", false),
+            ("self.all(oldSelf, oldSelf > 0)", "oldSelf", false),
+            ("oldSelf.all(x, x > 0)", "oldSelf", true),
+            ("self.all(x, x in oldSelf)", "oldSelf", true),
+            ("has(oldSelf.a)", "oldSelf", true),
+        ] {
+            assert_eq!(
+                parse(source).unwrap().references(variable),
+                expected,
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for (source, expected) in [
+            (
+                "",
+                "ERROR: <input>:1:1: Syntax error: mismatched input '<EOF>'",
+            ),
+            (
+                "1 +",
+                "ERROR: <input>:1:4: Syntax error: mismatched input '<EOF>'",
+            ),
+            (
+                "(1",
+                "ERROR: <input>:1:3: Syntax error: mismatched input '<EOF>'",
+            ),
+            (
+                "1 2",
+                "ERROR: <input>:1:3: Syntax error: mismatched input '2'",
+            ),
+            (
+                "a ? b",
+                "ERROR: <input>:1:6: Syntax error: mismatched input '<EOF>'",
+            ),
+            (
+                "a.1",
+                "ERROR: <input>:1:2: Syntax error: mismatched input '.1'",
+            ),
+            (
+                "{1: 2",
+                "ERROR: <input>:1:6: Syntax error: mismatched input '<EOF>'",
+            ),
+            (
+                "9223372036854775808",
+                "ERROR: <input>:1:1: invalid int literal: 9223372036854775808",
+            ),
+            (
+                "has(a)",
+                "ERROR: <input>:1:1: invalid argument to has() macro",
+            ),
+            (
+                "l.all(x.y, true)",
+                "ERROR: <input>:1:16: all() variable name must be a simple identifier",
+            ),
+            (
+                &format!("{}1{}", "(".repeat(101), ")".repeat(101)),
+                "ERROR: <input>:1:101: expression recursion limit exceeded",
+            ),
+            (
+                &format!("{}a", "!".repeat(100)),
+                "ERROR: <input>:1:101: expression recursion limit exceeded",
+            ),
+            (
+                &format!("a{}", " + a".repeat(100)),
+                "ERROR: <input>:1:401: expression recursion limit exceeded",
+            ),
+            (
+                &format!("a{}", ".b".repeat(100)),
+                "ERROR: <input>:1:201: expression recursion limit exceeded",
+            ),
+        ] {
+            assert_eq!(error(source), expected, "{source}");
+        }
+    }
+}
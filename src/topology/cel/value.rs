@@ -0,0 +1,446 @@
+//! Values handled by CEL expressions and their conversion from JSON values typed by an
+//! OpenAPI schema, the way the apiserver binds `self` in `x-kubernetes-validations` rules.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use base64::Engine;
+use k8s_openapi::chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::api::duration::GoDuration;
+
+/// Keys of CEL maps.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    String(String),
+}
+
+impl Key {
+    pub fn to_value(&self) -> Value {
+        match self {
+            Key::Bool(b) => Value::Bool(*b),
+            Key::Int(i) => Value::Int(*i),
+            Key::Uint(u) => Value::Uint(*u),
+            Key::String(s) => Value::String(s.clone()),
+        }
+    }
+}
+
+/// A CEL value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Double(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Map(BTreeMap<Key, Value>),
+    /// `google.protobuf.Duration`, in nanoseconds.
+    Duration(i64),
+    /// `google.protobuf.Timestamp`.
+    Timestamp(DateTime<Utc>),
+    /// A type, e.g. the result of `type(self)` or the identifier `string`.
+    Type(String),
+}
+
+/// Names of the types that can be referred to by identifier, e.g. `type(x) == int`.
+pub(super) const TYPE_NAMES: &[&str] = &[
+    "bool",
+    "bytes",
+    "double",
+    "int",
+    "list",
+    "map",
+    "null_type",
+    "string",
+    "type",
+    "uint",
+    "google.protobuf.Duration",
+    "google.protobuf.Timestamp",
+];
+
+impl Value {
+    /// The CEL type name, e.g. `int` or `google.protobuf.Duration`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null_type",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Uint(_) => "uint",
+            Value::Double(_) => "double",
+            Value::String(_) => "string",
+            Value::Bytes(_) => "bytes",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Duration(_) => "google.protobuf.Duration",
+            Value::Timestamp(_) => "google.protobuf.Timestamp",
+            Value::Type(_) => "type",
+        }
+    }
+
+    pub fn to_key(&self) -> Option<Key> {
+        match self {
+            Value::Bool(b) => Some(Key::Bool(*b)),
+            Value::Int(i) => Some(Key::Int(*i)),
+            Value::Uint(u) => Some(Key::Uint(*u)),
+            Value::String(s) => Some(Key::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    /// Converts JSON to a CEL value following the schema of the value: `integer` and `number`
+    /// become ints and doubles, and strings with a `byte`, `duration`, `date` or `date-time`
+    /// format become bytes, durations and timestamps. Without a schema, JSON types are used.
+    pub fn from_json(value: &serde_json::Value, schema: Option<&serde_json::Value>) -> Self {
+        let ty = schema.and_then(|s| s.get("type")).and_then(|t| t.as_str());
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match (ty, n.as_i64(), n.as_u64()) {
+                (Some("number"), _, _) => Value::Double(n.as_f64().unwrap_or_default()),
+                (_, Some(i), _) => Value::Int(i),
+                (_, None, Some(u)) => Value::Uint(u),
+                _ => {
+                    let f = n.as_f64().unwrap_or_default();
+                    if ty == Some("integer") && f.fract() == 0.0 {
+                        Value::Int(f as i64)
+                    } else {
+                        Value::Double(f)
+                    }
+                }
+            },
+            serde_json::Value::String(s) => {
+                let format = schema
+                    .and_then(|s| s.get("format"))
+                    .and_then(|f| f.as_str());
+                match format {
+                    Some("byte") => base64::engine::general_purpose::STANDARD
+                        .decode(s)
+                        .map_or_else(|_| Value::String(s.clone()), Value::Bytes),
+                    Some("duration") => s.parse::<GoDuration>().map_or_else(
+                        |_| Value::String(s.clone()),
+                        |d| Value::Duration(d.as_nanos()),
+                    ),
+                    Some("date-time") => DateTime::parse_from_rfc3339(s).map_or_else(
+                        |_| Value::String(s.clone()),
+                        |t| Value::Timestamp(t.with_timezone(&Utc)),
+                    ),
+                    Some("date") => DateTime::parse_from_rfc3339(&format!("{s}T00:00:00Z"))
+                        .map_or_else(
+                            |_| Value::String(s.clone()),
+                            |t| Value::Timestamp(t.with_timezone(&Utc)),
+                        ),
+                    _ => Value::String(s.clone()),
+                }
+            }
+            serde_json::Value::Array(items) => {
+                let items_schema = schema.and_then(|s| s.get("items"));
+                Value::List(
+                    items
+                        .iter()
+                        .map(|item| Value::from_json(item, items_schema))
+                        .collect(),
+                )
+            }
+            serde_json::Value::Object(object) => {
+                let properties = schema.and_then(|s| s.get("properties"));
+                let additional = schema
+                    .and_then(|s| s.get("additionalProperties"))
+                    .filter(|s| s.is_object());
+                Value::Map(
+                    object
+                        .iter()
+                        .map(|(key, value)| {
+                            let schema = properties.and_then(|p| p.get(key)).or(additional);
+                            (Key::String(key.clone()), Value::from_json(value, schema))
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Converts to JSON; bytes are base64 encoded, durations and timestamps are strings.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => (*b).into(),
+            Value::Int(i) => (*i).into(),
+            Value::Uint(u) => (*u).into(),
+            Value::Double(f) => {
+                serde_json::Number::from_f64(*f).map_or(serde_json::Value::Null, Into::into)
+            }
+            Value::String(s) => s.clone().into(),
+            Value::Bytes(b) => base64::engine::general_purpose::STANDARD.encode(b).into(),
+            Value::List(items) => items.iter().map(Value::to_json).collect(),
+            Value::Map(map) => map
+                .iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        Key::String(s) => s.clone(),
+                        key => key.to_value().to_string(),
+                    };
+                    (key, value.to_json())
+                })
+                .collect(),
+            Value::Duration(_) | Value::Timestamp(_) | Value::Type(_) => self.to_string().into(),
+        }
+    }
+
+    /// Equality as defined by CEL: numbers compare by value across int, uint and double, and
+    /// values of different types are not equal.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::List(a), Value::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.equals(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.equals(b)))
+            }
+            (a, b) => match a.compare(b) {
+                Some(ordering) => ordering == Ordering::Equal,
+                None => a == b,
+            },
+        }
+    }
+
+    /// Ordering of comparable values, `None` if the values cannot be compared.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+            (Value::Duration(a), Value::Duration(b)) => Some(a.cmp(b)),
+            (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Uint(a), Value::Uint(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Uint(b)) => Some(i128::from(*a).cmp(&i128::from(*b))),
+            (Value::Uint(a), Value::Int(b)) => Some(i128::from(*a).cmp(&i128::from(*b))),
+            (a, b) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Uint(u) => Some(*u as f64),
+            Value::Double(f) => Some(*f),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a double like CEL's `string(double)`.
+pub(super) fn format_double(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else if f != 0.0 && (f.abs() >= 1e21 || f.abs() < 1e-4) {
+        let s = format!("{f:e}");
+        // Go writes the exponent with a sign and at least two digits, e.g. 1e+21
+        match s.split_once('e') {
+            Some((mantissa, exp)) => {
+                let (sign, digits) = match exp.strip_prefix('-') {
+                    Some(digits) => ('-', digits),
+                    None => ('+', exp),
+                };
+                format!("{mantissa}e{sign}{digits:0>2}")
+            }
+            None => s,
+        }
+    } else {
+        format!("{f}")
+    }
+}
+
+/// Formats a duration like CEL's `string(duration)`, i.e. in seconds such as `90s` or `1.5s`.
+pub(super) fn format_duration(nanos: i64) -> String {
+    let seconds = nanos / 1_000_000_000;
+    let fraction = (nanos % 1_000_000_000).unsigned_abs();
+    if fraction == 0 {
+        return format!("{seconds}s");
+    }
+    let sign = if nanos < 0 && seconds == 0 { "-" } else { "" };
+    let fraction = format!("{fraction:09}");
+    format!("{sign}{seconds}.{}s", fraction.trim_end_matches('0'))
+}
+
+impl fmt::Display for Value {
+    /// Formats like CEL's `string()` conversion, and lists and maps like CEL literals.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Uint(u) => write!(f, "{u}"),
+            Value::Double(d) => f.write_str(&format_double(*d)),
+            Value::String(s) => f.write_str(s),
+            Value::Bytes(b) => f.write_str(&String::from_utf8_lossy(b)),
+            Value::List(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(map) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {value}", key.to_value())?;
+                }
+                f.write_str("}")
+            }
+            Value::Duration(nanos) => f.write_str(&format_duration(*nanos)),
+            Value::Timestamp(t) => f.write_str(&t.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            Value::Type(name) => f.write_str(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn converts_json_by_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "int": {"type": "integer"},
+                "num": {"type": "number"},
+                "big": {"type": "integer"},
+                "bytes": {"type": "string", "format": "byte"},
+                "badBytes": {"type": "string", "format": "byte"},
+                "dur": {"type": "string", "format": "duration"},
+                "date": {"type": "string", "format": "date"},
+                "time": {"type": "string", "format": "date-time"},
+                "list": {"type": "array", "items": {"type": "number"}},
+            },
+            "additionalProperties": {"type": "string", "format": "duration"},
+        });
+        let value = Value::from_json(
+            &json!({
+                "int": 1,
+                "num": 1,
+                "big": 18446744073709551615u64,
+                "bytes": "aGk=",
+                "badBytes": "!",
+                "dur": "1m30s",
+                "date": "2024-03-01",
+                "time": "2024-03-01T12:00:00Z",
+                "list": [1, 2],
+                "other": "1s",
+            }),
+            Some(&schema),
+        );
+        let date = |s| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let expected = [
+            ("int", Value::Int(1)),
+            ("num", Value::Double(1.0)),
+            ("big", Value::Uint(u64::MAX)),
+            ("bytes", Value::Bytes(b"hi".to_vec())),
+            ("badBytes", Value::String("!".into())),
+            ("dur", Value::Duration(90_000_000_000)),
+            ("date", Value::Timestamp(date("2024-03-01T00:00:00Z"))),
+            ("time", Value::Timestamp(date("2024-03-01T12:00:00Z"))),
+            (
+                "list",
+                Value::List(vec![Value::Double(1.0), Value::Double(2.0)]),
+            ),
+            ("other", Value::Duration(1_000_000_000)),
+        ];
+        assert_eq!(
+            value,
+            Value::Map(
+                expected
+                    .into_iter()
+                    .map(|(k, v)| (Key::String(k.into()), v))
+                    .collect()
+            )
+        );
+        assert_eq!(
+            Value::from_json(&json!({"a": [1, 1.5, "s", null, true]}), None).to_json(),
+            json!({"a": [1, 1.5, "s", null, true]})
+        );
+    }
+
+    #[test]
+    fn converts_to_json() {
+        let map = Value::Map(BTreeMap::from([
+            (Key::Int(1), Value::Bytes(b"hi".to_vec())),
+            (Key::Bool(true), Value::Duration(1_500_000_000)),
+            (Key::String("nan".into()), Value::Double(f64::NAN)),
+            (Key::Uint(2), Value::Type("int".into())),
+        ]));
+        assert_eq!(
+            map.to_json(),
+            json!({"true": "1.5s", "1": "aGk=", "2": "int", "nan": null})
+        );
+    }
+
+    #[test]
+    fn compares() {
+        for (a, b, expected) in [
+            (Value::Int(1), Value::Uint(1), Some(Ordering::Equal)),
+            (Value::Int(-1), Value::Uint(u64::MAX), Some(Ordering::Less)),
+            (Value::Uint(2), Value::Double(1.5), Some(Ordering::Greater)),
+            (Value::Double(f64::NAN), Value::Double(f64::NAN), None),
+            (Value::String("a".into()), Value::Int(1), None),
+            (Value::Null, Value::Null, None),
+            (Value::Bool(false), Value::Bool(true), Some(Ordering::Less)),
+        ] {
+            assert_eq!(a.compare(&b), expected, "{a:?} {b:?}");
+        }
+        assert!(Value::Null.equals(&Value::Null));
+        assert!(!Value::Double(f64::NAN).equals(&Value::Double(f64::NAN)));
+        assert!(!Value::Int(1).equals(&Value::String("1".into())));
+        assert!(Value::List(vec![Value::Int(1)]).equals(&Value::List(vec![Value::Double(1.0)])));
+    }
+
+    #[test]
+    fn formats() {
+        for (value, expected) in [
+            (Value::Double(1.0), "1"),
+            (Value::Double(-0.5), "-0.5"),
+            (Value::Double(1e21), "1e+21"),
+            (Value::Double(1.5e-7), "1.5e-07"),
+            (Value::Double(f64::INFINITY), "+Inf"),
+            (Value::Double(f64::NEG_INFINITY), "-Inf"),
+            (Value::Double(f64::NAN), "NaN"),
+            (Value::Duration(90_000_000_000), "90s"),
+            (Value::Duration(1_500_000_000), "1.5s"),
+            (Value::Duration(-500_000_000), "-0.5s"),
+            (Value::Duration(-1_000_000_001), "-1.000000001s"),
+            (Value::Bytes(b"hi".to_vec()), "hi"),
+            (
+                Value::List(vec![Value::Int(1), Value::String("a".into())]),
+                "[1, a]",
+            ),
+            (
+                Value::Map(BTreeMap::from([(Key::String("k".into()), Value::Null)])),
+                "{k: null}",
+            ),
+        ] {
+            assert_eq!(value.to_string(), expected, "{value:?}");
+        }
+    }
+}
@@ -0,0 +1,475 @@
+//! Compatibility of ClusterClass changes, following the upstream `internal/topology/check`
+//! package and the ClusterClass and Cluster webhooks.
+//!
+//! A ClusterClass can be changed in place, or a Cluster rebased onto another ClusterClass, as
+//! long as the templates keep their apiVersion group and kind, and nothing a Cluster uses is
+//! removed.
+
+use k8s_openapi::api::core::v1::ObjectReference;
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::{ClusterClass, ClusterClassSpec};
+
+use super::field::{self, FieldError};
+use super::variables::{
+    class_variables, validate_cluster_variable, ClassVariable, ClusterVariable,
+};
+
+/// Checks that a ClusterClass spec can be replaced by the desired one, either by updating the
+/// ClusterClass or by rebasing a Cluster from one ClusterClass to the other.
+///
+/// Errors about the ClusterClasses have paths in the desired ClusterClass. With the Cluster
+/// using the ClusterClass, the MachineDeployment and MachinePool classes and the variables it
+/// uses are checked too: classes must not be removed, and its variables must still be defined
+/// and valid. Errors about the values of its variables have paths in the Cluster. Variables
+/// are only checked if the desired ClusterClass has no external patches, as these define
+/// variables that are not part of the spec.
+pub fn check_class_compatibility(
+    current: &ClusterClassSpec,
+    desired: &ClusterClassSpec,
+    cluster: Option<&Cluster>,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if let (Some(current), Some(desired)) = (&current.infrastructure, &desired.infrastructure) {
+        errors.extend(check_references(
+            &current.r#ref,
+            &desired.r#ref,
+            "spec.infrastructure.ref",
+        ));
+    }
+    if let (Some(current), Some(desired)) = (&current.control_plane, &desired.control_plane) {
+        errors.extend(check_references(
+            &current.r#ref,
+            &desired.r#ref,
+            "spec.controlPlane.ref",
+        ));
+        if let (Some(current), Some(desired)) = (
+            &current.machine_infrastructure,
+            &desired.machine_infrastructure,
+        ) {
+            errors.extend(check_references(
+                &current.r#ref,
+                &desired.r#ref,
+                "spec.controlPlane.machineInfrastructure.ref",
+            ));
+        }
+    }
+
+    // bootstrap templates may change, the infrastructure templates of classes kept must stay
+    // compatible
+    let current_workers = current.workers.as_ref();
+    let desired_workers = desired.workers.as_ref();
+    let current_mds = current_workers
+        .and_then(|workers| workers.machine_deployments.as_deref())
+        .unwrap_or_default();
+    for (i, md) in desired_workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(current) = current_mds.iter().find(|c| c.class == md.class) {
+            errors.extend(check_references(
+                &current.template.infrastructure.r#ref,
+                &md.template.infrastructure.r#ref,
+                &field::child(
+                    &field::index("spec.workers.machineDeployments", i),
+                    "template.infrastructure.ref",
+                ),
+            ));
+        }
+    }
+    let current_mps = current_workers
+        .and_then(|workers| workers.machine_pools.as_deref())
+        .unwrap_or_default();
+    for (i, mp) in desired_workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(current) = current_mps.iter().find(|c| c.class == mp.class) {
+            errors.extend(check_references(
+                &current.template.infrastructure.r#ref,
+                &mp.template.infrastructure.r#ref,
+                &field::child(
+                    &field::index("spec.workers.machinePools", i),
+                    "template.infrastructure.ref",
+                ),
+            ));
+        }
+    }
+
+    if let Some(cluster) = cluster {
+        errors.extend(check_cluster(desired, cluster));
+    }
+    errors
+}
+
+/// Checks that a template reference keeps its apiVersion group and kind.
+fn check_references(
+    current: &ObjectReference,
+    desired: &ObjectReference,
+    path: &str,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let current_group = api_group(current.api_version.as_deref().unwrap_or_default());
+    let desired_group = api_group(desired.api_version.as_deref().unwrap_or_default());
+    if current_group != desired_group {
+        errors.push(FieldError::forbidden(
+            path,
+            format!("apiGroup must not be changed from {current_group:?} to {desired_group:?}"),
+        ));
+    }
+    let current_kind = current.kind.as_deref().unwrap_or_default();
+    let desired_kind = desired.kind.as_deref().unwrap_or_default();
+    if current_kind != desired_kind {
+        errors.push(FieldError::forbidden(
+            path,
+            format!("kind must not be changed from {current_kind:?} to {desired_kind:?}"),
+        ));
+    }
+    errors
+}
+
+/// The group of an apiVersion, empty for the core group.
+fn api_group(api_version: &str) -> &str {
+    api_version.split_once('/').map_or("", |(group, _)| group)
+}
+
+/// Checks that the desired ClusterClass still has what a Cluster uses.
+fn check_cluster(desired: &ClusterClassSpec, cluster: &Cluster) -> Vec<FieldError> {
+    let Some(topology) = &cluster.spec.topology else {
+        return Vec::new();
+    };
+    let cluster_name = cluster.metadata.name.as_deref().unwrap_or_default();
+    let mut errors = Vec::new();
+    let workers = topology.workers.as_ref();
+    let desired_workers = desired.workers.as_ref();
+    for md in workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        let defined = desired_workers
+            .and_then(|workers| workers.machine_deployments.as_ref())
+            .is_some_and(|classes| classes.iter().any(|class| class.class == md.class));
+        if !defined {
+            errors.push(FieldError::forbidden(
+                "spec.workers.machineDeployments",
+                format!(
+                    "MachineDeploymentClass {:?} cannot be removed because it is used by MachineDeployment topology {:?} of Cluster {cluster_name:?}",
+                    md.class, md.name
+                ),
+            ));
+        }
+    }
+    for mp in workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        let defined = desired_workers
+            .and_then(|workers| workers.machine_pools.as_ref())
+            .is_some_and(|classes| classes.iter().any(|class| class.class == mp.class));
+        if !defined {
+            errors.push(FieldError::forbidden(
+                "spec.workers.machinePools",
+                format!(
+                    "MachinePoolClass {:?} cannot be removed because it is used by MachinePool topology {:?} of Cluster {cluster_name:?}",
+                    mp.class, mp.name
+                ),
+            ));
+        }
+    }
+
+    let external = desired
+        .patches
+        .iter()
+        .flatten()
+        .any(|patch| patch.external.is_some());
+    if external {
+        return errors;
+    }
+    let class = ClusterClass {
+        metadata: Default::default(),
+        spec: desired.clone(),
+        status: None,
+    };
+    let definitions = class_variables(&class);
+    let path = "spec.topology";
+    errors.extend(check_variables(
+        topology.variables.as_deref().unwrap_or_default(),
+        &definitions,
+        cluster_name,
+        &field::child(path, "variables"),
+    ));
+    if let Some(overrides) = topology
+        .control_plane
+        .as_ref()
+        .and_then(|control_plane| control_plane.variables.as_ref())
+        .and_then(|variables| variables.overrides.as_deref())
+    {
+        errors.extend(check_variables(
+            overrides,
+            &definitions,
+            cluster_name,
+            &field::child(path, "controlPlane.variables.overrides"),
+        ));
+    }
+    for (i, md) in workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = md
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let path = field::index(&field::child(path, "workers.machineDeployments"), i);
+            errors.extend(check_variables(
+                overrides,
+                &definitions,
+                cluster_name,
+                &field::child(&path, "variables.overrides"),
+            ));
+        }
+    }
+    for (i, mp) in workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = mp
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let path = field::index(&field::child(path, "workers.machinePools"), i);
+            errors.extend(check_variables(
+                overrides,
+                &definitions,
+                cluster_name,
+                &field::child(&path, "variables.overrides"),
+            ));
+        }
+    }
+    errors
+}
+
+/// Checks that the variables set by a Cluster are defined by the desired ClusterClass and
+/// that their values match the desired schemas.
+fn check_variables<V: ClusterVariable>(
+    values: &[V],
+    definitions: &[ClassVariable],
+    cluster_name: &str,
+    path: &str,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (i, value) in values.iter().enumerate() {
+        let definition = definitions
+            .iter()
+            .find(|variable| variable.name == value.name())
+            .and_then(|variable| variable.definitions.first());
+        match definition {
+            Some(definition) => errors.extend(validate_cluster_variable(
+                value.name(),
+                value.value(),
+                None,
+                definition,
+                &field::index(path, i),
+            )),
+            None => errors.push(FieldError::forbidden(
+                "spec.variables",
+                format!(
+                    "variable {:?} cannot be removed because it is used by Cluster {cluster_name:?} at {}",
+                    value.name(),
+                    field::index(path, i)
+                ),
+            )),
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn reference(api_version: &str, kind: &str) -> Value {
+        json!({"apiVersion": api_version, "kind": kind, "name": kind.to_lowercase()})
+    }
+
+    fn template(infrastructure_kind: &str) -> Value {
+        json!({
+            "bootstrap": {"ref": reference("bootstrap.cluster.x-k8s.io/v1beta1", "KubeadmConfigTemplate")},
+            "infrastructure": {"ref": reference("infrastructure.cluster.x-k8s.io/v1beta1", infrastructure_kind)},
+        })
+    }
+
+    fn spec() -> Value {
+        json!({
+            "infrastructure": {"ref": reference("infrastructure.cluster.x-k8s.io/v1beta1", "DockerClusterTemplate")},
+            "controlPlane": {
+                "ref": reference("controlplane.cluster.x-k8s.io/v1beta1", "KubeadmControlPlaneTemplate"),
+                "machineInfrastructure": {"ref": reference("infrastructure.cluster.x-k8s.io/v1beta1", "DockerMachineTemplate")},
+            },
+            "workers": {
+                "machineDeployments": [
+                    {"class": "md-a", "template": template("DockerMachineTemplate")},
+                    {"class": "md-b", "template": template("DockerMachineTemplate")},
+                ],
+                "machinePools": [{"class": "mp-a", "template": template("DockerMachinePoolTemplate")}],
+            },
+            "variables": [{
+                "name": "replicas",
+                "required": false,
+                "schema": {"openAPIV3Schema": {"type": "integer", "maximum": 5}},
+            }],
+        })
+    }
+
+    fn errors(desired: Value, cluster: Option<Value>) -> Vec<String> {
+        let current: ClusterClassSpec = serde_json::from_value(spec()).unwrap();
+        let desired: ClusterClassSpec = serde_json::from_value(desired).unwrap();
+        let cluster: Option<Cluster> = cluster.map(|topology| {
+            serde_json::from_value(
+                json!({"metadata": {"name": "c1"}, "spec": {"topology": topology}}),
+            )
+            .unwrap()
+        });
+        check_class_compatibility(&current, &desired, cluster.as_ref())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn checks_references() {
+        let changed = |pointer: &str, value: Value| {
+            let mut spec = spec();
+            *spec.pointer_mut(pointer).unwrap() = value;
+            spec
+        };
+        for (desired, expected) in [
+            (spec(), vec![]),
+            // a new version of the same group is allowed
+            (
+                changed(
+                    "/infrastructure/ref/apiVersion",
+                    json!("infrastructure.cluster.x-k8s.io/v1beta2"),
+                ),
+                vec![],
+            ),
+            (changed("/controlPlane/ref/name", json!("other")), vec![]),
+            (
+                changed(
+                    "/infrastructure/ref/apiVersion",
+                    json!("infrastructure.example.com/v1beta1"),
+                ),
+                vec![
+                    r#"spec.infrastructure.ref: Forbidden: apiGroup must not be changed from "infrastructure.cluster.x-k8s.io" to "infrastructure.example.com""#,
+                ],
+            ),
+            (
+                changed("/controlPlane/ref/kind", json!("RKE2ControlPlaneTemplate")),
+                vec![
+                    r#"spec.controlPlane.ref: Forbidden: kind must not be changed from "KubeadmControlPlaneTemplate" to "RKE2ControlPlaneTemplate""#,
+                ],
+            ),
+            (
+                changed(
+                    "/controlPlane/machineInfrastructure/ref",
+                    reference("v1", "ConfigMap"),
+                ),
+                vec![
+                    r#"spec.controlPlane.machineInfrastructure.ref: Forbidden: apiGroup must not be changed from "infrastructure.cluster.x-k8s.io" to """#,
+                    r#"spec.controlPlane.machineInfrastructure.ref: Forbidden: kind must not be changed from "DockerMachineTemplate" to "ConfigMap""#,
+                ],
+            ),
+            (
+                changed(
+                    "/workers/machineDeployments/1/template/infrastructure/ref/kind",
+                    json!("AWSMachineTemplate"),
+                ),
+                vec![
+                    r#"spec.workers.machineDeployments[1].template.infrastructure.ref: Forbidden: kind must not be changed from "DockerMachineTemplate" to "AWSMachineTemplate""#,
+                ],
+            ),
+            (
+                changed(
+                    "/workers/machinePools/0/template/infrastructure/ref/kind",
+                    json!("AWSMachinePool"),
+                ),
+                vec![
+                    r#"spec.workers.machinePools[0].template.infrastructure.ref: Forbidden: kind must not be changed from "DockerMachinePoolTemplate" to "AWSMachinePool""#,
+                ],
+            ),
+            // bootstrap templates may change
+            (
+                changed(
+                    "/workers/machineDeployments/0/template/bootstrap/ref",
+                    reference("bootstrap.example.com/v1", "TalosConfigTemplate"),
+                ),
+                vec![],
+            ),
+            // classes that are not in the current ClusterClass are not checked
+            (
+                changed("/workers/machineDeployments/0/class", json!("md-c")),
+                vec![],
+            ),
+        ] {
+            assert_eq!(errors(desired.clone(), None), expected, "{desired}");
+        }
+    }
+
+    #[test]
+    fn checks_the_cluster() {
+        let topology = json!({
+            "class": "class",
+            "version": "v1.30.0",
+            "variables": [{"name": "replicas", "value": 3}],
+            "workers": {
+                "machineDeployments": [{"class": "md-a", "name": "md-0"}],
+                "machinePools": [{"class": "mp-a", "name": "mp-0"}],
+            },
+        });
+        assert_eq!(errors(spec(), Some(topology.clone())), Vec::<String>::new());
+
+        let mut desired = spec();
+        // md-b is not used by the Cluster
+        desired["workers"]["machineDeployments"] = json!([]);
+        desired["workers"]["machinePools"] = json!([]);
+        desired["variables"] = json!([]);
+        assert_eq!(
+            errors(desired.clone(), Some(topology.clone())),
+            [
+                r#"spec.workers.machineDeployments: Forbidden: MachineDeploymentClass "md-a" cannot be removed because it is used by MachineDeployment topology "md-0" of Cluster "c1""#,
+                r#"spec.workers.machinePools: Forbidden: MachinePoolClass "mp-a" cannot be removed because it is used by MachinePool topology "mp-0" of Cluster "c1""#,
+                r#"spec.variables: Forbidden: variable "replicas" cannot be removed because it is used by Cluster "c1" at spec.topology.variables[0]"#,
+            ]
+        );
+
+        // variables defined by external patches are not checked
+        desired["patches"] =
+            json!([{"name": "external", "external": {"generateExtension": "generate"}}]);
+        assert_eq!(errors(desired, Some(topology.clone())).len(), 2);
+
+        let mut desired = spec();
+        desired["variables"][0]["schema"]["openAPIV3Schema"]["maximum"] = json!(2);
+        let mut topology = topology;
+        topology["controlPlane"] = json!({"variables": {"overrides": [{"name": "replicas", "value": 1}, {"name": "foo", "value": 1}]}});
+        assert_eq!(
+            errors(desired, Some(topology)),
+            [
+                "spec.topology.variables[0].value: Invalid value: 3: spec.topology.variables[0].value in body should be less than or equal to 2",
+                r#"spec.variables: Forbidden: variable "foo" cannot be removed because it is used by Cluster "c1" at spec.topology.controlPlane.variables.overrides[1]"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Field errors, following `k8s.io/apimachinery/pkg/util/validation/field`, so validation results
+//! read like the ones returned by the Cluster API webhooks.
+
+use std::fmt;
+
+/// The kind of a [`FieldError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldErrorType {
+    NotFound,
+    Required,
+    Duplicate,
+    Invalid,
+    NotSupported,
+    Forbidden,
+    TooLong,
+    TooMany,
+    Internal,
+    TypeInvalid,
+}
+
+impl FieldErrorType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFound => "Not found",
+            Self::Required => "Required value",
+            Self::Duplicate => "Duplicate value",
+            Self::Invalid | Self::TypeInvalid => "Invalid value",
+            Self::NotSupported => "Unsupported value",
+            Self::Forbidden => "Forbidden",
+            Self::TooLong => "Too long",
+            Self::TooMany => "Too many",
+            Self::Internal => "Internal error",
+        }
+    }
+}
+
+impl fmt::Display for FieldErrorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error for a single field, e.g.
+/// `spec.topology.variables[0].value.replicas: Invalid value: 7: ... should be less than or equal to 5`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub error_type: FieldErrorType,
+    /// Path of the field, e.g. `spec.topology.variables[0].value`.
+    pub field: String,
+    /// The offending value, not shown for required, forbidden and too long errors.
+    pub bad_value: Option<serde_json::Value>,
+    pub detail: String,
+}
+
+impl FieldError {
+    fn new(
+        error_type: FieldErrorType,
+        field: impl Into<String>,
+        bad_value: Option<serde_json::Value>,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            error_type,
+            field: field.into(),
+            bad_value,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn not_found(field: impl Into<String>, value: serde_json::Value) -> Self {
+        Self::new(FieldErrorType::NotFound, field, Some(value), "")
+    }
+
+    pub fn required(field: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(FieldErrorType::Required, field, None, detail)
+    }
+
+    pub fn duplicate(field: impl Into<String>, value: serde_json::Value) -> Self {
+        Self::new(FieldErrorType::Duplicate, field, Some(value), "")
+    }
+
+    pub fn invalid(
+        field: impl Into<String>,
+        value: serde_json::Value,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self::new(FieldErrorType::Invalid, field, Some(value), detail)
+    }
+
+    pub fn type_invalid(
+        field: impl Into<String>,
+        value: serde_json::Value,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self::new(FieldErrorType::TypeInvalid, field, Some(value), detail)
+    }
+
+    /// An unsupported value, listing the supported ones.
+    pub fn not_supported(
+        field: impl Into<String>,
+        value: serde_json::Value,
+        supported: &[serde_json::Value],
+    ) -> Self {
+        let detail = if supported.is_empty() {
+            String::new()
+        } else {
+            let quoted: Vec<_> = supported
+                .iter()
+                .map(|value| format!("{:?}", plain(value)))
+                .collect();
+            format!("supported values: {}", quoted.join(", "))
+        };
+        Self::new(FieldErrorType::NotSupported, field, Some(value), detail)
+    }
+
+    pub fn forbidden(field: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(FieldErrorType::Forbidden, field, None, detail)
+    }
+
+    pub fn too_long(field: impl Into<String>, max_length: i64) -> Self {
+        Self::new(
+            FieldErrorType::TooLong,
+            field,
+            None,
+            format!("may not be longer than {max_length}"),
+        )
+    }
+
+    pub fn too_many(field: impl Into<String>, actual: usize, max: i64) -> Self {
+        Self::new(
+            FieldErrorType::TooMany,
+            field,
+            Some(actual.into()),
+            format!("must have at most {max} items"),
+        )
+    }
+
+    pub fn internal(field: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(FieldErrorType::Internal, field, None, detail)
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.error_type)?;
+        if let Some(value) = &self.bad_value {
+            match value {
+                serde_json::Value::String(s) => write!(f, ": {s:?}")?,
+                value => write!(f, ": {value}")?,
+            }
+        }
+        if !self.detail.is_empty() {
+            write!(f, ": {}", self.detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// A value as Go's `fmt.Sprint` would print a scalar.
+fn plain(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Appends a field to a path, e.g. `spec` and `topology` give `spec.topology`.
+pub fn child(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Appends a list index to a path, e.g. `variables[0]`.
+pub fn index(path: &str, index: usize) -> String {
+    format!("{path}[{index}]")
+}
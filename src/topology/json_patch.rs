@@ -0,0 +1,151 @@
+//! The subset of RFC 6902 JSON patches ClusterClasses can express: `add`, `replace` and `remove`.
+
+use serde_json::Value;
+
+/// Error applying a JSON patch operation.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum JsonPatchError {
+    #[error("invalid path {0:?}: must be empty or start with '/'")]
+    InvalidPath(String),
+    #[error("{op} operation does not apply: doc is missing path: {path:?}")]
+    MissingPath { op: &'static str, path: String },
+    #[error("{op} operation does not apply: index {index:?} is invalid for path {path:?}")]
+    InvalidIndex {
+        op: &'static str,
+        path: String,
+        index: String,
+    },
+}
+
+/// Splits a JSON pointer into its unescaped reference tokens.
+fn tokens(path: &str) -> Result<Vec<String>, JsonPatchError> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = path.strip_prefix('/') else {
+        return Err(JsonPatchError::InvalidPath(path.to_string()));
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Resolves the parent of the target of a patch operation.
+fn parent<'a>(
+    doc: &'a mut Value,
+    op: &'static str,
+    path: &str,
+) -> Result<(&'a mut Value, String), JsonPatchError> {
+    let mut tokens = tokens(path)?;
+    let missing = || JsonPatchError::MissingPath {
+        op,
+        path: path.to_string(),
+    };
+    let last = tokens.pop().ok_or_else(missing)?;
+    let mut current = doc;
+    for token in &tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(array) => token
+                .parse::<usize>()
+                .ok()
+                .filter(|_| is_array_index(token))
+                .and_then(|i| array.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(missing)?;
+    }
+    Ok((current, last))
+}
+
+/// Array indexes are decimal numbers without leading zeros.
+fn is_array_index(token: &str) -> bool {
+    !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'))
+}
+
+fn array_index(
+    op: &'static str,
+    path: &str,
+    token: &str,
+    len: usize,
+) -> Result<usize, JsonPatchError> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|i| is_array_index(token) && *i <= len)
+        .ok_or_else(|| JsonPatchError::InvalidIndex {
+            op,
+            path: path.to_string(),
+            index: token.to_string(),
+        })
+}
+
+pub(crate) fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), JsonPatchError> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = parent(doc, "add", path)?;
+    match parent {
+        Value::Object(map) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        Value::Array(array) => {
+            let index = if last == "-" {
+                array.len()
+            } else {
+                array_index("add", path, &last, array.len())?
+            };
+            array.insert(index, value);
+            Ok(())
+        }
+        _ => Err(JsonPatchError::MissingPath {
+            op: "add",
+            path: path.to_string(),
+        }),
+    }
+}
+
+pub(crate) fn replace(doc: &mut Value, path: &str, value: Value) -> Result<(), JsonPatchError> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = parent(doc, "replace", path)?;
+    let target = match parent {
+        Value::Object(map) => map.get_mut(&last),
+        Value::Array(array) => {
+            let index = array_index("replace", path, &last, array.len())?;
+            array.get_mut(index)
+        }
+        _ => None,
+    };
+    let target = target.ok_or_else(|| JsonPatchError::MissingPath {
+        op: "replace",
+        path: path.to_string(),
+    })?;
+    *target = value;
+    Ok(())
+}
+
+pub(crate) fn remove(doc: &mut Value, path: &str) -> Result<(), JsonPatchError> {
+    let (parent, last) = parent(doc, "remove", path)?;
+    let removed = match parent {
+        Value::Object(map) => map.remove(&last),
+        Value::Array(array) => {
+            let index = array_index("remove", path, &last, array.len())?;
+            (index < array.len()).then(|| array.remove(index))
+        }
+        _ => None,
+    };
+    removed
+        .map(drop)
+        .ok_or_else(|| JsonPatchError::MissingPath {
+            op: "remove",
+            path: path.to_string(),
+        })
+}
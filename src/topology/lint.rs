@@ -0,0 +1,981 @@
+//! Lints for ClusterClasses, catching mistakes the apiserver accepts or only reports once a
+//! Cluster uses the class: patches referring to variables that are not defined, variables no
+//! patch uses, selectors matching no template, duplicate classes and naming strategies that
+//! generate the same names.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use k8s_openapi::api::core::v1::ObjectReference;
+
+use crate::api::capi_clusterclass::{ClusterClass, ClusterClassPatchesDefinitionsSelector};
+
+use super::field;
+use super::patches::class_name_matches;
+use super::template::Template;
+use super::variables::BUILTINS_NAME;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Likely a mistake, but the ClusterClass works.
+    Warning,
+    /// The ClusterClass is rejected or fails once used by a Cluster.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// What a diagnostic is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A patch refers to a variable the ClusterClass does not define.
+    UndefinedVariable,
+    /// A variable is not used by any patch.
+    UnusedVariable,
+    /// A selector names a MachineDeployment or MachinePool class that is not defined.
+    UnknownClass,
+    /// A MachineDeployment or MachinePool class is defined more than once.
+    DuplicateClass,
+    /// A selector does not match the templates of the resources it selects.
+    InvalidSelector,
+    /// A naming strategy generates the same name for different objects.
+    NamingConflict,
+    /// A template does not parse.
+    InvalidTemplate,
+}
+
+/// A problem found in a ClusterClass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: LintKind,
+    /// Field path of the problem, e.g. `spec.patches[0].definitions[1].selector.kind`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// Lints a ClusterClass, returning its diagnostics in the order of the checks: variable
+/// references, unused variables, selectors, duplicate classes and naming strategies.
+///
+/// Variables are defined by `spec.variables` or, for external patches, by `status.variables`.
+/// Variables are not reported as unused if the class has external patches or a template
+/// passes on all variables, e.g. with `{{ toJson . }}`.
+pub fn lint_cluster_class(class: &ClusterClass) -> Vec<Diagnostic> {
+    let mut lint = Lint::default();
+    lint.variable_references(class);
+    lint.selectors(class);
+    lint.duplicate_classes(class);
+    lint.naming_strategies(class);
+    lint.diagnostics
+}
+
+#[derive(Default)]
+struct Lint {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lint {
+    fn push(&mut self, severity: Severity, kind: LintKind, path: String, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            path,
+            message,
+        });
+    }
+
+    /// Parses a template and returns the variables it refers to, reporting parse errors.
+    fn template_references(&mut self, path: &str, source: &str) -> Option<BTreeSet<String>> {
+        match Template::parse("tpl", source) {
+            Ok(template) => Some(template.references()),
+            Err(err) => {
+                self.push(
+                    Severity::Error,
+                    LintKind::InvalidTemplate,
+                    path.to_string(),
+                    format!("failed to parse template: {err}"),
+                );
+                None
+            }
+        }
+    }
+
+    fn variable_references(&mut self, class: &ClusterClass) {
+        let inline: Vec<&str> = class
+            .spec
+            .variables
+            .iter()
+            .flatten()
+            .map(|variable| variable.name.as_str())
+            .collect();
+        let mut defined: BTreeSet<&str> = inline.iter().copied().collect();
+        defined.extend(
+            class
+                .status
+                .as_ref()
+                .and_then(|status| status.variables.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|variable| variable.name.as_str()),
+        );
+        defined.insert(BUILTINS_NAME);
+
+        // templates and `valueFrom.variable` references of the inline patches
+        let mut templates = Vec::new();
+        let mut variables = Vec::new();
+        let mut all_used = false;
+        for (i, patch) in class.spec.patches.iter().flatten().enumerate() {
+            let path = field::index("spec.patches", i);
+            if patch.external.is_some() {
+                all_used = true;
+            }
+            if let Some(enabled_if) = &patch.enabled_if {
+                templates.push((field::child(&path, "enabledIf"), enabled_if.as_str()));
+            }
+            for (d, definition) in patch.definitions.iter().flatten().enumerate() {
+                let path = field::index(&field::child(&path, "definitions"), d);
+                for (j, json_patch) in definition.json_patches.iter().enumerate() {
+                    let Some(value_from) = &json_patch.value_from else {
+                        continue;
+                    };
+                    let path = field::child(
+                        &field::index(&field::child(&path, "jsonPatches"), j),
+                        "valueFrom",
+                    );
+                    if let Some(template) = &value_from.template {
+                        templates.push((field::child(&path, "template"), template.as_str()));
+                    }
+                    if let Some(variable) = &value_from.variable {
+                        variables.push((field::child(&path, "variable"), variable.as_str()));
+                    }
+                }
+            }
+        }
+
+        let mut used = BTreeSet::new();
+        for (path, source) in templates {
+            for reference in self
+                .template_references(&path, source)
+                .into_iter()
+                .flatten()
+            {
+                if reference.is_empty() {
+                    all_used = true;
+                    continue;
+                }
+                let name = reference.split('.').next().unwrap_or_default().to_string();
+                if !defined.contains(name.as_str()) {
+                    self.push(
+                        Severity::Error,
+                        LintKind::UndefinedVariable,
+                        path.clone(),
+                        format!("template refers to variable {name:?}, which is not defined"),
+                    );
+                }
+                used.insert(name);
+            }
+        }
+        for (path, variable) in variables {
+            let name = variable.split(['.', '[']).next().unwrap_or_default();
+            if !defined.contains(name) {
+                self.push(
+                    Severity::Error,
+                    LintKind::UndefinedVariable,
+                    path,
+                    format!("variable {name:?} is not defined"),
+                );
+            }
+            used.insert(name.to_string());
+        }
+        if all_used {
+            return;
+        }
+        for (i, name) in inline.iter().enumerate() {
+            if !used.contains(*name) {
+                self.push(
+                    Severity::Warning,
+                    LintKind::UnusedVariable,
+                    field::index("spec.variables", i),
+                    format!("variable {name:?} is not used by any patch"),
+                );
+            }
+        }
+    }
+
+    fn selectors(&mut self, class: &ClusterClass) {
+        let spec = &class.spec;
+        let infrastructure = spec.infrastructure.as_ref().map(|i| &i.r#ref);
+        let control_plane: Vec<&ObjectReference> = spec
+            .control_plane
+            .iter()
+            .flat_map(|control_plane| {
+                std::iter::once(&control_plane.r#ref).chain(
+                    control_plane
+                        .machine_infrastructure
+                        .as_ref()
+                        .map(|machine| &machine.r#ref),
+                )
+            })
+            .collect();
+        let workers = spec.workers.as_ref();
+        let machine_deployments: Vec<(&str, [&ObjectReference; 2])> = workers
+            .and_then(|workers| workers.machine_deployments.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|md| {
+                (
+                    md.class.as_str(),
+                    [
+                        &md.template.bootstrap.r#ref,
+                        &md.template.infrastructure.r#ref,
+                    ],
+                )
+            })
+            .collect();
+        let machine_pools: Vec<(&str, [&ObjectReference; 2])> = workers
+            .and_then(|workers| workers.machine_pools.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|mp| {
+                (
+                    mp.class.as_str(),
+                    [
+                        &mp.template.bootstrap.r#ref,
+                        &mp.template.infrastructure.r#ref,
+                    ],
+                )
+            })
+            .collect();
+
+        for (i, patch) in spec.patches.iter().flatten().enumerate() {
+            for (d, definition) in patch.definitions.iter().flatten().enumerate() {
+                let path = field::child(
+                    &field::index(
+                        &field::child(&field::index("spec.patches", i), "definitions"),
+                        d,
+                    ),
+                    "selector",
+                );
+                let selector = &definition.selector;
+                if !self.selector_type(selector, &path) {
+                    continue;
+                }
+                let resources = &selector.match_resources;
+                let path = field::child(&path, "matchResources");
+                let mut enabled = false;
+                if resources.infrastructure_cluster.unwrap_or_default() {
+                    enabled = true;
+                    if !infrastructure.is_some_and(|r| selects(selector, r)) {
+                        self.push(
+                            Severity::Error,
+                            LintKind::InvalidSelector,
+                            field::child(&path, "infrastructureCluster"),
+                            "selector is enabled but does not match the infrastructure cluster template"
+                                .to_string(),
+                        );
+                    }
+                }
+                if resources.control_plane.unwrap_or_default() {
+                    enabled = true;
+                    if !control_plane.iter().any(|r| selects(selector, r)) {
+                        self.push(
+                            Severity::Error,
+                            LintKind::InvalidSelector,
+                            field::child(&path, "controlPlane"),
+                            "selector is enabled but matches neither the control plane template nor the control plane machine infrastructure template"
+                                .to_string(),
+                        );
+                    }
+                }
+                let md_names = resources
+                    .machine_deployment_class
+                    .as_ref()
+                    .and_then(|c| c.names.as_deref())
+                    .unwrap_or_default();
+                enabled |= !md_names.is_empty();
+                self.selector_classes(
+                    selector,
+                    md_names,
+                    &machine_deployments,
+                    &field::child(&path, "machineDeploymentClass.names"),
+                    "MachineDeployment",
+                );
+                let mp_names = resources
+                    .machine_pool_class
+                    .as_ref()
+                    .and_then(|c| c.names.as_deref())
+                    .unwrap_or_default();
+                enabled |= !mp_names.is_empty();
+                self.selector_classes(
+                    selector,
+                    mp_names,
+                    &machine_pools,
+                    &field::child(&path, "machinePoolClass.names"),
+                    "MachinePool",
+                );
+                if !enabled {
+                    self.push(
+                        Severity::Error,
+                        LintKind::InvalidSelector,
+                        path,
+                        "selector does not match any resources".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks the apiVersion and kind of a selector, returning whether they are valid.
+    fn selector_type(
+        &mut self,
+        selector: &ClusterClassPatchesDefinitionsSelector,
+        path: &str,
+    ) -> bool {
+        let mut valid = true;
+        if !is_api_version(&selector.api_version) {
+            self.push(
+                Severity::Error,
+                LintKind::InvalidSelector,
+                field::child(path, "apiVersion"),
+                format!("invalid apiVersion {:?}", selector.api_version),
+            );
+            valid = false;
+        }
+        if !is_kind(&selector.kind) {
+            self.push(
+                Severity::Error,
+                LintKind::InvalidSelector,
+                field::child(path, "kind"),
+                format!("invalid kind {:?}", selector.kind),
+            );
+            valid = false;
+        }
+        valid
+    }
+
+    /// Checks that every class name of a selector matches a class with a selected template.
+    fn selector_classes(
+        &mut self,
+        selector: &ClusterClassPatchesDefinitionsSelector,
+        names: &[String],
+        classes: &[(&str, [&ObjectReference; 2])],
+        path: &str,
+        kind: &str,
+    ) {
+        for (k, name) in names.iter().enumerate() {
+            let path = field::index(path, k);
+            let matching: Vec<_> = classes
+                .iter()
+                .filter(|(class, _)| class_name_matches(class, name))
+                .collect();
+            if matching.is_empty() {
+                self.push(
+                    Severity::Error,
+                    LintKind::UnknownClass,
+                    path,
+                    format!("{name:?} does not match any {kind} class"),
+                );
+            } else if !matching
+                .iter()
+                .any(|(_, refs)| refs.iter().any(|r| selects(selector, r)))
+            {
+                self.push(
+                    Severity::Error,
+                    LintKind::InvalidSelector,
+                    path,
+                    format!(
+                        "selector is enabled for {name:?} but matches neither the bootstrap nor the infrastructure template of its {kind} classes"
+                    ),
+                );
+            }
+        }
+    }
+
+    fn duplicate_classes(&mut self, class: &ClusterClass) {
+        let Some(workers) = &class.spec.workers else {
+            return;
+        };
+        let machine_deployments = workers.machine_deployments.iter().flatten();
+        self.duplicates(
+            machine_deployments.map(|md| md.class.as_str()),
+            "spec.workers.machineDeployments",
+            "MachineDeployment",
+        );
+        let machine_pools = workers.machine_pools.iter().flatten();
+        self.duplicates(
+            machine_pools.map(|mp| mp.class.as_str()),
+            "spec.workers.machinePools",
+            "MachinePool",
+        );
+    }
+
+    fn duplicates<'a>(&mut self, classes: impl Iterator<Item = &'a str>, path: &str, kind: &str) {
+        let mut seen = BTreeMap::new();
+        for (i, class) in classes.enumerate() {
+            if let Some(first) = seen.get(class) {
+                self.push(
+                    Severity::Error,
+                    LintKind::DuplicateClass,
+                    field::child(&field::index(path, i), "class"),
+                    format!(
+                        "{kind} class {class:?} is already defined at {}",
+                        field::index(path, *first)
+                    ),
+                );
+            } else {
+                seen.insert(class, i);
+            }
+        }
+    }
+
+    /// Naming strategies of MachineDeployment and MachinePool classes have to tell apart the
+    /// objects of a Cluster using them, either with `.random` or with the topology name.
+    fn naming_strategies(&mut self, class: &ClusterClass) {
+        let spec = &class.spec;
+        let templates = [
+            (
+                "spec.infrastructureNamingStrategy.template",
+                spec.infrastructure_naming_strategy
+                    .as_ref()
+                    .and_then(|s| s.template.as_deref()),
+            ),
+            (
+                "spec.controlPlane.namingStrategy.template",
+                spec.control_plane
+                    .as_ref()
+                    .and_then(|cp| cp.naming_strategy.as_ref())
+                    .and_then(|s| s.template.as_deref()),
+            ),
+        ];
+        for (path, template) in templates {
+            if let Some(template) = template {
+                self.template_references(path, template);
+            }
+        }
+        let workers = spec.workers.as_ref();
+        let machine_deployments = workers
+            .and_then(|workers| workers.machine_deployments.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|md| {
+                let template = md
+                    .naming_strategy
+                    .as_ref()
+                    .and_then(|s| s.template.as_deref());
+                (md.class.as_str(), template)
+            });
+        self.worker_naming_strategies(
+            machine_deployments,
+            "spec.workers.machineDeployments",
+            "machineDeployment",
+            "MachineDeployment",
+        );
+        let machine_pools = workers
+            .and_then(|workers| workers.machine_pools.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|mp| {
+                let template = mp
+                    .naming_strategy
+                    .as_ref()
+                    .and_then(|s| s.template.as_deref());
+                (mp.class.as_str(), template)
+            });
+        self.worker_naming_strategies(
+            machine_pools,
+            "spec.workers.machinePools",
+            "machinePool",
+            "MachinePool",
+        );
+    }
+
+    fn worker_naming_strategies<'a>(
+        &mut self,
+        classes: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+        path: &str,
+        variable: &str,
+        kind: &str,
+    ) {
+        let topology_name = format!("{variable}.topologyName");
+        // templates that generate the same name for every object of a class, and the class
+        // using them first
+        let mut fixed: BTreeMap<&str, &str> = BTreeMap::new();
+        for (i, (class, template)) in classes.enumerate() {
+            let Some(template) = template else {
+                continue;
+            };
+            let path = field::child(&field::index(path, i), "namingStrategy.template");
+            let Some(references) = self.template_references(&path, template) else {
+                continue;
+            };
+            if references.contains("random") || references.contains(&topology_name) {
+                continue;
+            }
+            match fixed.get(template) {
+                // duplicate classes are reported on their own
+                Some(other) if *other == class => {}
+                Some(other) => self.push(
+                    Severity::Error,
+                    LintKind::NamingConflict,
+                    path,
+                    format!("{kind}s of classes {other:?} and {class:?} get the same names"),
+                ),
+                None => {
+                    self.push(
+                        Severity::Warning,
+                        LintKind::NamingConflict,
+                        path,
+                        format!(
+                            "template refers to neither .random nor .{topology_name}, so the {kind}s of a Cluster using class {class:?} get the same name"
+                        ),
+                    );
+                    fixed.insert(template, class);
+                }
+            }
+        }
+    }
+}
+
+/// Whether a selector selects a template, by apiVersion and kind.
+fn selects(selector: &ClusterClassPatchesDefinitionsSelector, reference: &ObjectReference) -> bool {
+    reference.api_version.as_deref() == Some(selector.api_version.as_str())
+        && reference.kind.as_deref() == Some(selector.kind.as_str())
+}
+
+/// Whether an apiVersion is a version, e.g. `v1`, or a group and version, e.g.
+/// `infrastructure.cluster.x-k8s.io/v1beta1`.
+fn is_api_version(api_version: &str) -> bool {
+    let (group, version) = match api_version.split_once('/') {
+        Some((group, version)) => (Some(group), version),
+        None => (None, api_version),
+    };
+    let is_label = |s: &str| {
+        !s.is_empty()
+            && s.len() <= 63
+            && s.bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+            && !s.starts_with('-')
+            && !s.ends_with('-')
+    };
+    is_label(version)
+        && version.starts_with(|c: char| c.is_ascii_lowercase())
+        && group.is_none_or(|group| group.len() <= 253 && group.split('.').all(is_label))
+}
+
+/// Whether a kind is a CamelCase name, e.g. `DockerMachineTemplate`.
+fn is_kind(kind: &str) -> bool {
+    kind.starts_with(|c: char| c.is_ascii_uppercase())
+        && kind.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A ClusterClass with a control plane, a MachineDeployment class `md` and a MachinePool
+    /// class `mp`, the variables `a` and `b`, and the given patches and worker overrides.
+    fn class(patches: serde_json::Value, extra_spec: serde_json::Value) -> ClusterClass {
+        let template = |api_version: &str, kind: &str| json!({"ref": {"apiVersion": api_version, "kind": kind, "name": "t"}});
+        let bootstrap = "bootstrap.cluster.x-k8s.io/v1beta1";
+        let infrastructure = "infrastructure.cluster.x-k8s.io/v1beta1";
+        let mut spec = json!({
+            "infrastructure": template(infrastructure, "DockerClusterTemplate"),
+            "controlPlane": {
+                "ref": {
+                    "apiVersion": "controlplane.cluster.x-k8s.io/v1beta1",
+                    "kind": "KubeadmControlPlaneTemplate",
+                    "name": "t",
+                },
+                "machineInfrastructure": template(infrastructure, "DockerMachineTemplate"),
+            },
+            "workers": {
+                "machineDeployments": [{
+                    "class": "md",
+                    "template": {
+                        "bootstrap": template(bootstrap, "KubeadmConfigTemplate"),
+                        "infrastructure": template(infrastructure, "DockerMachineTemplate"),
+                    },
+                }],
+                "machinePools": [{
+                    "class": "mp",
+                    "template": {
+                        "bootstrap": template(bootstrap, "KubeadmConfigTemplate"),
+                        "infrastructure": template(infrastructure, "DockerMachinePoolTemplate"),
+                    },
+                }],
+            },
+            "variables": [
+                {"name": "a", "required": false, "schema": {"openAPIV3Schema": {"type": "string"}}},
+                {"name": "b", "required": false, "schema": {"openAPIV3Schema": {"type": "string"}}},
+            ],
+            "patches": patches,
+        });
+        for (key, value) in extra_spec.as_object().into_iter().flatten() {
+            spec[key] = value.clone();
+        }
+        serde_json::from_value(json!({"metadata": {"name": "class"}, "spec": spec})).unwrap()
+    }
+
+    fn patch(json_patches: serde_json::Value) -> serde_json::Value {
+        json!({
+            "name": "p",
+            "definitions": [{
+                "selector": {
+                    "apiVersion": "infrastructure.cluster.x-k8s.io/v1beta1",
+                    "kind": "DockerClusterTemplate",
+                    "matchResources": {"infrastructureCluster": true},
+                },
+                "jsonPatches": json_patches,
+            }],
+        })
+    }
+
+    fn lints(class: &ClusterClass) -> Vec<(Severity, LintKind, String)> {
+        lint_cluster_class(class)
+            .into_iter()
+            .map(|d| (d.severity, d.kind, d.path))
+            .collect()
+    }
+
+    fn value_from(value_from: serde_json::Value) -> serde_json::Value {
+        json!([{"op": "add", "path": "/spec/template/spec/x", "valueFrom": value_from}])
+    }
+
+    #[test]
+    fn variable_references() {
+        use LintKind::*;
+        use Severity::*;
+        let json_patches = "spec.patches[0].definitions[0].jsonPatches[0].valueFrom";
+        for (patches, expected) in [
+            (
+                json!([
+                    patch(value_from(json!({"variable": "a"}))),
+                    patch(value_from(
+                        json!({"template": "{{ .b }}-{{ .builtin.cluster.name }}"})
+                    ))
+                ]),
+                vec![],
+            ),
+            (
+                json!([patch(value_from(json!({"variable": "a.x"})))]),
+                vec![(Warning, UnusedVariable, "spec.variables[1]".to_string())],
+            ),
+            (
+                json!([patch(value_from(json!({"template": "{{ .a }}{{ .c.d }}"})))]),
+                vec![
+                    (Error, UndefinedVariable, format!("{json_patches}.template")),
+                    (Warning, UnusedVariable, "spec.variables[1]".to_string()),
+                ],
+            ),
+            (
+                json!([patch(value_from(json!({"variable": "c[0]"})))]),
+                vec![
+                    (Error, UndefinedVariable, format!("{json_patches}.variable")),
+                    (Warning, UnusedVariable, "spec.variables[0]".to_string()),
+                    (Warning, UnusedVariable, "spec.variables[1]".to_string()),
+                ],
+            ),
+            (
+                json!([{"name": "p", "enabledIf": "{{ .c }}", "definitions": []}]),
+                vec![
+                    (
+                        Error,
+                        UndefinedVariable,
+                        "spec.patches[0].enabledIf".to_string(),
+                    ),
+                    (Warning, UnusedVariable, "spec.variables[0]".to_string()),
+                    (Warning, UnusedVariable, "spec.variables[1]".to_string()),
+                ],
+            ),
+            // templates passing on every variable use them all
+            (
+                json!([patch(value_from(json!({"template": "{{ toJson . }}"})))]),
+                vec![],
+            ),
+            // so do external patches, whose variables are not known
+            (
+                json!([{"name": "p", "external": {"generatePatchesExtension": "x"}}]),
+                vec![],
+            ),
+            (
+                json!([patch(value_from(json!({"template": "{{ .a "})))]),
+                vec![
+                    (Error, InvalidTemplate, format!("{json_patches}.template")),
+                    (Warning, UnusedVariable, "spec.variables[0]".to_string()),
+                    (Warning, UnusedVariable, "spec.variables[1]".to_string()),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                lints(&class(patches.clone(), json!({}))),
+                expected,
+                "{patches}"
+            );
+        }
+
+        let diagnostics = lint_cluster_class(&class(
+            json!([patch(value_from(
+                json!({"template": "{{ .a }}{{ .b }}{{ .c }}"})
+            ))]),
+            json!({}),
+        ));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [format!("error: {json_patches}.template: template refers to variable \"c\", which is not defined")]
+        );
+    }
+
+    #[test]
+    fn variables_defined_by_status() {
+        let mut class = class(
+            json!([patch(value_from(
+                json!({"template": "{{ .a }}{{ .b }}{{ .fromStatus }}"})
+            ))]),
+            json!({}),
+        );
+        class.status = serde_json::from_value(json!({
+            "variables": [{
+                "name": "fromStatus",
+                "definitions": [{
+                    "from": "ext",
+                    "required": false,
+                    "schema": {"openAPIV3Schema": {"type": "string"}},
+                }],
+            }],
+        }))
+        .unwrap();
+        assert_eq!(lints(&class), vec![]);
+    }
+
+    #[test]
+    fn selectors() {
+        use LintKind::*;
+        use Severity::*;
+        let selector = |api_version: &str, kind: &str, match_resources: serde_json::Value| {
+            json!([{
+                "name": "p",
+                "definitions": [{
+                    "selector": {
+                        "apiVersion": api_version,
+                        "kind": kind,
+                        "matchResources": match_resources,
+                    },
+                    "jsonPatches": value_from(json!({"template": "{{ .a }}{{ .b }}"})),
+                }],
+            }])
+        };
+        let infrastructure = "infrastructure.cluster.x-k8s.io/v1beta1";
+        let path = "spec.patches[0].definitions[0].selector";
+        for (patches, expected) in [
+            (
+                selector(
+                    infrastructure,
+                    "DockerMachineTemplate",
+                    json!({
+                        "controlPlane": true,
+                        "machineDeploymentClass": {"names": ["md", "m*"]},
+                    }),
+                ),
+                vec![],
+            ),
+            (
+                selector(
+                    "Infra/v1",
+                    "DockerMachineTemplate",
+                    json!({"controlPlane": true}),
+                ),
+                vec![(Error, InvalidSelector, format!("{path}.apiVersion"))],
+            ),
+            (
+                selector(
+                    infrastructure,
+                    "dockerMachineTemplate",
+                    json!({"controlPlane": true}),
+                ),
+                vec![(Error, InvalidSelector, format!("{path}.kind"))],
+            ),
+            (
+                selector(infrastructure, "DockerMachineTemplate", json!({})),
+                vec![(Error, InvalidSelector, format!("{path}.matchResources"))],
+            ),
+            (
+                selector(
+                    infrastructure,
+                    "DockerMachineTemplate",
+                    json!({"infrastructureCluster": true}),
+                ),
+                vec![(
+                    Error,
+                    InvalidSelector,
+                    format!("{path}.matchResources.infrastructureCluster"),
+                )],
+            ),
+            (
+                selector(
+                    infrastructure,
+                    "DockerClusterTemplate",
+                    json!({"controlPlane": true}),
+                ),
+                vec![(
+                    Error,
+                    InvalidSelector,
+                    format!("{path}.matchResources.controlPlane"),
+                )],
+            ),
+            (
+                selector(
+                    infrastructure,
+                    "DockerMachineTemplate",
+                    json!({
+                        "machineDeploymentClass": {"names": ["other"]},
+                        "machinePoolClass": {"names": ["mp"]},
+                    }),
+                ),
+                vec![
+                    (
+                        Error,
+                        UnknownClass,
+                        format!("{path}.matchResources.machineDeploymentClass.names[0]"),
+                    ),
+                    (
+                        Error,
+                        InvalidSelector,
+                        format!("{path}.matchResources.machinePoolClass.names[0]"),
+                    ),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                lints(&class(patches.clone(), json!({}))),
+                expected,
+                "{patches}"
+            );
+        }
+    }
+
+    #[test]
+    fn duplicate_classes_and_naming_strategies() {
+        use LintKind::*;
+        use Severity::*;
+        let patches = json!([patch(value_from(json!({"template": "{{ .a }}{{ .b }}"})))]);
+        let worker = |class: &str, naming: Option<&str>| {
+            let template = |kind: &str| json!({"ref": {"apiVersion": "infrastructure.cluster.x-k8s.io/v1beta1", "kind": kind, "name": "t"}});
+            let mut worker = json!({
+                "class": class,
+                "template": {
+                    "bootstrap": template("KubeadmConfigTemplate"),
+                    "infrastructure": template("DockerMachineTemplate"),
+                },
+            });
+            if let Some(naming) = naming {
+                worker["namingStrategy"] = json!({"template": naming});
+            }
+            worker
+        };
+        let mds = "spec.workers.machineDeployments";
+        for (extra_spec, expected) in [
+            (
+                json!({"workers": {"machineDeployments": [
+                    worker("a", Some("{{ .cluster.name }}-{{ .machineDeployment.topologyName }}")),
+                    worker("b", Some("{{ .cluster.name }}-{{ .random }}")),
+                ]}}),
+                vec![],
+            ),
+            (
+                json!({"workers": {"machineDeployments": [worker("a", None), worker("a", None)]}}),
+                vec![(Error, DuplicateClass, format!("{mds}[1].class"))],
+            ),
+            (
+                json!({"workers": {
+                    "machineDeployments": [
+                        worker("a", Some("{{ .cluster.name }}-md")),
+                        worker("a", Some("{{ .cluster.name }}-md")),
+                        worker("b", Some("{{ .cluster.name }}-md")),
+                    ],
+                    "machinePools": [worker("a", Some("{{ .cluster.name }}-mp"))],
+                }}),
+                vec![
+                    (Error, DuplicateClass, format!("{mds}[1].class")),
+                    (
+                        Warning,
+                        NamingConflict,
+                        format!("{mds}[0].namingStrategy.template"),
+                    ),
+                    (
+                        Error,
+                        NamingConflict,
+                        format!("{mds}[2].namingStrategy.template"),
+                    ),
+                    (
+                        Warning,
+                        NamingConflict,
+                        "spec.workers.machinePools[0].namingStrategy.template".to_string(),
+                    ),
+                ],
+            ),
+            (
+                json!({
+                    "workers": {},
+                    "controlPlane": {
+                        "ref": {"apiVersion": "v1", "kind": "T", "name": "t"},
+                        "namingStrategy": {"template": "{{ .cluster.name"},
+                    },
+                }),
+                vec![(
+                    Error,
+                    InvalidTemplate,
+                    "spec.controlPlane.namingStrategy.template".to_string(),
+                )],
+            ),
+        ] {
+            assert_eq!(
+                lints(&class(patches.clone(), extra_spec.clone())),
+                expected,
+                "{extra_spec}"
+            );
+        }
+    }
+
+    #[test]
+    fn api_versions_and_kinds() {
+        for (api_version, expected) in [
+            ("v1", true),
+            ("infrastructure.cluster.x-k8s.io/v1beta1", true),
+            ("", false),
+            ("V1", false),
+            ("1v", false),
+            ("apps/", false),
+            ("Apps/v1", false),
+            ("a..b/v1", false),
+            ("a/b/v1", false),
+        ] {
+            assert_eq!(is_api_version(api_version), expected, "{api_version}");
+        }
+        for (kind, expected) in [
+            ("DockerMachineTemplate", true),
+            ("dockerMachineTemplate", false),
+            ("Docker-Machine", false),
+            ("", false),
+        ] {
+            assert_eq!(is_kind(kind), expected, "{kind}");
+        }
+    }
+}
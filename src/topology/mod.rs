@@ -0,0 +1,26 @@
+//! Offline evaluation of ClusterClass topologies, mirroring what the Cluster API topology
+//! controller does, so ClusterClasses can be previewed and tested without a management cluster.
+
+pub mod builtins;
+pub mod cel;
+pub mod compatibility;
+pub mod field;
+mod json_patch;
+pub mod lint;
+pub mod naming;
+pub mod patches;
+pub mod plan;
+pub mod template;
+pub mod variables;
+
+pub use builtins::Builtins;
+pub use compatibility::check_class_compatibility;
+pub use field::FieldError;
+pub use json_patch::JsonPatchError;
+pub use lint::{lint_cluster_class, Diagnostic, Severity};
+pub use naming::{NameGenerator, NamingError};
+pub use patches::{apply_patches, check_templates, PatchError, PatchTarget, TemplateHolder};
+pub use plan::{plan_topology, TopologyPlan};
+pub use variables::{
+    cluster_variables, default_topology_variables, validate_topology_variables, Variables,
+};
@@ -0,0 +1,537 @@
+//! Names generated from naming strategies, following the upstream `internal/topology/names`
+//! package: the naming strategies of ClusterClasses for the objects of a topology, and the
+//! machine naming strategies of MachineDeployments and MachineSets.
+//!
+//! Templates get `.cluster.name`, `.random` and, depending on the object, the topology name of
+//! the MachineDeployment or MachinePool or the name of the MachineSet. Names longer than 63
+//! characters are trimmed to 58 characters and get another random suffix. The random suffixes
+//! can be chosen, so generated names can be predicted, e.g. with [`seeded_random`].
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::api::capi_clusterclass::ClusterClass;
+
+use super::field::{self, FieldError};
+use super::template::{Template, TemplateError};
+
+/// Alphabet of the random suffixes of Kubernetes' name generator, alphanumeric without vowels
+/// and confusable characters.
+const RANDOM_ALPHABET: &[u8] = b"bcdfghjklmnpqrstvwxz2456789";
+/// Length of `.random` and of the suffix of trimmed names.
+pub const RANDOM_LENGTH: usize = 5;
+/// Maximum length of generated names.
+pub const MAX_NAME_LENGTH: usize = 63;
+
+const DNS1123_SUBDOMAIN_MAX_LENGTH: usize = 253;
+const DNS1123_SUBDOMAIN_FMT: &str =
+    r"[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*";
+
+/// Error generating a name.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum NamingError {
+    #[error(transparent)]
+    Template(Box<TemplateError>),
+    #[error("generated name {name:?} is not valid: {}", .messages.join("; "))]
+    InvalidName { name: String, messages: Vec<String> },
+}
+
+/// Generates names from the template of a naming strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameGenerator {
+    template: String,
+    data: Value,
+}
+
+impl NameGenerator {
+    /// The InfrastructureCluster of a Cluster, `{{ .cluster.name }}-{{ .random }}` by default.
+    pub fn infrastructure_cluster(template: Option<&str>, cluster_name: &str) -> Self {
+        Self::new(
+            template.unwrap_or("{{ .cluster.name }}-{{ .random }}"),
+            json!({ "cluster": { "name": cluster_name } }),
+        )
+    }
+
+    /// The ControlPlane of a Cluster, `{{ .cluster.name }}-{{ .random }}` by default.
+    pub fn control_plane(template: Option<&str>, cluster_name: &str) -> Self {
+        Self::new(
+            template.unwrap_or("{{ .cluster.name }}-{{ .random }}"),
+            json!({ "cluster": { "name": cluster_name } }),
+        )
+    }
+
+    /// A MachineDeployment of a Cluster topology,
+    /// `{{ .cluster.name }}-{{ .machineDeployment.topologyName }}-{{ .random }}` by default.
+    pub fn machine_deployment(
+        template: Option<&str>,
+        cluster_name: &str,
+        topology_name: &str,
+    ) -> Self {
+        Self::new(
+            template.unwrap_or(
+                "{{ .cluster.name }}-{{ .machineDeployment.topologyName }}-{{ .random }}",
+            ),
+            json!({
+                "cluster": { "name": cluster_name },
+                "machineDeployment": { "topologyName": topology_name },
+            }),
+        )
+    }
+
+    /// A MachinePool of a Cluster topology,
+    /// `{{ .cluster.name }}-{{ .machinePool.topologyName }}-{{ .random }}` by default.
+    pub fn machine_pool(template: Option<&str>, cluster_name: &str, topology_name: &str) -> Self {
+        Self::new(
+            template.unwrap_or("{{ .cluster.name }}-{{ .machinePool.topologyName }}-{{ .random }}"),
+            json!({
+                "cluster": { "name": cluster_name },
+                "machinePool": { "topologyName": topology_name },
+            }),
+        )
+    }
+
+    /// A Machine of a MachineSet, `{{ .machineSet.name }}-{{ .random }}` by default. The
+    /// template is the machine naming strategy of the MachineSet, which it gets from its
+    /// MachineDeployment.
+    pub fn machine(template: Option<&str>, cluster_name: &str, machine_set_name: &str) -> Self {
+        Self::new(
+            template.unwrap_or("{{ .machineSet.name }}-{{ .random }}"),
+            json!({
+                "cluster": { "name": cluster_name },
+                "machineSet": { "name": machine_set_name },
+            }),
+        )
+    }
+
+    fn new(template: &str, data: Value) -> Self {
+        Self {
+            template: template.to_string(),
+            data,
+        }
+    }
+
+    /// The template names are generated from.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Generates a name with random suffixes.
+    pub fn generate(&self) -> Result<String, NamingError> {
+        self.generate_with(random_string)
+    }
+
+    /// Generates a name, taking `.random` and the suffix of a trimmed name from `random`.
+    /// Templates fail on variables that do not exist, and names that are not valid DNS-1123
+    /// subdomains are rejected.
+    pub fn generate_with(&self, mut random: impl FnMut() -> String) -> Result<String, NamingError> {
+        let template = Template::parse("template name generator", self.template.as_str())
+            .map_err(|err| NamingError::Template(Box::new(err)))?
+            .with_missing_key_error();
+        let mut data = self.data.clone();
+        data["random"] = random().into();
+        let mut name = template
+            .execute(&(&data).into())
+            .map_err(|err| NamingError::Template(Box::new(err)))?;
+        if name.len() > MAX_NAME_LENGTH {
+            name = simple_name(&name, &random());
+        }
+        let messages = validate_dns1123_subdomain(&name);
+        if messages.is_empty() {
+            Ok(name)
+        } else {
+            Err(NamingError::InvalidName { name, messages })
+        }
+    }
+}
+
+/// A name made of a prefix and a random suffix, like Kubernetes' `generateName`. The prefix is
+/// trimmed so the name is at most 63 characters long.
+pub fn simple_name(prefix: &str, suffix: &str) -> String {
+    let max = MAX_NAME_LENGTH - RANDOM_LENGTH;
+    let prefix = match prefix.char_indices().nth(max) {
+        Some((end, _)) => &prefix[..end],
+        None => prefix,
+    };
+    format!("{prefix}{suffix}")
+}
+
+/// A random string for `.random`.
+pub fn random_string() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_nanos());
+    hasher.write_u128(now);
+    let mut bits = hasher.finish();
+    let base = RANDOM_ALPHABET.len() as u64;
+    (0..RANDOM_LENGTH)
+        .map(|_| {
+            let c = RANDOM_ALPHABET[(bits % base) as usize] as char;
+            bits /= base;
+            c
+        })
+        .collect()
+}
+
+/// Random strings derived from a seed, to generate the same names every time. The first string
+/// only depends on the seed, the following ones on the seed and their position.
+pub fn seeded_random(seed: impl Into<String>) -> impl FnMut() -> String {
+    let seed = seed.into();
+    let mut count = 0;
+    move || {
+        let input = match count {
+            0 => seed.clone(),
+            n => format!("{seed}/{n}"),
+        };
+        count += 1;
+        Sha256::digest(input)
+            .iter()
+            .take(RANDOM_LENGTH)
+            .map(|b| RANDOM_ALPHABET[usize::from(*b) % RANDOM_ALPHABET.len()] as char)
+            .collect()
+    }
+}
+
+/// Checks that a value is a lowercase RFC 1123 subdomain, like `IsDNS1123Subdomain` of
+/// `k8s.io/apimachinery`, returning the reasons it is not.
+pub fn validate_dns1123_subdomain(value: &str) -> Vec<String> {
+    let mut messages = Vec::new();
+    if value.len() > DNS1123_SUBDOMAIN_MAX_LENGTH {
+        messages.push(format!(
+            "must be no more than {DNS1123_SUBDOMAIN_MAX_LENGTH} characters"
+        ));
+    }
+    static DNS1123_SUBDOMAIN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(&format!("^{DNS1123_SUBDOMAIN_FMT}$")).expect("valid regex"));
+    if !DNS1123_SUBDOMAIN.is_match(value) {
+        messages.push(format!(
+            "a lowercase RFC 1123 subdomain must consist of lower case alphanumeric characters, '-' or '.', and must start and end with an alphanumeric character (e.g. 'example.com', regex used for validation is '{DNS1123_SUBDOMAIN_FMT}')"
+        ));
+    }
+    messages
+}
+
+/// Validates the naming strategies of a ClusterClass like the ClusterClass webhook, by
+/// generating a name for a Cluster named `cluster`.
+pub fn validate_class_naming_strategies(class: &ClusterClass) -> Vec<FieldError> {
+    let spec = &class.spec;
+    let mut errors = Vec::new();
+    if let Some(template) = spec
+        .control_plane
+        .as_ref()
+        .and_then(|cp| cp.naming_strategy.as_ref())
+        .and_then(|strategy| strategy.template.as_deref())
+    {
+        errors.extend(validate_template(
+            NameGenerator::control_plane(Some(template), "cluster"),
+            "spec.controlPlane.namingStrategy.template",
+            "invalid ControlPlane name template",
+        ));
+    }
+    if let Some(template) = spec
+        .infrastructure_naming_strategy
+        .as_ref()
+        .and_then(|strategy| strategy.template.as_deref())
+    {
+        errors.extend(validate_template(
+            NameGenerator::infrastructure_cluster(Some(template), "cluster"),
+            "spec.infrastructureNamingStrategy.template",
+            "invalid InfrastructureCluster name template",
+        ));
+    }
+    let workers = spec.workers.as_ref();
+    for (i, md) in workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(template) = md
+            .naming_strategy
+            .as_ref()
+            .and_then(|strategy| strategy.template.as_deref())
+        {
+            errors.extend(validate_template(
+                NameGenerator::machine_deployment(Some(template), "cluster", "mdtopology"),
+                &field::child(
+                    &field::index("spec.workers.machineDeployments", i),
+                    "namingStrategy.template",
+                ),
+                "invalid MachineDeployment name template",
+            ));
+        }
+    }
+    for (i, mp) in workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(template) = mp
+            .naming_strategy
+            .as_ref()
+            .and_then(|strategy| strategy.template.as_deref())
+        {
+            errors.extend(validate_template(
+                NameGenerator::machine_pool(Some(template), "cluster", "mptopology"),
+                &field::child(
+                    &field::index("spec.workers.machinePools", i),
+                    "namingStrategy.template",
+                ),
+                "invalid MachinePool name template",
+            ));
+        }
+    }
+    errors
+}
+
+/// Validates the machine naming strategy template of a MachineDeployment or MachineSet like
+/// their webhooks, e.g. at `spec.machineNamingStrategy.template`. The template must use
+/// `.random`, so the Machines of a MachineSet get different names.
+pub fn validate_machine_naming_strategy(template: &str, path: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if !template.contains("{{ .random }}") {
+        errors.push(FieldError::invalid(
+            path,
+            template.into(),
+            "invalid template, {{ .random }} is missing",
+        ));
+    }
+    errors.extend(validate_template(
+        NameGenerator::machine(Some(template), "cluster", "machineset"),
+        path,
+        "invalid template",
+    ));
+    errors
+}
+
+fn validate_template(generator: NameGenerator, path: &str, context: &str) -> Vec<FieldError> {
+    let template = Value::from(generator.template());
+    match generator.generate() {
+        Ok(_) => Vec::new(),
+        Err(NamingError::Template(err)) => vec![FieldError::invalid(
+            path,
+            template,
+            format!("{context}: {err}"),
+        )],
+        Err(NamingError::InvalidName { messages, .. }) => messages
+            .into_iter()
+            .map(|message| FieldError::invalid(path, template.clone(), message))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const NOT_A_SUBDOMAIN: &str = "a lowercase RFC 1123 subdomain must consist of lower case alphanumeric characters, '-' or '.', and must start and end with an alphanumeric character (e.g. 'example.com', regex used for validation is '[a-z0-9]([-a-z0-9]*[a-z0-9])?(\\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*')";
+
+    #[test]
+    fn simple_names() {
+        assert_eq!(simple_name("md-0-", "abcde"), "md-0-abcde");
+        let long = "a".repeat(70);
+        let name = simple_name(&long, "bcdfg");
+        assert_eq!(name.len(), MAX_NAME_LENGTH);
+        assert_eq!(name, format!("{}bcdfg", "a".repeat(58)));
+        // a prefix of exactly 58 characters is kept
+        assert_eq!(simple_name(&long[..58], "bcdfg"), name);
+    }
+
+    #[test]
+    fn random_strings() {
+        let random = random_string();
+        assert_eq!(random.len(), RANDOM_LENGTH);
+        assert!(
+            random.bytes().all(|c| RANDOM_ALPHABET.contains(&c)),
+            "{random}"
+        );
+
+        let mut a = seeded_random("seed");
+        let mut b = seeded_random(String::from("seed"));
+        let first: Vec<_> = (0..3).map(|_| a()).collect();
+        assert_eq!(first, (0..3).map(|_| b()).collect::<Vec<_>>());
+        assert_eq!(first, ["8vkhm", "j8g9v", "cfvc5"]);
+        assert_ne!(seeded_random("other")(), first[0]);
+    }
+
+    #[test]
+    fn generates_names() {
+        // trimmed to 58 characters, with the second random string as suffix
+        let trimmed = format!("{}-{}j8g9v", "c".repeat(40), "m".repeat(17));
+        for (generator, expected) in [
+            (
+                NameGenerator::infrastructure_cluster(None, "c1"),
+                "c1-8vkhm",
+            ),
+            (NameGenerator::control_plane(None, "c1"), "c1-8vkhm"),
+            (
+                NameGenerator::machine_deployment(None, "c1", "md-0"),
+                "c1-md-0-8vkhm",
+            ),
+            (
+                NameGenerator::machine_pool(None, "c1", "mp-0"),
+                "c1-mp-0-8vkhm",
+            ),
+            (
+                NameGenerator::machine(None, "c1", "c1-md-0-abcde"),
+                "c1-md-0-abcde-8vkhm",
+            ),
+            (
+                NameGenerator::control_plane(Some("{{ .cluster.name }}-cp"), "c1"),
+                "c1-cp",
+            ),
+            (
+                NameGenerator::machine_deployment(None, &"c".repeat(40), &"m".repeat(20)),
+                &trimmed,
+            ),
+        ] {
+            assert_eq!(
+                generator.generate_with(seeded_random("seed")).unwrap(),
+                expected,
+                "{}",
+                generator.template()
+            );
+        }
+    }
+
+    #[test]
+    fn validates_dns1123_subdomains() {
+        for (value, expected) in [
+            ("example.com", vec![]),
+            ("a", vec![]),
+            ("a-b.c-d", vec![]),
+            ("", vec![NOT_A_SUBDOMAIN]),
+            ("Example", vec![NOT_A_SUBDOMAIN]),
+            ("-a", vec![NOT_A_SUBDOMAIN]),
+            ("a-", vec![NOT_A_SUBDOMAIN]),
+            ("a..b", vec![NOT_A_SUBDOMAIN]),
+            ("a_b", vec![NOT_A_SUBDOMAIN]),
+            (
+                &"a".repeat(254),
+                vec!["must be no more than 253 characters"],
+            ),
+            (
+                &format!("{}-", "a".repeat(253)),
+                vec!["must be no more than 253 characters", NOT_A_SUBDOMAIN],
+            ),
+        ] {
+            assert_eq!(validate_dns1123_subdomain(value), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn name_errors() {
+        for (template, expected) in [
+            (
+                "{{ .cluster.nme }}",
+                r#"template: template name generator:1:3: executing "template name generator" at <.cluster.nme>: map has no entry for key "nme""#.to_string(),
+            ),
+            (
+                "{{ .cluster.name ",
+                "template: template name generator:1: unclosed action".to_string(),
+            ),
+            (
+                "{{ .cluster.name }}_cp",
+                format!(r#"generated name "c1_cp" is not valid: {NOT_A_SUBDOMAIN}"#),
+            ),
+        ] {
+            let generator = NameGenerator::control_plane(Some(template), "c1");
+            assert_eq!(
+                generator
+                    .generate_with(seeded_random("seed"))
+                    .unwrap_err()
+                    .to_string(),
+                expected,
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn validates_class_naming_strategies() {
+        let class: ClusterClass = serde_json::from_value(json!({
+            "metadata": {"name": "class"},
+            "spec": {
+                "infrastructureNamingStrategy": {"template": "{{ .cluster.name }}-{{ .random }}"},
+                "controlPlane": {
+                    "ref": {"kind": "KubeadmControlPlaneTemplate"},
+                    "namingStrategy": {"template": "{{ .cluster.name }}-{{ .machineDeployment.topologyName }}"},
+                },
+                "workers": {
+                    "machineDeployments": [
+                        {
+                            "class": "md-a",
+                            "template": {"bootstrap": {"ref": {}}, "infrastructure": {"ref": {}}},
+                            "namingStrategy": {"template": "{{ .cluster.name }}-{{ .machineDeployment.topologyName }}-{{ .random }}"},
+                        },
+                        {
+                            "class": "md-b",
+                            "template": {"bootstrap": {"ref": {}}, "infrastructure": {"ref": {}}},
+                            "namingStrategy": {"template": "{{ .cluster.name }}-{{ .machinePool.topologyName }}"},
+                        },
+                    ],
+                    "machinePools": [{
+                        "class": "mp-a",
+                        "template": {"bootstrap": {"ref": {}}, "infrastructure": {"ref": {}}},
+                        "namingStrategy": {"template": "{{ .cluster.name }}.-{{ .random }}"},
+                    }],
+                },
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            validate_class_naming_strategies(&class)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                r#"spec.controlPlane.namingStrategy.template: Invalid value: "{{ .cluster.name }}-{{ .machineDeployment.topologyName }}": invalid ControlPlane name template: template: template name generator:1:23: executing "template name generator" at <.machineDeployment.topologyName>: map has no entry for key "machineDeployment""#.to_string(),
+                r#"spec.workers.machineDeployments[1].namingStrategy.template: Invalid value: "{{ .cluster.name }}-{{ .machinePool.topologyName }}": invalid MachineDeployment name template: template: template name generator:1:23: executing "template name generator" at <.machinePool.topologyName>: map has no entry for key "machinePool""#.to_string(),
+                format!(r#"spec.workers.machinePools[0].namingStrategy.template: Invalid value: "{{{{ .cluster.name }}}}.-{{{{ .random }}}}": {NOT_A_SUBDOMAIN}"#),
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_machine_naming_strategies() {
+        let path = "spec.machineNamingStrategy.template";
+        for (template, expected) in [
+            ("{{ .machineSet.name }}-{{ .random }}", vec![]),
+            // names longer than 63 characters are trimmed
+            (
+                "{{ .machineSet.name }}-a-very-long-machine-name-that-gets-trimmed-{{ .random }}",
+                vec![],
+            ),
+            (
+                "{{ .machineSet.name }}",
+                vec![r#"spec.machineNamingStrategy.template: Invalid value: "{{ .machineSet.name }}": invalid template, {{ .random }} is missing"#.to_string()],
+            ),
+            (
+                "{{ .machineSet.name }}-{{ .random }}-{{ .foo }}",
+                vec![r#"spec.machineNamingStrategy.template: Invalid value: "{{ .machineSet.name }}-{{ .random }}-{{ .foo }}": invalid template: template: template name generator:1:40: executing "template name generator" at <.foo>: map has no entry for key "foo""#.to_string()],
+            ),
+            (
+                "{{ .machineSet.name }}_{{ .random }}",
+                vec![format!(r#"spec.machineNamingStrategy.template: Invalid value: "{{{{ .machineSet.name }}}}_{{{{ .random }}}}": {NOT_A_SUBDOMAIN}"#)],
+            ),
+        ] {
+            assert_eq!(
+                validate_machine_naming_strategy(template, path)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                expected,
+                "{template}"
+            );
+        }
+    }
+}
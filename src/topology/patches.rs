@@ -0,0 +1,900 @@
+//! Inline ClusterClass patches, following the topology controller's
+//! `internal/controllers/topology/cluster/patches/inline` generator.
+//!
+//! Patches are applied in the order of `ClusterClass.spec.patches`. For every template a patch
+//! definition selects, `enabledIf` is evaluated, the values are calculated from the variables
+//! and the JSON patches are applied. As upstream, only the `spec` of a patched template is kept,
+//! so patches must not remove it.
+//! External patches are served by runtime extensions and are skipped.
+
+use std::fmt;
+
+use crate::api::capi_clusterclass::{
+    ClusterClass, ClusterClassPatches, ClusterClassPatchesDefinitionsJsonPatches,
+    ClusterClassPatchesDefinitionsJsonPatchesOp, ClusterClassPatchesDefinitionsSelector,
+};
+
+use super::json_patch::{self, JsonPatchError};
+use super::template::{Template, TemplateError};
+use super::variables::{self, get_variable_value, template_data, VariableError, Variables};
+
+/// The reference holding a template, which decides the patch selectors that match it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TemplateHolder {
+    /// `Cluster.spec.infrastructureRef`.
+    InfrastructureCluster,
+    /// `Cluster.spec.controlPlaneRef`.
+    ControlPlane,
+    /// `spec.machineTemplate.infrastructureRef` of the control plane.
+    ControlPlaneInfrastructureMachine,
+    /// `MachineDeployment.spec.template.spec.bootstrap.configRef`.
+    MachineDeploymentBootstrap,
+    /// `MachineDeployment.spec.template.spec.infrastructureRef`.
+    MachineDeploymentInfrastructure,
+    /// `MachinePool.spec.template.spec.bootstrap.configRef`.
+    MachinePoolBootstrap,
+    /// `MachinePool.spec.template.spec.infrastructureRef`.
+    MachinePoolInfrastructure,
+}
+
+impl TemplateHolder {
+    /// The field path of the reference in its holder.
+    pub fn field_path(self) -> &'static str {
+        match self {
+            Self::InfrastructureCluster => "spec.infrastructureRef",
+            Self::ControlPlane => "spec.controlPlaneRef",
+            Self::ControlPlaneInfrastructureMachine => "spec.machineTemplate.infrastructureRef",
+            Self::MachineDeploymentBootstrap | Self::MachinePoolBootstrap => {
+                "spec.template.spec.bootstrap.configRef"
+            }
+            Self::MachineDeploymentInfrastructure | Self::MachinePoolInfrastructure => {
+                "spec.template.spec.infrastructureRef"
+            }
+        }
+    }
+}
+
+impl fmt::Display for TemplateHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let holder = match self {
+            Self::InfrastructureCluster | Self::ControlPlane => "Cluster",
+            Self::ControlPlaneInfrastructureMachine => "ControlPlane",
+            Self::MachineDeploymentBootstrap | Self::MachineDeploymentInfrastructure => {
+                "MachineDeployment"
+            }
+            Self::MachinePoolBootstrap | Self::MachinePoolInfrastructure => "MachinePool",
+        };
+        write!(f, "{holder} {}", self.field_path())
+    }
+}
+
+/// A template to patch, with the variables specific to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchTarget {
+    pub holder: TemplateHolder,
+    /// The template object, e.g. a `DockerMachineTemplate`.
+    pub template: serde_json::Value,
+    /// Template specific variables, e.g. `builtin.machineDeployment`, merged over the global ones.
+    pub variables: Variables,
+}
+
+impl PatchTarget {
+    pub fn new(holder: TemplateHolder, template: serde_json::Value) -> Self {
+        Self {
+            holder,
+            template,
+            variables: Variables::new(),
+        }
+    }
+
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    fn api_version(&self) -> &str {
+        self.template["apiVersion"].as_str().unwrap_or_default()
+    }
+
+    fn kind(&self) -> &str {
+        self.template["kind"].as_str().unwrap_or_default()
+    }
+
+    fn matches(&self, selector: &ClusterClassPatchesDefinitionsSelector) -> bool {
+        if selector.api_version != self.api_version() || selector.kind != self.kind() {
+            return false;
+        }
+        let resources = &selector.match_resources;
+        let (names, class_variable) = match self.holder {
+            TemplateHolder::InfrastructureCluster => {
+                return resources.infrastructure_cluster.unwrap_or_default()
+            }
+            TemplateHolder::ControlPlane | TemplateHolder::ControlPlaneInfrastructureMachine => {
+                return resources.control_plane.unwrap_or_default()
+            }
+            TemplateHolder::MachineDeploymentBootstrap
+            | TemplateHolder::MachineDeploymentInfrastructure => (
+                resources
+                    .machine_deployment_class
+                    .as_ref()
+                    .and_then(|c| c.names.as_ref()),
+                "builtin.machineDeployment.class",
+            ),
+            TemplateHolder::MachinePoolBootstrap | TemplateHolder::MachinePoolInfrastructure => (
+                resources
+                    .machine_pool_class
+                    .as_ref()
+                    .and_then(|c| c.names.as_ref()),
+                "builtin.machinePool.class",
+            ),
+        };
+        let Some(names) = names else {
+            return false;
+        };
+        let Ok(serde_json::Value::String(class)) =
+            get_variable_value(&self.variables, class_variable)
+        else {
+            return false;
+        };
+        names.iter().any(|name| class_name_matches(&class, name))
+    }
+}
+
+/// Matches a class against a selector name, which may be `*` or start or end with `*`.
+pub(super) fn class_name_matches(class: &str, name: &str) -> bool {
+    if name == "*" {
+        true
+    } else if let Some(suffix) = name.strip_prefix('*') {
+        class.ends_with(suffix)
+    } else if let Some(prefix) = name.strip_suffix('*') {
+        class.starts_with(prefix)
+    } else {
+        class == name
+    }
+}
+
+/// Error applying the patches of a ClusterClass.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("failed to apply patch {patch:?} to {holder}: {kind}")]
+pub struct PatchError {
+    /// Name of the ClusterClass patch.
+    pub patch: String,
+    pub holder: TemplateHolder,
+    pub kind: PatchErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum PatchErrorKind {
+    #[error("failed to calculate value for enabledIf: {0}")]
+    EnabledIf(#[source] ValueError),
+    #[error("definitions[{definition}].jsonPatches[{index}]: failed to calculate value: {source}")]
+    Value {
+        definition: usize,
+        index: usize,
+        source: ValueError,
+    },
+    #[error("definitions[{definition}].jsonPatches[{index}]: unknown op {op:?}")]
+    UnknownOp {
+        definition: usize,
+        index: usize,
+        op: String,
+    },
+    #[error("definitions[{definition}].jsonPatches[{index}]: {source}")]
+    Apply {
+        definition: usize,
+        index: usize,
+        source: JsonPatchError,
+    },
+    #[error("missing field \"spec\" in {api_version}, Kind={kind}")]
+    MissingSpec { api_version: String, kind: String },
+}
+
+/// Error calculating the value of a JSON patch or `enabledIf`.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ValueError {
+    #[error("neither .value nor .valueFrom are set")]
+    NoValue,
+    #[error("both .value and .valueFrom are set")]
+    BothValues,
+    #[error(".valueFrom is set, but neither .valueFrom.variable nor .valueFrom.template are set")]
+    NoValueFrom,
+    #[error(".valueFrom is set, but both .valueFrom.variable and .valueFrom.template are set")]
+    BothValueFrom,
+    #[error(transparent)]
+    Variable(#[from] VariableError),
+    #[error("failed to render template {template:?}: {source}")]
+    Template {
+        template: String,
+        source: Box<TemplateError>,
+    },
+    #[error("failed to convert rendered template to JSON {rendered:?}: {message}")]
+    Yaml { rendered: String, message: String },
+}
+
+/// Renders a template against the variables and parses the output as YAML.
+pub fn render_value_template(
+    template: &str,
+    variables: &Variables,
+) -> Result<serde_json::Value, ValueError> {
+    let rendered = Template::parse("tpl", template)
+        .and_then(|tpl| tpl.execute(&template_data(variables)))
+        .map_err(|source| ValueError::Template {
+            template: template.to_string(),
+            source: Box::new(source),
+        })?;
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(&rendered).map_err(|err| ValueError::Yaml {
+            rendered: rendered.clone(),
+            message: err.to_string(),
+        })?;
+    serde_json::to_value(value).map_err(|err| ValueError::Yaml {
+        rendered,
+        message: err.to_string(),
+    })
+}
+
+/// Whether a patch is enabled: a missing `enabledIf` is, otherwise it has to render to `true`.
+pub fn patch_is_enabled(
+    enabled_if: Option<&str>,
+    variables: &Variables,
+) -> Result<bool, ValueError> {
+    match enabled_if {
+        None => Ok(true),
+        Some(enabled_if) => {
+            Ok(render_value_template(enabled_if, variables)? == serde_json::Value::Bool(true))
+        }
+    }
+}
+
+/// Calculates the value of an `add` or `replace` JSON patch.
+pub fn calculate_value(
+    patch: &ClusterClassPatchesDefinitionsJsonPatches,
+    variables: &Variables,
+) -> Result<serde_json::Value, ValueError> {
+    let value_from = match (&patch.value, &patch.value_from) {
+        (None, None) => return Err(ValueError::NoValue),
+        (Some(_), Some(_)) => return Err(ValueError::BothValues),
+        (Some(value), None) => return Ok(value.clone()),
+        (None, Some(value_from)) => value_from,
+    };
+    match (&value_from.variable, &value_from.template) {
+        (None, None) => Err(ValueError::NoValueFrom),
+        (Some(_), Some(_)) => Err(ValueError::BothValueFrom),
+        (Some(variable), None) => Ok(get_variable_value(variables, variable)?),
+        (None, Some(template)) => render_value_template(template, variables),
+    }
+}
+
+/// A ClusterClass template that failed to render.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{location} of patch {patch:?}: {source}")]
+pub struct TemplateCheckError {
+    /// Name of the ClusterClass patch.
+    pub patch: String,
+    /// Field path of the template, e.g. `spec.patches[0].definitions[1].jsonPatches[2].valueFrom.template`.
+    pub location: String,
+    pub source: ValueError,
+}
+
+/// Renders every `enabledIf` and `valueFrom.template` of the inline patches against the
+/// variables and returns the ones that fail, so a ClusterClass can be checked in CI against
+/// representative Clusters. Templates are rendered whether or not their patch is enabled.
+pub fn check_templates(class: &ClusterClass, variables: &Variables) -> Vec<TemplateCheckError> {
+    let mut errors = Vec::new();
+    for (i, patch) in class.spec.patches.iter().flatten().enumerate() {
+        let mut check = |location: String, template: &str| {
+            if let Err(source) = render_value_template(template, variables) {
+                errors.push(TemplateCheckError {
+                    patch: patch.name.clone(),
+                    location,
+                    source,
+                });
+            }
+        };
+        if let Some(enabled_if) = &patch.enabled_if {
+            check(format!("spec.patches[{i}].enabledIf"), enabled_if);
+        }
+        for (d, definition) in patch.definitions.iter().flatten().enumerate() {
+            for (j, json_patch) in definition.json_patches.iter().enumerate() {
+                let template = json_patch
+                    .value_from
+                    .as_ref()
+                    .and_then(|value_from| value_from.template.as_deref());
+                if let Some(template) = template {
+                    let location = format!(
+                        "spec.patches[{i}].definitions[{d}].jsonPatches[{j}].valueFrom.template"
+                    );
+                    check(location, template);
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// Applies the inline patches of a ClusterClass to the targets, with the global variables
+/// usually taken from [`cluster_variables`](super::variables::cluster_variables).
+pub fn apply_patches(
+    class: &ClusterClass,
+    variables: &Variables,
+    targets: &mut [PatchTarget],
+) -> Result<(), PatchError> {
+    for patch in class.spec.patches.iter().flatten() {
+        for target in targets.iter_mut() {
+            apply_patch(patch, variables, target).map_err(|kind| PatchError {
+                patch: patch.name.clone(),
+                holder: target.holder,
+                kind,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn apply_patch(
+    patch: &ClusterClassPatches,
+    global: &Variables,
+    target: &mut PatchTarget,
+) -> Result<(), PatchErrorKind> {
+    let Some(definitions) = &patch.definitions else {
+        return Ok(());
+    };
+    let matching: Vec<_> = definitions
+        .iter()
+        .enumerate()
+        .filter(|(_, definition)| target.matches(&definition.selector))
+        .collect();
+    if matching.is_empty() {
+        return Ok(());
+    }
+    let variables = variables::merge_variables([global, &target.variables]);
+    if !patch_is_enabled(patch.enabled_if.as_deref(), &variables)
+        .map_err(PatchErrorKind::EnabledIf)?
+    {
+        return Ok(());
+    }
+    let mut patched = target.template.clone();
+    for (definition, patches) in matching {
+        for (index, json_patch) in patches.json_patches.iter().enumerate() {
+            let path = json_patch.path.as_str();
+            let value = || {
+                calculate_value(json_patch, &variables).map_err(|source| PatchErrorKind::Value {
+                    definition,
+                    index,
+                    source,
+                })
+            };
+            let applied = match &json_patch.op {
+                ClusterClassPatchesDefinitionsJsonPatchesOp::Add => {
+                    json_patch::add(&mut patched, path, value()?)
+                }
+                ClusterClassPatchesDefinitionsJsonPatchesOp::Replace => {
+                    json_patch::replace(&mut patched, path, value()?)
+                }
+                ClusterClassPatchesDefinitionsJsonPatchesOp::Remove => {
+                    json_patch::remove(&mut patched, path)
+                }
+                ClusterClassPatchesDefinitionsJsonPatchesOp::Other(op) => {
+                    return Err(PatchErrorKind::UnknownOp {
+                        definition,
+                        index,
+                        op: op.clone(),
+                    })
+                }
+            };
+            applied.map_err(|source| PatchErrorKind::Apply {
+                definition,
+                index,
+                source,
+            })?;
+        }
+    }
+    // only the spec of a template can be patched, like copySpec
+    let spec =
+        patched
+            .get_mut("spec")
+            .map(std::mem::take)
+            .ok_or_else(|| PatchErrorKind::MissingSpec {
+                api_version: patched["apiVersion"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                kind: patched["kind"].as_str().unwrap_or_default().to_string(),
+            })?;
+    target.template["spec"] = spec;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const INFRASTRUCTURE: &str = "infrastructure.cluster.x-k8s.io/v1beta1";
+
+    fn class(patches: serde_json::Value) -> ClusterClass {
+        serde_json::from_value(json!({"metadata": {"name": "class"}, "spec": {"patches": patches}}))
+            .unwrap()
+    }
+
+    /// A patch with a single definition adding `spec.template.spec.patched` to the templates
+    /// the selector matches.
+    fn selector_patch(selector: serde_json::Value) -> serde_json::Value {
+        json!([{
+            "name": "p",
+            "definitions": [{
+                "selector": selector,
+                "jsonPatches": [
+                    {"op": "add", "path": "/spec/template/spec/patched", "value": true},
+                ],
+            }],
+        }])
+    }
+
+    /// A patch with a single definition for the infrastructure cluster template.
+    fn patch(json_patches: serde_json::Value) -> serde_json::Value {
+        json!([{
+            "name": "p",
+            "definitions": [{
+                "selector": {
+                    "apiVersion": INFRASTRUCTURE,
+                    "kind": "DockerClusterTemplate",
+                    "matchResources": {"infrastructureCluster": true},
+                },
+                "jsonPatches": json_patches,
+            }],
+        }])
+    }
+
+    fn template(api_version: &str, kind: &str) -> serde_json::Value {
+        json!({
+            "apiVersion": api_version,
+            "kind": kind,
+            "metadata": {"name": "t", "namespace": "ns"},
+            "spec": {"template": {"spec": {}}},
+        })
+    }
+
+    fn builtin(key: &str, class: &str) -> Variables {
+        Variables::from([("builtin".to_string(), json!({key: {"class": class}}))])
+    }
+
+    fn targets() -> Vec<PatchTarget> {
+        let bootstrap = "bootstrap.cluster.x-k8s.io/v1beta1";
+        vec![
+            PatchTarget::new(
+                TemplateHolder::InfrastructureCluster,
+                template(INFRASTRUCTURE, "DockerClusterTemplate"),
+            ),
+            PatchTarget::new(
+                TemplateHolder::ControlPlane,
+                template(
+                    "controlplane.cluster.x-k8s.io/v1beta1",
+                    "KubeadmControlPlaneTemplate",
+                ),
+            ),
+            PatchTarget::new(
+                TemplateHolder::ControlPlaneInfrastructureMachine,
+                template(INFRASTRUCTURE, "DockerMachineTemplate"),
+            ),
+            PatchTarget::new(
+                TemplateHolder::MachineDeploymentBootstrap,
+                template(bootstrap, "KubeadmConfigTemplate"),
+            )
+            .with_variables(builtin("machineDeployment", "default-worker")),
+            PatchTarget::new(
+                TemplateHolder::MachineDeploymentInfrastructure,
+                template(INFRASTRUCTURE, "DockerMachineTemplate"),
+            )
+            .with_variables(builtin("machineDeployment", "default-worker")),
+            PatchTarget::new(
+                TemplateHolder::MachinePoolBootstrap,
+                template(bootstrap, "KubeadmConfigTemplate"),
+            )
+            .with_variables(builtin("machinePool", "pool")),
+            PatchTarget::new(
+                TemplateHolder::MachinePoolInfrastructure,
+                template(INFRASTRUCTURE, "DockerMachinePoolTemplate"),
+            )
+            .with_variables(builtin("machinePool", "pool")),
+        ]
+    }
+
+    /// Applies the patches to the infrastructure cluster template with the given spec.
+    fn apply(
+        patches: serde_json::Value,
+        variables: &Variables,
+        spec: serde_json::Value,
+    ) -> Result<serde_json::Value, PatchError> {
+        let mut template = template(INFRASTRUCTURE, "DockerClusterTemplate");
+        template["spec"]["template"]["spec"] = spec;
+        let mut targets = [PatchTarget::new(
+            TemplateHolder::InfrastructureCluster,
+            template,
+        )];
+        apply_patches(&class(patches), variables, &mut targets)?;
+        let [target] = targets;
+        Ok(target.template)
+    }
+
+    #[test]
+    fn selects_templates() {
+        use TemplateHolder::*;
+        let selector = |api_version: &str, kind: &str, match_resources: serde_json::Value| json!({"apiVersion": api_version, "kind": kind, "matchResources": match_resources});
+        let md_class = |names: &[&str]| json!({"machineDeploymentClass": {"names": names}});
+        for (selector, expected) in [
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerClusterTemplate",
+                    json!({"infrastructureCluster": true}),
+                ),
+                vec![InfrastructureCluster],
+            ),
+            (
+                selector(
+                    "controlplane.cluster.x-k8s.io/v1beta1",
+                    "KubeadmControlPlaneTemplate",
+                    json!({"controlPlane": true}),
+                ),
+                vec![ControlPlane],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    json!({"controlPlane": true}),
+                ),
+                vec![ControlPlaneInfrastructureMachine],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    md_class(&["default-worker"]),
+                ),
+                vec![MachineDeploymentInfrastructure],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    md_class(&["other", "default-*"]),
+                ),
+                vec![MachineDeploymentInfrastructure],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    md_class(&["*-worker"]),
+                ),
+                vec![MachineDeploymentInfrastructure],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    md_class(&["other"]),
+                ),
+                vec![],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachineTemplate",
+                    json!({"controlPlane": true, "machineDeploymentClass": {"names": ["*"]}}),
+                ),
+                vec![
+                    ControlPlaneInfrastructureMachine,
+                    MachineDeploymentInfrastructure,
+                ],
+            ),
+            (
+                selector(
+                    "bootstrap.cluster.x-k8s.io/v1beta1",
+                    "KubeadmConfigTemplate",
+                    md_class(&["*"]),
+                ),
+                vec![MachineDeploymentBootstrap],
+            ),
+            (
+                selector(
+                    "bootstrap.cluster.x-k8s.io/v1beta1",
+                    "KubeadmConfigTemplate",
+                    json!({"machinePoolClass": {"names": ["pool"]}}),
+                ),
+                vec![MachinePoolBootstrap],
+            ),
+            (
+                selector(
+                    INFRASTRUCTURE,
+                    "DockerMachinePoolTemplate",
+                    json!({"machinePoolClass": {"names": ["pool"]}}),
+                ),
+                vec![MachinePoolInfrastructure],
+            ),
+            // the API version has to match exactly
+            (
+                selector(
+                    "infrastructure.cluster.x-k8s.io/v1beta2",
+                    "DockerClusterTemplate",
+                    json!({"infrastructureCluster": true}),
+                ),
+                vec![],
+            ),
+            (
+                selector(INFRASTRUCTURE, "DockerClusterTemplate", json!({})),
+                vec![],
+            ),
+        ] {
+            let mut targets = targets();
+            apply_patches(
+                &class(selector_patch(selector.clone())),
+                &Variables::new(),
+                &mut targets,
+            )
+            .unwrap();
+            let patched: Vec<_> = targets
+                .iter()
+                .filter(|target| target.template["spec"]["template"]["spec"]["patched"] == true)
+                .map(|target| target.holder)
+                .collect();
+            assert_eq!(patched, expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn class_names() {
+        for (class, name, expected) in [
+            ("default-worker", "default-worker", true),
+            ("default-worker", "default", false),
+            ("default-worker", "*", true),
+            ("default-worker", "default-*", true),
+            ("default-worker", "*-worker", true),
+            ("default-worker", "*-pool", false),
+            ("", "*", true),
+        ] {
+            assert_eq!(class_name_matches(class, name), expected, "{class} {name}");
+        }
+    }
+
+    #[test]
+    fn applies_operations() {
+        let spec = json!({"a": 1, "list": ["x"], "nested": {"b": 2, "c": 3}});
+        let patched = apply(
+            patch(json!([
+                {"op": "add", "path": "/spec/template/spec/d", "value": {"e": [1]}},
+                {"op": "replace", "path": "/spec/template/spec/a", "value": "one"},
+                {"op": "remove", "path": "/spec/template/spec/nested/b"},
+                {"op": "add", "path": "/spec/template/spec/list/-", "value": "z"},
+                {"op": "add", "path": "/spec/template/spec/list/0", "value": "w"},
+                {"op": "add", "path": "/spec/template/spec/list/1", "value": "y"},
+                {"op": "add", "path": "/spec/template/spec/d/e/-", "value": 2},
+                {"op": "replace", "path": "/spec/template/spec/list/3", "value": "last"},
+                {"op": "add", "path": "/spec/template/spec/a~1b", "value": "slash"},
+            ])),
+            &Variables::new(),
+            spec,
+        )
+        .unwrap();
+        assert_eq!(
+            patched["spec"]["template"]["spec"],
+            json!({
+                "a": "one",
+                "a/b": "slash",
+                "d": {"e": [1, 2]},
+                "list": ["w", "y", "x", "last"],
+                "nested": {"c": 3},
+            })
+        );
+    }
+
+    #[test]
+    fn only_copies_the_spec() {
+        let patched = apply(
+            patch(json!([
+                {"op": "add", "path": "/metadata/labels", "value": {"a": "b"}},
+                {"op": "replace", "path": "/kind", "value": "Other"},
+                {"op": "remove", "path": "/metadata/namespace"},
+                {"op": "add", "path": "/spec/template/spec/a", "value": 1},
+            ])),
+            &Variables::new(),
+            json!({}),
+        )
+        .unwrap();
+        let mut expected = template(INFRASTRUCTURE, "DockerClusterTemplate");
+        expected["spec"]["template"]["spec"] = json!({"a": 1});
+        assert_eq!(patched, expected);
+
+        // the spec may be replaced as a whole
+        let patched = apply(
+            patch(json!([{"op": "replace", "path": "/spec", "value": {"x": 1}}])),
+            &Variables::new(),
+            json!({}),
+        )
+        .unwrap();
+        assert_eq!(patched["spec"], json!({"x": 1}));
+    }
+
+    #[test]
+    fn calculates_values() {
+        let variables = Variables::from([
+            ("builtin".to_string(), json!({"cluster": {"name": "c1"}})),
+            (
+                "network".to_string(),
+                json!({"subnets": [{"cidr": "10.0.0.0/24"}, {"cidr": "10.0.1.0/24"}]}),
+            ),
+            ("port".to_string(), json!(6443)),
+            ("enabled".to_string(), json!(true)),
+        ]);
+        let patched = apply(
+            patch(json!([
+                {"op": "add", "path": "/spec/template/spec/cidr", "valueFrom": {"variable": "network.subnets[1].cidr"}},
+                {"op": "add", "path": "/spec/template/spec/subnets", "valueFrom": {"variable": "network.subnets"}},
+                {"op": "add", "path": "/spec/template/spec/cluster", "valueFrom": {"variable": "builtin.cluster.name"}},
+                {"op": "add", "path": "/spec/template/spec/lb", "valueFrom": {"template": "{{ .builtin.cluster.name }}-lb"}},
+                {"op": "add", "path": "/spec/template/spec/endpoint", "valueFrom": {"template": "host: {{ .builtin.cluster.name }}\nport: {{ .port }}"}},
+            ])),
+            &variables,
+            json!({}),
+        )
+        .unwrap();
+        assert_eq!(
+            patched["spec"]["template"]["spec"],
+            json!({
+                "cidr": "10.0.1.0/24",
+                "subnets": [{"cidr": "10.0.0.0/24"}, {"cidr": "10.0.1.0/24"}],
+                "cluster": "c1",
+                "lb": "c1-lb",
+                "endpoint": {"host": "c1", "port": 6443},
+            })
+        );
+
+        for (enabled_if, expected) in [
+            ("{{ .enabled }}", json!({"a": 1})),
+            ("{{ not .enabled }}", json!({})),
+            ("{{ if .enabled }}true{{ end }}", json!({"a": 1})),
+            // anything but true disables a patch
+            ("yes", json!({})),
+            ("{{ .port }}", json!({})),
+        ] {
+            let mut patches = patch(json!([
+                {"op": "add", "path": "/spec/template/spec/a", "value": 1},
+            ]));
+            patches[0]["enabledIf"] = enabled_if.into();
+            let patched = apply(patches, &variables, json!({})).unwrap();
+            assert_eq!(
+                patched["spec"]["template"]["spec"], expected,
+                "{enabled_if}"
+            );
+        }
+    }
+
+    #[test]
+    fn template_variables_override_global_ones() {
+        let variables = Variables::from([
+            ("builtin".to_string(), json!({"cluster": {"name": "c1"}})),
+            ("size".to_string(), json!("small")),
+        ]);
+        let mut targets = targets();
+        targets[4]
+            .variables
+            .insert("size".to_string(), json!("large"));
+        let patches = json!([{
+            "name": "p",
+            "definitions": [{
+                "selector": {
+                    "apiVersion": INFRASTRUCTURE,
+                    "kind": "DockerMachineTemplate",
+                    "matchResources": {"controlPlane": true, "machineDeploymentClass": {"names": ["*"]}},
+                },
+                "jsonPatches": [{
+                    "op": "add",
+                    "path": "/spec/template/spec/size",
+                    "valueFrom": {"template": "{{ .size }}-{{ .builtin.cluster.name }}"},
+                }],
+            }],
+        }]);
+        apply_patches(&class(patches), &variables, &mut targets).unwrap();
+        assert_eq!(
+            targets[2].template["spec"]["template"]["spec"],
+            json!({"size": "small-c1"})
+        );
+        assert_eq!(
+            targets[4].template["spec"]["template"]["spec"],
+            json!({"size": "large-c1"})
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let variables = Variables::from([("a".to_string(), json!({"b": [1]}))]);
+        let prefix = r#"failed to apply patch "p" to Cluster spec.infrastructureRef: "#;
+        for (json_patches, expected) in [
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x", "valueFrom": {"variable": "missing"}}]),
+                r#"definitions[0].jsonPatches[0]: failed to calculate value: variable "missing" does not exist"#,
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x", "valueFrom": {"variable": "a.c"}}]),
+                r#"definitions[0].jsonPatches[0]: failed to calculate value: variable "a.c" does not exist: failed to lookup segment "c""#,
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x", "valueFrom": {"variable": "a.b[1]"}}]),
+                r#"definitions[0].jsonPatches[0]: failed to calculate value: variable "a.b[1]": index 1 out of bounds for "b""#,
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x"}]),
+                "definitions[0].jsonPatches[0]: failed to calculate value: neither .value nor .valueFrom are set",
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x", "value": 1, "valueFrom": {"variable": "a"}}]),
+                "definitions[0].jsonPatches[0]: failed to calculate value: both .value and .valueFrom are set",
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/spec/x", "valueFrom": {}}]),
+                "definitions[0].jsonPatches[0]: failed to calculate value: .valueFrom is set, but neither .valueFrom.variable nor .valueFrom.template are set",
+            ),
+            (
+                json!([
+                    {"op": "add", "path": "/spec/template/spec/x", "value": 1},
+                    {"op": "move", "path": "/spec/template/spec/y"},
+                ]),
+                r#"definitions[0].jsonPatches[1]: unknown op "move""#,
+            ),
+            (
+                json!([{"op": "replace", "path": "/spec/template/spec/x", "value": 1}]),
+                r#"definitions[0].jsonPatches[0]: replace operation does not apply: doc is missing path: "/spec/template/spec/x""#,
+            ),
+            (
+                json!([{"op": "remove", "path": "/spec/template/spec/x"}]),
+                r#"definitions[0].jsonPatches[0]: remove operation does not apply: doc is missing path: "/spec/template/spec/x""#,
+            ),
+            (
+                json!([{"op": "add", "path": "/spec/template/x/y", "value": 1}]),
+                r#"definitions[0].jsonPatches[0]: add operation does not apply: doc is missing path: "/spec/template/x/y""#,
+            ),
+            (
+                json!([
+                    {"op": "add", "path": "/spec/template/spec/list", "value": []},
+                    {"op": "add", "path": "/spec/template/spec/list/1", "value": 1},
+                ]),
+                r#"definitions[0].jsonPatches[1]: add operation does not apply: index "1" is invalid for path "/spec/template/spec/list/1""#,
+            ),
+            (
+                json!([{"op": "add", "path": "spec", "value": 1}]),
+                r#"definitions[0].jsonPatches[0]: invalid path "spec": must be empty or start with '/'"#,
+            ),
+            (
+                json!([{"op": "remove", "path": "/spec"}]),
+                r#"missing field "spec" in infrastructure.cluster.x-k8s.io/v1beta1, Kind=DockerClusterTemplate"#,
+            ),
+        ] {
+            assert_eq!(
+                apply(patch(json_patches.clone()), &variables, json!({}))
+                    .unwrap_err()
+                    .to_string(),
+                format!("{prefix}{expected}"),
+                "{json_patches}"
+            );
+        }
+
+        let mut patches = patch(json!([]));
+        patches[0]["enabledIf"] = "{{ if }}".into();
+        assert_eq!(
+            apply(patches, &variables, json!({}))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "{prefix}failed to calculate value for enabledIf: failed to render template {:?}: template: tpl:1: missing value for if",
+                "{{ if }}"
+            ),
+        );
+        // missing variables render as "<no value>", which disables a patch
+        let mut patches = patch(json!([{"op": "remove", "path": "/spec"}]));
+        patches[0]["enabledIf"] = "{{ .missing.x }}".into();
+        assert!(apply(patches, &variables, json!({})).is_ok());
+    }
+}
@@ -0,0 +1,1345 @@
+//! Offline topology plans, like `clusterctl alpha topology plan`: the objects the topology
+//! controller computes for a Cluster from its ClusterClass and templates, and how they differ
+//! from the objects that exist.
+//!
+//! The desired state follows `internal/controllers/topology/cluster/desired_state.go`:
+//! templates are patched, then cloned into the InfrastructureCluster, the ControlPlane, the
+//! MachineDeployments and MachinePools with their templates, and the MachineHealthChecks.
+//! Existing objects keep their names; a template whose spec changes is rotated to a new name,
+//! as the controller does to roll out the change. Upgrades are not sequenced: every object gets
+//! the version of the topology. External patches are skipped.
+//!
+//! Generated names would normally contain a random suffix. To keep plans reproducible,
+//! `.random` is derived from the Cluster and the object being named instead.
+
+use std::fmt;
+
+use k8s_openapi::api::core::v1::ObjectReference;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::api::capi_machinedeployment::MachineDeployment;
+use crate::api::capi_machinepool::MachinePool;
+use crate::api::well_known::{annotations, labels};
+
+use super::builtins::Builtins;
+use super::field::{self, FieldError};
+use super::naming::{seeded_random, simple_name, NameGenerator, NamingError};
+use super::patches::{apply_patches, PatchError, PatchTarget, TemplateHolder};
+use super::variables::{
+    cluster_variables, default_topology_variables, merge_variables, override_variables,
+    validate_topology_variables,
+};
+
+const CLUSTER_API_VERSION: &str = "cluster.x-k8s.io/v1beta1";
+
+/// Identifies an object in a plan.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId {
+    pub api_version: String,
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+}
+
+impl ObjectId {
+    /// The id of an unstructured object.
+    pub fn of(object: &Value) -> Self {
+        let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+        Self {
+            api_version: text(&object["apiVersion"]),
+            kind: text(&object["kind"]),
+            namespace: text(&object["metadata"]["namespace"]),
+            name: text(&object["metadata"]["name"]),
+        }
+    }
+
+    /// Whether two ids refer to the same object, whatever the version of their apiVersion.
+    pub fn same_object(&self, other: &ObjectId) -> bool {
+        api_group(&self.api_version) == api_group(&other.api_version)
+            && self.kind == other.kind
+            && self.namespace == other.namespace
+            && self.name == other.name
+    }
+}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.kind, self.namespace, self.name)
+    }
+}
+
+fn api_group(api_version: &str) -> &str {
+    api_version.split_once('/').map_or("", |(group, _)| group)
+}
+
+/// An existing object the plan changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModifiedObject {
+    pub id: ObjectId,
+    /// Paths of the fields the plan sets to a different value, e.g. `spec.replicas`.
+    pub fields: Vec<String>,
+}
+
+/// The desired objects of a Cluster topology and their difference with the current objects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TopologyPlan {
+    /// The desired objects: the Cluster, the InfrastructureCluster, the control plane objects,
+    /// then the objects of each MachineDeployment and MachinePool.
+    pub desired: Vec<Value>,
+    pub created: Vec<ObjectId>,
+    pub modified: Vec<ModifiedObject>,
+    /// Current objects owned by the topology of the Cluster that are no longer desired,
+    /// including templates replaced by a rotation.
+    pub deleted: Vec<ObjectId>,
+}
+
+/// Error computing a topology plan.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum PlanError {
+    #[error("Cluster has no spec.topology")]
+    NoTopology,
+    #[error("ClusterClass has no spec.{0}")]
+    MissingClassField(&'static str),
+    #[error("invalid Cluster variables: {}", join_errors(.0))]
+    Variables(Vec<FieldError>),
+    #[error("{path}: {kind} class {class:?} is not defined in the ClusterClass")]
+    ClassNotFound {
+        path: String,
+        kind: &'static str,
+        class: String,
+    },
+    #[error("{path}: template {kind} {name:?} not found")]
+    TemplateNotFound {
+        path: String,
+        kind: String,
+        name: String,
+    },
+    #[error("failed to generate a name from {path}: {source}")]
+    Name {
+        path: String,
+        source: Box<NamingError>,
+    },
+    #[error(transparent)]
+    Patch(Box<PatchError>),
+    #[error("failed to compute {id}: {message}")]
+    Object { id: ObjectId, message: String },
+}
+
+fn join_errors(errors: &[FieldError]) -> String {
+    let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    errors.join("; ")
+}
+
+/// Computes the desired objects of a Cluster topology, like the topology controller does,
+/// and compares them with the current objects, e.g. the objects of the Cluster read from the
+/// management cluster. `templates` are the objects the ClusterClass refers to.
+///
+/// The topology variables are defaulted and validated first, as by the Cluster webhook. The
+/// Cluster itself is compared with the given Cluster, so defaulted variables and the references
+/// to the InfrastructureCluster and ControlPlane show up as its modifications.
+pub fn plan_topology(
+    cluster: &Cluster,
+    class: &ClusterClass,
+    templates: &[Value],
+    current: &[Value],
+) -> Result<TopologyPlan, PlanError> {
+    if cluster.spec.topology.is_none() {
+        return Err(PlanError::NoTopology);
+    }
+    let mut defaulted = cluster.clone();
+    default_topology_variables(cluster, class)
+        .map_err(PlanError::Variables)?
+        .apply(&mut defaulted);
+    let errors = validate_topology_variables(&defaulted, None, class);
+    if !errors.is_empty() {
+        return Err(PlanError::Variables(errors));
+    }
+    let planner = Planner {
+        cluster: &defaulted,
+        class,
+        templates,
+        current,
+        namespace: cluster.metadata.namespace.clone().unwrap_or_default(),
+        cluster_name: cluster.metadata.name.clone().unwrap_or_default(),
+    };
+    let desired = planner.desired()?;
+    let original = serde_json::to_value(cluster).unwrap_or_default();
+    Ok(planner.diff(desired, &original))
+}
+
+struct Planner<'a> {
+    cluster: &'a Cluster,
+    class: &'a ClusterClass,
+    templates: &'a [Value],
+    current: &'a [Value],
+    namespace: String,
+    cluster_name: String,
+}
+
+/// A MachineDeployment or MachinePool with the indexes of its patch targets.
+struct Workers<'a> {
+    object: Value,
+    bootstrap: usize,
+    infrastructure: usize,
+    current: Option<&'a Value>,
+    /// Prefix of the generated names of its templates.
+    prefix: String,
+    /// Name of the topology, to derive names from.
+    topology_name: String,
+}
+
+impl<'a> Planner<'a> {
+    fn desired(&self) -> Result<Vec<Value>, PlanError> {
+        let topology = self
+            .cluster
+            .spec
+            .topology
+            .as_ref()
+            .ok_or(PlanError::NoTopology)?;
+        let spec = &self.class.spec;
+        let global = merge_variables([
+            &cluster_variables(self.cluster),
+            &Builtins::for_cluster(self.cluster).to_variables(),
+        ]);
+        let mut targets = Vec::new();
+
+        let infrastructure = spec
+            .infrastructure
+            .as_ref()
+            .ok_or(PlanError::MissingClassField("infrastructure"))?;
+        targets.push(PatchTarget::new(
+            TemplateHolder::InfrastructureCluster,
+            self.template(&infrastructure.r#ref, "spec.infrastructure.ref")?,
+        ));
+        let infrastructure_name =
+            match self.find_reference(self.cluster.spec.infrastructure_ref.as_ref()) {
+                Some(current) => object_name(current),
+                None => self.name(
+                    NameGenerator::infrastructure_cluster(
+                        spec.infrastructure_naming_strategy
+                            .as_ref()
+                            .and_then(|strategy| strategy.template.as_deref()),
+                        &self.cluster_name,
+                    ),
+                    "spec.infrastructureNamingStrategy.template",
+                    "infrastructureCluster",
+                )?,
+            };
+
+        // control plane
+        let control_plane = spec
+            .control_plane
+            .as_ref()
+            .ok_or(PlanError::MissingClassField("controlPlane"))?;
+        let topology_control_plane = topology.control_plane.as_ref();
+        let current_control_plane =
+            self.find_reference(self.cluster.spec.control_plane_ref.as_ref());
+        let control_plane_name = match current_control_plane {
+            Some(current) => object_name(current),
+            None => self.name(
+                NameGenerator::control_plane(
+                    control_plane
+                        .naming_strategy
+                        .as_ref()
+                        .and_then(|strategy| strategy.template.as_deref()),
+                    &self.cluster_name,
+                ),
+                "spec.controlPlane.namingStrategy.template",
+                "controlPlane",
+            )?,
+        };
+        let current_machine_infrastructure = current_control_plane.and_then(|cp| {
+            self.find_ref_value(&cp["spec"]["machineTemplate"]["infrastructureRef"])
+        });
+        let machine_infrastructure_name = match current_machine_infrastructure {
+            Some(current) => object_name(current),
+            None => self.generate_name(
+                &format!("{}-", self.cluster_name),
+                "controlPlane/machineInfrastructure",
+            ),
+        };
+        let replicas = topology_control_plane.and_then(|cp| cp.replicas);
+        let builtins = Builtins::for_control_plane(
+            topology_control_plane,
+            &json!({
+                "metadata": { "name": control_plane_name },
+                "spec": { "version": topology.version, "replicas": replicas },
+            }),
+            control_plane
+                .machine_infrastructure
+                .as_ref()
+                .map(|_| json!({ "metadata": { "name": machine_infrastructure_name } }))
+                .as_ref(),
+        );
+        let overrides = topology_control_plane
+            .and_then(|cp| cp.variables.as_ref())
+            .and_then(|variables| variables.overrides.as_deref())
+            .unwrap_or_default();
+        let variables = merge_variables([&override_variables(overrides), &builtins.to_variables()]);
+        let control_plane_target = targets.len();
+        targets.push(
+            PatchTarget::new(
+                TemplateHolder::ControlPlane,
+                self.template(&control_plane.r#ref, "spec.controlPlane.ref")?,
+            )
+            .with_variables(variables.clone()),
+        );
+        let machine_infrastructure_target = match &control_plane.machine_infrastructure {
+            Some(machine_infrastructure) => {
+                targets.push(
+                    PatchTarget::new(
+                        TemplateHolder::ControlPlaneInfrastructureMachine,
+                        self.template(
+                            &machine_infrastructure.r#ref,
+                            "spec.controlPlane.machineInfrastructure.ref",
+                        )?,
+                    )
+                    .with_variables(variables),
+                );
+                Some(targets.len() - 1)
+            }
+            None => None,
+        };
+
+        let workers = topology.workers.as_ref();
+        let class_workers = spec.workers.as_ref();
+        let mut machine_deployments = Vec::new();
+        for (i, md) in workers
+            .and_then(|workers| workers.machine_deployments.as_ref())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let (j, md_class) = class_workers
+                .and_then(|workers| workers.machine_deployments.as_ref())
+                .and_then(|classes| {
+                    classes
+                        .iter()
+                        .enumerate()
+                        .find(|(_, c)| c.class == md.class)
+                })
+                .ok_or_else(|| PlanError::ClassNotFound {
+                    path: field::child(
+                        &field::index("spec.topology.workers.machineDeployments", i),
+                        "class",
+                    ),
+                    kind: "MachineDeployment",
+                    class: md.class.clone(),
+                })?;
+            let class_path = field::index("spec.workers.machineDeployments", j);
+            let bootstrap_template = self.template(
+                &md_class.template.bootstrap.r#ref,
+                &field::child(&class_path, "template.bootstrap.ref"),
+            )?;
+            let infrastructure_template = self.template(
+                &md_class.template.infrastructure.r#ref,
+                &field::child(&class_path, "template.infrastructure.ref"),
+            )?;
+            let current = self.find_labeled(
+                "MachineDeployment",
+                labels::CLUSTER_TOPOLOGY_MACHINE_DEPLOYMENT_NAME,
+                &md.name,
+            );
+            let name = match current {
+                Some(current) => object_name(current),
+                None => self.name(
+                    NameGenerator::machine_deployment(
+                        md_class
+                            .naming_strategy
+                            .as_ref()
+                            .and_then(|strategy| strategy.template.as_deref()),
+                        &self.cluster_name,
+                        &md.name,
+                    ),
+                    &field::child(&class_path, "namingStrategy.template"),
+                    &format!("machineDeployment/{}", md.name),
+                )?,
+            };
+            let prefix = format!("{}-{}-", self.cluster_name, md.name);
+            // `pointer` is the JSON pointer of the reference in the Machine template spec
+            let template_ref = |pointer: &str, template: &Value, role: &str| {
+                let name = current
+                    .and_then(|current| current.pointer(&format!("/spec/template/spec{pointer}")))
+                    .and_then(|reference| self.find_ref_value(reference))
+                    .map(object_name)
+                    .unwrap_or_else(|| {
+                        self.generate_name(
+                            &prefix,
+                            &format!("machineDeployment/{}/{role}", md.name),
+                        )
+                    });
+                self.reference(template, &name)
+            };
+            let bootstrap_ref =
+                template_ref("/bootstrap/configRef", &bootstrap_template, "bootstrap");
+            let infrastructure_ref = template_ref(
+                "/infrastructureRef",
+                &infrastructure_template,
+                "infrastructure",
+            );
+
+            let mut labels = self.base_labels();
+            labels.insert(
+                labels::CLUSTER_TOPOLOGY_MACHINE_DEPLOYMENT_NAME.to_string(),
+                md.name.clone().into(),
+            );
+            let metadata = [to_json(&md_class.template.metadata), to_json(&md.metadata)];
+            let mut machine_template_spec = json!({
+                "clusterName": self.cluster_name,
+                "version": topology.version,
+                "bootstrap": { "configRef": bootstrap_ref },
+                "infrastructureRef": infrastructure_ref,
+            });
+            let machine_spec = object_at(&mut machine_template_spec, &[]);
+            set(
+                machine_spec,
+                "failureDomain",
+                first(&md.failure_domain, &md_class.failure_domain),
+            );
+            set(
+                machine_spec,
+                "nodeDrainTimeout",
+                first(&md.node_drain_timeout, &md_class.node_drain_timeout),
+            );
+            set(
+                machine_spec,
+                "nodeVolumeDetachTimeout",
+                first(
+                    &md.node_volume_detach_timeout,
+                    &md_class.node_volume_detach_timeout,
+                ),
+            );
+            set(
+                machine_spec,
+                "nodeDeletionTimeout",
+                first(&md.node_deletion_timeout, &md_class.node_deletion_timeout),
+            );
+            set(
+                machine_spec,
+                "readinessGates",
+                first(&md.readiness_gates, &md_class.readiness_gates),
+            );
+            let mut object = json!({
+                "apiVersion": CLUSTER_API_VERSION,
+                "kind": "MachineDeployment",
+                "metadata": self.metadata(&name, labels.clone(), &metadata),
+                "spec": {
+                    "clusterName": self.cluster_name,
+                    "selector": { "matchLabels": labels },
+                    "template": {
+                        "metadata": metadata_maps(labels.clone(), &metadata),
+                        "spec": machine_template_spec,
+                    },
+                },
+            });
+            let object_spec = object_at(&mut object, &["spec"]);
+            set(object_spec, "replicas", md.replicas);
+            set(
+                object_spec,
+                "minReadySeconds",
+                first(&md.min_ready_seconds, &md_class.min_ready_seconds),
+            );
+            set(
+                object_spec,
+                "strategy",
+                first(&md.strategy, &md_class.strategy),
+            );
+
+            let typed: MachineDeployment =
+                serde_json::from_value(object.clone()).map_err(|err| PlanError::Object {
+                    id: ObjectId::of(&object),
+                    message: err.to_string(),
+                })?;
+            let builtins = Builtins::for_machine_deployment(
+                md,
+                &typed,
+                Some(
+                    &json!({ "metadata": { "name": object["spec"]["template"]["spec"]["bootstrap"]["configRef"]["name"] } }),
+                ),
+                Some(
+                    &json!({ "metadata": { "name": object["spec"]["template"]["spec"]["infrastructureRef"]["name"] } }),
+                ),
+            );
+            let overrides = md
+                .variables
+                .as_ref()
+                .and_then(|variables| variables.overrides.as_deref())
+                .unwrap_or_default();
+            let variables =
+                merge_variables([&override_variables(overrides), &builtins.to_variables()]);
+            targets.push(
+                PatchTarget::new(
+                    TemplateHolder::MachineDeploymentBootstrap,
+                    bootstrap_template,
+                )
+                .with_variables(variables.clone()),
+            );
+            targets.push(
+                PatchTarget::new(
+                    TemplateHolder::MachineDeploymentInfrastructure,
+                    infrastructure_template,
+                )
+                .with_variables(variables),
+            );
+            machine_deployments.push((
+                md,
+                md_class,
+                Workers {
+                    object,
+                    bootstrap: targets.len() - 2,
+                    infrastructure: targets.len() - 1,
+                    current,
+                    prefix,
+                    topology_name: md.name.clone(),
+                },
+            ));
+        }
+
+        let mut machine_pools = Vec::new();
+        for (i, mp) in workers
+            .and_then(|workers| workers.machine_pools.as_ref())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let (j, mp_class) = class_workers
+                .and_then(|workers| workers.machine_pools.as_ref())
+                .and_then(|classes| {
+                    classes
+                        .iter()
+                        .enumerate()
+                        .find(|(_, c)| c.class == mp.class)
+                })
+                .ok_or_else(|| PlanError::ClassNotFound {
+                    path: field::child(
+                        &field::index("spec.topology.workers.machinePools", i),
+                        "class",
+                    ),
+                    kind: "MachinePool",
+                    class: mp.class.clone(),
+                })?;
+            let class_path = field::index("spec.workers.machinePools", j);
+            let bootstrap_template = self.template(
+                &mp_class.template.bootstrap.r#ref,
+                &field::child(&class_path, "template.bootstrap.ref"),
+            )?;
+            let infrastructure_template = self.template(
+                &mp_class.template.infrastructure.r#ref,
+                &field::child(&class_path, "template.infrastructure.ref"),
+            )?;
+            let current = self.find_labeled(
+                "MachinePool",
+                labels::CLUSTER_TOPOLOGY_MACHINE_POOL_NAME,
+                &mp.name,
+            );
+            let name = match current {
+                Some(current) => object_name(current),
+                None => self.name(
+                    NameGenerator::machine_pool(
+                        mp_class
+                            .naming_strategy
+                            .as_ref()
+                            .and_then(|strategy| strategy.template.as_deref()),
+                        &self.cluster_name,
+                        &mp.name,
+                    ),
+                    &field::child(&class_path, "namingStrategy.template"),
+                    &format!("machinePool/{}", mp.name),
+                )?,
+            };
+            let prefix = format!("{}-{}-", self.cluster_name, mp.name);
+            // the bootstrap config and InfrastructureMachinePool are objects, not templates
+            let object_ref = |pointer: &str, template: &Value, role: &str| {
+                let name = current
+                    .and_then(|current| current.pointer(&format!("/spec/template/spec{pointer}")))
+                    .and_then(|reference| self.find_ref_value(reference))
+                    .map(object_name)
+                    .unwrap_or_else(|| {
+                        self.generate_name(&prefix, &format!("machinePool/{}/{role}", mp.name))
+                    });
+                let mut reference = self.reference(template, &name);
+                reference["kind"] = object_kind(template).into();
+                reference
+            };
+            let bootstrap_ref =
+                object_ref("/bootstrap/configRef", &bootstrap_template, "bootstrap");
+            let infrastructure_ref = object_ref(
+                "/infrastructureRef",
+                &infrastructure_template,
+                "infrastructure",
+            );
+
+            let mut labels = self.base_labels();
+            labels.insert(
+                labels::CLUSTER_TOPOLOGY_MACHINE_POOL_NAME.to_string(),
+                mp.name.clone().into(),
+            );
+            let metadata = [to_json(&mp_class.template.metadata), to_json(&mp.metadata)];
+            let mut machine_template_spec = json!({
+                "clusterName": self.cluster_name,
+                "version": topology.version,
+                "bootstrap": { "configRef": bootstrap_ref },
+                "infrastructureRef": infrastructure_ref,
+            });
+            let machine_spec = object_at(&mut machine_template_spec, &[]);
+            set(
+                machine_spec,
+                "nodeDrainTimeout",
+                first(&mp.node_drain_timeout, &mp_class.node_drain_timeout),
+            );
+            set(
+                machine_spec,
+                "nodeVolumeDetachTimeout",
+                first(
+                    &mp.node_volume_detach_timeout,
+                    &mp_class.node_volume_detach_timeout,
+                ),
+            );
+            set(
+                machine_spec,
+                "nodeDeletionTimeout",
+                first(&mp.node_deletion_timeout, &mp_class.node_deletion_timeout),
+            );
+            let mut object = json!({
+                "apiVersion": CLUSTER_API_VERSION,
+                "kind": "MachinePool",
+                "metadata": self.metadata(&name, labels.clone(), &metadata),
+                "spec": {
+                    "clusterName": self.cluster_name,
+                    "template": {
+                        "metadata": metadata_maps(labels, &metadata),
+                        "spec": machine_template_spec,
+                    },
+                },
+            });
+            let object_spec = object_at(&mut object, &["spec"]);
+            set(object_spec, "replicas", mp.replicas);
+            set(
+                object_spec,
+                "minReadySeconds",
+                first(&mp.min_ready_seconds, &mp_class.min_ready_seconds),
+            );
+            set(
+                object_spec,
+                "failureDomains",
+                first(&mp.failure_domains, &mp_class.failure_domains),
+            );
+
+            let typed: MachinePool =
+                serde_json::from_value(object.clone()).map_err(|err| PlanError::Object {
+                    id: ObjectId::of(&object),
+                    message: err.to_string(),
+                })?;
+            let builtins = Builtins::for_machine_pool(
+                mp,
+                &typed,
+                Some(
+                    &json!({ "metadata": { "name": object["spec"]["template"]["spec"]["bootstrap"]["configRef"]["name"] } }),
+                ),
+                Some(
+                    &json!({ "metadata": { "name": object["spec"]["template"]["spec"]["infrastructureRef"]["name"] } }),
+                ),
+            );
+            let overrides = mp
+                .variables
+                .as_ref()
+                .and_then(|variables| variables.overrides.as_deref())
+                .unwrap_or_default();
+            let variables =
+                merge_variables([&override_variables(overrides), &builtins.to_variables()]);
+            targets.push(
+                PatchTarget::new(TemplateHolder::MachinePoolBootstrap, bootstrap_template)
+                    .with_variables(variables.clone()),
+            );
+            targets.push(
+                PatchTarget::new(
+                    TemplateHolder::MachinePoolInfrastructure,
+                    infrastructure_template,
+                )
+                .with_variables(variables),
+            );
+            machine_pools.push(Workers {
+                object,
+                bootstrap: targets.len() - 2,
+                infrastructure: targets.len() - 1,
+                current,
+                prefix,
+                topology_name: mp.name.clone(),
+            });
+        }
+
+        apply_patches(self.class, &global, &mut targets)
+            .map_err(|err| PlanError::Patch(Box::new(err)))?;
+
+        let infrastructure_cluster =
+            self.object_from_template(&targets[0].template, &infrastructure_name);
+        let mut control_plane_object =
+            self.object_from_template(&targets[control_plane_target].template, &control_plane_name);
+        let mut control_plane_objects = Vec::new();
+        {
+            let cp_spec = object_at(&mut control_plane_object, &["spec"]);
+            cp_spec.insert("version".to_string(), topology.version.clone().into());
+            set(cp_spec, "replicas", replicas);
+        }
+        let machine_template = object_at(&mut control_plane_object, &["spec", "machineTemplate"]);
+        if let Some(index) = machine_infrastructure_target {
+            let mut template =
+                self.template_copy(&targets[index].template, &machine_infrastructure_name);
+            self.rotate(
+                &mut template,
+                current_machine_infrastructure,
+                &format!("{}-", self.cluster_name),
+                "controlPlane/machineInfrastructure",
+            );
+            machine_template.insert(
+                "infrastructureRef".to_string(),
+                self.reference(&template, &object_name(&template)),
+            );
+            control_plane_objects.push(template);
+        }
+        let mut machine_labels = Map::new();
+        machine_labels.insert(labels::CLUSTER_TOPOLOGY_OWNED.to_string(), "".into());
+        let metadata = metadata_maps(
+            machine_labels,
+            &[
+                to_json(&control_plane.metadata),
+                to_json(&topology_control_plane.and_then(|cp| cp.metadata.as_ref())),
+            ],
+        );
+        machine_template.insert("metadata".to_string(), metadata);
+        set(
+            machine_template,
+            "nodeDrainTimeout",
+            first(
+                &topology_control_plane.and_then(|cp| cp.node_drain_timeout),
+                &control_plane.node_drain_timeout,
+            ),
+        );
+        set(
+            machine_template,
+            "nodeVolumeDetachTimeout",
+            first(
+                &topology_control_plane.and_then(|cp| cp.node_volume_detach_timeout),
+                &control_plane.node_volume_detach_timeout,
+            ),
+        );
+        set(
+            machine_template,
+            "nodeDeletionTimeout",
+            first(
+                &topology_control_plane.and_then(|cp| cp.node_deletion_timeout),
+                &control_plane.node_deletion_timeout,
+            ),
+        );
+        set(
+            machine_template,
+            "readinessGates",
+            first(
+                &topology_control_plane.and_then(|cp| cp.readiness_gates.clone()),
+                &control_plane.readiness_gates,
+            ),
+        );
+        let control_plane_health_check = self.machine_health_check(
+            &control_plane_name,
+            json!({
+                labels::CLUSTER_TOPOLOGY_OWNED: "",
+                labels::MACHINE_CONTROL_PLANE: "",
+            }),
+            to_json(&control_plane.machine_health_check),
+            to_json(&topology_control_plane.and_then(|cp| cp.machine_health_check.as_ref())),
+        );
+
+        let mut cluster = serde_json::to_value(self.cluster).unwrap_or_default();
+        let cluster_spec = object_at(&mut cluster, &["spec"]);
+        cluster_spec.insert(
+            "infrastructureRef".to_string(),
+            self.reference(&infrastructure_cluster, &infrastructure_name),
+        );
+        cluster_spec.insert(
+            "controlPlaneRef".to_string(),
+            self.reference(&control_plane_object, &control_plane_name),
+        );
+
+        let mut desired = vec![cluster, infrastructure_cluster];
+        desired.extend(control_plane_objects);
+        desired.push(control_plane_object);
+        desired.extend(control_plane_health_check);
+
+        for (md, md_class, workers) in machine_deployments {
+            let mut object = workers.object;
+            let spec = &mut object["spec"]["template"]["spec"];
+            let mut bootstrap = self.template_copy(
+                &targets[workers.bootstrap].template,
+                spec["bootstrap"]["configRef"]["name"]
+                    .as_str()
+                    .unwrap_or_default(),
+            );
+            let current_bootstrap = workers.current.and_then(|c| {
+                self.find_ref_value(&c["spec"]["template"]["spec"]["bootstrap"]["configRef"])
+            });
+            let seed = format!("machineDeployment/{}", workers.topology_name);
+            self.rotate(
+                &mut bootstrap,
+                current_bootstrap,
+                &workers.prefix,
+                &format!("{seed}/bootstrap"),
+            );
+            spec["bootstrap"]["configRef"]["name"] = object_name(&bootstrap).into();
+            let mut infrastructure = self.template_copy(
+                &targets[workers.infrastructure].template,
+                spec["infrastructureRef"]["name"]
+                    .as_str()
+                    .unwrap_or_default(),
+            );
+            let current_infrastructure = workers.current.and_then(|c| {
+                self.find_ref_value(&c["spec"]["template"]["spec"]["infrastructureRef"])
+            });
+            self.rotate(
+                &mut infrastructure,
+                current_infrastructure,
+                &workers.prefix,
+                &format!("{seed}/infrastructure"),
+            );
+            spec["infrastructureRef"]["name"] = object_name(&infrastructure).into();
+            let health_check = self.machine_health_check(
+                &object_name(&object),
+                json!({
+                    labels::CLUSTER_TOPOLOGY_OWNED: "",
+                    labels::CLUSTER_TOPOLOGY_MACHINE_DEPLOYMENT_NAME: md.name,
+                }),
+                to_json(&md_class.machine_health_check),
+                to_json(&md.machine_health_check),
+            );
+            desired.push(bootstrap);
+            desired.push(infrastructure);
+            desired.push(object);
+            desired.extend(health_check);
+        }
+        for workers in machine_pools {
+            let object = workers.object;
+            let spec = &object["spec"]["template"]["spec"];
+            desired.push(
+                self.object_from_template(
+                    &targets[workers.bootstrap].template,
+                    spec["bootstrap"]["configRef"]["name"]
+                        .as_str()
+                        .unwrap_or_default(),
+                ),
+            );
+            desired.push(
+                self.object_from_template(
+                    &targets[workers.infrastructure].template,
+                    spec["infrastructureRef"]["name"]
+                        .as_str()
+                        .unwrap_or_default(),
+                ),
+            );
+            desired.push(object);
+        }
+        Ok(desired)
+    }
+
+    /// Looks up the template a ClusterClass refers to at `path`.
+    fn template(&self, reference: &ObjectReference, path: &str) -> Result<Value, PlanError> {
+        let kind = reference.kind.clone().unwrap_or_default();
+        let name = reference.name.clone().unwrap_or_default();
+        let group = api_group(reference.api_version.as_deref().unwrap_or_default());
+        let namespace = reference
+            .namespace
+            .clone()
+            .or_else(|| self.class.metadata.namespace.clone())
+            .unwrap_or_else(|| self.namespace.clone());
+        self.templates
+            .iter()
+            .find(|template| {
+                let id = ObjectId::of(template);
+                api_group(&id.api_version) == group
+                    && id.kind == kind
+                    && id.name == name
+                    && (id.namespace.is_empty() || id.namespace == namespace)
+            })
+            .cloned()
+            .ok_or_else(|| PlanError::TemplateNotFound {
+                path: path.to_string(),
+                kind,
+                name,
+            })
+    }
+
+    /// Finds a current object in the namespace of the Cluster.
+    fn find(&self, kind: &str, name: &str) -> Option<&'a Value> {
+        self.current.iter().find(|object| {
+            let id = ObjectId::of(object);
+            id.kind == kind
+                && id.name == name
+                && (id.namespace.is_empty() || id.namespace == self.namespace)
+        })
+    }
+
+    fn find_reference(&self, reference: Option<&ObjectReference>) -> Option<&'a Value> {
+        let reference = reference?;
+        self.find(reference.kind.as_deref()?, reference.name.as_deref()?)
+    }
+
+    /// Finds the current object an unstructured reference refers to.
+    fn find_ref_value(&self, reference: &Value) -> Option<&'a Value> {
+        self.find(reference["kind"].as_str()?, reference["name"].as_str()?)
+    }
+
+    /// Finds a current Cluster API object of the Cluster by a topology label.
+    fn find_labeled(&self, kind: &str, label: &str, value: &str) -> Option<&'a Value> {
+        self.current.iter().find(|object| {
+            let id = ObjectId::of(object);
+            let labels = &object["metadata"]["labels"];
+            api_group(&id.api_version) == api_group(CLUSTER_API_VERSION)
+                && id.kind == kind
+                && (id.namespace.is_empty() || id.namespace == self.namespace)
+                && labels[labels::CLUSTER_NAME].as_str() == Some(&self.cluster_name)
+                && labels[label].as_str() == Some(value)
+        })
+    }
+
+    /// Random strings derived from the Cluster and the seed.
+    fn random(&self, seed: &str) -> impl FnMut() -> String {
+        seeded_random(format!("{}/{}/{seed}", self.namespace, self.cluster_name))
+    }
+
+    /// Generates the name of an object from its naming strategy.
+    fn name(&self, generator: NameGenerator, path: &str, seed: &str) -> Result<String, PlanError> {
+        generator
+            .generate_with(self.random(seed))
+            .map_err(|source| PlanError::Name {
+                path: path.to_string(),
+                source: Box::new(source),
+            })
+    }
+
+    /// Generates a name like Kubernetes' `generateName`, e.g. for a cloned template.
+    fn generate_name(&self, prefix: &str, seed: &str) -> String {
+        simple_name(prefix, &self.random(seed)())
+    }
+
+    /// Gives a template a new name if its spec differs from the current template, as the
+    /// controller rotates templates to roll out changes.
+    fn rotate(&self, template: &mut Value, current: Option<&Value>, prefix: &str, seed: &str) {
+        let Some(current) = current else {
+            return;
+        };
+        let mut changed = Vec::new();
+        changed_fields(&template["spec"], &current["spec"], "spec", &mut changed);
+        if !changed.is_empty() {
+            let seed = format!("{seed}/{}", template["spec"]);
+            template["metadata"]["name"] = self.generate_name(prefix, &seed).into();
+        }
+    }
+
+    fn base_labels(&self) -> Map<String, Value> {
+        let mut labels = Map::new();
+        labels.insert(
+            labels::CLUSTER_NAME.to_string(),
+            self.cluster_name.clone().into(),
+        );
+        labels.insert(labels::CLUSTER_TOPOLOGY_OWNED.to_string(), "".into());
+        labels
+    }
+
+    /// Metadata of a generated object with the labels and annotations of the given metadata.
+    fn metadata(&self, name: &str, labels: Map<String, Value>, metadata: &[Value]) -> Value {
+        let mut maps = metadata_maps(labels, metadata);
+        maps["name"] = name.into();
+        maps["namespace"] = self.namespace.clone().into();
+        maps
+    }
+
+    /// Clones a template into the object it is a template for, e.g. a `DockerCluster` from a
+    /// `DockerClusterTemplate`.
+    fn object_from_template(&self, template: &Value, name: &str) -> Value {
+        let metadata = &template["spec"]["template"]["metadata"];
+        let mut metadata = self.metadata(name, self.base_labels(), std::slice::from_ref(metadata));
+        cloned_from(&mut metadata, template);
+        let spec = match &template["spec"]["template"]["spec"] {
+            Value::Null => json!({}),
+            spec => spec.clone(),
+        };
+        json!({
+            "apiVersion": template["apiVersion"],
+            "kind": object_kind(template),
+            "metadata": metadata,
+            "spec": spec,
+        })
+    }
+
+    /// Clones a template, e.g. the InfrastructureMachineTemplate of a MachineDeployment.
+    fn template_copy(&self, template: &Value, name: &str) -> Value {
+        let mut metadata = self.metadata(
+            name,
+            self.base_labels(),
+            std::slice::from_ref(&template["metadata"]),
+        );
+        cloned_from(&mut metadata, template);
+        json!({
+            "apiVersion": template["apiVersion"],
+            "kind": template["kind"],
+            "metadata": metadata,
+            "spec": template["spec"],
+        })
+    }
+
+    /// A reference to an object of the same apiVersion and kind as the given one.
+    fn reference(&self, object: &Value, name: &str) -> Value {
+        json!({
+            "apiVersion": object["apiVersion"],
+            "kind": object["kind"],
+            "name": name,
+            "namespace": self.namespace,
+        })
+    }
+
+    /// The MachineHealthCheck of the control plane or a MachineDeployment, if enabled. The
+    /// checks of the topology replace the ones of the ClusterClass.
+    fn machine_health_check(
+        &self,
+        name: &str,
+        match_labels: Value,
+        class: Value,
+        topology: Value,
+    ) -> Option<Value> {
+        if topology["enable"] == Value::Bool(false) {
+            return None;
+        }
+        let mut topology = topology;
+        if let Some(topology) = topology.as_object_mut() {
+            topology.remove("enable");
+        }
+        let checks = match topology {
+            Value::Object(checks) if !checks.is_empty() => checks,
+            _ => match class {
+                Value::Object(checks) => checks,
+                _ => return None,
+            },
+        };
+        let mut spec = json!({
+            "clusterName": self.cluster_name,
+            "selector": { "matchLabels": match_labels },
+        });
+        object_at(&mut spec, &[]).extend(checks);
+        Some(json!({
+            "apiVersion": CLUSTER_API_VERSION,
+            "kind": "MachineHealthCheck",
+            "metadata": self.metadata(name, self.base_labels(), &[]),
+            "spec": spec,
+        }))
+    }
+
+    fn diff(&self, desired: Vec<Value>, original_cluster: &Value) -> TopologyPlan {
+        let mut plan = TopologyPlan::default();
+        let mut matched = vec![false; self.current.len()];
+        let cluster_id = ObjectId::of(original_cluster);
+        for object in &desired {
+            let id = ObjectId::of(object);
+            let current = if id.same_object(&cluster_id) {
+                Some(original_cluster)
+            } else {
+                self.current
+                    .iter()
+                    .enumerate()
+                    .find(|(_, current)| ObjectId::of(current).same_object(&id))
+                    .map(|(i, current)| {
+                        matched[i] = true;
+                        current
+                    })
+            };
+            match current {
+                None => plan.created.push(id),
+                Some(current) => {
+                    let mut fields = Vec::new();
+                    changed_fields(object, current, "", &mut fields);
+                    if !fields.is_empty() {
+                        plan.modified.push(ModifiedObject { id, fields });
+                    }
+                }
+            }
+        }
+        for (object, matched) in self.current.iter().zip(matched) {
+            let labels = &object["metadata"]["labels"];
+            let owned = labels[labels::CLUSTER_NAME].as_str() == Some(&self.cluster_name)
+                && labels.get(labels::CLUSTER_TOPOLOGY_OWNED).is_some();
+            if !matched && owned {
+                plan.deleted.push(ObjectId::of(object));
+            }
+        }
+        plan.desired = desired;
+        plan
+    }
+}
+
+fn object_name(object: &Value) -> String {
+    object["metadata"]["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The kind of the objects a template is for, e.g. `DockerMachine` for `DockerMachineTemplate`.
+fn object_kind(template: &Value) -> String {
+    let kind = template["kind"].as_str().unwrap_or_default();
+    kind.strip_suffix("Template").unwrap_or(kind).to_string()
+}
+
+/// Records the template an object was cloned from in its annotations.
+fn cloned_from(metadata: &mut Value, template: &Value) {
+    let group = api_group(template["apiVersion"].as_str().unwrap_or_default());
+    let kind = template["kind"].as_str().unwrap_or_default();
+    let annotations = object_at(metadata, &["annotations"]);
+    annotations.insert(
+        annotations::TEMPLATE_CLONED_FROM_NAME.to_string(),
+        template["metadata"]["name"].clone(),
+    );
+    annotations.insert(
+        annotations::TEMPLATE_CLONED_FROM_GROUP_KIND.to_string(),
+        format!("{kind}.{group}").into(),
+    );
+}
+
+/// Labels and annotations merged from metadata, later ones win, over the given labels.
+fn metadata_maps(labels: Map<String, Value>, metadata: &[Value]) -> Value {
+    let mut merged_labels = Map::new();
+    let mut merged_annotations = Map::new();
+    for metadata in metadata {
+        if let Some(labels) = metadata["labels"].as_object() {
+            merged_labels.extend(labels.clone());
+        }
+        if let Some(annotations) = metadata["annotations"].as_object() {
+            merged_annotations.extend(annotations.clone());
+        }
+    }
+    merged_labels.extend(labels);
+    let mut maps = json!({ "labels": merged_labels });
+    if !merged_annotations.is_empty() {
+        maps["annotations"] = merged_annotations.into();
+    }
+    maps
+}
+
+/// The object at a path, replacing what is not an object on the way.
+fn object_at<'v>(value: &'v mut Value, path: &[&str]) -> &'v mut Map<String, Value> {
+    let mut value = value;
+    for key in path {
+        if !value.is_object() {
+            *value = json!({});
+        }
+        value = value
+            .as_object_mut()
+            .expect("value is an object")
+            .entry(key.to_string())
+            .or_insert_with(|| json!({}));
+    }
+    if !value.is_object() {
+        *value = json!({});
+    }
+    value.as_object_mut().expect("value is an object")
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// The topology value if set, else the ClusterClass one. The types of both are generated
+/// separately, so they are compared as JSON.
+fn first<T: Serialize, C: Serialize>(topology: &Option<T>, class: &Option<C>) -> Option<Value> {
+    match (topology, class) {
+        (Some(topology), _) => Some(to_json(topology)),
+        (None, Some(class)) => Some(to_json(class)),
+        (None, None) => None,
+    }
+}
+
+fn set<T: Serialize>(object: &mut Map<String, Value>, key: &str, value: Option<T>) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), to_json(&value));
+    }
+}
+
+/// Collects the paths of the fields of `desired` that are missing from or different in
+/// `current`. Fields only set in `current`, e.g. defaults and status, are ignored.
+fn changed_fields(desired: &Value, current: &Value, path: &str, changed: &mut Vec<String>) {
+    match (desired, current) {
+        (Value::Object(desired), Value::Object(current)) => {
+            for (key, value) in desired {
+                let path = field::child(path, key);
+                match current.get(key) {
+                    Some(current) => changed_fields(value, current, &path, changed),
+                    None if value.is_null() => {}
+                    None => changed.push(path),
+                }
+            }
+        }
+        (desired, current) => {
+            if desired != current {
+                changed.push(path.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn template(api_version: &str, kind: &str, spec: Value) -> Value {
+        json!({
+            "apiVersion": api_version,
+            "kind": kind,
+            "metadata": {"name": "quick-start", "namespace": "ns"},
+            "spec": {"template": {"spec": spec}},
+        })
+    }
+
+    fn fixture() -> (Cluster, ClusterClass, Vec<Value>) {
+        let infrastructure = "infrastructure.cluster.x-k8s.io/v1beta1";
+        let bootstrap = "bootstrap.cluster.x-k8s.io/v1beta1";
+        let reference = |api_version: &str, kind: &str| json!({"ref": {"apiVersion": api_version, "kind": kind, "name": "quick-start"}});
+        let cluster = serde_json::from_value(json!({
+            "metadata": {"name": "c1", "namespace": "ns"},
+            "spec": {
+                "topology": {
+                    "class": "quick-start",
+                    "version": "v1.31.0",
+                    "controlPlane": {"replicas": 1},
+                    "workers": {
+                        "machineDeployments": [{"class": "default-worker", "name": "md-0"}],
+                        "machinePools": [{"class": "default-worker", "name": "mp-0"}],
+                    },
+                },
+            },
+        }))
+        .unwrap();
+        let class = serde_json::from_value(json!({
+            "metadata": {"name": "quick-start", "namespace": "ns"},
+            "spec": {
+                "infrastructure": reference(infrastructure, "DockerClusterTemplate"),
+                "controlPlane": {
+                    "ref": {
+                        "apiVersion": "controlplane.cluster.x-k8s.io/v1beta1",
+                        "kind": "KubeadmControlPlaneTemplate",
+                        "name": "quick-start",
+                    },
+                    "machineInfrastructure": reference(infrastructure, "DockerMachineTemplate"),
+                },
+                "workers": {
+                    "machineDeployments": [{
+                        "class": "default-worker",
+                        "template": {
+                            "bootstrap": reference(bootstrap, "KubeadmConfigTemplate"),
+                            "infrastructure": reference(infrastructure, "DockerMachineTemplate"),
+                        },
+                    }],
+                    "machinePools": [{
+                        "class": "default-worker",
+                        "template": {
+                            "bootstrap": reference(bootstrap, "KubeadmConfigTemplate"),
+                            "infrastructure": reference(infrastructure, "DockerMachinePoolTemplate"),
+                        },
+                    }],
+                },
+            },
+        }))
+        .unwrap();
+        let templates = vec![
+            template(infrastructure, "DockerClusterTemplate", json!({})),
+            template(
+                "controlplane.cluster.x-k8s.io/v1beta1",
+                "KubeadmControlPlaneTemplate",
+                json!({"kubeadmConfigSpec": {}}),
+            ),
+            template(
+                infrastructure,
+                "DockerMachineTemplate",
+                json!({"extraMounts": []}),
+            ),
+            template(
+                bootstrap,
+                "KubeadmConfigTemplate",
+                json!({"joinConfiguration": {}}),
+            ),
+            template(
+                infrastructure,
+                "DockerMachinePoolTemplate",
+                json!({"template": {}}),
+            ),
+        ];
+        (cluster, class, templates)
+    }
+
+    /// Replaces every string equal to a renamed name.
+    fn rename(value: &mut Value, names: &BTreeMap<String, String>) {
+        match value {
+            Value::String(s) => {
+                if let Some(name) = names.get(s.as_str()) {
+                    *s = name.clone();
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| rename(item, names)),
+            Value::Object(map) => map.values_mut().for_each(|item| rename(item, names)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn plans_a_new_cluster() {
+        let (cluster, class, templates) = fixture();
+        let plan = plan_topology(&cluster, &class, &templates, &[]).unwrap();
+        let kinds: Vec<_> = plan.created.iter().map(|id| id.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "DockerCluster",
+                "DockerMachineTemplate",
+                "KubeadmControlPlane",
+                "KubeadmConfigTemplate",
+                "DockerMachineTemplate",
+                "MachineDeployment",
+                "KubeadmConfig",
+                "DockerMachinePool",
+                "MachinePool",
+            ]
+        );
+        assert_eq!(
+            plan.modified,
+            [ModifiedObject {
+                id: ObjectId::of(&plan.desired[0]),
+                fields: vec![
+                    "spec.controlPlaneRef".to_string(),
+                    "spec.infrastructureRef".to_string(),
+                ],
+            }]
+        );
+        assert!(plan.deleted.is_empty());
+    }
+
+    #[test]
+    fn replans_objects_with_names_chosen_by_the_controller() {
+        let (cluster, class, templates) = fixture();
+        let plan = plan_topology(&cluster, &class, &templates, &[]).unwrap();
+        // the controller generates other random suffixes than the plan
+        let names: BTreeMap<String, String> = plan.desired[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, object)| (object_name(object), format!("chosen-{i}")))
+            .collect();
+        let mut current = plan.desired.clone();
+        current.iter_mut().for_each(|object| rename(object, &names));
+        let cluster: Cluster = serde_json::from_value(current[0].clone()).unwrap();
+
+        let replan = plan_topology(&cluster, &class, &templates, &current[1..]).unwrap();
+        assert_eq!(replan.created, []);
+        assert_eq!(replan.modified, []);
+        assert_eq!(replan.deleted, []);
+        assert_eq!(replan.desired, current);
+    }
+}
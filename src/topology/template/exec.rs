@@ -0,0 +1,514 @@
+//! Executor for parsed templates, following `text/template/exec.go`.
+
+use super::funcs::{self, FuncError};
+use super::parse::{Arg, Branch, Command, Node, Pipe};
+use super::sprig;
+use super::value::Value;
+use super::{Template, TemplateError};
+
+/// How control leaves a list of nodes.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+struct State<'a> {
+    tmpl: &'a Template,
+    vars: Vec<(String, Value)>,
+    out: String,
+}
+
+pub(super) fn execute(tmpl: &Template, data: &Value) -> Result<String, TemplateError> {
+    let mut state = State {
+        tmpl,
+        vars: vec![("$".to_string(), data.clone())],
+        out: String::new(),
+    };
+    state.walk(data, &tmpl.root)?;
+    Ok(state.out)
+}
+
+impl State<'_> {
+    fn error(
+        &self,
+        pos: usize,
+        context: impl ToString,
+        message: impl Into<String>,
+    ) -> TemplateError {
+        TemplateError::exec(
+            &self.tmpl.name,
+            &self.tmpl.source,
+            pos,
+            context.to_string(),
+            message,
+        )
+    }
+
+    fn walk(&mut self, dot: &Value, list: &[Node]) -> Result<Flow, TemplateError> {
+        for node in list {
+            let flow = match node {
+                Node::Text(text) => {
+                    self.out.push_str(text);
+                    Flow::Normal
+                }
+                Node::Action(pipe) => {
+                    let value = self.eval_pipeline(dot, pipe)?;
+                    if pipe.decl.is_empty() {
+                        self.out.push_str(&value.to_string());
+                    }
+                    Flow::Normal
+                }
+                Node::If(branch) => self.walk_if_or_with(dot, branch, false)?,
+                Node::With(branch) => self.walk_if_or_with(dot, branch, true)?,
+                Node::Range(branch) => self.walk_range(dot, branch)?,
+                Node::Break(_) => Flow::Break,
+                Node::Continue(_) => Flow::Continue,
+            };
+            if !matches!(flow, Flow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn walk_if_or_with(
+        &mut self,
+        dot: &Value,
+        branch: &Branch,
+        with: bool,
+    ) -> Result<Flow, TemplateError> {
+        let mark = self.vars.len();
+        let value = self.eval_pipeline(dot, &branch.pipe)?;
+        let flow = if value.is_true() {
+            if with {
+                self.walk(&value, &branch.list)?
+            } else {
+                self.walk(dot, &branch.list)?
+            }
+        } else if let Some(else_list) = &branch.else_list {
+            self.walk(dot, else_list)?
+        } else {
+            Flow::Normal
+        };
+        self.vars.truncate(mark);
+        Ok(flow)
+    }
+
+    fn walk_range(&mut self, dot: &Value, branch: &Branch) -> Result<Flow, TemplateError> {
+        let mark = self.vars.len();
+        let pipe = Pipe {
+            decl: Vec::new(),
+            ..branch.pipe.clone()
+        };
+        let value = self.eval_pipeline(dot, &pipe)?;
+        let items: Vec<(Value, Value)> = match value {
+            Value::Missing | Value::Nil => Vec::new(),
+            Value::List(list) => list
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| (Value::Int(i as i64), v))
+                .collect(),
+            Value::Map(map) => map
+                .into_iter()
+                .map(|(k, v)| (Value::String(k), v))
+                .collect(),
+            Value::Int(n) => {
+                if branch.pipe.decl.len() > 1 {
+                    return Err(self.error(
+                        branch.pipe.pos,
+                        &branch.pipe,
+                        format!("can't use {n} to iterate over more than one variable"),
+                    ));
+                }
+                (0..n.max(0))
+                    .map(|i| (Value::Int(i), Value::Int(i)))
+                    .collect()
+            }
+            value => {
+                return Err(self.error(
+                    branch.pipe.pos,
+                    &branch.pipe,
+                    format!("range can't iterate over {value}"),
+                ))
+            }
+        };
+        if items.is_empty() {
+            let flow = match &branch.else_list {
+                Some(else_list) => self.walk(dot, else_list)?,
+                None => Flow::Normal,
+            };
+            self.vars.truncate(mark);
+            return Ok(flow);
+        }
+        for (key, elem) in items {
+            // a single variable is the element, two are the key and the element
+            match branch.pipe.decl.as_slice() {
+                [] => {}
+                [v] => self.declare(&branch.pipe, v, elem.clone()),
+                [k, v, ..] => {
+                    self.declare(&branch.pipe, k, key);
+                    self.declare(&branch.pipe, v, elem.clone());
+                }
+            }
+            let flow = self.walk(&elem, &branch.list)?;
+            self.vars.truncate(mark);
+            if let Flow::Break = flow {
+                break;
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn declare(&mut self, pipe: &Pipe, name: &str, value: Value) {
+        if pipe.is_assign {
+            self.set_var(name, value);
+        } else {
+            self.vars.push((name.to_string(), value));
+        }
+    }
+
+    fn set_var(&mut self, name: &str, value: Value) {
+        if let Some(var) = self.vars.iter_mut().rev().find(|(n, _)| n == name) {
+            var.1 = value;
+        }
+    }
+
+    fn var_value(&self, name: &str) -> Value {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default()
+    }
+
+    fn eval_pipeline(&mut self, dot: &Value, pipe: &Pipe) -> Result<Value, TemplateError> {
+        let mut value = None;
+        for cmd in &pipe.cmds {
+            value = Some(self.eval_command(dot, cmd, value)?);
+        }
+        let value = value.unwrap_or_default();
+        for name in &pipe.decl {
+            self.declare(pipe, name, value.clone());
+        }
+        Ok(value)
+    }
+
+    fn eval_command(
+        &mut self,
+        dot: &Value,
+        cmd: &Command,
+        last: Option<Value>,
+    ) -> Result<Value, TemplateError> {
+        let first = &cmd.args[0];
+        let has_args = cmd.args.len() > 1 || last.is_some();
+        match first {
+            Arg::Function(pos, name) => self.eval_function(dot, cmd, *pos, name, last),
+            Arg::Nil(pos) => Err(self.error(*pos, cmd, "nil is not a command")),
+            arg if has_args => Err(self.error(
+                arg.pos(),
+                cmd,
+                format!("can't give argument to non-function {arg}"),
+            )),
+            arg => self.eval_arg(dot, arg),
+        }
+    }
+
+    fn eval_arg(&mut self, dot: &Value, arg: &Arg) -> Result<Value, TemplateError> {
+        match arg {
+            Arg::Field(pos, fields) => self.eval_fields(*pos, arg, dot.clone(), fields),
+            Arg::Variable(pos, name, fields) => {
+                let value = self.var_value(name);
+                self.eval_fields(*pos, arg, value, fields)
+            }
+            Arg::Chain(pos, pipe, fields) => {
+                let value = self.eval_pipeline(dot, pipe)?;
+                self.eval_fields(*pos, arg, value, fields)
+            }
+            Arg::Dot(_) => Ok(dot.clone()),
+            Arg::Nil(_) => Ok(Value::Nil),
+            Arg::Constant(_, value, _) => Ok(value.clone()),
+            Arg::Function(pos, name) => {
+                let cmd = Command {
+                    pos: *pos,
+                    args: vec![arg.clone()],
+                };
+                self.eval_function(dot, &cmd, *pos, name, None)
+            }
+        }
+    }
+
+    fn eval_fields(
+        &self,
+        pos: usize,
+        arg: &Arg,
+        mut value: Value,
+        fields: &[String],
+    ) -> Result<Value, TemplateError> {
+        for field in fields {
+            value = match value {
+                Value::Map(mut map) => match map.remove(field) {
+                    Some(value) => value,
+                    None if self.tmpl.missing_key_error => {
+                        return Err(self.error(
+                            pos,
+                            arg,
+                            format!("map has no entry for key {field:?}"),
+                        ))
+                    }
+                    None => Value::Missing,
+                },
+                Value::Missing => Value::Missing,
+                Value::Nil => {
+                    return Err(self.error(
+                        pos,
+                        arg,
+                        format!("nil pointer evaluating interface {{}}.{field}"),
+                    ))
+                }
+                value => {
+                    return Err(self.error(
+                        pos,
+                        arg,
+                        format!("can't evaluate field {field} in type {}", value.type_name()),
+                    ))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn eval_function(
+        &mut self,
+        dot: &Value,
+        cmd: &Command,
+        pos: usize,
+        name: &str,
+        last: Option<Value>,
+    ) -> Result<Value, TemplateError> {
+        let args = &cmd.args[1..];
+        if name == "and" || name == "or" {
+            return self.eval_and_or(dot, cmd, pos, name, args, last);
+        }
+        let mut values = Vec::with_capacity(args.len() + 1);
+        for arg in args {
+            values.push(self.eval_arg(dot, arg)?);
+        }
+        values.extend(last);
+        if name != "eq" && name != "ne" {
+            // missing values are passed as nil to functions taking interfaces
+            for value in &mut values {
+                if let Value::Missing = value {
+                    *value = Value::Nil;
+                }
+            }
+        }
+        let func = funcs::lookup(name).expect("functions are checked when parsing");
+        let result = func(&values).map_err(|err| self.error(pos, cmd, err.message(name)))?;
+        if sprig::MUTATING.contains(&name) {
+            // sprig changes maps in place, e.g. `{{ $_ := set $d "k" "v" }}` updates $d
+            if let Some(Arg::Variable(_, var, fields)) = args.first() {
+                self.update_var(var, fields, result.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    fn update_var(&mut self, name: &str, fields: &[String], value: Value) {
+        let var = self.vars.iter_mut().rev().find(|(n, _)| n == name);
+        let Some(mut target) = var.map(|(_, v)| v) else {
+            return;
+        };
+        for field in fields {
+            match target {
+                Value::Map(map) => match map.get_mut(field) {
+                    Some(next) => target = next,
+                    None => return,
+                },
+                _ => return,
+            }
+        }
+        *target = value;
+    }
+
+    /// `and` and `or` evaluate their arguments lazily and return the deciding one.
+    fn eval_and_or(
+        &mut self,
+        dot: &Value,
+        cmd: &Command,
+        pos: usize,
+        name: &str,
+        args: &[Arg],
+        last: Option<Value>,
+    ) -> Result<Value, TemplateError> {
+        let count = args.len() + usize::from(last.is_some());
+        if count == 0 {
+            let err = FuncError::Arity {
+                want: "at least 1".to_string(),
+                got: 0,
+            };
+            return Err(self.error(pos, cmd, err.message(name)));
+        }
+        let mut value = Value::Missing;
+        for arg in args {
+            value = self.eval_arg(dot, arg)?;
+            if value.is_true() == (name == "or") {
+                return Ok(value);
+            }
+        }
+        Ok(last.unwrap_or(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::topology::template::{render, Template};
+
+    fn data() -> serde_json::Value {
+        json!({
+            "s": "str",
+            "n": 3,
+            "f": 1.5,
+            "t": true,
+            "null": null,
+            "l": ["a", "b", "c"],
+            "empty": [],
+            "m": {"b": 1, "a": 2},
+            "nested": {"k": {"v": "deep"}},
+        })
+    }
+
+    #[test]
+    fn renders() {
+        for (source, expected) in [
+            ("text only", "text only"),
+            ("{{ .s }}-{{ .n }}-{{ .f }}-{{ .t }}", "str-3-1.5-true"),
+            ("{{ .null }}", "<nil>"),
+            ("{{ .missing }}", "<no value>"),
+            ("{{ .missing.deeper }}", "<no value>"),
+            ("{{ .nested.k.v }}", "deep"),
+            ("{{ .l }} {{ .m }}", "[a b c] map[a:2 b:1]"),
+            ("{{ . | len }}", "9"),
+            (r#"{{ "a" | printf "%s-%s" "b" }}"#, "b-a"),
+            (r#"{{ (index .nested "k").v }}"#, "deep"),
+            ("{{ $x := .s }}{{ $x }}", "str"),
+            (
+                "{{ $x := 1 }}{{ if true }}{{ $x = 2 }}{{ end }}{{ $x }}",
+                "2",
+            ),
+            ("{{ with .s }}{{ . }}{{ end }}", "str"),
+            ("{{ with .missing }}{{ . }}{{ else }}none{{ end }}", "none"),
+            ("{{ with $v := .n }}{{ $v }}{{ end }}", "3"),
+            ("{{ if .t }}yes{{ else }}no{{ end }}", "yes"),
+            ("{{ if .empty }}yes{{ else }}no{{ end }}", "no"),
+            (
+                "{{ if .missing }}1{{ else if eq .s \"str\" }}2{{ else }}3{{ end }}",
+                "2",
+            ),
+            (
+                "{{ with .missing }}1{{ else with .s }}{{ . }}{{ end }}",
+                "str",
+            ),
+            ("{{ range .l }}{{ . }}{{ end }}", "abc"),
+            (
+                "{{ range $i, $v := .l }}{{ $i }}={{ $v }},{{ end }}",
+                "0=a,1=b,2=c,",
+            ),
+            ("{{ range $v := .l }}{{ $v }}{{ end }}", "abc"),
+            ("{{ range $k, $v := .m }}{{ $k }}{{ $v }}{{ end }}", "a2b1"),
+            ("{{ range .empty }}x{{ else }}empty{{ end }}", "empty"),
+            ("{{ range .missing }}x{{ else }}none{{ end }}", "none"),
+            ("{{ range 3 }}{{ . }}{{ end }}", "012"),
+            (
+                "{{ range .l }}{{ if eq . \"b\" }}{{ continue }}{{ end }}{{ . }}{{ end }}",
+                "ac",
+            ),
+            (
+                "{{ range .l }}{{ if eq . \"b\" }}{{ break }}{{ end }}{{ . }}{{ end }}",
+                "a",
+            ),
+            ("{{ range .l }}{{ $.s }}{{ end }}", "strstrstr"),
+            ("{{ or .missing .null \"d\" }}", "d"),
+            ("{{ or .s \"d\" }}", "str"),
+            ("{{ and 1 0 2 }}", "0"),
+            ("{{ and 1 2 }}", "2"),
+            ("{{ and .missing (index .null 0) }}", "<no value>"),
+            ("{{ not .missing }}", "true"),
+            ("a  {{- .s -}}  b", "astrb"),
+            (
+                "{{ $d := dict }}{{ $_ := set $d \"k\" \"v\" }}{{ $d.k }}",
+                "v",
+            ),
+        ] {
+            assert_eq!(
+                render(source, &data()).unwrap_or_else(|err| panic!("{source}: {err}")),
+                expected,
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_errors_with_context() {
+        for (source, message) in [
+            (
+                "{{ .null.x }}",
+                r#"template: tpl:1:3: executing "tpl" at <.null.x>: nil pointer evaluating interface {}.x"#,
+            ),
+            (
+                "{{ .s.x }}",
+                r#"template: tpl:1:3: executing "tpl" at <.s.x>: can't evaluate field x in type string"#,
+            ),
+            (
+                "a\n{{ lt .n 1 }}",
+                r#"template: tpl:2:3: executing "tpl" at <lt .n 1>: error calling lt: incompatible types for comparison"#,
+            ),
+            (
+                "{{ range .s }}{{ end }}",
+                r#"template: tpl:1:9: executing "tpl" at <.s>: range can't iterate over str"#,
+            ),
+            (
+                "{{ range $i, $v := 3 }}{{ end }}",
+                r#"template: tpl:1:9: executing "tpl" at <$i, $v := 3>: can't use 3 to iterate over more than one variable"#,
+            ),
+            (
+                "{{ .s 1 }}",
+                r#"template: tpl:1:3: executing "tpl" at <.s 1>: can't give argument to non-function .s"#,
+            ),
+            (
+                "{{ nil }}",
+                r#"template: tpl:1:3: executing "tpl" at <nil>: nil is not a command"#,
+            ),
+            (
+                "{{ and }}",
+                r#"template: tpl:1:3: executing "tpl" at <and>: wrong number of args for and: want at least 1 got 0"#,
+            ),
+        ] {
+            assert_eq!(
+                render(source, &data()).unwrap_err().to_string(),
+                message,
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn fails_on_missing_keys_when_asked() {
+        let template = Template::parse("tpl", "{{ .nested.x.y }}")
+            .unwrap()
+            .with_missing_key_error();
+        let err = template.execute(&data().into()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"template: tpl:1:3: executing "tpl" at <.nested.x.y>: map has no entry for key "x""#
+        );
+        assert_eq!((err.line, err.column), (1, 3));
+        assert_eq!(
+            template.execute(&json!({"nested": {"x": {"y": 1}}}).into()),
+            Ok("1".into())
+        );
+    }
+}
@@ -0,0 +1,802 @@
+//! Functions available to templates, following `text/template/funcs.go`.
+
+use std::cmp::Ordering;
+
+use super::sprig;
+use super::value::{format_float, Value};
+
+/// Error returned by a template function.
+#[derive(Debug)]
+pub(super) enum FuncError {
+    /// The number of arguments does not match, `want` is e.g. `2` or `at least 1`.
+    Arity {
+        want: String,
+        got: usize,
+    },
+    Call(String),
+    /// An argument of the wrong type, reported without the function name as Go does.
+    Argument(String),
+}
+
+impl FuncError {
+    pub fn message(&self, name: &str) -> String {
+        match self {
+            FuncError::Arity { want, got } => {
+                format!("wrong number of args for {name}: want {want} got {got}")
+            }
+            FuncError::Call(message) => format!("error calling {name}: {message}"),
+            FuncError::Argument(message) => message.clone(),
+        }
+    }
+}
+
+impl From<String> for FuncError {
+    fn from(message: String) -> Self {
+        FuncError::Call(message)
+    }
+}
+
+impl From<&str> for FuncError {
+    fn from(message: &str) -> Self {
+        FuncError::Call(message.to_string())
+    }
+}
+
+pub(super) type Func = fn(&[Value]) -> Result<Value, FuncError>;
+
+/// The builtin functions. `and` and `or` short-circuit, so they are evaluated by the executor.
+const BUILTINS: &[(&str, Func)] = &[
+    ("and", |_| unreachable!("evaluated by the executor")),
+    ("or", |_| unreachable!("evaluated by the executor")),
+    ("eq", eq),
+    ("ge", |args| Ok(Value::Bool(!lt(exactly(args, 2)?)?))),
+    ("gt", |args| Ok(Value::Bool(!le(exactly(args, 2)?)?))),
+    ("html", |args| Ok(Value::String(html_escape(&sprint(args))))),
+    ("index", index),
+    ("js", |args| Ok(Value::String(js_escape(&sprint(args))))),
+    ("le", |args| Ok(Value::Bool(le(exactly(args, 2)?)?))),
+    ("len", len),
+    ("lt", |args| Ok(Value::Bool(lt(exactly(args, 2)?)?))),
+    ("ne", |args| {
+        let args = exactly(args, 2)?;
+        Ok(Value::Bool(!equal(&args[0], &args[1])?))
+    }),
+    ("not", |args| {
+        Ok(Value::Bool(!exactly(args, 1)?[0].is_true()))
+    }),
+    ("print", |args| Ok(Value::String(sprint(args)))),
+    ("printf", printf),
+    ("println", |args| Ok(Value::String(sprintln(args)))),
+    ("slice", slice),
+    ("urlquery", |args| {
+        Ok(Value::String(query_escape(&sprint(args))))
+    }),
+];
+
+/// The function with the name. Like functions added with Go's `Funcs`, sprig's take
+/// precedence over the builtins.
+pub(super) fn lookup(name: &str) -> Option<Func> {
+    sprig::lookup(name).or_else(|| BUILTINS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f))
+}
+
+pub(super) fn is_function(name: &str) -> bool {
+    lookup(name).is_some()
+}
+
+pub(super) fn exactly(args: &[Value], n: usize) -> Result<&[Value], FuncError> {
+    if args.len() != n {
+        return Err(FuncError::Arity {
+            want: n.to_string(),
+            got: args.len(),
+        });
+    }
+    Ok(args)
+}
+
+pub(super) fn at_least(args: &[Value], n: usize) -> Result<&[Value], FuncError> {
+    if args.len() < n {
+        return Err(FuncError::Arity {
+            want: format!("at least {n}"),
+            got: args.len(),
+        });
+    }
+    Ok(args)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Invalid,
+    Bool,
+    Int,
+    Float,
+    String,
+    Other,
+}
+
+fn basic_kind(v: &Value) -> Kind {
+    match v {
+        Value::Missing | Value::Nil => Kind::Invalid,
+        Value::Bool(_) => Kind::Bool,
+        Value::Int(_) => Kind::Int,
+        Value::Float(_) => Kind::Float,
+        Value::String(_) => Kind::String,
+        Value::List(_) | Value::Map(_) => Kind::Other,
+    }
+}
+
+const ERR_BAD_COMPARISON_TYPE: &str = "invalid type for comparison";
+const ERR_BAD_COMPARISON: &str = "incompatible types for comparison";
+
+fn equal(a: &Value, b: &Value) -> Result<bool, FuncError> {
+    let (k1, k2) = (basic_kind(a), basic_kind(b));
+    if k1 != k2 {
+        if k1 != Kind::Invalid && k2 != Kind::Invalid {
+            return Err(ERR_BAD_COMPARISON.into());
+        }
+        return Ok(false);
+    }
+    match (a, b) {
+        (Value::List(_) | Value::Map(_), _) => {
+            Err(format!("non-comparable type {a}: {}", a.type_name()).into())
+        }
+        _ => Ok(a == b || k1 == Kind::Invalid),
+    }
+}
+
+/// `eq arg1 arg2...` is true if arg1 equals any of the other arguments.
+fn eq(args: &[Value]) -> Result<Value, FuncError> {
+    let args = at_least(args, 1)?;
+    if args.len() == 1 {
+        return Err("missing argument for comparison".into());
+    }
+    for arg in &args[1..] {
+        if equal(&args[0], arg)? {
+            return Ok(Value::Bool(true));
+        }
+    }
+    Ok(Value::Bool(false))
+}
+
+fn compare(args: &[Value]) -> Result<Ordering, FuncError> {
+    let (a, b) = (&args[0], &args[1]);
+    let (k1, k2) = (basic_kind(a), basic_kind(b));
+    if matches!(k1, Kind::Invalid | Kind::Other) || matches!(k2, Kind::Invalid | Kind::Other) {
+        return Err(ERR_BAD_COMPARISON_TYPE.into());
+    }
+    if k1 != k2 {
+        return Err(ERR_BAD_COMPARISON.into());
+    }
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => Ok(x.cmp(y)),
+        (Value::Float(x), Value::Float(y)) => Ok(x.partial_cmp(y).unwrap_or(Ordering::Equal)),
+        (Value::String(x), Value::String(y)) => Ok(x.cmp(y)),
+        _ => Err(ERR_BAD_COMPARISON_TYPE.into()),
+    }
+}
+
+fn lt(args: &[Value]) -> Result<bool, FuncError> {
+    Ok(compare(args)? == Ordering::Less)
+}
+
+fn le(args: &[Value]) -> Result<bool, FuncError> {
+    Ok(compare(args)? != Ordering::Greater)
+}
+
+fn len(args: &[Value]) -> Result<Value, FuncError> {
+    let n = match &exactly(args, 1)?[0] {
+        Value::String(s) => s.len(),
+        Value::List(l) => l.len(),
+        Value::Map(m) => m.len(),
+        Value::Missing | Value::Nil => return Err("len of nil pointer".into()),
+        v => return Err(format!("len of type {}", v.type_name()).into()),
+    };
+    Ok(Value::Int(n as i64))
+}
+
+fn index_arg(index: &Value, cap: usize) -> Result<usize, FuncError> {
+    let Value::Int(i) = index else {
+        return Err(format!("cannot index slice/array with type {}", index.type_name()).into());
+    };
+    if *i < 0 || *i as usize > cap {
+        return Err(format!("index out of range: {i}").into());
+    }
+    Ok(*i as usize)
+}
+
+/// `index item 1 2 3` is `item[1][2][3]`.
+fn index(args: &[Value]) -> Result<Value, FuncError> {
+    let args = at_least(args, 1)?;
+    let mut item = args[0].clone();
+    for index in &args[1..] {
+        item = match item {
+            Value::Missing | Value::Nil => return Err("index of untyped nil".into()),
+            Value::List(list) => {
+                let i = index_arg(index, list.len())?;
+                list.into_iter()
+                    .nth(i)
+                    .ok_or_else(|| format!("index out of range: {i}"))?
+            }
+            Value::String(s) => {
+                let i = index_arg(index, s.len())?;
+                let byte = s
+                    .as_bytes()
+                    .get(i)
+                    .ok_or_else(|| format!("index out of range: {i}"))?;
+                Value::Int(i64::from(*byte))
+            }
+            Value::Map(mut map) => match index {
+                Value::String(key) => map.remove(key).unwrap_or(Value::Nil),
+                Value::Missing | Value::Nil => Value::Nil,
+                key => {
+                    return Err(
+                        format!("value has type {}; should be string", key.type_name()).into(),
+                    )
+                }
+            },
+            item => return Err(format!("can't index item of type {}", item.type_name()).into()),
+        };
+    }
+    Ok(item)
+}
+
+/// `slice x 1 2` is `x[1:2]`.
+fn slice(args: &[Value]) -> Result<Value, FuncError> {
+    let args = at_least(args, 1)?;
+    if args.len() > 4 {
+        return Err(format!("too many slice indexes: {}", args.len() - 1).into());
+    }
+    let len = match &args[0] {
+        Value::String(s) => s.len(),
+        Value::List(l) => l.len(),
+        Value::Missing | Value::Nil => return Err("slice of untyped nil".into()),
+        v => return Err(format!("can't slice item of type {}", v.type_name()).into()),
+    };
+    let mut indexes = [0, len, len];
+    for (i, index) in args[1..].iter().enumerate() {
+        indexes[i] = index_arg(index, len)?;
+    }
+    let (start, end) = (indexes[0], indexes[1]);
+    if start > end {
+        return Err(format!("invalid slice index: {start} > {end}").into());
+    }
+    match &args[0] {
+        Value::String(s) => {
+            if args.len() > 3 {
+                return Err("cannot 3-index slice a string".into());
+            }
+            s.get(start..end)
+                .map(|s| Value::String(s.to_string()))
+                .ok_or_else(|| "invalid slice index: not a character boundary".into())
+        }
+        Value::List(l) => Ok(Value::List(l[start..end].to_vec())),
+        _ => unreachable!(),
+    }
+}
+
+/// Formats like Go's `fmt.Sprint`: spaces are added between operands when neither is a string.
+pub(super) fn sprint(args: &[Value]) -> String {
+    let mut out = String::new();
+    for (i, arg) in args.iter().enumerate() {
+        let is_string = matches!(arg, Value::String(_));
+        if i > 0 && !is_string && !matches!(args[i - 1], Value::String(_)) {
+            out.push(' ');
+        }
+        out.push_str(&print_arg(arg));
+    }
+    out
+}
+
+/// Formats like Go's `fmt.Sprintln`: spaces are always added and a newline is appended.
+pub(super) fn sprintln(args: &[Value]) -> String {
+    let mut out = args.iter().map(print_arg).collect::<Vec<_>>().join(" ");
+    out.push('\n');
+    out
+}
+
+fn print_arg(v: &Value) -> String {
+    match v {
+        // missing values are passed to functions as nil
+        Value::Missing => "<nil>".to_string(),
+        v => v.to_string(),
+    }
+}
+
+fn printf(args: &[Value]) -> Result<Value, FuncError> {
+    let args = at_least(args, 1)?;
+    let Value::String(format) = &args[0] else {
+        return Err(FuncError::Argument(format!(
+            "wrong type for value; expected string; got {}",
+            args[0].type_name()
+        )));
+    };
+    Ok(Value::String(sprintf(format, &args[1..])))
+}
+
+#[derive(Default)]
+struct Spec {
+    minus: bool,
+    plus: bool,
+    sharp: bool,
+    space: bool,
+    zero: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    fn pad(&self, s: String) -> String {
+        let Some(width) = self.width else {
+            return s;
+        };
+        let len = s.chars().count();
+        if len >= width {
+            return s;
+        }
+        let fill = width - len;
+        if self.minus {
+            format!("{s}{}", " ".repeat(fill))
+        } else if self.zero {
+            let (sign, digits) = match s.strip_prefix(['-', '+']) {
+                Some(digits) => (&s[..1], digits),
+                None => ("", s.as_str()),
+            };
+            format!("{sign}{}{digits}", "0".repeat(fill))
+        } else {
+            format!("{}{s}", " ".repeat(fill))
+        }
+    }
+
+    fn sign(&self, negative: bool) -> &'static str {
+        match (negative, self.plus, self.space) {
+            (true, _, _) => "-",
+            (false, true, _) => "+",
+            (false, false, true) => " ",
+            _ => "",
+        }
+    }
+}
+
+/// Formats like Go's `fmt.Sprintf` for the verbs that make sense on template values.
+pub(super) fn sprintf(format: &str, args: &[Value]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let mut spec = Spec::default();
+        while let Some(&flag) = chars.peek() {
+            match flag {
+                '-' => spec.minus = true,
+                '+' => spec.plus = true,
+                '#' => spec.sharp = true,
+                ' ' => spec.space = true,
+                '0' => spec.zero = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let mut n: Option<usize> = None;
+            while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                n = Some(n.unwrap_or(0) * 10 + d as usize);
+                chars.next();
+            }
+            n
+        };
+        spec.width = number(&mut chars);
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            spec.precision = Some(number(&mut chars).unwrap_or(0));
+        }
+        let Some(verb) = chars.next() else {
+            out.push_str("%!(NOVERB)");
+            break;
+        };
+        if verb == '%' {
+            out.push('%');
+            continue;
+        }
+        match args.next() {
+            None => out.push_str(&format!("%!{verb}(MISSING)")),
+            Some(arg) => out.push_str(&format_arg(&spec, verb, arg)),
+        }
+    }
+    let extra: Vec<_> = args
+        .map(|a| match a {
+            Value::Missing | Value::Nil => "<nil>".to_string(),
+            a => format!("{}={}", a.type_name(), print_arg(a)),
+        })
+        .collect();
+    if !extra.is_empty() {
+        out.push_str(&format!("%!(EXTRA {})", extra.join(", ")));
+    }
+    out
+}
+
+fn bad_verb(verb: char, arg: &Value) -> String {
+    match arg {
+        Value::Missing | Value::Nil => format!("%!{verb}(<nil>)"),
+        arg => format!("%!{verb}({}={})", arg.type_name(), print_arg(arg)),
+    }
+}
+
+fn format_arg(spec: &Spec, verb: char, arg: &Value) -> String {
+    if verb == 'T' {
+        let name = match arg {
+            Value::Missing | Value::Nil => "<nil>",
+            arg => arg.type_name(),
+        };
+        return spec.pad(name.to_string());
+    }
+    match arg {
+        Value::Missing | Value::Nil if verb == 'v' => spec.pad("<nil>".to_string()),
+        Value::Missing | Value::Nil => bad_verb(verb, arg),
+        Value::Bool(b) => match verb {
+            'v' | 't' => spec.pad(b.to_string()),
+            _ => bad_verb(verb, arg),
+        },
+        Value::Int(i) => format_int(spec, verb, *i).unwrap_or_else(|| bad_verb(verb, arg)),
+        Value::Float(f) => format_float_verb(spec, verb, *f).unwrap_or_else(|| bad_verb(verb, arg)),
+        Value::String(s) => match verb {
+            'v' | 's' => {
+                let s = match spec.precision {
+                    Some(p) => s.chars().take(p).collect(),
+                    None => s.clone(),
+                };
+                spec.pad(s)
+            }
+            'q' => spec.pad(if spec.sharp && !s.contains('`') {
+                format!("`{s}`")
+            } else {
+                quote(s)
+            }),
+            'x' | 'X' => {
+                let hex: String = s.bytes().map(|b| format!("{b:02x}")).collect();
+                spec.pad(if verb == 'X' { hex.to_uppercase() } else { hex })
+            }
+            _ => bad_verb(verb, arg),
+        },
+        Value::List(l) => {
+            let items: Vec<_> = l
+                .iter()
+                .map(|v| format_arg(&Spec::default(), verb, v))
+                .collect();
+            spec.pad(format!("[{}]", items.join(" ")))
+        }
+        Value::Map(m) => {
+            let items: Vec<_> = m
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}:{}",
+                        format_arg(&Spec::default(), verb, &Value::String(k.clone())),
+                        format_arg(&Spec::default(), verb, v)
+                    )
+                })
+                .collect();
+            spec.pad(format!("map[{}]", items.join(" ")))
+        }
+    }
+}
+
+fn format_int(spec: &Spec, verb: char, i: i64) -> Option<String> {
+    let sign = spec.sign(i < 0);
+    let abs = i.unsigned_abs();
+    let digits = match verb {
+        'v' | 'd' => abs.to_string(),
+        'b' => format!("{abs:b}"),
+        'o' => format!("{}{abs:o}", if spec.sharp { "0" } else { "" }),
+        'O' => format!("0o{abs:o}"),
+        'x' => format!("{}{abs:x}", if spec.sharp { "0x" } else { "" }),
+        'X' => format!("{}{abs:X}", if spec.sharp { "0X" } else { "" }),
+        'c' => return char::from_u32(i as u32).map(|c| spec.pad(c.to_string())),
+        'q' => return char::from_u32(i as u32).map(|c| spec.pad(format!("{c:?}"))),
+        'U' => return Some(spec.pad(format!("U+{abs:04X}"))),
+        'e' | 'E' | 'f' | 'F' | 'g' | 'G' => return None,
+        _ => return None,
+    };
+    let digits = match spec.precision {
+        Some(p) if digits.len() < p => format!("{}{digits}", "0".repeat(p - digits.len())),
+        _ => digits,
+    };
+    Some(spec.pad(format!("{sign}{digits}")))
+}
+
+fn format_float_verb(spec: &Spec, verb: char, f: f64) -> Option<String> {
+    let (format, precision) = match verb {
+        'v' | 'g' | 'G' => ('g', spec.precision),
+        'e' | 'E' => ('e', Some(spec.precision.unwrap_or(6))),
+        'f' | 'F' => ('f', Some(spec.precision.unwrap_or(6))),
+        _ => return None,
+    };
+    let s = format_float(f.abs(), format, precision);
+    let s = if verb.is_ascii_uppercase() {
+        s.to_uppercase()
+    } else {
+        s
+    };
+    let sign = if f.is_nan() {
+        ""
+    } else {
+        spec.sign(f.is_sign_negative() && f != 0.0)
+    };
+    let s = s.trim_start_matches('+');
+    Some(spec.pad(format!("{sign}{s}")))
+}
+
+/// Quotes a string like Go's `strconv.Quote`.
+pub(super) fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{07}' => out.push_str("\\a"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{0b}' => out.push_str("\\v"),
+            c if c.is_control() => {
+                if (c as u32) < 0x80 {
+                    out.push_str(&format!("\\x{:02x}", c as u32));
+                } else if (c as u32) < 0x10000 {
+                    out.push_str(&format!("\\u{:04x}", c as u32));
+                } else {
+                    out.push_str(&format!("\\U{:08x}", c as u32));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\0' => out.push('\u{FFFD}'),
+            '"' => out.push_str("&#34;"),
+            '\'' => out.push_str("&#39;"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn js_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '<' => out.push_str("\\u003C"),
+            '>' => out.push_str("\\u003E"),
+            '&' => out.push_str("\\u0026"),
+            '=' => out.push_str("\\u003D"),
+            '`' => out.push_str("\\u0060"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn query_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b' ' => out.push('+'),
+            b => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{slice, Value};
+    use crate::topology::template::render;
+
+    fn render_ok(source: &str) -> String {
+        let data = json!({"n": 3, "f": 2.5, "s": "str", "l": [1, 2], "m": {"k": "v"}});
+        render(source, &data).unwrap_or_else(|err| panic!("{source}: {err}"))
+    }
+
+    fn render_err(source: &str) -> String {
+        let err = render(source, &json!({"n": 3, "l": [1, 2]})).unwrap_err();
+        // the message without the location and context
+        err.message
+    }
+
+    #[test]
+    fn printf_verbs() {
+        for (source, expected) in [
+            (r#"{{ printf "%d" 42 }}"#, "42"),
+            (
+                r#"{{ printf "%5d|%-5d|%05d|%+d|%.3d" 42 42 42 42 7 }}"#,
+                "   42|42   |00042|+42|007",
+            ),
+            (
+                r#"{{ printf "%x %#x %X %o %#o %O %b" 255 255 255 8 8 8 5 }}"#,
+                "ff 0xff FF 10 010 0o10 101",
+            ),
+            (r#"{{ printf "%c %q %U" 65 65 0x1F600 }}"#, "A 'A' U+1F600"),
+            (r#"{{ printf "%d" .n }}"#, "%!d(float64=3)"),
+            (r#"{{ printf "%v %g" .n .f }}"#, "3 2.5"),
+            (
+                r#"{{ printf "%f|%.2f|%8.3f" 3.14159 3.14159 3.14159 }}"#,
+                "3.141590|3.14|   3.142",
+            ),
+            (r#"{{ printf "%+.1f|%06.2f" 2.0 -1.5 }}"#, "+2.0|-01.50"),
+            (
+                r#"{{ printf "%e|%E|%.2e" 1234.5678 1234.5678 0.000123 }}"#,
+                "1.234568e+03|1.234568E+03|1.23e-04",
+            ),
+            (
+                r#"{{ printf "%g|%g|%.3g" 0.00001234 1e21 1234.5678 }}"#,
+                "1.234e-05|1e+21|1.23e+03",
+            ),
+            (
+                r#"{{ printf "%s|%5s|%-5s|%.1s" "hi" "hi" "hi" "hi" }}"#,
+                "hi|   hi|hi   |h",
+            ),
+            (
+                r#"{{ printf "%q|%#q|%x|%X" "a\"b" "ab" "hi" "hi" }}"#,
+                r#""a\"b"|`ab`|6869|6869"#,
+            ),
+            (r#"{{ printf "%t %v" true false }}"#, "true false"),
+            (
+                r#"{{ printf "%T %T %T %T %T" 1 .f "s" .l nil }}"#,
+                "int float64 string []interface {} <nil>",
+            ),
+            (
+                r#"{{ printf "%v|%d" .l .m }}"#,
+                "[1 2]|map[%!d(string=k):%!d(string=v)]",
+            ),
+            (r#"{{ printf "%v %d" nil nil }}"#, "<nil> %!d(<nil>)"),
+            (r#"{{ printf "%v" .missing }}"#, "<nil>"),
+            (r#"{{ printf "100%%" }}"#, "100%"),
+            (r#"{{ printf "%s %s" "a" }}"#, "a %!s(MISSING)"),
+            (r#"{{ printf "%d" 1 2 "a" }}"#, "1%!(EXTRA int=2, string=a)"),
+            (r#"{{ printf "%z" 1 }}"#, "%!z(int=1)"),
+            (r#"{{ printf "%d" "a" }}"#, "%!d(string=a)"),
+            (r#"{{ printf "a%" }}"#, "a%!(NOVERB)"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn builtins() {
+        for (source, expected) in [
+            (r#"{{ print 1 2 "a" 3 .missing }}"#, "1 2a3 <nil>"),
+            (r#"{{ println 1 "a" }}"#, "1 a\n"),
+            (
+                r#"{{ eq 1 1 }} {{ eq .s "x" "str" }} {{ eq .missing nil }}"#,
+                "true true true",
+            ),
+            (r#"{{ ne .n 3.0 }} {{ eq .n 3.0 }}"#, "false true"),
+            (
+                r#"{{ lt 1 2 }} {{ le 2 2 }} {{ gt "b" "a" }} {{ ge 1.5 .f }}"#,
+                "true true true false",
+            ),
+            (r#"{{ len .s }} {{ len .l }} {{ len .m }}"#, "3 2 1"),
+            (
+                r#"{{ index .l 1 }} {{ index .m "k" }} {{ index .m "x" }} {{ index "ab" 1 }}"#,
+                "2 v <nil> 98",
+            ),
+            (r#"{{ html "<a&'\">" }}"#, "&lt;a&amp;&#39;&#34;&gt;"),
+            (
+                r#"{{ js "<'a'=\"b\">" }}"#,
+                r#"\u003C\'a\'\u003D\"b\"\u003E"#,
+            ),
+            (r#"{{ urlquery "a b&c/é" }}"#, "a+b%26c%2F%C3%A9"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn builtin_errors() {
+        for (source, message) in [
+            (
+                "{{ eq 1 }}",
+                "error calling eq: missing argument for comparison",
+            ),
+            (
+                "{{ eq 1 \"a\" }}",
+                "error calling eq: incompatible types for comparison",
+            ),
+            (
+                "{{ eq .n 3 }}",
+                "error calling eq: incompatible types for comparison",
+            ),
+            (
+                "{{ eq .l .l }}",
+                "error calling eq: non-comparable type [1 2]: []interface {}",
+            ),
+            (
+                "{{ lt .n 1 }}",
+                "error calling lt: incompatible types for comparison",
+            ),
+            (
+                "{{ lt nil 1 }}",
+                "error calling lt: invalid type for comparison",
+            ),
+            ("{{ lt 1 }}", "wrong number of args for lt: want 2 got 1"),
+            ("{{ len 1 }}", "error calling len: len of type int"),
+            ("{{ len nil }}", "error calling len: len of nil pointer"),
+            (
+                "{{ index .l 5 }}",
+                "error calling index: index out of range: 5",
+            ),
+            (
+                "{{ index .l \"a\" }}",
+                "error calling index: cannot index slice/array with type string",
+            ),
+            (
+                "{{ index nil 1 }}",
+                "error calling index: index of untyped nil",
+            ),
+            (
+                "{{ index 1 1 }}",
+                "error calling index: can't index item of type int",
+            ),
+            (
+                "{{ printf 1 }}",
+                "wrong type for value; expected string; got int",
+            ),
+        ] {
+            assert_eq!(render_err(source), message, "{source}");
+        }
+    }
+
+    #[test]
+    fn builtin_slice() {
+        // templates get sprig's slice, which shadows this one as in Go
+        let s = Value::from("str");
+        let l = Value::List(vec![Value::Int(1), Value::Int(2)]);
+        for (args, expected) in [
+            (vec![s.clone(), Value::Int(1)], Value::from("tr")),
+            (
+                vec![s.clone(), Value::Int(1), Value::Int(2)],
+                Value::from("t"),
+            ),
+            (
+                vec![l.clone(), Value::Int(0), Value::Int(1)],
+                Value::List(vec![Value::Int(1)]),
+            ),
+        ] {
+            assert_eq!(slice(&args).unwrap(), expected, "{args:?}");
+        }
+        for (args, message) in [
+            (
+                vec![l, Value::Int(2), Value::Int(1)],
+                "error calling slice: invalid slice index: 2 > 1",
+            ),
+            (
+                vec![s, Value::Int(0), Value::Int(1), Value::Int(2)],
+                "error calling slice: cannot 3-index slice a string",
+            ),
+        ] {
+            assert_eq!(slice(&args).unwrap_err().message("slice"), message);
+        }
+    }
+}
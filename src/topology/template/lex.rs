@@ -0,0 +1,597 @@
+//! Lexer for Go templates, following `text/template/parse/lex.go`.
+
+use super::TemplateError;
+
+const LEFT_DELIM: &str = "{{";
+const RIGHT_DELIM: &str = "}}";
+const LEFT_COMMENT: &str = "/*";
+const RIGHT_COMMENT: &str = "*/";
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+    Text(String),
+    LeftDelim,
+    RightDelim,
+    Space,
+    Bool(bool),
+    Char(char),
+    Number(String),
+    String(String),
+    Field(String),
+    Identifier(String),
+    Variable(String),
+    Dot,
+    Pipe,
+    LeftParen,
+    RightParen,
+    Declare,
+    Assign,
+    Comma,
+    Nil,
+    // keywords
+    Block,
+    Break,
+    Continue,
+    Define,
+    Else,
+    End,
+    If,
+    Range,
+    Template,
+    With,
+    Eof,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Item {
+    pub token: Token,
+    /// Byte offset of the token in the template source.
+    pub pos: usize,
+}
+
+pub(super) struct Lexer<'a> {
+    name: &'a str,
+    input: &'a str,
+    pos: usize,
+    items: Vec<Item>,
+    paren_depth: usize,
+}
+
+/// Splits the template source into tokens.
+pub(super) fn lex(name: &str, input: &str) -> Result<Vec<Item>, TemplateError> {
+    let mut lexer = Lexer {
+        name,
+        input,
+        pos: 0,
+        items: Vec::new(),
+        paren_depth: 0,
+    };
+    lexer.run()?;
+    Ok(lexer.items)
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+fn is_alphanumeric(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+/// Formats a character like Go's `%#U` verb, e.g. `U+0023 '#'`.
+fn unicode(c: char) -> String {
+    if c.is_control() {
+        format!("U+{:04X}", c as u32)
+    } else {
+        format!("U+{:04X} '{c}'", c as u32)
+    }
+}
+
+impl Lexer<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn emit(&mut self, token: Token, pos: usize) {
+        self.items.push(Item { token, pos });
+    }
+
+    fn error(&self, pos: usize, message: impl Into<String>) -> TemplateError {
+        TemplateError::parse(self.name, self.input, pos, message)
+    }
+
+    fn run(&mut self) -> Result<(), TemplateError> {
+        loop {
+            let Some(offset) = self.rest().find(LEFT_DELIM) else {
+                let text = self.rest().to_string();
+                if !text.is_empty() {
+                    self.emit(Token::Text(text), self.pos);
+                }
+                self.emit(Token::Eof, self.input.len());
+                return Ok(());
+            };
+            let delim = self.pos + offset;
+            let after = &self.input[delim + LEFT_DELIM.len()..];
+            let trim = after.starts_with('-') && after[1..].starts_with(is_space);
+            let mut text = &self.input[self.pos..delim];
+            if trim {
+                text = text.trim_end_matches(is_space);
+            }
+            if !text.is_empty() {
+                self.emit(Token::Text(text.to_string()), self.pos);
+            }
+            self.pos = delim + LEFT_DELIM.len();
+            if trim {
+                self.pos += 2;
+            }
+            if self.rest().starts_with(LEFT_COMMENT) {
+                self.lex_comment(delim)?;
+            } else {
+                self.emit(Token::LeftDelim, delim);
+                self.lex_inside_action()?;
+            }
+        }
+    }
+
+    fn lex_comment(&mut self, delim: usize) -> Result<(), TemplateError> {
+        let start = self.pos + LEFT_COMMENT.len();
+        let Some(end) = self.input[start..].find(RIGHT_COMMENT) else {
+            return Err(self.error(delim, "unclosed comment"));
+        };
+        self.pos = start + end + RIGHT_COMMENT.len();
+        let (trim, found) = self.at_right_delim();
+        if !found {
+            return Err(self.error(delim, "comment ends before closing delimiter"));
+        }
+        self.pos += RIGHT_DELIM.len();
+        if trim {
+            self.pos += 2;
+            self.skip_leading_space();
+        }
+        Ok(())
+    }
+
+    /// Whether the input is at the right delimiter, possibly preceded by a trim marker.
+    fn at_right_delim(&self) -> (bool, bool) {
+        let rest = self.rest();
+        if rest.starts_with(is_space)
+            && rest[1..].starts_with('-')
+            && rest[2..].starts_with(RIGHT_DELIM)
+        {
+            return (true, true);
+        }
+        (false, rest.starts_with(RIGHT_DELIM))
+    }
+
+    fn skip_leading_space(&mut self) {
+        let trimmed = self.rest().trim_start_matches(is_space);
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    fn lex_inside_action(&mut self) -> Result<(), TemplateError> {
+        loop {
+            let (trim, at_delim) = self.at_right_delim();
+            if at_delim && self.paren_depth == 0 {
+                if trim {
+                    self.pos += 2;
+                }
+                self.emit(Token::RightDelim, self.pos);
+                self.pos += RIGHT_DELIM.len();
+                if trim {
+                    self.skip_leading_space();
+                }
+                return Ok(());
+            }
+            let start = self.pos;
+            let Some(c) = self.next() else {
+                return Err(self.error(start, "unclosed action"));
+            };
+            match c {
+                c if is_space(c) => self.lex_space(start),
+                '=' => self.emit(Token::Assign, start),
+                ':' => {
+                    if self.next() != Some('=') {
+                        return Err(self.error(start, "expected :="));
+                    }
+                    self.emit(Token::Declare, start);
+                }
+                '|' => self.emit(Token::Pipe, start),
+                '"' => self.lex_quote(start)?,
+                '`' => self.lex_raw_quote(start)?,
+                '$' => self.lex_variable(start),
+                '\'' => self.lex_char(start)?,
+                '.' => {
+                    if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos = start;
+                        self.lex_number(start)?;
+                    } else {
+                        self.lex_field(start);
+                    }
+                }
+                '+' | '-' | '0'..='9' => {
+                    self.pos = start;
+                    self.lex_number(start)?;
+                }
+                c if is_alphanumeric(c) => self.lex_identifier(start)?,
+                '(' => {
+                    self.paren_depth += 1;
+                    self.emit(Token::LeftParen, start);
+                }
+                ')' => {
+                    if self.paren_depth == 0 {
+                        return Err(self.error(start, "unexpected right paren"));
+                    }
+                    self.paren_depth -= 1;
+                    self.emit(Token::RightParen, start);
+                }
+                ',' => self.emit(Token::Comma, start),
+                c => {
+                    return Err(self.error(
+                        start,
+                        format!("unrecognized character in action: {}", unicode(c)),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn lex_space(&mut self, start: usize) {
+        while self.peek().is_some_and(is_space) {
+            let (_, at_delim) = self.at_right_delim();
+            if at_delim {
+                // the space belongs to the " -}}" trim marker
+                break;
+            }
+            self.next();
+        }
+        self.emit(Token::Space, start);
+    }
+
+    fn lex_identifier(&mut self, start: usize) -> Result<(), TemplateError> {
+        while self.peek().is_some_and(is_alphanumeric) {
+            self.next();
+        }
+        let word = &self.input[start..self.pos];
+        self.check_terminator(start)?;
+        let token = match word {
+            "block" => Token::Block,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
+            "define" => Token::Define,
+            "else" => Token::Else,
+            "end" => Token::End,
+            "if" => Token::If,
+            "range" => Token::Range,
+            "nil" => Token::Nil,
+            "template" => Token::Template,
+            "with" => Token::With,
+            "true" => Token::Bool(true),
+            "false" => Token::Bool(false),
+            _ => Token::Identifier(word.to_string()),
+        };
+        self.emit(token, start);
+        Ok(())
+    }
+
+    fn lex_field(&mut self, start: usize) {
+        while self.peek().is_some_and(is_alphanumeric) {
+            self.next();
+        }
+        let name = &self.input[start + 1..self.pos];
+        if name.is_empty() {
+            self.emit(Token::Dot, start);
+        } else {
+            self.emit(Token::Field(name.to_string()), start);
+        }
+    }
+
+    fn lex_variable(&mut self, start: usize) {
+        while self.peek().is_some_and(is_alphanumeric) {
+            self.next();
+        }
+        let name = self.input[start..self.pos].to_string();
+        self.emit(Token::Variable(name), start);
+    }
+
+    /// Checks that an identifier is followed by a character that can end it.
+    fn check_terminator(&self, start: usize) -> Result<(), TemplateError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) if is_space(c) || matches!(c, '.' | ',' | '|' | ':' | ')' | '(' | '=') => {
+                Ok(())
+            }
+            Some(_) if self.rest().starts_with(RIGHT_DELIM) => Ok(()),
+            Some(c) => Err(self.error(start, format!("bad character {}", unicode(c)))),
+        }
+    }
+
+    fn lex_quote(&mut self, start: usize) -> Result<(), TemplateError> {
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None | Some('\n') => return Err(self.error(start, "unterminated quoted string")),
+                Some('"') => break,
+                Some('\\') => value.push(self.lex_escape(start, '"')?),
+                Some(c) => value.push(c),
+            }
+        }
+        self.emit(Token::String(value), start);
+        Ok(())
+    }
+
+    fn lex_raw_quote(&mut self, start: usize) -> Result<(), TemplateError> {
+        let Some(end) = self.rest().find('`') else {
+            return Err(self.error(start, "unterminated raw quoted string"));
+        };
+        let value = self.rest()[..end].replace('\r', "");
+        self.pos += end + 1;
+        self.emit(Token::String(value), start);
+        Ok(())
+    }
+
+    fn lex_char(&mut self, start: usize) -> Result<(), TemplateError> {
+        let c = match self.next() {
+            Some('\\') => self.lex_escape(start, '\'')?,
+            Some(c) if c != '\n' && c != '\'' => c,
+            _ => return Err(self.error(start, "unterminated character constant")),
+        };
+        if self.next() != Some('\'') {
+            return Err(self.error(start, "unterminated character constant"));
+        }
+        self.emit(Token::Char(c), start);
+        Ok(())
+    }
+
+    /// Unescapes a backslash sequence as Go's `strconv.Unquote` does.
+    fn lex_escape(&mut self, start: usize, quote: char) -> Result<char, TemplateError> {
+        let invalid = |lexer: &Self| lexer.error(start, "invalid syntax");
+        let c = self.next().ok_or_else(|| invalid(self))?;
+        let digits = |lexer: &mut Self, n: usize, radix: u32| -> Result<char, TemplateError> {
+            let end = lexer.pos + n;
+            let code = lexer
+                .input
+                .get(lexer.pos..end)
+                .and_then(|s| u32::from_str_radix(s, radix).ok())
+                .and_then(char::from_u32)
+                .ok_or_else(|| invalid(lexer))?;
+            lexer.pos = end;
+            Ok(code)
+        };
+        Ok(match c {
+            'a' => '\u{07}',
+            'b' => '\u{08}',
+            'f' => '\u{0c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{0b}',
+            '\\' => '\\',
+            'x' => digits(self, 2, 16)?,
+            'u' => digits(self, 4, 16)?,
+            'U' => digits(self, 8, 16)?,
+            '0'..='7' => {
+                self.pos -= 1;
+                digits(self, 3, 8)?
+            }
+            c if c == quote => c,
+            _ => return Err(invalid(self)),
+        })
+    }
+
+    fn lex_number(&mut self, start: usize) -> Result<(), TemplateError> {
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.next();
+        }
+        let (digits, exponent) = match self.rest().get(..2) {
+            Some("0x" | "0X") => ("0123456789abcdefABCDEF_", "pP"),
+            Some("0o" | "0O") => ("01234567_", ""),
+            Some("0b" | "0B") => ("01_", ""),
+            _ => ("0123456789_", "eE"),
+        };
+        if digits.len() != 11 {
+            self.pos += 2;
+        }
+        self.accept(digits);
+        if self.peek() == Some('.') {
+            self.next();
+            self.accept(digits);
+        }
+        if self.peek().is_some_and(|c| exponent.contains(c)) {
+            self.next();
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.next();
+            }
+            self.accept("0123456789_");
+        }
+        if self.peek().is_some_and(is_alphanumeric) {
+            self.next();
+            let text = &self.input[start..self.pos];
+            return Err(self.error(start, format!("bad number syntax: {text:?}")));
+        }
+        self.emit(
+            Token::Number(self.input[start..self.pos].to_string()),
+            start,
+        );
+        Ok(())
+    }
+
+    fn accept(&mut self, valid: &str) {
+        while self.peek().is_some_and(|c| valid.contains(c)) {
+            self.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<Token> {
+        lex("tpl", input)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.token)
+            .collect()
+    }
+
+    fn error(input: &str) -> String {
+        lex("tpl", input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn lexes_actions() {
+        use Token::*;
+        assert_eq!(
+            tokens(r#"a{{ .x.y | printf "%d" $v 1.5 }}b"#),
+            vec![
+                Text("a".into()),
+                LeftDelim,
+                Space,
+                Field("x".into()),
+                Field("y".into()),
+                Space,
+                Pipe,
+                Space,
+                Identifier("printf".into()),
+                Space,
+                String("%d".into()),
+                Space,
+                Variable("$v".into()),
+                Space,
+                Number("1.5".into()),
+                Space,
+                RightDelim,
+                Text("b".into()),
+                Eof,
+            ]
+        );
+        assert_eq!(
+            tokens("{{if true}}{{else if nil}}{{end}}"),
+            vec![
+                LeftDelim,
+                If,
+                Space,
+                Bool(true),
+                RightDelim,
+                LeftDelim,
+                Else,
+                Space,
+                If,
+                Space,
+                Nil,
+                RightDelim,
+                LeftDelim,
+                End,
+                RightDelim,
+                Eof,
+            ]
+        );
+        assert_eq!(
+            tokens("{{$i, $x := (.)}}"),
+            vec![
+                LeftDelim,
+                Variable("$i".into()),
+                Comma,
+                Space,
+                Variable("$x".into()),
+                Space,
+                Declare,
+                Space,
+                LeftParen,
+                Dot,
+                RightParen,
+                RightDelim,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_constants() {
+        for (input, token) in [
+            ("{{'a'}}", Token::Char('a')),
+            (r"{{'\n'}}", Token::Char('\n')),
+            (r"{{'\x41'}}", Token::Char('A')),
+            (r"{{'\101'}}", Token::Char('A')),
+            (r"{{'é'}}", Token::Char('é')),
+            (r#"{{"a\tb\"c"}}"#, Token::String("a\tb\"c".into())),
+            ("{{`a\\n\r`}}", Token::String("a\\n".into())),
+            ("{{-1}}", Token::Number("-1".into())),
+            ("{{0x1F}}", Token::Number("0x1F".into())),
+            ("{{1_000}}", Token::Number("1_000".into())),
+            ("{{1e-3}}", Token::Number("1e-3".into())),
+            ("{{.5}}", Token::Number(".5".into())),
+        ] {
+            assert_eq!(tokens(input)[1], token, "{input}");
+        }
+    }
+
+    #[test]
+    fn trims_space_and_skips_comments() {
+        assert_eq!(
+            tokens("a \n {{- 1 -}} \n b"),
+            vec![
+                Token::Text("a".into()),
+                Token::LeftDelim,
+                Token::Number("1".into()),
+                Token::RightDelim,
+                Token::Text("b".into()),
+                Token::Eof,
+            ]
+        );
+        assert_eq!(
+            tokens("a {{/* c */}} b {{- /* c */ -}} c"),
+            vec![
+                Token::Text("a ".into()),
+                Token::Text(" b".into()),
+                Token::Text("c".into()),
+                Token::Eof,
+            ]
+        );
+        // without a space, the dash belongs to a number
+        assert_eq!(tokens("{{-1}}")[1], Token::Number("-1".into()));
+    }
+
+    #[test]
+    fn reports_errors() {
+        for (input, message) in [
+            ("{{ .x", "template: tpl:1: unclosed action"),
+            ("a\n{{ .x", "template: tpl:2: unclosed action"),
+            ("{{/* c", "template: tpl:1: unclosed comment"),
+            (
+                "{{/* c */ .x }}",
+                "template: tpl:1: comment ends before closing delimiter",
+            ),
+            (r#"{{ "a }}"#, "template: tpl:1: unterminated quoted string"),
+            (
+                "{{ `a }}",
+                "template: tpl:1: unterminated raw quoted string",
+            ),
+            (
+                "{{ 'ab' }}",
+                "template: tpl:1: unterminated character constant",
+            ),
+            (r#"{{ "\q" }}"#, "template: tpl:1: invalid syntax"),
+            ("{{ 3x }}", r#"template: tpl:1: bad number syntax: "3x""#),
+            ("{{ ) }}", "template: tpl:1: unexpected right paren"),
+            (
+                "{{ # }}",
+                "template: tpl:1: unrecognized character in action: U+0023 '#'",
+            ),
+            ("{{ a# }}", "template: tpl:1: bad character U+0023 '#'"),
+            ("{{ : }}", "template: tpl:1: expected :="),
+        ] {
+            assert_eq!(error(input), message, "{input}");
+        }
+    }
+}
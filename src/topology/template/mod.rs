@@ -0,0 +1,141 @@
+//! Go `text/template` evaluation, as used by ClusterClass `enabledIf` and `valueFrom.template`.
+//!
+//! Data is converted the way upstream does before executing a template: JSON numbers become
+//! floats, so `{{ printf "%d" .replicas }}` prints `%!d(float64=3)` exactly as in the
+//! topology controller. Besides the Go builtins, the hermetic sprig functions upstream
+//! registers are available, e.g. `default`, `toJson`, `dict` and `semverCompare`, except
+//! the float math, date, crypto and url ones.
+//! Named templates (`define`, `template`, `block`) are not supported.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+mod exec;
+mod funcs;
+mod lex;
+mod parse;
+mod sprig;
+mod value;
+
+pub use value::Value;
+
+/// A parsed template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    name: String,
+    source: String,
+    root: Vec<parse::Node>,
+    missing_key_error: bool,
+}
+
+impl Template {
+    /// Parses a template. The name is only used in error messages, upstream uses `tpl`.
+    pub fn parse(
+        name: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Result<Self, TemplateError> {
+        let name = name.into();
+        let source = source.into();
+        let root = parse::parse(&name, &source)?;
+        Ok(Self {
+            name,
+            source,
+            root,
+            missing_key_error: false,
+        })
+    }
+
+    /// Fails on fields missing from maps instead of printing `<no value>`, like Go's
+    /// `Option("missingkey=error")`.
+    pub fn with_missing_key_error(mut self) -> Self {
+        self.missing_key_error = true;
+        self
+    }
+
+    /// Executes the template against the data, returning the rendered text.
+    pub fn execute(&self, data: &Value) -> Result<String, TemplateError> {
+        exec::execute(self, data)
+    }
+
+    /// The fields of the data the template refers to, as dotted paths such as
+    /// `builtin.cluster.name`; an empty path means the whole data is used, e.g. by
+    /// `{{ toJson . }}`. Fields reached through the dot of `range` and `with` or through
+    /// variables other than `$` are not followed.
+    pub fn references(&self) -> BTreeSet<String> {
+        let mut refs = BTreeSet::new();
+        parse::references(&self.root, true, &mut refs);
+        refs
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Parses and executes a template named `tpl` against JSON data.
+pub fn render(source: &str, data: &serde_json::Value) -> Result<String, TemplateError> {
+    Template::parse("tpl", source)?.execute(&data.into())
+}
+
+/// Error parsing or executing a template, formatted like Go's `text/template` errors.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub struct TemplateError {
+    /// Name of the template.
+    pub name: String,
+    /// Line of the error in the template, starting at 1.
+    pub line: usize,
+    /// Byte offset of the error in its line, starting at 0.
+    pub column: usize,
+    /// The node being executed, e.g. `.builtin.cluster.name`, for execution errors.
+    pub context: Option<String>,
+    pub message: String,
+}
+
+impl TemplateError {
+    fn at(name: &str, source: &str, pos: usize, context: Option<String>, message: String) -> Self {
+        let before = &source[..pos.min(source.len())];
+        let line = 1 + before.matches('\n').count();
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            name: name.to_string(),
+            line,
+            column,
+            context,
+            message,
+        }
+    }
+
+    pub(crate) fn parse(name: &str, source: &str, pos: usize, message: impl Into<String>) -> Self {
+        Self::at(name, source, pos, None, message.into())
+    }
+
+    pub(crate) fn exec(
+        name: &str,
+        source: &str,
+        pos: usize,
+        context: String,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::at(name, source, pos, Some(context), message.into())
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.context {
+            None => write!(f, "template: {}:{}: {}", self.name, self.line, self.message),
+            Some(context) => write!(
+                f,
+                "template: {name}:{}:{}: executing {name:?} at <{context}>: {}",
+                self.line,
+                self.column,
+                self.message,
+                name = self.name,
+            ),
+        }
+    }
+}
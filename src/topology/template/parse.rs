@@ -0,0 +1,741 @@
+//! Parser for Go templates, following `text/template/parse/parse.go`.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::funcs::is_function;
+use super::lex::{lex, Item, Token};
+use super::value::Value;
+use super::TemplateError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Node {
+    Text(String),
+    Action(Pipe),
+    If(Branch),
+    Range(Branch),
+    With(Branch),
+    Break(usize),
+    Continue(usize),
+}
+
+/// The body of an `if`, `range` or `with` action.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Branch {
+    pub pipe: Pipe,
+    pub list: Vec<Node>,
+    pub else_list: Option<Vec<Node>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Pipe {
+    pub pos: usize,
+    /// Whether the variables are assigned with `=` rather than declared with `:=`.
+    pub is_assign: bool,
+    pub decl: Vec<String>,
+    pub cmds: Vec<Command>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Command {
+    pub pos: usize,
+    pub args: Vec<Arg>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Arg {
+    /// `.a.b`
+    Field(usize, Vec<String>),
+    /// `$x.a.b`
+    Variable(usize, String, Vec<String>),
+    /// `(pipeline).a.b`
+    Chain(usize, Box<Pipe>, Vec<String>),
+    Dot(usize),
+    Nil(usize),
+    Constant(usize, Value, String),
+    Function(usize, String),
+}
+
+impl Arg {
+    pub fn pos(&self) -> usize {
+        match self {
+            Arg::Field(pos, _)
+            | Arg::Variable(pos, _, _)
+            | Arg::Chain(pos, _, _)
+            | Arg::Dot(pos)
+            | Arg::Nil(pos)
+            | Arg::Constant(pos, _, _)
+            | Arg::Function(pos, _) => *pos,
+        }
+    }
+}
+
+impl fmt::Display for Pipe {
+    /// Formats the pipeline the way Go prints it in execution errors.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.decl.is_empty() {
+            let op = if self.is_assign { "=" } else { ":=" };
+            write!(f, "{} {op} ", self.decl.join(", "))?;
+        }
+        for (i, cmd) in self.cmds.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            write!(f, "{cmd}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{arg}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arg::Field(_, fields) => f.write_str(&chain(fields)),
+            Arg::Variable(_, name, fields) => write!(f, "{name}{}", chain(fields)),
+            Arg::Chain(_, pipe, fields) => write!(f, "({pipe}){}", chain(fields)),
+            Arg::Dot(_) => f.write_str("."),
+            Arg::Nil(_) => f.write_str("nil"),
+            Arg::Constant(_, _, text) | Arg::Function(_, text) => f.write_str(text),
+        }
+    }
+}
+
+fn chain(fields: &[String]) -> String {
+    fields.iter().map(|field| format!(".{field}")).collect()
+}
+
+/// What the parser stopped at when reading a list of nodes.
+enum ListEnd {
+    End,
+    Else(usize),
+    Eof,
+}
+
+struct Parser<'a> {
+    name: &'a str,
+    source: &'a str,
+    items: Vec<Item>,
+    index: usize,
+    /// Variables in scope, innermost last.
+    vars: Vec<String>,
+    range_depth: usize,
+}
+
+/// Parses the template source into its list of nodes.
+pub(super) fn parse(name: &str, source: &str) -> Result<Vec<Node>, TemplateError> {
+    let items = lex(name, source)?;
+    let mut parser = Parser {
+        name,
+        source,
+        items,
+        index: 0,
+        vars: vec!["$".to_string()],
+        range_depth: 0,
+    };
+    let (list, end) = parser.item_list()?;
+    match end {
+        ListEnd::Eof => Ok(list),
+        ListEnd::End => Err(parser.error_at_previous("unexpected {{end}}")),
+        ListEnd::Else(_) => Err(parser.error_at_previous("unexpected {{else}}")),
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Item {
+        &self.items[self.index]
+    }
+
+    fn next(&mut self) -> Item {
+        let item = self.items[self.index].clone();
+        if item.token != Token::Eof {
+            self.index += 1;
+        }
+        item
+    }
+
+    fn next_non_space(&mut self) -> Item {
+        loop {
+            let item = self.next();
+            if item.token != Token::Space {
+                return item;
+            }
+        }
+    }
+
+    fn peek_non_space(&mut self) -> &Item {
+        while self.peek().token == Token::Space {
+            self.index += 1;
+        }
+        self.peek()
+    }
+
+    fn error(&self, pos: usize, message: impl Into<String>) -> TemplateError {
+        TemplateError::parse(self.name, self.source, pos, message)
+    }
+
+    fn error_at_previous(&self, message: impl Into<String>) -> TemplateError {
+        let pos = self.items[self.index.saturating_sub(1)].pos;
+        self.error(pos, message)
+    }
+
+    fn unexpected(&self, item: &Item, context: &str) -> TemplateError {
+        let message = match &item.token {
+            Token::Eof => format!("unexpected EOF in {context}"),
+            token => format!("unexpected {} in {context}", describe(token)),
+        };
+        self.error(item.pos, message)
+    }
+
+    fn expect_right_delim(&mut self, context: &str) -> Result<(), TemplateError> {
+        let item = self.next_non_space();
+        if item.token != Token::RightDelim {
+            return Err(self.unexpected(&item, context));
+        }
+        Ok(())
+    }
+
+    fn item_list(&mut self) -> Result<(Vec<Node>, ListEnd), TemplateError> {
+        let mut list = Vec::new();
+        loop {
+            let item = self.next();
+            match item.token {
+                Token::Eof => return Ok((list, ListEnd::Eof)),
+                Token::Text(text) => list.push(Node::Text(text)),
+                Token::LeftDelim => {
+                    let item = self.next_non_space();
+                    match item.token {
+                        Token::End => {
+                            self.expect_right_delim("end")?;
+                            return Ok((list, ListEnd::End));
+                        }
+                        Token::Else => return Ok((list, ListEnd::Else(item.pos))),
+                        _ => list.push(self.action(item)?),
+                    }
+                }
+                _ => return Err(self.unexpected(&item, "input")),
+            }
+        }
+    }
+
+    fn action(&mut self, item: Item) -> Result<Node, TemplateError> {
+        match item.token {
+            Token::If => Ok(Node::If(self.branch("if")?)),
+            Token::Range => Ok(Node::Range(self.branch("range")?)),
+            Token::With => Ok(Node::With(self.branch("with")?)),
+            Token::Break | Token::Continue => {
+                let keyword = if item.token == Token::Break {
+                    "break"
+                } else {
+                    "continue"
+                };
+                if self.range_depth == 0 {
+                    return Err(
+                        self.error(item.pos, format!("{{{{{keyword}}}}} outside {{{{range}}}}"))
+                    );
+                }
+                self.expect_right_delim(keyword)?;
+                Ok(if item.token == Token::Break {
+                    Node::Break(item.pos)
+                } else {
+                    Node::Continue(item.pos)
+                })
+            }
+            Token::Block | Token::Define | Token::Template => Err(self.error(
+                item.pos,
+                format!("{} is not supported", describe(&item.token)),
+            )),
+            _ => {
+                self.index -= 1;
+                let pipe = self.pipeline("command", Token::RightDelim)?;
+                Ok(Node::Action(pipe))
+            }
+        }
+    }
+
+    /// Parses `pipeline}} list [{{else}} list] {{end}}`, including `{{else if ...}}` chains.
+    fn branch(&mut self, context: &'static str) -> Result<Branch, TemplateError> {
+        let scope = self.vars.len();
+        let pipe = self.pipeline(context, Token::RightDelim)?;
+        // break and continue apply to the body of a range, not to its else branch
+        let in_range = usize::from(context == "range");
+        self.range_depth += in_range;
+        let items = self.item_list();
+        self.range_depth -= in_range;
+        let (list, end) = items?;
+        let else_list = match end {
+            ListEnd::End => None,
+            ListEnd::Eof => return Err(self.error(self.source.len(), "unexpected EOF")),
+            ListEnd::Else(pos) => {
+                let next = self.peek_non_space().token.clone();
+                if (context == "if" && next == Token::If)
+                    || (context == "with" && next == Token::With)
+                {
+                    // {{else if ...}} is sugar for {{else}}{{if ...}}...{{end}}{{end}}
+                    let item = self.next_non_space();
+                    let nested = self.action(item)?;
+                    Some(vec![nested])
+                } else {
+                    self.expect_right_delim("else")?;
+                    let (else_list, end) = self.item_list()?;
+                    match end {
+                        ListEnd::End => Some(else_list),
+                        ListEnd::Else(pos) => {
+                            return Err(self.error(pos, "expected end; found {{else}}"))
+                        }
+                        ListEnd::Eof => return Err(self.error(pos, "unexpected EOF")),
+                    }
+                }
+            }
+        };
+        self.vars.truncate(scope);
+        Ok(Branch {
+            pipe,
+            list,
+            else_list,
+        })
+    }
+
+    fn pipeline(&mut self, context: &str, end: Token) -> Result<Pipe, TemplateError> {
+        let pos = self.peek_non_space().pos;
+        let mut pipe = Pipe {
+            pos,
+            is_assign: false,
+            decl: Vec::new(),
+            cmds: Vec::new(),
+        };
+        self.declarations(&mut pipe, context)?;
+        loop {
+            let item = self.peek_non_space().clone();
+            if item.token == end {
+                self.next();
+                if pipe.cmds.is_empty() {
+                    return Err(self.error(item.pos, format!("missing value for {context}")));
+                }
+                // only the first command of a pipeline can be a constant
+                for (i, cmd) in pipe.cmds.iter().enumerate().skip(1) {
+                    if let Arg::Constant(..) | Arg::Dot(_) | Arg::Nil(_) = cmd.args[0] {
+                        return Err(self.error(
+                            cmd.pos,
+                            format!("non executable command in pipeline stage {}", i + 1),
+                        ));
+                    }
+                }
+                return Ok(pipe);
+            }
+            match item.token {
+                Token::Bool(_)
+                | Token::Char(_)
+                | Token::Dot
+                | Token::Field(_)
+                | Token::Identifier(_)
+                | Token::Number(_)
+                | Token::Nil
+                | Token::String(_)
+                | Token::Variable(_)
+                | Token::LeftParen => {
+                    let cmd = self.command()?;
+                    pipe.cmds.push(cmd);
+                }
+                _ => return Err(self.unexpected(&item, context)),
+            }
+        }
+    }
+
+    /// Parses `$x :=`, `$x =` and, in range, `$i, $v :=`.
+    fn declarations(&mut self, pipe: &mut Pipe, context: &str) -> Result<(), TemplateError> {
+        let start = self.index;
+        let Token::Variable(first) = self.peek_non_space().token.clone() else {
+            return Ok(());
+        };
+        self.next();
+        let mut names = vec![first];
+        let mut item = self.next_non_space();
+        if item.token == Token::Comma && context != "range" {
+            return Err(self.error(item.pos, format!("too many declarations in {context}")));
+        }
+        if item.token == Token::Comma {
+            let second = self.next_non_space();
+            let Token::Variable(name) = second.token else {
+                return Err(self.error(second.pos, "range can only initialize variables"));
+            };
+            names.push(name);
+            item = self.next_non_space();
+            if item.token != Token::Declare && item.token != Token::Assign {
+                return Err(self.error(item.pos, "range can only initialize variables"));
+            }
+        }
+        match item.token {
+            Token::Declare => {
+                self.vars.extend(names.iter().cloned());
+                pipe.decl = names;
+            }
+            Token::Assign => {
+                for name in &names {
+                    self.check_variable(item.pos, name)?;
+                }
+                pipe.is_assign = true;
+                pipe.decl = names;
+            }
+            _ => {
+                // a plain variable reference, not a declaration
+                self.index = start;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_variable(&self, pos: usize, name: &str) -> Result<(), TemplateError> {
+        if self.vars.iter().any(|v| v == name) {
+            Ok(())
+        } else {
+            Err(self.error(pos, format!("undefined variable {name:?}")))
+        }
+    }
+
+    fn command(&mut self) -> Result<Command, TemplateError> {
+        let pos = self.peek_non_space().pos;
+        let mut args = Vec::new();
+        loop {
+            self.peek_non_space();
+            if let Some(arg) = self.operand()? {
+                args.push(arg);
+            }
+            let item = self.peek().clone();
+            match item.token {
+                Token::Space => continue,
+                Token::RightDelim | Token::RightParen => break,
+                Token::Pipe => {
+                    self.next();
+                    break;
+                }
+                _ => return Err(self.unexpected(&item, "operand")),
+            }
+        }
+        if args.is_empty() {
+            return Err(self.error(pos, "empty command"));
+        }
+        Ok(Command { pos, args })
+    }
+
+    fn operand(&mut self) -> Result<Option<Arg>, TemplateError> {
+        let Some(term) = self.term()? else {
+            return Ok(None);
+        };
+        let mut chain = Vec::new();
+        while let Token::Field(name) = &self.peek().token {
+            chain.push(name.clone());
+            self.next();
+        }
+        if chain.is_empty() {
+            return Ok(Some(term));
+        }
+        Ok(Some(match term {
+            Arg::Field(pos, mut fields) => {
+                fields.extend(chain);
+                Arg::Field(pos, fields)
+            }
+            Arg::Variable(pos, name, mut fields) => {
+                fields.extend(chain);
+                Arg::Variable(pos, name, fields)
+            }
+            Arg::Chain(pos, pipe, mut fields) => {
+                fields.extend(chain);
+                Arg::Chain(pos, pipe, fields)
+            }
+            Arg::Dot(pos) => return Err(self.error(pos, "unexpected . after term \".\"")),
+            Arg::Nil(pos) => return Err(self.error(pos, "nil is not a command")),
+            Arg::Constant(pos, _, text) | Arg::Function(pos, text) => {
+                return Err(self.error(pos, format!("unexpected . after term {text:?}")))
+            }
+        }))
+    }
+
+    fn term(&mut self) -> Result<Option<Arg>, TemplateError> {
+        let item = self.next();
+        let pos = item.pos;
+        Ok(Some(match item.token {
+            Token::Identifier(name) => {
+                if !is_function(&name) {
+                    return Err(self.error(pos, format!("function {name:?} not defined")));
+                }
+                Arg::Function(pos, name)
+            }
+            Token::Dot => Arg::Dot(pos),
+            Token::Nil => Arg::Nil(pos),
+            Token::Variable(name) => {
+                self.check_variable(pos, &name)?;
+                Arg::Variable(pos, name, Vec::new())
+            }
+            Token::Field(name) => Arg::Field(pos, vec![name]),
+            Token::Bool(b) => Arg::Constant(pos, Value::Bool(b), b.to_string()),
+            Token::Char(c) => Arg::Constant(pos, Value::Int(c as i64), format!("{c:?}")),
+            Token::Number(text) => {
+                let value = number(&text).map_err(|message| self.error(pos, message))?;
+                Arg::Constant(pos, value, text)
+            }
+            Token::String(s) => Arg::Constant(pos, Value::String(s.clone()), format!("{s:?}")),
+            Token::LeftParen => {
+                let pipe = self.pipeline("parenthesized pipeline", Token::RightParen)?;
+                Arg::Chain(pos, Box::new(pipe), Vec::new())
+            }
+            _ => {
+                self.index -= 1;
+                return Ok(None);
+            }
+        }))
+    }
+}
+
+/// Converts a number constant the way Go converts an ideal constant to `interface{}`:
+/// integers become `int`, everything with a fraction or exponent becomes `float64`.
+fn number(text: &str) -> Result<Value, String> {
+    let clean = text.replace('_', "");
+    let (negative, unsigned) = match clean.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, clean.strip_prefix('+').unwrap_or(&clean)),
+    };
+    let lower = unsigned.to_ascii_lowercase();
+    let radix = match lower.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => None,
+    };
+    let int = match radix {
+        Some(radix) if !lower.contains('.') && !lower.contains('p') => {
+            i64::from_str_radix(&lower[2..], radix).ok()
+        }
+        Some(_) => None,
+        None if !lower.contains(['.', 'e']) => {
+            if lower.len() > 1 && lower.starts_with('0') {
+                i64::from_str_radix(&lower[1..], 8).ok()
+            } else {
+                lower.parse().ok()
+            }
+        }
+        None => None,
+    };
+    if let Some(i) = int {
+        return Ok(Value::Int(if negative { -i } else { i }));
+    }
+    match lower.parse::<f64>() {
+        // an integer Go cannot parse as int, e.g. too large or an invalid octal like 09
+        Ok(_) if radix.is_none() && !lower.contains(['.', 'e']) => {
+            Err(format!("integer overflow: {text:?}"))
+        }
+        Ok(f) if radix.is_none() => Ok(Value::Float(if negative { -f } else { f })),
+        _ => Err(format!("illegal number syntax: {text:?}")),
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Text(_) => "text".to_string(),
+        Token::LeftDelim => "\"{{\"".to_string(),
+        Token::RightDelim => "\"}}\"".to_string(),
+        Token::Space => "space".to_string(),
+        Token::Bool(b) => format!("{b:?}"),
+        Token::Char(c) => format!("{c:?}"),
+        Token::Number(n) => format!("{n:?}"),
+        Token::String(s) => format!("{s:?}"),
+        Token::Field(f) => format!("\".{f}\""),
+        Token::Identifier(i) => format!("{i:?}"),
+        Token::Variable(v) => format!("{v:?}"),
+        Token::Dot => "\".\"".to_string(),
+        Token::Pipe => "\"|\"".to_string(),
+        Token::LeftParen => "\"(\"".to_string(),
+        Token::RightParen => "\")\"".to_string(),
+        Token::Declare => "\":=\"".to_string(),
+        Token::Assign => "\"=\"".to_string(),
+        Token::Comma => "\",\"".to_string(),
+        Token::Nil => "<nil>".to_string(),
+        Token::Block => "<block>".to_string(),
+        Token::Break => "<break>".to_string(),
+        Token::Continue => "<continue>".to_string(),
+        Token::Define => "<define>".to_string(),
+        Token::Else => "<else>".to_string(),
+        Token::End => "<end>".to_string(),
+        Token::If => "<if>".to_string(),
+        Token::Range => "<range>".to_string(),
+        Token::Template => "<template>".to_string(),
+        Token::With => "<with>".to_string(),
+        Token::Eof => "EOF".to_string(),
+    }
+}
+
+/// Collects the fields of the data referenced by the nodes while dot is the data, see
+/// [`Template::references`](super::Template::references).
+pub(super) fn references(nodes: &[Node], root: bool, refs: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) | Node::Break(_) | Node::Continue(_) => {}
+            Node::Action(pipe) => pipe_references(pipe, root, refs),
+            Node::If(branch) | Node::Range(branch) | Node::With(branch) => {
+                pipe_references(&branch.pipe, root, refs);
+                // range and with move dot, their else branches keep it
+                let moves_dot = !matches!(node, Node::If(_));
+                references(&branch.list, root && !moves_dot, refs);
+                if let Some(else_list) = &branch.else_list {
+                    references(else_list, root, refs);
+                }
+            }
+        }
+    }
+}
+
+fn pipe_references(pipe: &Pipe, root: bool, refs: &mut BTreeSet<String>) {
+    for arg in pipe.cmds.iter().flat_map(|cmd| &cmd.args) {
+        match arg {
+            Arg::Field(_, fields) if root => {
+                refs.insert(fields.join("."));
+            }
+            Arg::Dot(_) if root => {
+                refs.insert(String::new());
+            }
+            Arg::Variable(_, name, fields) if name == "$" => {
+                refs.insert(fields.join("."));
+            }
+            Arg::Chain(_, pipe, _) => pipe_references(pipe, root, refs),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::template::Template;
+
+    fn error(source: &str) -> String {
+        Template::parse("tpl", source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reports_errors() {
+        for (source, message) in [
+            ("{{ end }}", "unexpected {{end}}"),
+            ("{{ else }}", "unexpected {{else}}"),
+            ("{{ if true }}", "unexpected EOF"),
+            ("{{ if true }}{{ else }}", "unexpected EOF"),
+            (
+                "{{ if true }}{{ else }}{{ else }}{{ end }}",
+                "expected end; found {{else}}",
+            ),
+            ("{{ }}", "missing value for command"),
+            ("{{ if }}{{ end }}", "missing value for if"),
+            ("{{ foo }}", r#"function "foo" not defined"#),
+            ("{{ $x }}", r#"undefined variable "$x""#),
+            ("{{ $x = 1 }}", r#"undefined variable "$x""#),
+            (
+                "{{ if true }}{{ $x := 1 }}{{ end }}{{ $x }}",
+                r#"undefined variable "$x""#,
+            ),
+            ("{{ break }}", "{{break}} outside {{range}}"),
+            (
+                "{{ range . }}{{ else }}{{ continue }}{{ end }}",
+                "{{continue}} outside {{range}}",
+            ),
+            ("{{ 1 | 2 }}", "non executable command in pipeline stage 2"),
+            (
+                "{{ len | . }}",
+                "non executable command in pipeline stage 2",
+            ),
+            ("{{ .x 1 = }}", r#"unexpected "=" in operand"#),
+            ("{{ $x, $y := . }}", "too many declarations in command"),
+            (
+                "{{ range $x, 1 := . }}{{ end }}",
+                "range can only initialize variables",
+            ),
+            ("{{ 1.x }}", r#"bad number syntax: "1.x""#),
+            ("{{ 09 }}", r#"integer overflow: "09""#),
+            ("{{ \"a\".x }}", r#"unexpected . after term "\"a\"""#),
+            ("{{ define \"x\" }}{{ end }}", "<define> is not supported"),
+            ("{{ template \"x\" }}", "<template> is not supported"),
+        ] {
+            assert_eq!(
+                error(source),
+                format!("template: tpl:1: {message}"),
+                "{source}"
+            );
+        }
+        assert_eq!(
+            error("a\n\n{{ if true }}\n"),
+            "template: tpl:4: unexpected EOF"
+        );
+    }
+
+    #[test]
+    fn converts_numbers_like_go_constants() {
+        for (text, value) in [
+            ("0", Value::Int(0)),
+            ("42", Value::Int(42)),
+            ("-2", Value::Int(-2)),
+            ("+2", Value::Int(2)),
+            ("1_000", Value::Int(1000)),
+            ("017", Value::Int(15)),
+            ("0o17", Value::Int(15)),
+            ("0x1F", Value::Int(31)),
+            ("0b101", Value::Int(5)),
+            ("1.5", Value::Float(1.5)),
+            ("-.5", Value::Float(-0.5)),
+            ("1e3", Value::Float(1000.0)),
+        ] {
+            assert_eq!(number(text), Ok(value), "{text}");
+        }
+        assert_eq!(
+            number("99999999999999999999"),
+            Err(r#"integer overflow: "99999999999999999999""#.into())
+        );
+    }
+
+    #[test]
+    fn prints_pipelines_like_go() {
+        let root = parse(
+            "tpl",
+            r#"{{ $x := .a.b | printf "%s-%d" 'c' 1 | len }}{{ (index $.m "k").f }}"#,
+        )
+        .unwrap();
+        let [Node::Action(first), Node::Action(second)] = root.as_slice() else {
+            panic!("{root:?}");
+        };
+        assert_eq!(
+            first.to_string(),
+            r#"$x := .a.b | printf "%s-%d" 'c' 1 | len"#
+        );
+        assert_eq!(second.to_string(), r#"(index $.m "k").f"#);
+    }
+
+    #[test]
+    fn collects_references() {
+        let template = Template::parse(
+            "tpl",
+            "{{ if .a.b }}{{ .c }}{{ end }}\
+             {{ range .l }}{{ .x }}{{ $.d }}{{ else }}{{ .e }}{{ end }}\
+             {{ with .w }}{{ .y }}{{ end }}\
+             {{ $v := .v }}{{ $v.z }}{{ toJson (dict \"k\" .k) }}",
+        )
+        .unwrap();
+        assert_eq!(
+            template.references().into_iter().collect::<Vec<_>>(),
+            ["a.b", "c", "d", "e", "k", "l", "v", "w"]
+        );
+        assert_eq!(
+            Template::parse("tpl", "{{ toJson . }}")
+                .unwrap()
+                .references(),
+            [String::new()].into()
+        );
+    }
+}
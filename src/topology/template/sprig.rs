@@ -0,0 +1,1729 @@
+//! The sprig functions of `sprig.HermeticTxtFuncMap`, which upstream registers for ClusterClass
+//! templates. Functions keep sprig's lenient conversions, e.g. `int "x"` is `0`, while
+//! arguments Go passes to typed parameters are checked like `text/template` does. As in Go,
+//! they take precedence over the builtins, so `slice` is sprig's, which only slices lists.
+//!
+//! Not available are the float math functions (`addf`, `maxf`, ...), which need decimal
+//! arithmetic to match, the date, crypto and certificate functions, `semver` and the url, os
+//! path, `wrap` and `duration` functions.
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use base64::Engine;
+use regex::Regex;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use super::funcs::{at_least, exactly, quote, Func, FuncError};
+use super::value::Value;
+
+const FUNCS: &[(&str, Func)] = &[
+    // strings
+    ("upper", |args| {
+        Ok(string_arg(exactly(args, 1)?, 0)?.to_uppercase().into())
+    }),
+    ("lower", |args| {
+        Ok(string_arg(exactly(args, 1)?, 0)?.to_lowercase().into())
+    }),
+    ("title", |args| {
+        Ok(title(string_arg(exactly(args, 1)?, 0)?).into())
+    }),
+    ("untitle", |args| {
+        Ok(untitle(string_arg(exactly(args, 1)?, 0)?).into())
+    }),
+    ("trim", |args| {
+        Ok(string_arg(exactly(args, 1)?, 0)?.trim().into())
+    }),
+    ("trimAll", |args| {
+        let args = exactly(args, 2)?;
+        let cutset = string_arg(args, 0)?;
+        Ok(string_arg(args, 1)?
+            .trim_matches(|c| cutset.contains(c))
+            .into())
+    }),
+    ("trimPrefix", |args| {
+        let args = exactly(args, 2)?;
+        let s = string_arg(args, 1)?;
+        Ok(s.strip_prefix(string_arg(args, 0)?).unwrap_or(s).into())
+    }),
+    ("trimSuffix", |args| {
+        let args = exactly(args, 2)?;
+        let s = string_arg(args, 1)?;
+        Ok(s.strip_suffix(string_arg(args, 0)?).unwrap_or(s).into())
+    }),
+    ("contains", |args| {
+        let args = exactly(args, 2)?;
+        Ok(string_arg(args, 1)?.contains(string_arg(args, 0)?).into())
+    }),
+    ("hasPrefix", |args| {
+        let args = exactly(args, 2)?;
+        Ok(string_arg(args, 1)?
+            .starts_with(string_arg(args, 0)?)
+            .into())
+    }),
+    ("hasSuffix", |args| {
+        let args = exactly(args, 2)?;
+        Ok(string_arg(args, 1)?.ends_with(string_arg(args, 0)?).into())
+    }),
+    ("replace", |args| {
+        let args = exactly(args, 3)?;
+        let (old, new) = (string_arg(args, 0)?, string_arg(args, 1)?);
+        Ok(string_arg(args, 2)?.replace(old, new).into())
+    }),
+    ("repeat", |args| {
+        let args = exactly(args, 2)?;
+        let count = int_arg(args, 0)?;
+        if count < 0 {
+            return Err("strings: negative Repeat count".into());
+        }
+        Ok(string_arg(args, 1)?.repeat(count as usize).into())
+    }),
+    ("substr", |args| {
+        let args = exactly(args, 3)?;
+        let (start, end) = (int_arg(args, 0)?, int_arg(args, 1)?);
+        Ok(substr(string_arg(args, 2)?, start, end)?.into())
+    }),
+    ("trunc", |args| {
+        let args = exactly(args, 2)?;
+        Ok(trunc(string_arg(args, 1)?, int_arg(args, 0)?)?.into())
+    }),
+    ("nospace", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(s.chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .into())
+    }),
+    ("trimall", |args| {
+        let args = exactly(args, 2)?;
+        let cutset = string_arg(args, 0)?;
+        Ok(string_arg(args, 1)?
+            .trim_matches(|c| cutset.contains(c))
+            .into())
+    }),
+    ("abbrev", |args| {
+        let args = exactly(args, 2)?;
+        let (width, s) = (int_arg(args, 0)?, string_arg(args, 1)?);
+        if width < 4 {
+            return Ok(s.into());
+        }
+        Ok(abbreviate(s, 0, width as usize).into())
+    }),
+    ("abbrevboth", |args| {
+        let args = exactly(args, 3)?;
+        let (left, right) = (int_arg(args, 0)?, int_arg(args, 1)?);
+        let s = string_arg(args, 2)?;
+        if right < 4 || (left > 0 && right < 7) {
+            return Ok(s.into());
+        }
+        Ok(abbreviate(s, left.max(0) as usize, right as usize).into())
+    }),
+    ("initials", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(s.split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .collect::<String>()
+            .into())
+    }),
+    ("swapcase", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        let swapped = s.chars().flat_map(|c| -> Box<dyn Iterator<Item = char>> {
+            if c.is_uppercase() {
+                Box::new(c.to_lowercase())
+            } else {
+                Box::new(c.to_uppercase())
+            }
+        });
+        Ok(swapped.collect::<String>().into())
+    }),
+    ("snakecase", |args| {
+        Ok(words(string_arg(exactly(args, 1)?, 0)?, '_').into())
+    }),
+    ("kebabcase", |args| {
+        Ok(words(string_arg(exactly(args, 1)?, 0)?, '-').into())
+    }),
+    ("camelcase", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(s.split([' ', '_', '-'])
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect::<String>()
+            .into())
+    }),
+    ("plural", |args| {
+        let args = exactly(args, 3)?;
+        let (one, many) = (string_arg(args, 0)?, string_arg(args, 1)?);
+        Ok(if int_arg(args, 2)? == 1 { one } else { many }.into())
+    }),
+    ("quote", |args| {
+        let quoted: Vec<_> = non_nil(args).map(|v| quote(&strval(v))).collect();
+        Ok(quoted.join(" ").into())
+    }),
+    ("squote", |args| {
+        let quoted: Vec<_> = non_nil(args).map(|v| format!("'{}'", strval(v))).collect();
+        Ok(quoted.join(" ").into())
+    }),
+    ("cat", |args| {
+        let all: Vec<_> = non_nil(args).map(strval).collect();
+        Ok(all.join(" ").into())
+    }),
+    ("indent", |args| {
+        let args = exactly(args, 2)?;
+        Ok(indent(int_arg(args, 0)?, string_arg(args, 1)?).into())
+    }),
+    ("nindent", |args| {
+        let args = exactly(args, 2)?;
+        Ok(format!("\n{}", indent(int_arg(args, 0)?, string_arg(args, 1)?)).into())
+    }),
+    ("splitList", |args| {
+        let args = exactly(args, 2)?;
+        let (sep, s) = (string_arg(args, 0)?, string_arg(args, 1)?);
+        Ok(Value::List(go_split(s, sep).map(Value::from).collect()))
+    }),
+    ("split", |args| {
+        let args = exactly(args, 2)?;
+        let (sep, s) = (string_arg(args, 0)?, string_arg(args, 1)?);
+        let parts = go_split(s, sep)
+            .enumerate()
+            .map(|(i, part)| (format!("_{i}"), part.into()))
+            .collect();
+        Ok(Value::Map(parts))
+    }),
+    ("splitn", |args| {
+        let args = exactly(args, 3)?;
+        let (sep, n, s) = (
+            string_arg(args, 0)?,
+            int_arg(args, 1)?,
+            string_arg(args, 2)?,
+        );
+        let parts: Vec<&str> = match n {
+            0 => Vec::new(),
+            n if n < 0 => go_split(s, sep).collect(),
+            n if sep.is_empty() => {
+                let n = n as usize;
+                let mut chars: Vec<&str> = go_split(s, sep).collect();
+                if chars.len() > n {
+                    let rest = chars[n - 1..].concat().len();
+                    chars.truncate(n - 1);
+                    chars.push(&s[s.len() - rest..]);
+                }
+                chars
+            }
+            n => s.splitn(n as usize, sep).collect(),
+        };
+        let parts = parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, part)| (format!("_{i}"), part.into()))
+            .collect();
+        Ok(Value::Map(parts))
+    }),
+    ("join", |args| {
+        let args = exactly(args, 2)?;
+        Ok(strslice(&args[1]).join(string_arg(args, 0)?).into())
+    }),
+    ("toString", |args| Ok(strval(&exactly(args, 1)?[0]).into())),
+    ("toStrings", |args| {
+        let strings = strslice(&exactly(args, 1)?[0]);
+        Ok(Value::List(strings.into_iter().map(Value::from).collect()))
+    }),
+    ("sortAlpha", |args| {
+        let mut strings = strslice(&exactly(args, 1)?[0]);
+        strings.sort();
+        Ok(Value::List(strings.into_iter().map(Value::from).collect()))
+    }),
+    // defaults
+    ("default", |args| {
+        let args = at_least(args, 1)?;
+        match args.get(1) {
+            Some(given) if !empty(given) => Ok(given.clone()),
+            _ => Ok(args[0].clone()),
+        }
+    }),
+    ("empty", |args| Ok(empty(&exactly(args, 1)?[0]).into())),
+    ("coalesce", |args| {
+        Ok(args
+            .iter()
+            .find(|v| !empty(v))
+            .cloned()
+            .unwrap_or(Value::Nil))
+    }),
+    ("all", |args| Ok(args.iter().all(|v| !empty(v)).into())),
+    ("any", |args| Ok(args.iter().any(|v| !empty(v)).into())),
+    ("ternary", |args| {
+        let args = exactly(args, 3)?;
+        let Value::Bool(condition) = args[2] else {
+            return Err(wrong_type(&args[2], "bool"));
+        };
+        Ok(args[usize::from(!condition)].clone())
+    }),
+    ("fail", |args| {
+        Err(FuncError::Call(
+            string_arg(exactly(args, 1)?, 0)?.to_string(),
+        ))
+    }),
+    // encoding
+    ("toJson", |args| {
+        Ok(to_json(&exactly(args, 1)?[0], false, true).into())
+    }),
+    ("toPrettyJson", |args| {
+        Ok(to_json(&exactly(args, 1)?[0], true, true).into())
+    }),
+    ("toRawJson", |args| {
+        Ok(to_json(&exactly(args, 1)?[0], false, false).into())
+    }),
+    ("fromJson", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(serde_json::from_str::<serde_json::Value>(s).map_or(Value::Nil, Value::from))
+    }),
+    ("mustFromJson", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        serde_json::from_str::<serde_json::Value>(s)
+            .map(Value::from)
+            .map_err(|err| err.to_string().into())
+    }),
+    ("b64enc", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(s).into())
+    }),
+    ("b64dec", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(match base64::engine::general_purpose::STANDARD.decode(s) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned().into(),
+            // sprig returns the error message instead of failing
+            Err(err) => format!("illegal base64 data: {err}").into(),
+        })
+    }),
+    ("sha1sum", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(hex(&Sha1::digest(s.as_bytes())).into())
+    }),
+    ("sha256sum", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(hex(&Sha256::digest(s.as_bytes())).into())
+    }),
+    ("sha512sum", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(hex(&Sha512::digest(s.as_bytes())).into())
+    }),
+    ("adler32sum", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(adler32(s.as_bytes()).to_string().into())
+    }),
+    ("b32enc", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(base32_encode(s.as_bytes()).into())
+    }),
+    ("b32dec", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        Ok(match base32_decode(s) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned().into(),
+            // sprig returns the error message instead of failing
+            Err(at) => format!("illegal base32 data at input byte {at}").into(),
+        })
+    }),
+    // lists
+    ("list", |args| Ok(Value::List(args.to_vec()))),
+    ("append", push),
+    ("push", push),
+    ("prepend", |args| {
+        let args = exactly(args, 2)?;
+        let mut list = list_arg(&args[0], "prepend on")?;
+        list.insert(0, args[1].clone());
+        Ok(Value::List(list))
+    }),
+    ("first", |args| {
+        Ok(list_arg(&exactly(args, 1)?[0], "find first on")?
+            .first()
+            .cloned()
+            .unwrap_or(Value::Nil))
+    }),
+    ("last", |args| {
+        Ok(list_arg(&exactly(args, 1)?[0], "find last on")?
+            .last()
+            .cloned()
+            .unwrap_or(Value::Nil))
+    }),
+    ("rest", |args| {
+        let list = list_arg(&exactly(args, 1)?[0], "find rest on")?;
+        Ok(if list.is_empty() {
+            Value::Nil
+        } else {
+            Value::List(list[1..].to_vec())
+        })
+    }),
+    ("initial", |args| {
+        let list = list_arg(&exactly(args, 1)?[0], "find initial on")?;
+        Ok(if list.is_empty() {
+            Value::Nil
+        } else {
+            Value::List(list[..list.len() - 1].to_vec())
+        })
+    }),
+    ("reverse", |args| {
+        let mut list = list_arg(&exactly(args, 1)?[0], "find reverse on")?;
+        list.reverse();
+        Ok(Value::List(list))
+    }),
+    ("uniq", |args| {
+        let list = list_arg(&exactly(args, 1)?[0], "find uniq on")?;
+        let mut unique: Vec<Value> = Vec::new();
+        for item in list {
+            if !unique.contains(&item) {
+                unique.push(item);
+            }
+        }
+        Ok(Value::List(unique))
+    }),
+    ("without", |args| {
+        let args = at_least(args, 1)?;
+        let list = list_arg(&args[0], "find without on")?;
+        Ok(Value::List(
+            list.into_iter()
+                .filter(|item| !args[1..].contains(item))
+                .collect(),
+        ))
+    }),
+    ("has", |args| {
+        let args = exactly(args, 2)?;
+        Ok(match &args[1] {
+            Value::Missing | Value::Nil => false,
+            haystack => list_arg(haystack, "find has on")?.contains(&args[0]),
+        }
+        .into())
+    }),
+    ("compact", |args| {
+        let list = list_arg(&exactly(args, 1)?[0], "compact on")?;
+        Ok(Value::List(
+            list.into_iter().filter(|item| !empty(item)).collect(),
+        ))
+    }),
+    ("concat", |args| {
+        let mut all = Vec::new();
+        for list in args {
+            all.extend(list_arg(list, "concat type")?);
+        }
+        Ok(Value::List(all))
+    }),
+    ("until", |args| {
+        let n = int_arg(exactly(args, 1)?, 0)?;
+        let step = if n < 0 { -1 } else { 1 };
+        Ok(Value::List(
+            (0..n.abs()).map(|i| Value::Int(i * step)).collect(),
+        ))
+    }),
+    ("untilStep", |args| {
+        let args = exactly(args, 3)?;
+        let (start, stop, step) = (int_arg(args, 0)?, int_arg(args, 1)?, int_arg(args, 2)?);
+        Ok(Value::List(
+            until_step(start, stop, step)
+                .into_iter()
+                .map(Value::Int)
+                .collect(),
+        ))
+    }),
+    ("seq", |args| {
+        let mut params = Vec::with_capacity(args.len());
+        for i in 0..args.len() {
+            params.push(int_arg(args, i)?);
+        }
+        let ints = match params[..] {
+            [end] => {
+                let increment = if end < 1 { -1 } else { 1 };
+                until_step(1, end + increment, increment)
+            }
+            [start, end] => {
+                let step = if end < start { -1 } else { 1 };
+                until_step(start, end + step, step)
+            }
+            [start, step, end] => {
+                let increment = if end < start { -1 } else { 1 };
+                if end < start && step > 0 {
+                    Vec::new()
+                } else {
+                    until_step(start, end + increment, step)
+                }
+            }
+            _ => Vec::new(),
+        };
+        let ints: Vec<_> = ints.iter().map(i64::to_string).collect();
+        Ok(ints.join(" ").into())
+    }),
+    ("chunk", |args| {
+        let args = exactly(args, 2)?;
+        let size = int_arg(args, 0)?;
+        let list = list_arg(&args[1], "chunk")?;
+        if size <= 0 {
+            return Err("runtime error: integer divide by zero".into());
+        }
+        Ok(Value::List(
+            list.chunks(size as usize)
+                .map(|chunk| Value::List(chunk.to_vec()))
+                .collect(),
+        ))
+    }),
+    ("slice", |args| {
+        let args = at_least(args, 1)?;
+        let list = match &args[0] {
+            Value::List(list) => list,
+            value => {
+                return Err(format!(
+                    "list should be type of slice or array but {}",
+                    kind_of(value)
+                )
+                .into())
+            }
+        };
+        if list.is_empty() {
+            return Ok(Value::Nil);
+        }
+        let start = args.get(1).map_or(0, to_int64);
+        let end = args.get(2).map_or(list.len() as i64, to_int64);
+        if start < 0 || end < start || end as usize > list.len() {
+            return Err("reflect.Value.Slice: slice index out of bounds".into());
+        }
+        Ok(Value::List(list[start as usize..end as usize].to_vec()))
+    }),
+    // dicts
+    ("dict", |args| {
+        let mut dict = BTreeMap::new();
+        for pair in args.chunks(2) {
+            let value = pair.get(1).cloned().unwrap_or_else(|| "".into());
+            dict.insert(strval(&pair[0]), value);
+        }
+        Ok(Value::Map(dict))
+    }),
+    ("get", |args| {
+        let args = exactly(args, 2)?;
+        let dict = dict_arg(args, 0)?;
+        Ok(dict
+            .get(string_arg(args, 1)?)
+            .cloned()
+            .unwrap_or_else(|| "".into()))
+    }),
+    ("set", |args| {
+        let args = exactly(args, 3)?;
+        let mut dict = dict_arg(args, 0)?.clone();
+        dict.insert(string_arg(args, 1)?.to_string(), args[2].clone());
+        Ok(Value::Map(dict))
+    }),
+    ("unset", |args| {
+        let args = exactly(args, 2)?;
+        let mut dict = dict_arg(args, 0)?.clone();
+        dict.remove(string_arg(args, 1)?);
+        Ok(Value::Map(dict))
+    }),
+    ("hasKey", |args| {
+        let args = exactly(args, 2)?;
+        Ok(dict_arg(args, 0)?.contains_key(string_arg(args, 1)?).into())
+    }),
+    ("keys", |args| {
+        let mut keys = Vec::new();
+        for i in 0..args.len() {
+            keys.extend(dict_arg(args, i)?.keys().map(|k| Value::from(k.as_str())));
+        }
+        Ok(Value::List(keys))
+    }),
+    ("values", |args| {
+        Ok(Value::List(
+            dict_arg(exactly(args, 1)?, 0)?.values().cloned().collect(),
+        ))
+    }),
+    ("pluck", |args| {
+        let args = at_least(args, 1)?;
+        let name = string_arg(args, 0)?;
+        let mut values = Vec::new();
+        for i in 1..args.len() {
+            values.extend(dict_arg(args, i)?.get(name).cloned());
+        }
+        Ok(Value::List(values))
+    }),
+    ("pick", |args| {
+        let args = at_least(args, 1)?;
+        let dict = dict_arg(args, 0)?;
+        let keys: Vec<_> = args[1..].iter().map(strval).collect();
+        Ok(Value::Map(
+            dict.iter()
+                .filter(|(k, _)| keys.contains(k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        ))
+    }),
+    ("omit", |args| {
+        let args = at_least(args, 1)?;
+        let dict = dict_arg(args, 0)?;
+        let keys: Vec<_> = args[1..].iter().map(strval).collect();
+        Ok(Value::Map(
+            dict.iter()
+                .filter(|(k, _)| !keys.contains(k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        ))
+    }),
+    ("merge", |args| merge(args, false)),
+    ("mergeOverwrite", |args| merge(args, true)),
+    ("deepCopy", |args| Ok(exactly(args, 1)?[0].clone())),
+    ("dig", |args| {
+        let args = at_least(args, 3)?;
+        let (keys, rest) = args.split_at(args.len() - 2);
+        let mut current = dict_arg(rest, 1)?;
+        for i in 0..keys.len() {
+            let key = string_arg(keys, i)?;
+            match current.get(key) {
+                Some(Value::Map(map)) if i + 1 < keys.len() => current = map,
+                Some(value) if i + 1 == keys.len() => return Ok(value.clone()),
+                _ => return Ok(rest[0].clone()),
+            }
+        }
+        Ok(rest[0].clone())
+    }),
+    // math
+    ("add", |args| {
+        Ok(Value::Int(
+            args.iter().map(to_int64).fold(0i64, i64::wrapping_add),
+        ))
+    }),
+    ("add1", |args| {
+        Ok(Value::Int(to_int64(&exactly(args, 1)?[0]).wrapping_add(1)))
+    }),
+    ("sub", |args| {
+        let args = exactly(args, 2)?;
+        Ok(Value::Int(
+            to_int64(&args[0]).wrapping_sub(to_int64(&args[1])),
+        ))
+    }),
+    ("mul", |args| {
+        let args = at_least(args, 1)?;
+        Ok(Value::Int(
+            args.iter().map(to_int64).fold(1i64, i64::wrapping_mul),
+        ))
+    }),
+    ("div", |args| {
+        let args = exactly(args, 2)?;
+        to_int64(&args[0])
+            .checked_div(to_int64(&args[1]))
+            .map(Value::Int)
+            .ok_or_else(|| "runtime error: integer divide by zero".into())
+    }),
+    ("mod", |args| {
+        let args = exactly(args, 2)?;
+        to_int64(&args[0])
+            .checked_rem(to_int64(&args[1]))
+            .map(Value::Int)
+            .ok_or_else(|| "runtime error: integer divide by zero".into())
+    }),
+    ("biggest", |args| {
+        let args = at_least(args, 1)?;
+        Ok(Value::Int(
+            args.iter().map(to_int64).max().unwrap_or_default(),
+        ))
+    }),
+    ("max", |args| {
+        let args = at_least(args, 1)?;
+        Ok(Value::Int(
+            args.iter().map(to_int64).max().unwrap_or_default(),
+        ))
+    }),
+    ("min", |args| {
+        let args = at_least(args, 1)?;
+        Ok(Value::Int(
+            args.iter().map(to_int64).min().unwrap_or_default(),
+        ))
+    }),
+    ("floor", |args| {
+        Ok(Value::Float(to_float64(&exactly(args, 1)?[0]).floor()))
+    }),
+    ("ceil", |args| {
+        Ok(Value::Float(to_float64(&exactly(args, 1)?[0]).ceil()))
+    }),
+    ("round", |args| {
+        let args = at_least(args, 2)?;
+        let (value, precision) = (to_float64(&args[0]), to_int64(&args[1]));
+        let round_on = args.get(2).map_or(0.5, to_float64);
+        let pow = 10f64.powi(precision as i32);
+        let digit = pow * value;
+        let rounded = if digit.fract().abs() >= round_on {
+            digit.trunc() + digit.signum()
+        } else {
+            digit.trunc()
+        };
+        Ok(Value::Float(rounded / pow))
+    }),
+    ("int", |args| {
+        Ok(Value::Int(to_int64(&exactly(args, 1)?[0])))
+    }),
+    ("int64", |args| {
+        Ok(Value::Int(to_int64(&exactly(args, 1)?[0])))
+    }),
+    ("float64", |args| {
+        Ok(Value::Float(to_float64(&exactly(args, 1)?[0])))
+    }),
+    ("toDecimal", |args| {
+        let s = strval(&exactly(args, 1)?[0]);
+        Ok(Value::Int(i64::from_str_radix(&s, 8).unwrap_or_default()))
+    }),
+    ("atoi", |args| {
+        Ok(Value::Int(
+            string_arg(exactly(args, 1)?, 0)?
+                .parse()
+                .unwrap_or_default(),
+        ))
+    }),
+    // regular expressions
+    ("regexMatch", |args| {
+        let args = exactly(args, 2)?;
+        let (regex, s) = (string_arg(args, 0)?, string_arg(args, 1)?);
+        Ok(Regex::new(regex).is_ok_and(|re| re.is_match(s)).into())
+    }),
+    ("mustRegexMatch", |args| {
+        let args = exactly(args, 2)?;
+        Ok(regex_arg(args, 0)?.is_match(string_arg(args, 1)?).into())
+    }),
+    ("regexFind", |args| {
+        let args = exactly(args, 2)?;
+        let re = regex_arg(args, 0)?;
+        Ok(re
+            .find(string_arg(args, 1)?)
+            .map_or("", |m| m.as_str())
+            .into())
+    }),
+    ("regexFindAll", |args| {
+        let args = exactly(args, 3)?;
+        let re = regex_arg(args, 0)?;
+        let n = limit(int_arg(args, 2)?);
+        let found = re
+            .find_iter(string_arg(args, 1)?)
+            .take(n)
+            .map(|m| m.as_str().into());
+        Ok(Value::List(found.collect()))
+    }),
+    ("regexReplaceAll", |args| {
+        let args = exactly(args, 3)?;
+        let re = regex_arg(args, 0)?;
+        Ok(re
+            .replace_all(string_arg(args, 1)?, string_arg(args, 2)?)
+            .into_owned()
+            .into())
+    }),
+    ("regexReplaceAllLiteral", |args| {
+        let args = exactly(args, 3)?;
+        let re = regex_arg(args, 0)?;
+        let replacement = regex::NoExpand(string_arg(args, 2)?);
+        Ok(re
+            .replace_all(string_arg(args, 1)?, replacement)
+            .into_owned()
+            .into())
+    }),
+    ("regexSplit", |args| {
+        let args = exactly(args, 3)?;
+        let re = regex_arg(args, 0)?;
+        let (s, n) = (string_arg(args, 1)?, int_arg(args, 2)?);
+        let parts: Vec<Value> = match n {
+            0 => Vec::new(),
+            n if n < 0 => re.split(s).map(Value::from).collect(),
+            n => re.splitn(s, n as usize).map(Value::from).collect(),
+        };
+        Ok(Value::List(parts))
+    }),
+    ("regexQuoteMeta", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        let mut quoted = String::with_capacity(s.len());
+        for c in s.chars() {
+            if "\\.+*?()|[]{}^$".contains(c) {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        Ok(quoted.into())
+    }),
+    // paths
+    ("base", |args| {
+        Ok(path_base(string_arg(exactly(args, 1)?, 0)?).into())
+    }),
+    ("dir", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        let dir = s.rfind('/').map_or("", |i| &s[..=i]);
+        Ok(path_clean(dir).into())
+    }),
+    ("clean", |args| {
+        Ok(path_clean(string_arg(exactly(args, 1)?, 0)?).into())
+    }),
+    ("ext", |args| {
+        let s = string_arg(exactly(args, 1)?, 0)?;
+        let name = s.rfind('/').map_or(s, |i| &s[i + 1..]);
+        Ok(name.rfind('.').map_or("", |i| &name[i..]).into())
+    }),
+    ("isAbs", |args| {
+        Ok(string_arg(exactly(args, 1)?, 0)?.starts_with('/').into())
+    }),
+    // semantic versions
+    ("semverCompare", |args| {
+        let args = exactly(args, 2)?;
+        let constraint = string_arg(args, 0)?;
+        let version = string_arg(args, 1)?;
+        Ok(semver_compare(constraint, version)?.into())
+    }),
+    // reflection
+    ("kindOf", |args| Ok(kind_of(&exactly(args, 1)?[0]).into())),
+    ("kindIs", |args| {
+        let args = exactly(args, 2)?;
+        Ok((string_arg(args, 0)? == kind_of(&args[1])).into())
+    }),
+    ("typeOf", |args| Ok(type_of(&exactly(args, 1)?[0]).into())),
+    ("typeIs", |args| {
+        let args = exactly(args, 2)?;
+        Ok((string_arg(args, 0)? == type_of(&args[1])).into())
+    }),
+    // there are no pointers, so a type is only like itself
+    ("typeIsLike", |args| {
+        let args = exactly(args, 2)?;
+        Ok((string_arg(args, 0)? == type_of(&args[1])).into())
+    }),
+    ("deepEqual", |args| {
+        let args = exactly(args, 2)?;
+        Ok((args[0] == args[1]).into())
+    }),
+];
+
+/// The `must` variants that behave like the functions they wrap, which fail the same way
+/// since sprig panics on errors and `text/template` reports the panic.
+const MUST_ALIASES: &[(&str, &str)] = &[
+    ("mustAppend", "append"),
+    ("mustChunk", "chunk"),
+    ("mustCompact", "compact"),
+    ("mustDeepCopy", "deepCopy"),
+    ("mustFirst", "first"),
+    ("mustHas", "has"),
+    ("mustInitial", "initial"),
+    ("mustLast", "last"),
+    ("mustMerge", "merge"),
+    ("mustMergeOverwrite", "mergeOverwrite"),
+    ("mustPrepend", "prepend"),
+    ("mustPush", "push"),
+    ("mustRegexFind", "regexFind"),
+    ("mustRegexFindAll", "regexFindAll"),
+    ("mustRegexReplaceAll", "regexReplaceAll"),
+    ("mustRegexReplaceAllLiteral", "regexReplaceAllLiteral"),
+    ("mustRegexSplit", "regexSplit"),
+    ("mustRest", "rest"),
+    ("mustReverse", "reverse"),
+    ("mustSlice", "slice"),
+    ("mustToJson", "toJson"),
+    ("mustToPrettyJson", "toPrettyJson"),
+    ("mustToRawJson", "toRawJson"),
+    ("mustUniq", "uniq"),
+    ("mustWithout", "without"),
+    ("tuple", "list"),
+];
+
+/// The sprig function with the name, including the `must` variants.
+pub(super) fn lookup(name: &str) -> Option<Func> {
+    let name = MUST_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, target)| target);
+    FUNCS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f)
+}
+
+/// The functions that modify their first argument in place in Go, so the executor writes
+/// their result back to the variable they were called on.
+pub(super) const MUTATING: &[&str] = &[
+    "set",
+    "unset",
+    "merge",
+    "mergeOverwrite",
+    "mustMerge",
+    "mustMergeOverwrite",
+];
+
+fn wrong_type(value: &Value, expected: &str) -> FuncError {
+    match value {
+        Value::Missing | Value::Nil => {
+            FuncError::Argument(format!("invalid value; expected {expected}"))
+        }
+        value => FuncError::Argument(format!(
+            "wrong type for value; expected {expected}; got {}",
+            value.type_name()
+        )),
+    }
+}
+
+fn string_arg(args: &[Value], i: usize) -> Result<&str, FuncError> {
+    match &args[i] {
+        Value::String(s) => Ok(s),
+        value => Err(wrong_type(value, "string")),
+    }
+}
+
+fn int_arg(args: &[Value], i: usize) -> Result<i64, FuncError> {
+    match &args[i] {
+        Value::Int(n) => Ok(*n),
+        value => Err(wrong_type(value, "int")),
+    }
+}
+
+fn dict_arg(args: &[Value], i: usize) -> Result<&BTreeMap<String, Value>, FuncError> {
+    match &args[i] {
+        Value::Map(map) => Ok(map),
+        value => Err(wrong_type(value, "map[string]interface {}")),
+    }
+}
+
+fn list_arg(value: &Value, action: &str) -> Result<Vec<Value>, FuncError> {
+    match value {
+        Value::List(list) => Ok(list.clone()),
+        value => Err(format!("Cannot {action} type {}", value.type_name()).into()),
+    }
+}
+
+fn regex_arg(args: &[Value], i: usize) -> Result<Regex, FuncError> {
+    let regex = string_arg(args, i)?;
+    Regex::new(regex)
+        .map_err(|err| FuncError::Call(format!("regexp: Compile({}): {err}", quote(regex))))
+}
+
+fn non_nil(args: &[Value]) -> impl Iterator<Item = &Value> {
+    args.iter()
+        .filter(|v| !matches!(v, Value::Missing | Value::Nil))
+}
+
+/// Converts a value to a string like sprig's `strval`.
+fn strval(value: &Value) -> String {
+    match value {
+        Value::Missing | Value::Nil => "<nil>".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Converts a value to a list of strings like sprig's `strslice`, skipping nil items.
+fn strslice(value: &Value) -> Vec<String> {
+    match value {
+        Value::List(list) => non_nil(list).map(strval).collect(),
+        Value::Missing | Value::Nil => Vec::new(),
+        value => vec![strval(value)],
+    }
+}
+
+/// Emptiness as defined by sprig: nil, zero values and empty collections are empty.
+fn empty(value: &Value) -> bool {
+    !value.is_true()
+}
+
+/// Converts like `cast.ToInt64`, falling back to `0`.
+fn to_int64(value: &Value) -> i64 {
+    match value {
+        Value::Int(n) => *n,
+        Value::Float(f) => *f as i64,
+        Value::Bool(b) => i64::from(*b),
+        Value::String(s) => {
+            let s = s.strip_suffix(".0").unwrap_or(s);
+            let (negative, digits) = match s.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, s.strip_prefix('+').unwrap_or(s)),
+            };
+            let lower = digits.to_ascii_lowercase();
+            let parsed = if let Some(hex) = lower.strip_prefix("0x") {
+                i64::from_str_radix(hex, 16)
+            } else if let Some(bin) = lower.strip_prefix("0b") {
+                i64::from_str_radix(bin, 2)
+            } else if let Some(oct) = lower.strip_prefix("0o") {
+                i64::from_str_radix(oct, 8)
+            } else if lower.len() > 1 && lower.starts_with('0') {
+                i64::from_str_radix(&lower[1..], 8)
+            } else {
+                lower.parse()
+            };
+            parsed.map_or(0, |n| if negative { -n } else { n })
+        }
+        _ => 0,
+    }
+}
+
+/// Converts like `cast.ToFloat64`, falling back to `0`.
+fn to_float64(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Float(f) => *f,
+        Value::Bool(b) => f64::from(u8::from(*b)),
+        Value::String(s) => s.parse().unwrap_or_default(),
+        _ => 0.0,
+    }
+}
+
+/// Go's `strings.Split`: an empty separator splits into characters.
+fn go_split<'a>(s: &'a str, sep: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+    if sep.is_empty() {
+        Box::new(s.char_indices().map(move |(i, c)| &s[i..i + c.len_utf8()]))
+    } else {
+        Box::new(s.split(sep))
+    }
+}
+
+fn title(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut prev_separator = true;
+    for c in s.chars() {
+        if prev_separator {
+            out.extend(c.to_uppercase());
+        } else {
+            out.push(c);
+        }
+        prev_separator = if c.is_ascii() {
+            !(c.is_ascii_alphanumeric() || c == '_')
+        } else {
+            c.is_whitespace()
+        };
+    }
+    out
+}
+
+fn untitle(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut prev_space = true;
+    for c in s.chars() {
+        if prev_space {
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+        prev_space = c.is_whitespace();
+    }
+    out
+}
+
+fn indent(spaces: i64, s: &str) -> String {
+    let pad = " ".repeat(spaces.max(0) as usize);
+    format!("{pad}{}", s.replace('\n', &format!("\n{pad}")))
+}
+
+fn byte_range(s: &str, start: usize, end: usize) -> Result<String, FuncError> {
+    s.get(start..end)
+        .map(str::to_string)
+        .ok_or_else(|| format!("runtime error: slice bounds out of range [{start}:{end}]").into())
+}
+
+fn substr(s: &str, start: i64, end: i64) -> Result<String, FuncError> {
+    let len = s.len() as i64;
+    if start < 0 {
+        return byte_range(s, 0, end.clamp(0, len) as usize);
+    }
+    if end < 0 || end > len {
+        return byte_range(s, start.min(len) as usize, len as usize);
+    }
+    byte_range(s, start as usize, end as usize)
+}
+
+fn trunc(s: &str, c: i64) -> Result<String, FuncError> {
+    let len = s.len() as i64;
+    if c < 0 && len + c > 0 {
+        return byte_range(s, (len + c) as usize, len as usize);
+    }
+    if c >= 0 && len > c {
+        return byte_range(s, 0, c as usize);
+    }
+    Ok(s.to_string())
+}
+
+/// Abbreviates with an ellipsis like `goutils.AbbreviateFull`, assuming `width` is at least 4.
+fn abbreviate(s: &str, offset: usize, width: usize) -> String {
+    let len = s.len();
+    if len <= width {
+        return s.to_string();
+    }
+    let mut offset = offset.min(len);
+    if len - offset < width - 3 {
+        offset = len - (width - 3);
+    }
+    let head = |s: &str, n: usize| s.get(..n).unwrap_or(s).to_string();
+    if offset <= 4 {
+        return format!("{}...", head(s, width - 3));
+    }
+    if width < 7 {
+        return String::new();
+    }
+    if offset + width - 3 < len {
+        let rest = s.get(offset..).unwrap_or_default();
+        return format!("...{}", abbreviate(rest, 0, width - 3));
+    }
+    format!("...{}", s.get(len - (width - 3)..).unwrap_or_default())
+}
+
+/// Splits a string into lowercase words joined by the separator like `xstrings.ToSnakeCase`:
+/// words end at non-alphanumeric characters, before an uppercase letter following a lowercase
+/// one or starting a word after an acronym, and between letters and digits.
+fn words(s: &str, separator: char) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if let Some(&prev) = word.chars().last().as_ref() {
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let boundary = (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_is_lower)
+                || (prev.is_numeric() != c.is_numeric());
+            if boundary {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(&separator.to_string())
+}
+
+/// Integers from `start` towards `stop`, excluded, like sprig's `untilStep`.
+fn until_step(start: i64, stop: i64, step: i64) -> Vec<i64> {
+    let mut ints = Vec::new();
+    let mut i = start;
+    if stop < start {
+        while step < 0 && i > stop {
+            ints.push(i);
+            i += step;
+        }
+    } else {
+        while step > 0 && i < stop {
+            ints.push(i);
+            i += step;
+        }
+    }
+    ints
+}
+
+/// The last element of a slash-separated path like Go's `path.Base`.
+fn path_base(s: &str) -> String {
+    if s.is_empty() {
+        return ".".to_string();
+    }
+    let trimmed = s.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return "/".to_string();
+    }
+    trimmed
+        .rsplit_once('/')
+        .map_or(trimmed, |(_, base)| base)
+        .to_string()
+}
+
+/// The shortest equivalent of a slash-separated path like Go's `path.Clean`.
+fn path_clean(s: &str) -> String {
+    let rooted = s.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in s.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            ".." if rooted => {}
+            part => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    match (rooted, joined.is_empty()) {
+        (true, _) => format!("/{joined}"),
+        (false, true) => ".".to_string(),
+        (false, false) => joined,
+    }
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + u32::from(*byte)) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes with the padded standard alphabet of RFC 4648, like Go's `base32.StdEncoding`.
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = buf.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        let symbols = (chunk.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < symbols {
+                out.push(BASE32_ALPHABET[((bits >> (35 - i * 5)) & 31) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes padded standard base32, returning the offset of the first invalid byte on error.
+fn base32_decode(s: &str) -> Result<Vec<u8>, usize> {
+    let input: Vec<u8> = s.bytes().filter(|b| *b != b'\r' && *b != b'\n').collect();
+    if !input.len().is_multiple_of(8) {
+        return Err(input.len() / 8 * 8);
+    }
+    let mut out = Vec::with_capacity(input.len() / 8 * 5);
+    for (index, chunk) in input.chunks(8).enumerate() {
+        let padding = chunk.iter().rev().take_while(|b| **b == b'=').count();
+        let bytes = match padding {
+            0 => 5,
+            1 => 4,
+            3 => 3,
+            4 => 2,
+            6 => 1,
+            _ => return Err(index * 8 + 8 - padding),
+        };
+        let mut bits = 0u64;
+        for (i, b) in chunk.iter().enumerate() {
+            let value = match BASE32_ALPHABET.iter().position(|a| a == b) {
+                Some(value) => value as u64,
+                None if i >= 8 - padding => 0,
+                None => return Err(index * 8 + i),
+            };
+            bits = (bits << 5) | value;
+        }
+        out.extend_from_slice(&bits.to_be_bytes()[3..3 + bytes]);
+        if padding > 0 && index + 1 < input.len() / 8 {
+            return Err(index * 8 + 8 - padding);
+        }
+    }
+    Ok(out)
+}
+
+fn push(args: &[Value]) -> Result<Value, FuncError> {
+    let args = exactly(args, 2)?;
+    let mut list = list_arg(&args[0], "push on")?;
+    list.push(args[1].clone());
+    Ok(Value::List(list))
+}
+
+/// Merges maps into the first one like mergo: nested maps are merged recursively and, unless
+/// overwriting, only missing or empty values are set.
+fn merge(args: &[Value], overwrite: bool) -> Result<Value, FuncError> {
+    let args = at_least(args, 1)?;
+    let mut dest = dict_arg(args, 0)?.clone();
+    for i in 1..args.len() {
+        merge_maps(&mut dest, dict_arg(args, i)?, overwrite);
+    }
+    Ok(Value::Map(dest))
+}
+
+fn merge_maps(dest: &mut BTreeMap<String, Value>, src: &BTreeMap<String, Value>, overwrite: bool) {
+    for (key, value) in src {
+        match (dest.get_mut(key), value) {
+            (Some(Value::Map(dest)), Value::Map(src)) => merge_maps(dest, src, overwrite),
+            (Some(existing), value) => {
+                if empty(existing) || (overwrite && !empty(value)) {
+                    *existing = value.clone();
+                }
+            }
+            (None, value) => {
+                dest.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Marshals like Go's `encoding/json`, which escapes HTML characters unless asked not to.
+fn to_json(value: &Value, pretty: bool, escape_html: bool) -> String {
+    let json = value.to_json().unwrap_or_default();
+    let out = if pretty {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    }
+    .unwrap_or_default();
+    if !escape_html {
+        return out;
+    }
+    out.replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The number of matches to return for Go's `n` argument, negative meaning all.
+fn limit(n: i64) -> usize {
+    usize::try_from(n).unwrap_or(usize::MAX)
+}
+
+fn kind_of(value: &Value) -> &'static str {
+    match value {
+        Value::Missing | Value::Nil => "invalid",
+        Value::List(_) => "slice",
+        Value::Map(_) => "map",
+        value => value.type_name(),
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Missing | Value::Nil => "<nil>",
+        value => value.type_name(),
+    }
+}
+
+/// A version in a semver constraint, where missing parts and `x`, `X` or `*` are wildcards.
+struct PartialVersion {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: semver::Prerelease,
+}
+
+impl PartialVersion {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+        let s = s.split_once('+').map_or(s, |(s, _)| s);
+        let (numbers, pre) = match s.split_once('-') {
+            Some((numbers, pre)) => (numbers, semver::Prerelease::new(pre).ok()?),
+            None => (s, semver::Prerelease::EMPTY),
+        };
+        let mut parts = numbers.split('.');
+        let mut next = || -> Option<Option<u64>> {
+            match parts.next() {
+                None | Some("x" | "X" | "*") => Some(None),
+                Some(part) => part.parse().ok().map(Some),
+            }
+        };
+        let (major, minor, patch) = (next()?, next()?, next()?);
+        if parts.next().is_some() || (major.is_none() && numbers.is_empty()) {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor: major.and(minor),
+            patch: major.and(minor).and(patch),
+            pre,
+        })
+    }
+
+    fn is_partial(&self) -> bool {
+        self.patch.is_none()
+    }
+
+    /// The lowest version matching the wildcards.
+    fn lower(&self) -> semver::Version {
+        let mut version = semver::Version::new(
+            self.major.unwrap_or_default(),
+            self.minor.unwrap_or_default(),
+            self.patch.unwrap_or_default(),
+        );
+        version.pre = self.pre.clone();
+        version
+    }
+
+    /// The lowest version above the wildcards, `None` if the major version is a wildcard.
+    fn upper(&self) -> Option<semver::Version> {
+        let major = self.major?;
+        Some(match (self.minor, self.patch) {
+            (None, _) => semver::Version::new(major + 1, 0, 0),
+            (Some(minor), None) => semver::Version::new(major, minor + 1, 0),
+            (Some(minor), Some(patch)) => semver::Version::new(major, minor, patch + 1),
+        })
+    }
+
+    fn matches(&self, op: &str, v: &semver::Version) -> bool {
+        // prereleases only match constraints that have a prerelease themselves
+        if !v.pre.is_empty() && self.pre.is_empty() {
+            return false;
+        }
+        let lower = self.lower();
+        let below_upper = |upper: Option<semver::Version>| upper.is_none_or(|upper| *v < upper);
+        match op {
+            "" | "=" if self.is_partial() => *v >= lower && below_upper(self.upper()),
+            "" | "=" => *v == lower,
+            "!=" => !self.matches("=", v),
+            ">" if self.is_partial() => self.upper().is_some_and(|upper| *v >= upper),
+            ">" => *v > lower,
+            ">=" | "=>" => *v >= lower,
+            "<" => *v < lower,
+            "<=" | "=<" if self.is_partial() => below_upper(self.upper()),
+            "<=" | "=<" => *v <= lower,
+            "~" | "~>" => {
+                let upper = match (self.major, self.minor) {
+                    (Some(major), None) => Some(semver::Version::new(major + 1, 0, 0)),
+                    (Some(major), Some(minor)) => Some(semver::Version::new(major, minor + 1, 0)),
+                    (None, _) => None,
+                };
+                *v >= lower && below_upper(upper)
+            }
+            "^" => {
+                let upper = match (self.major, self.minor, self.patch) {
+                    (None, _, _) => None,
+                    (Some(0), None, _) => Some(semver::Version::new(1, 0, 0)),
+                    (Some(0), Some(0), Some(patch)) => Some(semver::Version::new(0, 0, patch + 1)),
+                    (Some(0), Some(minor), _) => Some(semver::Version::new(0, minor + 1, 0)),
+                    (Some(major), _, _) => Some(semver::Version::new(major + 1, 0, 0)),
+                };
+                *v >= lower && below_upper(upper)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Parses a version the way Masterminds/semver does, allowing a `v` prefix and missing parts.
+fn parse_version(s: &str) -> Option<semver::Version> {
+    let partial = PartialVersion::parse(s.trim())?;
+    if partial.major.is_none() || s.contains(['x', 'X', '*']) {
+        return None;
+    }
+    Some(partial.lower())
+}
+
+/// Evaluates a Masterminds/semver constraint such as `>= 1.28.0-0, < 1.30` or `~1.2 || ^2`.
+fn semver_compare(constraint: &str, version: &str) -> Result<bool, FuncError> {
+    let improper = || FuncError::Call(format!("improper constraint: {constraint}"));
+    static COMPARATOR: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^\s*(!=|>=|=>|<=|=<|~>|>|<|=|~|\^)?\s*(\S+)\s*$").expect("valid regex")
+    });
+    static HYPHEN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*(\S+)\s+-\s+(\S+)\s*$").expect("valid regex"));
+    let version = parse_version(version).ok_or("Invalid Semantic Version")?;
+    let mut any = false;
+    for group in constraint.split("||") {
+        let mut all = true;
+        let comparators: Vec<String> = if let Some(caps) = HYPHEN.captures(group) {
+            vec![format!(">={}", &caps[1]), format!("<={}", &caps[2])]
+        } else {
+            split_comparators(group)
+        };
+        if comparators.is_empty() {
+            return Err(improper());
+        }
+        for c in &comparators {
+            let caps = COMPARATOR.captures(c).ok_or_else(improper)?;
+            let op = caps.get(1).map_or("", |m| m.as_str());
+            let partial = PartialVersion::parse(&caps[2]).ok_or_else(improper)?;
+            all &= partial.matches(op, &version);
+        }
+        any |= all;
+    }
+    Ok(any)
+}
+
+/// Splits an AND group on commas and spaces, keeping operators with their versions.
+fn split_comparators(group: &str) -> Vec<String> {
+    let mut comparators: Vec<String> = Vec::new();
+    let mut pending_op = String::new();
+    for token in group.split([',', ' ']).filter(|t| !t.is_empty()) {
+        if token.chars().all(|c| "<>=!~^".contains(c)) {
+            pending_op.push_str(token);
+        } else {
+            comparators.push(format!("{}{token}", std::mem::take(&mut pending_op)));
+        }
+    }
+    if !pending_op.is_empty() {
+        comparators.push(pending_op);
+    }
+    comparators
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::topology::template::render;
+
+    fn render_ok(source: &str) -> String {
+        let data = json!({"n": 3, "s": "str", "l": ["b", "a"], "m": {"k": "v"}});
+        render(source, &data).unwrap_or_else(|err| panic!("{source}: {err}"))
+    }
+
+    fn render_err(source: &str) -> String {
+        render(source, &json!({})).unwrap_err().message
+    }
+
+    #[test]
+    fn strings() {
+        for (source, expected) in [
+            (r#"{{ upper "aB" }} {{ lower "aB" }}"#, "AB ab"),
+            (r#"{{ title "hello wide_world" }}"#, "Hello Wide_world"),
+            (r#"{{ untitle "Hello World" }}"#, "hello world"),
+            (
+                r#"{{ trim "  a  " }}|{{ trimAll "$" "$5.00$" }}|{{ trimall "$" "$5$" }}"#,
+                "a|5.00|5",
+            ),
+            (
+                r#"{{ trimPrefix "-" "-a-" }} {{ trimSuffix "-" "-a-" }}"#,
+                "a- -a",
+            ),
+            (
+                r#"{{ contains "cat" "catch" }} {{ hasPrefix "cat" "catch" }} {{ hasSuffix "cat" "catch" }}"#,
+                "true true false",
+            ),
+            (
+                r#"{{ replace " " "-" "a b c" }} {{ repeat 3 "ab" }}"#,
+                "a-b-c ababab",
+            ),
+            (
+                r#"{{ substr 0 5 "hello world" }}|{{ substr -1 3 "hello" }}|{{ substr 2 99 "hello" }}"#,
+                "hello|hel|llo",
+            ),
+            (
+                r#"{{ trunc 5 "hello world" }}|{{ trunc -5 "hello world" }}|{{ trunc 50 "hi" }}"#,
+                "hello|world|hi",
+            ),
+            (r#"{{ nospace "a b \t c" }}"#, "abc"),
+            (
+                r#"{{ abbrev 5 "hello world" }}|{{ abbrev 3 "hello" }}"#,
+                "he...|hello",
+            ),
+            (r#"{{ abbrevboth 5 10 "1234 5678 9123" }}"#, "...5678..."),
+            (
+                r#"{{ initials "First Try" }} {{ swapcase "This Is A.Test" }}"#,
+                "FT tHIS iS a.tEST",
+            ),
+            (
+                r#"{{ snakecase "FirstName" }} {{ snakecase "HTTPServer" }} {{ snakecase "any kind-of_string" }}"#,
+                "first_name http_server any_kind_of_string",
+            ),
+            (
+                r#"{{ snakecase "numbers2and55with000" }}"#,
+                "numbers_2_and_55_with_000",
+            ),
+            (
+                r#"{{ kebabcase "FirstName" }} {{ camelcase "http_server" }} {{ camelcase "some words-here" }}"#,
+                "first-name HttpServer SomeWordsHere",
+            ),
+            (
+                r#"{{ plural "one" "many" 1 }} {{ plural "one" "many" 2 }}"#,
+                "one many",
+            ),
+            (
+                r#"{{ quote "a" nil 1 }}|{{ squote "a" }}|{{ cat "a" nil 1 }}"#,
+                r#""a" "1"|'a'|a 1"#,
+            ),
+            (
+                r#"{{ indent 2 "a\nb" }}|{{ nindent 2 "a" }}"#,
+                "  a\n  b|\n  a",
+            ),
+            (
+                r#"{{ splitList "$" "a$b" }} {{ split "$" "a$b" }}"#,
+                "[a b] map[_0:a _1:b]",
+            ),
+            (
+                r#"{{ splitn "$" 2 "foo$bar$baz" }} {{ splitn "$" -1 "a$b" }} {{ splitn "$" 0 "a" }}"#,
+                "map[_0:foo _1:bar$baz] map[_0:a _1:b] map[]",
+            ),
+            (r#"{{ splitn "" 2 "abc" }}"#, "map[_0:a _1:bc]"),
+            (
+                r#"{{ join "-" (list 1 "a" nil) }} {{ join "-" "a" }}"#,
+                "1-a a",
+            ),
+            (
+                r#"{{ toString 1.5 }} {{ toStrings (list 1 nil) }} {{ sortAlpha .l }}"#,
+                "1.5 [1] [a b]",
+            ),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn defaults_and_encoding() {
+        for (source, expected) in [
+            (r#"{{ default "d" "" }} {{ default "d" "x" }} {{ .missing | default "d" }}"#, "d x d"),
+            (r#"{{ empty "" }} {{ empty 0 }} {{ empty (list) }} {{ empty "a" }}"#, "true true true false"),
+            (r#"{{ coalesce nil "" "x" }} {{ all 1 "a" }} {{ all 1 "" }} {{ any 0 "" }} {{ any 0 "a" }}"#, "x true false false true"),
+            (r#"{{ ternary "a" "b" true }} {{ ternary "a" "b" false }}"#, "a b"),
+            (r#"{{ toJson (dict "a" "<b>" "n" .n) }}"#, r#"{"a":"\u003cb\u003e","n":3}"#),
+            (r#"{{ toRawJson (dict "a" "<b>") }} {{ mustToJson (list 1.5) }}"#, r#"{"a":"<b>"} [1.5]"#),
+            ("{{ toPrettyJson (list 1) }}", "[\n  1\n]"),
+            (r#"{{ (fromJson "{\"a\":1}").a }} {{ fromJson "x" }} {{ (mustFromJson "[1]") }}"#, "1 <nil> [1]"),
+            (r#"{{ b64enc "hello" }} {{ b64dec "aGVsbG8=" }} {{ b64dec "!" }}"#, "aGVsbG8= hello illegal base64 data: Invalid symbol 33, offset 0."),
+            (r#"{{ b32enc "hello" }} {{ b32enc "hi" }} {{ b32enc "hello world!" }}"#, "NBSWY3DP NBUQ==== NBSWY3DPEB3W64TMMQQQ===="),
+            (r#"{{ b32dec "NBSWY3DP" }} {{ b32dec "NBUQ====" }} {{ b32dec "NB!Q====" }}"#, "hello hi illegal base32 data at input byte 2"),
+            (r#"{{ sha1sum "hello" }}"#, "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"),
+            (r#"{{ sha256sum "hello" }}"#, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"),
+            (r#"{{ sha512sum "hello" }}"#, "9b71d224bd62f3785d96d46ad3ea3d73319bfbc2890caadae2dff72519673ca72323c3d99ba5c11d7c7acc6e14b8c5da0c4663475c2e5c3adef46f73bcdec043"),
+            (r#"{{ adler32sum "hello" }}"#, "103547413"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn lists() {
+        for (source, expected) in [
+            ("{{ list 1 \"a\" }} {{ tuple 1 2 }}", "[1 a] [1 2]"),
+            ("{{ append .l \"c\" }} {{ mustPush .l \"c\" }} {{ prepend .l \"c\" }}", "[b a c] [b a c] [c b a]"),
+            ("{{ first .l }} {{ last .l }} {{ rest .l }} {{ initial .l }} {{ first list }}", "b a [a] [b] <nil>"),
+            ("{{ reverse .l }} {{ uniq (list 1 1 2) }} {{ without .l \"a\" }}", "[a b] [1 2] [b]"),
+            ("{{ has \"a\" .l }} {{ mustHas \"c\" .l }} {{ has 1 nil }}", "true false false"),
+            ("{{ compact (list 1 \"\" nil 0 2) }} {{ concat .l (list 1) }}", "[1 2] [b a 1]"),
+            ("{{ until 3 }} {{ until -2 }} {{ untilStep 3 6 2 }} {{ untilStep 6 3 -2 }} {{ untilStep 0 3 0 }}", "[0 1 2] [0 -1] [3 5] [6 4] []"),
+            ("{{ seq 3 }}|{{ seq -1 }}|{{ seq 2 4 }}|{{ seq 0 2 6 }}|{{ seq 6 -2 0 }}|{{ seq 0 2 -6 }}", "1 2 3|1 0 -1|2 3 4|0 2 4 6|6 4 2 0|"),
+            ("{{ chunk 2 (list 1 2 3) }} {{ mustChunk 5 .l }}", "[[1 2] [3]] [[b a]]"),
+            ("{{ slice (list 1 2 3) 1 }} {{ slice (list 1 2 3) 0 2 }} {{ slice (list) 0 }}", "[2 3] [1 2] <nil>"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn dicts() {
+        for (source, expected) in [
+            ("{{ dict \"a\" 1 \"b\" }}", "map[a:1 b:]"),
+            ("{{ get .m \"k\" }}|{{ get .m \"x\" }}|{{ hasKey .m \"k\" }}", "v||true"),
+            ("{{ $d := dict \"a\" 1 }}{{ $_ := set $d \"b\" 2 }}{{ $_ := unset $d \"a\" }}{{ $d }}", "map[b:2]"),
+            ("{{ keys .m (dict \"a\" 1) | sortAlpha }} {{ values .m }}", "[a k] [v]"),
+            ("{{ pluck \"k\" .m (dict \"k\" 1) (dict) }}", "[v 1]"),
+            ("{{ pick (dict \"a\" 1 \"b\" 2) \"a\" }} {{ omit (dict \"a\" 1 \"b\" 2) \"a\" }}", "map[a:1] map[b:2]"),
+            (
+                "{{ $d := dict \"a\" 1 \"n\" (dict \"x\" 1) }}{{ $_ := merge $d (dict \"a\" 2 \"b\" 3 \"n\" (dict \"y\" 2)) }}{{ $d }}",
+                "map[a:1 b:3 n:map[x:1 y:2]]",
+            ),
+            (
+                "{{ $d := dict \"a\" 1 }}{{ $_ := mustMergeOverwrite $d (dict \"a\" 2) }}{{ $d }}",
+                "map[a:2]",
+            ),
+            ("{{ dig \"a\" \"b\" \"d\" (dict \"a\" (dict \"b\" 1)) }} {{ dig \"x\" \"d\" .m }}", "1 d"),
+            ("{{ deepCopy .m }} {{ mustDeepCopy .l }}", "map[k:v] [b a]"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn math_regexes_paths_and_reflection() {
+        for (source, expected) in [
+            ("{{ add 1 2 .n }} {{ add1 .n }} {{ sub 5 .n }} {{ mul 2 3 }} {{ div 7 2 }} {{ mod 7 2 }}", "6 4 2 6 3 1"),
+            ("{{ max 1 5 3 }} {{ biggest 1 5 }} {{ min 4 2 \"8\" }}", "5 5 2"),
+            ("{{ floor 1.5 }} {{ ceil 1.2 }} {{ round 3.14159 2 }} {{ round 2.5 0 }}", "1 2 3.14 3"),
+            ("{{ int \"42\" }} {{ int64 \"0x10\" }} {{ int \"x\" }} {{ float64 \"1.5\" }} {{ atoi \"12\" }} {{ toDecimal \"0777\" }}", "42 16 0 1.5 12 511"),
+            ("{{ regexMatch \"^a\" \"abc\" }} {{ regexMatch \"(\" \"a\" }}", "true false"),
+            ("{{ regexFind \"[0-9]+\" \"ab12cd34\" }} {{ regexFindAll \"[0-9]+\" \"ab12cd34\" -1 }} {{ mustRegexFindAll \"[0-9]\" \"1234\" 2 }}", "12 [12 34] [1 2]"),
+            ("{{ regexReplaceAll \"a(x*)b\" \"-ab-axxb-\" \"${1}W\" }} {{ regexReplaceAllLiteral \"a(x*)b\" \"-ab-\" \"${1}\" }}", "-W-xxW- -${1}-"),
+            ("{{ regexSplit \"z+\" \"pizza\" -1 }} {{ regexSplit \"z+\" \"pizza\" 0 }}", "[pi a] []"),
+            ("{{ regexQuoteMeta \"1.2.3+[a]\" }}", r"1\.2\.3\+\[a\]"),
+            ("{{ base \"foo/bar.txt\" }} {{ base \"/\" }} {{ base \"\" }} {{ base \"a/b/\" }}", "bar.txt / . b"),
+            ("{{ dir \"foo/bar/baz\" }} {{ dir \"baz\" }} {{ dir \"/baz\" }}", "foo/bar . /"),
+            ("{{ clean \"foo/../bar//baz/.\" }} {{ clean \"/../a\" }} {{ clean \"../a/..\" }} {{ clean \"\" }}", "bar/baz /a .. ."),
+            ("{{ ext \"foo.bar.tar\" }}|{{ ext \"a.b/c\" }}|{{ isAbs \"/a\" }} {{ isAbs \"a\" }}", ".tar||true false"),
+            ("{{ semverCompare \">= 1.28.0-0\" \"v1.29.1\" }} {{ semverCompare \"~1.2\" \"1.3.0\" }} {{ semverCompare \"^1.2\" \"1.9.0\" }}", "true false true"),
+            ("{{ semverCompare \"1.2 - 1.4\" \"1.4.9\" }} {{ semverCompare \"<1.2 || >=2\" \"2.1.0\" }} {{ semverCompare \"1.x\" \"1.28.0-rc.1\" }}", "true true false"),
+            ("{{ kindOf .l }} {{ kindOf .m }} {{ kindOf .n }} {{ kindIs \"string\" .s }}", "slice map float64 true"),
+            ("{{ typeOf 1 }} {{ typeOf nil }} {{ typeIs \"int\" 1 }} {{ typeIsLike \"float64\" .n }}", "int <nil> true true"),
+            ("{{ deepEqual (list 1) (list 1) }} {{ deepEqual .n 3 }}", "true false"),
+        ] {
+            assert_eq!(render_ok(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for (source, message) in [
+            ("{{ fail \"boom\" }}", "error calling fail: boom"),
+            (
+                "{{ upper 1 }}",
+                "wrong type for value; expected string; got int",
+            ),
+            ("{{ upper nil }}", "invalid value; expected string"),
+            (
+                "{{ repeat -1 \"a\" }}",
+                "error calling repeat: strings: negative Repeat count",
+            ),
+            (
+                "{{ substr 3 1 \"hello\" }}",
+                "error calling substr: runtime error: slice bounds out of range [3:1]",
+            ),
+            (
+                "{{ first 1 }}",
+                "error calling first: Cannot find first on type int",
+            ),
+            (
+                "{{ mustFirst 1 }}",
+                "error calling mustFirst: Cannot find first on type int",
+            ),
+            (
+                "{{ slice \"ab\" 1 }}",
+                "error calling slice: list should be type of slice or array but string",
+            ),
+            (
+                "{{ slice (list 1) 0 2 }}",
+                "error calling slice: reflect.Value.Slice: slice index out of bounds",
+            ),
+            (
+                "{{ div 1 0 }}",
+                "error calling div: runtime error: integer divide by zero",
+            ),
+            (
+                "{{ chunk 0 (list 1) }}",
+                "error calling chunk: runtime error: integer divide by zero",
+            ),
+            (
+                "{{ get 1 \"a\" }}",
+                "wrong type for value; expected map[string]interface {}; got int",
+            ),
+            (
+                "{{ ternary 1 2 \"x\" }}",
+                "wrong type for value; expected bool; got string",
+            ),
+            (
+                "{{ semverCompare \">=1\" \"x\" }}",
+                "error calling semverCompare: Invalid Semantic Version",
+            ),
+            (
+                "{{ semverCompare \">>1\" \"1.0.0\" }}",
+                "error calling semverCompare: improper constraint: >>1",
+            ),
+        ] {
+            assert_eq!(render_err(source), message, "{source}");
+        }
+        for source in [
+            "{{ mustRegexMatch \"(\" \"a\" }}",
+            "{{ regexFind \"(\" \"a\" }}",
+            "{{ mustRegexSplit \"(\" \"a\" -1 }}",
+        ] {
+            assert!(
+                render_err(source).contains(r#"regexp: Compile("("): "#),
+                "{source}"
+            );
+        }
+        assert!(render_err("{{ mustFromJson \"x\" }}").starts_with("error calling mustFromJson: "));
+    }
+
+    #[test]
+    fn converts_leniently() {
+        for (value, int, float) in [
+            (Value::Int(3), 3, 3.0),
+            (Value::Float(2.9), 2, 2.9),
+            (Value::Bool(true), 1, 1.0),
+            ("-12".into(), -12, -12.0),
+            ("0x1f".into(), 31, 0.0),
+            ("017".into(), 15, 17.0),
+            ("4.0".into(), 4, 4.0),
+            ("x".into(), 0, 0.0),
+            (Value::Nil, 0, 0.0),
+        ] {
+            assert_eq!(to_int64(&value), int, "{value:?}");
+            assert_eq!(to_float64(&value), float, "{value:?}");
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Values handled by templates, mirroring the Go types produced by `json.Unmarshal`
+//! into an `interface{}`, plus the int constants of the template language.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A template value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    /// A missing map entry, printed as `<no value>`.
+    #[default]
+    Missing,
+    /// A JSON null or untyped nil, printed as `<nil>`.
+    Nil,
+    Bool(bool),
+    /// Integer constants and results of integer functions. Numbers coming from JSON are floats.
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// Truth as defined by Go templates: false, 0, nil, missing and empty collections are false.
+    pub fn is_true(&self) -> bool {
+        match self {
+            Value::Missing | Value::Nil => false,
+            Value::Bool(b) => *b,
+            Value::Int(i) => *i != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::List(l) => !l.is_empty(),
+            Value::Map(m) => !m.is_empty(),
+        }
+    }
+
+    /// The Go type name, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Missing => "<invalid>",
+            Value::Nil => "<nil>",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float64",
+            Value::String(_) => "string",
+            Value::List(_) => "[]interface {}",
+            Value::Map(_) => "map[string]interface {}",
+        }
+    }
+
+    /// Converts to JSON, `None` for a missing value. Integral floats become JSON integers.
+    pub fn to_json(&self) -> Option<serde_json::Value> {
+        Some(match self {
+            Value::Missing => return None,
+            Value::Nil => serde_json::Value::Null,
+            Value::Bool(b) => (*b).into(),
+            Value::Int(i) => (*i).into(),
+            Value::Float(f) => float_to_json(*f),
+            Value::String(s) => s.clone().into(),
+            Value::List(l) => l.iter().map(|v| v.to_json().unwrap_or_default()).collect(),
+            Value::Map(m) => m
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_json().unwrap_or_default()))
+                .collect(),
+        })
+    }
+}
+
+fn float_to_json(f: f64) -> serde_json::Value {
+    if f.fract() == 0.0 && f.abs() < 1e15 {
+        (f as i64).into()
+    } else {
+        serde_json::Number::from_f64(f).map_or(serde_json::Value::Null, Into::into)
+    }
+}
+
+impl From<&serde_json::Value> for Value {
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => Value::Float(n.as_f64().unwrap_or_default()),
+            serde_json::Value::String(s) => Value::String(s.clone()),
+            serde_json::Value::Array(a) => Value::List(a.iter().map(Into::into).collect()),
+            serde_json::Value::Object(o) => {
+                Value::Map(o.iter().map(|(k, v)| (k.clone(), v.into())).collect())
+            }
+        }
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        (&value).into()
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Value::Int(i)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self {
+        Value::Float(f)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats like Go's `%v` verb.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Missing => f.write_str("<no value>"),
+            Value::Nil => f.write_str("<nil>"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) => f.write_str(&format_float(*x, 'g', None)),
+            Value::String(s) => f.write_str(s),
+            Value::List(l) => {
+                f.write_str("[")?;
+                for (i, v) in l.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    nested(f, v)?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(m) => {
+                f.write_str("map[")?;
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{k}:")?;
+                    nested(f, v)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// Nested missing values are nil interfaces in Go.
+fn nested(f: &mut fmt::Formatter<'_>, v: &Value) -> fmt::Result {
+    match v {
+        Value::Missing => f.write_str("<nil>"),
+        v => write!(f, "{v}"),
+    }
+}
+
+/// Formats a float like Go's `strconv.FormatFloat` for the `e`, `f` and `g` formats.
+/// A `None` precision means the shortest representation that round-trips.
+pub(super) fn format_float(x: f64, format: char, precision: Option<usize>) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x.is_infinite() {
+        return if x > 0.0 { "+Inf" } else { "-Inf" }.to_string();
+    }
+    match format {
+        'e' | 'E' => {
+            let s = match precision {
+                Some(p) => format!("{x:.p$e}"),
+                None => format!("{x:e}"),
+            };
+            go_exponent(&s, format)
+        }
+        'f' | 'F' => match precision {
+            Some(p) => format!("{x:.p$}"),
+            None => format!("{x}"),
+        },
+        _ => {
+            // %e is used if the exponent is less than -4 or greater than or equal to the
+            // precision; the shortest representation uses precision 6 for this decision.
+            let (eprec, rounded) = match precision {
+                None => (6, format!("{x:e}")),
+                Some(p) => {
+                    let p = p.max(1);
+                    (p, trim_mantissa(&format!("{x:.prec$e}", prec = p - 1)))
+                }
+            };
+            let exp: i32 = rounded
+                .split_once('e')
+                .and_then(|(_, exp)| exp.parse().ok())
+                .unwrap_or_default();
+            if exp < -4 || exp >= eprec as i32 {
+                return go_exponent(&rounded, 'e');
+            }
+            match precision {
+                None => format!("{x}"),
+                Some(_) => {
+                    let decimals = (eprec as i32 - 1 - exp).max(0) as usize;
+                    let s = format!("{x:.decimals$}");
+                    if s.contains('.') {
+                        s.trim_end_matches('0').trim_end_matches('.').to_string()
+                    } else {
+                        s
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn trim_mantissa(s: &str) -> String {
+    match s.split_once('e') {
+        Some((mantissa, exp)) if mantissa.contains('.') => {
+            let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+            format!("{mantissa}e{exp}")
+        }
+        _ => s.to_string(),
+    }
+}
+
+/// Rewrites Rust's `1.5e6` exponent notation into Go's `1.5e+06`.
+fn go_exponent(s: &str, format: char) -> String {
+    let Some((mantissa, exp)) = s.split_once('e') else {
+        return s.to_string();
+    };
+    let (sign, digits) = match exp.strip_prefix('-') {
+        Some(digits) => ('-', digits),
+        None => ('+', exp),
+    };
+    format!("{mantissa}{format}{sign}{digits:0>2}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn formats_like_go() {
+        for (value, expected) in [
+            (Value::Missing, "<no value>"),
+            (Value::Nil, "<nil>"),
+            (Value::Int(-3), "-3"),
+            (Value::Float(3.0), "3"),
+            (Value::Float(1.5), "1.5"),
+            (Value::Float(100000.0), "100000"),
+            (Value::Float(1e6), "1e+06"),
+            (Value::Float(0.0001), "0.0001"),
+            (Value::Float(0.00001), "1e-05"),
+            (Value::Float(f64::NAN), "NaN"),
+            (Value::Float(f64::NEG_INFINITY), "-Inf"),
+            (
+                Value::List(vec![Value::Int(1), Value::Missing, "a".into()]),
+                "[1 <nil> a]",
+            ),
+            (
+                json!({"b": [true], "a": {"c": null}}).into(),
+                "map[a:map[c:<nil>] b:[true]]",
+            ),
+        ] {
+            assert_eq!(value.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn formats_floats() {
+        for (x, format, precision, expected) in [
+            (1234.5678, 'e', Some(2), "1.23e+03"),
+            (0.000123, 'e', None, "1.23e-04"),
+            (1e100, 'e', Some(0), "1e+100"),
+            (1.23456, 'f', Some(2), "1.23"),
+            (2.0, 'f', None, "2"),
+            (1234.5678, 'g', Some(3), "1.23e+03"),
+            (1234.5678, 'g', Some(6), "1234.57"),
+            (100.0, 'g', Some(3), "100"),
+            (0.5, 'g', Some(0), "0.5"),
+        ] {
+            assert_eq!(
+                format_float(x, format, precision),
+                expected,
+                "{x} {format} {precision:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn converts_json() {
+        let value: Value = json!({"n": 3, "l": [1.5, "a", null]}).into();
+        assert_eq!(
+            value,
+            Value::Map(
+                [
+                    ("n".to_string(), Value::Float(3.0)),
+                    (
+                        "l".to_string(),
+                        Value::List(vec![Value::Float(1.5), "a".into(), Value::Nil])
+                    ),
+                ]
+                .into()
+            )
+        );
+        assert_eq!(
+            value.to_json(),
+            Some(json!({"n": 3, "l": [1.5, "a", null]}))
+        );
+        assert_eq!(Value::Float(1e20).to_json(), Some(json!(1e20)));
+        assert_eq!(Value::Missing.to_json(), None);
+        assert_eq!(
+            Value::List(vec![Value::Missing]).to_json(),
+            Some(json!([null]))
+        );
+    }
+
+    #[test]
+    fn truth() {
+        for (value, expected) in [
+            (Value::Missing, false),
+            (Value::Nil, false),
+            (Value::Bool(false), false),
+            (Value::Int(0), false),
+            (Value::Float(0.0), false),
+            (Value::String(String::new()), false),
+            (Value::List(vec![]), false),
+            (Value::Map(BTreeMap::new()), false),
+            (Value::Int(-1), true),
+            (Value::Float(0.1), true),
+            ("0".into(), true),
+            (Value::List(vec![Value::Nil]), true),
+        ] {
+            assert_eq!(value.is_true(), expected, "{value:?}");
+        }
+    }
+}
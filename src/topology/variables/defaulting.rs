@@ -0,0 +1,627 @@
+//! Defaulting of the variables of a Cluster from the schemas of its ClusterClass, following
+//! `DefaultClusterVariables` and `DefaultMachineVariables` of the upstream
+//! `internal/topology/variables` package.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::api::capi_cluster::{
+    Cluster, ClusterTopologyControlPlaneVariables, ClusterTopologyControlPlaneVariablesOverrides,
+    ClusterTopologyVariables, ClusterTopologyWorkersMachineDeploymentsVariables,
+    ClusterTopologyWorkersMachineDeploymentsVariablesOverrides,
+    ClusterTopologyWorkersMachinePoolsVariables,
+    ClusterTopologyWorkersMachinePoolsVariablesOverrides,
+};
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::topology::field::{self, FieldError};
+
+use super::definitions::{class_variables, ClassVariable, ClusterVariable};
+use super::validation::{definition, values_index};
+
+/// A value set from the default of a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefaultedValue {
+    /// Path of the defaulted field, e.g. `spec.topology.variables[2].value` for a variable
+    /// added with its default or `spec.topology.variables[0].value.network.mtu` for a field
+    /// of a variable.
+    pub field: String,
+    /// Name of the variable the field belongs to.
+    pub variable: String,
+    pub value: Value,
+}
+
+impl fmt::Display for DefaultedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.value)
+    }
+}
+
+/// The variables of a Cluster topology with the defaults of their schemas applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DefaultedVariables {
+    /// `spec.topology.variables`, with the variables missing from the Cluster added at the end.
+    pub variables: Vec<ClusterTopologyVariables>,
+    /// `spec.topology.controlPlane.variables.overrides`, if set.
+    pub control_plane_overrides: Option<Vec<ClusterTopologyControlPlaneVariablesOverrides>>,
+    /// The overrides of the MachineDeployments setting them, by MachineDeployment topology name.
+    pub machine_deployment_overrides:
+        BTreeMap<String, Vec<ClusterTopologyWorkersMachineDeploymentsVariablesOverrides>>,
+    /// The overrides of the MachinePools setting them, by MachinePool topology name.
+    pub machine_pool_overrides:
+        BTreeMap<String, Vec<ClusterTopologyWorkersMachinePoolsVariablesOverrides>>,
+    /// The values that were defaulted, in the order they were set.
+    pub defaulted: Vec<DefaultedValue>,
+}
+
+impl DefaultedVariables {
+    /// Whether any value was defaulted.
+    pub fn is_empty(&self) -> bool {
+        self.defaulted.is_empty()
+    }
+
+    /// Writes the defaulted variables and overrides to the topology of a Cluster.
+    pub fn apply(self, cluster: &mut Cluster) {
+        let Some(topology) = &mut cluster.spec.topology else {
+            return;
+        };
+        if !self.variables.is_empty() {
+            topology.variables = Some(self.variables);
+        }
+        if let Some(overrides) = self.control_plane_overrides {
+            topology
+                .control_plane
+                .get_or_insert_with(Default::default)
+                .variables
+                .get_or_insert_with(ClusterTopologyControlPlaneVariables::default)
+                .overrides = Some(overrides);
+        }
+        let workers = topology.workers.as_mut();
+        let (machine_deployments, machine_pools) = match workers {
+            Some(workers) => (
+                workers.machine_deployments.as_mut(),
+                workers.machine_pools.as_mut(),
+            ),
+            None => (None, None),
+        };
+        let mut machine_deployment_overrides = self.machine_deployment_overrides;
+        for machine_deployment in machine_deployments.into_iter().flatten() {
+            if let Some(overrides) = machine_deployment_overrides.remove(&machine_deployment.name) {
+                machine_deployment
+                    .variables
+                    .get_or_insert_with(ClusterTopologyWorkersMachineDeploymentsVariables::default)
+                    .overrides = Some(overrides);
+            }
+        }
+        let mut machine_pool_overrides = self.machine_pool_overrides;
+        for machine_pool in machine_pools.into_iter().flatten() {
+            if let Some(overrides) = machine_pool_overrides.remove(&machine_pool.name) {
+                machine_pool
+                    .variables
+                    .get_or_insert_with(ClusterTopologyWorkersMachinePoolsVariables::default)
+                    .overrides = Some(overrides);
+            }
+        }
+    }
+}
+
+/// Defaults the topology variables of a Cluster and its control plane, MachineDeployment and
+/// MachinePool overrides from the variable schemas of its ClusterClass.
+///
+/// Variables missing from `spec.topology.variables` are added when their schema has a default.
+/// If the definitions of a variable conflict, a value is added per definition with a default,
+/// with `definitionFrom` set to the patch the definition comes from. Overrides are never added,
+/// but like the topology variables they get the defaults of nested fields. Values that do not
+/// refer to a definition are left as they are for validation to report.
+pub fn default_topology_variables(
+    cluster: &Cluster,
+    class: &ClusterClass,
+) -> Result<DefaultedVariables, Vec<FieldError>> {
+    let Some(topology) = &cluster.spec.topology else {
+        return Ok(DefaultedVariables::default());
+    };
+    let definitions = class_variables(class);
+    let path = "spec.topology";
+    let mut errors = Vec::new();
+    let mut result = DefaultedVariables::default();
+    match default_cluster_variables(
+        topology.variables.as_deref().unwrap_or_default(),
+        &definitions,
+        true,
+        &field::child(path, "variables"),
+    ) {
+        Ok((variables, defaulted)) => {
+            result.variables = variables;
+            result.defaulted.extend(defaulted);
+        }
+        Err(error) => errors.push(error),
+    }
+    if let Some(overrides) = topology
+        .control_plane
+        .as_ref()
+        .and_then(|control_plane| control_plane.variables.as_ref())
+        .and_then(|variables| variables.overrides.as_deref())
+    {
+        match default_cluster_variables(
+            overrides,
+            &definitions,
+            false,
+            &field::child(path, "controlPlane.variables.overrides"),
+        ) {
+            Ok((overrides, defaulted)) => {
+                result.control_plane_overrides = Some(overrides);
+                result.defaulted.extend(defaulted);
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+    let workers = topology.workers.as_ref();
+    for (i, machine_deployment) in workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = machine_deployment
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let path = field::index(&field::child(path, "workers.machineDeployments"), i);
+            match default_cluster_variables(
+                overrides,
+                &definitions,
+                false,
+                &field::child(&path, "variables.overrides"),
+            ) {
+                Ok((overrides, defaulted)) => {
+                    result
+                        .machine_deployment_overrides
+                        .insert(machine_deployment.name.clone(), overrides);
+                    result.defaulted.extend(defaulted);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+    for (i, machine_pool) in workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = machine_pool
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let path = field::index(&field::child(path, "workers.machinePools"), i);
+            match default_cluster_variables(
+                overrides,
+                &definitions,
+                false,
+                &field::child(&path, "variables.overrides"),
+            ) {
+                Ok((overrides, defaulted)) => {
+                    result
+                        .machine_pool_overrides
+                        .insert(machine_pool.name.clone(), overrides);
+                    result.defaulted.extend(defaulted);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(result)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Defaults variable values from the variable definitions of a ClusterClass, reporting the
+/// defaulted fields below `path`, e.g. `spec.topology.variables`. With `add_missing`, variables
+/// that are not set and have a default are added after the existing values. Fails if the
+/// values are ambiguous, i.e. a variable is set more than once for the same definition.
+pub fn default_cluster_variables<V: ClusterVariable + Clone>(
+    values: &[V],
+    definitions: &[ClassVariable],
+    add_missing: bool,
+    path: &str,
+) -> Result<(Vec<V>, Vec<DefaultedValue>), FieldError> {
+    let values_index = values_index(values).map_err(|message| {
+        let values = values
+            .iter()
+            .map(|value| serde_json::json!({ "name": value.name() }))
+            .collect();
+        FieldError::invalid(
+            path,
+            Value::Array(values),
+            format!("cluster variables not valid: {message}"),
+        )
+    })?;
+    let mut defaulted = Vec::new();
+    let mut out = values.to_vec();
+    for (i, value) in out.iter_mut().enumerate() {
+        let Ok(definition) = definition(definitions, value.name(), value.definition_from()) else {
+            continue;
+        };
+        let name = value.name().to_string();
+        let field = field::child(&field::index(path, i), "value");
+        for (field, default) in default_value(&definition.schema, value.value_mut(), &field) {
+            defaulted.push(DefaultedValue {
+                field,
+                variable: name.clone(),
+                value: default,
+            });
+        }
+    }
+    if add_missing {
+        let mut added = Vec::new();
+        for variable in definitions {
+            let froms = values_index.get(variable.name.as_str());
+            for definition in &variable.definitions {
+                let set = froms.is_some_and(|froms| {
+                    !variable.definitions_conflict || froms.contains(&definition.from.as_str())
+                });
+                if set {
+                    continue;
+                }
+                let Some(mut value) = definition.schema.get("default").cloned() else {
+                    continue;
+                };
+                // the defaults of the fields of the default are part of the added value
+                default_value(&definition.schema, &mut value, "");
+                let definition_from = variable
+                    .definitions_conflict
+                    .then(|| definition.from.clone());
+                added.push(V::new(variable.name.clone(), definition_from, value));
+                if !variable.definitions_conflict {
+                    break;
+                }
+            }
+        }
+        for value in added {
+            defaulted.push(DefaultedValue {
+                field: field::child(&field::index(path, out.len()), "value"),
+                variable: value.name().to_string(),
+                value: value.value().clone(),
+            });
+            out.push(value);
+        }
+    }
+    Ok((out, defaulted))
+}
+
+/// Sets the defaults of the fields missing from a value, like the structural defaulting of the
+/// apiserver, and returns the defaulted fields with their values.
+fn default_value(schema: &Value, value: &mut Value, path: &str) -> Vec<(String, Value)> {
+    let mut defaulted = Vec::new();
+    walk(schema, value, path, &mut defaulted);
+    defaulted
+}
+
+fn walk(schema: &Value, value: &mut Value, path: &str, defaulted: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, schema) in properties.into_iter().flatten() {
+                if object.contains_key(key) {
+                    continue;
+                }
+                if let Some(default) = schema.get("default") {
+                    let mut default = default.clone();
+                    walk(schema, &mut default, "", &mut Vec::new());
+                    defaulted.push((field::child(path, key), default.clone()));
+                    object.insert(key.clone(), default);
+                }
+            }
+            let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+            for (key, value) in object.iter_mut() {
+                if let Some(schema) = properties.and_then(|p| p.get(key)).or(additional) {
+                    walk(schema, value, &field::child(path, key), defaulted);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    walk(schema, item, &field::index(path, i), defaulted);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn class() -> ClusterClass {
+        let variable = |name: &str, schema: Value| json!({"name": name, "required": false, "schema": {"openAPIV3Schema": schema}});
+        serde_json::from_value(json!({
+            "metadata": {"name": "class"},
+            "spec": {"variables": [
+                variable("replicas", json!({"type": "integer", "default": 3})),
+                variable("token", json!({"type": "string"})),
+                variable("network", json!({
+                    "type": "object",
+                    "default": {},
+                    "properties": {
+                        "dns": {
+                            "type": "object",
+                            "default": {},
+                            "properties": {"servers": {"type": "array", "default": ["1.1.1.1"]}},
+                        },
+                        "mtu": {"type": "integer", "default": 1500},
+                    },
+                })),
+                variable("labels", json!({
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": {"color": {"type": "string", "default": "blue"}},
+                    },
+                })),
+                variable("hosts", json!({
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"port": {"type": "integer", "default": 22}},
+                    },
+                })),
+            ]},
+        }))
+        .unwrap()
+    }
+
+    fn cluster(topology: Value) -> Cluster {
+        let mut spec = json!({"class": "class", "version": "v1.30.0"});
+        for (key, value) in topology.as_object().into_iter().flatten() {
+            spec[key] = value.clone();
+        }
+        serde_json::from_value(json!({"metadata": {"name": "c1"}, "spec": {"topology": spec}}))
+            .unwrap()
+    }
+
+    fn strings<T: ToString>(values: &[T]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn defaults_topology_variables() {
+        let mut cluster = cluster(json!({
+            "variables": [
+                {"name": "labels", "value": {"a": {}, "b": {"color": "red"}}},
+                {"name": "hosts", "value": [{"name": "x"}, {"port": 80}]},
+                {"name": "network", "value": {"dns": {}}},
+            ],
+        }));
+        let defaulted = default_topology_variables(&cluster, &class()).unwrap();
+        assert_eq!(
+            strings(&defaulted.defaulted),
+            [
+                r#"spec.topology.variables[0].value.a.color: "blue""#,
+                "spec.topology.variables[1].value[0].port: 22",
+                // missing fields are defaulted before the ones that are set
+                "spec.topology.variables[2].value.mtu: 1500",
+                r#"spec.topology.variables[2].value.dns.servers: ["1.1.1.1"]"#,
+                "spec.topology.variables[3].value: 3",
+            ]
+        );
+        assert_eq!(
+            defaulted
+                .defaulted
+                .iter()
+                .map(|defaulted| defaulted.variable.as_str())
+                .collect::<Vec<_>>(),
+            ["labels", "hosts", "network", "network", "replicas"]
+        );
+        defaulted.apply(&mut cluster);
+        assert_eq!(
+            serde_json::to_value(&cluster.spec.topology.unwrap().variables).unwrap(),
+            json!([
+                {"name": "labels", "value": {"a": {"color": "blue"}, "b": {"color": "red"}}},
+                {"name": "hosts", "value": [{"name": "x", "port": 22}, {"port": 80}]},
+                {"name": "network", "value": {"dns": {"servers": ["1.1.1.1"]}, "mtu": 1500}},
+                {"name": "replicas", "value": 3},
+            ])
+        );
+    }
+
+    #[test]
+    fn adds_missing_variables_with_the_defaults_of_their_fields() {
+        let defaulted = default_topology_variables(&cluster(json!({})), &class()).unwrap();
+        // the fields of an added default are part of its value, not defaulted separately
+        assert_eq!(
+            strings(&defaulted.defaulted),
+            [
+                "spec.topology.variables[0].value: 3",
+                r#"spec.topology.variables[1].value: {"dns":{"servers":["1.1.1.1"]},"mtu":1500}"#,
+            ]
+        );
+        assert_eq!(
+            strings(
+                &defaulted
+                    .variables
+                    .iter()
+                    .map(|variable| &variable.name)
+                    .collect::<Vec<_>>()
+            ),
+            ["replicas", "network"]
+        );
+    }
+
+    #[test]
+    fn defaults_overrides_without_adding_them() {
+        let mut cluster = cluster(json!({
+            "variables": [{"name": "replicas", "value": 1}, {"name": "network", "value": {"mtu": 9000}}],
+            "controlPlane": {"variables": {"overrides": [{"name": "network", "value": {"dns": {"servers": []}}}]}},
+            "workers": {
+                "machineDeployments": [
+                    {"class": "md", "name": "md-0"},
+                    {"class": "md", "name": "md-1", "variables": {"overrides": [{"name": "hosts", "value": [{}]}]}},
+                ],
+                "machinePools": [
+                    {"class": "mp", "name": "mp-0", "variables": {"overrides": [{"name": "token", "value": "t"}]}},
+                ],
+            },
+        }));
+        let defaulted = default_topology_variables(&cluster, &class()).unwrap();
+        assert_eq!(
+            strings(&defaulted.defaulted),
+            [
+                r#"spec.topology.variables[1].value.dns: {"servers":["1.1.1.1"]}"#,
+                "spec.topology.controlPlane.variables.overrides[0].value.mtu: 1500",
+                "spec.topology.workers.machineDeployments[1].variables.overrides[0].value[0].port: 22",
+            ]
+        );
+        assert_eq!(defaulted.variables.len(), 2);
+        assert_eq!(
+            defaulted
+                .machine_deployment_overrides
+                .keys()
+                .collect::<Vec<_>>(),
+            ["md-1"]
+        );
+        assert_eq!(
+            defaulted.machine_pool_overrides.keys().collect::<Vec<_>>(),
+            ["mp-0"]
+        );
+        defaulted.apply(&mut cluster);
+        let topology = cluster.spec.topology.unwrap();
+        assert_eq!(
+            serde_json::to_value(topology.control_plane.unwrap().variables).unwrap(),
+            json!({"overrides": [{"name": "network", "value": {"dns": {"servers": []}, "mtu": 1500}}]})
+        );
+        let machine_deployments = topology.workers.unwrap().machine_deployments.unwrap();
+        assert_eq!(machine_deployments[0].variables, None);
+        assert_eq!(
+            serde_json::to_value(&machine_deployments[1].variables).unwrap(),
+            json!({"overrides": [{"name": "hosts", "value": [{"port": 22}]}]})
+        );
+    }
+
+    #[test]
+    fn adds_a_value_per_conflicting_definition() {
+        let definition = |from: &str, schema: Value| json!({"from": from, "required": false, "schema": {"openAPIV3Schema": schema}});
+        let class = |conflict: bool| -> ClusterClass {
+            serde_json::from_value(json!({
+                "metadata": {"name": "class"},
+                "spec": {},
+                "status": {"variables": [{
+                    "name": "size",
+                    "definitionsConflict": conflict,
+                    "definitions": [
+                        definition("inline", json!({"type": "string", "default": "small"})),
+                        definition("patch-a", json!({"type": "integer", "default": 1})),
+                        definition("patch-b", json!({"type": "integer"})),
+                    ],
+                }]},
+            }))
+            .unwrap()
+        };
+        let topology_variables = |cluster: &Cluster, class: &ClusterClass| {
+            let defaulted = default_topology_variables(cluster, class).unwrap();
+            serde_json::to_value(defaulted.variables).unwrap()
+        };
+        assert_eq!(
+            topology_variables(&cluster(json!({})), &class(true)),
+            json!([
+                {"name": "size", "definitionFrom": "inline", "value": "small"},
+                {"name": "size", "definitionFrom": "patch-a", "value": 1},
+            ])
+        );
+        assert_eq!(
+            topology_variables(
+                &cluster(
+                    json!({"variables": [{"name": "size", "definitionFrom": "patch-a", "value": 2}]})
+                ),
+                &class(true)
+            ),
+            json!([
+                {"name": "size", "definitionFrom": "patch-a", "value": 2},
+                {"name": "size", "definitionFrom": "inline", "value": "small"},
+            ])
+        );
+        // without a conflict the first definition with a default is used for all of them
+        assert_eq!(
+            topology_variables(&cluster(json!({})), &class(false)),
+            json!([{"name": "size", "value": "small"}])
+        );
+        assert_eq!(
+            topology_variables(
+                &cluster(json!({"variables": [{"name": "size", "value": "large"}]})),
+                &class(false)
+            ),
+            json!([{"name": "size", "value": "large"}])
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_values() {
+        let cluster = cluster(json!({
+            "variables": [{"name": "replicas", "value": 1}],
+            "controlPlane": {"variables": {"overrides": [
+                {"name": "replicas", "value": 1},
+                {"name": "replicas", "value": 2},
+            ]}},
+        }));
+        assert_eq!(
+            strings(&default_topology_variables(&cluster, &class()).unwrap_err()),
+            [
+                r#"spec.topology.controlPlane.variables.overrides: Invalid value: [{"name":"replicas"},{"name":"replicas"}]: cluster variables not valid: variable name must be unique. Variable with name "replicas" and definitionFrom "" is defined more than once"#
+            ]
+        );
+    }
+
+    #[test]
+    fn walks_values() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "a": {
+                    "type": "object",
+                    "default": {"b": {}},
+                    "properties": {
+                        "b": {"type": "object", "properties": {"c": {"type": "integer", "default": 1}}},
+                        "d": {"type": "string", "default": "d"},
+                    },
+                },
+                "e": {"type": "array", "items": {"type": "object", "properties": {"f": {"default": true}}}},
+                "g": {
+                    "type": "object",
+                    "additionalProperties": {"type": "object", "properties": {"h": {"default": 0}}},
+                },
+            },
+        });
+        for (mut value, expected, defaulted) in [
+            (
+                json!({}),
+                json!({"a": {"b": {"c": 1}, "d": "d"}}),
+                vec![("v.a", json!({"b": {"c": 1}, "d": "d"}))],
+            ),
+            (
+                json!({"a": {}, "e": [{}, {"f": false}], "g": {"x": {}, "y": {"h": 1}}}),
+                json!({"a": {"d": "d"}, "e": [{"f": true}, {"f": false}], "g": {"x": {"h": 0}, "y": {"h": 1}}}),
+                vec![
+                    ("v.a.d", json!("d")),
+                    ("v.e[0].f", json!(true)),
+                    ("v.g.x.h", json!(0)),
+                ],
+            ),
+            (json!("a"), json!("a"), vec![]),
+        ] {
+            let expected_defaulted: Vec<_> = defaulted
+                .into_iter()
+                .map(|(field, value)| (field.to_string(), value))
+                .collect();
+            assert_eq!(default_value(&schema, &mut value, "v"), expected_defaulted);
+            assert_eq!(value, expected);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Variable definitions of a ClusterClass and the variable values that refer to them.
+
+use crate::api::capi_cluster::{
+    ClusterTopologyControlPlaneVariablesOverrides, ClusterTopologyVariables,
+    ClusterTopologyWorkersMachineDeploymentsVariablesOverrides,
+    ClusterTopologyWorkersMachinePoolsVariablesOverrides,
+};
+use crate::api::capi_clusterclass::ClusterClass;
+
+use super::INLINE_DEFINITION_FROM;
+
+/// A variable of a ClusterClass with all its definitions, like `ClusterClassStatusVariable`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClassVariable {
+    pub name: String,
+    /// Whether the definitions have different schemas, in which case values must set
+    /// `definitionFrom`.
+    pub definitions_conflict: bool,
+    pub definitions: Vec<VariableDefinition>,
+}
+
+/// A definition of a variable, either inline or from a patch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VariableDefinition {
+    /// `inline` or the name of the patch the definition comes from.
+    pub from: String,
+    pub required: bool,
+    /// The `openAPIV3Schema` of the variable as JSON.
+    pub schema: serde_json::Value,
+}
+
+/// The variables of a ClusterClass. These are the `status.variables` maintained by the
+/// controller, or the inline `spec.variables` if the status has not been written yet, e.g.
+/// for a ClusterClass read from a manifest.
+pub fn class_variables(class: &ClusterClass) -> Vec<ClassVariable> {
+    if let Some(status_variables) = class
+        .status
+        .as_ref()
+        .and_then(|status| status.variables.as_ref())
+        .filter(|variables| !variables.is_empty())
+    {
+        return status_variables
+            .iter()
+            .map(|variable| ClassVariable {
+                name: variable.name.clone(),
+                definitions_conflict: variable.definitions_conflict.unwrap_or_default(),
+                definitions: variable
+                    .definitions
+                    .iter()
+                    .map(|definition| VariableDefinition {
+                        from: definition.from.clone(),
+                        required: definition.required,
+                        schema: serde_json::to_value(&definition.schema.open_apiv3_schema)
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+            .collect();
+    }
+    class
+        .spec
+        .variables
+        .iter()
+        .flatten()
+        .map(|variable| ClassVariable {
+            name: variable.name.clone(),
+            definitions_conflict: false,
+            definitions: vec![VariableDefinition {
+                from: INLINE_DEFINITION_FROM.to_string(),
+                required: variable.required,
+                schema: serde_json::to_value(&variable.schema.open_apiv3_schema)
+                    .unwrap_or_default(),
+            }],
+        })
+        .collect()
+}
+
+/// A variable value set on a Cluster, i.e. a topology variable or a control plane,
+/// MachineDeployment or MachinePool override.
+pub trait ClusterVariable {
+    fn name(&self) -> &str;
+    fn definition_from(&self) -> Option<&str>;
+    fn value(&self) -> &serde_json::Value;
+    fn value_mut(&mut self) -> &mut serde_json::Value;
+    /// Creates a value, e.g. for a variable set from the default of its schema.
+    fn new(name: String, definition_from: Option<String>, value: serde_json::Value) -> Self
+    where
+        Self: Sized;
+}
+
+macro_rules! impl_cluster_variable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ClusterVariable for $ty {
+                fn name(&self) -> &str {
+                    &self.name
+                }
+
+                fn definition_from(&self) -> Option<&str> {
+                    self.definition_from.as_deref()
+                }
+
+                fn value(&self) -> &serde_json::Value {
+                    &self.value
+                }
+
+                fn value_mut(&mut self) -> &mut serde_json::Value {
+                    &mut self.value
+                }
+
+                fn new(
+                    name: String,
+                    definition_from: Option<String>,
+                    value: serde_json::Value,
+                ) -> Self {
+                    Self {
+                        name,
+                        definition_from,
+                        value,
+                        ..Default::default()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_cluster_variable!(
+    ClusterTopologyVariables,
+    ClusterTopologyControlPlaneVariablesOverrides,
+    ClusterTopologyWorkersMachineDeploymentsVariablesOverrides,
+    ClusterTopologyWorkersMachinePoolsVariablesOverrides,
+);
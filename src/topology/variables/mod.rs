@@ -0,0 +1,190 @@
+//! Variables passed to ClusterClass patches, following `internal/controllers/topology/cluster/patches/variables`.
+//!
+//! Variables are kept by name as JSON. The `builtin` variable holds the values computed from the
+//! Cluster and its topology, the others are the user variables set in `Cluster.spec.topology.variables`.
+
+mod defaulting;
+mod definitions;
+pub mod rules;
+pub mod schema;
+mod validation;
+
+use std::collections::BTreeMap;
+
+use crate::api::capi_cluster::Cluster;
+
+use super::template;
+
+pub use defaulting::{
+    default_cluster_variables, default_topology_variables, DefaultedValue, DefaultedVariables,
+};
+pub use definitions::{class_variables, ClassVariable, ClusterVariable, VariableDefinition};
+pub use rules::{evaluate_rules, RuleViolation, ValidationReason, ValidationRule};
+pub use validation::{
+    validate_cluster_variable, validate_cluster_variables, validate_topology_variables,
+};
+
+/// Variable values by name.
+pub type Variables = BTreeMap<String, serde_json::Value>;
+
+/// Name of the variable holding the builtin variables.
+pub const BUILTINS_NAME: &str = "builtin";
+
+/// `definitionFrom` of the variables defined for inline patches.
+const INLINE_DEFINITION_FROM: &str = "inline";
+
+/// The user variables of a Cluster available to inline patches, i.e. the topology variables
+/// without a `definitionFrom` or defined `inline`. A user variable called `builtin` is ignored.
+pub fn cluster_variables(cluster: &Cluster) -> Variables {
+    let topology_variables = cluster
+        .spec
+        .topology
+        .as_ref()
+        .and_then(|topology| topology.variables.as_deref())
+        .unwrap_or_default();
+    override_variables(topology_variables)
+}
+
+/// The variables set by values available to inline patches, e.g. the variable overrides of a
+/// MachineDeployment topology, filtered like [`cluster_variables`].
+pub fn override_variables<V: ClusterVariable>(values: &[V]) -> Variables {
+    values
+        .iter()
+        .filter(|variable| variable.name() != BUILTINS_NAME)
+        .filter(|variable| {
+            matches!(
+                variable.definition_from(),
+                None | Some("") | Some(INLINE_DEFINITION_FROM)
+            )
+        })
+        .map(|variable| (variable.name().to_string(), variable.value().clone()))
+        .collect()
+}
+
+/// Merges variable maps, later maps win. The `builtin` variables are merged recursively so
+/// template specific builtins, e.g. `builtin.machineDeployment`, extend the global ones.
+pub fn merge_variables<'a>(maps: impl IntoIterator<Item = &'a Variables>) -> Variables {
+    let mut merged = Variables::new();
+    for map in maps {
+        for (name, value) in map {
+            match merged.get_mut(name) {
+                Some(existing) if name == BUILTINS_NAME => merge_json(existing, value),
+                _ => {
+                    merged.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+    merged
+}
+
+fn merge_json(dest: &mut serde_json::Value, src: &serde_json::Value) {
+    match (dest, src) {
+        (serde_json::Value::Object(dest), serde_json::Value::Object(src)) => {
+            for (key, value) in src {
+                match dest.get_mut(key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        dest.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (dest, src) => *dest = src.clone(),
+    }
+}
+
+/// Error looking up a variable path.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum VariableError {
+    #[error("variable {0:?} does not exist")]
+    NotFound(String),
+    #[error("variable {path:?} does not exist: failed to lookup segment {segment:?}")]
+    MissingSegment { path: String, segment: String },
+    #[error("variable {path:?}: failed to get array element: {segment:?} is not an array")]
+    NotAnArray { path: String, segment: String },
+    #[error("variable {path:?}: index {index} out of bounds for {segment:?}")]
+    IndexOutOfBounds {
+        path: String,
+        segment: String,
+        index: usize,
+    },
+    #[error("variable {path:?}: invalid path segment {segment:?}")]
+    InvalidSegment { path: String, segment: String },
+}
+
+/// Returns the value at a variable path such as `builtin.cluster.name` or `subnets[0].cidr`.
+pub fn get_variable_value(
+    variables: &Variables,
+    path: &str,
+) -> Result<serde_json::Value, VariableError> {
+    let mut segments = path.split('.');
+    let name = segments.next().unwrap_or_default();
+    let (name, index) = parse_segment(path, name)?;
+    let mut value = variables
+        .get(name)
+        .ok_or_else(|| VariableError::NotFound(name.to_string()))?;
+    if let Some(index) = index {
+        value = array_element(path, name, value, index)?;
+    }
+    for segment in segments {
+        let (field, index) = parse_segment(path, segment)?;
+        value = value
+            .get(field)
+            .filter(|_| value.is_object())
+            .ok_or_else(|| VariableError::MissingSegment {
+                path: path.to_string(),
+                segment: field.to_string(),
+            })?;
+        if let Some(index) = index {
+            value = array_element(path, field, value, index)?;
+        }
+    }
+    Ok(value.clone())
+}
+
+/// Splits `name[index]` into the name and the index.
+fn parse_segment<'a>(
+    path: &str,
+    segment: &'a str,
+) -> Result<(&'a str, Option<usize>), VariableError> {
+    let Some((name, rest)) = segment.split_once('[') else {
+        return Ok((segment, None));
+    };
+    rest.strip_suffix(']')
+        .and_then(|index| index.parse().ok())
+        .map(|index| (name, Some(index)))
+        .ok_or_else(|| VariableError::InvalidSegment {
+            path: path.to_string(),
+            segment: segment.to_string(),
+        })
+}
+
+fn array_element<'a>(
+    path: &str,
+    segment: &str,
+    value: &'a serde_json::Value,
+    index: usize,
+) -> Result<&'a serde_json::Value, VariableError> {
+    let array = value.as_array().ok_or_else(|| VariableError::NotAnArray {
+        path: path.to_string(),
+        segment: segment.to_string(),
+    })?;
+    array
+        .get(index)
+        .ok_or_else(|| VariableError::IndexOutOfBounds {
+            path: path.to_string(),
+            segment: segment.to_string(),
+            index,
+        })
+}
+
+/// The data templates are executed with, e.g. `{{ .builtin.cluster.name }}`.
+pub fn template_data(variables: &Variables) -> template::Value {
+    template::Value::Map(
+        variables
+            .iter()
+            .map(|(name, value)| (name.clone(), value.into()))
+            .collect(),
+    )
+}
@@ -0,0 +1,230 @@
+//! `x-kubernetes-validations` rules of variable schemas, evaluated with CEL the way the
+//! apiserver does for custom resources: `self` is the value the rule is declared on and
+//! `oldSelf` the previous value, so transition rules only run when there is one.
+
+use serde_json::Value;
+
+use crate::api::capi_clusterclass::{
+    ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidations,
+    ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidationsReason,
+};
+use crate::topology::cel::{self, Activation, Program};
+use crate::topology::field::{self, FieldError};
+
+/// A validation rule; the status copy of a schema has the same shape.
+pub type ValidationRule = ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidations;
+
+/// The reason of a failed validation rule, `FieldValueInvalid` unless the rule sets one.
+pub type ValidationReason = ClusterClassVariablesSchemaOpenApiv3SchemaXKubernetesValidationsReason;
+
+const SELF: &str = "self";
+const OLD_SELF: &str = "oldSelf";
+
+/// A validation rule that failed or could not be evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleViolation {
+    /// Path of the field, including the `fieldPath` of the rule.
+    pub field: String,
+    pub rule: String,
+    pub reason: ValidationReason,
+    pub message: String,
+    /// The schema type of the validated value, reported as the bad value upstream.
+    pub schema_type: String,
+}
+
+impl From<RuleViolation> for FieldError {
+    fn from(violation: RuleViolation) -> Self {
+        let RuleViolation {
+            field,
+            reason,
+            message,
+            schema_type,
+            ..
+        } = violation;
+        match reason {
+            ValidationReason::FieldValueForbidden => FieldError::forbidden(field, message),
+            ValidationReason::FieldValueRequired => FieldError::required(field, message),
+            ValidationReason::FieldValueDuplicate => {
+                FieldError::duplicate(field, schema_type.into())
+            }
+            ValidationReason::FieldValueInvalid | ValidationReason::Other(_) => {
+                FieldError::invalid(field, schema_type.into(), message)
+            }
+        }
+    }
+}
+
+/// Evaluates the validation rules of a schema and its nested schemas against a value. Rules
+/// referring to `oldSelf` are skipped where there is no old value to compare with.
+pub fn evaluate_rules(
+    schema: &Value,
+    value: &Value,
+    old_value: Option<&Value>,
+    path: &str,
+) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    walk(schema, value, old_value, path, &mut violations);
+    violations
+}
+
+/// Like [`evaluate_rules`], returning the violations as field errors.
+pub fn validate_rules(
+    schema: &Value,
+    value: &Value,
+    old_value: Option<&Value>,
+    path: &str,
+) -> Vec<FieldError> {
+    evaluate_rules(schema, value, old_value, path)
+        .into_iter()
+        .map(Into::into)
+        .collect()
+}
+
+fn walk(
+    schema: &Value,
+    value: &Value,
+    old_value: Option<&Value>,
+    path: &str,
+    violations: &mut Vec<RuleViolation>,
+) {
+    let rules: Vec<ValidationRule> = schema
+        .get("x-kubernetes-validations")
+        .and_then(|rules| serde_json::from_value(rules.clone()).ok())
+        .unwrap_or_default();
+    if !rules.is_empty() {
+        let mut activation = Activation::new();
+        activation.insert(SELF.to_string(), cel::Value::from_json(value, Some(schema)));
+        if let Some(old_value) = old_value {
+            activation.insert(
+                OLD_SELF.to_string(),
+                cel::Value::from_json(old_value, Some(schema)),
+            );
+        }
+        let schema_type = schema
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        for rule in &rules {
+            violations.extend(evaluate_rule(rule, &activation, schema_type, path));
+        }
+    }
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+            for (key, value) in object {
+                if let Some(schema) = properties.and_then(|p| p.get(key)).or(additional) {
+                    let old_value = old_value.and_then(|old| old.get(key));
+                    walk(
+                        schema,
+                        value,
+                        old_value,
+                        &field::child(path, key),
+                        violations,
+                    );
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                // list items are not correlated with old items, so transition rules on them
+                // are skipped
+                for (i, item) in items.iter().enumerate() {
+                    walk(schema, item, None, &field::index(path, i), violations);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn evaluate_rule(
+    rule: &ValidationRule,
+    activation: &Activation,
+    schema_type: &str,
+    path: &str,
+) -> Option<RuleViolation> {
+    let field = match rule.field_path.as_deref().filter(|p| !p.is_empty()) {
+        Some(field_path) if field_path.starts_with(['.', '[']) => format!("{path}{field_path}"),
+        Some(field_path) => field::child(path, field_path),
+        None => path.to_string(),
+    };
+    let violation = |reason, message| RuleViolation {
+        field: field.clone(),
+        rule: rule.rule.clone(),
+        reason,
+        message,
+        schema_type: schema_type.to_string(),
+    };
+    let program = match Program::compile(&rule.rule) {
+        Ok(program) => program,
+        Err(err) => {
+            return Some(violation(
+                ValidationReason::FieldValueInvalid,
+                format!("rule compile error: {err}"),
+            ))
+        }
+    };
+    if program.references(OLD_SELF) && !activation.contains_key(OLD_SELF) {
+        return None;
+    }
+    match program.evaluate(activation) {
+        Ok(cel::Value::Bool(true)) => None,
+        Ok(cel::Value::Bool(false)) => Some(violation(
+            rule.reason
+                .clone()
+                .unwrap_or(ValidationReason::FieldValueInvalid),
+            message(rule, activation),
+        )),
+        Ok(result) => Some(violation(
+            ValidationReason::FieldValueInvalid,
+            format!(
+                "rule evaluated to {}, expected bool: {}",
+                result.type_name(),
+                rule_error_string(rule)
+            ),
+        )),
+        Err(err) => {
+            let err = err.to_string();
+            let message = if err.starts_with("found no matching overload") {
+                format!(
+                    "'{err}': call arguments did not match a supported operator, function or macro signature for rule: {}",
+                    rule_error_string(rule)
+                )
+            } else {
+                format!("{err} evaluating rule: {}", rule_error_string(rule))
+            };
+            Some(violation(ValidationReason::FieldValueInvalid, message))
+        }
+    }
+}
+
+/// The message of a failed rule: the result of `messageExpression` if it evaluates to a
+/// single line, else `message`, else `failed rule: <rule>`.
+fn message(rule: &ValidationRule, activation: &Activation) -> String {
+    let expression_message = rule
+        .message_expression
+        .as_deref()
+        .filter(|expression| !expression.trim().is_empty())
+        .and_then(|expression| Program::compile(expression).ok())
+        .and_then(|program| program.evaluate(activation).ok())
+        .and_then(|result| match result {
+            cel::Value::String(message)
+                if !message.trim().is_empty() && !message.contains(['\n', '\r']) =>
+            {
+                Some(message.trim().to_string())
+            }
+            _ => None,
+        });
+    expression_message.unwrap_or_else(|| match rule.message.as_deref().map(str::trim) {
+        Some(message) if !message.is_empty() => message.to_string(),
+        _ => format!("failed rule: {}", rule_error_string(rule)),
+    })
+}
+
+fn rule_error_string(rule: &ValidationRule) -> &str {
+    match rule.message.as_deref().map(str::trim) {
+        Some(message) if !message.is_empty() => message,
+        _ => rule.rule.trim(),
+    }
+}
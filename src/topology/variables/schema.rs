@@ -0,0 +1,708 @@
+//! Validation of variable values against their OpenAPI schema, following the structural schema
+//! validation the apiserver does for custom resources, which the Cluster API webhooks reuse for
+//! variables.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::LazyLock;
+
+use base64::Engine;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::api::duration::GoDuration;
+use crate::topology::field::{self, FieldError};
+
+/// Validates a value against an `openAPIV3Schema`, reporting errors below `path`.
+pub fn validate_value(schema: &Value, value: &Value, path: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    validate(schema, value, path, &mut errors);
+    errors
+}
+
+/// The fields of a value that are not specified in the schema, i.e. the ones the apiserver
+/// would prune, as paths relative to the value such as `network.dns` or `hosts[1].port`.
+pub fn unknown_fields(schema: &Value, value: &Value) -> Vec<String> {
+    let mut unknown = Vec::new();
+    collect_unknown(schema, value, "", &mut unknown);
+    unknown
+}
+
+fn collect_unknown(schema: &Value, value: &Value, path: &str, unknown: &mut Vec<String>) {
+    let preserve = schema
+        .get("x-kubernetes-preserve-unknown-fields")
+        .and_then(Value::as_bool)
+        .unwrap_or_default();
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+            for (key, value) in object {
+                let path = field::child(path, key);
+                match (properties.and_then(|p| p.get(key)), additional) {
+                    (Some(schema), _) | (None, Some(schema)) => {
+                        collect_unknown(schema, value, &path, unknown)
+                    }
+                    _ if preserve || schema.get("additionalProperties") == Some(&true.into()) => {}
+                    _ => unknown.push(path),
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items").filter(|s| s.is_object()) {
+                for (i, item) in items.iter().enumerate() {
+                    collect_unknown(schema, item, &field::index(path, i), unknown);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The JSON type of a value as the OpenAPI validation reports it.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn is_integer(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn matches_type(ty: &str, value: &Value) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => is_integer(value),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        _ => true,
+    }
+}
+
+/// JSON equality comparing numbers by value, so `1` equals `1.0`.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_eq(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+fn validate(schema: &Value, value: &Value, path: &str, errors: &mut Vec<FieldError>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if flag(schema, "x-kubernetes-int-or-string") {
+        if !is_integer(value) && !value.is_string() {
+            errors.push(type_error(path, "integer,string", value));
+            return;
+        }
+    } else if let Some(ty) = schema.get("type").and_then(Value::as_str) {
+        if !matches_type(ty, value) {
+            errors.push(type_error(path, ty, value));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.iter().any(|allowed| json_eq(allowed, value)) {
+            errors.push(FieldError::not_supported(path, value.clone(), allowed));
+        }
+    }
+    match value {
+        Value::String(s) => validate_string(schema, s, path, errors),
+        Value::Number(_) => validate_number(schema, value, path, errors),
+        Value::Array(items) => validate_array(schema, items, path, errors),
+        Value::Object(object) => validate_object(schema, object, path, errors),
+        _ => {}
+    }
+    validate_combinators(schema, value, path, errors);
+}
+
+fn type_error(path: &str, ty: &str, value: &Value) -> FieldError {
+    let actual = type_name(value);
+    FieldError::type_invalid(
+        path,
+        actual.into(),
+        format!("{path} in body must be of type {ty}: {actual:?}"),
+    )
+}
+
+fn int(schema: &Map<String, Value>, name: &str) -> Option<i64> {
+    schema.get(name).and_then(Value::as_i64)
+}
+
+fn validate_string(schema: &Map<String, Value>, s: &str, path: &str, errors: &mut Vec<FieldError>) {
+    let length = s.chars().count() as i64;
+    if let Some(max) = int(schema, "maxLength").filter(|max| length > *max) {
+        errors.push(FieldError::too_long(path, max));
+    }
+    if let Some(min) = int(schema, "minLength").filter(|min| length < *min) {
+        errors.push(FieldError::invalid(
+            path,
+            s.into(),
+            format!("{path} in body should be at least {min} chars long"),
+        ));
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        match Regex::new(pattern) {
+            Ok(re) if re.is_match(s) => {}
+            Ok(_) => errors.push(FieldError::invalid(
+                path,
+                s.into(),
+                format!("{path} in body should match '{pattern}'"),
+            )),
+            Err(err) => errors.push(FieldError::internal(
+                path,
+                format!("invalid pattern {pattern:?}: {err}"),
+            )),
+        }
+    }
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        if !matches_format(format, s) {
+            errors.push(FieldError::invalid(
+                path,
+                s.into(),
+                format!("{path} in body must be of type {format}: {s:?}"),
+            ));
+        }
+    }
+}
+
+fn validate_number(
+    schema: &Map<String, Value>,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    let number = value.as_f64().unwrap_or_default();
+    if let Some(max) = schema.get("maximum").filter(|max| max.is_number()) {
+        let limit = max.as_f64().unwrap_or_default();
+        if flag(schema, "exclusiveMaximum") {
+            if number >= limit {
+                errors.push(FieldError::invalid(
+                    path,
+                    value.clone(),
+                    format!("{path} in body should be less than {max}"),
+                ));
+            }
+        } else if number > limit {
+            errors.push(FieldError::invalid(
+                path,
+                value.clone(),
+                format!("{path} in body should be less than or equal to {max}"),
+            ));
+        }
+    }
+    if let Some(min) = schema.get("minimum").filter(|min| min.is_number()) {
+        let limit = min.as_f64().unwrap_or_default();
+        if flag(schema, "exclusiveMinimum") {
+            if number <= limit {
+                errors.push(FieldError::invalid(
+                    path,
+                    value.clone(),
+                    format!("{path} in body should be greater than {min}"),
+                ));
+            }
+        } else if number < limit {
+            errors.push(FieldError::invalid(
+                path,
+                value.clone(),
+                format!("{path} in body should be greater than or equal to {min}"),
+            ));
+        }
+    }
+    if let Some(factor) = schema.get("multipleOf").filter(|factor| factor.is_number()) {
+        let divisor = factor.as_f64().unwrap_or_default();
+        if divisor != 0.0 && (number / divisor).fract() != 0.0 {
+            errors.push(FieldError::invalid(
+                path,
+                value.clone(),
+                format!("{path} in body should be a multiple of {factor}"),
+            ));
+        }
+    }
+}
+
+fn flag(schema: &Map<String, Value>, name: &str) -> bool {
+    schema
+        .get(name)
+        .and_then(Value::as_bool)
+        .unwrap_or_default()
+}
+
+fn validate_array(
+    schema: &Map<String, Value>,
+    items: &[Value],
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    if let Some(max) = int(schema, "maxItems").filter(|max| items.len() as i64 > *max) {
+        errors.push(FieldError::too_many(path, items.len(), max));
+    }
+    if let Some(min) = int(schema, "minItems").filter(|min| (items.len() as i64) < *min) {
+        errors.push(FieldError::invalid(
+            path,
+            Value::Array(items.to_vec()),
+            format!("{path} in body should have at least {min} items"),
+        ));
+    }
+    if flag(schema, "uniqueItems") {
+        if let Some(i) =
+            (1..items.len()).find(|&i| items[..i].iter().any(|prev| json_eq(prev, &items[i])))
+        {
+            errors.push(FieldError::duplicate(
+                field::index(path, i),
+                items[i].clone(),
+            ));
+        }
+    }
+    if let Some(item_schema) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            validate(item_schema, item, &field::index(path, i), errors);
+        }
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    let len = object.len() as i64;
+    if let Some(max) = int(schema, "maxProperties").filter(|max| len > *max) {
+        errors.push(FieldError::invalid(
+            path,
+            Value::Object(object.clone()),
+            format!("{path} in body should have at most {max} properties"),
+        ));
+    }
+    if let Some(min) = int(schema, "minProperties").filter(|min| len < *min) {
+        errors.push(FieldError::invalid(
+            path,
+            Value::Object(object.clone()),
+            format!("{path} in body should have at least {min} properties"),
+        ));
+    }
+    for required in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(required) {
+            errors.push(FieldError::required(field::child(path, required), ""));
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let additional = schema.get("additionalProperties").filter(|s| s.is_object());
+    for (key, value) in object {
+        let property_schema = properties.and_then(|p| p.get(key)).or(additional);
+        if let Some(property_schema) = property_schema {
+            validate(property_schema, value, &field::child(path, key), errors);
+        }
+    }
+}
+
+fn validate_combinators(
+    schema: &Map<String, Value>,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    let subschemas = |name| {
+        schema
+            .get(name)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    };
+    for subschema in subschemas("allOf") {
+        validate(subschema, value, path, errors);
+    }
+    let any_of = subschemas("anyOf");
+    if !any_of.is_empty()
+        && !any_of
+            .iter()
+            .any(|subschema| validate_value(subschema, value, path).is_empty())
+    {
+        errors.push(FieldError::invalid(
+            path,
+            value.clone(),
+            format!("{path} in body must validate at least one schema (anyOf)"),
+        ));
+    }
+    let one_of = subschemas("oneOf");
+    if !one_of.is_empty() {
+        let valid = one_of
+            .iter()
+            .filter(|subschema| validate_value(subschema, value, path).is_empty())
+            .count();
+        if valid != 1 {
+            errors.push(FieldError::invalid(
+                path,
+                value.clone(),
+                format!("{path} in body must validate one and only one schema (oneOf)"),
+            ));
+        }
+    }
+    if let Some(not) = schema.get("not") {
+        if validate_value(not, value, path).is_empty() {
+            errors.push(FieldError::invalid(
+                path,
+                value.clone(),
+                format!("{path} in body must not validate the schema (not)"),
+            ));
+        }
+    }
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("valid regex")
+}
+
+static TIME: LazyLock<Regex> = LazyLock::new(|| {
+    regex(r"^[Tt ]([01]\d|2[0-3]):[0-5]\d:([0-5]\d|60)(\.\d+)?([Zz]|[+-]([01]\d|2[0-3]):[0-5]\d)$")
+});
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| regex(r"^[^@\s]+@[^@\s]+\.[^@\s]+$"));
+static HOSTNAME: LazyLock<Regex> = LazyLock::new(|| {
+    regex(
+        r"^[a-zA-Z0-9]([-a-zA-Z0-9]{0,61}[a-zA-Z0-9])?(\.[a-zA-Z0-9]([-a-zA-Z0-9]{0,61}[a-zA-Z0-9])?)*$",
+    )
+});
+static CIDR_PREFIX: LazyLock<Regex> = LazyLock::new(|| regex(r"^(0|[1-9]\d{0,2})$"));
+static MAC: LazyLock<Regex> = LazyLock::new(|| regex(r"^([0-9A-Fa-f]{2}[:-]){5}[0-9A-Fa-f]{2}$"));
+static UUID: LazyLock<Regex> =
+    LazyLock::new(|| regex(r"^(?i)[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$"));
+static URI: LazyLock<Regex> = LazyLock::new(|| regex(r"^[a-zA-Z][a-zA-Z0-9+.-]*:\S*$"));
+static HEX_COLOR: LazyLock<Regex> = LazyLock::new(|| regex(r"^#([0-9a-fA-F]{3}|[0-9a-fA-F]{6})$"));
+static RGB_COLOR: LazyLock<Regex> =
+    LazyLock::new(|| regex(r"^rgb\(\s*(\d{1,3})\s*,\s*(\d{1,3})\s*,\s*(\d{1,3})\s*\)$"));
+static DATE: LazyLock<Regex> = LazyLock::new(|| regex(r"^\d{4}-\d{2}-\d{2}$"));
+
+/// Checks the string formats the apiserver knows; unknown formats are not validated.
+fn matches_format(format: &str, s: &str) -> bool {
+    match format {
+        "byte" => base64::engine::general_purpose::STANDARD.decode(s).is_ok(),
+        "date" => is_date(s),
+        "date-time" | "datetime" => {
+            s.get(..10).is_some_and(is_date) && s.get(10..).is_some_and(|time| TIME.is_match(time))
+        }
+        "duration" => s.parse::<GoDuration>().is_ok(),
+        "email" => EMAIL.is_match(s),
+        "hostname" => s.len() <= 253 && HOSTNAME.is_match(s),
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        "cidr" => s.split_once('/').is_some_and(|(ip, prefix)| {
+            let max = match ip.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => 32,
+                Ok(IpAddr::V6(_)) => 128,
+                Err(_) => return false,
+            };
+            CIDR_PREFIX.is_match(prefix) && prefix.parse::<u32>().is_ok_and(|p| p <= max)
+        }),
+        "mac" => MAC.is_match(s),
+        "uuid" => UUID.is_match(s),
+        "uuid3" | "uuid4" | "uuid5" => {
+            let version = &format[4..];
+            UUID.is_match(s)
+                && s[14..15] == *version
+                && (version == "3" || "89abAB".contains(&s[19..20]))
+        }
+        "uri" => s.starts_with('/') || URI.is_match(s),
+        "hexcolor" => HEX_COLOR.is_match(s),
+        "rgbcolor" => RGB_COLOR.is_match(s),
+        _ => true,
+    }
+}
+
+fn is_date(s: &str) -> bool {
+    if !DATE.is_match(s) {
+        return false;
+    }
+    let (year, month, day): (u32, u32, u32) = (
+        s[..4].parse().unwrap_or_default(),
+        s[5..7].parse().unwrap_or_default(),
+        s[8..].parse().unwrap_or_default(),
+    );
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn formats() {
+        for (format, valid, invalid) in [
+            ("byte", &["aGVsbG8=", ""][..], &["aGVsbG8", "!!"][..]),
+            (
+                "date",
+                &["2024-02-29", "2023-12-31"],
+                &["2023-02-29", "2024-13-01", "2024-1-01"],
+            ),
+            (
+                "date-time",
+                &[
+                    "2024-01-01T12:00:00Z",
+                    "2024-01-01 12:00:00.5+01:00",
+                    "2024-01-01t23:59:60z",
+                ],
+                &[
+                    "2024-01-01",
+                    "2024-01-01T24:00:00Z",
+                    "2024-01-01T12:00:00",
+                    "2024-02-30T12:00:00Z",
+                ],
+            ),
+            ("datetime", &["2024-01-01T12:00:00-05:30"], &["12:00:00Z"]),
+            ("duration", &["1h30m", "0", "1.5s"], &["1d", "h"]),
+            (
+                "email",
+                &["a.b@example.com"],
+                &["a@b", "a b@example.com", "@example.com"],
+            ),
+            (
+                "hostname",
+                &["example.com", "a", "a-b.c-d"],
+                &["-a.com", "a_b", "a..b", &format!("{}.com", "a".repeat(64))],
+            ),
+            ("ipv4", &["10.0.0.1"], &["10.0.0.256", "10.0.0", "::1"]),
+            ("ipv6", &["::1", "fe80::1"], &["10.0.0.1", ":::"]),
+            (
+                "cidr",
+                &["10.0.0.0/8", "0.0.0.0/0", "::/0", "fd00::/128"],
+                &["10.0.0.0", "10.0.0.0/33", "10.0.0.0/08", "::/129", "foo/8"],
+            ),
+            (
+                "mac",
+                &["00:1A:2b:3c:4d:5e", "00-1a-2b-3c-4d-5e"],
+                &["00:1a:2b:3c:4d", "00:1a:2b:3c:4d:5g"],
+            ),
+            (
+                "uuid",
+                &[
+                    "123e4567-e89b-12d3-a456-426614174000",
+                    "123E4567-E89B-12D3-A456-426614174000",
+                ],
+                &[
+                    "123e4567e89b12d3a456426614174000",
+                    "123e4567-e89b-12d3-a456-42661417400",
+                ],
+            ),
+            (
+                "uuid3",
+                &["a3bb189e-8bf9-3888-1912-ace4e6543002"],
+                &["a3bb189e-8bf9-4888-9912-ace4e6543002"],
+            ),
+            (
+                "uuid4",
+                &[
+                    "123e4567-e89b-42d3-a456-426614174000",
+                    "123e4567-e89b-42d3-B456-426614174000",
+                ],
+                &[
+                    "123e4567-e89b-42d3-c456-426614174000",
+                    "123e4567-e89b-12d3-a456-426614174000",
+                ],
+            ),
+            (
+                "uuid5",
+                &["123e4567-e89b-52d3-8456-426614174000"],
+                &["123e4567-e89b-52d3-7456-426614174000"],
+            ),
+            (
+                "uri",
+                &["https://example.com/a?b", "/path", "urn:x"],
+                &["example.com", "1http://a", ""],
+            ),
+            ("hexcolor", &["#fff", "#A0b1C2"], &["fff", "#ffff", "#ggg"]),
+            (
+                "rgbcolor",
+                &["rgb(255, 0, 0)", "rgb(1,2,3)"],
+                &["rgb(1,2)", "rgba(1,2,3,4)"],
+            ),
+            ("unknown", &["anything"], &[]),
+        ] {
+            for s in valid {
+                assert!(matches_format(format, s), "{format} {s:?}");
+            }
+            for s in invalid {
+                assert!(!matches_format(format, s), "{format} {s:?}");
+            }
+        }
+    }
+
+    fn errors(schema: serde_json::Value, value: serde_json::Value) -> Vec<String> {
+        validate_value(&schema, &value, "v")
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn validates_values() {
+        for (schema, value, expected) in [
+            (json!({"type": "string"}), json!("a"), vec![]),
+            (
+                json!({"type": "integer"}),
+                json!(1.5),
+                vec![r#"v: Invalid value: "number": v in body must be of type integer: "number""#],
+            ),
+            (json!({"type": "integer"}), json!(2.0), vec![]),
+            (json!({"type": "number"}), json!(2), vec![]),
+            (
+                json!({"x-kubernetes-int-or-string": true}),
+                json!("50%"),
+                vec![],
+            ),
+            (
+                json!({"x-kubernetes-int-or-string": true}),
+                json!(true),
+                vec![
+                    r#"v: Invalid value: "boolean": v in body must be of type integer,string: "boolean""#,
+                ],
+            ),
+            (
+                json!({"type": "string", "enum": ["a", "b"]}),
+                json!("c"),
+                vec![r#"v: Unsupported value: "c": supported values: "a", "b""#],
+            ),
+            (
+                json!({"type": "string", "minLength": 2, "pattern": "^a"}),
+                json!("b"),
+                vec![
+                    r#"v: Invalid value: "b": v in body should be at least 2 chars long"#,
+                    r#"v: Invalid value: "b": v in body should match '^a'"#,
+                ],
+            ),
+            (
+                json!({"type": "string", "pattern": "("}),
+                json!("b"),
+                vec![
+                    "v: Internal error: invalid pattern \"(\": regex parse error:\n    (\n    ^\nerror: unclosed group",
+                ],
+            ),
+            (
+                json!({"type": "integer", "minimum": 1, "exclusiveMinimum": true, "multipleOf": 2}),
+                json!(1),
+                vec![
+                    "v: Invalid value: 1: v in body should be greater than 1",
+                    "v: Invalid value: 1: v in body should be a multiple of 2",
+                ],
+            ),
+            (
+                json!({"type": "number", "maximum": 1.5, "exclusiveMaximum": true}),
+                json!(1.5),
+                vec!["v: Invalid value: 1.5: v in body should be less than 1.5"],
+            ),
+            (
+                json!({"type": "array", "maxItems": 2, "uniqueItems": true, "items": {"type": "integer"}}),
+                json!([1, "a", 1]),
+                vec![
+                    "v: Too many: 3: must have at most 2 items",
+                    "v[2]: Duplicate value: 1",
+                    r#"v[1]: Invalid value: "string": v[1] in body must be of type integer: "string""#,
+                ],
+            ),
+            (
+                json!({"type": "array", "minItems": 1}),
+                json!([]),
+                vec!["v: Invalid value: []: v in body should have at least 1 items"],
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "required": ["a", "b"],
+                    "maxProperties": 1,
+                    "properties": {"a": {"type": "string"}},
+                    "additionalProperties": {"type": "integer"},
+                }),
+                json!({"a": "x", "c": "y"}),
+                vec![
+                    r#"v: Invalid value: {"a":"x","c":"y"}: v in body should have at most 1 properties"#,
+                    "v.b: Required value",
+                    r#"v.c: Invalid value: "string": v.c in body must be of type integer: "string""#,
+                ],
+            ),
+            (
+                json!({"anyOf": [{"type": "string"}, {"type": "integer"}]}),
+                json!(true),
+                vec!["v: Invalid value: true: v in body must validate at least one schema (anyOf)"],
+            ),
+            (
+                json!({"oneOf": [{"type": "number"}, {"type": "integer"}]}),
+                json!(1),
+                vec![
+                    "v: Invalid value: 1: v in body must validate one and only one schema (oneOf)",
+                ],
+            ),
+            (
+                json!({"not": {"type": "integer"}}),
+                json!(1),
+                vec!["v: Invalid value: 1: v in body must not validate the schema (not)"],
+            ),
+        ] {
+            assert_eq!(errors(schema, value.clone()), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn finds_unknown_fields() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "network": {"type": "object", "properties": {"cidr": {"type": "string"}}},
+                "hosts": {"type": "array", "items": {"type": "object", "properties": {"name": {"type": "string"}}}},
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                "extra": {"type": "object", "x-kubernetes-preserve-unknown-fields": true},
+                "free": {"type": "object", "additionalProperties": true},
+            },
+        });
+        assert_eq!(
+            unknown_fields(
+                &schema,
+                &json!({
+                    "network": {"cidr": "10.0.0.0/8", "dns": "1.1.1.1"},
+                    "hosts": [{"name": "a"}, {"name": "b", "port": 22}],
+                    "labels": {"a": "b"},
+                    "extra": {"a": {"b": 1}},
+                    "free": {"a": 1},
+                    "foo": 1,
+                })
+            ),
+            ["foo", "hosts[1].port", "network.dns"]
+        );
+    }
+}
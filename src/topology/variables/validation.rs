@@ -0,0 +1,555 @@
+//! Validation of the variables of a Cluster against the definitions of its ClusterClass,
+//! following `ValidateClusterVariables` and `ValidateMachineVariables` of the upstream
+//! `internal/topology/variables` package.
+
+use std::collections::BTreeMap;
+
+use crate::api::capi_cluster::Cluster;
+use crate::api::capi_clusterclass::ClusterClass;
+use crate::topology::field::{self, FieldError};
+
+use super::definitions::{class_variables, ClassVariable, ClusterVariable, VariableDefinition};
+use super::rules::validate_rules;
+use super::schema::{unknown_fields, validate_value};
+
+/// Validates the topology variables of a Cluster and the control plane, MachineDeployment and
+/// MachinePool overrides against the variables of its ClusterClass. Variables marked required
+/// must be set in `spec.topology.variables`; overrides may leave them out. With the previous
+/// version of the Cluster, transition rules comparing against `oldSelf` are evaluated too.
+pub fn validate_topology_variables(
+    cluster: &Cluster,
+    old_cluster: Option<&Cluster>,
+    class: &ClusterClass,
+) -> Vec<FieldError> {
+    let Some(topology) = &cluster.spec.topology else {
+        return Vec::new();
+    };
+    let old_topology = old_cluster.and_then(|old| old.spec.topology.as_ref());
+    let definitions = class_variables(class);
+    let path = "spec.topology";
+    let mut errors = validate_cluster_variables(
+        topology.variables.as_deref().unwrap_or_default(),
+        old_topology
+            .and_then(|old| old.variables.as_deref())
+            .unwrap_or_default(),
+        &definitions,
+        true,
+        &field::child(path, "variables"),
+    );
+    if let Some(overrides) = topology
+        .control_plane
+        .as_ref()
+        .and_then(|control_plane| control_plane.variables.as_ref())
+        .and_then(|variables| variables.overrides.as_deref())
+    {
+        let old_overrides = old_topology
+            .and_then(|old| old.control_plane.as_ref())
+            .and_then(|control_plane| control_plane.variables.as_ref())
+            .and_then(|variables| variables.overrides.as_deref());
+        errors.extend(validate_cluster_variables(
+            overrides,
+            old_overrides.unwrap_or_default(),
+            &definitions,
+            false,
+            &field::child(path, "controlPlane.variables.overrides"),
+        ));
+    }
+    let workers = topology.workers.as_ref();
+    let old_workers = old_topology.and_then(|old| old.workers.as_ref());
+    for (i, machine_deployment) in workers
+        .and_then(|workers| workers.machine_deployments.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = machine_deployment
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let old_overrides = old_workers
+                .and_then(|old| old.machine_deployments.as_ref())
+                .and_then(|old| old.iter().find(|old| old.name == machine_deployment.name))
+                .and_then(|old| old.variables.as_ref())
+                .and_then(|variables| variables.overrides.as_deref());
+            let path = field::index(&field::child(path, "workers.machineDeployments"), i);
+            errors.extend(validate_cluster_variables(
+                overrides,
+                old_overrides.unwrap_or_default(),
+                &definitions,
+                false,
+                &field::child(&path, "variables.overrides"),
+            ));
+        }
+    }
+    for (i, machine_pool) in workers
+        .and_then(|workers| workers.machine_pools.as_ref())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(overrides) = machine_pool
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.overrides.as_deref())
+        {
+            let old_overrides = old_workers
+                .and_then(|old| old.machine_pools.as_ref())
+                .and_then(|old| old.iter().find(|old| old.name == machine_pool.name))
+                .and_then(|old| old.variables.as_ref())
+                .and_then(|variables| variables.overrides.as_deref());
+            let path = field::index(&field::child(path, "workers.machinePools"), i);
+            errors.extend(validate_cluster_variables(
+                overrides,
+                old_overrides.unwrap_or_default(),
+                &definitions,
+                false,
+                &field::child(&path, "variables.overrides"),
+            ));
+        }
+    }
+    errors
+}
+
+/// Validates variable values against the variable definitions of a ClusterClass, reporting
+/// errors below `path`, e.g. `spec.topology.variables`. Old values are matched by name and
+/// `definitionFrom` for transition rules.
+pub fn validate_cluster_variables<V: ClusterVariable>(
+    values: &[V],
+    old_values: &[V],
+    definitions: &[ClassVariable],
+    validate_required: bool,
+    path: &str,
+) -> Vec<FieldError> {
+    let values_index = match values_index(values) {
+        Ok(index) => index,
+        Err(message) => {
+            let values = values
+                .iter()
+                .map(|value| serde_json::json!({ "name": value.name() }))
+                .collect();
+            return vec![FieldError::invalid(
+                path,
+                serde_json::Value::Array(values),
+                format!("cluster variables not valid: {message}"),
+            )];
+        }
+    };
+    let mut errors = Vec::new();
+    if validate_required {
+        errors.extend(validate_required_variables(
+            &values_index,
+            definitions,
+            path,
+        ));
+    }
+    for (i, value) in values.iter().enumerate() {
+        let path = field::index(path, i);
+        match definition(definitions, value.name(), value.definition_from()) {
+            Ok(definition) => errors.extend(validate_cluster_variable(
+                value.name(),
+                value.value(),
+                old_values
+                    .iter()
+                    .find(|old| {
+                        old.name() == value.name()
+                            && old.definition_from().unwrap_or_default()
+                                == value.definition_from().unwrap_or_default()
+                    })
+                    .map(ClusterVariable::value),
+                definition,
+                &path,
+            )),
+            Err(message) => errors.push(FieldError::invalid(
+                field::child(&path, "name"),
+                value.name().into(),
+                message,
+            )),
+        }
+    }
+    errors
+}
+
+/// Validates a single variable value against its definition, reporting errors below `path`,
+/// e.g. `spec.topology.variables[0]`. The `x-kubernetes-validations` rules are evaluated once
+/// the value matches the schema.
+pub fn validate_cluster_variable(
+    name: &str,
+    value: &serde_json::Value,
+    old_value: Option<&serde_json::Value>,
+    definition: &VariableDefinition,
+    path: &str,
+) -> Vec<FieldError> {
+    let path = field::child(path, "value");
+    let unknown = unknown_fields(&definition.schema, value);
+    if !unknown.is_empty() {
+        return vec![FieldError::invalid(
+            path,
+            String::new().into(),
+            format!(
+                "failed validation: {:?} field(s) are not specified in the variable schema of variable {name:?}",
+                unknown.join(",")
+            ),
+        )];
+    }
+    let errors = validate_value(&definition.schema, value, &path);
+    if !errors.is_empty() {
+        return errors;
+    }
+    validate_rules(&definition.schema, value, old_value, &path)
+}
+
+/// The `definitionFrom` values set for each variable name.
+pub(super) type ValuesIndex<'a> = BTreeMap<&'a str, Vec<&'a str>>;
+
+pub(super) fn values_index<V: ClusterVariable>(values: &[V]) -> Result<ValuesIndex<'_>, String> {
+    let mut index = ValuesIndex::new();
+    for value in values {
+        let from = value.definition_from().unwrap_or_default();
+        let froms = index.entry(value.name()).or_default();
+        if froms.contains(&from) {
+            return Err(format!(
+                "variable name must be unique. Variable with name {:?} and definitionFrom {from:?} is defined more than once",
+                value.name()
+            ));
+        }
+        froms.push(from);
+        if froms.contains(&"") && froms.len() > 1 {
+            return Err(format!(
+                "variable {:?} has a mix of values with empty and non-empty definitionFrom",
+                value.name()
+            ));
+        }
+    }
+    Ok(index)
+}
+
+fn validate_required_variables(
+    values: &ValuesIndex<'_>,
+    definitions: &[ClassVariable],
+    path: &str,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for variable in definitions {
+        let froms = values.get(variable.name.as_str());
+        for definition in variable.definitions.iter().filter(|d| d.required) {
+            let set = froms.is_some_and(|froms| {
+                froms.contains(&definition.from.as_str())
+                    || (!variable.definitions_conflict && froms.contains(&""))
+            });
+            if set {
+                continue;
+            }
+            let detail = if variable.definitions_conflict {
+                format!(
+                    "required variable with name {:?} from {:?} must be defined",
+                    variable.name, definition.from
+                )
+            } else {
+                format!(
+                    "required variable with name {:?} must be defined",
+                    variable.name
+                )
+            };
+            errors.push(FieldError::required(path, detail));
+            if !variable.definitions_conflict {
+                break;
+            }
+        }
+    }
+    errors
+}
+
+/// Finds the definition a value refers to.
+pub(super) fn definition<'a>(
+    definitions: &'a [ClassVariable],
+    name: &str,
+    definition_from: Option<&str>,
+) -> Result<&'a VariableDefinition, String> {
+    let variable = definitions
+        .iter()
+        .find(|variable| variable.name == name)
+        .ok_or_else(|| format!("no definitions found for variable {name:?}"))?;
+    match definition_from.filter(|from| !from.is_empty()) {
+        None if variable.definitions_conflict => Err(format!(
+            "variable {name:?} has conflicting definitions. It requires a non-empty `definitionFrom`"
+        )),
+        None => variable
+            .definitions
+            .first()
+            .ok_or_else(|| format!("no definitions found for variable {name:?}")),
+        Some(from) => variable
+            .definitions
+            .iter()
+            .find(|definition| definition.from == from)
+            .ok_or_else(|| format!("no definitions found for variable {name:?} from {from:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A ClusterClass with the required variable `replicas` and the optional `network`.
+    fn class() -> ClusterClass {
+        serde_json::from_value(json!({
+            "metadata": {"name": "class"},
+            "spec": {
+                "variables": [
+                    {
+                        "name": "replicas",
+                        "required": true,
+                        "schema": {"openAPIV3Schema": {"type": "integer", "minimum": 1, "maximum": 5}},
+                    },
+                    {
+                        "name": "network",
+                        "required": false,
+                        "schema": {"openAPIV3Schema": {
+                            "type": "object",
+                            "properties": {
+                                "dns": {"type": "string", "maxLength": 10},
+                                "subnets": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "required": ["cidr"],
+                                        "properties": {"cidr": {"type": "string", "format": "cidr"}},
+                                    },
+                                },
+                            },
+                        }},
+                    },
+                ],
+            },
+        }))
+        .unwrap()
+    }
+
+    /// A ClusterClass whose `size` variable is defined inline and by the patch `patch-a`, with
+    /// different schemas.
+    fn conflicting_class() -> ClusterClass {
+        let definition = |from: &str, ty: &str| json!({"from": from, "required": true, "schema": {"openAPIV3Schema": {"type": ty}}});
+        serde_json::from_value(json!({
+            "metadata": {"name": "class"},
+            "spec": {},
+            "status": {"variables": [{
+                "name": "size",
+                "definitionsConflict": true,
+                "definitions": [definition("inline", "string"), definition("patch-a", "integer")],
+            }]},
+        }))
+        .unwrap()
+    }
+
+    fn cluster(topology: serde_json::Value) -> Cluster {
+        let mut spec = json!({"class": "class", "version": "v1.30.0"});
+        for (key, value) in topology.as_object().into_iter().flatten() {
+            spec[key] = value.clone();
+        }
+        serde_json::from_value(json!({
+            "metadata": {"name": "c1", "namespace": "ns"},
+            "spec": {"topology": spec},
+        }))
+        .unwrap()
+    }
+
+    fn errors(class: &ClusterClass, topology: serde_json::Value) -> Vec<String> {
+        validate_topology_variables(&cluster(topology), None, class)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn validates_topology_variables() {
+        for (variables, expected) in [
+            (json!([{"name": "replicas", "value": 3}]), vec![]),
+            (
+                json!([{"name": "replicas", "value": 3}, {"name": "network", "value": {"subnets": [{"cidr": "10.0.0.0/24"}]}}]),
+                vec![],
+            ),
+            (
+                json!([]),
+                vec![r#"spec.topology.variables: Required value: required variable with name "replicas" must be defined"#],
+            ),
+            (
+                json!([{"name": "replicas", "value": 3}, {"name": "foo", "value": 1}]),
+                vec![r#"spec.topology.variables[1].name: Invalid value: "foo": no definitions found for variable "foo""#],
+            ),
+            (
+                json!([{"name": "replicas", "value": 7}]),
+                vec!["spec.topology.variables[0].value: Invalid value: 7: spec.topology.variables[0].value in body should be less than or equal to 5"],
+            ),
+            (
+                json!([{"name": "replicas", "value": "3"}]),
+                vec![r#"spec.topology.variables[0].value: Invalid value: "string": spec.topology.variables[0].value in body must be of type integer: "string""#],
+            ),
+            (
+                json!([{"name": "replicas", "value": 1}, {"name": "replicas", "value": 2}]),
+                vec![r#"spec.topology.variables: Invalid value: [{"name":"replicas"},{"name":"replicas"}]: cluster variables not valid: variable name must be unique. Variable with name "replicas" and definitionFrom "" is defined more than once"#],
+            ),
+        ] {
+            assert_eq!(
+                errors(&class(), json!({"variables": variables})),
+                expected,
+                "{variables}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_nested_field_paths() {
+        for (network, expected) in [
+            (
+                json!({"dns": "a-very-long-name", "subnets": [{"cidr": "10.0.0.0/24"}, {"cidr": "10.0.0.0"}, {}]}),
+                vec![
+                    "spec.topology.variables[1].value.dns: Too long: may not be longer than 10",
+                    r#"spec.topology.variables[1].value.subnets[1].cidr: Invalid value: "10.0.0.0": spec.topology.variables[1].value.subnets[1].cidr in body must be of type cidr: "10.0.0.0""#,
+                    "spec.topology.variables[1].value.subnets[2].cidr: Required value",
+                ],
+            ),
+            (
+                json!({"subnets": "10.0.0.0/24"}),
+                vec![
+                    r#"spec.topology.variables[1].value.subnets: Invalid value: "string": spec.topology.variables[1].value.subnets in body must be of type array: "string""#,
+                ],
+            ),
+            // fields unknown to the schema are reported before any other error
+            (
+                json!({"dns": 1, "foo": true, "subnets": [{"cidr": "10.0.0.0/24", "gateway": "10.0.0.1"}]}),
+                vec![
+                    r#"spec.topology.variables[1].value: Invalid value: "": failed validation: "foo,subnets[0].gateway" field(s) are not specified in the variable schema of variable "network""#,
+                ],
+            ),
+        ] {
+            assert_eq!(
+                errors(
+                    &class(),
+                    json!({"variables": [{"name": "replicas", "value": 1}, {"name": "network", "value": network}]})
+                ),
+                expected,
+                "{network}"
+            );
+        }
+    }
+
+    #[test]
+    fn requires_definition_from_for_conflicting_definitions() {
+        for (variables, expected) in [
+            (
+                json!([
+                    {"name": "size", "definitionFrom": "inline", "value": "small"},
+                    {"name": "size", "definitionFrom": "patch-a", "value": 1},
+                ]),
+                vec![],
+            ),
+            (
+                json!([{"name": "size", "definitionFrom": "inline", "value": "small"}]),
+                vec![
+                    r#"spec.topology.variables: Required value: required variable with name "size" from "patch-a" must be defined"#,
+                ],
+            ),
+            (
+                json!([]),
+                vec![
+                    r#"spec.topology.variables: Required value: required variable with name "size" from "inline" must be defined"#,
+                    r#"spec.topology.variables: Required value: required variable with name "size" from "patch-a" must be defined"#,
+                ],
+            ),
+            (
+                json!([
+                    {"name": "size", "definitionFrom": "inline", "value": "small"},
+                    {"name": "size", "definitionFrom": "patch-a", "value": "large"},
+                ]),
+                vec![
+                    r#"spec.topology.variables[1].value: Invalid value: "string": spec.topology.variables[1].value in body must be of type integer: "string""#,
+                ],
+            ),
+            (
+                json!([
+                    {"name": "size", "definitionFrom": "inline", "value": "small"},
+                    {"name": "size", "definitionFrom": "patch-a", "value": 1},
+                    {"name": "size", "definitionFrom": "patch-b", "value": 1},
+                ]),
+                vec![
+                    r#"spec.topology.variables[2].name: Invalid value: "size": no definitions found for variable "size" from "patch-b""#,
+                ],
+            ),
+            (
+                json!([{"name": "size", "value": "small"}]),
+                vec![
+                    r#"spec.topology.variables: Required value: required variable with name "size" from "inline" must be defined"#,
+                    r#"spec.topology.variables: Required value: required variable with name "size" from "patch-a" must be defined"#,
+                    r#"spec.topology.variables[0].name: Invalid value: "size": variable "size" has conflicting definitions. It requires a non-empty `definitionFrom`"#,
+                ],
+            ),
+            (
+                json!([
+                    {"name": "size", "value": "small"},
+                    {"name": "size", "definitionFrom": "patch-a", "value": 1},
+                ]),
+                vec![
+                    r#"spec.topology.variables: Invalid value: [{"name":"size"},{"name":"size"}]: cluster variables not valid: variable "size" has a mix of values with empty and non-empty definitionFrom"#,
+                ],
+            ),
+        ] {
+            assert_eq!(
+                errors(&conflicting_class(), json!({"variables": variables})),
+                expected,
+                "{variables}"
+            );
+        }
+    }
+
+    #[test]
+    fn validates_overrides() {
+        let topology = json!({
+            "variables": [{"name": "replicas", "value": 3}],
+            "controlPlane": {"variables": {"overrides": [{"name": "replicas", "value": 0}]}},
+            "workers": {
+                "machineDeployments": [
+                    {"class": "md", "name": "md-0", "variables": {"overrides": [{"name": "network", "value": {}}]}},
+                    {"class": "md", "name": "md-1", "variables": {"overrides": [
+                        {"name": "network", "value": {"dns": "a-very-long-name"}},
+                        {"name": "bar", "value": 1},
+                    ]}},
+                ],
+                "machinePools": [
+                    {"class": "mp", "name": "mp-0", "variables": {"overrides": [{"name": "replicas", "value": 6}]}},
+                ],
+            },
+        });
+        assert_eq!(
+            errors(&class(), topology),
+            [
+                "spec.topology.controlPlane.variables.overrides[0].value: Invalid value: 0: spec.topology.controlPlane.variables.overrides[0].value in body should be greater than or equal to 1",
+                "spec.topology.workers.machineDeployments[1].variables.overrides[0].value.dns: Too long: may not be longer than 10",
+                r#"spec.topology.workers.machineDeployments[1].variables.overrides[1].name: Invalid value: "bar": no definitions found for variable "bar""#,
+                "spec.topology.workers.machinePools[0].variables.overrides[0].value: Invalid value: 6: spec.topology.workers.machinePools[0].variables.overrides[0].value in body should be less than or equal to 5",
+            ]
+        );
+        // overrides do not have to set required variables
+        assert_eq!(
+            errors(
+                &class(),
+                json!({
+                    "variables": [{"name": "replicas", "value": 3}],
+                    "controlPlane": {"variables": {"overrides": [{"name": "network", "value": {}}]}},
+                })
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn without_topology() {
+        let cluster: Cluster = serde_json::from_value(json!({
+            "metadata": {"name": "c1"},
+            "spec": {},
+        }))
+        .unwrap();
+        assert_eq!(validate_topology_variables(&cluster, None, &class()), []);
+    }
+}
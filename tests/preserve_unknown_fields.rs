@@ -0,0 +1,135 @@
+#![cfg(feature = "preserve-unknown-fields")]
+
+use cluster_api_rs::api::capi_cluster::Cluster;
+use cluster_api_rs::api::capi_machinedeployment::MachineDeployment;
+use cluster_api_rs::AnyCapiObject;
+use serde_json::{json, Value};
+
+fn machine_deployment() -> Value {
+    json!({
+        "apiVersion": "cluster.x-k8s.io/v1beta1",
+        "kind": "MachineDeployment",
+        "metadata": {"name": "md-0", "namespace": "default"},
+        "spec": {
+            "clusterName": "cluster",
+            "replicas": 3,
+            "futureSpecField": {"nested": [1, 2, 3]},
+            "selector": {
+                "matchLabels": {"cluster.x-k8s.io/cluster-name": "cluster"},
+                "futureSelectorField": true
+            },
+            "template": {
+                "metadata": {"labels": {"cluster.x-k8s.io/cluster-name": "cluster"}},
+                "spec": {
+                    "clusterName": "cluster",
+                    "bootstrap": {
+                        "configRef": {
+                            "apiVersion": "bootstrap.cluster.x-k8s.io/v1beta1",
+                            "kind": "KubeadmConfigTemplate",
+                            "name": "md-0"
+                        }
+                    },
+                    "infrastructureRef": {
+                        "apiVersion": "infrastructure.cluster.x-k8s.io/v1beta1",
+                        "kind": "DockerMachineTemplate",
+                        "name": "md-0"
+                    },
+                    "nodeDrainTimeout": "5m0s",
+                    "futureTemplateField": "value"
+                }
+            }
+        },
+        "status": {
+            "phase": "Running",
+            "replicas": 3,
+            "futureStatusField": {"a": "b"},
+            "conditions": [{
+                "type": "Ready",
+                "status": "True",
+                "lastTransitionTime": "2024-01-01T00:00:00Z",
+                "futureConditionField": 1
+            }],
+            "v1beta2": {
+                "availableReplicas": 3,
+                "futureV1beta2Field": null
+            }
+        }
+    })
+}
+
+#[test]
+fn round_trips_unknown_fields() {
+    let value = machine_deployment();
+    let md: MachineDeployment = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&md).unwrap(), value);
+}
+
+#[test]
+fn unknown_fields_are_captured_per_struct() {
+    let md: MachineDeployment = serde_json::from_value(machine_deployment()).unwrap();
+    assert_eq!(
+        md.spec.extra.get("futureSpecField"),
+        Some(&json!({"nested": [1, 2, 3]}))
+    );
+    assert_eq!(
+        md.spec.selector.extra.get("futureSelectorField"),
+        Some(&json!(true))
+    );
+    assert_eq!(
+        md.spec
+            .template
+            .spec
+            .as_ref()
+            .unwrap()
+            .extra
+            .get("futureTemplateField"),
+        Some(&json!("value"))
+    );
+    assert_eq!(
+        md.status.as_ref().unwrap().conditions.as_ref().unwrap()[0]
+            .extra
+            .get("futureConditionField"),
+        Some(&json!(1))
+    );
+    assert!(!md.spec.extra.contains_key("replicas"));
+}
+
+#[test]
+fn modify_and_replace_keeps_unknown_fields() {
+    let mut md: MachineDeployment = serde_json::from_value(machine_deployment()).unwrap();
+    md.spec.replicas = Some(5);
+
+    let mut expected = machine_deployment();
+    expected["spec"]["replicas"] = json!(5);
+    assert_eq!(serde_json::to_value(&md).unwrap(), expected);
+}
+
+#[test]
+fn round_trips_unknown_fields_through_yaml() {
+    let yaml = r#"apiVersion: cluster.x-k8s.io/v1beta1
+kind: Cluster
+metadata:
+  name: cluster
+spec:
+  paused: false
+  futureField:
+    enabled: true
+  topology:
+    class: quick-start
+    version: v1.31.0
+    futureTopologyField: 42
+"#;
+    let cluster: Cluster = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(
+        serde_yaml::to_value(&cluster).unwrap(),
+        serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap()
+    );
+}
+
+#[test]
+fn any_capi_object_round_trips_unknown_fields() {
+    let value = machine_deployment();
+    let object: AnyCapiObject = serde_json::from_value(value.clone()).unwrap();
+    assert!(matches!(object, AnyCapiObject::MachineDeployment(_)));
+    assert_eq!(serde_json::to_value(&object).unwrap(), value);
+}